@@ -0,0 +1,58 @@
+//! Criterion benchmarks of `QuantumCircuit.execute` on random, QFT and GHZ
+//! circuits from 10 to 26 qubits.
+//!
+//!     cargo bench --no-default-features --bench circuits
+//!     cargo bench --no-default-features --bench circuits -- qft/18
+//!
+//! The extension-module feature has to be off so the binary links libpython.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use pyo3::prelude::*;
+use quantum_engine::QuantumCircuit;
+
+const QUBITS: [usize; 5] = [10, 14, 18, 22, 26];
+
+/// Circuit family name and builder
+type Family = (&'static str, fn(usize) -> QuantumCircuit);
+
+fn random(qubits: usize) -> QuantumCircuit {
+    QuantumCircuit::random(qubits, 10, Some(7), None).unwrap()
+}
+
+fn qft(qubits: usize) -> QuantumCircuit {
+    let mut circuit = QuantumCircuit::new(qubits, 0);
+    for q in 0..qubits {
+        circuit.h(q).unwrap();
+    }
+    circuit.qft((0..qubits).collect(), true).unwrap();
+    circuit
+}
+
+fn ghz(qubits: usize) -> QuantumCircuit {
+    QuantumCircuit::ghz(qubits).unwrap()
+}
+
+fn execute(c: &mut Criterion) {
+    pyo3::prepare_freethreaded_python();
+    let families: [Family; 3] = [("random", random), ("qft", qft), ("ghz", ghz)];
+    Python::with_gil(|py| {
+        for (name, build) in families {
+            let mut group = c.benchmark_group(name);
+            for qubits in QUBITS {
+                // Past 20 qubits one run takes long enough that criterion's
+                // default 100 samples would dominate the suite
+                if qubits > 20 {
+                    group.sample_size(10);
+                }
+                let circuit = build(qubits);
+                group.bench_with_input(BenchmarkId::from_parameter(qubits), &circuit, |b, circuit| {
+                    b.iter(|| circuit.execute(py, "f32", None, "cpu", 1, "statevector", 0, 1e-5).unwrap())
+                });
+            }
+            group.finish();
+        }
+    });
+}
+
+criterion_group!(benches, execute);
+criterion_main!(benches);
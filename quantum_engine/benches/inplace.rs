@@ -0,0 +1,80 @@
+//! Criterion benchmarks of single-qubit `apply_gate` on 22 qubits, one
+//! thread: the in-place butterfly against the allocating one it replaced,
+//! which wrote every pair into a freshly zeroed state vector and swapped it in.
+//! The SIMD kernels are forced off so both sides run the same scalar loop and
+//! only the allocation differs.
+//!
+//!     cargo bench --no-default-features --bench inplace
+//!     cargo bench --no-default-features --bench inplace -- "H/21"
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_complex::Complex32;
+use quantum_engine::{set_simd, QuantumState};
+
+const QUBITS: usize = 22;
+
+/// (gate, target, parameter)
+const CASES: [(&str, usize, Option<f32>); 4] = [
+    ("H", 0, None),
+    ("H", 10, None),
+    ("H", 21, None),
+    ("RX", 10, Some(0.3)),
+];
+
+fn matrix(gate: &str, param: Option<f32>) -> [[Complex32; 2]; 2] {
+    match gate {
+        "H" => {
+            let h = Complex32::new(std::f32::consts::FRAC_1_SQRT_2, 0.0);
+            [[h, h], [h, -h]]
+        }
+        "RX" => {
+            let half = param.unwrap_or(0.0) / 2.0;
+            let (c, s) = (Complex32::new(half.cos(), 0.0), Complex32::new(0.0, -half.sin()));
+            [[c, s], [s, c]]
+        }
+        _ => unreachable!("no matrix for {}", gate),
+    }
+}
+
+/// The butterfly as it was before gates ran in place: read the old vector,
+/// write a new one
+fn apply_allocating(state: &mut Vec<Complex32>, target: usize, gate: [[Complex32; 2]; 2]) {
+    let dim = state.len();
+    let mut new_state = vec![Complex32::new(0.0, 0.0); dim];
+    let step = 1 << target;
+    for i in (0..dim).step_by(step * 2) {
+        for j in 0..step {
+            let idx0 = i + j;
+            let idx1 = idx0 + step;
+            let a = state[idx0];
+            let b = state[idx1];
+            new_state[idx0] = gate[0][0] * a + gate[0][1] * b;
+            new_state[idx1] = gate[1][0] * a + gate[1][1] * b;
+        }
+    }
+    *state = new_state;
+}
+
+fn butterflies(c: &mut Criterion) {
+    rayon::ThreadPoolBuilder::new().num_threads(1).build_global().unwrap();
+    set_simd(false);
+    let mut state = QuantumState::new(QUBITS).unwrap();
+    let mut amplitudes = vec![Complex32::new(0.0, 0.0); 1 << QUBITS];
+    amplitudes[0] = Complex32::new(1.0, 0.0);
+    for (gate, target, param) in CASES {
+        let mut group = c.benchmark_group(format!("{}/{}", gate, target));
+        group.sample_size(20);
+        let m = matrix(gate, param);
+        group.bench_function(BenchmarkId::new("f32", "allocating"), |b| {
+            b.iter(|| apply_allocating(black_box(&mut amplitudes), target, m))
+        });
+        group.bench_function(BenchmarkId::new("f32", "in_place"), |b| {
+            b.iter(|| state.apply_gate(gate, target, param).unwrap())
+        });
+        group.finish();
+    }
+    set_simd(true);
+}
+
+criterion_group!(benches, butterflies);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+//! Criterion benchmarks behind the README's single-qubit kernel table:
+//! `apply_gate` on 22 qubits, one thread, with the explicit SIMD kernels
+//! forced off (`scalar`) and on (`simd`), in both precisions.
+//!
+//!     cargo bench --no-default-features --bench simd
+//!     cargo bench --no-default-features --bench simd -- "H/3"
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use quantum_engine::{set_simd, QuantumState, QuantumStateF64};
+
+const QUBITS: usize = 22;
+
+/// (gate, target, parameter) rows of the README table
+const CASES: [(&str, usize, Option<f32>); 6] = [
+    ("H", 0, None),
+    ("H", 3, None),
+    ("H", 21, None),
+    ("RX", 10, Some(0.3)),
+    ("T", 10, None),
+    ("T", 21, None),
+];
+
+fn kernels(c: &mut Criterion) {
+    rayon::ThreadPoolBuilder::new().num_threads(1).build_global().unwrap();
+    let mut f32_state = QuantumState::new(QUBITS).unwrap();
+    let mut f64_state = QuantumStateF64::new(QUBITS).unwrap();
+    for (gate, target, param) in CASES {
+        let mut group = c.benchmark_group(format!("{}/{}", gate, target));
+        group.sample_size(20);
+        for (label, simd) in [("scalar", false), ("simd", true)] {
+            set_simd(simd);
+            group.bench_function(BenchmarkId::new("f32", label), |b| {
+                b.iter(|| f32_state.apply_gate(gate, target, param).unwrap())
+            });
+            group.bench_function(BenchmarkId::new("f64", label), |b| {
+                b.iter(|| f64_state.apply_gate(gate, target, param).unwrap())
+            });
+        }
+        group.finish();
+    }
+    set_simd(true);
+}
+
+criterion_group!(benches, kernels);
+criterion_main!(benches);
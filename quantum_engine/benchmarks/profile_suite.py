@@ -0,0 +1,70 @@
+"""
+Benchmark suite for the Rust quantum engine.
+
+Runs a few circuit families from 10 to 26 qubits through
+QuantumCircuit.execute_profiled and prints the total time, the time per
+gate and the slowest gate types. Pass --json to keep the full reports.
+
+    python benchmarks/profile_suite.py --min-qubits 10 --max-qubits 26 --json results.json
+"""
+
+import argparse
+import json
+import sys
+
+import quantum_engine
+
+
+def random_layers(qubits, depth=10, seed=7):
+    """Layers of random rotations on every qubit followed by a CNOT brickwork"""
+    return quantum_engine.QuantumCircuit.random(qubits, depth, seed=seed)
+
+
+def qft(qubits):
+    circuit = quantum_engine.QuantumCircuit(qubits)
+    for q in range(qubits):
+        circuit.h(q)
+    circuit.qft(list(range(qubits)))
+    return circuit
+
+
+def ghz(qubits):
+    return quantum_engine.QuantumCircuit.ghz(qubits)
+
+
+FAMILIES = {"random": random_layers, "qft": qft, "ghz": ghz}
+
+
+def run(min_qubits, max_qubits, step, families, transpile_level):
+    results = []
+    print(f"{'circuit':8s} {'qubits':>6s} {'gates':>6s} {'total':>10s} {'per gate':>10s} {'util':>5s}  slowest gate types")
+    for name in families:
+        for qubits in range(min_qubits, max_qubits + 1, step):
+            report = FAMILIES[name](qubits).execute_profiled(transpile_level=transpile_level)
+            report["circuit"] = name
+            results.append(report)
+
+            slowest = sorted(report["gate_types"].items(), key=lambda kv: -kv[1]["total_s"])[:3]
+            slowest = ", ".join(f"{gate} {stats['total_s'] * 1e3:.1f} ms" for gate, stats in slowest)
+            util = report["thread_utilization"]
+            util = f"{util:5.2f}" if util is not None else "    -"
+            print(f"{name:8s} {qubits:6d} {report['gates']:6d} {report['total_time_s'] * 1e3:8.1f}ms "
+                  f"{report['total_time_s'] / max(report['gates'], 1) * 1e6:8.1f}us {util}  {slowest}")
+    return results
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[1])
+    parser.add_argument("--min-qubits", type=int, default=10)
+    parser.add_argument("--max-qubits", type=int, default=26)
+    parser.add_argument("--step", type=int, default=2)
+    parser.add_argument("--families", nargs="+", choices=sorted(FAMILIES), default=list(FAMILIES))
+    parser.add_argument("--transpile-level", type=int, default=1)
+    parser.add_argument("--json", help="write the full reports to this file")
+    args = parser.parse_args()
+
+    results = run(args.min_qubits, args.max_qubits, args.step, args.families, args.transpile_level)
+    if args.json:
+        with open(args.json, "w") as f:
+            json.dump(results, f, indent=2)
+        print(f"\nWrote {len(results)} reports to {args.json}", file=sys.stderr)
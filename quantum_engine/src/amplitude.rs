@@ -0,0 +1,326 @@
+//! Amplitude amplification and estimation.
+//!
+//! `state_prep` is a unitary A with A|0...0> = sqrt(1 - a)|bad> + sqrt(a)|good>
+//! and `oracle` flips the phase of the good states, given as a phase-oracle
+//! circuit or marked bitstrings (as for GroverOperator). The Grover iterate
+//! Q = A S_0 A^dagger S_good, with S_0 = I - 2|0><0|, rotates by 2 theta in the
+//! good/bad plane, where a = sin^2(theta), so measuring Q^k A|0> finds a good
+//! state with probability sin^2((2k + 1) theta).
+//!
+//! Estimation simulates that measurement: the good-state probability is read
+//! off the state vector as (1 - <psi|S_good|psi>) / 2 and shot counts are drawn
+//! from it. "iterative" is the IQAE of Grinko et al. (2021) with
+//! Chernoff-Hoeffding intervals, picking the largest k that keeps (4k + 2)
+//! theta inside one half-plane; "mle" is the maximum-likelihood estimation of
+//! Suzuki et al. (2020) over the powers k = 0, 1, 2, 4, ..., doubling until the
+//! Fisher-information interval is narrow enough.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::grover::GroverOperator;
+use crate::{dynamic, rng, transpile, Gate, QuantumCircuit, QuantumState};
+
+/// Largest Grover power either estimator will try
+const MAX_POWER: usize = 1 << 20;
+
+/// Outcome of `amplitude_estimation`
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct AmplitudeEstimationResult {
+    /// Estimated probability a of measuring a good state after state_prep
+    #[pyo3(get)]
+    estimation: f64,
+    /// (low, high) interval holding a with probability at least 1 - alpha
+    #[pyo3(get)]
+    confidence_interval: (f64, f64),
+    /// Grover powers k that were measured, in order
+    #[pyo3(get)]
+    powers: Vec<usize>,
+    #[pyo3(get)]
+    shots: usize,
+    /// Applications of Q summed over every shot
+    #[pyo3(get)]
+    oracle_queries: usize,
+}
+
+#[pymethods]
+impl AmplitudeEstimationResult {
+    fn __repr__(&self) -> String {
+        format!("AmplitudeEstimationResult(estimation={}, confidence_interval=({}, {}), oracle_queries={})",
+            self.estimation, self.confidence_interval.0, self.confidence_interval.1, self.oracle_queries)
+    }
+}
+
+/// Phase-oracle circuit of anything the GroverOperator constructor accepts
+fn oracle_circuit(oracle: &PyAny, qubits: usize) -> PyResult<QuantumCircuit> {
+    let operator = match oracle.extract::<GroverOperator>() {
+        Ok(operator) => operator,
+        Err(_) => GroverOperator::new(oracle)?,
+    };
+    let circuit = operator.oracle();
+    if circuit.qubits != qubits {
+        return Err(crate::errors::DimensionMismatchError::new_err(format!(
+            "Oracle acts on {} qubits but state_prep on {}", circuit.qubits, qubits)));
+    }
+    Ok(circuit)
+}
+
+fn check_state_prep(state_prep: &QuantumCircuit) -> PyResult<()> {
+    if dynamic::is_dynamic(&state_prep.gates) || state_prep.initial.is_some() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "state_prep must be unitary (no measurement, reset, c_if or initialized state)"));
+    }
+    Ok(())
+}
+
+/// One Grover iterate Q = A S_0 A^dagger S_good as a circuit
+fn grover_iterate(state_prep: &QuantumCircuit, oracle: &QuantumCircuit) -> PyResult<QuantumCircuit> {
+    let n = state_prep.qubits;
+    let mut q = oracle.clone();
+    q.append(&state_prep.inverse()?, |q| q, |b| b);
+    for t in 0..n {
+        q.x(t)?;
+    }
+    q.mcz((0..n - 1).collect(), n - 1)?;
+    for t in 0..n {
+        q.x(t)?;
+    }
+    q.append(state_prep, |q| q, |b| b);
+    Ok(q)
+}
+
+/// |0...0> followed by state_prep and `iterations` Grover iterates, which
+/// raises the good-state probability to sin^2((2 iterations + 1) theta)
+#[pyfunction]
+pub fn amplitude_amplification(state_prep: &QuantumCircuit, oracle: &PyAny, iterations: usize) -> PyResult<QuantumCircuit> {
+    check_state_prep(state_prep)?;
+    let oracle = oracle_circuit(oracle, state_prep.qubits)?;
+    let step = grover_iterate(state_prep, &oracle)?;
+    let mut circuit = state_prep.clone();
+    for _ in 0..iterations {
+        circuit.append(&step, |q| q, |b| b);
+    }
+    Ok(circuit)
+}
+
+/// Q^k A|0> for increasing k, with the good-state probability of each
+struct Amplifier {
+    state: QuantumState,
+    power: usize,
+    iterate: Vec<Gate>,
+    oracle: Vec<Gate>,
+    rng: StdRng,
+    shots: usize,
+    queries: usize,
+}
+
+impl Amplifier {
+    /// Probability of a good state in Q^k A|0>; k must not decrease between calls
+    fn probability(&mut self, k: usize) -> PyResult<f64> {
+        for _ in self.power..k {
+            for gate in &self.iterate {
+                self.state.apply(gate)?;
+            }
+        }
+        self.power = k;
+        let mut flipped = self.state.clone();
+        for gate in &self.oracle {
+            flipped.apply(gate)?;
+        }
+        let overlap: Complex32 = self.state.overlap(&flipped)?;
+        Ok(((1.0 - overlap.re as f64) / 2.0).clamp(0.0, 1.0))
+    }
+
+    /// Good outcomes among `shots` measurements of Q^k A|0>
+    fn measure(&mut self, k: usize, shots: usize) -> PyResult<usize> {
+        let p = self.probability(k)?;
+        self.shots += shots;
+        self.queries += shots * k;
+        Ok((0..shots).filter(|_| self.rng.gen::<f64>() < p).count())
+    }
+}
+
+/// Inverse standard normal CDF (Acklam's rational approximation, relative
+/// error below 1.2e-9)
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+        1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+        6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+        -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Next power for IQAE: the largest k above `ratio` times the current K = 4k + 2
+/// whose scaled interval K [theta_low, theta_high] stays in one half-plane
+fn next_power(k: usize, low: f64, high: f64, up: bool) -> (usize, bool) {
+    let current = 4 * k + 2;
+    let mut scale = ((PI / (high - low)).floor() as usize).min(4 * MAX_POWER + 2);
+    // Scales of the form 4k + 2
+    scale -= (scale + 2) % 4;
+    while scale >= 2 * current {
+        let (a, b) = ((scale as f64 * low) % TAU, (scale as f64 * high) % TAU);
+        if a <= b && b <= PI {
+            return ((scale - 2) / 4, true);
+        }
+        if a >= PI && a <= b {
+            return ((scale - 2) / 4, false);
+        }
+        scale -= 4;
+    }
+    (k, up)
+}
+
+fn iterative(amplifier: &mut Amplifier, epsilon: f64, alpha: f64, shots: usize) -> PyResult<((f64, f64), Vec<usize>)> {
+    let rounds = (PI / (8.0 * epsilon)).log2().ceil().max(1.0);
+    let (mut low, mut high) = (0.0f64, FRAC_PI_2);
+    let (mut k, mut up) = (0usize, true);
+    let (mut hits, mut total) = (0usize, 0usize);
+    let mut powers = Vec::new();
+    while (high.sin().powi(2) - low.sin().powi(2)) / 2.0 > epsilon && powers.len() < 10_000 {
+        let (next, next_up) = next_power(k, low, high, up);
+        if next != k {
+            hits = 0;
+            total = 0;
+        }
+        (k, up) = (next, next_up);
+        hits += amplifier.measure(k, shots)?;
+        total += shots;
+        powers.push(k);
+        // Chernoff-Hoeffding interval on sin^2((2k + 1) theta), then back to theta
+        let radius = ((2.0 * rounds / alpha).ln() / (2.0 * total as f64)).sqrt();
+        let p = hits as f64 / total as f64;
+        let (p_min, p_max) = ((p - radius).max(0.0), (p + radius).min(1.0));
+        let scale = (4 * k + 2) as f64;
+        let (phi_min, phi_max) = if up {
+            ((1.0 - 2.0 * p_min).acos(), (1.0 - 2.0 * p_max).acos())
+        } else {
+            (TAU - (1.0 - 2.0 * p_max).acos(), TAU - (1.0 - 2.0 * p_min).acos())
+        };
+        let turns = (scale * low / TAU).floor() * TAU;
+        let (new_low, new_high) = ((turns + phi_min) / scale, (turns + phi_max) / scale);
+        (low, high) = if new_low.max(low) <= new_high.min(high) {
+            (new_low.max(low), new_high.min(high))
+        } else {
+            (new_low, new_high)
+        };
+        if k >= MAX_POWER {
+            break;
+        }
+    }
+    Ok(((low.sin().powi(2), high.sin().powi(2)), powers))
+}
+
+/// Log-likelihood of theta given (k, hits, shots) observations
+fn log_likelihood(theta: f64, data: &[(usize, usize, usize)]) -> f64 {
+    data.iter()
+        .map(|&(k, hits, shots)| {
+            let angle = (2 * k + 1) as f64 * theta;
+            let (p, q) = (angle.sin().powi(2).max(1e-300), angle.cos().powi(2).max(1e-300));
+            hits as f64 * p.ln() + (shots - hits) as f64 * q.ln()
+        })
+        .sum()
+}
+
+/// Maximum-likelihood theta: a grid fine enough to resolve the fastest
+/// oscillation, refined by golden-section search around the best point
+fn maximize(data: &[(usize, usize, usize)]) -> f64 {
+    let fastest = data.iter().map(|d| 2 * d.0 + 1).max().unwrap_or(1);
+    let points = (50 * fastest).max(1000);
+    let step = FRAC_PI_2 / points as f64;
+    let best = (0..=points)
+        .map(|i| i as f64 * step)
+        .max_by(|a, b| log_likelihood(*a, data).total_cmp(&log_likelihood(*b, data)))
+        .unwrap_or(0.0);
+    let (mut lo, mut hi) = ((best - step).max(0.0), (best + step).min(FRAC_PI_2));
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    for _ in 0..60 {
+        let (x1, x2) = (hi - ratio * (hi - lo), lo + ratio * (hi - lo));
+        if log_likelihood(x1, data) < log_likelihood(x2, data) {
+            lo = x1;
+        } else {
+            hi = x2;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+fn maximum_likelihood(amplifier: &mut Amplifier, epsilon: f64, alpha: f64, shots: usize)
+    -> PyResult<(f64, (f64, f64), Vec<usize>)> {
+    let z = normal_quantile(1.0 - alpha / 2.0);
+    let mut data: Vec<(usize, usize, usize)> = Vec::new();
+    let mut k = 0;
+    loop {
+        data.push((k, amplifier.measure(k, shots)?, shots));
+        let theta = maximize(&data);
+        // Fisher information of theta: 4 sum_k N_k (2k + 1)^2
+        let information: f64 = data.iter().map(|&(k, _, n)| 4.0 * n as f64 * ((2 * k + 1) as f64).powi(2)).sum();
+        let radius = z / information.sqrt();
+        let (low, high) = ((theta - radius).max(0.0), (theta + radius).min(FRAC_PI_2));
+        let interval = (low.sin().powi(2), high.sin().powi(2));
+        if (interval.1 - interval.0) / 2.0 <= epsilon || k >= MAX_POWER {
+            return Ok((theta.sin().powi(2), interval, data.iter().map(|d| d.0).collect()));
+        }
+        k = if k == 0 { 1 } else { 2 * k };
+    }
+}
+
+/// Estimate the good-state probability a of state_prep: "iterative" (IQAE)
+/// or "mle" (maximum likelihood over powers 0, 1, 2, 4, ...), stopping once the
+/// (1 - alpha) confidence interval has half-width at most `epsilon`; each
+/// Grover power is measured `shots` times
+#[pyfunction]
+#[pyo3(signature = (state_prep, oracle, method="iterative", epsilon=0.01, alpha=0.05, shots=100, seed=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn amplitude_estimation(py: Python, state_prep: &QuantumCircuit, oracle: &PyAny, method: &str, epsilon: f64, alpha: f64,
+                            shots: usize, seed: Option<u64>) -> PyResult<AmplitudeEstimationResult> {
+    check_state_prep(state_prep)?;
+    if epsilon <= 0.0 || epsilon >= 0.5 || alpha <= 0.0 || alpha >= 1.0 || shots == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "epsilon must lie in (0, 0.5), alpha in (0, 1) and shots must be positive"));
+    }
+    let oracle = oracle_circuit(oracle, state_prep.qubits)?;
+    let iterate = transpile::fuse(grover_iterate(state_prep, &oracle)?.bound_gates()?, 1);
+    let oracle = oracle.bound_gates()?.to_vec();
+    let method = method.to_ascii_lowercase();
+    if method != "iterative" && method != "mle" {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown method '{}' (expected 'iterative' or 'mle')", method)));
+    }
+    py.allow_threads(|| {
+        let state = state_prep.simulate(&mut dynamic::ClassicalRegister::new(0, None), 1)?;
+        let mut amplifier = Amplifier { state, power: 0, iterate, oracle, rng: rng::seeded(seed), shots: 0, queries: 0 };
+        let (estimation, confidence_interval, powers) = if method == "mle" {
+            maximum_likelihood(&mut amplifier, epsilon, alpha, shots)?
+        } else {
+            let (interval, powers) = iterative(&mut amplifier, epsilon, alpha, shots)?;
+            ((interval.0 + interval.1) / 2.0, interval, powers)
+        };
+        Ok(AmplitudeEstimationResult {
+            estimation,
+            confidence_interval,
+            powers,
+            shots: amplifier.shots,
+            oracle_queries: amplifier.queries,
+        })
+    })
+}
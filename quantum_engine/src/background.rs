@@ -0,0 +1,117 @@
+//! Background execution for `QuantumCircuit.execute_async`.
+//!
+//! The circuit runs gate by gate on its own OS thread, outside the GIL. Between
+//! gates the worker checks a cancellation flag and, every `callback_every`
+//! gates, briefly takes the GIL to report progress to an optional Python
+//! callback as (gates_done, total, current_entropy).
+
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::{dynamic, Gate, QuantumState};
+
+/// Handle to a circuit executing on a background thread
+#[pyclass]
+pub struct ExecutionHandle {
+    cancelled: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+    worker: Mutex<Option<JoinHandle<PyResult<QuantumState>>>>,
+    outcome: Mutex<Option<PyResult<QuantumState>>>,
+}
+
+#[pymethods]
+impl ExecutionHandle {
+    /// True once the simulation has finished, failed or been cancelled
+    pub fn is_done(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Block until the simulation finishes and return the final state; raises
+    /// the simulation's error, or RuntimeError if it was cancelled
+    pub fn result(&self, py: Python) -> PyResult<QuantumState> {
+        // Wait without the GIL so the worker can still reach the progress callback
+        py.allow_threads(|| {
+            let mut outcome = self.outcome.lock().unwrap();
+            if let Some(worker) = self.worker.lock().unwrap().take() {
+                *outcome = Some(worker.join().unwrap_or_else(|_| {
+                    Err(pyo3::exceptions::PyRuntimeError::new_err("Background execution panicked"))
+                }));
+            }
+        });
+        match self.outcome.lock().unwrap().as_ref() {
+            Some(Ok(state)) => Ok(state.clone()),
+            Some(Err(err)) => Err(err.clone_ref(py)),
+            None => unreachable!(),
+        }
+    }
+
+    /// Ask the simulation to stop before its next gate
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[getter]
+    pub fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for ExecutionHandle {
+    /// Nothing can collect the result once the handle is gone, so stop the worker
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Start running `gates` from `initial` on a new thread
+pub fn spawn(
+    initial: QuantumState,
+    clbits: usize,
+    gates: Vec<Gate>,
+    seed: Option<u64>,
+    callback: Option<PyObject>,
+    callback_every: usize,
+) -> PyResult<ExecutionHandle> {
+    if callback_every == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("callback_every must be positive"));
+    }
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let finished = Arc::new(AtomicBool::new(false));
+    let (cancel_flag, finished_flag) = (Arc::clone(&cancelled), Arc::clone(&finished));
+    let worker = std::thread::spawn(move || {
+        let outcome = run(initial, clbits, &gates, seed, callback, callback_every, &cancel_flag);
+        finished_flag.store(true, Ordering::Release);
+        outcome
+    });
+    Ok(ExecutionHandle { cancelled, finished, worker: Mutex::new(Some(worker)), outcome: Mutex::new(None) })
+}
+
+fn run(
+    initial: QuantumState,
+    clbits: usize,
+    gates: &[Gate],
+    seed: Option<u64>,
+    callback: Option<PyObject>,
+    callback_every: usize,
+    cancelled: &AtomicBool,
+) -> PyResult<QuantumState> {
+    let mut state = initial;
+    let mut creg = dynamic::ClassicalRegister::new(clbits, seed);
+    let total = gates.len();
+    for (i, gate) in gates.iter().enumerate() {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(pyo3::exceptions::PyRuntimeError::new_err("Execution was cancelled"));
+        }
+        dynamic::run(&mut state.state, state.qubits, std::slice::from_ref(gate), &mut creg)?;
+        let done = i + 1;
+        if let Some(callback) = &callback {
+            if done % callback_every == 0 || done == total {
+                let entropy = state.calculate_entropy();
+                Python::with_gil(|py| callback.call1(py, (done, total, entropy)))?;
+            }
+        }
+    }
+    Ok(state)
+}
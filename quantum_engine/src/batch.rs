@@ -0,0 +1,45 @@
+//! Batched execution of independent circuits.
+//!
+//! `execute_many` takes a list of circuits and runs them on the rayon pool with
+//! the GIL released, so hundreds of small circuits (tomography settings, kernel
+//! feature maps) cost one Python call instead of one each. Every circuit gets
+//! its own seed, drawn in order from one generator seeded with `seed`, so the
+//! results do not depend on the thread count. `rng::derive` is not used for
+//! this: the per-shot streams derive from the circuit seed the same way, and
+//! circuit i + 1 would replay circuit i's shots shifted by one.
+
+use pyo3::prelude::*;
+use rand::Rng;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::bitorder::BitOrder;
+use crate::noise::NoiseModel;
+use crate::{dynamic, rng, QuantumCircuit, QuantumState};
+
+/// Run every circuit in parallel and return the results in input order: the
+/// final QuantumStates, or with `shots` a counts dictionary per circuit (as
+/// `QuantumCircuit.run` gives, `noise_model` included)
+#[pyfunction]
+#[pyo3(signature = (circuits, shots=None, seed=None, noise_model=None, bit_order="little"))]
+pub fn execute_many(py: Python, circuits: Vec<QuantumCircuit>, shots: Option<usize>, seed: Option<u64>,
+    noise_model: Option<PyRef<NoiseModel>>, bit_order: &str) -> PyResult<PyObject> {
+    let bit_order = BitOrder::from_name(bit_order)?;
+    let mut stream = rng::seeded(seed);
+    let seeds: Vec<u64> = circuits.iter().map(|_| stream.gen()).collect();
+    let model: Option<&NoiseModel> = noise_model.as_deref();
+    let Some(shots) = shots else {
+        if model.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "noise_model needs shots; use execute_noisy for density matrices"));
+        }
+        let states = py.allow_threads(|| circuits.par_iter().zip(&seeds)
+            .map(|(c, &seed)| c.simulate(&mut dynamic::ClassicalRegister::new(c.clbits, Some(seed)), 1))
+            .collect::<PyResult<Vec<QuantumState>>>())?;
+        return Ok(states.into_py(py));
+    };
+    let counts = py.allow_threads(|| circuits.par_iter().zip(&seeds)
+        .map(|(c, &seed)| c.sample_counts(shots, Some(seed), model))
+        .collect::<PyResult<Vec<HashMap<String, usize>>>>())?;
+    Ok(counts.into_iter().map(|c| bit_order.counts(c)).collect::<Vec<_>>().into_py(py))
+}
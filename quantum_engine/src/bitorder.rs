@@ -0,0 +1,81 @@
+//! Bit-ordering conventions for bitstrings and basis-state indices.
+//!
+//! The engine is little-endian throughout: qubit q is bit q of a basis-state
+//! index and character q of a bitstring, so qubit 0 is written first. The
+//! `bit_order="big"` option on sampling and probability methods reverses the
+//! qubit order instead: qubit 0 becomes the last character (as in Qiskit's
+//! counts) and the most significant bit of an index. Big-endian output of a
+//! state equals little-endian output of its `reverse_bits()`.
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// Qubit order selected by the `bit_order` argument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    Little,
+    Big,
+}
+
+impl BitOrder {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "little" => Ok(BitOrder::Little),
+            "big" => Ok(BitOrder::Big),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(
+                format!("Unknown bit order '{}' (expected 'little' or 'big')", name))),
+        }
+    }
+
+    /// Translate a basis-state index between the engine's order and this one
+    /// (the map is its own inverse)
+    pub fn index(self, idx: usize, n_qubits: usize) -> usize {
+        match self {
+            BitOrder::Little => idx,
+            BitOrder::Big => reverse(idx, n_qubits),
+        }
+    }
+
+    /// Values indexed by basis state, re-indexed into this order
+    pub fn permute<T: Copy>(self, values: Vec<T>, n_qubits: usize) -> Vec<T> {
+        match self {
+            BitOrder::Little => values,
+            BitOrder::Big => (0..values.len()).map(|i| values[reverse(i, n_qubits)]).collect(),
+        }
+    }
+
+    /// A bitstring written in engine order, rewritten in this order
+    pub fn bitstring(self, bits: String) -> String {
+        match self {
+            BitOrder::Little => bits,
+            BitOrder::Big => bits.chars().rev().collect(),
+        }
+    }
+
+    /// Counts keyed by engine-order bitstrings, re-keyed in this order
+    pub fn counts(self, counts: HashMap<String, usize>) -> HashMap<String, usize> {
+        match self {
+            BitOrder::Little => counts,
+            BitOrder::Big => counts.into_iter().map(|(k, v)| (self.bitstring(k), v)).collect(),
+        }
+    }
+}
+
+/// `idx` with its lowest `n_qubits` bits in reverse order
+pub fn reverse(idx: usize, n_qubits: usize) -> usize {
+    if n_qubits == 0 {
+        return 0;
+    }
+    idx.reverse_bits() >> (usize::BITS as usize - n_qubits)
+}
+
+/// Values indexed by basis state, re-indexed so that qubit q becomes qubit
+/// destination[q]; `destination` must be a permutation of 0..n
+pub fn permute_qubits<T: Copy + Default>(values: &[T], destination: &[usize]) -> Vec<T> {
+    let mut out = vec![T::default(); values.len()];
+    for (idx, &v) in values.iter().enumerate() {
+        let moved = destination.iter().enumerate().fold(0, |acc, (q, &d)| acc | (((idx >> q) & 1) << d));
+        out[moved] = v;
+    }
+    out
+}
@@ -0,0 +1,163 @@
+//! Least-recently-used cache of expectation values for `execute_batch` and `VQE`.
+//!
+//! Entries are keyed by (circuit, parameter vector, observable): the circuit by
+//! a hash of its serialized form (gates, symbols and initial state), the
+//! observable by a hash of its Pauli terms, and the parameters by their exact
+//! bit patterns, so only a revisit of the very same point hits. Optimizers
+//! that re-evaluate points (line searches, SPSA resampling, the convergence
+//! check after a step) then pay for each distinct point once. Gradients are
+//! not cached.
+
+use pyo3::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use crate::observables::PauliTerm;
+use crate::{serialize, QuantumCircuit};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    circuit: u64,
+    observable: u64,
+    parameters: Vec<u32>,
+}
+
+/// What a cache stores about one (circuit, observable) pair; combine with a
+/// parameter vector to get a key
+#[derive(Clone, Copy, Debug)]
+pub struct Scope {
+    circuit: u64,
+    observable: u64,
+}
+
+impl Scope {
+    pub fn new(circuit: &QuantumCircuit, terms: &[PauliTerm]) -> PyResult<Scope> {
+        let mut hasher = DefaultHasher::new();
+        serialize::to_bytes(circuit)?.hash(&mut hasher);
+        let circuit = hasher.finish();
+        let mut hasher = DefaultHasher::new();
+        for term in terms {
+            (term.coeff.to_bits(), term.masks.x, term.masks.z).hash(&mut hasher);
+        }
+        Ok(Scope { circuit, observable: hasher.finish() })
+    }
+
+    pub fn key(&self, parameters: &[f32]) -> Key {
+        Key { circuit: self.circuit, observable: self.observable, parameters: parameters.iter().map(|p| p.to_bits()).collect() }
+    }
+}
+
+struct Lru {
+    /// Value and last-use tick of each entry
+    entries: HashMap<Key, (f32, u64)>,
+    /// Entries by last-use tick, oldest first
+    order: BTreeMap<u64, Key>,
+    tick: u64,
+    hits: usize,
+    misses: usize,
+}
+
+/// Bounded store of expectation values, shared by any number of
+/// `execute_batch` calls and `VQE` drivers; the oldest-used entry is evicted
+/// once `capacity` are held
+#[pyclass(module = "quantum_engine")]
+pub struct ExpectationCache {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+impl ExpectationCache {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Lru> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Cached value for `key`, counting a hit or a miss
+    pub fn get(&self, key: &Key) -> Option<f32> {
+        let mut lru = self.lock();
+        lru.tick += 1;
+        let tick = lru.tick;
+        let Some((value, last)) = lru.entries.get_mut(key).map(|(v, t)| (*v, std::mem::replace(t, tick))) else {
+            lru.misses += 1;
+            return None;
+        };
+        lru.order.remove(&last);
+        lru.order.insert(tick, key.clone());
+        lru.hits += 1;
+        Some(value)
+    }
+
+    pub fn insert(&self, key: Key, value: f32) {
+        let mut lru = self.lock();
+        lru.tick += 1;
+        let tick = lru.tick;
+        if let Some((_, last)) = lru.entries.insert(key.clone(), (value, tick)) {
+            lru.order.remove(&last);
+        }
+        lru.order.insert(tick, key);
+        while lru.entries.len() > self.capacity {
+            let Some((_, oldest)) = lru.order.pop_first() else { break };
+            lru.entries.remove(&oldest);
+        }
+    }
+}
+
+#[pymethods]
+impl ExpectationCache {
+    #[new]
+    #[pyo3(signature = (capacity=1024))]
+    pub fn new(capacity: usize) -> PyResult<Self> {
+        if capacity == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("capacity must be positive"));
+        }
+        let lru = Lru { entries: HashMap::new(), order: BTreeMap::new(), tick: 0, hits: 0, misses: 0 };
+        Ok(ExpectationCache { capacity, inner: Mutex::new(lru) })
+    }
+
+    #[getter]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Lookups answered from the cache
+    #[getter]
+    pub fn hits(&self) -> usize {
+        self.lock().hits
+    }
+
+    /// Lookups that had to simulate
+    #[getter]
+    pub fn misses(&self) -> usize {
+        self.lock().misses
+    }
+
+    /// hits / (hits + misses), 0 before any lookup
+    #[getter]
+    pub fn hit_rate(&self) -> f64 {
+        let lru = self.lock();
+        match lru.hits + lru.misses {
+            0 => 0.0,
+            total => lru.hits as f64 / total as f64,
+        }
+    }
+
+    /// Drop every entry and zero the statistics
+    pub fn clear(&self) {
+        let mut lru = self.lock();
+        lru.entries.clear();
+        lru.order.clear();
+        lru.hits = 0;
+        lru.misses = 0;
+    }
+
+    fn __len__(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let lru = self.lock();
+        format!("ExpectationCache(size={}, capacity={}, hits={}, misses={})",
+            lru.entries.len(), self.capacity, lru.hits, lru.misses)
+    }
+}
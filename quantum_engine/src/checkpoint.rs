@@ -0,0 +1,203 @@
+//! Versioned on-disk checkpoints of state vectors and density matrices.
+//!
+//! A checkpoint is a 24-byte header followed by the raw amplitude buffer:
+//!
+//! | bytes | field                                                 |
+//! |-------|-------------------------------------------------------|
+//! | 0-3   | magic `QECK`                                          |
+//! | 4-5   | format version (u16)                                  |
+//! | 6     | kind: 0 = state vector, 1 = density matrix            |
+//! | 7     | precision: bytes per real component (4 or 8)          |
+//! | 8     | endianness of every later field: 0 = little, 1 = big  |
+//! | 9-11  | reserved, zero                                        |
+//! | 12-15 | qubit count (u32)                                     |
+//! | 16-23 | amplitude count (u64)                                 |
+//! | 24-   | amplitudes as (re, im) pairs, row-major for matrices  |
+//!
+//! Files are written little-endian to a temporary sibling and renamed into
+//! place, so a crash mid-save never leaves a truncated checkpoint at `path`.
+//! Either precision loads into either class; f32 data widens exactly.
+
+use num_complex::{Complex32, Complex64};
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+
+const MAGIC: &[u8; 4] = b"QECK";
+const VERSION: u16 = 1;
+pub const HEADER_LEN: usize = 24;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    StateVector,
+    DensityMatrix,
+}
+
+impl Kind {
+    fn code(self) -> u8 {
+        match self {
+            Kind::StateVector => 0,
+            Kind::DensityMatrix => 1,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::StateVector => "a state vector",
+            Kind::DensityMatrix => "a density matrix",
+        }
+    }
+
+    /// Amplitudes held by an n-qubit object of this kind
+    fn amplitudes(self, qubits: usize) -> Option<usize> {
+        let dim = 1usize.checked_shl(qubits as u32)?;
+        match self {
+            Kind::StateVector => Some(dim),
+            Kind::DensityMatrix => dim.checked_mul(dim),
+        }
+    }
+}
+
+/// Borrowed amplitude buffer in either precision
+pub enum Buffer<'a> {
+    F32(&'a [Complex32]),
+    F64(&'a [Complex64]),
+}
+
+pub fn io_error(path: &str, e: std::io::Error) -> PyErr {
+    pyo3::exceptions::PyIOError::new_err(format!("{}: {}", path, e))
+}
+
+fn invalid(path: &str, message: &str) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("{}: {}", path, message))
+}
+
+/// Little-endian header for `len` amplitudes of `precision` bytes per component
+pub fn header(kind: Kind, qubits: usize, precision: u8, len: u64) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.extend_from_slice(&[kind.code(), precision, 0, 0, 0, 0]);
+    header.extend_from_slice(&(qubits as u32).to_le_bytes());
+    header.extend_from_slice(&len.to_le_bytes());
+    header
+}
+
+pub fn save(path: &str, kind: Kind, qubits: usize, buffer: Buffer) -> PyResult<()> {
+    let (precision, len) = match buffer {
+        Buffer::F32(b) => (4u8, b.len()),
+        Buffer::F64(b) => (8u8, b.len()),
+    };
+    let header = header(kind, qubits, precision, len as u64);
+
+    let tmp = format!("{}.tmp", path);
+    let write = || -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(&tmp)?);
+        out.write_all(&header)?;
+        match buffer {
+            Buffer::F32(b) => for c in b {
+                out.write_all(&c.re.to_le_bytes())?;
+                out.write_all(&c.im.to_le_bytes())?;
+            },
+            Buffer::F64(b) => for c in b {
+                out.write_all(&c.re.to_le_bytes())?;
+                out.write_all(&c.im.to_le_bytes())?;
+            },
+        }
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        std::fs::rename(&tmp, path)
+    };
+    write().map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        io_error(path, e)
+    })
+}
+
+/// Amplitude type a checkpoint can be loaded into
+pub trait Amplitude: Sized {
+    fn from_parts(re: f64, im: f64) -> Self;
+}
+
+impl Amplitude for Complex32 {
+    fn from_parts(re: f64, im: f64) -> Self {
+        Complex32::new(re as f32, im as f32)
+    }
+}
+
+impl Amplitude for Complex64 {
+    fn from_parts(re: f64, im: f64) -> Self {
+        Complex64::new(re, im)
+    }
+}
+
+/// Read a checkpoint of the given kind, returning its qubit count and
+/// amplitudes. The size is checked against the memory guard before anything
+/// is allocated, and amplitudes are decoded straight from the file into the
+/// returned buffer.
+pub fn load<T: Amplitude>(path: &str, kind: Kind) -> PyResult<(usize, Vec<T>)> {
+    let file = File::open(path).map_err(|e| io_error(path, e))?;
+    let file_len = file.metadata().map_err(|e| io_error(path, e))?.len();
+    let mut input = BufReader::new(file);
+    let mut header = [0u8; HEADER_LEN];
+    input.read_exact(&mut header).map_err(|_| invalid(path, "not a quantum_engine checkpoint"))?;
+    if &header[..4] != MAGIC {
+        return Err(invalid(path, "not a quantum_engine checkpoint"));
+    }
+    let big_endian = match header[8] {
+        0 => false,
+        1 => true,
+        _ => return Err(invalid(path, "unknown endianness marker")),
+    };
+    let u16_at = |b: [u8; 2]| if big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) };
+    let u32_at = |b: [u8; 4]| if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) };
+    let u64_at = |b: [u8; 8]| if big_endian { u64::from_be_bytes(b) } else { u64::from_le_bytes(b) };
+
+    let version = u16_at(header[4..6].try_into().unwrap());
+    if version != VERSION {
+        return Err(invalid(path, &format!("unsupported checkpoint version {} (this build reads version {})", version, VERSION)));
+    }
+    let found = match header[6] {
+        0 => Kind::StateVector,
+        1 => Kind::DensityMatrix,
+        _ => return Err(invalid(path, "unknown checkpoint kind")),
+    };
+    if found != kind {
+        return Err(invalid(path, &format!("checkpoint holds {}, not {}", found.name(), kind.name())));
+    }
+    let precision = header[7];
+    if precision != 4 && precision != 8 {
+        return Err(invalid(path, "unknown precision"));
+    }
+    let qubits = u32_at(header[12..16].try_into().unwrap()) as usize;
+    let len = u64_at(header[16..24].try_into().unwrap());
+    if kind.amplitudes(qubits).map(|l| l as u64) != Some(len) {
+        return Err(invalid(path, &format!("{} amplitudes do not match {} qubits", len, qubits)));
+    }
+
+    let width = 2 * precision as usize;
+    if Some(file_len) != len.checked_mul(width as u64).and_then(|b| b.checked_add(HEADER_LEN as u64)) {
+        return Err(invalid(path, "checkpoint is truncated"));
+    }
+    match kind {
+        Kind::StateVector => crate::resources::check_state::<T>(qubits)?,
+        Kind::DensityMatrix => crate::resources::check_density::<T>(qubits)?,
+    }
+
+    let component = |b: &[u8]| -> f64 {
+        match (precision, big_endian) {
+            (4, false) => f32::from_le_bytes(b.try_into().unwrap()) as f64,
+            (4, true) => f32::from_be_bytes(b.try_into().unwrap()) as f64,
+            (_, false) => f64::from_le_bytes(b.try_into().unwrap()),
+            (_, true) => f64::from_be_bytes(b.try_into().unwrap()),
+        }
+    };
+    let mut amplitudes = Vec::with_capacity(len as usize);
+    let mut pair = [0u8; 16];
+    let pair = &mut pair[..width];
+    for _ in 0..len {
+        input.read_exact(pair).map_err(|e| io_error(path, e))?;
+        let (re, im) = pair.split_at(precision as usize);
+        amplitudes.push(T::from_parts(component(re), component(im)));
+    }
+    Ok((qubits, amplitudes))
+}
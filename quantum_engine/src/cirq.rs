@@ -0,0 +1,123 @@
+//! Cirq JSON export, in the format read by `cirq.read_json`.
+//!
+//! Gates go on LineQubits and are grouped into Moments by circuit layer
+//! (`introspect::levels`). Each gate maps onto its Cirq equivalent: the Pauli
+//! and Clifford gates as EigenGates with the matching exponent, rotations as
+//! Rx/Ry/Rz, RXX/RYY/RZZ as XX/YY/ZZPowGate with global shift -1/2, and
+//! controlled gates as ControlledGate. U and dense unitaries become
+//! MatrixGates, whose first qubit is the most significant bit of the matrix
+//! index. Measurements use the key "c<bit>"; snapshots are dropped and c_if
+//! blocks are rejected.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use std::f64::consts::PI;
+
+use crate::{introspect, unitary, Gate, GateType};
+
+fn pow_gate(cirq_type: &str, exponent: f64, global_shift: f64) -> Value {
+    json!({"cirq_type": cirq_type, "exponent": exponent, "global_shift": global_shift})
+}
+
+fn line_qubit(q: usize) -> Value {
+    json!({"cirq_type": "LineQubit", "x": q})
+}
+
+fn complex(c: Complex32) -> Value {
+    json!({"cirq_type": "complex", "real": c.re as f64, "imag": c.im as f64})
+}
+
+fn controlled(sub_gate: Value, controls: usize) -> Value {
+    json!({
+        "cirq_type": "ControlledGate",
+        "sub_gate": sub_gate,
+        "num_controls": controls,
+        "control_values": vec![vec![1]; controls],
+        "control_qid_shape": vec![2; controls],
+    })
+}
+
+/// MatrixGate of `gate` and the qubits it is applied to; bit i of the matrix
+/// index is gate.qubits()[i], so they are listed most significant first
+fn matrix_gate(gate: &Gate) -> PyResult<(Value, Vec<usize>)> {
+    let qubits = gate.qubits();
+    let k = qubits.len();
+    let dim = 1 << k;
+    let matrix = unitary::gate_matrix(gate)?;
+    let rows: Vec<Value> = matrix.chunks(dim).map(|row| Value::Array(row.iter().map(|&c| complex(c)).collect())).collect();
+    let gate = json!({"cirq_type": "MatrixGate", "matrix": rows, "qid_shape": vec![2; k]});
+    Ok((gate, qubits.into_iter().rev().collect()))
+}
+
+/// Cirq form of a single-qubit gate
+fn single(gate_type: &GateType, target: usize) -> PyResult<Value> {
+    Ok(match *gate_type {
+        GateType::H => pow_gate("HPowGate", 1.0, 0.0),
+        GateType::X => pow_gate("XPowGate", 1.0, 0.0),
+        GateType::Y => pow_gate("YPowGate", 1.0, 0.0),
+        GateType::Z => pow_gate("ZPowGate", 1.0, 0.0),
+        GateType::S => pow_gate("ZPowGate", 0.5, 0.0),
+        GateType::T => pow_gate("ZPowGate", 0.25, 0.0),
+        GateType::SDG => pow_gate("ZPowGate", -0.5, 0.0),
+        GateType::TDG => pow_gate("ZPowGate", -0.25, 0.0),
+        GateType::RX(theta) => json!({"cirq_type": "Rx", "rads": theta as f64}),
+        GateType::RY(theta) => json!({"cirq_type": "Ry", "rads": theta as f64}),
+        GateType::RZ(phi) => json!({"cirq_type": "Rz", "rads": phi as f64}),
+        GateType::P(lambda) => pow_gate("ZPowGate", lambda as f64 / PI, 0.0),
+        ref other => matrix_gate(&Gate { gate_type: other.clone(), target, control: None })?.0,
+    })
+}
+
+/// (gate, qubits) of one GateOperation
+fn operation(gate: &Gate) -> PyResult<Option<(Value, Vec<usize>)>> {
+    let t = gate.target;
+    let qubits = gate.qubits();
+    Ok(Some(match &gate.gate_type {
+        GateType::CNOT => (pow_gate("CXPowGate", 1.0, 0.0), qubits),
+        GateType::CPHASE(phi) => (pow_gate("CZPowGate", *phi as f64 / PI, 0.0), qubits),
+        GateType::CRX(theta) => (controlled(single(&GateType::RX(*theta), t)?, 1), qubits),
+        GateType::CRY(theta) => (controlled(single(&GateType::RY(*theta), t)?, 1), qubits),
+        GateType::CRZ(phi) => (controlled(single(&GateType::RZ(*phi), t)?, 1), qubits),
+        GateType::CH => (controlled(single(&GateType::H, t)?, 1), qubits),
+        GateType::MCX(controls) => (controlled(single(&GateType::X, t)?, controls.len()), qubits),
+        GateType::CONTROLLED(controls, inner) => (controlled(single(inner, t)?, controls.len()), qubits),
+        GateType::SWAP(_) => (pow_gate("SwapPowGate", 1.0, 0.0), qubits),
+        GateType::ISWAP(_) => (pow_gate("ISwapPowGate", 1.0, 0.0), qubits),
+        // exp(-i theta/2 P x P) = e^(-i pi t/2) (P x P)^t with t = theta / pi
+        GateType::RXX(_, theta) => (pow_gate("XXPowGate", *theta as f64 / PI, -0.5), qubits),
+        GateType::RYY(_, theta) => (pow_gate("YYPowGate", *theta as f64 / PI, -0.5), qubits),
+        GateType::RZZ(_, theta) => (pow_gate("ZZPowGate", *theta as f64 / PI, -0.5), qubits),
+        GateType::UNITARY(..) => matrix_gate(gate)?,
+        GateType::MEASURE(bit) => (
+            json!({"cirq_type": "MeasurementGate", "num_qubits": 1, "key": format!("c{}", bit), "invert_mask": [], "qid_shape": [2]}),
+            qubits,
+        ),
+        GateType::RESET => (json!({"cirq_type": "ResetChannel", "dimension": 2}), qubits),
+        GateType::IF(..) => {
+            return Err(pyo3::exceptions::PyValueError::new_err("c_if blocks have no Cirq JSON equivalent in this exporter"));
+        }
+        GateType::SNAPSHOT(_) => return Ok(None),
+        single_qubit => (single(single_qubit, t)?, qubits),
+    }))
+}
+
+/// Serialize a gate list as a cirq.Circuit JSON document
+pub fn to_json(qubits: usize, clbits: usize, gates: &[Gate]) -> PyResult<String> {
+    let levels = introspect::levels(gates, qubits, clbits);
+    let mut moments: Vec<Vec<Value>> = vec![Vec::new(); levels.iter().copied().max().unwrap_or(0)];
+    for (gate, &level) in gates.iter().zip(&levels) {
+        if let Some((cirq_gate, on)) = operation(gate)? {
+            moments[level - 1].push(json!({
+                "cirq_type": "GateOperation",
+                "gate": cirq_gate,
+                "qubits": on.into_iter().map(line_qubit).collect::<Vec<_>>(),
+            }));
+        }
+    }
+    let moments: Vec<Value> = moments.into_iter()
+        .map(|operations| json!({"cirq_type": "Moment", "operations": operations}))
+        .collect();
+    serde_json::to_string_pretty(&json!({"cirq_type": "Circuit", "moments": moments}))
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
@@ -0,0 +1,395 @@
+//! Rewriting circuits into a restricted gate basis.
+//!
+//! Gates whose name is in the basis are kept as written; everything else is
+//! lowered to single-qubit matrices and CNOTs. Controlled gates use the
+//! A X B X C construction with a phase on the control, multi-controlled ones
+//! the square-root recursion of Barenco et al. (no ancillas), and RXX / RYY /
+//! RZZ a CNOT-RZ-CNOT ladder in the rotated basis. Runs of single-qubit
+//! matrices on a qubit are multiplied together and emitted as Euler angles in
+//! whichever rotations the basis offers: U, RZ RY RZ, RZ RX RZ or RX RY RX
+//! (P stands in for RZ). CNOT becomes H CZ H when only "cz" (CPHASE(pi)) is
+//! available. The result equals the circuit up to global phase; measurement,
+//! reset and snapshots pass through and c_if bodies are rewritten in place.
+
+use num_complex::Complex64;
+use pyo3::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::kernels::{self, Matrix2};
+use crate::{errors, Gate, GateType};
+
+/// Angles this close to a multiple of 2 pi are dropped
+const ANGLE_TOLERANCE: f64 = 1e-7;
+
+const KNOWN: [&str; 28] = [
+    "h", "x", "y", "z", "s", "t", "sdg", "tdg", "rx", "ry", "rz", "u", "p", "cnot", "cz", "crx", "cry", "crz", "ch",
+    "cphase", "mcx", "controlled", "swap", "iswap", "rxx", "ryy", "rzz", "unitary",
+];
+
+/// Single-qubit rotations the basis can express a general 2x2 unitary with
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Euler {
+    U,
+    Zyz,
+    Zxz,
+    Xyx,
+}
+
+/// Instruction between lowering and emission
+enum Step {
+    /// Single-qubit matrix on a qubit, fused with its neighbours
+    One(usize, Matrix2<f64>),
+    /// CNOT(control, target)
+    Cnot(usize, usize),
+    /// Gate emitted as is
+    Keep(Gate),
+}
+
+pub struct Basis {
+    names: HashSet<String>,
+    euler: Option<Euler>,
+    /// Name of the rotation standing in for RZ
+    z_gate: Option<&'static str>,
+}
+
+impl Basis {
+    pub fn new(names: &[String]) -> PyResult<Self> {
+        let names: HashSet<String> = names.iter().map(|n| n.to_ascii_lowercase()).collect();
+        if let Some(unknown) = names.iter().find(|n| !KNOWN.contains(&n.as_str())) {
+            return Err(errors::InvalidGateError::new_err(format!(
+                "Unknown basis gate '{}' (expected any of {})", unknown, KNOWN.join(", "))));
+        }
+        let z_gate = ["rz", "p"].into_iter().find(|n| names.contains(*n));
+        let (x, y) = (names.contains("rx"), names.contains("ry"));
+        let euler = if names.contains("u") {
+            Some(Euler::U)
+        } else if z_gate.is_some() && y {
+            Some(Euler::Zyz)
+        } else if z_gate.is_some() && x {
+            Some(Euler::Zxz)
+        } else if x && y {
+            Some(Euler::Xyx)
+        } else {
+            None
+        };
+        Ok(Basis { names, euler, z_gate })
+    }
+
+    fn keeps(&self, gate_type: &GateType) -> bool {
+        match gate_type {
+            GateType::CPHASE(phi) if (*phi as f64 - PI).abs() < ANGLE_TOLERANCE && self.names.contains("cz") => true,
+            other => self.names.contains(other.name()),
+        }
+    }
+
+    fn listed(&self) -> String {
+        let mut names: Vec<&str> = self.names.iter().map(String::as_str).collect();
+        names.sort_unstable();
+        format!("[{}]", names.join(", "))
+    }
+}
+
+fn matrix_of(gate_type: &GateType) -> Matrix2<f64> {
+    kernels::matrix::<f64>(gate_type).expect("single-qubit or controlled gate")
+}
+
+fn mul(a: &Matrix2<f64>, b: &Matrix2<f64>) -> Matrix2<f64> {
+    let entry = |r: usize, c: usize| a[r][0] * b[0][c] + a[r][1] * b[1][c];
+    [[entry(0, 0), entry(0, 1)], [entry(1, 0), entry(1, 1)]]
+}
+
+fn dagger(m: &Matrix2<f64>) -> Matrix2<f64> {
+    [[m[0][0].conj(), m[1][0].conj()], [m[0][1].conj(), m[1][1].conj()]]
+}
+
+fn rz(phi: f64) -> Matrix2<f64> {
+    let zero = Complex64::new(0.0, 0.0);
+    [[Complex64::from_polar(1.0, -phi / 2.0), zero], [zero, Complex64::from_polar(1.0, phi / 2.0)]]
+}
+
+fn ry(theta: f64) -> Matrix2<f64> {
+    let (s, c) = (theta / 2.0).sin_cos();
+    [[Complex64::new(c, 0.0), Complex64::new(-s, 0.0)], [Complex64::new(s, 0.0), Complex64::new(c, 0.0)]]
+}
+
+/// Principal square root of a 2x2 unitary: (U + s I) / sqrt(tr U + 2s) with
+/// s = sqrt(det U), flipping the sign of s when the denominator vanishes
+fn sqrt(m: &Matrix2<f64>) -> Matrix2<f64> {
+    let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+    let mut s = det.sqrt();
+    if (m[0][0] + m[1][1] + 2.0 * s).norm() < 1e-9 {
+        s = -s;
+    }
+    let t = (m[0][0] + m[1][1] + 2.0 * s).sqrt();
+    [[(m[0][0] + s) / t, m[0][1] / t], [m[1][0] / t, (m[1][1] + s) / t]]
+}
+
+/// (alpha, phi, theta, lambda) with m = e^(i alpha) RZ(phi) RY(theta) RZ(lambda)
+fn zyz(m: &Matrix2<f64>) -> (f64, f64, f64, f64) {
+    let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+    let alpha = det.arg() / 2.0;
+    // In e^(-i alpha) m, entry (1, 1) is cos(theta/2) e^(i (phi + lambda)/2)
+    // and entry (1, 0) is sin(theta/2) e^(i (phi - lambda)/2)
+    let unphased = Complex64::from_polar(1.0, -alpha);
+    let (diagonal, off) = (m[1][1] * unphased, m[1][0] * unphased);
+    let theta = 2.0 * off.norm().atan2(diagonal.norm());
+    let half_sum = if diagonal.norm() < 1e-12 { 0.0 } else { diagonal.arg() };
+    let half_diff = if off.norm() < 1e-12 { 0.0 } else { off.arg() };
+    (alpha, half_sum + half_diff, theta, half_sum - half_diff)
+}
+
+/// True when `angle` is within ANGLE_TOLERANCE of a multiple of 2 pi
+fn negligible(angle: f64) -> bool {
+    let r = angle.rem_euclid(TAU);
+    r.min(TAU - r) < ANGLE_TOLERANCE
+}
+
+struct Lowering<'a> {
+    basis: &'a Basis,
+    steps: Vec<Step>,
+}
+
+impl Lowering<'_> {
+    fn one(&mut self, qubit: usize, m: Matrix2<f64>) {
+        self.steps.push(Step::One(qubit, m));
+    }
+
+    fn cnot(&mut self, control: usize, target: usize) {
+        self.steps.push(Step::Cnot(control, target));
+    }
+
+    /// U on `target` when `control` is |1>: C, CNOT, B, CNOT, A with
+    /// A B C = I, then the phase e^(i alpha) as P(alpha) on the control
+    fn controlled(&mut self, control: usize, target: usize, m: &Matrix2<f64>) {
+        let (alpha, phi, theta, lambda) = zyz(m);
+        self.one(target, rz((lambda - phi) / 2.0));
+        self.cnot(control, target);
+        self.one(target, mul(&ry(-theta / 2.0), &rz(-(lambda + phi) / 2.0)));
+        self.cnot(control, target);
+        self.one(target, mul(&rz(phi), &ry(theta / 2.0)));
+        if !negligible(alpha) {
+            let zero = Complex64::new(0.0, 0.0);
+            self.one(control, [[Complex64::new(1.0, 0.0), zero], [zero, Complex64::from_polar(1.0, alpha)]]);
+        }
+    }
+
+    /// U on `target` when every control is |1>, with V = sqrt(U):
+    /// C(V) from the last control, C^(k-1)X onto it, C(V^dagger), C^(k-1)X,
+    /// then C^(k-1)(V) from the remaining controls
+    fn multi_controlled(&mut self, controls: &[usize], target: usize, m: &Matrix2<f64>) {
+        match controls {
+            [] => self.one(target, *m),
+            [c] => self.controlled(*c, target, m),
+            [rest @ .., last] => {
+                let v = sqrt(m);
+                let x = matrix_of(&GateType::X);
+                self.controlled(*last, target, &v);
+                self.multi_controlled(rest, *last, &x);
+                self.controlled(*last, target, &dagger(&v));
+                self.multi_controlled(rest, *last, &x);
+                self.multi_controlled(rest, target, &v);
+            }
+        }
+    }
+
+    /// exp(-i theta/2 P x P) as (V x V) CNOT RZ(theta) CNOT (V x V)^dagger,
+    /// where V maps Z onto P
+    fn pauli_rotation(&mut self, a: usize, b: usize, theta: f32, v: Matrix2<f64>) {
+        let v_dagger = dagger(&v);
+        self.one(a, v_dagger);
+        self.one(b, v_dagger);
+        self.cnot(a, b);
+        self.one(b, rz(theta as f64));
+        self.cnot(a, b);
+        self.one(a, v);
+        self.one(b, v);
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.cnot(a, b);
+        self.cnot(b, a);
+        self.cnot(a, b);
+    }
+
+    fn lower(&mut self, gate: &Gate) -> PyResult<()> {
+        if self.basis.keeps(&gate.gate_type) {
+            self.steps.push(Step::Keep(gate.clone()));
+            return Ok(());
+        }
+        let t = gate.target;
+        match &gate.gate_type {
+            single if single.is_single_qubit() => self.one(t, matrix_of(single)),
+            GateType::CNOT => self.cnot(gate.control.unwrap(), t),
+            GateType::CRX(_) | GateType::CRY(_) | GateType::CRZ(_) | GateType::CH | GateType::CPHASE(_) => {
+                self.controlled(gate.control.unwrap(), t, &matrix_of(&gate.gate_type));
+            }
+            GateType::MCX(controls) | GateType::CONTROLLED(controls, _) => {
+                self.multi_controlled(controls, t, &matrix_of(&gate.gate_type));
+            }
+            GateType::SWAP(a) => self.swap(*a, t),
+            GateType::ISWAP(a) => {
+                // iSWAP = CZ SWAP (S x S)
+                let s = matrix_of(&GateType::S);
+                self.one(*a, s);
+                self.one(t, s);
+                self.swap(*a, t);
+                let h = matrix_of(&GateType::H);
+                self.one(t, h);
+                self.cnot(*a, t);
+                self.one(t, h);
+            }
+            GateType::RXX(a, theta) => self.pauli_rotation(*a, t, *theta, matrix_of(&GateType::H)),
+            GateType::RYY(a, theta) => {
+                // S H Z H Sdg = S X Sdg = Y
+                self.pauli_rotation(*a, t, *theta, mul(&matrix_of(&GateType::S), &matrix_of(&GateType::H)));
+            }
+            GateType::RZZ(a, theta) => {
+                let identity = matrix_of(&GateType::RZ(0.0));
+                self.pauli_rotation(*a, t, *theta, identity);
+            }
+            GateType::UNITARY(targets, m) if targets.len() == 1 => {
+                let e = |i: usize| Complex64::new(m[i].re as f64, m[i].im as f64);
+                self.one(t, [[e(0), e(1)], [e(2), e(3)]]);
+            }
+            GateType::UNITARY(targets, _) => {
+                return Err(errors::InvalidGateError::new_err(format!(
+                    "Cannot decompose a dense {}-qubit unitary; only single-qubit unitaries are supported",
+                    targets.len())));
+            }
+            GateType::IF(bit, value, body) => {
+                let body = decompose(body, self.basis)?;
+                self.steps.push(Step::Keep(Gate { gate_type: GateType::IF(*bit, *value, body), ..gate.clone() }));
+            }
+            _ => self.steps.push(Step::Keep(gate.clone())),
+        }
+        Ok(())
+    }
+}
+
+struct Emitter<'a> {
+    basis: &'a Basis,
+    out: Vec<Gate>,
+}
+
+impl Emitter<'_> {
+    fn push(&mut self, gate_type: GateType, target: usize) {
+        self.out.push(Gate { gate_type, target, control: None });
+    }
+
+    fn z_rotation(&mut self, qubit: usize, angle: f64) {
+        if negligible(angle) {
+            return;
+        }
+        let angle = angle as f32;
+        match self.basis.z_gate {
+            Some("p") => self.push(GateType::P(angle), qubit),
+            _ => self.push(GateType::RZ(angle), qubit),
+        }
+    }
+
+    fn rotation(&mut self, qubit: usize, angle: f64, make: fn(f32) -> GateType) {
+        if !negligible(angle) {
+            self.push(make(angle as f32), qubit);
+        }
+    }
+
+    /// Euler-angle gates for `m` up to global phase; nothing for the identity
+    fn single(&mut self, qubit: usize, m: &Matrix2<f64>) -> PyResult<()> {
+        let euler = self.basis.euler.ok_or_else(|| errors::InvalidGateError::new_err(format!(
+            "Basis {} cannot express arbitrary single-qubit gates (add u, or two of rx, ry and rz)",
+            self.basis.listed())))?;
+        let (_, phi, theta, lambda) = zyz(m);
+        let z_only = theta.abs() < ANGLE_TOLERANCE;
+        match euler {
+            Euler::U => {
+                if !(z_only && negligible(phi + lambda)) {
+                    self.push(GateType::U(theta as f32, phi as f32, lambda as f32), qubit);
+                }
+            }
+            Euler::Zyz if z_only => self.z_rotation(qubit, phi + lambda),
+            Euler::Zyz => {
+                self.z_rotation(qubit, lambda);
+                self.rotation(qubit, theta, GateType::RY);
+                self.z_rotation(qubit, phi);
+            }
+            // RY(theta) = RZ(pi/2) RX(theta) RZ(-pi/2)
+            Euler::Zxz if z_only => self.z_rotation(qubit, phi + lambda),
+            Euler::Zxz => {
+                self.z_rotation(qubit, lambda - FRAC_PI_2);
+                self.rotation(qubit, theta, GateType::RX);
+                self.z_rotation(qubit, phi + FRAC_PI_2);
+            }
+            Euler::Xyx => {
+                // H m H = RZ(phi) RY(theta) RZ(lambda) gives m = RX(phi) RY(-theta) RX(lambda)
+                let h = matrix_of(&GateType::H);
+                let (_, phi, theta, lambda) = zyz(&mul(&h, &mul(m, &h)));
+                if theta.abs() < ANGLE_TOLERANCE {
+                    self.rotation(qubit, phi + lambda, GateType::RX);
+                } else {
+                    self.rotation(qubit, lambda, GateType::RX);
+                    self.rotation(qubit, -theta, GateType::RY);
+                    self.rotation(qubit, phi, GateType::RX);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn cnot(&mut self, control: usize, target: usize, pending: &mut BTreeMap<usize, Matrix2<f64>>) -> PyResult<()> {
+        if self.basis.names.contains("cnot") {
+            self.out.push(Gate { gate_type: GateType::CNOT, target, control: Some(control) });
+            return Ok(());
+        }
+        if !self.basis.names.contains("cz") {
+            return Err(errors::InvalidGateError::new_err(format!(
+                "Basis {} has no two-qubit gate to decompose into (add cnot or cz)", self.basis.listed())));
+        }
+        // CNOT = (I x H) CZ (I x H); the trailing H joins the target's next run
+        let h = matrix_of(&GateType::H);
+        let before = pending.remove(&target).map_or(h, |m| mul(&h, &m));
+        self.single(target, &before)?;
+        self.out.push(Gate { gate_type: GateType::CPHASE(PI as f32), target, control: Some(control) });
+        pending.insert(target, h);
+        Ok(())
+    }
+}
+
+/// `gates` rewritten into `basis`, equal up to global phase
+pub fn decompose(gates: &[Gate], basis: &Basis) -> PyResult<Vec<Gate>> {
+    let mut lowering = Lowering { basis, steps: Vec::new() };
+    for gate in gates {
+        lowering.lower(gate)?;
+    }
+    let mut emitter = Emitter { basis, out: Vec::with_capacity(lowering.steps.len()) };
+    let mut pending: BTreeMap<usize, Matrix2<f64>> = BTreeMap::new();
+    for step in lowering.steps {
+        match step {
+            Step::One(q, m) => {
+                let product = pending.remove(&q).map_or(m, |prev| mul(&m, &prev));
+                pending.insert(q, product);
+            }
+            Step::Cnot(c, t) => {
+                for q in [c, t] {
+                    if let Some(m) = pending.remove(&q) {
+                        emitter.single(q, &m)?;
+                    }
+                }
+                emitter.cnot(c, t, &mut pending)?;
+            }
+            Step::Keep(gate) => {
+                let qubits = if gate.is_barrier() { pending.keys().copied().collect() } else { gate.qubits() };
+                for q in qubits {
+                    if let Some(m) = pending.remove(&q) {
+                        emitter.single(q, &m)?;
+                    }
+                }
+                emitter.out.push(gate);
+            }
+        }
+    }
+    for (q, m) in pending {
+        emitter.single(q, &m)?;
+    }
+    Ok(emitter.out)
+}
@@ -0,0 +1,31 @@
+//! Execution device selection for `QuantumCircuit::execute`.
+//!
+//! The CPU state-vector backend is always compiled in; `"gpu"` selects the
+//! wgpu backend in `gpu`, built with the `gpu` cargo feature, and without it is
+//! recognised so callers get a clear error instead of an unknown-device one.
+
+use pyo3::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    Cpu,
+    #[cfg(feature = "gpu")]
+    Gpu,
+}
+
+impl Device {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "cpu" => Ok(Device::Cpu),
+            #[cfg(feature = "gpu")]
+            "gpu" => Ok(Device::Gpu),
+            #[cfg(not(feature = "gpu"))]
+            "gpu" => Err(pyo3::exceptions::PyValueError::new_err(
+                "GPU execution is not available in this build of quantum_engine (rebuild with --features gpu, or use device='cpu')")),
+            "cuda" => Err(pyo3::exceptions::PyValueError::new_err(
+                "There is no CUDA backend; device='gpu' runs on wgpu (Vulkan, Metal, DirectX 12 or OpenGL)")),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(
+                format!("Unknown device '{}' (expected 'cpu' or 'gpu')", name))),
+        }
+    }
+}
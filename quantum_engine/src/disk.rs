@@ -0,0 +1,275 @@
+//! Out-of-core state vector for `QuantumCircuit.execute_on_disk`.
+//!
+//! The 2^n amplitudes live in a file laid out as an f32 state-vector
+//! checkpoint (so `QuantumState.load` reads it back once it fits in memory)
+//! and stream through RAM in blocks of 2^block_qubits amplitudes with
+//! positioned reads and writes, leaving caching to the OS page cache.
+//!
+//! Consecutive gates whose qubits all lie below block_qubits are applied block
+//! by block in a single pass over the file. A gate touching h higher qubits
+//! instead gathers, for every setting of the remaining high bits, the 2^h
+//! chunks that differ only in those bits into one buffer and relabels them onto
+//! the buffer's top bits. Chunks shrink by 2^k for a k-qubit gate so the buffer
+//! never exceeds the block size. Either way every read and write is a long
+//! contiguous run, which suits the butterfly and permutation kernels.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::bitorder::BitOrder;
+use crate::checkpoint::{self, Kind, HEADER_LEN};
+use crate::{format_bitstring, kernels, parse_bitstring, reduction, rng, Gate, QuantumState};
+
+/// Bytes per stored amplitude (two little-endian f32s)
+const AMPLITUDE_BYTES: u64 = 8;
+/// Largest register the file layout addresses (2^40 amplitudes is 8 TiB)
+const MAX_QUBITS: usize = 40;
+const MAX_BLOCK_QUBITS: usize = 30;
+
+/// State vector stored in a file and processed in blocks
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone)]
+pub struct DiskState {
+    #[pyo3(get)]
+    qubits: usize,
+    #[pyo3(get)]
+    path: String,
+    block_qubits: usize,
+}
+
+#[pymethods]
+impl DiskState {
+    /// (re, im) amplitude of one basis state (character i of the bitstring is
+    /// qubit i, or qubit n - 1 - i with bit_order="big")
+    #[pyo3(signature = (bitstring, bit_order="little"))]
+    pub fn amplitude(&self, bitstring: &str, bit_order: &str) -> PyResult<(f32, f32)> {
+        let idx = BitOrder::from_name(bit_order)?.index(parse_bitstring(bitstring, self.qubits)?, self.qubits);
+        let mut c = [Complex32::new(0.0, 0.0)];
+        read_chunk(&mut self.open()?, idx as u64, &mut c).map_err(|e| self.io_error(e))?;
+        Ok((c[0].re, c[0].im))
+    }
+
+    /// |amplitude|^2 of one basis state
+    #[pyo3(signature = (bitstring, bit_order="little"))]
+    pub fn probability(&self, bitstring: &str, bit_order: &str) -> PyResult<f32> {
+        let (re, im) = self.amplitude(bitstring, bit_order)?;
+        Ok(re * re + im * im)
+    }
+
+    /// <psi|psi>, accumulated block by block in double precision
+    pub fn norm(&self, py: Python) -> PyResult<f64> {
+        py.allow_threads(|| Ok(self.block_weights()?.iter().sum()))
+    }
+
+    /// Sample `shots` computational-basis outcomes as a counts dictionary in two
+    /// streaming passes: block weights first, then the blocks the draws land in
+    #[pyo3(signature = (shots, seed=None, bit_order="little"))]
+    pub fn measure(&self, py: Python, shots: usize, seed: Option<u64>, bit_order: &str) -> PyResult<HashMap<String, usize>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        let counts = py.allow_threads(|| self.sample(shots, seed))?;
+        Ok(counts.into_iter()
+            .map(|(idx, count)| (bit_order.bitstring(format_bitstring(idx, self.qubits)), count))
+            .collect())
+    }
+
+    /// Load the whole state into memory as a QuantumState
+    pub fn to_state(&self, py: Python) -> PyResult<QuantumState> {
+        let (qubits, amplitudes) = py.allow_threads(|| checkpoint::load(&self.path, Kind::StateVector))?;
+        Ok(QuantumState { qubits, state: amplitudes, phase: 0.0, drift: 0.0 })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DiskState(qubits={}, path='{}', block_qubits={})", self.qubits, self.path, self.block_qubits)
+    }
+}
+
+impl DiskState {
+    fn open(&self) -> PyResult<File> {
+        OpenOptions::new().read(true).write(true).open(&self.path).map_err(|e| self.io_error(e))
+    }
+
+    fn io_error(&self, e: std::io::Error) -> PyErr {
+        checkpoint::io_error(&self.path, e)
+    }
+
+    fn block_len(&self) -> usize {
+        1 << self.block_qubits
+    }
+
+    fn n_blocks(&self) -> u64 {
+        1 << (self.qubits - self.block_qubits)
+    }
+
+    /// Squared norm of every block
+    fn block_weights(&self) -> PyResult<Vec<f64>> {
+        let mut file = self.open()?;
+        let mut buf = vec![Complex32::new(0.0, 0.0); self.block_len()];
+        (0..self.n_blocks())
+            .map(|b| {
+                read_chunk(&mut file, b * buf.len() as u64, &mut buf).map_err(|e| self.io_error(e))?;
+                Ok(buf.iter().map(|c| c.norm_sqr() as f64).sum())
+            })
+            .collect()
+    }
+
+    fn sample(&self, shots: usize, seed: Option<u64>) -> PyResult<HashMap<usize, usize>> {
+        let weights = self.block_weights()?;
+        let total: f64 = weights.iter().sum();
+        let mut rng = rng::seeded(seed);
+        let mut draws: Vec<f64> = (0..shots).map(|_| rng.gen::<f64>() * total).collect();
+        draws.sort_unstable_by(f64::total_cmp);
+
+        let mut file = self.open()?;
+        let mut buf = vec![Complex32::new(0.0, 0.0); self.block_len()];
+        let mut counts = HashMap::new();
+        let (mut next, mut acc) = (0, 0.0);
+        let mut last = 0;
+        for (b, &w) in weights.iter().enumerate() {
+            if next == shots {
+                break;
+            }
+            if w == 0.0 || draws[next] >= acc + w {
+                acc += w;
+                continue;
+            }
+            read_chunk(&mut file, b as u64 * buf.len() as u64, &mut buf).map_err(|e| self.io_error(e))?;
+            for (i, c) in buf.iter().enumerate() {
+                let p = c.norm_sqr() as f64;
+                if p == 0.0 {
+                    continue;
+                }
+                last = (b << self.block_qubits) | i;
+                acc += p;
+                while next < shots && draws[next] < acc {
+                    *counts.entry(last).or_insert(0) += 1;
+                    next += 1;
+                }
+            }
+        }
+        // Rounding can leave the largest draws just past the final sum
+        if next < shots {
+            *counts.entry(last).or_insert(0) += shots - next;
+        }
+        Ok(counts)
+    }
+
+    /// Apply consecutive gates that only touch qubits below block_qubits in one pass
+    fn apply_local(&self, file: &mut File, gates: &[Gate]) -> std::io::Result<()> {
+        let mut buf = vec![Complex32::new(0.0, 0.0); self.block_len()];
+        for b in 0..self.n_blocks() {
+            let start = b * buf.len() as u64;
+            read_chunk(file, start, &mut buf)?;
+            for gate in gates {
+                kernels::apply(&mut buf, gate, false);
+            }
+            write_chunk(file, start, &buf)?;
+        }
+        Ok(())
+    }
+
+    /// Apply a gate with qubits at or above block_qubits by gathering the
+    /// chunks that differ only in its high bits
+    fn apply_gathered(&self, file: &mut File, gate: &Gate) -> std::io::Result<()> {
+        let n = self.qubits;
+        let qubits = gate.qubits();
+        let chunk_qubits = self.block_qubits.saturating_sub(qubits.len());
+        let mut high: Vec<usize> = qubits.into_iter().filter(|&q| q >= chunk_qubits).collect();
+        high.sort_unstable();
+        let rest: Vec<usize> = (chunk_qubits..n).filter(|q| !high.contains(q)).collect();
+        let local = gate.remapped(|q| match high.iter().position(|&h| h == q) {
+            Some(j) => chunk_qubits + j,
+            None => q,
+        });
+
+        let chunk = 1usize << chunk_qubits;
+        let high_offsets = reduction::scatter_indices(&high);
+        let rest_offsets = |r: usize| rest.iter().enumerate()
+            .filter(|(k, _)| (r >> k) & 1 == 1)
+            .fold(0usize, |acc, (_, &q)| acc | (1 << q));
+        let mut buf = vec![Complex32::new(0.0, 0.0); chunk * high_offsets.len()];
+        for r in 0..1usize << rest.len() {
+            let base = rest_offsets(r);
+            for (j, offset) in high_offsets.iter().enumerate() {
+                read_chunk(file, (base | offset) as u64, &mut buf[j * chunk..(j + 1) * chunk])?;
+            }
+            kernels::apply(&mut buf, &local, false);
+            for (j, offset) in high_offsets.iter().enumerate() {
+                write_chunk(file, (base | offset) as u64, &buf[j * chunk..(j + 1) * chunk])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn seek_to(file: &mut File, index: u64) -> std::io::Result<()> {
+    file.seek(SeekFrom::Start(HEADER_LEN as u64 + index * AMPLITUDE_BYTES)).map(|_| ())
+}
+
+/// Read the amplitudes starting at basis index `start` into `buf`
+fn read_chunk(file: &mut File, start: u64, buf: &mut [Complex32]) -> std::io::Result<()> {
+    seek_to(file, start)?;
+    let mut bytes = vec![0u8; buf.len() * AMPLITUDE_BYTES as usize];
+    file.read_exact(&mut bytes)?;
+    for (c, b) in buf.iter_mut().zip(bytes.chunks_exact(AMPLITUDE_BYTES as usize)) {
+        *c = Complex32::new(f32::from_le_bytes(b[..4].try_into().unwrap()), f32::from_le_bytes(b[4..].try_into().unwrap()));
+    }
+    Ok(())
+}
+
+fn write_chunk(file: &mut File, start: u64, buf: &[Complex32]) -> std::io::Result<()> {
+    seek_to(file, start)?;
+    let bytes: Vec<u8> = buf.iter().flat_map(|c| c.re.to_le_bytes().into_iter().chain(c.im.to_le_bytes())).collect();
+    file.write_all(&bytes)
+}
+
+/// Create the file at `path` holding `initial` (|0...0> when None) and run the
+/// unitary `gates` on it
+pub fn run(path: &str, qubits: usize, initial: Option<&[Complex32]>, gates: &[Gate], block_qubits: usize) -> PyResult<DiskState> {
+    if qubits > MAX_QUBITS {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Out-of-core execution supports at most {} qubits", MAX_QUBITS)));
+    }
+    if block_qubits == 0 || block_qubits > MAX_BLOCK_QUBITS {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "block_qubits must be between 1 and {}", MAX_BLOCK_QUBITS)));
+    }
+    for gate in gates {
+        kernels::validate(gate, qubits)?;
+    }
+    let state = DiskState { qubits, path: path.to_string(), block_qubits: block_qubits.min(qubits) };
+    let dim = 1u64 << qubits;
+
+    let io = |e| state.io_error(e);
+    let mut file = File::create(path).map_err(io)?;
+    file.write_all(&checkpoint::header(Kind::StateVector, qubits, 4, dim)).map_err(io)?;
+    // Extending the file zero-fills it (sparsely where the filesystem allows)
+    file.set_len(HEADER_LEN as u64 + dim * AMPLITUDE_BYTES).map_err(io)?;
+    match initial {
+        Some(amps) => write_chunk(&mut file, 0, amps),
+        None => write_chunk(&mut file, 0, &[Complex32::new(1.0, 0.0)]),
+    }
+    .map_err(io)?;
+    drop(file);
+
+    let mut file = state.open()?;
+    let gates: Vec<&Gate> = gates.iter().filter(|g| !g.is_snapshot()).collect();
+    let is_local = |g: &Gate| g.qubits().iter().all(|&q| q < state.block_qubits);
+    let mut i = 0;
+    while i < gates.len() {
+        if is_local(gates[i]) {
+            let run_len = gates[i..].iter().take_while(|g| is_local(g)).count();
+            let batch: Vec<Gate> = gates[i..i + run_len].iter().map(|&g| g.clone()).collect();
+            state.apply_local(&mut file, &batch).map_err(io)?;
+            i += run_len;
+        } else {
+            state.apply_gathered(&mut file, gates[i]).map_err(io)?;
+            i += 1;
+        }
+    }
+    file.sync_all().map_err(io)?;
+    Ok(state)
+}
@@ -0,0 +1,387 @@
+//! Distributed state-vector simulation for `QuantumCircuit.execute_distributed`.
+//!
+//! With 2^r ranks, rank k holds the 2^(n-r) amplitudes whose top r index bits
+//! (the global qubits) equal k. Gates on local qubits run independently on
+//! every shard. For a gate on global qubit g, every rank first swaps g with a
+//! local qubit f the gate does not touch: ranks that differ in bit g trade the
+//! halves of their shards with opposite values of f. The relabelled gate then
+//! runs locally and a second exchange swaps the qubits back.
+//!
+//! Ranks are worker processes (`run_worker`) connected over TCP to a
+//! coordinator, which sends each its rank, the circuit and its shard, relays
+//! the exchanged halves and gathers the final shards into a QuantumState. By
+//! default the workers are spawned on this machine; with `spawn=False` the
+//! coordinator waits for `n_ranks` workers started elsewhere. Every frame is a
+//! tag byte, a little-endian u64 payload length and the payload.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::{resources, serialize, Gate, QuantumCircuit, QuantumState};
+
+/// Coordinator -> worker: rank, rank count, circuit and initial shard
+const TAG_INIT: u8 = 1;
+/// Half a shard on its way between two ranks
+const TAG_DATA: u8 = 2;
+/// Worker -> coordinator: the final shard
+const TAG_RESULT: u8 = 3;
+/// Worker -> coordinator: what went wrong
+const TAG_ERROR: u8 = 4;
+
+/// Spawned worker command line: the Python interpreter and the sys.path it
+/// needs to import this module
+pub struct Spawn {
+    pub python: String,
+    pub python_path: String,
+}
+
+/// One step of the per-rank schedule, identical on every rank
+enum Step {
+    Local(Gate),
+    Swap { global: usize, local: usize },
+}
+
+fn write_frame(stream: &mut impl Write, tag: u8, payload: &[u8]) -> io::Result<()> {
+    stream.write_all(&[tag])?;
+    stream.write_all(&(payload.len() as u64).to_le_bytes())?;
+    stream.write_all(payload)?;
+    stream.flush()
+}
+
+fn read_frame(stream: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut head = [0u8; 9];
+    stream.read_exact(&mut head)?;
+    let len = u64::from_le_bytes(head[1..].try_into().unwrap()) as usize;
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload)?;
+    Ok((head[0], payload))
+}
+
+fn io_error(e: io::Error) -> PyErr {
+    pyo3::exceptions::PyIOError::new_err(format!("Distributed execution: {}", e))
+}
+
+fn protocol_error(message: &str) -> PyErr {
+    pyo3::exceptions::PyRuntimeError::new_err(format!("Distributed execution: {}", message))
+}
+
+fn u32_at(data: &[u8], at: usize) -> PyResult<u32> {
+    data.get(at..at + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap())).ok_or_else(|| protocol_error("truncated frame"))
+}
+
+fn u64_at(data: &[u8], at: usize) -> PyResult<u64> {
+    data.get(at..at + 8).map(|b| u64::from_le_bytes(b.try_into().unwrap())).ok_or_else(|| protocol_error("truncated frame"))
+}
+
+/// Local gates and global-local swaps for an n-qubit circuit on shards of
+/// `local_qubits` qubits
+fn schedule(gates: &[Gate], n: usize, local_qubits: usize) -> PyResult<Vec<Step>> {
+    let mut steps = Vec::new();
+    for gate in gates {
+        crate::kernels::validate(gate, n)?;
+        if gate.is_snapshot() {
+            continue;
+        }
+        let qubits = gate.qubits();
+        let global: Vec<usize> = qubits.iter().copied().filter(|&q| q >= local_qubits).collect();
+        if global.is_empty() {
+            steps.push(Step::Local(gate.clone()));
+            continue;
+        }
+        let free: Vec<usize> = (0..local_qubits).filter(|q| !qubits.contains(q)).take(global.len()).collect();
+        if free.len() < global.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "A {}-qubit gate needs more than the {} local qubits each rank holds; use fewer ranks",
+                qubits.len(), local_qubits)));
+        }
+        let pairs: Vec<(usize, usize)> = global.into_iter().zip(free).collect();
+        steps.extend(pairs.iter().map(|&(global, local)| Step::Swap { global, local }));
+        steps.push(Step::Local(gate.remapped(|q| pairs.iter().find(|p| p.0 == q).map_or(q, |p| p.1))));
+        steps.extend(pairs.iter().rev().map(|&(global, local)| Step::Swap { global, local }));
+    }
+    Ok(steps)
+}
+
+/// Run the coordinator: start or await `n_ranks` workers, distribute `circuit`
+/// (unitary, parameters bound) and gather the final state
+pub fn execute(
+    circuit: QuantumCircuit,
+    initial: Option<Vec<Complex32>>,
+    n_ranks: usize,
+    address: &str,
+    spawn: Option<Spawn>,
+    timeout: Duration,
+) -> PyResult<QuantumState> {
+    let n = circuit.qubits;
+    if !n_ranks.is_power_of_two() || (n_ranks > 1 && n_ranks.trailing_zeros() as usize >= n) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "n_ranks must be a power of two below 2^{} (one local qubit per rank at least)", n)));
+    }
+    // The shards are gathered back into one full state at the end
+    resources::check_state::<Complex32>(n)?;
+    let local_qubits = n - n_ranks.trailing_zeros() as usize;
+    schedule(&circuit.gates, n, local_qubits)?;
+    let shard_len = 1usize << local_qubits;
+    let program = serialize::to_bytes(&circuit)?;
+
+    let listener = TcpListener::bind(address).map_err(io_error)?;
+    let mut connect = listener.local_addr().map_err(io_error)?;
+    if connect.ip().is_unspecified() {
+        connect = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), connect.port());
+    }
+    let mut children: Vec<Child> = Vec::new();
+    if let Some(spawn) = &spawn {
+        for _ in 0..n_ranks {
+            let child = Command::new(&spawn.python)
+                .arg("-c")
+                .arg(format!("import quantum_engine; quantum_engine.run_worker('{}')", connect))
+                .env("PYTHONPATH", &spawn.python_path)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .spawn()
+                .map_err(io_error);
+            match child {
+                Ok(child) => children.push(child),
+                Err(e) => {
+                    stop(&mut children);
+                    return Err(e);
+                }
+            }
+        }
+    }
+    let outcome = coordinate(&listener, &mut children, &program, initial.as_deref(), n_ranks, shard_len, timeout);
+    match outcome {
+        Ok(shards) => {
+            for child in &mut children {
+                let _ = child.wait();
+            }
+            Ok(QuantumState { qubits: n, state: shards.concat(), phase: 0.0, drift: 0.0 })
+        }
+        Err(e) => {
+            stop(&mut children);
+            Err(e)
+        }
+    }
+}
+
+fn stop(children: &mut [Child]) {
+    for child in children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Accept the workers, relay their exchanges and collect the shards in rank order
+fn coordinate(
+    listener: &TcpListener,
+    children: &mut [Child],
+    program: &[u8],
+    initial: Option<&[Complex32]>,
+    n_ranks: usize,
+    shard_len: usize,
+    timeout: Duration,
+) -> PyResult<Vec<Vec<Complex32>>> {
+    let deadline = Instant::now() + timeout;
+    listener.set_nonblocking(true).map_err(io_error)?;
+    let mut streams = Vec::with_capacity(n_ranks);
+    while streams.len() < n_ranks {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false).map_err(io_error)?;
+                stream.set_nodelay(true).map_err(io_error)?;
+                streams.push(stream);
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if children.iter_mut().any(|c| matches!(c.try_wait(), Ok(Some(_)))) {
+                    return Err(protocol_error("a worker process exited before connecting"));
+                }
+                if Instant::now() > deadline {
+                    return Err(protocol_error(&format!(
+                        "only {} of {} workers connected before the timeout", streams.len(), n_ranks)));
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Err(e) => return Err(io_error(e)),
+        }
+    }
+
+    for (rank, stream) in streams.iter_mut().enumerate() {
+        let mut init = Vec::new();
+        init.extend_from_slice(&(rank as u32).to_le_bytes());
+        init.extend_from_slice(&(n_ranks as u32).to_le_bytes());
+        init.extend_from_slice(&(program.len() as u64).to_le_bytes());
+        init.extend_from_slice(program);
+        if let Some(amps) = initial {
+            init.extend(serialize::buffer_to_bytes(&amps[rank * shard_len..(rank + 1) * shard_len]));
+        }
+        write_frame(stream, TAG_INIT, &init).map_err(io_error)?;
+    }
+
+    // One writer thread per rank drains its outbox, so relaying never blocks
+    // on a worker that is busy sending its own half
+    let (results_tx, results_rx) = mpsc::channel();
+    let mut outboxes = Vec::with_capacity(n_ranks);
+    for stream in &streams {
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let mut writer = stream.try_clone().map_err(io_error)?;
+        std::thread::spawn(move || {
+            for payload in rx {
+                if write_frame(&mut writer, TAG_DATA, &payload).is_err() {
+                    break;
+                }
+            }
+        });
+        outboxes.push(tx);
+    }
+    for (rank, stream) in streams.iter().enumerate() {
+        let mut reader = stream.try_clone().map_err(io_error)?;
+        let outboxes = outboxes.clone();
+        let results = results_tx.clone();
+        std::thread::spawn(move || loop {
+            let outcome = match read_frame(&mut reader) {
+                Ok((TAG_DATA, payload)) => {
+                    let dest = u32_at(&payload, 0).ok().and_then(|d| outboxes.get(d as usize));
+                    match dest {
+                        Some(outbox) if outbox.send(payload[4..].to_vec()).is_ok() => continue,
+                        _ => Err(format!("rank {} sent to an unknown rank", rank)),
+                    }
+                }
+                Ok((TAG_RESULT, payload)) => Ok(payload),
+                Ok((TAG_ERROR, payload)) => Err(format!("rank {}: {}", rank, String::from_utf8_lossy(&payload))),
+                Ok((tag, _)) => Err(format!("rank {} sent unknown frame {}", rank, tag)),
+                Err(e) => Err(format!("rank {} disconnected: {}", rank, e)),
+            };
+            let _ = results.send((rank, outcome));
+            break;
+        });
+    }
+    drop(outboxes);
+    drop(results_tx);
+
+    let mut shards = vec![Vec::new(); n_ranks];
+    let mut outcome = Ok(());
+    for _ in 0..n_ranks {
+        let Ok((rank, result)) = results_rx.recv() else { break };
+        match result.map_err(|e| protocol_error(&e)).and_then(|data| serialize::buffer_from_bytes(&data, shard_len)) {
+            Ok(shard) => shards[rank] = shard,
+            Err(e) => {
+                outcome = Err(e);
+                break;
+            }
+        }
+    }
+    for stream in &streams {
+        let _ = stream.shutdown(Shutdown::Both);
+    }
+    outcome.map(|_| shards)
+}
+
+/// Rank-side state: the shard, the link to the coordinator and halves that
+/// arrived ahead of the exchange they belong to
+struct Worker {
+    stream: TcpStream,
+    rank: usize,
+    local_qubits: usize,
+    shard: Vec<Complex32>,
+    early: HashMap<u64, Vec<u8>>,
+}
+
+impl Worker {
+    fn connect(address: &str) -> PyResult<(Worker, QuantumCircuit)> {
+        let mut stream = TcpStream::connect(address).map_err(io_error)?;
+        stream.set_nodelay(true).map_err(io_error)?;
+        let (tag, init) = read_frame(&mut stream).map_err(io_error)?;
+        if tag != TAG_INIT {
+            return Err(protocol_error("expected an init frame"));
+        }
+        let rank = u32_at(&init, 0)? as usize;
+        let n_ranks = u32_at(&init, 4)? as usize;
+        let program_len = u64_at(&init, 8)? as usize;
+        let program = init.get(16..16 + program_len).ok_or_else(|| protocol_error("truncated frame"))?;
+        let circuit = serialize::from_bytes(program)?;
+        if !n_ranks.is_power_of_two() || n_ranks.trailing_zeros() as usize > circuit.qubits {
+            return Err(protocol_error("invalid rank count"));
+        }
+        let local_qubits = circuit.qubits - n_ranks.trailing_zeros() as usize;
+        let shard_data = &init[16 + program_len..];
+        resources::check_state::<Complex32>(local_qubits)?;
+        let shard = if shard_data.is_empty() {
+            let mut shard = vec![Complex32::new(0.0, 0.0); 1 << local_qubits];
+            if rank == 0 {
+                shard[0] = Complex32::new(1.0, 0.0);
+            }
+            shard
+        } else {
+            serialize::buffer_from_bytes(shard_data, 1 << local_qubits)?
+        };
+        Ok((Worker { stream, rank, local_qubits, shard, early: HashMap::new() }, circuit))
+    }
+
+    fn run(&mut self, gates: &[Gate], n: usize) -> PyResult<()> {
+        for (step, action) in schedule(gates, n, self.local_qubits)?.iter().enumerate() {
+            match action {
+                Step::Local(gate) => crate::kernels::apply(&mut self.shard, gate, false),
+                Step::Swap { global, local } => self.exchange(*global, *local, step as u64)?,
+            }
+        }
+        write_frame(&mut self.stream, TAG_RESULT, &serialize::buffer_to_bytes(&self.shard)).map_err(io_error)
+    }
+
+    /// SWAP(global, local): trade the half of the shard whose `local` bit
+    /// differs from this rank's `global` bit with the partner rank
+    fn exchange(&mut self, global: usize, local: usize, step: u64) -> PyResult<()> {
+        let bit = (self.rank >> (global - self.local_qubits)) & 1;
+        let partner = self.rank ^ (1 << (global - self.local_qubits));
+        let outgoing = |i: &usize| (i >> local) & 1 != bit;
+        let half: Vec<Complex32> = (0..self.shard.len()).filter(outgoing).map(|i| self.shard[i]).collect();
+
+        let mut payload = Vec::with_capacity(12 + 8 * half.len());
+        payload.extend_from_slice(&(partner as u32).to_le_bytes());
+        payload.extend_from_slice(&step.to_le_bytes());
+        payload.extend(serialize::buffer_to_bytes(&half));
+        write_frame(&mut self.stream, TAG_DATA, &payload).map_err(io_error)?;
+
+        let incoming = serialize::buffer_from_bytes(&self.receive(step)?, half.len())?;
+        for (i, c) in (0..self.shard.len()).filter(outgoing).zip(incoming) {
+            self.shard[i] = c;
+        }
+        Ok(())
+    }
+
+    /// Amplitudes sent to this rank for exchange `step`
+    fn receive(&mut self, step: u64) -> PyResult<Vec<u8>> {
+        loop {
+            if let Some(data) = self.early.remove(&step) {
+                return Ok(data);
+            }
+            let (tag, mut payload) = read_frame(&mut self.stream).map_err(io_error)?;
+            if tag != TAG_DATA {
+                return Err(protocol_error("expected a data frame"));
+            }
+            let arrived = u64_at(&payload, 0)?;
+            self.early.insert(arrived, payload.split_off(8));
+        }
+    }
+}
+
+/// Serve as one rank of a distributed execution coordinated at `address`
+/// ("host:port"); returns once the coordinator has the final shard. Start one
+/// per rank on each machine when calling execute_distributed(spawn=False).
+#[pyfunction]
+pub fn run_worker(py: Python, address: &str) -> PyResult<()> {
+    py.allow_threads(|| {
+        let (mut worker, circuit) = Worker::connect(address)?;
+        let outcome = worker.run(&circuit.gates, circuit.qubits);
+        if let Err(e) = &outcome {
+            let message = Python::with_gil(|py| e.value(py).to_string());
+            let _ = write_frame(&mut worker.stream, TAG_ERROR, message.as_bytes());
+        }
+        outcome
+    })
+}
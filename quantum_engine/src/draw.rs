@@ -0,0 +1,155 @@
+//! Text diagrams for `QuantumCircuit.draw`.
+//!
+//! One row per qubit (qubit 0 on top) plus a connector row between
+//! neighbours. Gates are packed left into layers: a gate lands in the first
+//! layer after every gate that touches a qubit in its vertical span, so
+//! independent gates share a column. Boxes hold gate labels, `*` marks a
+//! control, `x` a SWAP end and `|` a connection between the qubits of a
+//! multi-qubit gate; snapshots show as a column of `#` and restores of `<`.
+
+use std::collections::HashMap;
+
+use crate::params::ParamRef;
+use crate::{Gate, GateType, QuantumCircuit};
+
+/// One drawn instruction: the text on each involved qubit and the qubit range
+/// the connecting line spans
+struct Element {
+    cells: Vec<(usize, String)>,
+    low: usize,
+    high: usize,
+    /// Reads or writes the classical register, so stays ordered with other such elements
+    classical: bool,
+}
+
+fn angle(value: f32, param: Option<&ParamRef>) -> String {
+    match param {
+        Some(p) if p.scale == 1.0 => p.name.clone(),
+        Some(p) => format!("{}*{}", p.scale, p.name),
+        None => format!("{:.2}", value),
+    }
+}
+
+/// Box label of a single-qubit (or inner controlled) gate
+fn label(gate_type: &GateType, param: Option<&ParamRef>) -> String {
+    match gate_type {
+        GateType::RX(a) => format!("RX({})", angle(*a, param)),
+        GateType::RY(a) => format!("RY({})", angle(*a, param)),
+        GateType::RZ(a) => format!("RZ({})", angle(*a, param)),
+        GateType::P(a) => format!("P({})", angle(*a, param)),
+        GateType::U(theta, phi, lambda) => format!("U({:.2},{:.2},{:.2})", theta, phi, lambda),
+        other => other.name().to_ascii_uppercase(),
+    }
+}
+
+fn boxed(text: &str, condition: &str) -> String {
+    format!("[{}{}]", text, condition)
+}
+
+fn elements(gate: &Gate, param: Option<&ParamRef>, n_qubits: usize, condition: &str, out: &mut Vec<Element>) {
+    let controlled = |controls: Vec<usize>, inner: String| {
+        let mut cells: Vec<(usize, String)> = controls.into_iter().map(|c| (c, "*".to_string())).collect();
+        cells.push((gate.target, boxed(&inner, condition)));
+        cells
+    };
+    let controls: Vec<usize> = gate.control.into_iter().collect();
+    let every = |text: String| gate.qubits().into_iter().map(|q| (q, text.clone())).collect();
+    let cells = match &gate.gate_type {
+        GateType::IF(bit, value, body) => {
+            let condition = format!(" if c{}={}", bit, *value as u8);
+            // Every body gate waits for the measurement it is conditioned on
+            for g in body {
+                elements(g, None, n_qubits, &condition, out);
+            }
+            return;
+        }
+        GateType::SNAPSHOT(_) => (0..n_qubits).map(|q| (q, "#".to_string())).collect(),
+        GateType::RESTORE(_) => (0..n_qubits).map(|q| (q, "<".to_string())).collect(),
+        GateType::CNOT => controlled(controls, "X".to_string()),
+        GateType::CH => controlled(controls, "H".to_string()),
+        GateType::CRX(a) => controlled(controls, label(&GateType::RX(*a), param)),
+        GateType::CRY(a) => controlled(controls, label(&GateType::RY(*a), param)),
+        GateType::CRZ(a) => controlled(controls, label(&GateType::RZ(*a), param)),
+        GateType::CPHASE(a) => controlled(controls, label(&GateType::P(*a), param)),
+        GateType::MCX(cs) => controlled(cs.clone(), "X".to_string()),
+        GateType::CONTROLLED(cs, inner) => controlled(cs.clone(), label(inner, param)),
+        GateType::SWAP(_) if condition.is_empty() => every("x".to_string()),
+        GateType::RXX(_, a) => every(boxed(&format!("RXX({})", angle(*a, param)), condition)),
+        GateType::RYY(_, a) => every(boxed(&format!("RYY({})", angle(*a, param)), condition)),
+        GateType::RZZ(_, a) => every(boxed(&format!("RZZ({})", angle(*a, param)), condition)),
+        GateType::MEASURE(bit) => vec![(gate.target, boxed(&format!("M->c{}", bit), condition))],
+        GateType::RESET => vec![(gate.target, boxed("|0>", condition))],
+        GateType::SWAP(_) | GateType::ISWAP(_) | GateType::UNITARY(..) => every(boxed(&label(&gate.gate_type, None), condition)),
+        other => vec![(gate.target, boxed(&label(other, param), condition))],
+    };
+    let low = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let high = cells.iter().map(|c| c.0).max().unwrap_or(0);
+    let classical = !condition.is_empty() || matches!(gate.gate_type, GateType::MEASURE(_));
+    out.push(Element { cells, low, high, classical });
+}
+
+/// `text` centred in `width` columns, padded with `fill`
+fn centred(text: &str, width: usize, fill: char) -> String {
+    let len = text.chars().count();
+    let left = (width - len) / 2;
+    let right = width - len - left;
+    let pad = |k: usize| fill.to_string().repeat(k);
+    format!("{}{}{}", pad(left), text, pad(right))
+}
+
+pub fn draw(circuit: &QuantumCircuit) -> String {
+    let n = circuit.qubits;
+    if n == 0 {
+        return String::new();
+    }
+    let params: HashMap<usize, &ParamRef> = circuit.params.iter().map(|p| (p.gate, p)).collect();
+    let mut drawn = Vec::new();
+    for (i, gate) in circuit.gates.iter().enumerate() {
+        elements(gate, params.get(&i).copied(), n, "", &mut drawn);
+    }
+
+    // Pack into layers
+    let mut next_free = vec![0usize; n];
+    let mut classical_free = 0;
+    let mut layers: Vec<Vec<Element>> = Vec::new();
+    for element in drawn {
+        let mut layer = next_free[element.low..=element.high].iter().copied().max().unwrap_or(0);
+        if element.classical {
+            layer = layer.max(classical_free);
+            classical_free = layer + 1;
+        }
+        next_free[element.low..=element.high].iter_mut().for_each(|f| *f = layer + 1);
+        if layer == layers.len() {
+            layers.push(Vec::new());
+        }
+        layers[layer].push(element);
+    }
+
+    // Rows 2q are wires and rows 2q + 1 the connectors below them
+    let prefix_width = format!("q{}: ", n - 1).len();
+    let mut rows: Vec<String> = (0..2 * n - 1)
+        .map(|r| if r % 2 == 0 { format!("{:>w$}", format!("q{}: ", r / 2), w = prefix_width) } else { " ".repeat(prefix_width) })
+        .collect();
+    for layer in &layers {
+        let width = layer.iter().flat_map(|e| e.cells.iter().map(|c| c.1.chars().count())).max().unwrap_or(1);
+        for (r, row) in rows.iter_mut().enumerate() {
+            let q = r / 2;
+            let (text, fill) = if r % 2 == 0 {
+                let cell = layer.iter().find_map(|e| e.cells.iter().find(|c| c.0 == q).map(|c| c.1.clone()));
+                let crossed = layer.iter().any(|e| e.low < q && q < e.high);
+                (cell.unwrap_or_else(|| if crossed { "|".to_string() } else { String::new() }), '-')
+            } else {
+                let linked = layer.iter().any(|e| e.low <= q && q < e.high);
+                (if linked { "|".to_string() } else { String::new() }, ' ')
+            };
+            row.push(fill);
+            row.push_str(&centred(&text, width, fill));
+        }
+    }
+    for (r, row) in rows.iter_mut().enumerate() {
+        if r % 2 == 0 {
+            row.push('-');
+        }
+    }
+    rows.iter().map(|r| r.trim_end()).collect::<Vec<_>>().join("\n")
+}
@@ -0,0 +1,177 @@
+//! Mid-circuit measurement, reset and classically conditioned gates.
+//!
+//! Circuits containing these instructions no longer map to a single final state,
+//! so they run one shot at a time: every measurement draws its outcome from a
+//! seeded RNG, collapses and renormalizes the state, and records the bit in a
+//! classical register that later `c_if` blocks read. Snapshots save a copy of
+//! the amplitudes in the register too, which a later `restore` of the same
+//! label puts back; classical bits keep the values they have at the restore.
+
+use num_complex::{Complex, Complex64};
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::kernels::{self, Real};
+use crate::{format_bitstring, rng, sampling, Gate, GateType, QuantumState};
+
+/// Classical bits recorded during one shot, with the RNG that produced them
+pub struct ClassicalRegister {
+    bits: Vec<bool>,
+    rng: StdRng,
+    /// Amplitudes saved by the latest snapshot of each label, widened so both
+    /// precisions can share the register
+    saved: HashMap<String, Vec<Complex64>>,
+}
+
+impl ClassicalRegister {
+    pub fn new(size: usize, seed: Option<u64>) -> Self {
+        ClassicalRegister {
+            bits: vec![false; size],
+            rng: rng::seeded(seed),
+            saved: HashMap::new(),
+        }
+    }
+
+    /// Bit i of the register is character i
+    pub fn bitstring(&self) -> String {
+        self.bits.iter().map(|&b| if b { '1' } else { '0' }).collect()
+    }
+
+    pub fn read(&self, bit: usize) -> PyResult<bool> {
+        self.bits.get(bit).copied()
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Classical bit index out of range"))
+    }
+
+    /// Store a measurement outcome drawn by another backend
+    pub fn write(&mut self, bit: usize, value: bool) -> PyResult<()> {
+        self.read(bit)?;
+        self.bits[bit] = value;
+        Ok(())
+    }
+
+    /// Uniform draw in [0, 1) that decides the next measurement outcome
+    pub fn draw(&mut self) -> f64 {
+        self.rng.gen()
+    }
+
+    fn save<T: Real>(&mut self, label: &str, state: &[Complex<T>]) {
+        let widened = state.iter()
+            .map(|c| Complex64::new(c.re.to_f64().unwrap_or(0.0), c.im.to_f64().unwrap_or(0.0)))
+            .collect();
+        self.saved.insert(label.to_string(), widened);
+    }
+
+    fn restore<T: Real>(&self, label: &str, state: &mut [Complex<T>]) -> PyResult<()> {
+        let saved = self.saved.get(label).ok_or_else(|| pyo3::exceptions::PyValueError::new_err(format!(
+            "restore({:?}) ran before any snapshot with that label", label)))?;
+        if saved.len() != state.len() {
+            return Err(crate::errors::DimensionMismatchError::new_err(format!(
+                "Snapshot {:?} has {} amplitudes but the state has {}", label, saved.len(), state.len())));
+        }
+        for (c, s) in state.iter_mut().zip(saved) {
+            *c = Complex::new(T::from(s.re).unwrap_or_else(T::zero), T::from(s.im).unwrap_or_else(T::zero));
+        }
+        Ok(())
+    }
+}
+
+/// Error raised when a measurement, reset, c_if or restore reaches a unitary-only execution path
+pub fn unsupported() -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(
+        "Mid-circuit measurement, reset, c_if and restore are only supported by execute and run")
+}
+
+/// True when any instruction depends on (or produces) a measurement outcome,
+/// or replaces the state with a snapshot
+pub fn is_dynamic(gates: &[Gate]) -> bool {
+    gates.iter().any(|g| matches!(g.gate_type, GateType::MEASURE(_) | GateType::RESET | GateType::IF(..) | GateType::RESTORE(_)))
+}
+
+/// Z-basis measurement of `qubit` with outcome 1 when `r` < P(1); collapses
+/// and renormalizes the state and returns the outcome
+fn collapse<T: Real>(state: &mut [Complex<T>], qubit: usize, r: f64) -> bool {
+    let mask = 1 << qubit;
+    let p1: f64 = state.par_iter().enumerate()
+        .filter(|(i, _)| i & mask != 0)
+        .map(|(_, c)| c.norm_sqr().to_f64().unwrap_or(0.0))
+        .sum();
+    let outcome = r < p1;
+    let p = if outcome { p1 } else { 1.0 - p1 };
+    let scale = T::from(1.0 / p.max(1e-300).sqrt()).unwrap_or_else(T::one);
+    state.par_iter_mut().enumerate().for_each(|(i, c)| {
+        if (i & mask != 0) == outcome {
+            *c *= scale;
+        } else {
+            *c = Complex::new(T::zero(), T::zero());
+        }
+    });
+    outcome
+}
+
+/// Run `gates` on an `n`-qubit state, sampling measurements into `creg`
+pub fn run<T: Real>(state: &mut [Complex<T>], n: usize, gates: &[Gate], creg: &mut ClassicalRegister) -> PyResult<()> {
+    for gate in gates {
+        match &gate.gate_type {
+            GateType::MEASURE(_) | GateType::RESET if gate.target >= n => {
+                return Err(crate::errors::QubitIndexError::new_err("Qubit index out of range"));
+            }
+            GateType::MEASURE(bit) => {
+                creg.read(*bit)?;
+                let r = creg.rng.gen::<f64>();
+                creg.bits[*bit] = collapse(state, gate.target, r);
+            }
+            GateType::RESET => {
+                let r = creg.rng.gen::<f64>();
+                if collapse(state, gate.target, r) {
+                    let x = kernels::matrix::<T>(&GateType::X).unwrap();
+                    kernels::apply_1q(state, gate.target, x, 0);
+                }
+            }
+            GateType::IF(bit, value, body) => {
+                if creg.read(*bit)? == *value {
+                    run(state, n, body, creg)?;
+                }
+            }
+            GateType::SNAPSHOT(label) => creg.save(label, state),
+            GateType::RESTORE(label) => creg.restore(label, state)?,
+            _ => {
+                kernels::validate(gate, n)?;
+                kernels::apply(state, gate, false);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Counts over `shots` independent runs, keyed by the classical register
+/// (or by a final measurement of every qubit when the circuit has no classical bits)
+pub fn counts(initial: &QuantumState, clbits: usize, gates: &[Gate], shots: usize, seed: Option<u64>) -> PyResult<HashMap<String, usize>> {
+    let qubits = initial.qubits;
+    let base_seed = rng::resolve(seed);
+    (0..shots).into_par_iter()
+        .map(|s| -> PyResult<String> {
+            let shot_seed = rng::derive(base_seed, s as u64);
+            let mut creg = ClassicalRegister::new(clbits, Some(shot_seed));
+            let mut state = initial.clone();
+            run(&mut state.state, qubits, gates, &mut creg)?;
+            if clbits > 0 {
+                return Ok(creg.bitstring());
+            }
+            let probs: Vec<f32> = state.state.iter().map(|c| c.norm_sqr()).collect();
+            let idx = sampling::sample_counts(&probs, 1, Some(creg.rng.gen())).into_keys().next().unwrap_or(0);
+            Ok(format_bitstring(idx, qubits))
+        })
+        .try_fold(HashMap::new, |mut acc, key| {
+            *acc.entry(key?).or_insert(0) += 1;
+            Ok(acc)
+        })
+        .try_reduce(HashMap::new, |mut a, b| {
+            for (k, v) in b {
+                *a.entry(k).or_insert(0) += v;
+            }
+            Ok(a)
+        })
+}
@@ -0,0 +1,155 @@
+//! Affective readout of a quantum state, in one place.
+//!
+//! With n qubits, d = 2^n, rho the density matrix (|psi><psi| for pure states)
+//! and r_q = (<X_q>, <Y_q>, <Z_q>) the Bloch vector of qubit q:
+//!
+//! | metric    | formula                                         | range   |
+//! |-----------|-------------------------------------------------|---------|
+//! | energy    | 1 - rho_00                                      | [0, 1]  |
+//! | entropy   | -sum_i rho_ii log2 rho_ii / n                   | [0, 1]  |
+//! | valence   | mean_q <Z_q>                                    | [-1, 1] |
+//! | arousal   | mean_q sqrt(<X_q>^2 + <Y_q>^2)                  | [0, 1]  |
+//! | coherence | sum_{i != j} abs(rho_ij) / (d - 1)              | [0, 1]  |
+//! | purity    | (d Tr(rho^2) - 1) / (d - 1)                     | [0, 1]  |
+//! | stability | purity * (1 - entropy)                          | [0, 1]  |
+//!
+//! Valence follows the longitudinal (comfort) axis of the emotional
+//! Hamiltonian: qubits relaxed to |0> pull it up, excited qubits pull it down.
+//! Arousal is the transverse (stimulation) component. Amplitude damping
+//! ("depression") drives valence towards +1 and arousal to 0; phase damping
+//! ("anxiety") leaves valence alone but erodes arousal, coherence and
+//! stability. Energy, entropy and resonance are the values of the per-class
+//! `expectation_value`, `entropy` (for state vectors) and `resonance` methods.
+
+use pyo3::prelude::*;
+
+use crate::{pauli, DensityMatrix, QuantumState};
+
+#[pyclass]
+#[derive(Clone)]
+pub struct EmotionalProfile {
+    #[pyo3(get)]
+    qubits: usize,
+    #[pyo3(get)]
+    valence: f32,
+    #[pyo3(get)]
+    arousal: f32,
+    #[pyo3(get)]
+    coherence: f32,
+    #[pyo3(get)]
+    stability: f32,
+    #[pyo3(get)]
+    purity: f32,
+    #[pyo3(get)]
+    energy: f32,
+    #[pyo3(get)]
+    entropy: f32,
+    #[pyo3(get)]
+    resonance: Vec<f32>,
+}
+
+/// Raw ingredients shared by both state types
+struct Parts {
+    qubits: usize,
+    populations: Vec<f32>,
+    bloch: Vec<(f32, f32, f32)>,
+    /// sum over i != j of |rho_ij|
+    off_diagonal: f32,
+    /// Tr(rho^2)
+    purity: f32,
+    resonance: Vec<f32>,
+}
+
+#[pymethods]
+impl EmotionalProfile {
+    /// Profile of a QuantumState or DensityMatrix
+    #[new]
+    pub fn new(py: Python, state: &PyAny) -> PyResult<Self> {
+        if let Ok(state) = state.extract::<PyRef<QuantumState>>() {
+            let state: &QuantumState = &state;
+            return Ok(py.allow_threads(|| Self::from_state(state)));
+        }
+        match state.extract::<PyRef<DensityMatrix>>() {
+            Ok(rho) => {
+                let rho: &DensityMatrix = &rho;
+                Ok(py.allow_threads(|| Self::from_density(rho)))
+            }
+            Err(_) => Err(pyo3::exceptions::PyTypeError::new_err(
+                "EmotionalProfile expects a QuantumState or a DensityMatrix")),
+        }
+    }
+
+    /// All metrics as a plain dictionary
+    pub fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new(py);
+        dict.set_item("valence", self.valence)?;
+        dict.set_item("arousal", self.arousal)?;
+        dict.set_item("coherence", self.coherence)?;
+        dict.set_item("stability", self.stability)?;
+        dict.set_item("purity", self.purity)?;
+        dict.set_item("energy", self.energy)?;
+        dict.set_item("entropy", self.entropy)?;
+        dict.set_item("resonance", self.resonance.clone())?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "EmotionalProfile(valence={:.3}, arousal={:.3}, coherence={:.3}, stability={:.3})",
+            self.valence, self.arousal, self.coherence, self.stability)
+    }
+}
+
+impl EmotionalProfile {
+    pub fn from_state(state: &QuantumState) -> Self {
+        Self::from_parts(Parts {
+            qubits: state.qubits,
+            populations: state.state.iter().map(|c| c.norm_sqr()).collect(),
+            bloch: state.bloch_vectors(),
+            off_diagonal: state.l1_coherence(),
+            purity: state.purity(),
+            resonance: state.calculate_resonance(),
+        })
+    }
+
+    pub fn from_density(rho: &DensityMatrix) -> Self {
+        let n = rho.qubits;
+        let dim = 1 << n;
+        let e = |x: usize, z: usize| pauli::expectation_density(&rho.matrix, dim, pauli::PauliMasks::new(x, z));
+        Self::from_parts(Parts {
+            qubits: n,
+            populations: rho.populations(),
+            bloch: (0..n).map(|q| (e(1 << q, 0), e(1 << q, 1 << q), e(0, 1 << q))).collect(),
+            off_diagonal: rho.l1_coherence(),
+            purity: rho.purity(),
+            resonance: rho.resonance(),
+        })
+    }
+
+    fn from_parts(parts: Parts) -> Self {
+        let n = parts.qubits.max(1) as f32;
+        let d = parts.populations.len() as f32;
+        // Zero-qubit states are trivially incoherent and fully pure
+        let span = (d - 1.0).max(1.0);
+        let entropy = -parts.populations.iter()
+            .filter(|&&p| p > 1e-9)
+            .map(|&p| p * p.log2())
+            .sum::<f32>() / n;
+        let valence = parts.bloch.iter().map(|b| b.2).sum::<f32>() / n;
+        let arousal = parts.bloch.iter().map(|b| b.0.hypot(b.1)).sum::<f32>() / n;
+        let coherence = (parts.off_diagonal / span).clamp(0.0, 1.0);
+        let purity = if d > 1.0 { ((d * parts.purity - 1.0) / span).clamp(0.0, 1.0) } else { 1.0 };
+        let entropy = entropy.clamp(0.0, 1.0);
+        EmotionalProfile {
+            qubits: parts.qubits,
+            valence,
+            arousal,
+            coherence,
+            stability: purity * (1.0 - entropy),
+            purity,
+            energy: 1.0 - parts.populations[0],
+            entropy,
+            resonance: parts.resonance,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Exception types raised by the engine.
+//!
+//! Every engine-specific failure derives from `QuantumEngineError`, which is
+//! itself a `ValueError`, so existing `except ValueError` handlers keep working
+//! while callers that care can catch simulator errors separately from their own.
+//! `QubitIndexError` is an `IndexError` as well.
+//! Argument errors that are not about qubits, gates or shapes (unknown option
+//! names, non-positive counts, ...) stay plain `ValueError`s.
+
+// pyo3 0.19's create_exception! expansion checks a cfg newer rustc does not know
+#![allow(unexpected_cfgs)]
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+use pyo3::AsPyPointer;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyTuple, PyType};
+
+create_exception!(quantum_engine, QuantumEngineError, pyo3::exceptions::PyValueError,
+    "Base class of the errors raised by the simulator.");
+create_exception!(quantum_engine, InvalidGateError, QuantumEngineError,
+    "A gate is unknown, badly parameterized or acts on repeated qubits.");
+create_exception!(quantum_engine, DimensionMismatchError, QuantumEngineError,
+    "Operands have incompatible qubit counts or matrix shapes.");
+
+/// A qubit index is outside the circuit or state.
+///
+/// Spelled out instead of going through create_exception!, which allows a
+/// single base: this one is also an `IndexError`, so `except IndexError`
+/// handlers written for list-style indexing catch it too.
+#[repr(transparent)]
+pub struct QubitIndexError(PyAny);
+
+pyo3::impl_exception_boilerplate!(QubitIndexError);
+pyo3::pyobject_native_type_core!(
+    QubitIndexError,
+    *QubitIndexError::type_object_raw(Python::assume_gil_acquired()),
+    #module=Some("quantum_engine")
+);
+
+impl QubitIndexError {
+    fn type_object_raw(py: Python<'_>) -> *mut pyo3::ffi::PyTypeObject {
+        static TYPE_OBJECT: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+        TYPE_OBJECT
+            .get_or_init(py, || {
+                // type(name, bases, namespace), as a class statement would build it
+                let bases = PyTuple::new(py, [py.get_type::<QuantumEngineError>(), py.get_type::<PyIndexError>()]);
+                let namespace = PyDict::new(py);
+                namespace.set_item("__module__", "quantum_engine").expect("Failed to build exception namespace");
+                namespace.set_item("__doc__", "A qubit index is outside the circuit or state.")
+                    .expect("Failed to build exception namespace");
+                py.get_type::<PyType>()
+                    .call1(("QubitIndexError", bases, namespace))
+                    .and_then(|t| Ok(t.downcast::<PyType>()?.into()))
+                    .expect("Failed to initialize new exception type.")
+            })
+            .as_ptr() as *mut pyo3::ffi::PyTypeObject
+    }
+}
+pub fn register(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("QuantumEngineError", py.get_type::<QuantumEngineError>())?;
+    m.add("QubitIndexError", py.get_type::<QubitIndexError>())?;
+    m.add("InvalidGateError", py.get_type::<InvalidGateError>())?;
+    m.add("DimensionMismatchError", py.get_type::<DimensionMismatchError>())?;
+    Ok(())
+}
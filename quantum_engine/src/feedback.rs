@@ -0,0 +1,114 @@
+//! Closed-loop execution for `QuantumCircuit.execute_feedback` ("echo feedback").
+//!
+//! The circuit runs in blocks of `every` gates with the GIL released; after
+//! each block the probes are evaluated, still without the GIL, and only then is
+//! it taken back to call the Python callback with the gate count and readings.
+//! The callback steers the rest of the run through its return value: a
+//! QuantumCircuit whose gates run next, a {channel: strength} dict of noise
+//! channels applied once to every qubit, both as a (circuit, dict) pair, or
+//! None. Noise on the pure state is one stochastic Kraus jump per qubit, as in
+//! `execute_trajectories`, so a noisy run is a single trajectory. Requested
+//! gates run before requested noise.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::noise::NoiseChannel;
+use crate::trace::Probe;
+use crate::{dynamic, rng, rz_phase, trajectories, Gate, QuantumCircuit, QuantumState};
+
+/// Kraus operators of each channel a callback asked for
+type Noise = Vec<Vec<[[Complex32; 2]; 2]>>;
+
+/// Gates and noise requested by one callback return value
+fn parse_reply(reply: &PyAny, circuit: &QuantumCircuit) -> PyResult<(Vec<Gate>, Noise)> {
+    if reply.is_none() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    if let Ok(pair) = reply.downcast::<PyTuple>() {
+        if pair.len() != 2 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "A feedback tuple must be (QuantumCircuit, {channel: strength})"));
+        }
+        let (gates, _) = parse_reply(pair.get_item(0)?, circuit)?;
+        let (_, noise) = parse_reply(pair.get_item(1)?, circuit)?;
+        return Ok((gates, noise));
+    }
+    if let Ok(channels) = reply.downcast::<PyDict>() {
+        let mut noise = Vec::new();
+        for (name, strength) in channels {
+            let (name, strength): (&str, f32) = (name.extract()?, strength.extract()?);
+            if !(0.0..=1.0).contains(&strength) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Noise strength {} for '{}' is outside [0, 1]", strength, name)));
+            }
+            noise.push(NoiseChannel::from_name(name, strength)?.kraus());
+        }
+        return Ok((Vec::new(), noise));
+    }
+    let Ok(extra) = reply.extract::<PyRef<QuantumCircuit>>() else {
+        return Err(pyo3::exceptions::PyTypeError::new_err(
+            "The feedback callback must return None, a QuantumCircuit, a {channel: strength} dict or a (circuit, dict) tuple"));
+    };
+    if extra.qubits > circuit.qubits || extra.clbits > circuit.clbits {
+        return Err(crate::errors::DimensionMismatchError::new_err(format!(
+            "Feedback circuit has {} qubits and {} clbits; the running circuit has {} and {}",
+            extra.qubits, extra.clbits, circuit.qubits, circuit.clbits)));
+    }
+    Ok((extra.bound_gates()?.to_vec(), Vec::new()))
+}
+
+/// Run a callback's gates, then its noise channels on every qubit
+fn apply(state: &mut QuantumState, gates: &[Gate], noise: &Noise, creg: &mut dynamic::ClassicalRegister, rng: &mut StdRng)
+    -> PyResult<()> {
+    dynamic::run(&mut state.state, state.qubits, gates, creg)?;
+    for kraus in noise {
+        for q in 0..state.qubits {
+            trajectories::apply_random_kraus(state, q, kraus, rng);
+        }
+    }
+    Ok(())
+}
+
+/// Run `circuit` calling `callback(gates_done, readings)` after every `every`
+/// gates and after the last, applying whatever each call returns before
+/// continuing. Returns the final state.
+pub fn run(py: Python, circuit: &QuantumCircuit, callback: &PyAny, every: usize, probes: &[Probe], seed: Option<u64>)
+    -> PyResult<QuantumState> {
+    if every == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("every must be positive"));
+    }
+    let gates = circuit.bound_gates()?;
+    let base = rng::resolve(seed);
+    let mut creg = dynamic::ClassicalRegister::new(circuit.clbits, Some(base));
+    // Noise jumps draw from their own stream so they do not shift measurement outcomes
+    let mut noise_rng = StdRng::seed_from_u64(rng::derive(base, 1));
+    let mut state = circuit.initial_state()?;
+    // Every gate run, circuit and feedback alike, for the global-phase bookkeeping
+    let mut applied: Vec<Gate> = Vec::with_capacity(gates.len());
+    let (mut pending, mut noise): (Vec<Gate>, Noise) = (Vec::new(), Vec::new());
+    let mut done = 0;
+
+    for block in gates.chunks(every) {
+        let readings = py.allow_threads(|| -> PyResult<_> {
+            apply(&mut state, &pending, &noise, &mut creg, &mut noise_rng)?;
+            dynamic::run(&mut state.state, circuit.qubits, block, &mut creg)?;
+            Ok(probes.iter().map(|p| p.read(&state)).collect::<Vec<_>>())
+        })?;
+        applied.append(&mut pending);
+        applied.extend_from_slice(block);
+        done += block.len();
+
+        let readings: Vec<PyObject> = readings.into_iter().map(|r| r.into_py(py)).collect();
+        (pending, noise) = parse_reply(callback.call1((done, readings))?, circuit)?;
+    }
+
+    // What the last call asked for still runs
+    py.allow_threads(|| apply(&mut state, &pending, &noise, &mut creg, &mut noise_rng))?;
+    applied.append(&mut pending);
+    state.phase += rz_phase(&applied);
+    Ok(state)
+}
@@ -0,0 +1,352 @@
+//! wgpu compute backend for `QuantumCircuit.execute(device="gpu")`, compiled
+//! with the `gpu` cargo feature.
+//!
+//! The state vector lives in one storage buffer of (re, im) f32 pairs and
+//! every gate is a compute dispatch over it: single-qubit and controlled
+//! single-qubit gates (CNOT, CRZ, MCX, CONTROLLED, ...) run the `single`
+//! butterfly, skipping pairs whose control bits are not all set, and the
+//! remaining two-qubit gates (SWAP, iSWAP, RXX/RYY/RZZ, two-qubit UNITARY) run
+//! `pair` with their dense 4x4 matrix. Each gate's parameters sit at their own
+//! dynamic offset of a uniform buffer, so a whole chunk of gates is one
+//! submission and the state is only copied back to the host at the end.
+//!
+//! The adapter is picked once per process (honouring WGPU_BACKEND and
+//! WGPU_POWER_PREF) and requested with its full limits; the largest state is
+//! the adapter's max_storage_buffer_binding_size. Gates are fused at
+//! `transpile_level` first, as on the CPU. Mid-circuit measurement, reset,
+//! c_if, restore and gates on more than two qubits that are not controlled
+//! single-qubit gates are rejected.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use std::sync::OnceLock;
+
+use crate::{dynamic, errors, introspect, kernels, mps, rz_phase, transpile, Gate, GateType, QuantumCircuit, QuantumState};
+
+const SHADER: &str = r#"
+struct Op {
+    // single: target, unused, control mask, butterflies; pair: first, second, control mask, groups
+    qubits: vec4<u32>,
+    // x: invocations per dispatch row
+    grid: vec4<u32>,
+    // Row-major 4x4 complex matrix, two entries per vec4; `single` reads the top-left 2x2
+    m: array<vec4<f32>, 8>,
+}
+
+@group(0) @binding(0) var<storage, read_write> amps: array<vec2<f32>>;
+@group(0) @binding(1) var<uniform> op: Op;
+
+fn cmul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+fn entry(r: u32, c: u32) -> vec2<f32> {
+    let v = op.m[r * 2u + c / 2u];
+    return select(v.zw, v.xy, c % 2u == 0u);
+}
+
+// k with a zero bit inserted at position `bit`
+fn insert_zero(k: u32, bit: u32) -> u32 {
+    return ((k >> bit) << (bit + 1u)) | (k & ((1u << bit) - 1u));
+}
+
+@compute @workgroup_size(256)
+fn single(@builtin(global_invocation_id) id: vec3<u32>) {
+    let k = id.x + id.y * op.grid.x;
+    if (k >= op.qubits.w) {
+        return;
+    }
+    let t = op.qubits.x;
+    let i = insert_zero(k, t);
+    if ((i & op.qubits.z) != op.qubits.z) {
+        return;
+    }
+    let j = i | (1u << t);
+    let a = amps[i];
+    let b = amps[j];
+    amps[i] = cmul(entry(0u, 0u), a) + cmul(entry(0u, 1u), b);
+    amps[j] = cmul(entry(1u, 0u), a) + cmul(entry(1u, 1u), b);
+}
+
+@compute @workgroup_size(256)
+fn pair(@builtin(global_invocation_id) id: vec3<u32>) {
+    let k = id.x + id.y * op.grid.x;
+    if (k >= op.qubits.w) {
+        return;
+    }
+    let qa = op.qubits.x;
+    let qb = op.qubits.y;
+    let base = insert_zero(insert_zero(k, min(qa, qb)), max(qa, qb));
+    if ((base & op.qubits.z) != op.qubits.z) {
+        return;
+    }
+    var idx: array<u32, 4>;
+    var v: array<vec2<f32>, 4>;
+    for (var l = 0u; l < 4u; l++) {
+        idx[l] = base | select(0u, 1u << qa, (l & 1u) != 0u) | select(0u, 1u << qb, (l & 2u) != 0u);
+        v[l] = amps[idx[l]];
+    }
+    for (var r = 0u; r < 4u; r++) {
+        var sum = vec2<f32>(0.0, 0.0);
+        for (var c = 0u; c < 4u; c++) {
+            sum += cmul(entry(r, c), v[c]);
+        }
+        amps[idx[r]] = sum;
+    }
+}
+"#;
+
+const WORKGROUP: u64 = 256;
+/// Gates encoded per submission
+const CHUNK: usize = 1024;
+
+/// Uniform block of one dispatch, laid out as `Op` in the shader
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    qubits: [u32; 4],
+    grid: [u32; 4],
+    m: [[f32; 4]; 8],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kernel {
+    Single,
+    Pair,
+}
+
+/// One dispatch: which kernel and its parameters, less the dispatch grid
+struct Op {
+    kernel: Kernel,
+    params: Params,
+}
+
+impl Op {
+    /// `matrix` is dim x dim row-major, dim = 2 for `single` and 4 for `pair`
+    fn new(kernel: Kernel, qubits: [u32; 2], controls: u32, matrix: &[Complex32]) -> Op {
+        let dim = if kernel == Kernel::Single { 2 } else { 4 };
+        let mut m = [[0.0; 4]; 8];
+        for (i, c) in matrix.iter().enumerate() {
+            let (r, col) = (i / dim, i % dim);
+            let slot = &mut m[r * 2 + col / 2];
+            slot[2 * (col % 2)] = c.re;
+            slot[2 * (col % 2) + 1] = c.im;
+        }
+        Op { kernel, params: Params { qubits: [qubits[0], qubits[1], controls, 0], grid: [0; 4], m } }
+    }
+}
+
+/// The dispatch running `gate`, None for the ones that do nothing to the state
+fn lower(gate: &Gate) -> PyResult<Option<Op>> {
+    if let GateType::SNAPSHOT(_) = gate.gate_type {
+        return Ok(None);
+    }
+    let qubits = gate.qubits();
+    if let Some(m) = kernels::matrix::<f32>(&gate.gate_type) {
+        let controls = qubits.iter().filter(|&&q| q != gate.target).fold(0u32, |mask, &q| mask | (1 << q));
+        let flat = [m[0][0], m[0][1], m[1][0], m[1][1]];
+        return Ok(Some(Op::new(Kernel::Single, [gate.target as u32, 0], controls, &flat)));
+    }
+    let kernel = match qubits.len() {
+        1 => Kernel::Single,
+        2 => Kernel::Pair,
+        n => return Err(errors::InvalidGateError::new_err(format!(
+            "{}: the GPU backend runs gates on at most two qubits, got {}", introspect::name(&gate.gate_type), n))),
+    };
+    let matrix: Vec<Complex32> = mps::local_matrix(gate, &qubits)?.iter()
+        .map(|c| Complex32::new(c.re as f32, c.im as f32))
+        .collect();
+    let second = qubits.get(1).copied().unwrap_or(0) as u32;
+    Ok(Some(Op::new(kernel, [qubits[0] as u32, second], 0, &matrix)))
+}
+
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    single: wgpu::ComputePipeline,
+    pair: wgpu::ComputePipeline,
+    limits: wgpu::Limits,
+    name: String,
+}
+
+/// The process-wide device, created on first use
+fn context() -> PyResult<&'static Gpu> {
+    static GPU: OnceLock<Result<Gpu, String>> = OnceLock::new();
+    GPU.get_or_init(|| pollster::block_on(Gpu::new()))
+        .as_ref()
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("GPU execution: {}", e)))
+}
+
+impl Gpu {
+    async fn new() -> Result<Gpu, String> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle_from_env());
+        let options = wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::from_env().unwrap_or(wgpu::PowerPreference::HighPerformance),
+            ..Default::default()
+        };
+        let adapter = instance.request_adapter(&options).await.map_err(|e| format!("no adapter found ({})", e))?;
+        let limits = adapter.limits();
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("quantum_engine"),
+                required_limits: limits.clone(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("quantum_engine kernels"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let buffer = |ty, dynamic| wgpu::BindingType::Buffer { ty, has_dynamic_offset: dynamic, min_binding_size: None };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: buffer(wgpu::BufferBindingType::Storage { read_only: false }, false),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: buffer(wgpu::BufferBindingType::Uniform, true),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[Some(&layout)],
+            immediate_size: 0,
+        });
+        let pipeline = |entry| device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(entry),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some(entry),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let (single, pair) = (pipeline("single"), pipeline("pair"));
+        Ok(Gpu { name: adapter.get_info().name, device, queue, layout, single, pair, limits })
+    }
+
+    /// Run `ops` on the `qubits`-qubit state vector `state` in place
+    fn run(&self, state: &mut [Complex32], qubits: usize, ops: &mut [Op]) -> PyResult<()> {
+        let bytes = std::mem::size_of_val(state) as u64;
+        let max = self.limits.max_storage_buffer_binding_size.min(self.limits.max_buffer_size);
+        if bytes > max {
+            return Err(pyo3::exceptions::PyMemoryError::new_err(format!(
+                "A {}-qubit state vector needs {} bytes; {} binds at most {}", qubits, bytes, self.name, max)));
+        }
+        let amps = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("amplitudes"),
+            size: bytes,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        self.queue.write_buffer(&amps, 0, bytemuck::cast_slice(state));
+
+        let stride = (std::mem::size_of::<Params>() as u64).next_multiple_of(self.limits.min_uniform_buffer_offset_alignment as u64);
+        let uniforms = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gate parameters"),
+            size: stride * CHUNK.min(ops.len()).max(1) as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: amps.as_entire_binding() },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &uniforms,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<Params>() as u64),
+                    }),
+                },
+            ],
+        });
+
+        for chunk in ops.chunks_mut(CHUNK) {
+            let mut block = vec![0u8; stride as usize * chunk.len()];
+            let mut dispatches = Vec::with_capacity(chunk.len());
+            for (i, op) in chunk.iter_mut().enumerate() {
+                // Amplitude pairs (or quadruples) the kernel visits, spread over
+                // as many dispatch rows as the per-dimension limit requires
+                let work = 1u64 << (qubits - if op.kernel == Kernel::Single { 1 } else { 2 });
+                let groups = work.div_ceil(WORKGROUP);
+                let x = groups.min(self.limits.max_compute_workgroups_per_dimension as u64);
+                op.params.qubits[3] = work as u32;
+                op.params.grid[0] = (x * WORKGROUP) as u32;
+                let offset = i * stride as usize;
+                block[offset..offset + std::mem::size_of::<Params>()].copy_from_slice(bytemuck::bytes_of(&op.params));
+                dispatches.push((op.kernel, offset as u32, x as u32, groups.div_ceil(x) as u32));
+            }
+            self.queue.write_buffer(&uniforms, 0, &block);
+
+            let mut encoder = self.device.create_command_encoder(&Default::default());
+            {
+                let mut pass = encoder.begin_compute_pass(&Default::default());
+                for (kernel, offset, x, y) in dispatches {
+                    pass.set_pipeline(if kernel == Kernel::Single { &self.single } else { &self.pair });
+                    pass.set_bind_group(0, &bind_group, &[offset]);
+                    pass.dispatch_workgroups(x, y, 1);
+                }
+            }
+            self.queue.submit([encoder.finish()]);
+        }
+
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: bytes,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(&amps, 0, &staging, 0, bytes);
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        staging.map_async(wgpu::MapMode::Read, .., move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("GPU execution: {}", e)))?;
+        receiver.recv()
+            .map_err(|e| e.to_string())
+            .and_then(|r| r.map_err(|e| e.to_string()))
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("GPU execution: {}", e)))?;
+        let view = staging.get_mapped_range(..)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("GPU execution: {}", e)))?;
+        state.copy_from_slice(bytemuck::cast_slice(&view));
+        Ok(())
+    }
+}
+
+/// Execute `circuit` on the GPU, returning the final state with the same
+/// global-phase bookkeeping as the CPU path
+pub fn run(circuit: &QuantumCircuit, transpile_level: usize) -> PyResult<QuantumState> {
+    let source = circuit.bound_gates()?;
+    if dynamic::is_dynamic(source) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "The GPU backend does not support measurement, reset, c_if or restore"));
+    }
+    let gates = transpile::fuse(source, transpile_level);
+    let mut ops = Vec::with_capacity(gates.len());
+    for gate in &gates {
+        ops.extend(lower(gate)?);
+    }
+    let mut state = circuit.initial_state()?;
+    if !ops.is_empty() {
+        context()?.run(&mut state.state, circuit.qubits, &mut ops)?;
+    }
+    state.phase += rz_phase(source);
+    Ok(state)
+}
@@ -0,0 +1,154 @@
+//! Gradients of Pauli observables with respect to circuit rotation angles.
+//!
+//! Parameter shift: for a rotation exp(-i theta G) with G having eigenvalues +-1/2 (RX, RY, RZ)
+//! dE/dtheta = [E(theta + pi/2) - E(theta - pi/2)] / 2. Controlled rotations have
+//! eigenvalues {0, +-1/2} and need the four-term rule; CPhase (eigenvalues {0, 1})
+//! uses the two-term rule again.
+//!
+//! Adjoint: one forward pass, then a backward sweep that undoes each gate on both
+//! |psi> and |lambda> = O|psi>, reading dE/dtheta_k = 2 Re <lambda|(-iG_k)|psi_k>
+//! off each rotation on the way (G_k the generator of gate k).
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use crate::observables::PauliTerm;
+use crate::params::ParamRef;
+use crate::{kernels, pauli, Gate, GateType, QuantumState};
+
+/// (angle shift, coefficient) pairs whose weighted energies sum to dE/dtheta
+fn shift_rule(gate_type: &GateType) -> Vec<(f32, f32)> {
+    match gate_type {
+        // Controlled phases keep generator eigenvalues {0, 1}, so the two-term rule still holds
+        GateType::CONTROLLED(_, inner) if matches!(**inner, GateType::P(_)) => shift_rule(inner),
+        GateType::CRX(_) | GateType::CRY(_) | GateType::CRZ(_) | GateType::CONTROLLED(..) => {
+            let sqrt2 = 2.0_f32.sqrt();
+            let c_plus = (sqrt2 + 1.0) / (4.0 * sqrt2);
+            let c_minus = (sqrt2 - 1.0) / (4.0 * sqrt2);
+            vec![
+                (FRAC_PI_2, c_plus),
+                (-FRAC_PI_2, -c_plus),
+                (3.0 * FRAC_PI_2, -c_minus),
+                (-3.0 * FRAC_PI_2, c_minus),
+            ]
+        }
+        _ => vec![(FRAC_PI_2, 0.5), (-FRAC_PI_2, -0.5)],
+    }
+}
+
+/// <psi|O|psi> after running `gates` from `initial`
+pub fn energy(initial: &QuantumState, gates: &[Gate], terms: &[PauliTerm]) -> PyResult<f32> {
+    let mut state = initial.clone();
+    for gate in gates {
+        state.apply(gate)?;
+    }
+    Ok(terms.iter().map(|t| t.coeff * pauli::expectation(&state.state, t.masks)).sum())
+}
+
+/// d<O>/d(name) for every name in `names`, summing the contributions of all
+/// gates that share a parameter. Each shifted circuit runs as its own rayon task.
+pub fn parameter_shift(
+    initial: &QuantumState,
+    gates: &[Gate],
+    refs: &[ParamRef],
+    names: &[String],
+    terms: &[PauliTerm],
+) -> PyResult<Vec<f32>> {
+    let evaluations: Vec<(usize, usize, f32, f32)> = refs.iter()
+        .flat_map(|r| {
+            let slot = names.iter().position(|n| n == &r.name).unwrap_or(0);
+            shift_rule(&gates[r.gate].gate_type).into_iter()
+                .map(move |(shift, coeff)| (slot, r.gate, shift, coeff * r.scale))
+        })
+        .collect();
+
+    let contributions = evaluations.par_iter()
+        .map(|&(slot, gate, shift, coeff)| {
+            let mut shifted = gates.to_vec();
+            if let Some(angle) = shifted[gate].gate_type.angle_mut() {
+                *angle += shift;
+            }
+            energy(initial, &shifted, terms).map(|e| (slot, coeff * e))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let mut grads = vec![0.0; names.len()];
+    for (slot, value) in contributions {
+        grads[slot] += value;
+    }
+    Ok(grads)
+}
+
+/// -iG restricted to the target qubit (the controls act as a |1><1| projector)
+fn generator(gate_type: &GateType) -> Option<[[Complex32; 2]; 2]> {
+    let zero = Complex32::new(0.0, 0.0);
+    let half = |re: f32, im: f32| Complex32::new(re / 2.0, im / 2.0);
+    Some(match gate_type {
+        GateType::CONTROLLED(_, inner) => return generator(inner),
+        GateType::RX(_) | GateType::CRX(_) => [[zero, half(0.0, -1.0)], [half(0.0, -1.0), zero]],
+        GateType::RY(_) | GateType::CRY(_) => [[zero, half(-1.0, 0.0)], [half(1.0, 0.0), zero]],
+        GateType::RZ(_) | GateType::CRZ(_) => [[half(0.0, -1.0), zero], [zero, half(0.0, 1.0)]],
+        GateType::P(_) | GateType::CPHASE(_) => [[zero, zero], [zero, Complex32::new(0.0, 1.0)]],
+        _ => return None,
+    })
+}
+
+/// <lambda| (P_controls x M_target) |psi>
+fn generator_overlap(lambda: &[Complex32], psi: &[Complex32], target: usize, m: [[Complex32; 2]; 2], control_mask: usize) -> f64 {
+    let step = 1 << target;
+    (0..psi.len()).into_par_iter()
+        .filter(|&i| i & step == 0 && i & control_mask == control_mask)
+        .map(|i| {
+            let j = i | step;
+            let (a, b) = (psi[i], psi[j]);
+            let v = lambda[i].conj() * (m[0][0] * a + m[0][1] * b) + lambda[j].conj() * (m[1][0] * a + m[1][1] * b);
+            v.re as f64
+        })
+        .sum()
+}
+
+/// O|psi> for a weighted sum of Pauli strings
+fn apply_observable(state: &[Complex32], terms: &[PauliTerm]) -> Vec<Complex32> {
+    let mut out = vec![Complex32::new(0.0, 0.0); state.len()];
+    out.par_iter_mut().enumerate().for_each(|(k, val)| {
+        for t in terms {
+            // P|j> = i^n_y (-1)^|j & z| |j ^ x>, so <k|P|psi> reads amplitude k ^ x
+            let j = k ^ t.masks.x;
+            let sign = if (j & t.masks.z).count_ones() & 1 == 0 { 1.0 } else { -1.0 };
+            *val += t.masks.y_phase::<f32>() * state[j] * (t.coeff * sign);
+        }
+    });
+    out
+}
+
+/// dE/dtheta for every rotation gate, as (gate index, derivative) in gate order
+pub fn adjoint(initial: &QuantumState, gates: &[Gate], terms: &[PauliTerm]) -> PyResult<Vec<(usize, f32)>> {
+    let mut psi = initial.clone();
+    for gate in gates {
+        psi.apply(gate)?;
+    }
+    let mut psi = psi.state;
+    let mut lambda = apply_observable(&psi, terms);
+
+    let mut grads = Vec::new();
+    for (k, gate) in gates.iter().enumerate().rev() {
+        if let Some(m) = generator(&gate.gate_type) {
+            let overlap = generator_overlap(&lambda, &psi, gate.target, m, kernels::control_mask(gate));
+            grads.push((k, (2.0 * overlap) as f32));
+        } else if let GateType::RXX(..) | GateType::RYY(..) | GateType::RZZ(..) = gate.gate_type {
+            // exp(-i pi/2 P x P) = -i P x P, twice the generator -i/2 P x P
+            let mut half_turn = gate.clone();
+            *half_turn.gate_type.angle_mut().unwrap() = PI;
+            let mut rotated = psi.clone();
+            kernels::apply(&mut rotated, &half_turn, false);
+            let overlap: f64 = lambda.par_iter().zip(rotated.par_iter()).map(|(l, r)| (l.conj() * r).re as f64).sum();
+            grads.push((k, overlap as f32));
+        }
+        kernels::apply_dagger(&mut psi, gate);
+        kernels::apply_dagger(&mut lambda, gate);
+    }
+    grads.reverse();
+    Ok(grads)
+}
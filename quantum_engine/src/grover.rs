@@ -0,0 +1,156 @@
+//! Grover search: repeated phase oracle + diffusion over a uniform superposition.
+//!
+//! Bitstring oracles mark basis states with X-conjugated multi-controlled Z
+//! gates; circuit oracles are taken as given and must already flip the phase of
+//! the marked states. The diffusion operator is H^n X^n MCZ X^n H^n, which is
+//! 2|s><s| - I up to a global phase.
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::{dynamic, parse_bitstring, QuantumCircuit};
+
+/// One Grover iteration (oracle followed by diffusion) on `qubits` qubits
+#[pyclass]
+#[derive(Clone)]
+pub struct GroverOperator {
+    #[pyo3(get)]
+    qubits: usize,
+    oracle: QuantumCircuit,
+    /// How many states the oracle marks, when it was built from bitstrings
+    marked: Option<usize>,
+}
+
+#[pymethods]
+impl GroverOperator {
+    /// `oracle` is a marked bitstring (character i is qubit i), a list of
+    /// them, or a QuantumCircuit that flips the phase of the marked states
+    #[new]
+    pub fn new(oracle: &PyAny) -> PyResult<Self> {
+        if let Ok(circuit) = oracle.extract::<QuantumCircuit>() {
+            return Self::from_circuit(circuit);
+        }
+        if let Ok(bits) = oracle.extract::<String>() {
+            return Self::from_bitstrings(&[bits]);
+        }
+        match oracle.extract::<Vec<String>>() {
+            Ok(marked) => Self::from_bitstrings(&marked),
+            Err(_) => Err(pyo3::exceptions::PyTypeError::new_err(
+                "Oracle must be a bitstring, a list of bitstrings or a QuantumCircuit")),
+        }
+    }
+
+    /// Circuit flipping the sign of the marked states
+    pub fn oracle(&self) -> QuantumCircuit {
+        self.oracle.clone()
+    }
+
+    /// Inversion about the mean, 2|s><s| - I up to global phase
+    pub fn diffusion(&self) -> PyResult<QuantumCircuit> {
+        let n = self.qubits;
+        let mut circuit = QuantumCircuit::new(n, 0);
+        for q in 0..n {
+            circuit.h(q)?;
+            circuit.x(q)?;
+        }
+        circuit.mcz((0..n - 1).collect(), n - 1)?;
+        for q in 0..n {
+            circuit.x(q)?;
+            circuit.h(q)?;
+        }
+        Ok(circuit)
+    }
+
+    /// The oracle followed by the diffusion operator
+    pub fn circuit(&self) -> PyResult<QuantumCircuit> {
+        let mut circuit = self.oracle.clone();
+        circuit.append(&self.diffusion()?, |q| q, |b| b);
+        Ok(circuit)
+    }
+
+    /// floor(pi / (4 asin(sqrt(M / N)))) for M marked states out of N, the
+    /// iteration count that maximizes the success probability
+    pub fn optimal_iterations(&self) -> PyResult<usize> {
+        let marked = self.marked.ok_or_else(|| pyo3::exceptions::PyValueError::new_err(
+            "The number of marked states is unknown for circuit oracles; pass iterations explicitly"))?;
+        let angle = (marked as f64 / (1u64 << self.qubits) as f64).sqrt().asin();
+        Ok((std::f64::consts::FRAC_PI_4 / angle).floor() as usize)
+    }
+
+    /// |+>^n followed by `iterations` Grover iterations (optimal_iterations when omitted)
+    #[pyo3(signature = (iterations=None))]
+    pub fn search_circuit(&self, iterations: Option<usize>) -> PyResult<QuantumCircuit> {
+        let iterations = match iterations {
+            Some(k) => k,
+            None => self.optimal_iterations()?,
+        };
+        let mut circuit = QuantumCircuit::new(self.qubits, 0);
+        for q in 0..self.qubits {
+            circuit.h(q)?;
+        }
+        let step = self.circuit()?;
+        for _ in 0..iterations {
+            circuit.append(&step, |q| q, |b| b);
+        }
+        Ok(circuit)
+    }
+
+    fn __repr__(&self) -> String {
+        match self.marked {
+            Some(m) => format!("GroverOperator(qubits={}, marked={})", self.qubits, m),
+            None => format!("GroverOperator(qubits={})", self.qubits),
+        }
+    }
+}
+
+impl GroverOperator {
+    fn from_circuit(circuit: QuantumCircuit) -> PyResult<Self> {
+        if circuit.qubits == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("Oracle circuit has no qubits"));
+        }
+        if dynamic::is_dynamic(&circuit.gates) || circuit.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Oracle circuits must be unitary (no measurement, reset, c_if or initialized state)"));
+        }
+        Ok(GroverOperator { qubits: circuit.qubits, oracle: circuit, marked: None })
+    }
+
+    fn from_bitstrings(marked: &[String]) -> PyResult<Self> {
+        let n = marked.first().map_or(0, |b| b.len());
+        if n == 0 || n >= usize::BITS as usize {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Marked bitstrings must have between 1 and {} bits", usize::BITS - 1)));
+        }
+        let mut indices = marked.iter().map(|b| parse_bitstring(b, n)).collect::<PyResult<Vec<_>>>()?;
+        indices.sort_unstable();
+        if indices.windows(2).any(|w| w[0] == w[1]) {
+            return Err(pyo3::exceptions::PyValueError::new_err("Duplicate marked bitstring"));
+        }
+
+        let mut oracle = QuantumCircuit::new(n, 0);
+        for &idx in &indices {
+            let zeros: Vec<usize> = (0..n).filter(|q| (idx >> q) & 1 == 0).collect();
+            for &q in &zeros {
+                oracle.x(q)?;
+            }
+            oracle.mcz((0..n - 1).collect(), n - 1)?;
+            for &q in &zeros {
+                oracle.x(q)?;
+            }
+        }
+        Ok(GroverOperator { qubits: n, oracle, marked: Some(indices.len()) })
+    }
+}
+
+/// Run Grover search from |+>^n and sample the result as a counts dictionary.
+/// `oracle` is a GroverOperator or anything its constructor accepts;
+/// `iterations` defaults to the optimal count for bitstring oracles.
+#[pyfunction]
+#[pyo3(signature = (oracle, iterations=None, shots=1024, seed=None))]
+pub fn grover_search(py: Python, oracle: &PyAny, iterations: Option<usize>, shots: usize, seed: Option<u64>) -> PyResult<HashMap<String, usize>> {
+    let operator = match oracle.extract::<GroverOperator>() {
+        Ok(operator) => operator,
+        Err(_) => GroverOperator::new(oracle)?,
+    };
+    operator.search_circuit(iterations)?.run(py, shots, seed, None, "little")
+}
@@ -0,0 +1,220 @@
+//! Weighted sums of Pauli strings with their algebra.
+//!
+//! Terms are stored as (Pauli masks, complex coefficient) in insertion order,
+//! with P(x, z) = i^|x & z| X^x Z^z so that Y = iXZ. Multiplying two strings
+//! XORs their masks and picks up a power of i, which is all the commutator
+//! needs: [P, Q] vanishes when P and Q commute and is 2PQ otherwise.
+//! Everything that takes an Observable also accepts a Hamiltonian whose
+//! coefficients are real.
+
+use num_complex::Complex64;
+use pyo3::prelude::*;
+use pyo3::types::PyComplex;
+use std::collections::HashMap;
+
+use crate::observables;
+use crate::pauli::{self, PauliMasks};
+
+/// Imaginary parts below this are dropped when a Hamiltonian is measured
+const REAL_TOLERANCE: f64 = 1e-6;
+
+/// Coefficient of an operand: anything `complex()` accepts
+fn coefficient(value: &PyAny) -> PyResult<Complex64> {
+    let c: &PyComplex = value.py().get_type::<PyComplex>().call1((value,))?.downcast()?;
+    Ok(Complex64::new(c.real(), c.imag()))
+}
+
+/// P * Q as (phase, masks)
+pub fn multiply(p: PauliMasks, q: PauliMasks) -> (Complex64, PauliMasks) {
+    let (power, product) = pauli::product(p, q);
+    let phase = match power {
+        0 => Complex64::new(1.0, 0.0),
+        1 => Complex64::new(0.0, 1.0),
+        2 => Complex64::new(-1.0, 0.0),
+        _ => Complex64::new(0.0, -1.0),
+    };
+    (phase, product)
+}
+
+/// Sum the coefficients of equal strings, in order of first appearance, and
+/// drop sums at most `tolerance` in magnitude
+pub fn merge(terms: impl Iterator<Item = (PauliMasks, Complex64)>, tolerance: f64) -> Vec<(PauliMasks, Complex64)> {
+    let mut index: HashMap<PauliMasks, usize> = HashMap::new();
+    let mut merged: Vec<(PauliMasks, Complex64)> = Vec::new();
+    for (masks, c) in terms {
+        match index.get(&masks) {
+            Some(&i) => merged[i].1 += c,
+            None => {
+                index.insert(masks, merged.len());
+                merged.push((masks, c));
+            }
+        }
+    }
+    merged.retain(|(_, c)| c.norm() > tolerance);
+    merged
+}
+
+/// A linear combination of Pauli strings, e.g. 0.5 * "ZZ" - 1.2 * "XI"
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone, Debug, Default)]
+pub struct Hamiltonian {
+    terms: Vec<(PauliMasks, Complex64)>,
+    /// Length of the longest label, so labels print at one width
+    qubits: usize,
+}
+
+#[pymethods]
+impl Hamiltonian {
+    /// Build from (coefficient, label) pairs; character i of a label acts on qubit i
+    #[new]
+    #[pyo3(signature = (terms=None))]
+    pub fn new(terms: Option<Vec<(&PyAny, String)>>) -> PyResult<Self> {
+        let mut h = Hamiltonian::default();
+        for (coeff, label) in terms.unwrap_or_default() {
+            h.push(&label, coefficient(coeff)?)?;
+        }
+        Ok(h)
+    }
+
+    /// The Hamiltonian of a PauliString or Observable
+    #[staticmethod]
+    pub fn from_observable(observable: &PyAny) -> PyResult<Self> {
+        let mut h = Hamiltonian::default();
+        for (coeff, label) in observables::extract_labels(observable, usize::BITS as usize)? {
+            h.push(&label, Complex64::new(coeff as f64, 0.0))?;
+        }
+        Ok(h)
+    }
+
+    /// Append the term coeff * label
+    #[pyo3(signature = (label, coeff=None))]
+    pub fn add_term(&mut self, label: &str, coeff: Option<&PyAny>) -> PyResult<()> {
+        let coeff = coeff.map(coefficient).transpose()?.unwrap_or(Complex64::new(1.0, 0.0));
+        self.push(label, coeff)
+    }
+
+    #[getter]
+    pub fn num_qubits(&self) -> usize {
+        self.qubits
+    }
+
+    /// (coefficient, label) pairs in the order the terms were added
+    pub fn terms(&self, py: Python) -> Vec<(PyObject, String)> {
+        self.terms.iter()
+            .map(|&(masks, c)| (PyComplex::from_doubles(py, c.re, c.im).into(), self.label(masks)))
+            .collect()
+    }
+
+    /// Merge terms with the same Pauli string and drop those whose coefficient
+    /// is at most `tolerance` in magnitude
+    #[pyo3(signature = (tolerance=1e-10))]
+    pub fn simplify(&self, tolerance: f64) -> Hamiltonian {
+        Hamiltonian { terms: merge(self.terms.iter().copied(), tolerance), qubits: self.qubits }
+    }
+
+    /// The commutator [self, other] = self other - other self, simplified
+    pub fn commutator(&self, other: &Hamiltonian) -> Hamiltonian {
+        let products = self.terms.iter().flat_map(|&(p, a)| {
+            other.terms.iter().filter_map(move |&(q, b)| {
+                // Strings anticommute when an odd number of qubits carry two different non-identity factors
+                let anticommute = ((p.x & q.z).count_ones() + (p.z & q.x).count_ones()) % 2 == 1;
+                anticommute.then(|| {
+                    let (phase, masks) = multiply(p, q);
+                    (masks, a * b * phase * 2.0)
+                })
+            })
+        });
+        Hamiltonian { terms: merge(products, 1e-10), qubits: self.qubits.max(other.qubits) }
+    }
+
+    /// True when [self, other] vanishes
+    pub fn commutes_with(&self, other: &Hamiltonian) -> bool {
+        self.commutator(other).terms.is_empty()
+    }
+
+    /// True when every coefficient of the simplified sum is real within `tolerance`
+    #[pyo3(signature = (tolerance=1e-10))]
+    pub fn is_hermitian(&self, tolerance: f64) -> bool {
+        self.simplify(tolerance).terms.iter().all(|(_, c)| c.im.abs() <= tolerance)
+    }
+
+    fn __add__(&self, other: &Hamiltonian) -> Hamiltonian {
+        let terms = self.terms.iter().chain(&other.terms).copied().collect();
+        Hamiltonian { terms, qubits: self.qubits.max(other.qubits) }
+    }
+
+    fn __sub__(&self, other: &Hamiltonian) -> Hamiltonian {
+        self.__add__(&other.__neg__())
+    }
+
+    fn __mul__(&self, scalar: &PyAny) -> PyResult<Hamiltonian> {
+        let s = coefficient(scalar)?;
+        Ok(Hamiltonian { terms: self.terms.iter().map(|&(m, c)| (m, c * s)).collect(), qubits: self.qubits })
+    }
+
+    fn __rmul__(&self, scalar: &PyAny) -> PyResult<Hamiltonian> {
+        self.__mul__(scalar)
+    }
+
+    fn __neg__(&self) -> Hamiltonian {
+        Hamiltonian { terms: self.terms.iter().map(|&(m, c)| (m, -c)).collect(), qubits: self.qubits }
+    }
+
+    fn __len__(&self) -> usize {
+        self.terms.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let terms: Vec<String> = self.terms.iter()
+            .map(|&(masks, c)| {
+                let coeff = if c.im == 0.0 { c.re.to_string() } else { format!("({}{:+}j)", c.re, c.im) };
+                format!("{} * {}", coeff, self.label(masks))
+            })
+            .collect();
+        format!("Hamiltonian({})", terms.join(" + "))
+    }
+}
+
+impl Hamiltonian {
+    /// (masks, coefficient) terms as added, unsimplified
+    pub fn pauli_terms(&self) -> &[(PauliMasks, Complex64)] {
+        &self.terms
+    }
+
+    fn push(&mut self, label: &str, coeff: Complex64) -> PyResult<()> {
+        if label.len() > usize::BITS as usize {
+            return Err(crate::errors::DimensionMismatchError::new_err(format!(
+                "Hamiltonian terms act on at most {} qubits, got '{}'", usize::BITS, label)));
+        }
+        self.terms.push((observables::parse_label(label)?, coeff));
+        self.qubits = self.qubits.max(label.len());
+        Ok(())
+    }
+
+    /// Label of `masks` padded with I to the Hamiltonian's width
+    fn label(&self, masks: PauliMasks) -> String {
+        (0..self.qubits)
+            .map(|q| match ((masks.x >> q) & 1, (masks.z >> q) & 1) {
+                (0, 0) => 'I',
+                (1, 0) => 'X',
+                (1, 1) => 'Y',
+                _ => 'Z',
+            })
+            .collect()
+    }
+
+    /// Simplified (coefficient, label) terms, for the backends that take real
+    /// observables; errors when a coefficient is not real
+    pub fn real_terms(&self) -> PyResult<Vec<(f32, String)>> {
+        self.simplify(0.0).terms.iter()
+            .map(|&(masks, c)| {
+                if c.im.abs() > REAL_TOLERANCE {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "Hamiltonian term {} has the non-real coefficient {}; only Hermitian Hamiltonians \
+                         can be measured", self.label(masks), c)));
+                }
+                Ok((c.re as f32, self.label(masks)))
+            })
+            .collect()
+    }
+}
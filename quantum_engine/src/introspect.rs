@@ -0,0 +1,173 @@
+//! Read and edit access to a circuit's gate list from Python.
+//!
+//! A gate is described as (name, qubits, params): the builder name, every qubit
+//! it acts on (controls first, target last) and its angles. Multi-controlled
+//! single-qubit gates are named "controlled_<gate>" (e.g. "controlled_z" for
+//! `mcz`); a measurement carries its classical bit as its only param and a
+//! c_if block reads as ("c_if", body qubits, [bit, value]).
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::{distinct_qubits, Gate, GateType};
+
+pub type GateDescription = (String, Vec<usize>, Vec<f32>);
+
+/// The name `describe` and `count_ops` report for a gate
+pub fn name(gate_type: &GateType) -> String {
+    match gate_type {
+        GateType::CONTROLLED(_, inner) => format!("controlled_{}", inner.name()),
+        other => other.name().to_string(),
+    }
+}
+
+fn angles(gate_type: &GateType) -> Vec<f32> {
+    match gate_type {
+        GateType::RX(a) | GateType::RY(a) | GateType::RZ(a) | GateType::P(a)
+        | GateType::CRX(a) | GateType::CRY(a) | GateType::CRZ(a) | GateType::CPHASE(a)
+        | GateType::RXX(_, a) | GateType::RYY(_, a) | GateType::RZZ(_, a) => vec![*a],
+        GateType::U(theta, phi, lambda) => vec![*theta, *phi, *lambda],
+        GateType::CONTROLLED(_, inner) => angles(inner),
+        GateType::MEASURE(bit) => vec![*bit as f32],
+        GateType::IF(bit, value, _) => vec![*bit as f32, *value as u8 as f32],
+        _ => Vec::new(),
+    }
+}
+
+pub fn describe(gate: &Gate) -> GateDescription {
+    (name(&gate.gate_type), gate.qubits(), angles(&gate.gate_type))
+}
+
+/// Build a gate from a description, checking qubit indices against `n_qubits`
+pub fn from_description(description: &GateDescription, n_qubits: usize) -> PyResult<Gate> {
+    let (name, qubits, params) = description;
+    let name = name.to_ascii_lowercase();
+    if !distinct_qubits(qubits, n_qubits)? {
+        return Err(crate::errors::InvalidGateError::new_err("Duplicate qubit indices"));
+    }
+    let expect = |n_q: usize, n_p: usize| {
+        if qubits.len() != n_q || params.len() != n_p {
+            return Err(crate::errors::InvalidGateError::new_err(format!(
+                "Gate {} takes {} qubit(s) and {} parameter(s), got {} and {}",
+                name, n_q, n_p, qubits.len(), params.len())));
+        }
+        Ok(())
+    };
+    let Some((&target, controls)) = qubits.split_last() else {
+        return Err(crate::errors::InvalidGateError::new_err(format!("Gate {} needs at least one qubit", name)));
+    };
+    let gate = |gate_type: GateType, control: Option<usize>| Gate { gate_type, target, control };
+    let first = qubits[0];
+    Ok(match name.as_str() {
+        "cnot" | "cx" | "ch" => {
+            expect(2, 0)?;
+            gate(if name == "ch" { GateType::CH } else { GateType::CNOT }, Some(first))
+        }
+        "crx" | "cry" | "crz" | "cphase" => {
+            expect(2, 1)?;
+            let gate_type = match name.as_str() {
+                "crx" => GateType::CRX(params[0]),
+                "cry" => GateType::CRY(params[0]),
+                "crz" => GateType::CRZ(params[0]),
+                _ => GateType::CPHASE(params[0]),
+            };
+            gate(gate_type, Some(first))
+        }
+        "swap" | "iswap" => {
+            expect(2, 0)?;
+            gate(if name == "swap" { GateType::SWAP(first) } else { GateType::ISWAP(first) }, None)
+        }
+        "rxx" | "ryy" | "rzz" => {
+            expect(2, 1)?;
+            let gate_type = match name.as_str() {
+                "rxx" => GateType::RXX(first, params[0]),
+                "ryy" => GateType::RYY(first, params[0]),
+                _ => GateType::RZZ(first, params[0]),
+            };
+            gate(gate_type, None)
+        }
+        "mcx" => {
+            if controls.is_empty() || !params.is_empty() {
+                return Err(crate::errors::InvalidGateError::new_err(
+                    "Gate mcx takes at least 2 qubits and no parameters"));
+            }
+            gate(GateType::MCX(controls.to_vec()), None)
+        }
+        "measure" => {
+            expect(1, 1)?;
+            if params[0] < 0.0 || params[0].fract() != 0.0 {
+                return Err(pyo3::exceptions::PyValueError::new_err("The classical bit of a measurement must be a non-negative integer"));
+            }
+            gate(GateType::MEASURE(params[0] as usize), None)
+        }
+        "reset" => {
+            expect(1, 0)?;
+            gate(GateType::RESET, None)
+        }
+        "unitary" | "c_if" | "snapshot" | "restore" => {
+            return Err(crate::errors::InvalidGateError::new_err(format!(
+                "{} gates cannot be built from a description; use the builder method", name)));
+        }
+        other => match other.strip_prefix("controlled_") {
+            Some(inner) => {
+                if controls.is_empty() {
+                    return Err(crate::errors::InvalidGateError::new_err("Controlled gates need at least one control"));
+                }
+                gate(GateType::CONTROLLED(controls.to_vec(), Box::new(GateType::single_qubit(inner, params)?)), None)
+            }
+            None => {
+                if qubits.len() != 1 {
+                    return Err(crate::errors::InvalidGateError::new_err(format!(
+                        "Gate {} takes 1 qubit(s), got {}", other, qubits.len())));
+                }
+                gate(GateType::single_qubit(other, params)?, None)
+            }
+        },
+    })
+}
+
+/// Number of layers when every gate waits for the previous gates on its qubits
+/// and, for measurements and c_if blocks, on the classical bit it touches.
+/// Snapshots and restores take no layer.
+pub fn depth(gates: &[Gate], n_qubits: usize, n_clbits: usize) -> usize {
+    levels(gates, n_qubits, n_clbits).into_iter().max().unwrap_or(0)
+}
+
+/// Layer of every gate (from 1): one above the latest earlier gate sharing a
+/// qubit or classical bit with it; 0 for snapshots and restores, which occupy no layer
+pub fn levels(gates: &[Gate], n_qubits: usize, n_clbits: usize) -> Vec<usize> {
+    let mut qubit_level = vec![0usize; n_qubits];
+    let mut clbit_level = vec![0usize; n_clbits];
+    let mut levels = Vec::with_capacity(gates.len());
+    for gate in gates {
+        if gate.is_barrier() {
+            levels.push(0);
+            continue;
+        }
+        let qubits = gate.qubits();
+        let clbit = match gate.gate_type {
+            GateType::MEASURE(bit) | GateType::IF(bit, ..) => Some(bit),
+            _ => None,
+        };
+        let level = 1 + qubits.iter().map(|&q| qubit_level[q])
+            .chain(clbit.map(|b| clbit_level[b]))
+            .max()
+            .unwrap_or(0);
+        for q in qubits {
+            qubit_level[q] = level;
+        }
+        if let Some(b) = clbit {
+            clbit_level[b] = level;
+        }
+        levels.push(level);
+    }
+    levels
+}
+
+pub fn count_ops(gates: &[Gate]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for gate in gates {
+        *counts.entry(name(&gate.gate_type)).or_insert(0) += 1;
+    }
+    counts
+}
@@ -0,0 +1,520 @@
+//! Precision-generic state-vector kernels.
+//!
+//! The f32 `QuantumState`/`DensityMatrix` and the f64 classes in `precision`
+//! share these loops; only the scalar type differs.
+
+use num_complex::Complex;
+use num_traits::{Float, FloatConst, NumAssign};
+use rayon::prelude::*;
+use std::fmt::Debug;
+use std::iter::Sum;
+
+use crate::{dynamic, reduction, simd, Gate, GateType};
+
+/// Scalar types the simulator can run in (f32 and f64)
+pub trait Real: Float + FloatConst + NumAssign + Sum + Send + Sync + Debug + 'static {
+    fn from_f32(x: f32) -> Self;
+
+    /// 2x2 butterfly over two equal-length amplitude runs
+    fn butterfly(lo: &mut [Complex<Self>], hi: &mut [Complex<Self>], m: &Matrix2<Self>) {
+        simd::butterfly_scalar(lo, hi, m)
+    }
+
+    /// Multiply a run of amplitudes by a constant
+    fn scale(values: &mut [Complex<Self>], factor: Complex<Self>) {
+        simd::scale_scalar(values, factor)
+    }
+}
+
+impl Real for f32 {
+    fn from_f32(x: f32) -> Self {
+        x
+    }
+
+    fn butterfly(lo: &mut [Complex<f32>], hi: &mut [Complex<f32>], m: &Matrix2<f32>) {
+        simd::butterfly_f32(lo, hi, m)
+    }
+
+    fn scale(values: &mut [Complex<f32>], factor: Complex<f32>) {
+        simd::scale_f32(values, factor)
+    }
+}
+
+impl Real for f64 {
+    fn from_f32(x: f32) -> Self {
+        x as f64
+    }
+
+    fn butterfly(lo: &mut [Complex<f64>], hi: &mut [Complex<f64>], m: &Matrix2<f64>) {
+        simd::butterfly_f64(lo, hi, m)
+    }
+
+    fn scale(values: &mut [Complex<f64>], factor: Complex<f64>) {
+        simd::scale_f64(values, factor)
+    }
+}
+
+pub type Matrix2<T> = [[Complex<T>; 2]; 2];
+
+/// Amplitude pairs per task of a parallel butterfly (and amplitudes per task
+/// of an elementwise pass)
+const PARALLEL_RUN: usize = 4096;
+
+/// (diagonal, off-diagonal) entries of a symmetric 2x2 block
+pub type PairBlock<T> = (Complex<T>, Complex<T>);
+
+fn c<T: Real>(re: T, im: T) -> Complex<T> {
+    Complex::new(re, im)
+}
+
+/// Target-qubit matrix of a single-qubit or controlled single-qubit gate
+/// (X for CNOT and MCX, the inner gate's for CONTROLLED); None for the
+/// two-qubit gates
+pub fn matrix<T: Real>(gate_type: &GateType) -> Option<Matrix2<T>> {
+    let (zero, one) = (T::zero(), T::one());
+    let half = |x: f32| T::from_f32(x) / (one + one);
+    Some(match *gate_type {
+        GateType::CONTROLLED(_, ref inner) => return matrix(inner),
+        GateType::H | GateType::CH => {
+            let s = T::FRAC_1_SQRT_2();
+            [[c(s, zero), c(s, zero)], [c(s, zero), c(-s, zero)]]
+        }
+        GateType::X | GateType::CNOT | GateType::MCX(_) => {
+            [[c(zero, zero), c(one, zero)], [c(one, zero), c(zero, zero)]]
+        }
+        GateType::Y => [[c(zero, zero), c(zero, -one)], [c(zero, one), c(zero, zero)]],
+        GateType::Z => [[c(one, zero), c(zero, zero)], [c(zero, zero), c(-one, zero)]],
+        GateType::S => [[c(one, zero), c(zero, zero)], [c(zero, zero), c(zero, one)]],
+        GateType::T => {
+            let s = T::FRAC_1_SQRT_2();
+            [[c(one, zero), c(zero, zero)], [c(zero, zero), c(s, s)]]
+        }
+        GateType::SDG => [[c(one, zero), c(zero, zero)], [c(zero, zero), c(zero, -one)]],
+        GateType::TDG => {
+            let s = T::FRAC_1_SQRT_2();
+            [[c(one, zero), c(zero, zero)], [c(zero, zero), c(s, -s)]]
+        }
+        GateType::RX(theta) | GateType::CRX(theta) => {
+            let (s, co) = half(theta).sin_cos();
+            [[c(co, zero), c(zero, -s)], [c(zero, -s), c(co, zero)]]
+        }
+        GateType::RY(theta) | GateType::CRY(theta) => {
+            let (s, co) = half(theta).sin_cos();
+            [[c(co, zero), c(-s, zero)], [c(s, zero), c(co, zero)]]
+        }
+        GateType::RZ(phi) | GateType::CRZ(phi) => {
+            let (s, co) = half(phi).sin_cos();
+            [[c(co, -s), c(zero, zero)], [c(zero, zero), c(co, s)]]
+        }
+        GateType::U(theta, phi, lambda) => {
+            let (s, co) = half(theta).sin_cos();
+            let phase = |a: f32| Complex::from_polar(one, T::from_f32(a));
+            [[c(co, zero), -phase(lambda) * s], [phase(phi) * s, phase(phi) * phase(lambda) * co]]
+        }
+        GateType::P(phi) | GateType::CPHASE(phi) => {
+            let (s, co) = T::from_f32(phi).sin_cos();
+            [[c(one, zero), c(zero, zero)], [c(zero, zero), c(co, s)]]
+        }
+        GateType::SWAP(_) | GateType::ISWAP(_) | GateType::RXX(..) | GateType::RYY(..) | GateType::RZZ(..) | GateType::UNITARY(..)
+        | GateType::MEASURE(_) | GateType::RESET | GateType::IF(..) | GateType::SNAPSHOT(_) | GateType::RESTORE(_) => return None,
+    })
+}
+
+/// Check every qubit index of `gate` against an `n`-qubit register
+pub fn validate(gate: &Gate, n: usize) -> pyo3::PyResult<()> {
+    if dynamic::is_dynamic(std::slice::from_ref(gate)) {
+        return Err(dynamic::unsupported());
+    }
+    let qubits = gate.qubits();
+    if qubits.iter().any(|&q| q >= n) {
+        return Err(crate::errors::QubitIndexError::new_err("Qubit index out of range"));
+    }
+    let mask = qubits.iter().fold(0usize, |m, &q| m | (1 << q));
+    if mask.count_ones() as usize != qubits.len() {
+        return Err(crate::errors::InvalidGateError::new_err(match gate.gate_type {
+            GateType::SWAP(_) | GateType::ISWAP(_) => "Swapped qubits must be different",
+            GateType::RXX(..) | GateType::RYY(..) | GateType::RZZ(..) => "Rotated qubits must be different",
+            _ => "Control and target must be different",
+        }));
+    }
+    Ok(())
+}
+
+/// Apply a validated circuit instruction (or its entrywise conjugate U*) to a state vector
+pub fn apply<T: Real>(state: &mut [Complex<T>], gate: &Gate, conjugate: bool) {
+    let conj = |m: Matrix2<T>| if conjugate { m.map(|row| row.map(|e| e.conj())) } else { m };
+    match (&gate.gate_type, matrix::<T>(&gate.gate_type)) {
+        (GateType::SNAPSHOT(_), _) => {}
+        (GateType::SWAP(a), _) => swap_phased(state, *a, gate.target, c(T::one(), T::zero())),
+        (GateType::ISWAP(a), _) => {
+            let i = if conjugate { -T::one() } else { T::one() };
+            swap_phased(state, *a, gate.target, c(T::zero(), i))
+        }
+        (GateType::RXX(a, _) | GateType::RYY(a, _) | GateType::RZZ(a, _), _) => {
+            let (even, odd) = pair_rotation::<T>(&gate.gate_type, conjugate).unwrap();
+            rotate_pair(state, *a, gate.target, even, odd)
+        }
+        (GateType::UNITARY(targets, m), _) => {
+            let m: Vec<Complex<T>> = m.iter()
+                .map(|e| c(T::from_f32(e.re), T::from_f32(if conjugate { -e.im } else { e.im })))
+                .collect();
+            apply_k(state, targets, &m)
+        }
+        (_, Some(m)) => apply_1q(state, gate.target, conj(m), control_mask(gate)),
+        (_, None) => unreachable!(),
+    }
+}
+
+/// Apply the inverse U^dagger of a validated circuit instruction
+pub fn apply_dagger<T: Real>(state: &mut [Complex<T>], gate: &Gate) {
+    match (&gate.gate_type, matrix::<T>(&gate.gate_type)) {
+        (GateType::SNAPSHOT(_), _) => {}
+        (GateType::SWAP(a), _) => swap_phased(state, *a, gate.target, c(T::one(), T::zero())),
+        (GateType::ISWAP(a), _) => swap_phased(state, *a, gate.target, c(T::zero(), -T::one())),
+        // Each parity block is symmetric, so the dagger is the entrywise conjugate
+        (GateType::RXX(..) | GateType::RYY(..) | GateType::RZZ(..), _) => apply(state, gate, true),
+        (GateType::UNITARY(targets, m), _) => {
+            let k_dim = 1 << targets.len();
+            let dagger: Vec<Complex<T>> = (0..k_dim * k_dim)
+                .map(|idx| {
+                    let e = m[(idx % k_dim) * k_dim + idx / k_dim];
+                    c(T::from_f32(e.re), -T::from_f32(e.im))
+                })
+                .collect();
+            apply_k(state, targets, &dagger)
+        }
+        (_, Some(m)) => {
+            let dagger = [[m[0][0].conj(), m[1][0].conj()], [m[0][1].conj(), m[1][1].conj()]];
+            apply_1q(state, gate.target, dagger, control_mask(gate))
+        }
+        (_, None) => unreachable!(),
+    }
+}
+
+/// Bitmask of the qubits that must be |1> for the target matrix to act
+pub fn control_mask(gate: &Gate) -> usize {
+    match &gate.gate_type {
+        GateType::MCX(controls) | GateType::CONTROLLED(controls, _) => controls.iter().fold(0, |acc, &q| acc | (1 << q)),
+        _ => gate.control.map_or(0, |q| 1 << q),
+    }
+}
+
+/// How a 2x2 gate matrix moves amplitudes, which picks the `apply_1q` kernel
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// Phases only (Z, S, T, RZ, CZ, CPHASE): an elementwise multiply
+    Diagonal,
+    /// Exactly X (X, CNOT, MCX): the pairs are swapped
+    Flip,
+    /// Off-diagonal only (Y): swapped and phased
+    AntiDiagonal,
+    General,
+}
+
+fn shape<T: Real>(m: &Matrix2<T>) -> Shape {
+    let (zero, one) = (c(T::zero(), T::zero()), c(T::one(), T::zero()));
+    match (m[0][1] == zero && m[1][0] == zero, m[0][0] == zero && m[1][1] == zero) {
+        (true, _) => Shape::Diagonal,
+        (_, true) if m[0][1] == one && m[1][0] == one => Shape::Flip,
+        (_, true) => Shape::AntiDiagonal,
+        _ => Shape::General,
+    }
+}
+
+/// Butterfly over amplitude pairs (i, i + 2^target) in place, restricted to
+/// indices where every bit of `control_mask` is set. Diagonal gates multiply
+/// amplitudes in place and X-type gates swap them, without the full butterfly.
+pub fn apply_1q<T: Real>(state: &mut [Complex<T>], target: usize, gate: Matrix2<T>, control_mask: usize) {
+    let dim = state.len();
+    let step = 1 << target;
+    let shape = shape(&gate);
+    if control_mask == 0 && step >= simd::MIN_RUN {
+        apply_1q_runs(state, target, gate, shape);
+        return;
+    }
+    if shape == Shape::Diagonal {
+        apply_diagonal(state, target, gate[0][0], gate[1][1], control_mask);
+        return;
+    }
+    let butterfly = |i: usize, a: &mut Complex<T>, b: &mut Complex<T>| {
+        if i & control_mask == control_mask {
+            match shape {
+                Shape::Flip => std::mem::swap(a, b),
+                Shape::AntiDiagonal => (*a, *b) = (gate[0][1] * *b, gate[1][0] * *a),
+                _ => {
+                    let (x, y) = (*a, *b);
+                    *a = gate[0][0] * x + gate[0][1] * y;
+                    *b = gate[1][0] * x + gate[1][1] * y;
+                }
+            }
+        }
+    };
+
+    for_each_pair_run(state, target, dim > 1024, |base, lo, hi| {
+        lo.iter_mut().zip(hi.iter_mut()).enumerate().for_each(|(j, (a, b))| butterfly(base + j, a, b))
+    });
+}
+
+/// Visit every amplitude pair (i, i + 2^target) exactly once as matched runs
+/// (base, lo, hi) with lo[j] = state[base + j] and hi[j] = state[base + step + j].
+/// Low targets group whole blocks of 2^(target+1) amplitudes into tasks of
+/// 2 * PARALLEL_RUN; high targets cut both halves of each block into slices of
+/// PARALLEL_RUN. The tasks depend only on the target, never on the thread
+/// count, and `parallel` only decides whether rayon runs them.
+fn for_each_pair_run<T: Real, F>(state: &mut [Complex<T>], target: usize, parallel: bool, f: F)
+where
+    F: Fn(usize, &mut [Complex<T>], &mut [Complex<T>]) + Sync,
+{
+    let step = 1 << target;
+    if step >= PARALLEL_RUN {
+        let mut runs = Vec::with_capacity(state.len() / PARALLEL_RUN / 2);
+        for (k, block) in state.chunks_mut(2 * step).enumerate() {
+            let (lo, hi) = block.split_at_mut(step);
+            let slices = lo.chunks_mut(PARALLEL_RUN).zip(hi.chunks_mut(PARALLEL_RUN)).enumerate();
+            runs.extend(slices.map(|(r, (lo, hi))| (2 * step * k + PARALLEL_RUN * r, lo, hi)));
+        }
+        if parallel {
+            runs.into_par_iter().for_each(|(base, lo, hi)| f(base, lo, hi));
+        } else {
+            runs.into_iter().for_each(|(base, lo, hi)| f(base, lo, hi));
+        }
+    } else {
+        let group = 2 * PARALLEL_RUN;
+        let task = |(g, amps): (usize, &mut [Complex<T>])| {
+            for (k, block) in amps.chunks_mut(2 * step).enumerate() {
+                let (lo, hi) = block.split_at_mut(step);
+                f(group * g + 2 * step * k, lo, hi);
+            }
+        };
+        if parallel {
+            state.par_chunks_mut(group).enumerate().for_each(task);
+        } else {
+            state.chunks_mut(group).enumerate().for_each(task);
+        }
+    }
+}
+
+/// Multiply amplitudes with every bit of `control_mask` set by `d0` or `d1` by
+/// their target bit, skipping a half whose factor is one. Only the matching
+/// indices are visited, so an MCZ or MCP with k controls touches 2^(n-k-1)
+/// amplitudes instead of scanning all 2^n.
+fn apply_diagonal<T: Real>(state: &mut [Complex<T>], target: usize, d0: Complex<T>, d1: Complex<T>, control_mask: usize) {
+    let one = c(T::one(), T::zero());
+    let target_bit = 1 << target;
+    if d0 != one {
+        for_each_masked(state, control_mask, target_bit, move |v| *v *= d0);
+    }
+    if d1 != one {
+        for_each_masked(state, control_mask | target_bit, 0, move |v| *v *= d1);
+    }
+}
+
+/// Apply `f` to every amplitude whose index has all bits of `ones` set and all
+/// bits of `zeros` clear. Chunks whose high bits already fail the pattern are
+/// skipped whole; inside a chunk the free low bits are enumerated as subsets.
+fn for_each_masked<T: Real>(state: &mut [Complex<T>], ones: usize, zeros: usize, f: impl Fn(&mut Complex<T>) + Sync) {
+    // Both lengths are powers of two, so a chunk covers whole low-bit patterns
+    let run = state.len().min(PARALLEL_RUN);
+    let low = run - 1;
+    let free = low & !(ones | zeros);
+    let visit = |base: usize, chunk: &mut [Complex<T>]| {
+        if base & ones != ones & !low || base & zeros != 0 {
+            return;
+        }
+        let mut subset = 0usize;
+        loop {
+            f(&mut chunk[subset | (ones & low)]);
+            subset = subset.wrapping_sub(free) & free;
+            if subset == 0 {
+                break;
+            }
+        }
+    };
+    if state.len() > 1024 {
+        state.par_chunks_mut(run).enumerate().for_each(|(k, chunk)| visit(k * run, chunk));
+    } else {
+        visit(0, state);
+    }
+}
+
+/// Uncontrolled `apply_1q` on whole runs of contiguous amplitudes, through the
+/// vector kernels; diagonal gates only scale each half (and skip a half whose
+/// factor is one) and X swaps the halves
+fn apply_1q_runs<T: Real>(state: &mut [Complex<T>], target: usize, gate: Matrix2<T>, shape: Shape) {
+    let one = c(T::one(), T::zero());
+    let kernel = |lo: &mut [Complex<T>], hi: &mut [Complex<T>]| match shape {
+        Shape::Flip => lo.swap_with_slice(hi),
+        Shape::Diagonal => {
+            if gate[0][0] != one {
+                T::scale(lo, gate[0][0]);
+            }
+            if gate[1][1] != one {
+                T::scale(hi, gate[1][1]);
+            }
+        }
+        _ => T::butterfly(lo, hi, &gate),
+    };
+
+    for_each_pair_run(state, target, state.len() > 1024, |_, lo, hi| kernel(lo, hi));
+}
+
+/// Apply a dense 2^k x 2^k matrix (row-major, bit i of the row index on targets[i])
+pub fn apply_k<T: Real>(state: &mut [Complex<T>], targets: &[usize], matrix: &[Complex<T>]) {
+    if let [t] = targets {
+        apply_1q(state, *t, [[matrix[0], matrix[1]], [matrix[2], matrix[3]]], 0);
+        return;
+    }
+
+    let k_dim = 1 << targets.len();
+    let offsets = reduction::scatter_indices(targets);
+    let target_mask = offsets[k_dim - 1];
+    let old = state.to_vec();
+    state.par_iter_mut().enumerate().for_each(|(i, val)| {
+        let base = i & !target_mask;
+        let row = targets.iter().enumerate().fold(0, |r, (b, &q)| r | (((i >> q) & 1) << b));
+        *val = (0..k_dim).map(|col| matrix[row * k_dim + col] * old[base | offsets[col]]).sum();
+    });
+}
+
+/// Exchange amplitudes whose bits a and b differ, multiplying them by `phase`
+pub fn swap_phased<T: Real>(state: &mut [Complex<T>], a: usize, b: usize, phase: Complex<T>) {
+    let mask_a = 1 << a;
+    let mask_b = 1 << b;
+    let both = mask_a | mask_b;
+    // Swapped pairs differ only in bits a and b, so they share an aligned block
+    // of 2^(max(a, b) + 1) amplitudes and blocks can be permuted independently
+    let exchange = |block: &mut [Complex<T>]| {
+        for i in 0..block.len() {
+            if (i & mask_a) != 0 && (i & mask_b) == 0 {
+                let j = i ^ both;
+                let (x, y) = (block[i], block[j]);
+                block[i] = y * phase;
+                block[j] = x * phase;
+            }
+        }
+    };
+
+    if state.len() > 1024 {
+        state.par_chunks_mut(2 << a.max(b)).for_each(exchange);
+    } else {
+        exchange(state);
+    }
+}
+
+/// Coefficients (diagonal, off-diagonal) of exp(-i theta/2 P x P) on each
+/// amplitude pair (i, i ^ a ^ b), for pairs of even and of odd parity in bits
+/// a and b; None for anything but RXX, RYY and RZZ
+pub fn pair_rotation<T: Real>(gate_type: &GateType, conjugate: bool) -> Option<(PairBlock<T>, PairBlock<T>)> {
+    let zero = T::zero();
+    let half_angle = |theta: f32| {
+        let half = T::from_f32(theta) / (T::one() + T::one());
+        if conjugate { (-half).sin_cos() } else { half.sin_cos() }
+    };
+    Some(match *gate_type {
+        // XX|ab> = |~a~b>
+        GateType::RXX(_, theta) => {
+            let (s, co) = half_angle(theta);
+            ((c(co, zero), c(zero, -s)), (c(co, zero), c(zero, -s)))
+        }
+        // YY|ab> = -|~a~b> when a == b and +|~a~b> otherwise
+        GateType::RYY(_, theta) => {
+            let (s, co) = half_angle(theta);
+            ((c(co, zero), c(zero, s)), (c(co, zero), c(zero, -s)))
+        }
+        GateType::RZZ(_, theta) => {
+            let (s, co) = half_angle(theta);
+            ((c(co, -s), c(zero, zero)), (c(co, s), c(zero, zero)))
+        }
+        _ => return None,
+    })
+}
+
+/// Mix each amplitude pair (i, i ^ a ^ b) by [[d, o], [o, d]], taking (d, o) from
+/// `even` or `odd` by the parity of bits a and b (flipping both keeps it)
+pub fn rotate_pair<T: Real>(
+    state: &mut [Complex<T>],
+    a: usize,
+    b: usize,
+    even: PairBlock<T>,
+    odd: PairBlock<T>,
+) {
+    let dim = state.len();
+    let mask_a = 1 << a;
+    let mask_b = 1 << b;
+    let both = mask_a | mask_b;
+    let coefficients = |i: usize| if (i & both).count_ones() & 1 == 0 { even } else { odd };
+
+    // RZZ only phases each amplitude
+    let zero = c(T::zero(), T::zero());
+    if even.1 == zero && odd.1 == zero {
+        let phase = |i: usize, v: &mut Complex<T>| *v *= coefficients(i).0;
+        if dim > 1024 {
+            state.par_chunks_mut(PARALLEL_RUN).enumerate().for_each(|(k, chunk)| {
+                chunk.iter_mut().enumerate().for_each(|(j, v)| phase(k * PARALLEL_RUN + j, v))
+            });
+        } else {
+            state.iter_mut().enumerate().for_each(|(i, v)| phase(i, v));
+        }
+        return;
+    }
+
+    if dim > 1024 {
+        let old = state.to_vec();
+        state.par_iter_mut()
+            .enumerate()
+            .for_each(|(i, val)| {
+                let (d, o) = coefficients(i);
+                *val = d * old[i] + o * old[i ^ both];
+            });
+    } else {
+        for i in 0..dim {
+            if i & mask_a == 0 {
+                let j = i ^ both;
+                let (d, o) = coefficients(i);
+                let (x, y) = (state[i], state[j]);
+                state[i] = d * x + o * y;
+                state[j] = d * y + o * x;
+            }
+        }
+    }
+}
+
+/// rho -> sum_k E_k rho E_k^dagger for single-qubit Kraus operators on `qubit`
+/// of a flattened `dim x dim` density matrix
+pub fn channel_1q<T: Real>(matrix: &mut Vec<Complex<T>>, dim: usize, qubit: usize, kraus: &[Matrix2<T>]) {
+    let mask = 1 << qubit;
+    let zero = c(T::zero(), T::zero());
+
+    // Superoperator S[a][b][a'][b'] = sum_k E_k[a][a'] conj(E_k[b][b'])
+    let mut superop = [[[[zero; 2]; 2]; 2]; 2];
+    for e in kraus {
+        for (a, row) in superop.iter_mut().enumerate() {
+            for (b, block) in row.iter_mut().enumerate() {
+                for (a2, block_row) in block.iter_mut().enumerate() {
+                    for (b2, val) in block_row.iter_mut().enumerate() {
+                        *val += e[a][a2] * e[b][b2].conj();
+                    }
+                }
+            }
+        }
+    }
+
+    let old = &*matrix;
+    let mut out = vec![zero; dim * dim];
+    out.par_iter_mut().enumerate().for_each(|(idx, val)| {
+        let row = idx / dim;
+        let col = idx % dim;
+        let a = (row & mask != 0) as usize;
+        let b = (col & mask != 0) as usize;
+        let (r0, c0) = (row & !mask, col & !mask);
+        let mut acc = zero;
+        for (a2, block_row) in superop[a][b].iter().enumerate() {
+            for (b2, s) in block_row.iter().enumerate() {
+                let src = (r0 | (a2 * mask)) * dim + (c0 | (b2 * mask));
+                acc += *s * old[src];
+            }
+        }
+        *val = acc;
+    });
+    *matrix = out;
+}
@@ -1,423 +1,3348 @@
+// pyo3 0.19's #[pymethods] expansion trips rustc's non_local_definitions lint
+#![allow(non_local_definitions)]
+
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyComplex};
 use num_complex::Complex32;
+use numpy::{PyArray1, PyArray2, ToPyArray};
 use rayon::prelude::*;
-use rustfft::{FftPlanner, num_complex::Complex};
-use std::f32::consts::PI;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use bitorder::BitOrder;
+use device::Device;
+use params::ParamRef;
+use precision::Precision;
+
+pub use amplitude::AmplitudeEstimationResult;
+pub use background::ExecutionHandle;
+pub use cache::ExpectationCache;
+pub use disk::DiskState;
+pub use emotion::EmotionalProfile;
+pub use errors::{DimensionMismatchError, InvalidGateError, QuantumEngineError, QubitIndexError};
+pub use grover::GroverOperator;
+pub use hamiltonian::Hamiltonian;
+pub use lindblad::{LindbladResult, LindbladSolver};
+pub use mitigation::ZNEResult;
+pub use mps::MPSState;
+pub use noise::{KrausChannel, NoiseModel};
+pub use observables::{Observable, PauliString};
+pub use optimizers::{Adam, GradientDescent, OptimizerBase, SPSA};
+pub use pennylane::EchoDevice;
+use observables::PauliTerm;
+pub use precision::{DensityMatrixF64, QuantumStateF64};
+pub use qudit::{QuditCircuit, QuditState};
+pub use result::ExecutionResult;
+pub use routing::CouplingMap;
+pub use simd::set_simd;
+pub use sparse::SparseState;
+pub use stabilizer::StabilizerState;
+pub use stream::AmplitudeIterator;
+pub use trajectories::TrajectoryResult;
+pub use vqe::{VQEResult, VQE};
+
+mod amplitude;
+mod background;
+mod batch;
+mod bitorder;
+mod cache;
+mod checkpoint;
+mod cirq;
+mod decompose;
+mod device;
+mod disk;
+mod distributed;
+mod draw;
+mod dynamic;
+mod emotion;
+mod errors;
+mod feedback;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod gradients;
+mod grover;
+mod hamiltonian;
+mod introspect;
+mod kernels;
+mod lindblad;
+mod linalg;
+mod mitigation;
+mod mps;
+mod optimizers;
+mod noise;
+mod observables;
+mod params;
+mod pauli;
+mod pennylane;
+mod precision;
+mod profile;
+mod qaoa;
+mod qasm;
+mod qiskit;
+mod qkernel;
+mod qpe;
+mod qudit;
+mod quil;
+mod random;
+mod reduction;
+mod resources;
+mod result;
+mod routing;
+mod rng;
+mod sampling;
+mod serialize;
+mod simd;
+mod sparse;
+mod spectrum;
+mod stabilizer;
+mod stim;
+mod stream;
+mod superop;
+mod tomography;
+mod trace;
+mod trajectories;
+mod transpile;
+mod twirl;
+mod unitary;
+mod vqe;
+
+/// Represents the type of quantum gate
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum GateType {
+    H, X, Y, Z, S, T, SDG, TDG,
+    RX(f32), RY(f32), RZ(f32),
+    /// General single-qubit rotation U3(theta, phi, lambda)
+    U(f32, f32, f32),
+    /// Phase gate diag(1, e^{i lambda})
+    P(f32),
+    CNOT, CRX(f32), CRY(f32), CRZ(f32), CH, CPHASE(f32),
+    MCX(Vec<usize>),
+    /// Single-qubit gate on the target, applied when every listed control is |1>
+    CONTROLLED(Vec<usize>, Box<GateType>),
+    SWAP(usize), ISWAP(usize),
+    /// exp(-i theta/2 P x P) on the given qubit and the gate's target
+    RXX(usize, f32), RYY(usize, f32), RZZ(usize, f32),
+    /// Dense 2^k x 2^k matrix (row-major) on the listed qubits; bit i of the
+    /// row index is qubit targets[i]
+    UNITARY(Vec<usize>, Vec<Complex32>),
+    /// Z-basis measurement of the target into classical bit .0
+    MEASURE(usize),
+    /// Measure the target and return it to |0>
+    RESET,
+    /// Gates applied only when classical bit .0 reads .1
+    #[serde(rename = "c_if")]
+    IF(usize, bool, Vec<Gate>),
+    /// Record <X>, <Y> and <Z> of every qubit under this label; the state is untouched
+    SNAPSHOT(String),
+    /// Return the amplitudes to the copy saved at the last SNAPSHOT with this label
+    RESTORE(String),
+}
+
+/// Label and per-qubit (<X>, <Y>, <Z>) recorded at a SNAPSHOT
+type Snapshot = (String, Vec<(f32, f32, f32)>);
+
+/// Represents a single gate operation in the circuit
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Gate {
+    gate_type: GateType,
+    target: usize,
+    control: Option<usize>,
+}
+
+impl GateType {
+    /// Lowercase gate name, matching the QuantumCircuit builder method
+    fn name(&self) -> &'static str {
+        match self {
+            GateType::H => "h",
+            GateType::X => "x",
+            GateType::Y => "y",
+            GateType::Z => "z",
+            GateType::S => "s",
+            GateType::T => "t",
+            GateType::SDG => "sdg",
+            GateType::TDG => "tdg",
+            GateType::RX(_) => "rx",
+            GateType::RY(_) => "ry",
+            GateType::RZ(_) => "rz",
+            GateType::U(..) => "u",
+            GateType::P(_) => "p",
+            GateType::CNOT => "cnot",
+            GateType::CRX(_) => "crx",
+            GateType::CRY(_) => "cry",
+            GateType::CRZ(_) => "crz",
+            GateType::CH => "ch",
+            GateType::CPHASE(_) => "cphase",
+            GateType::MCX(_) => "mcx",
+            GateType::CONTROLLED(..) => "controlled",
+            GateType::SWAP(_) => "swap",
+            GateType::ISWAP(_) => "iswap",
+            GateType::RXX(..) => "rxx",
+            GateType::RYY(..) => "ryy",
+            GateType::RZZ(..) => "rzz",
+            GateType::UNITARY(..) => "unitary",
+            GateType::MEASURE(_) => "measure",
+            GateType::RESET => "reset",
+            GateType::IF(..) => "c_if",
+            GateType::SNAPSHOT(_) => "snapshot",
+            GateType::RESTORE(_) => "restore",
+        }
+    }
+
+    /// The rotation angle of a parameterizable gate
+    fn angle_mut(&mut self) -> Option<&mut f32> {
+        match self {
+            GateType::RX(a) | GateType::RY(a) | GateType::RZ(a) | GateType::P(a)
+            | GateType::CRX(a) | GateType::CRY(a) | GateType::CRZ(a) | GateType::CPHASE(a)
+            | GateType::RXX(_, a) | GateType::RYY(_, a) | GateType::RZZ(_, a) => Some(a),
+            GateType::CONTROLLED(_, inner) => inner.angle_mut(),
+            _ => None,
+        }
+    }
+
+    /// True for the uncontrolled single-qubit gates, the ones CONTROLLED can wrap
+    fn is_single_qubit(&self) -> bool {
+        matches!(self,
+            GateType::H | GateType::X | GateType::Y | GateType::Z | GateType::S | GateType::T | GateType::SDG | GateType::TDG
+            | GateType::RX(_) | GateType::RY(_) | GateType::RZ(_) | GateType::U(..) | GateType::P(_))
+    }
+
+    /// Uncontrolled single-qubit gate by builder name, taking its angles from `params`
+    fn single_qubit(name: &str, params: &[f32]) -> PyResult<GateType> {
+        let arity = match name {
+            "h" | "x" | "y" | "z" | "s" | "t" | "sdg" | "tdg" => 0,
+            "rx" | "ry" | "rz" | "p" => 1,
+            "u" => 3,
+            _ => return Err(errors::InvalidGateError::new_err(format!(
+                "Unknown single-qubit gate '{}' (expected h, x, y, z, s, t, sdg, tdg, rx, ry, rz, p or u)", name))),
+        };
+        if params.len() != arity {
+            return Err(errors::InvalidGateError::new_err(format!(
+                "Gate {} takes {} parameter(s), got {}", name, arity, params.len())));
+        }
+        Ok(match name {
+            "h" => GateType::H,
+            "x" => GateType::X,
+            "y" => GateType::Y,
+            "z" => GateType::Z,
+            "s" => GateType::S,
+            "t" => GateType::T,
+            "sdg" => GateType::SDG,
+            "tdg" => GateType::TDG,
+            "rx" => GateType::RX(params[0]),
+            "ry" => GateType::RY(params[0]),
+            "rz" => GateType::RZ(params[0]),
+            "p" => GateType::P(params[0]),
+            _ => GateType::U(params[0], params[1], params[2]),
+        })
+    }
+}
+
+impl Gate {
+    /// Every qubit the gate acts on (controls first, target last)
+    fn qubits(&self) -> Vec<usize> {
+        let mut qubits: Vec<usize> = match &self.gate_type {
+            GateType::MCX(controls) | GateType::CONTROLLED(controls, _) => controls.clone(),
+            GateType::SWAP(a) | GateType::ISWAP(a)
+            | GateType::RXX(a, _) | GateType::RYY(a, _) | GateType::RZZ(a, _) => vec![*a],
+            GateType::UNITARY(targets, _) => return targets.clone(),
+            GateType::SNAPSHOT(_) | GateType::RESTORE(_) => return Vec::new(),
+            GateType::IF(_, _, body) => {
+                let mut qubits: Vec<usize> = body.iter().flat_map(|g| g.qubits()).collect();
+                qubits.sort_unstable();
+                qubits.dedup();
+                return qubits;
+            }
+            _ => self.control.into_iter().collect(),
+        };
+        qubits.push(self.target);
+        qubits
+    }
+
+    fn is_snapshot(&self) -> bool {
+        matches!(self.gate_type, GateType::SNAPSHOT(_))
+    }
+
+    /// Snapshots and restores see the whole state, so no gate may fuse,
+    /// cancel or move across them
+    fn is_barrier(&self) -> bool {
+        matches!(self.gate_type, GateType::SNAPSHOT(_) | GateType::RESTORE(_))
+    }
+
+    /// Gates implementing this gate's inverse, in application order
+    fn inverse(&self) -> Vec<Gate> {
+        let with = |gate_type: GateType| Gate { gate_type, target: self.target, control: self.control };
+        let gate_type = match &self.gate_type {
+            GateType::S => GateType::SDG,
+            GateType::T => GateType::TDG,
+            GateType::SDG => GateType::S,
+            GateType::TDG => GateType::T,
+            GateType::RX(a) => GateType::RX(-a),
+            GateType::RY(a) => GateType::RY(-a),
+            GateType::RZ(a) => GateType::RZ(-a),
+            GateType::U(theta, phi, lambda) => GateType::U(-theta, -lambda, -phi),
+            GateType::P(a) => GateType::P(-a),
+            GateType::CRX(a) => GateType::CRX(-a),
+            GateType::CRY(a) => GateType::CRY(-a),
+            GateType::CRZ(a) => GateType::CRZ(-a),
+            GateType::CPHASE(a) => GateType::CPHASE(-a),
+            GateType::RXX(q, a) => GateType::RXX(*q, -a),
+            GateType::RYY(q, a) => GateType::RYY(*q, -a),
+            GateType::RZZ(q, a) => GateType::RZZ(*q, -a),
+            GateType::CONTROLLED(controls, inner) => {
+                let inverse = with((**inner).clone()).inverse().remove(0).gate_type;
+                GateType::CONTROLLED(controls.clone(), Box::new(inverse))
+            }
+            GateType::ISWAP(a) => {
+                // iSWAP = CZ SWAP (S x S), so its inverse is (Sdg x Sdg) SWAP CZ
+                let (a, b) = (*a, self.target);
+                return vec![
+                    Gate { gate_type: GateType::CPHASE(std::f32::consts::PI), target: b, control: Some(a) },
+                    Gate { gate_type: GateType::SWAP(a), target: b, control: None },
+                    Gate { gate_type: GateType::SDG, target: a, control: None },
+                    Gate { gate_type: GateType::SDG, target: b, control: None },
+                ];
+            }
+            GateType::UNITARY(targets, m) => {
+                let dim = 1 << targets.len();
+                let dagger = (0..dim * dim).map(|idx| m[(idx % dim) * dim + idx / dim].conj()).collect();
+                GateType::UNITARY(targets.clone(), dagger)
+            }
+            // H, X, Y, Z, CNOT, CH, MCX and SWAP are self-inverse, snapshots stay put; measurement,
+            // reset, c_if and restore have no inverse and are rejected by QuantumCircuit::inverse
+            other => other.clone(),
+        };
+        vec![with(gate_type)]
+    }
+
+    /// Copy of this gate with every qubit index moved up by `offset`
+    fn shifted(&self, offset: usize) -> Gate {
+        self.remapped(|q| q + offset)
+    }
+
+    /// Copy of this gate with every qubit index q replaced by map(q)
+    fn remapped(&self, map: impl Fn(usize) -> usize) -> Gate {
+        self.relabelled(&map, &|b| b)
+    }
+
+    /// Copy of this gate with qubit q moved to qmap(q) and classical bit b to cmap(b)
+    fn relabelled(&self, map: &dyn Fn(usize) -> usize, cmap: &dyn Fn(usize) -> usize) -> Gate {
+        let gate_type = match &self.gate_type {
+            GateType::MCX(controls) => GateType::MCX(controls.iter().map(|&c| map(c)).collect()),
+            GateType::CONTROLLED(controls, inner) => GateType::CONTROLLED(controls.iter().map(|&c| map(c)).collect(), inner.clone()),
+            GateType::SWAP(a) => GateType::SWAP(map(*a)),
+            GateType::ISWAP(a) => GateType::ISWAP(map(*a)),
+            GateType::RXX(a, theta) => GateType::RXX(map(*a), *theta),
+            GateType::RYY(a, theta) => GateType::RYY(map(*a), *theta),
+            GateType::RZZ(a, theta) => GateType::RZZ(map(*a), *theta),
+            GateType::UNITARY(targets, m) => GateType::UNITARY(targets.iter().map(|&q| map(q)).collect(), m.clone()),
+            GateType::MEASURE(bit) => GateType::MEASURE(cmap(*bit)),
+            GateType::IF(bit, value, body) => {
+                GateType::IF(cmap(*bit), *value, body.iter().map(|g| g.relabelled(map, cmap)).collect())
+            }
+            other => other.clone(),
+        };
+        Gate {
+            gate_type,
+            target: map(self.target),
+            control: self.control.map(map),
+        }
+    }
+}
+
+/// A quantum circuit builder that mimics Qiskit/PennyLane architecture
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuantumCircuit {
+    qubits: usize,
+    clbits: usize,
+    gates: Vec<Gate>,
+    params: Vec<ParamRef>,
+    /// Amplitudes execution starts from; |0...0> when None
+    initial: Option<Vec<Complex32>>,
+}
+
+#[pymethods]
+impl QuantumCircuit {
+    #[new]
+    #[pyo3(signature = (qubits, clbits=0))]
+    pub fn new(qubits: usize, clbits: usize) -> Self {
+        QuantumCircuit {
+            qubits,
+            clbits,
+            gates: Vec::new(),
+            params: Vec::new(),
+            initial: None,
+        }
+    }
+
+    /// Pickle support: unpickling calls QuantumCircuit(qubits, clbits) and then
+    /// restores the `to_bytes` form
+    pub fn __getnewargs__(&self) -> (usize, usize) {
+        (self.qubits, self.clbits)
+    }
+
+    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
+        self.to_bytes(py)
+    }
+
+    pub fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = serialize::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Start execution from a basis state given as a bitstring (character i is
+    /// qubit i), a list of (re, im) amplitudes, or a QuantumState
+    pub fn initialize(&mut self, state: &PyAny) -> PyResult<()> {
+        let initial = if let Ok(bits) = state.extract::<&str>() {
+            if bits.len() != self.qubits || bits.chars().any(|ch| ch != '0' && ch != '1') {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Bitstring must have {} characters, each '0' or '1'", self.qubits)));
+            }
+            resources::check_state::<Complex32>(self.qubits)?;
+            let idx = bits.chars().enumerate().filter(|(_, ch)| *ch == '1').fold(0, |acc, (q, _)| acc | (1 << q));
+            let mut amps = vec![Complex32::new(0.0, 0.0); 1 << self.qubits];
+            amps[idx] = Complex32::new(1.0, 0.0);
+            QuantumState { qubits: self.qubits, state: amps, phase: 0.0, drift: 0.0 }
+        } else if let Ok(prepared) = state.extract::<PyRef<QuantumState>>() {
+            prepared.clone()
+        } else {
+            QuantumState::from_amplitudes(state.extract()?)?
+        };
+        if initial.qubits != self.qubits {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "Initial state has {} qubits but the circuit has {}", initial.qubits, self.qubits)));
+        }
+        self.initial = Some(initial.state);
+        Ok(())
+    }
+
+    /// Size of the classical register
+    #[getter]
+    pub fn clbits(&self) -> usize {
+        self.clbits
+    }
+
+    pub fn h(&mut self, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::H, target, control: None })
+    }
+
+    pub fn x(&mut self, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::X, target, control: None })
+    }
+
+    pub fn y(&mut self, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::Y, target, control: None })
+    }
+
+    pub fn z(&mut self, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::Z, target, control: None })
+    }
+
+    pub fn s(&mut self, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::S, target, control: None })
+    }
+
+    pub fn t(&mut self, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::T, target, control: None })
+    }
+
+    /// S^dagger = diag(1, -i)
+    pub fn sdg(&mut self, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::SDG, target, control: None })
+    }
+
+    /// T^dagger = diag(1, e^(-i pi/4))
+    pub fn tdg(&mut self, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::TDG, target, control: None })
+    }
+
+    pub fn rx(&mut self, target: usize, theta: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::RX(theta), target, control: None })
+    }
+
+    pub fn ry(&mut self, target: usize, theta: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::RY(theta), target, control: None })
+    }
+
+    pub fn rz(&mut self, target: usize, phi: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::RZ(phi), target, control: None })
+    }
+
+    /// U3(theta, phi, lambda) = RZ(phi) RY(theta) RZ(lambda) up to global phase
+    pub fn u(&mut self, target: usize, theta: f32, phi: f32, lambda: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::U(theta, phi, lambda), target, control: None })
+    }
+
+    pub fn p(&mut self, target: usize, lambda: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::P(lambda), target, control: None })
+    }
+
+    pub fn cnot(&mut self, control: usize, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::CNOT, target, control: Some(control) })
+    }
+    
+    pub fn crx(&mut self, control: usize, target: usize, theta: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::CRX(theta), target, control: Some(control) })
+    }
+
+    pub fn cry(&mut self, control: usize, target: usize, theta: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::CRY(theta), target, control: Some(control) })
+    }
+
+    pub fn crz(&mut self, control: usize, target: usize, phi: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::CRZ(phi), target, control: Some(control) })
+    }
+
+    /// Controlled Hadamard
+    pub fn ch(&mut self, control: usize, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::CH, target, control: Some(control) })
+    }
+
+    /// Controlled phase: multiplies |11> by e^(i phi)
+    pub fn cphase(&mut self, control: usize, target: usize, phi: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::CPHASE(phi), target, control: Some(control) })
+    }
+
+    /// Toffoli gate: X on target when both controls are |1>
+    pub fn ccx(&mut self, c1: usize, c2: usize, target: usize) -> PyResult<()> {
+        self.mcx(vec![c1, c2], target)
+    }
+
+    /// Exchange the states of qubits a and b
+    pub fn swap(&mut self, a: usize, b: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::SWAP(a), target: b, control: None })
+    }
+
+    /// iSWAP: exchange qubits a and b, adding a phase of i to |01> and |10>
+    pub fn iswap(&mut self, a: usize, b: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::ISWAP(a), target: b, control: None })
+    }
+
+    /// Ising XX interaction exp(-i theta/2 X_a X_b)
+    pub fn rxx(&mut self, a: usize, b: usize, theta: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::RXX(a, theta), target: b, control: None })
+    }
+
+    /// Ising YY interaction exp(-i theta/2 Y_a Y_b)
+    pub fn ryy(&mut self, a: usize, b: usize, theta: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::RYY(a, theta), target: b, control: None })
+    }
+
+    /// Ising ZZ interaction exp(-i theta/2 Z_a Z_b)
+    pub fn rzz(&mut self, a: usize, b: usize, theta: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::RZZ(a, theta), target: b, control: None })
+    }
+
+    /// System-environment coupling exp(-i theta/2 sum_P P_system P_environment)
+    /// over the Paulis of `interaction`: "xx", "yy", "zz", "xy" (exchange,
+    /// XX + YY) or "heisenberg" (XX + YY + ZZ). The terms commute, so the
+    /// rxx / ryy / rzz gates it appends implement the exponential exactly.
+    #[pyo3(signature = (system, environment, theta, interaction="heisenberg"))]
+    pub fn couple(&mut self, system: usize, environment: usize, theta: f32, interaction: &str) -> PyResult<()> {
+        let (x, y, z) = match interaction.to_ascii_lowercase().as_str() {
+            "xx" => (true, false, false),
+            "yy" => (false, true, false),
+            "zz" => (false, false, true),
+            "xy" => (true, true, false),
+            "heisenberg" => (true, true, true),
+            _ => return Err(errors::InvalidGateError::new_err(format!(
+                "Unknown interaction '{}' (expected 'xx', 'yy', 'zz', 'xy' or 'heisenberg')", interaction))),
+        };
+        let terms = [(x, GateType::RXX(system, theta)), (y, GateType::RYY(system, theta)), (z, GateType::RZZ(system, theta))];
+        let gates: Vec<Gate> = terms.into_iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, gate_type)| Gate { gate_type, target: environment, control: None })
+            .collect();
+        // Validate all before appending any, so a bad qubit leaves the circuit unchanged
+        for gate in &gates {
+            self.check_gate(gate)?;
+        }
+        self.gates.extend(gates);
+        Ok(())
+    }
+
+    /// Multi-controlled X: X on target when every control is |1>
+    pub fn mcx(&mut self, controls: Vec<usize>, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::MCX(controls), target, control: None })
+    }
+
+    /// Multi-controlled Z: flips the sign of the state where every control and the target are |1>
+    pub fn mcz(&mut self, controls: Vec<usize>, target: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::CONTROLLED(controls, Box::new(GateType::Z)), target, control: None })
+    }
+
+    /// Multi-controlled phase: multiplies the state where every control and the
+    /// target are |1> by e^(i lambda); mcp(controls, t, pi) is mcz(controls, t)
+    pub fn mcp(&mut self, controls: Vec<usize>, target: usize, lambda: f32) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::CONTROLLED(controls, Box::new(GateType::P(lambda))), target, control: None })
+    }
+
+    /// Single-qubit gate `gate` (a builder name such as "h", "s", "rx" or "u") on
+    /// target, applied only when every control is |1>: controlled("z", [0, 1], 2)
+    /// is CCZ, controlled("rx", [0], 1, [theta]) is CRX
+    #[pyo3(signature = (gate, controls, target, params=vec![]))]
+    pub fn controlled(&mut self, gate: &str, controls: Vec<usize>, target: usize, params: Vec<f32>) -> PyResult<()> {
+        let inner = GateType::single_qubit(gate, &params)?;
+        self.push(Gate { gate_type: GateType::CONTROLLED(controls, Box::new(inner)), target, control: None })
+    }
+
+    /// Arbitrary unitary on `targets` from a 2^k x 2^k matrix of (re, im) entries,
+    /// where bit i of the row/column index is qubit targets[i]
+    pub fn unitary(&mut self, matrix: Vec<Vec<(f32, f32)>>, targets: Vec<usize>) -> PyResult<()> {
+        self.check_distinct(&targets)?;
+        if targets.is_empty() {
+            return Err(errors::InvalidGateError::new_err("Unitary needs at least one target qubit"));
+        }
+        let dim = 1 << targets.len();
+        if matrix.len() != dim || matrix.iter().any(|row| row.len() != dim) {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "Matrix for {} target qubit(s) must be {} x {}", targets.len(), dim, dim)));
+        }
+        let flat: Vec<Complex32> = matrix.iter().flatten().map(|&(re, im)| Complex32::new(re, im)).collect();
+        if !linalg::is_unitary(&linalg::to_c64(&flat), dim, 1e-4) {
+            return Err(errors::InvalidGateError::new_err("Matrix is not unitary"));
+        }
+        let target = targets[0];
+        self.gates.push(Gate { gate_type: GateType::UNITARY(targets, flat), target, control: None });
+        Ok(())
+    }
+
+    /// Measure `qubit` mid-circuit into classical bit `creg_bit`, growing the
+    /// classical register if needed
+    pub fn measure_qubit(&mut self, qubit: usize, creg_bit: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::MEASURE(creg_bit), target: qubit, control: None })?;
+        self.clbits = self.clbits.max(creg_bit + 1);
+        Ok(())
+    }
+
+    /// Measure `qubit` in the "X", "Y" or "Z" basis into classical bit `creg_bit`,
+    /// leaving it in the observed eigenstate of that basis
+    pub fn measure_basis(&mut self, qubit: usize, basis: &str, creg_bit: usize) -> PyResult<()> {
+        distinct_qubits(&[qubit], self.qubits)?;
+        match basis {
+            "X" | "x" => {
+                self.h(qubit)?;
+                self.measure_qubit(qubit, creg_bit)?;
+                self.h(qubit)?;
+            }
+            // Sdg then H takes the Y eigenbasis onto the Z eigenbasis
+            "Y" | "y" => {
+                self.sdg(qubit)?;
+                self.h(qubit)?;
+                self.measure_qubit(qubit, creg_bit)?;
+                self.h(qubit)?;
+                self.s(qubit)?;
+            }
+            "Z" | "z" => self.measure_qubit(qubit, creg_bit)?,
+            _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown measurement basis '{}' (expected 'X', 'Y' or 'Z')", basis))),
+        }
+        Ok(())
+    }
+
+    /// Mark a point where `execute_snapshots` records <X>, <Y> and <Z> of every
+    /// qubit and where execute and run save the state for `restore`; other
+    /// executors ignore it
+    pub fn snapshot(&mut self, label: &str) {
+        self.gates.push(Gate { gate_type: GateType::SNAPSHOT(label.to_string()), target: 0, control: None });
+    }
+
+    /// Return the state to the copy saved at the latest `snapshot(label)`, so
+    /// gates after it continue from that prefix instead; classical bits keep
+    /// their values. Only execute and run support it.
+    pub fn restore(&mut self, label: &str) -> PyResult<()> {
+        if !self.gates.iter().any(|g| matches!(&g.gate_type, GateType::SNAPSHOT(l) if l == label)) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "restore({:?}) needs an earlier snapshot with that label", label)));
+        }
+        self.gates.push(Gate { gate_type: GateType::RESTORE(label.to_string()), target: 0, control: None });
+        Ok(())
+    }
+
+    /// Return `qubit` to |0> (a measurement followed by X on outcome 1)
+    pub fn reset(&mut self, qubit: usize) -> PyResult<()> {
+        self.push(Gate { gate_type: GateType::RESET, target: qubit, control: None })
+    }
+
+    /// Append the gates of `body` (on the same qubit labels) to run only when
+    /// classical bit `creg_bit` was measured as `value`
+    #[pyo3(signature = (creg_bit, body, value=1))]
+    pub fn c_if(&mut self, creg_bit: usize, body: &QuantumCircuit, value: u8) -> PyResult<()> {
+        if creg_bit >= self.clbits {
+            return Err(pyo3::exceptions::PyValueError::new_err("Classical bit index out of range"));
+        }
+        if value > 1 {
+            return Err(pyo3::exceptions::PyValueError::new_err("c_if value must be 0 or 1"));
+        }
+        if body.qubits > self.qubits {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "c_if body has {} qubits but the circuit has {}", body.qubits, self.qubits)));
+        }
+        let gates = body.bound_gates()?.to_vec();
+        if gates.iter().any(|g| g.is_barrier()) {
+            return Err(pyo3::exceptions::PyValueError::new_err("c_if bodies cannot contain snapshots or restores"));
+        }
+        self.check_clbits(&gates)?;
+        let Some(first) = gates.first() else {
+            return Err(pyo3::exceptions::PyValueError::new_err("c_if body has no gates"));
+        };
+        let target = first.target;
+        self.gates.push(Gate { gate_type: GateType::IF(creg_bit, value == 1, gates), target, control: None });
+        Ok(())
+    }
+
+    /// Quantum Fourier transform on `qubits` (qubits[0] least significant):
+    /// |x> -> 2^(-m/2) sum_y e^(2 pi i x y / 2^m) |y>, built from H and controlled
+    /// phases followed by the bit-reversal swaps
+    #[pyo3(signature = (qubits, swaps=true))]
+    pub fn qft(&mut self, qubits: Vec<usize>, swaps: bool) -> PyResult<()> {
+        self.check_distinct(&qubits)?;
+        for j in (0..qubits.len()).rev() {
+            self.h(qubits[j])?;
+            for k in (0..j).rev() {
+                self.cphase(qubits[k], qubits[j], qft_angle(j - k))?;
+            }
+        }
+        if swaps {
+            for k in 0..qubits.len() / 2 {
+                self.swap(qubits[k], qubits[qubits.len() - 1 - k])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Inverse QFT: the gates of `qft` in reverse order with negated phases
+    #[pyo3(signature = (qubits, swaps=true))]
+    pub fn iqft(&mut self, qubits: Vec<usize>, swaps: bool) -> PyResult<()> {
+        self.check_distinct(&qubits)?;
+        if swaps {
+            for k in (0..qubits.len() / 2).rev() {
+                self.swap(qubits[k], qubits[qubits.len() - 1 - k])?;
+            }
+        }
+        for j in 0..qubits.len() {
+            for k in 0..j {
+                self.cphase(qubits[k], qubits[j], -qft_angle(j - k))?;
+            }
+            self.h(qubits[j])?;
+        }
+        Ok(())
+    }
+
+    /// RX whose angle is the named parameter, supplied later via bind/execute_bound
+    pub fn rx_param(&mut self, target: usize, name: &str) -> PyResult<()> {
+        self.push_param(Gate { gate_type: GateType::RX(0.0), target, control: None }, name)
+    }
+
+    pub fn ry_param(&mut self, target: usize, name: &str) -> PyResult<()> {
+        self.push_param(Gate { gate_type: GateType::RY(0.0), target, control: None }, name)
+    }
+
+    pub fn rz_param(&mut self, target: usize, name: &str) -> PyResult<()> {
+        self.push_param(Gate { gate_type: GateType::RZ(0.0), target, control: None }, name)
+    }
+
+    pub fn crx_param(&mut self, control: usize, target: usize, name: &str) -> PyResult<()> {
+        self.push_param(Gate { gate_type: GateType::CRX(0.0), target, control: Some(control) }, name)
+    }
+
+    pub fn cry_param(&mut self, control: usize, target: usize, name: &str) -> PyResult<()> {
+        self.push_param(Gate { gate_type: GateType::CRY(0.0), target, control: Some(control) }, name)
+    }
+
+    pub fn crz_param(&mut self, control: usize, target: usize, name: &str) -> PyResult<()> {
+        self.push_param(Gate { gate_type: GateType::CRZ(0.0), target, control: Some(control) }, name)
+    }
+
+    /// QAOA ansatz for a diagonal (I/Z) cost Hamiltonian: |+>^n followed by
+    /// `p_layers` of exp(-i gamma_k C) exp(-i beta_k sum_j X_j). Omitted angle
+    /// lists become the symbolic parameters gamma_k / beta_k.
+    #[staticmethod]
+    #[pyo3(signature = (cost_hamiltonian, p_layers, gammas=None, betas=None, qubits=None))]
+    pub fn qaoa(
+        cost_hamiltonian: &PyAny,
+        p_layers: usize,
+        gammas: Option<Vec<f32>>,
+        betas: Option<Vec<f32>>,
+        qubits: Option<usize>,
+    ) -> PyResult<QuantumCircuit> {
+        let terms = observables::extract_terms(cost_hamiltonian, qubits.unwrap_or(usize::BITS as usize))?;
+        let qubits = qubits.unwrap_or_else(|| qaoa::width(&terms));
+        qaoa::build(&terms, qubits, p_layers, gammas, betas)
+    }
+
+    /// Layered random circuit: every layer puts a gate drawn from the
+    /// single-qubit names in `gate_set` on each qubit (angles uniform in
+    /// [0, 2pi)) and then a gate drawn from its two-qubit names on neighbouring
+    /// pairs, (0, 1), (2, 3), ... in even layers and (1, 2), (3, 4), ... in odd
+    /// ones. The default gate set is rx, ry, rz and cnot.
+    #[staticmethod]
+    #[pyo3(signature = (qubits, depth, seed=None, gate_set=None))]
+    pub fn random(qubits: usize, depth: usize, seed: Option<u64>, gate_set: Option<Vec<String>>) -> PyResult<QuantumCircuit> {
+        let gate_set = gate_set.unwrap_or_else(|| random::DEFAULT_GATE_SET.iter().map(|g| g.to_string()).collect());
+        random::build(qubits, depth, seed, &gate_set)
+    }
+
+    /// Empty circuit on `n` qudits of `d` levels each (2 to 10), with the
+    /// generalized X, Z, Fourier (Hadamard) and controlled gates of QuditCircuit
+    #[staticmethod]
+    pub fn new_qudit(n: usize, d: usize) -> PyResult<QuditCircuit> {
+        QuditCircuit::new(n, d)
+    }
+
+    /// (|00> + |11>)/sqrt(2) on qubits `a` and `b`: H(a), CNOT(a, b). The
+    /// register has max(a, b) + 1 qubits unless `qubits` is given.
+    #[staticmethod]
+    #[pyo3(signature = (a=0, b=1, qubits=None))]
+    pub fn bell(a: usize, b: usize, qubits: Option<usize>) -> PyResult<QuantumCircuit> {
+        if a == b {
+            return Err(errors::InvalidGateError::new_err("A Bell pair needs two distinct qubits"));
+        }
+        let mut circuit = QuantumCircuit::new(qubits.unwrap_or(a.max(b) + 1), 0);
+        circuit.h(a)?;
+        circuit.cnot(a, b)?;
+        Ok(circuit)
+    }
+
+    /// (|0...0> + |1...1>)/sqrt(2) on `n` qubits: H(0) and a CNOT chain
+    #[staticmethod]
+    pub fn ghz(n: usize) -> PyResult<QuantumCircuit> {
+        if n == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("A GHZ state needs at least one qubit"));
+        }
+        let mut circuit = QuantumCircuit::new(n, 0);
+        circuit.h(0)?;
+        for q in 1..n {
+            circuit.cnot(q - 1, q)?;
+        }
+        Ok(circuit)
+    }
+
+    /// (|10...0> + |01...0> + ... + |0...01>)/sqrt(n) on `n` qubits. X(0)
+    /// excites qubit 0; step k keeps the excitation on qubit k with amplitude
+    /// sqrt(1/(n-k)) and passes the rest on with CRY(k, k+1) and CNOT(k+1, k).
+    #[staticmethod]
+    pub fn w_state(n: usize) -> PyResult<QuantumCircuit> {
+        if n == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("A W state needs at least one qubit"));
+        }
+        let mut circuit = QuantumCircuit::new(n, 0);
+        circuit.x(0)?;
+        for k in 0..n - 1 {
+            let theta = 2.0 * (1.0 / (n - k) as f64).sqrt().acos();
+            circuit.cry(k, k + 1, theta as f32)?;
+            circuit.cnot(k + 1, k)?;
+        }
+        Ok(circuit)
+    }
+
+    /// Phase estimation of `unitary` with `precision_qubits` counting qubits on
+    /// qubits 0..m and the unitary's qubits above them. Prepare an eigenstate on
+    /// the upper qubits; the counting register then reads y ~ 2^m phi for the
+    /// eigenvalue e^(2 pi i phi).
+    #[staticmethod]
+    pub fn phase_estimation(unitary: &QuantumCircuit, precision_qubits: usize) -> PyResult<QuantumCircuit> {
+        qpe::build(unitary, precision_qubits)
+    }
+
+    /// Append the gates (and symbolic parameters) of `other`, with its qubit i
+    /// placed on `qubit_map[i]` (identity mapping when omitted)
+    #[pyo3(signature = (other, qubit_map=None))]
+    pub fn compose(slf: &PyCell<Self>, other: &PyCell<QuantumCircuit>, qubit_map: Option<Vec<usize>>) -> PyResult<()> {
+        // Clone first so that c.compose(c) does not hit a borrow conflict
+        let other = other.borrow().clone();
+        let mut this = slf.borrow_mut();
+        let qubit_map = qubit_map.unwrap_or_else(|| (0..other.qubits).collect());
+        if qubit_map.len() != other.qubits {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "qubit_map must have one entry per qubit of the appended circuit ({})", other.qubits)));
+        }
+        this.check_distinct(&qubit_map)?;
+        this.clbits = this.clbits.max(other.clbits);
+        this.append(&other, |q| qubit_map[q], |b| b);
+        Ok(())
+    }
+
+    /// Side-by-side circuit on self.qubits + other.qubits qubits: this circuit on
+    /// the low qubits, `other` on the qubits above it (and likewise for classical bits)
+    pub fn tensor(&self, other: &QuantumCircuit) -> PyResult<QuantumCircuit> {
+        let mut out = self.clone();
+        if self.initial.is_some() || other.initial.is_some() {
+            resources::check_state::<Complex32>(self.qubits + other.qubits)?;
+            let (low, high) = (self.initial_state()?, other.initial_state()?);
+            let amps = (0..1usize << (self.qubits + other.qubits))
+                .map(|i| low.state[i & ((1 << self.qubits) - 1)] * high.state[i >> self.qubits])
+                .collect();
+            out.initial = Some(amps);
+        }
+        out.qubits += other.qubits;
+        out.clbits += other.clbits;
+        out.append(other, |q| q + self.qubits, |b| b + self.clbits);
+        Ok(out)
+    }
+
+    /// Copy with qubit q moved to mapping[q]; qubits missing from `mapping`
+    /// keep their index. The images must be distinct, and mapping onto indices
+    /// past the last qubit widens the circuit (an initialized state is padded
+    /// with |0> on the new qubits).
+    pub fn remap(&self, mapping: HashMap<usize, usize>) -> PyResult<QuantumCircuit> {
+        if let Some(&q) = mapping.keys().find(|&&q| q >= self.qubits) {
+            return Err(errors::QubitIndexError::new_err(format!(
+                "Cannot remap qubit {} of a {}-qubit circuit", q, self.qubits)));
+        }
+        let destination: Vec<usize> = (0..self.qubits).map(|q| mapping.get(&q).copied().unwrap_or(q)).collect();
+        let n = destination.iter().map(|&d| d.saturating_add(1)).max().unwrap_or(0).max(self.qubits);
+        let mut seen = HashSet::new();
+        for &d in &destination {
+            if !seen.insert(d) {
+                return Err(errors::InvalidGateError::new_err(format!("Two qubits are remapped onto qubit {}", d)));
+            }
+        }
+        let mut out = QuantumCircuit::new(n, self.clbits);
+        if let Some(amps) = &self.initial {
+            resources::check_state::<Complex32>(n)?;
+            // Qubits the circuit gains start in |0>, so they take the remaining indices
+            let mut full = destination.clone();
+            full.extend((0..n).filter(|d| !seen.contains(d)));
+            let mut padded = amps.clone();
+            padded.resize(1 << n, Complex32::new(0.0, 0.0));
+            out.initial = Some(bitorder::permute_qubits(&padded, &full));
+        }
+        out.append(self, |q| destination[q], |b| b);
+        Ok(out)
+    }
+
+    /// Copy with qubit q moved to qubits - 1 - q and classical bit b to
+    /// clbits - 1 - b, so its little-endian output reads like the original's
+    /// big-endian output (Qiskit's ordering)
+    pub fn reverse_bits(&self) -> QuantumCircuit {
+        let (n, n_c) = (self.qubits, self.clbits);
+        let mut out = QuantumCircuit::new(n, n_c);
+        out.initial = self.initial.clone().map(|amps| BitOrder::Big.permute(amps, n));
+        out.append(self, |q| n - 1 - q, |b| n_c - 1 - b);
+        out
+    }
+
+    /// New circuit implementing the adjoint: gates reversed and each replaced by
+    /// its inverse. Symbolic angles stay symbolic with their sign flipped.
+    pub fn inverse(&self) -> PyResult<QuantumCircuit> {
+        if dynamic::is_dynamic(&self.gates) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Circuits with measurement, reset, c_if or restore have no inverse"));
+        }
+        if self.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Circuits with an initialized state have no inverse"));
+        }
+        let mut out = QuantumCircuit::new(self.qubits, self.clbits);
+        for (k, gate) in self.gates.iter().enumerate().rev() {
+            let inverse = gate.inverse();
+            match self.params.iter().find(|r| r.gate == k) {
+                Some(r) => out.push_scaled_param(inverse[0].clone(), &r.name, -r.scale)?,
+                None => out.gates.extend(inverse),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Names of the unbound parameters, in order of first use
+    pub fn parameters(&self) -> Vec<String> {
+        params::names(&self.params)
+    }
+
+    /// Copy of the circuit with the given parameters substituted; any not in
+    /// `values` stay symbolic
+    pub fn bind(&self, values: HashMap<String, f32>) -> PyResult<QuantumCircuit> {
+        let (gates, params) = params::bind(&self.gates, &self.params, &values)?;
+        Ok(QuantumCircuit { gates, params, ..self.clone() })
+    }
+
+    /// Bind every parameter and execute, without keeping the bound circuit
+    pub fn execute_bound(&self, py: Python, values: HashMap<String, f32>) -> PyResult<QuantumState> {
+        let bound = self.bind(values)?;
+        py.allow_threads(|| bound.simulate(&mut dynamic::ClassicalRegister::new(self.clbits, None), 1))
+    }
+
+    /// <O> at every parameter set, each a list of values in `parameters()` order;
+    /// the sets are evaluated in parallel. With an `ExpectationCache`, sets
+    /// already evaluated for this circuit and observable are not simulated again.
+    #[pyo3(signature = (param_sets, observable, cache=None))]
+    pub fn execute_batch(&self, py: Python, param_sets: Vec<Vec<f32>>, observable: &PyAny,
+        cache: Option<PyRef<ExpectationCache>>) -> PyResult<Vec<f32>> {
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        let names = self.parameters();
+        if let Some((i, set)) = param_sets.iter().enumerate().find(|(_, set)| set.len() != names.len()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Parameter set {} has {} values, expected {}", i, set.len(), names.len())));
+        }
+        let initial = self.initial_state()?;
+        let evaluate = |sets: &[&Vec<f32>]| -> PyResult<Vec<f32>> {
+            py.allow_threads(|| {
+                sets.par_iter()
+                    .map(|values| {
+                        let gates = params::assign(&self.gates, &self.params, &names, values);
+                        gradients::energy(&initial, &transpile::fuse(&gates, 1), &terms)
+                    })
+                    .collect()
+            })
+        };
+        let Some(cache) = cache else {
+            return evaluate(&param_sets.iter().collect::<Vec<_>>());
+        };
+
+        // Look every set up first, simulating each distinct missing point once
+        let scope = cache::Scope::new(self, &terms)?;
+        let keys: Vec<cache::Key> = param_sets.iter().map(|set| scope.key(set)).collect();
+        let mut values: Vec<Option<f32>> = keys.iter().map(|key| cache.get(key)).collect();
+        let mut pending: HashMap<&cache::Key, usize> = HashMap::new();
+        let mut missing = Vec::new();
+        for (i, key) in keys.iter().enumerate() {
+            if values[i].is_none() && !pending.contains_key(key) {
+                pending.insert(key, missing.len());
+                missing.push(&param_sets[i]);
+            }
+        }
+        let computed = evaluate(&missing)?;
+        for (key, &slot) in &pending {
+            cache.insert((*key).clone(), computed[slot]);
+        }
+        for (value, key) in values.iter_mut().zip(&keys) {
+            if value.is_none() {
+                *value = Some(computed[pending[key]]);
+            }
+        }
+        Ok(values.into_iter().flatten().collect())
+    }
+
+    /// d<O>/dtheta for every parameter (in `parameters()` order) at `param_values`,
+    /// via the parameter-shift rule with the shifted circuits run in parallel
+    pub fn gradients(&self, py: Python, observable: &PyAny, param_values: HashMap<String, f32>) -> PyResult<Vec<f32>> {
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        let bound = self.bind(param_values)?;
+        let gates = bound.bound_gates()?;
+        py.allow_threads(|| {
+            gradients::parameter_shift(&self.initial_state()?, gates, &self.params, &self.parameters(), &terms)
+        })
+    }
+
+    /// Adjoint-method gradients from a single forward and backward pass.
+    /// With `param_values`, returns d<O>/dtheta per parameter in `parameters()` order;
+    /// for a circuit without symbols, returns one derivative per rotation gate.
+    #[pyo3(signature = (observable, param_values=None))]
+    pub fn gradients_adjoint(&self, py: Python, observable: &PyAny, param_values: Option<HashMap<String, f32>>) -> PyResult<Vec<f32>> {
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        let values = param_values.unwrap_or_default();
+        py.allow_threads(|| self.adjoint_gradients(&terms, &values))
+    }
+
+    /// Serialize the circuit (gates, symbolic parameters and initial state) as JSON
+    pub fn to_json(&self) -> PyResult<String> {
+        serialize::to_json(self)
+    }
+
+    /// Rebuild a circuit from `to_json` output
+    #[staticmethod]
+    pub fn from_json(src: &str) -> PyResult<QuantumCircuit> {
+        serialize::from_json(src)
+    }
+
+    /// Compact binary form of `to_json`
+    pub fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
+        Ok(PyBytes::new(py, &serialize::to_bytes(self)?))
+    }
+
+    /// Rebuild a circuit from `to_bytes` output
+    #[staticmethod]
+    pub fn from_bytes(data: &[u8]) -> PyResult<QuantumCircuit> {
+        serialize::from_bytes(data)
+    }
+
+    /// Build a circuit from an OpenQASM 2.0 program
+    #[staticmethod]
+    pub fn from_qasm(src: &str) -> PyResult<Self> {
+        let (qubits, clbits, gates) = qasm::parse(src)?;
+        let mut circuit = QuantumCircuit::new(qubits, clbits);
+        for gate in gates {
+            circuit.push(gate)?;
+        }
+        Ok(circuit)
+    }
+
+    /// Serialize the circuit as an OpenQASM 2.0 program
+    pub fn to_qasm(&self) -> PyResult<String> {
+        if self.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err("initialize has no OpenQASM 2.0 equivalent"));
+        }
+        qasm::emit(self.qubits, self.clbits, self.bound_gates()?)
+    }
+
+    /// Serialize the circuit as cirq.Circuit JSON (readable with cirq.read_json),
+    /// on LineQubits and with one Moment per circuit layer
+    pub fn to_cirq_json(&self) -> PyResult<String> {
+        if self.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err("initialize has no Cirq JSON equivalent"));
+        }
+        cirq::to_json(self.qubits, self.clbits, self.bound_gates()?)
+    }
+
+    /// Serialize the circuit as a Quil program, with measurements into `ro`
+    pub fn to_quil(&self) -> PyResult<String> {
+        if self.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err("initialize has no Quil equivalent"));
+        }
+        quil::emit(self.clbits, self.bound_gates()?)
+    }
+
+    /// Convert a qiskit.QuantumCircuit, keeping single-parameter symbolic angles,
+    /// measurements, resets and if_else blocks; gates without an engine
+    /// equivalent are imported as unitaries via their to_matrix()
+    #[staticmethod]
+    pub fn from_qiskit(qc: &PyAny) -> PyResult<QuantumCircuit> {
+        qiskit::from_qiskit(qc)
+    }
+
+    /// Equivalent qiskit.QuantumCircuit (qiskit must be installed); symbolic
+    /// angles become qiskit Parameters of the same names
+    pub fn to_qiskit(&self, py: Python) -> PyResult<PyObject> {
+        qiskit::to_qiskit(py, self)
+    }
+
+    /// Execute the circuit and return the resulting quantum state
+    /// (a QuantumStateF64 when `precision="f64"`). Mid-circuit measurements
+    /// collapse the state, drawing outcomes from `seed`. `device="gpu"` runs
+    /// the f32 state vector on the wgpu backend when built with the `gpu`
+    /// feature, for circuits without mid-circuit measurement. Gates are fused at `transpile_level` first
+    /// (see `transpile`); f64 execution skips fusion, since fused matrices are
+    /// stored in single precision. `backend="sparse"` returns a SparseState that
+    /// stores only non-zero amplitudes. With `renormalize_every=k` the f32
+    /// state-vector run checks the norm every k gates and renormalizes when
+    /// |1 - norm| exceeds `renormalize_tolerance`; the state's `norm_drift`
+    /// reports the total deviation removed.
+    #[pyo3(signature = (precision="f32", seed=None, device="cpu", transpile_level=1, backend="statevector",
+                        renormalize_every=0, renormalize_tolerance=1e-5))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute(&self, py: Python, precision: &str, seed: Option<u64>, device: &str, transpile_level: usize, backend: &str,
+                   renormalize_every: usize, renormalize_tolerance: f64) -> PyResult<PyObject> {
+        let device = Device::from_name(device)?;
+        transpile::check_level(transpile_level)?;
+        let precision = Precision::from_name(precision)?;
+        match device {
+            Device::Cpu => {}
+            #[cfg(feature = "gpu")]
+            Device::Gpu => {
+                if precision == Precision::F64 || !backend.eq_ignore_ascii_case("statevector") || renormalize_every > 0 {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "device='gpu' runs the f32 statevector backend without renormalize_every"));
+                }
+                return Ok(py.allow_threads(|| gpu::run(self, transpile_level))?.into_py(py));
+            }
+        }
+        if renormalize_every > 0 && (precision == Precision::F64 || !backend.eq_ignore_ascii_case("statevector")) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "renormalize_every applies to the f32 statevector backend only"));
+        }
+        match backend.to_ascii_lowercase().as_str() {
+            "statevector" => {}
+            "sparse" if precision == Precision::F64 => {
+                return Err(pyo3::exceptions::PyValueError::new_err("The sparse backend runs in single precision"));
+            }
+            "sparse" => {
+                let gates = transpile::fuse(self.bound_gates()?, transpile_level);
+                let mut creg = dynamic::ClassicalRegister::new(self.clbits, seed);
+                return Ok(py.allow_threads(|| sparse::run(self.qubits, self.initial.as_deref(), &gates, &mut creg))?.into_py(py));
+            }
+            _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown backend '{}' (expected 'statevector' or 'sparse')", backend))),
+        }
+        Ok(match precision {
+            Precision::F32 => py.allow_threads(|| {
+                let mut creg = dynamic::ClassicalRegister::new(self.clbits, seed);
+                self.simulate_guarded(&mut creg, transpile_level, renormalize_every, renormalize_tolerance)
+            })?.into_py(py),
+            Precision::F64 => py.allow_threads(|| -> PyResult<QuantumStateF64> {
+                resources::check_state::<num_complex::Complex64>(self.qubits)?;
+                let mut state = QuantumStateF64::widened(&self.initial_state()?);
+                let gates = self.bound_gates()?;
+                if dynamic::is_dynamic(gates) {
+                    state.run_dynamic(gates, &mut dynamic::ClassicalRegister::new(self.clbits, seed))?;
+                } else {
+                    for gate in gates {
+                        state.apply(gate)?;
+                    }
+                }
+                Ok(state)
+            })?.into_py(py),
+        })
+    }
+
+    /// Execute once and return an ExecutionResult bundling the final state (a
+    /// DensityMatrix under a non-ideal `noise_model`), the elapsed time, gate
+    /// counts, the optimizations applied (fusion at `transpile_level`, skipped
+    /// under noise), counts from `shots` samples, the snapshot records of
+    /// `execute_snapshots` and, for each of `trace`, the series `execute_traced`
+    /// records every `trace_every` gates
+    #[pyo3(signature = (shots=None, seed=None, transpile_level=1, noise_model=None, trace=vec![], trace_every=1,
+                        bit_order="little"))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_detailed(&self, py: Python, shots: Option<usize>, seed: Option<u64>, transpile_level: usize,
+                            noise_model: Option<PyRef<NoiseModel>>, trace: Vec<&PyAny>, trace_every: usize, bit_order: &str)
+                            -> PyResult<ExecutionResult> {
+        result::execute_detailed(py, self, shots, seed, transpile_level, noise_model.as_deref(), trace, trace_every, bit_order)
+    }
+
+    /// Execute in single precision, timing every instruction. Returns a dict
+    /// with the total time, per gate type {"count", "total_s", "mean_s"} in
+    /// "gate_types", the time spent in each circuit layer ("layer_times_s"),
+    /// the state size and estimated peak memory, the process peak RSS, CPU
+    /// time and thread utilization (None where /proc is unavailable, and the
+    /// utilization also for runs under 0.1 s). Fused
+    /// blocks are timed as "unitary"; transpile_level=0 times the gates as written.
+    #[pyo3(signature = (seed=None, transpile_level=1))]
+    pub fn execute_profiled(&self, py: Python, seed: Option<u64>, transpile_level: usize) -> PyResult<PyObject> {
+        transpile::check_level(transpile_level)?;
+        let gates = transpile::fuse(self.bound_gates()?, transpile_level);
+        let report = py.allow_threads(|| {
+            let mut state = self.initial_state()?;
+            profile::run(&mut state, self.clbits, &gates, &mut dynamic::ClassicalRegister::new(self.clbits, seed))
+        })?;
+        report.to_dict(py)
+    }
+
+    /// Execute the circuit, recording at each `snapshot` its label and the
+    /// (<X>, <Y>, <Z>) of every qubit. Returns the final state and the snapshots
+    /// in circuit order; mid-circuit measurements draw outcomes from `seed`.
+    #[pyo3(signature = (seed=None))]
+    pub fn execute_snapshots(&self, py: Python, seed: Option<u64>) -> PyResult<(QuantumState, Vec<Snapshot>)> {
+        let gates = self.bound_gates()?;
+        py.allow_threads(|| {
+            let mut state = self.initial_state()?;
+            let mut creg = dynamic::ClassicalRegister::new(self.clbits, seed);
+            let mut snapshots = Vec::new();
+            for gate in &transpile::fuse(gates, 1) {
+                if let GateType::SNAPSHOT(label) = &gate.gate_type {
+                    snapshots.push((label.clone(), state.bloch_vectors()));
+                }
+                dynamic::run(&mut state.state, self.qubits, std::slice::from_ref(gate), &mut creg)?;
+            }
+            Ok((state, snapshots))
+        })
+    }
+
+    /// Run the circuit once, evaluating each of `observables` ("energy", "entropy",
+    /// "resonance", a PauliString or an Observable) before the first gate, every
+    /// `every` gates and after the last one. Returns the gate counts at which
+    /// values were taken and one series per observable (resonance entries are
+    /// [r, g, b] lists).
+    #[pyo3(signature = (observables, every=1, seed=None))]
+    pub fn execute_traced(&self, py: Python, observables: Vec<&PyAny>, every: usize, seed: Option<u64>) -> PyResult<(Vec<usize>, Vec<PyObject>)> {
+        let probes = observables.into_iter()
+            .map(|o| trace::Probe::extract(o, self.qubits))
+            .collect::<PyResult<Vec<_>>>()?;
+        let gates = self.bound_gates()?;
+        let (steps, series) = py.allow_threads(|| trace::run(&self.initial_state()?, self.clbits, gates, &probes, every, seed))?;
+        Ok((steps, series.into_iter().map(|s| s.into_py(py)).collect()))
+    }
+
+    /// Closed-loop execution: after every `every` gates (and after the last)
+    /// call `callback(gates_done, readings)`, where readings holds the current
+    /// value of each of `observables` ("energy", "entropy", "resonance", a
+    /// PauliString or an Observable). The callback may return None, a
+    /// QuantumCircuit whose gates run next, a {channel: strength} dict of
+    /// noise channels (amplitude_damping, phase_damping, depolarizing,
+    /// bit_flip, phase_flip) applied once to every qubit as a stochastic
+    /// jump, or a (circuit, dict) pair. The simulation runs without the GIL
+    /// between calls. Returns the final state; `seed` drives measurements and
+    /// noise jumps.
+    #[pyo3(signature = (callback, every=1, observables=None, seed=None))]
+    pub fn execute_feedback(&self, py: Python, callback: &PyAny, every: usize, observables: Option<Vec<&PyAny>>,
+                            seed: Option<u64>) -> PyResult<QuantumState> {
+        let probes = match observables {
+            Some(observables) => observables.into_iter()
+                .map(|o| trace::Probe::extract(o, self.qubits))
+                .collect::<PyResult<Vec<_>>>()?,
+            None => vec![trace::Probe::Energy, trace::Probe::Entropy],
+        };
+        feedback::run(py, self, callback, every, &probes, seed)
+    }
+
+    /// Run the circuit once, layer by layer, evaluating each of `observables`
+    /// ("energy", "entropy", a PauliString, Observable or Hamiltonian) after
+    /// every layer. Returns a float32 numpy array of shape (depth, len(observables)).
+    #[pyo3(signature = (observables, seed=None))]
+    pub fn layer_observables<'py>(&self, py: Python<'py>, observables: Vec<&PyAny>, seed: Option<u64>) -> PyResult<&'py PyArray2<f32>> {
+        let probes = observables.into_iter()
+            .map(|o| trace::Probe::extract(o, self.qubits))
+            .collect::<PyResult<Vec<_>>>()?;
+        // Import first so a missing numpy raises ImportError instead of panicking
+        py.import("numpy")?;
+        let gates = self.bound_gates()?;
+        let (depth, values) = py.allow_threads(|| trace::layers(&self.initial_state()?, self.clbits, gates, &probes, seed))?;
+        values.to_pyarray(py).reshape([depth, probes.len()])
+    }
+
+    /// Copy of the circuit mapped onto `coupling_map`'s physical qubits, with
+    /// SWAPs inserted so every two-qubit gate acts on connected qubits. Virtual
+    /// qubit v starts on initial_layout[v] (default v). Returns (circuit,
+    /// final_layout, swaps_added), where final_layout[v] is the physical qubit
+    /// holding v at the end; gates on three or more qubits must be decomposed
+    /// first.
+    #[pyo3(signature = (coupling_map, initial_layout=None))]
+    pub fn route(&self, coupling_map: &CouplingMap, initial_layout: Option<Vec<usize>>) -> PyResult<(QuantumCircuit, Vec<usize>, usize)> {
+        if self.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err("Circuits with an initialized state cannot be routed"));
+        }
+        let (gates, layout, swaps) = routing::route(self.bound_gates()?, self.qubits, coupling_map, initial_layout)?;
+        let routed = QuantumCircuit { qubits: coupling_map.num_qubits(), gates, ..self.clone() };
+        Ok((routed, layout, swaps))
+    }
+
+    /// Copy of the circuit rewritten into the gates named in `basis`, equal up
+    /// to global phase. Gates already in the basis are kept; the rest become
+    /// CNOTs (or CZs, for basis=[..., "cz"]) and Euler rotations in u, rz/ry,
+    /// rz/rx or rx/ry, with p usable in place of rz. Multi-controlled gates are
+    /// decomposed without ancillas; dense unitaries on more than one qubit are
+    /// rejected.
+    #[pyo3(signature = (basis=vec!["rz".to_string(), "ry".to_string(), "cnot".to_string()]))]
+    pub fn decompose(&self, basis: Vec<String>) -> PyResult<QuantumCircuit> {
+        let basis = decompose::Basis::new(&basis)?;
+        Ok(QuantumCircuit { gates: decompose::decompose(self.bound_gates()?, &basis)?, ..self.clone() })
+    }
+
+    /// `num_instances` Pauli-twirled copies of the circuit: every two-qubit
+    /// Clifford gate (cnot, cz, swap, iswap, ...) gets a random Pauli on its
+    /// qubits before it and the conjugated Pauli after it, so each copy is
+    /// equivalent to the original up to global phase
+    #[pyo3(signature = (num_instances=1, seed=None))]
+    pub fn twirl(&self, num_instances: usize, seed: Option<u64>) -> PyResult<Vec<QuantumCircuit>> {
+        twirl::instances(self, num_instances, seed)
+    }
+
+    /// Density matrix averaged over `num_instances` twirled copies (see
+    /// `twirl`), each evolved gate by gate under `noise_model` as in
+    /// execute_noisy; the error after each twirled gate averages to a Pauli channel
+    #[pyo3(signature = (noise_model, num_instances=16, seed=None))]
+    pub fn execute_twirled(&self, py: Python, noise_model: PyRef<NoiseModel>, num_instances: usize, seed: Option<u64>) -> PyResult<DensityMatrix> {
+        let model: &NoiseModel = &noise_model;
+        py.allow_threads(|| twirl::average(self, model, num_instances, seed))
+    }
+
+    /// Copy of the circuit with runs of single-qubit gates fused into one 2x2
+    /// unitary per qubit (level 1), and back-to-back cphase / crz gates on the
+    /// same qubits merged (level 2); level 0 leaves the gates unchanged
+    #[pyo3(signature = (level=1))]
+    pub fn transpile(&self, level: usize) -> PyResult<QuantumCircuit> {
+        transpile::check_level(level)?;
+        Ok(QuantumCircuit { gates: transpile::fuse(self.bound_gates()?, level), ..self.clone() })
+    }
+
+    /// Simplified copy of the circuit: adjacent inverse pairs (h h, x x, cnot cnot,
+    /// s sdg, ...) cancel, consecutive rx / ry / rz on a qubit merge into one
+    /// rotation and rotations within `tolerance` of the identity are dropped.
+    /// Returns the circuit and counts of gates removed by each pass.
+    #[pyo3(signature = (tolerance=1e-6))]
+    pub fn optimize(&self, tolerance: f32) -> PyResult<(QuantumCircuit, HashMap<String, usize>)> {
+        let before = self.bound_gates()?.len();
+        let (gates, stats) = transpile::optimize(self.bound_gates()?, tolerance);
+        let report = HashMap::from([
+            ("gates_before".to_string(), before),
+            ("gates_after".to_string(), gates.len()),
+            ("cancelled".to_string(), stats.cancelled),
+            ("merged".to_string(), stats.merged),
+            ("dropped".to_string(), stats.dropped),
+        ]);
+        Ok((QuantumCircuit { gates, ..self.clone() }, report))
+    }
+
+    /// Build a circuit from a Stim program; the k-th measurement writes
+    /// classical bit k and REPEAT blocks are unrolled
+    #[staticmethod]
+    pub fn from_stim(src: &str) -> PyResult<Self> {
+        let (qubits, clbits, gates) = stim::parse(src)?;
+        Ok(QuantumCircuit { qubits, clbits, gates, params: Vec::new(), initial: None })
+    }
+
+    /// Serialize a Clifford circuit as a Stim program; errors listing the index
+    /// of every gate Stim cannot express
+    pub fn to_stim(&self) -> PyResult<String> {
+        if self.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err("initialize has no Stim equivalent"));
+        }
+        stim::emit(self.bound_gates()?)
+    }
+
+    /// Execute a Clifford-only circuit on a stabilizer tableau, which scales to
+    /// hundreds of qubits; errors on the first non-Clifford gate
+    pub fn execute_stabilizer(&self, py: Python) -> PyResult<StabilizerState> {
+        if self.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "execute_stabilizer starts from |0...0> and does not support initialize"));
+        }
+        let gates = self.bound_gates()?;
+        py.allow_threads(|| stabilizer::run(self.qubits, gates))
+    }
+
+    /// Execute on a matrix product state whose bond dimension is capped at
+    /// `max_bond_dim`; exact while entanglement stays below the cap, so 30-50
+    /// qubit circuits with limited entanglement fit in memory
+    #[pyo3(signature = (max_bond_dim=64))]
+    pub fn execute_mps(&self, py: Python, max_bond_dim: usize) -> PyResult<MPSState> {
+        if self.initial.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "execute_mps starts from |0...0> and does not support initialize"));
+        }
+        let gates = self.bound_gates()?;
+        py.allow_threads(|| mps::run(self.qubits, gates, max_bond_dim))
+    }
+
+    /// Execute with the state vector kept in a file at `path` (an f32 checkpoint
+    /// that `QuantumState.load` can read) and streamed through memory in blocks
+    /// of 2^block_qubits amplitudes, for registers that do not fit in RAM.
+    /// Unitary circuits only; the file is left in place.
+    #[pyo3(signature = (path, block_qubits=24, transpile_level=1))]
+    pub fn execute_on_disk(&self, py: Python, path: &str, block_qubits: usize, transpile_level: usize) -> PyResult<DiskState> {
+        transpile::check_level(transpile_level)?;
+        let gates = transpile::fuse(self.bound_gates()?, transpile_level);
+        py.allow_threads(|| disk::run(path, self.qubits, self.initial.as_deref(), &gates, block_qubits))
+    }
+
+    /// Execute with the state vector sharded across `n_ranks` worker processes
+    /// (a power of two) that exchange amplitudes over TCP for gates on the top
+    /// log2(n_ranks) qubits. With spawn=True the workers are started on this
+    /// machine; otherwise the coordinator listens on `address` for `n_ranks`
+    /// workers started elsewhere with `run_worker`. Unitary circuits only.
+    #[pyo3(signature = (n_ranks, address="127.0.0.1:0", spawn=true, transpile_level=1, timeout_s=60.0))]
+    pub fn execute_distributed(&self, py: Python, n_ranks: usize, address: &str, spawn: bool, transpile_level: usize,
+                               timeout_s: f64) -> PyResult<QuantumState> {
+        transpile::check_level(transpile_level)?;
+        if !timeout_s.is_finite() || timeout_s <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("timeout_s must be positive"));
+        }
+        let gates = transpile::fuse(self.bound_gates()?, transpile_level);
+        if dynamic::is_dynamic(&gates) {
+            return Err(dynamic::unsupported());
+        }
+        let program = QuantumCircuit { qubits: self.qubits, clbits: 0, gates, params: Vec::new(), initial: None };
+        let spawn = if spawn {
+            let sys = py.import("sys")?;
+            let path: Vec<String> = sys.getattr("path")?.extract()?;
+            let separator: String = py.import("os")?.getattr("pathsep")?.extract()?;
+            Some(distributed::Spawn { python: sys.getattr("executable")?.extract()?, python_path: path.join(&separator) })
+        } else {
+            None
+        };
+        let initial = self.initial.clone();
+        py.allow_threads(|| distributed::execute(program, initial, n_ranks, address, spawn,
+                                                 std::time::Duration::from_secs_f64(timeout_s)))
+    }
+
+    /// Every gate as (name, qubits, params): qubits lists controls first and the
+    /// target last; measurements carry their classical bit as the param
+    pub fn gates(&self) -> Vec<introspect::GateDescription> {
+        self.gates.iter().map(introspect::describe).collect()
+    }
+
+    /// Number of gate layers, counting dependencies through qubits and classical bits
+    pub fn depth(&self) -> usize {
+        introspect::depth(&self.gates, self.qubits, self.clbits)
+    }
+
+    /// Number of gates of each name
+    pub fn count_ops(&self) -> HashMap<String, usize> {
+        introspect::count_ops(&self.gates)
+    }
+
+    /// Remove and return gate `index`; parameter bindings of that gate are dropped
+    pub fn remove(&mut self, index: usize) -> PyResult<introspect::GateDescription> {
+        if index >= self.gates.len() {
+            return Err(pyo3::exceptions::PyIndexError::new_err(format!(
+                "Gate index {} out of range for {} gates", index, self.gates.len())));
+        }
+        let gate = self.gates.remove(index);
+        self.params.retain(|p| p.gate != index);
+        self.params.iter_mut().filter(|p| p.gate > index).for_each(|p| p.gate -= 1);
+        Ok(introspect::describe(&gate))
+    }
+
+    /// Insert a gate given as (name, qubits, params), as returned by `gates`,
+    /// before gate `index` (`len(circuit)` appends)
+    pub fn insert(&mut self, index: usize, gate: introspect::GateDescription) -> PyResult<()> {
+        if index > self.gates.len() {
+            return Err(pyo3::exceptions::PyIndexError::new_err(format!(
+                "Gate index {} out of range for {} gates", index, self.gates.len())));
+        }
+        let gate = introspect::from_description(&gate, self.qubits)?;
+        if let GateType::MEASURE(bit) = gate.gate_type {
+            self.clbits = self.clbits.max(bit + 1);
+        }
+        self.gates.insert(index, gate);
+        self.params.iter_mut().filter(|p| p.gate >= index).for_each(|p| p.gate += 1);
+        Ok(())
+    }
+
+    fn __len__(&self) -> usize {
+        self.gates.len()
+    }
+
+    /// Text diagram of the circuit: one wire per qubit, boxed gate labels, `*`
+    /// for controls and `x` for SWAP ends
+    pub fn draw(&self) -> String {
+        draw::draw(self)
+    }
+
+    /// The 2^n x 2^n unitary the circuit implements as a complex64 numpy array,
+    /// U[i, j] = <i|U|j> (bit k of an index is qubit k); limited to 12 qubits
+    pub fn to_unitary<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<Complex32>> {
+        py.import("numpy")?;
+        let dim = 1 << self.qubits;
+        let u = py.allow_threads(|| unitary::matrix(self))?;
+        u.to_pyarray(py).reshape([dim, dim])
+    }
+
+    /// True when both circuits implement the same unitary up to global phase.
+    /// Up to 8 qubits the unitaries are compared entry by entry; larger circuits
+    /// are run on 16 shared random states (drawn from `seed`) and every
+    /// |<a psi|b psi>| must be within `tolerance` of 1.
+    #[pyo3(signature = (other, tolerance=1e-4, seed=None))]
+    pub fn equivalent(&self, py: Python, other: &QuantumCircuit, tolerance: f32, seed: Option<u64>) -> PyResult<bool> {
+        py.allow_threads(|| unitary::equivalent(self, other, tolerance, seed))
+    }
+
+    /// Start executing on a background thread and return an ExecutionHandle
+    /// (`is_done`, `result`, `cancel`). If given, `callback(gates_done, total,
+    /// entropy)` is called every `callback_every` gates and after the last one.
+    #[pyo3(signature = (callback=None, callback_every=1, seed=None))]
+    pub fn execute_async(&self, callback: Option<PyObject>, callback_every: usize, seed: Option<u64>) -> PyResult<ExecutionHandle> {
+        background::spawn(self.initial_state()?, self.clbits, self.bound_gates()?.to_vec(), seed, callback, callback_every)
+    }
+
+    /// Execute one shot, returning the final state and the classical register
+    /// as a bitstring (character i is bit i, or bit clbits - 1 - i with
+    /// bit_order="big")
+    #[pyo3(signature = (seed=None, bit_order="little"))]
+    pub fn execute_measured(&self, py: Python, seed: Option<u64>, bit_order: &str) -> PyResult<(QuantumState, String)> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        let mut creg = dynamic::ClassicalRegister::new(self.clbits, seed);
+        let state = py.allow_threads(|| self.simulate(&mut creg, 1))?;
+        Ok((state, bit_order.bitstring(creg.bitstring())))
+    }
+
+    /// Execute the circuit and sample `shots` measurement outcomes as a counts dictionary.
+    /// Circuits with mid-circuit measurements are re-run per shot and counted by
+    /// classical register. With a `noise_model`, its channels are applied while
+    /// evolving a density matrix and its readout errors to the sampled bitstrings.
+    /// bit_order="big" writes the bitstrings with qubit (or classical bit) 0 last.
+    #[pyo3(signature = (shots, seed=None, noise_model=None, bit_order="little"))]
+    pub fn run(&self, py: Python, shots: usize, seed: Option<u64>, noise_model: Option<PyRef<NoiseModel>>, bit_order: &str) -> PyResult<HashMap<String, usize>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        let model: Option<&NoiseModel> = noise_model.as_deref();
+        let counts = py.allow_threads(|| self.sample_counts(shots, seed, model))?;
+        Ok(bit_order.counts(counts))
+    }
+
+    /// Evolve a density matrix gate-by-gate (rho -> U rho U^dagger), applying the
+    /// (amplitude, phase) damping channels after every gate
+    #[pyo3(signature = (noise_params=(0.0, 0.0), precision="f32"))]
+    pub fn execute_density(&self, py: Python, noise_params: (f32, f32), precision: &str) -> PyResult<PyObject> {
+        let (amp_damping, phase_damping) = noise_params;
+
+        Ok(match Precision::from_name(precision)? {
+            Precision::F32 => py.allow_threads(|| -> PyResult<DensityMatrix> {
+                let mut dm = self.initial_density()?;
+                for gate in self.bound_gates()?.iter().filter(|g| !g.is_snapshot()) {
+                    dm.apply_unitary(gate)?;
+                    if amp_damping > 0.0 {
+                        dm.apply_amplitude_damping(amp_damping, None, false)?;
+                    }
+                    if phase_damping > 0.0 {
+                        dm.apply_phase_damping(phase_damping, None, false)?;
+                    }
+                }
+                Ok(dm)
+            })?.into_py(py),
+            Precision::F64 => py.allow_threads(|| -> PyResult<DensityMatrixF64> {
+                let mut dm = DensityMatrixF64::new(self.qubits)?;
+                if self.initial.is_some() {
+                    dm.from_pure_state(&QuantumStateF64::widened(&self.initial_state()?))?;
+                }
+                for gate in self.bound_gates()?.iter().filter(|g| !g.is_snapshot()) {
+                    dm.apply_unitary(gate)?;
+                    if amp_damping > 0.0 {
+                        dm.apply_amplitude_damping(amp_damping, None)?;
+                    }
+                    if phase_damping > 0.0 {
+                        dm.apply_phase_damping(phase_damping, None)?;
+                    }
+                }
+                Ok(dm)
+            })?.into_py(py),
+        })
+    }
+
+    /// Monte Carlo trajectory simulation: evolve `n_trajectories` pure states with
+    /// randomly sampled Kraus jumps from `noise_model` and average the results.
+    /// Memory stays O(2^n), so this scales past the density-matrix limit.
+    #[pyo3(signature = (noise_model, n_trajectories, seed=None, observables=None))]
+    pub fn execute_trajectories(
+        &self,
+        py: Python,
+        noise_model: PyRef<NoiseModel>,
+        n_trajectories: usize,
+        seed: Option<u64>,
+        observables: Option<Vec<&PyAny>>,
+    ) -> PyResult<TrajectoryResult> {
+        if n_trajectories == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("n_trajectories must be positive"));
+        }
+        let observables = observables.unwrap_or_default().into_iter()
+            .map(|o| observables::extract_terms(o, self.qubits))
+            .collect::<PyResult<Vec<_>>>()?;
+        let seed = rng::resolve(seed);
+        let gates = self.bound_gates()?;
+        let model: &NoiseModel = &noise_model;
+        py.allow_threads(|| trajectories::run(&self.initial_state()?, gates, model, n_trajectories, seed, &observables))
+    }
+
+    /// Treat the `environment` qubits as a Stinespring dilation: run the whole
+    /// circuit as one pure state, then trace the environment out. Returns the
+    /// exact reduced state of the other qubits (in increasing order, renumbered
+    /// from 0), the open-system evolution the couplings induce without writing
+    /// down Kraus operators. Mid-circuit measurements draw from `seed`.
+    #[pyo3(signature = (environment, seed=None))]
+    pub fn execute_open(&self, py: Python, environment: Vec<usize>, seed: Option<u64>) -> PyResult<DensityMatrix> {
+        if !distinct_qubits(&environment, self.qubits)? {
+            return Err(pyo3::exceptions::PyValueError::new_err("Environment must not contain duplicate qubits"));
+        }
+        let system = reduction::complement(&environment, self.qubits);
+        if system.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err("Environment cannot contain every qubit"));
+        }
+        resources::check_density::<Complex32>(system.len())?;
+        py.allow_threads(|| {
+            let state = self.simulate(&mut dynamic::ClassicalRegister::new(self.clbits, seed), 1)?;
+            let matrix = reduction::reduce_pure_state(&state.state, self.qubits, &system);
+            Ok(DensityMatrix { qubits: system.len(), matrix })
+        })
+    }
+
+    /// Execute the circuit with noise and return a Density Matrix.
+    /// Accepts a NoiseModel (consulted after every gate while evolving rho), or the
+    /// legacy (amp_damping, phase_damping) tuple applied once to the final state.
+    /// The tuple keeps its original semantics: both are the global off-diagonal
+    /// decays of `apply_amplitude_damping` / `apply_phase_damping` with
+    /// `simplified=True`, not the per-qubit Kraus channels, so existing callers
+    /// see unchanged results; pass a NoiseModel for physical damping.
+    pub fn execute_noisy(&self, py: Python, noise: &PyAny) -> PyResult<DensityMatrix> {
+        if let Ok(model) = noise.extract::<PyRef<NoiseModel>>() {
+            let model: &NoiseModel = &model;
+            return py.allow_threads(|| {
+                let mut dm = self.initial_density()?;
+                for gate in self.bound_gates()? {
+                    dm.apply_unitary(gate)?;
+                    model.apply_after(gate, &mut dm)?;
+                }
+                Ok(dm)
+            });
+        }
+
+        let (amp_damping, phase_damping): (f32, f32) = noise.extract()
+            .map_err(|_| pyo3::exceptions::PyTypeError::new_err(
+                "execute_noisy expects a NoiseModel or an (amp_damping, phase_damping) tuple"))?;
+        py.allow_threads(|| {
+            let mut dm = DensityMatrix::new(self.qubits)?;
+
+            // 1. Evolve pure state first (approximation for efficiency)
+            let mut state = self.initial_state()?;
+            for gate in self.bound_gates()? {
+                state.apply(gate)?;
+            }
+
+            // 2. Convert to Density Matrix
+            dm.from_pure_state(&state);
+
+            // 3. Apply Noise Channels (Decoherence)
+            if amp_damping > 0.0 {
+                dm.apply_amplitude_damping(amp_damping, None, true)?;
+            }
+            if phase_damping > 0.0 {
+                dm.apply_phase_damping(phase_damping, None, true)?;
+            }
+
+            Ok(dm)
+        })
+    }
+}
+
+impl QuantumCircuit {
+    /// Counts of `shots` samples in little-endian order, as `run` returns them
+    fn sample_counts(&self, shots: usize, seed: Option<u64>, model: Option<&NoiseModel>) -> PyResult<HashMap<String, usize>> {
+        let gates = self.bound_gates()?;
+        if dynamic::is_dynamic(gates) {
+            if model.is_some_and(|m| !m.is_ideal()) {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Noisy sampling does not support measurement, reset or c_if"));
+            }
+            return dynamic::counts(&self.initial_state()?, self.clbits, &transpile::fuse(gates, 1), shots, seed);
+        }
+        let model = match model {
+            Some(m) if !m.is_ideal() => m,
+            _ => return Ok(self.simulate(&mut dynamic::ClassicalRegister::new(0, seed), 1)?.sample(shots, seed)),
+        };
+        let probs = if model.has_channels() {
+            let mut dm = self.initial_density()?;
+            for gate in gates {
+                dm.apply_unitary(gate)?;
+                model.apply_after(gate, &mut dm)?;
+            }
+            dm.populations()
+        } else {
+            let state = self.simulate(&mut dynamic::ClassicalRegister::new(0, seed), 1)?;
+            state.state.par_iter().map(|c| c.norm_sqr()).collect()
+        };
+        sampling::readout_counts(&probs, self.qubits, shots, seed, Some(model))
+    }
+
+    /// <O> with the parameters bound to `values`
+    fn energy_at(&self, terms: &[PauliTerm], values: &HashMap<String, f32>) -> PyResult<f32> {
+        let bound = self.bind(values.clone())?;
+        gradients::energy(&self.initial_state()?, bound.bound_gates()?, terms)
+    }
+
+    /// Adjoint gradients, aggregated per parameter (or per rotation gate when unparameterized)
+    fn adjoint_gradients(&self, terms: &[PauliTerm], values: &HashMap<String, f32>) -> PyResult<Vec<f32>> {
+        let bound = self.bind(values.clone())?;
+        let per_gate = gradients::adjoint(&self.initial_state()?, bound.bound_gates()?, terms)?;
+        if self.params.is_empty() {
+            return Ok(per_gate.into_iter().map(|(_, g)| g).collect());
+        }
+
+        // `names` is built from `self.params`, so every reference has a slot
+        let names = self.parameters();
+        let mut grads = vec![0.0; names.len()];
+        for r in &self.params {
+            let slot = names.iter().position(|n| n == &r.name).expect("parameter listed by parameters()");
+            if let Some((_, g)) = per_gate.iter().find(|(k, _)| *k == r.gate) {
+                grads[slot] += r.scale * g;
+            }
+        }
+        Ok(grads)
+    }
+
+    /// Append `other`'s gates and parameter references with qubits relabelled by
+    /// `map` and classical bits by `cmap`
+    fn append(&mut self, other: &QuantumCircuit, map: impl Fn(usize) -> usize, cmap: impl Fn(usize) -> usize) {
+        let offset = self.gates.len();
+        self.gates.extend(other.gates.iter().map(|g| g.relabelled(&map, &cmap)));
+        self.params.extend(other.params.iter().map(|r| ParamRef { gate: r.gate + offset, ..r.clone() }));
+    }
+
+    /// Reject qubit lists with out-of-range or repeated entries
+    fn check_distinct(&self, qubits: &[usize]) -> PyResult<()> {
+        if !distinct_qubits(qubits, self.qubits)? {
+            return Err(errors::InvalidGateError::new_err("Duplicate qubit indices"));
+        }
+        Ok(())
+    }
+
+    fn push_param(&mut self, gate: Gate, name: &str) -> PyResult<()> {
+        self.push_scaled_param(gate, name, 1.0)
+    }
+
+    /// Append a gate whose angle is `scale` times the named parameter
+    fn push_scaled_param(&mut self, gate: Gate, name: &str, scale: f32) -> PyResult<()> {
+        self.check_gate(&gate)?;
+        self.params.push(ParamRef { gate: self.gates.len(), name: name.to_string(), scale });
+        self.gates.push(gate);
+        Ok(())
+    }
+
+    /// Raise ValueError when a measurement or condition in `gates`, including
+    /// those nested in c_if bodies, names a classical bit past `clbits`
+    fn check_clbits(&self, gates: &[Gate]) -> PyResult<()> {
+        for gate in gates {
+            let bit = match &gate.gate_type {
+                GateType::MEASURE(bit) => *bit,
+                GateType::IF(bit, _, body) => {
+                    self.check_clbits(body)?;
+                    *bit
+                }
+                _ => continue,
+            };
+            if bit >= self.clbits {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "{}: classical bit {} out of range for {} classical bits",
+                    introspect::name(&gate.gate_type), bit, self.clbits)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Raise QubitIndexError for out-of-range qubits and InvalidGateError for
+    /// repeated ones, naming the gate
+    fn check_gate(&self, gate: &Gate) -> PyResult<()> {
+        let qubits = gate.qubits();
+        if let Some(q) = qubits.iter().find(|&&q| q >= self.qubits) {
+            return Err(errors::QubitIndexError::new_err(format!(
+                "{}: qubit {} out of range for a {}-qubit circuit", introspect::name(&gate.gate_type), q, self.qubits)));
+        }
+        if !distinct_qubits(&qubits, self.qubits)? {
+            let what = if qubits.len() == 2 && gate.control.is_some() { "control and target" } else { "qubits" };
+            return Err(errors::InvalidGateError::new_err(format!(
+                "{}: {} must be different, got {:?}", introspect::name(&gate.gate_type), what, qubits)));
+        }
+        Ok(())
+    }
+
+    /// Validate and append a gate
+    fn push(&mut self, gate: Gate) -> PyResult<()> {
+        self.check_gate(&gate)?;
+        self.gates.push(gate);
+        Ok(())
+    }
+
+    /// The gate list, provided every parameter has been bound
+    fn bound_gates(&self) -> PyResult<&[Gate]> {
+        if self.params.is_empty() {
+            return Ok(&self.gates);
+        }
+        Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Circuit has unbound parameters: {} (use bind or execute_bound)",
+            params::names(&self.params).join(", "))))
+    }
+
+    /// State execution starts from: the initialized amplitudes or |0...0>
+    fn initial_state(&self) -> PyResult<QuantumState> {
+        match &self.initial {
+            Some(amps) => Ok(QuantumState { qubits: self.qubits, state: amps.clone(), phase: 0.0, drift: 0.0 }),
+            None => QuantumState::new(self.qubits),
+        }
+    }
+
+    /// |psi><psi| of the initial state
+    fn initial_density(&self) -> PyResult<DensityMatrix> {
+        let mut dm = DensityMatrix::new(self.qubits)?;
+        if self.initial.is_some() {
+            dm.from_pure_state(&self.initial_state()?);
+        }
+        Ok(dm)
+    }
+
+    /// Run the circuit on a single-precision state vector, recording any
+    /// mid-circuit measurements in `creg`; gates are fused at `transpile_level` first
+    fn simulate(&self, creg: &mut dynamic::ClassicalRegister, transpile_level: usize) -> PyResult<QuantumState> {
+        self.simulate_guarded(creg, transpile_level, 0, 0.0)
+    }
+
+    /// `simulate` that checks the norm after every `every` gates (counted after
+    /// fusion; 0 never checks) and renormalizes when it has drifted from one by
+    /// more than `tolerance`
+    fn simulate_guarded(&self, creg: &mut dynamic::ClassicalRegister, transpile_level: usize, every: usize, tolerance: f64)
+        -> PyResult<QuantumState> {
+        let mut state = self.initial_state()?;
+        let source = self.bound_gates()?;
+        let gates = transpile::fuse(source, transpile_level);
+        let dynamic = dynamic::is_dynamic(&gates);
+        let chunk = if every == 0 { gates.len().max(1) } else { every };
+        for block in gates.chunks(chunk) {
+            if dynamic {
+                dynamic::run(&mut state.state, self.qubits, block, creg)?;
+            } else {
+                for gate in block {
+                    state.apply(gate)?;
+                }
+            }
+            if every > 0 && (1.0 - state.norm()).abs() > tolerance {
+                state.renormalize();
+            }
+        }
+        // apply() books the RZ gates it runs; add those fusion folded into
+        // matrices, or all of them on the dynamic path, which bypasses apply()
+        let applied = if dynamic { 0.0 } else { rz_phase(&gates) };
+        state.phase += rz_phase(source) - applied;
+        Ok(state)
+    }
+}
+
+/// Global phase RZ gates at the top level of `gates` add to the state; a
+/// restore returns to the phase booked at its snapshot
+fn rz_phase(gates: &[Gate]) -> f32 {
+    let mut saved = HashMap::new();
+    gates.iter().fold(0.0, |phase, gate| match &gate.gate_type {
+        GateType::RZ(phi) => phase - phi / 2.0,
+        GateType::SNAPSHOT(label) => {
+            saved.insert(label, phase);
+            phase
+        }
+        GateType::RESTORE(label) => saved.get(label).copied().unwrap_or(phase),
+        _ => phase,
+    })
+}
+
+/// High-performance quantum state with SIMD optimization
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone)]
+pub struct QuantumState {
+    qubits: usize,
+    state: Vec<Complex32>,
+    /// Global phase booked by RZ gates, which apply exp(-i phi Z / 2) =
+    /// e^(-i phi / 2) P(phi); the amplitudes already carry it
+    phase: f32,
+    /// Sum of |1 - norm| removed by renormalize()
+    drift: f64,
+}
+
+#[pymethods]
+impl QuantumState {
+    #[new]
+    pub fn new(qubits: usize) -> PyResult<Self> {
+        resources::check_state::<Complex32>(qubits)?;
+        let dim = 1 << qubits;
+        let mut state = vec![Complex32::new(0.0, 0.0); dim];
+        state[0] = Complex32::new(1.0, 0.0); // |0...0>
+        Ok(QuantumState { qubits, state, phase: 0.0, drift: 0.0 })
+    }
+
+    /// Pickle support: unpickling calls QuantumState(qubits) and then restores
+    /// the raw amplitude buffer along with global_phase and norm_drift
+    pub fn __getnewargs__(&self) -> (usize,) {
+        (self.qubits,)
+    }
+
+    pub fn __getstate__<'py>(&self, py: Python<'py>) -> (&'py PyBytes, f32, f64) {
+        (PyBytes::new(py, &serialize::buffer_to_bytes(&self.state)), self.phase, self.drift)
+    }
+
+    pub fn __setstate__(&mut self, state: (&[u8], f32, f64)) -> PyResult<()> {
+        let (amplitudes, phase, drift) = state;
+        self.state = serialize::buffer_from_bytes(amplitudes, self.state.len())?;
+        self.phase = phase;
+        self.drift = drift;
+        Ok(())
+    }
+
+    /// Write the amplitudes to a versioned checkpoint file at `path`
+    pub fn save(&self, py: Python, path: &str) -> PyResult<()> {
+        py.allow_threads(|| checkpoint::save(path, checkpoint::Kind::StateVector, self.qubits, checkpoint::Buffer::F32(&self.state)))
+    }
+
+    /// Read a state saved by `save` (from either precision)
+    #[staticmethod]
+    pub fn load(py: Python, path: &str) -> PyResult<Self> {
+        let (qubits, amplitudes) = py.allow_threads(|| checkpoint::load(path, checkpoint::Kind::StateVector))?;
+        Ok(QuantumState { qubits, state: amplitudes, phase: 0.0, drift: 0.0 })
+    }
+
+    /// State with the given (re, im) amplitudes; the length must be a power of two
+    /// and the squared norm within 1e-4 of one
+    #[staticmethod]
+    pub fn from_amplitudes(amplitudes: Vec<(f32, f32)>) -> PyResult<Self> {
+        if amplitudes.len() < 2 || !amplitudes.len().is_power_of_two() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Amplitude count must be a power of two (got {})", amplitudes.len())));
+        }
+        let state: Vec<Complex32> = amplitudes.iter().map(|&(re, im)| Complex32::new(re, im)).collect();
+        let norm: f64 = state.iter().map(|c| c.norm_sqr() as f64).sum();
+        if (norm - 1.0).abs() > 1e-4 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Amplitudes are not normalized (squared norm {})", norm)));
+        }
+        Ok(QuantumState { qubits: state.len().trailing_zeros() as usize, state, phase: 0.0, drift: 0.0 })
+    }
+
+    /// Apply single-qubit gate with SIMD optimization
+    pub fn apply_gate(&mut self, gate_name: &str, target: usize, param: Option<f32>) -> PyResult<()> {
+        if target >= self.qubits {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        let gate = self.gate_matrix(gate_name, param)?;
+        self.apply_matrix(target, gate);
+        if gate_name == "RZ" {
+            self.phase -= param.unwrap_or(0.0) / 2.0;
+        }
+        Ok(())
+    }
+
+    /// Apply CNOT gate with optimized permutation
+    pub fn apply_cnot(&mut self, control: usize, target: usize) -> PyResult<()> {
+        if control >= self.qubits || target >= self.qubits {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        if control == target {
+            return Err(errors::InvalidGateError::new_err("Control and target must be different"));
+        }
+
+        let x = self.x_gate();
+        kernels::apply_1q(&mut self.state, target, x, 1 << control);
+        Ok(())
+    }
+
+    /// Apply SWAP gate with optimized permutation
+    pub fn apply_swap(&mut self, a: usize, b: usize) -> PyResult<()> {
+        self.apply_swap_phased(a, b, Complex32::new(1.0, 0.0))
+    }
+
+    /// Apply iSWAP gate (SWAP with phase i on the exchanged amplitudes)
+    pub fn apply_iswap(&mut self, a: usize, b: usize) -> PyResult<()> {
+        self.apply_swap_phased(a, b, Complex32::new(0.0, 1.0))
+    }
+
+    /// Apply multi-controlled X gate via index masking
+    pub fn apply_mcx(&mut self, controls: Vec<usize>, target: usize) -> PyResult<()> {
+        if target >= self.qubits || controls.iter().any(|&c| c >= self.qubits) {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        if controls.contains(&target) {
+            return Err(errors::InvalidGateError::new_err("Control and target must be different"));
+        }
+        let control_mask = controls.iter().fold(0usize, |mask, &c| mask | (1 << c));
+        if control_mask.count_ones() as usize != controls.len() {
+            return Err(errors::InvalidGateError::new_err("Duplicate control qubits"));
+        }
+
+        let x = self.x_gate();
+        kernels::apply_1q(&mut self.state, target, x, control_mask);
+        Ok(())
+    }
+
+    /// Apply controlled RX gate
+    pub fn apply_controlled_rx(&mut self, control: usize, target: usize, theta: f32) -> PyResult<()> {
+        self.apply_controlled_1q(control, target, self.rx_gate(theta))
+    }
+
+    /// Apply controlled RY gate
+    pub fn apply_controlled_ry(&mut self, control: usize, target: usize, theta: f32) -> PyResult<()> {
+        self.apply_controlled_1q(control, target, self.ry_gate(theta))
+    }
+
+    /// Apply controlled RZ gate
+    pub fn apply_controlled_rz(&mut self, control: usize, target: usize, phi: f32) -> PyResult<()> {
+        self.apply_controlled_1q(control, target, self.rz_gate(phi))
+    }
+
+    /// Apply controlled Hadamard gate
+    pub fn apply_controlled_h(&mut self, control: usize, target: usize) -> PyResult<()> {
+        self.apply_controlled_1q(control, target, self.h_gate())
+    }
+
+    /// Apply controlled phase gate diag(1, 1, 1, e^(i phi))
+    pub fn apply_controlled_phase(&mut self, control: usize, target: usize, phi: f32) -> PyResult<()> {
+        self.apply_controlled_1q(control, target, kernels::matrix(&GateType::CPHASE(phi)).unwrap())
+    }
+
+    /// Calculate energy expectation value
+    pub fn expectation_value(&self) -> f32 {
+        self.calculate_energy()
+    }
+    
+    /// Expectation value <psi|O|psi> of a PauliString or Observable (weighted Pauli sum)
+    pub fn expectation(&self, py: Python, observable: &PyAny) -> PyResult<f32> {
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        Ok(py.allow_threads(|| {
+            terms.iter()
+                .map(|t| t.coeff * pauli::expectation(&self.state, t.masks))
+                .sum()
+        }))
+    }
+
+    /// Variance <O^2> - <O>^2 of a PauliString, Observable or Hamiltonian
+    pub fn variance(&self, py: Python, observable: &PyAny) -> PyResult<f32> {
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        Ok(py.allow_threads(|| {
+            let mean: f64 = terms.iter().map(|t| (t.coeff * pauli::expectation(&self.state, t.masks)) as f64).sum();
+            (pauli::second_moment(&self.state, &terms) - mean * mean).max(0.0) as f32
+        }))
+    }
+
+    /// Shot-based estimate of <O> with each Pauli term measured `shots` times
+    /// in its own eigenbasis; returns (mean, standard error)
+    #[pyo3(signature = (observable, shots, seed=None))]
+    pub fn estimate(&self, py: Python, observable: &PyAny, shots: usize, seed: Option<u64>) -> PyResult<(f32, f32)> {
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        py.allow_threads(|| observables::estimate(&terms, shots, seed, |masks| pauli::expectation(&self.state, masks)))
+    }
+
+    /// Get state vector for Python
+    pub fn get_state_vector(&self) -> Vec<(f32, f32)> {
+        self.state.iter()
+            .map(|c| (c.re, c.im))
+            .collect()
+    }
+
+    /// Iterator over (index, re, im) of every amplitude with magnitude above
+    /// `threshold`, in index order, read from the state as it goes rather
+    /// than copied up front
+    #[pyo3(signature = (threshold=0.0))]
+    pub fn iter_amplitudes(slf: PyRef<'_, Self>, threshold: f32) -> PyResult<AmplitudeIterator> {
+        if threshold.is_nan() || threshold < 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("threshold must be non-negative"));
+        }
+        Ok(AmplitudeIterator::new(slf.into(), threshold))
+    }
+
+    /// State vector as a complex64 numpy array (a single copy of the amplitude buffer)
+    pub fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray1<Complex32>> {
+        // Import first so a missing numpy raises ImportError instead of panicking
+        py.import("numpy")?;
+        Ok(self.state.to_pyarray(py))
+    }
+    
+    /// Calculate entropy
+    pub fn entropy(&self, py: Python) -> f32 {
+        py.allow_threads(|| self.calculate_entropy())
+    }
+    
+    /// Tr(rho^2) of |psi><psi|, always 1 for a pure state
+    pub fn purity(&self) -> f32 {
+        1.0
+    }
+
+    /// l1-norm of coherence, sum over i != j of |psi_i psi_j*| = (sum_i |psi_i|)^2 - 1
+    pub fn l1_coherence(&self) -> f32 {
+        let sum_abs: f32 = self.state.par_iter().map(|c| c.norm()).sum();
+        let norm: f32 = self.state.par_iter().map(|c| c.norm_sqr()).sum();
+        sum_abs * sum_abs - norm
+    }
+
+    /// 1 / sum_i p_i^2: roughly how many basis states carry the population
+    pub fn participation_ratio(&self) -> f32 {
+        1.0 / self.state.par_iter().map(|c| c.norm_sqr().powi(2)).sum::<f32>()
+    }
+
+    /// Calculate resonance
+    pub fn resonance(&self) -> Vec<f32> {
+        self.calculate_resonance()
+    }
+
+    /// Valence, arousal, coherence, stability and the legacy energy / entropy /
+    /// resonance readings in one object
+    pub fn emotional_profile(&self, py: Python) -> EmotionalProfile {
+        py.allow_threads(|| EmotionalProfile::from_state(self))
+    }
+
+    /// Mean FFT magnitude of the amplitudes in `n_bands` contiguous frequency
+    /// bands, after an optional "hann" window and as ln(1 + x) when `log_scale`
+    #[pyo3(signature = (n_bands=3, window="none", log_scale=false))]
+    pub fn resonance_spectrum(&self, n_bands: usize, window: &str, log_scale: bool) -> PyResult<Vec<f32>> {
+        let window = spectrum::Window::from_name(window)?;
+        spectrum::check_bands(n_bands, self.state.len())?;
+        Ok(spectrum::bands(self.state.clone(), n_bands, window, log_scale))
+    }
+
+    /// Mono PCM rendering of the resonance spectrum as a float32 numpy array:
+    /// the strongest FFT bins become sine partials between 110 Hz and 1760 Hz
+    /// weighted by magnitude, scaled by their total weight to stay within [-1, 1]
+    #[pyo3(signature = (duration_s=1.0, sample_rate=44100))]
+    pub fn sonify<'py>(&self, py: Python<'py>, duration_s: f32, sample_rate: u32) -> PyResult<&'py PyArray1<f32>> {
+        py.import("numpy")?;
+        let samples = py.allow_threads(|| spectrum::sonify(self.state.clone(), duration_s, sample_rate))?;
+        Ok(samples.to_pyarray(py))
+    }
+
+    /// (re, im) amplitude of one basis state (character i of the bitstring is
+    /// qubit i, or qubit n - 1 - i with bit_order="big")
+    #[pyo3(signature = (bitstring, bit_order="little"))]
+    pub fn amplitude(&self, bitstring: &str, bit_order: &str) -> PyResult<(f32, f32)> {
+        let c = self.state[parse_ordered(bitstring, self.qubits, bit_order)?];
+        Ok((c.re, c.im))
+    }
+
+    /// |amplitude|^2 of one basis state
+    #[pyo3(signature = (bitstring, bit_order="little"))]
+    pub fn probability(&self, bitstring: &str, bit_order: &str) -> PyResult<f32> {
+        Ok(self.state[parse_ordered(bitstring, self.qubits, bit_order)?].norm_sqr())
+    }
+
+    /// |amplitude|^2 of every basis state, indexed like the state vector (with
+    /// qubit 0 as the most significant index bit when bit_order="big")
+    #[pyo3(signature = (bit_order="little"))]
+    pub fn probabilities(&self, py: Python, bit_order: &str) -> PyResult<Vec<f32>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        Ok(py.allow_threads(|| {
+            let probs = self.state.par_iter().map(|c| c.norm_sqr()).collect();
+            bit_order.permute(probs, self.qubits)
+        }))
+    }
+
+    /// The product state |other> (x) |self>: this state on the low qubits,
+    /// `other` on the qubits above it (as QuantumCircuit.tensor)
+    pub fn kron_with(&self, py: Python, other: &QuantumState) -> PyResult<QuantumState> {
+        let qubits = register_width(self.qubits, other.qubits)?;
+        resources::check_state::<Complex32>(qubits)?;
+        let low = (1 << self.qubits) - 1;
+        let state = py.allow_threads(|| {
+            (0..1usize << qubits).into_par_iter()
+                .map(|i| self.state[i & low] * other.state[i >> self.qubits])
+                .collect()
+        });
+        Ok(QuantumState { qubits, state, phase: self.phase + other.phase, drift: self.drift + other.drift })
+    }
+
+    /// Copy with `n` qubits in |0> appended above the existing ones
+    pub fn add_qubits(&self, n: usize) -> PyResult<QuantumState> {
+        let qubits = register_width(self.qubits, n)?;
+        resources::check_state::<Complex32>(qubits)?;
+        let mut state = self.state.clone();
+        state.resize(1 << qubits, Complex32::new(0.0, 0.0));
+        Ok(QuantumState { qubits, state, phase: self.phase, drift: self.drift })
+    }
+
+    /// The state of the remaining qubits (in their original order, renumbered
+    /// from 0) after tracing out `qubits`, which must be unentangled with them:
+    /// errors when the reduced state rho is further than `tolerance` from pure,
+    /// measured as 1 - <psi|rho|psi>. The global phase is that of the discarded
+    /// qubits' most likely basis state.
+    #[pyo3(signature = (qubits, tolerance=1e-4))]
+    pub fn discard_qubits(&self, py: Python, qubits: Vec<usize>, tolerance: f32) -> PyResult<QuantumState> {
+        if qubits.is_empty() {
+            return Ok(self.clone());
+        }
+        check_subsystem(&qubits, self.qubits)?;
+        if qubits.len() == self.qubits {
+            return Err(pyo3::exceptions::PyValueError::new_err("Cannot discard every qubit of a state"));
+        }
+        let keep: Vec<usize> = (0..self.qubits).filter(|q| !qubits.contains(q)).collect();
+        let k = keep.len();
+        let mut destination = vec![0; self.qubits];
+        for (i, &q) in keep.iter().chain(&qubits).enumerate() {
+            destination[q] = i;
+        }
+        py.allow_threads(|| {
+            // Column j holds the amplitudes with the discarded qubits in basis state j
+            let moved = bitorder::permute_qubits(&self.state, &destination);
+            let columns: Vec<&[Complex32]> = moved.chunks(1 << k).collect();
+            let weight = |c: &[Complex32]| c.iter().map(|a| a.norm_sqr() as f64).sum::<f64>();
+            let (pivot, norm) = columns.iter().map(|c| weight(c)).enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap_or((0, 0.0));
+            let scale = (norm.sqrt().max(f64::MIN_POSITIVE).recip()) as f32;
+            let state: Vec<Complex32> = columns[pivot].iter().map(|&a| a * scale).collect();
+            // <psi|rho|psi> = sum_j |<psi|column j>|^2, which is 1 exactly when
+            // the remaining qubits are in the pure state psi
+            let total: f64 = columns.iter().map(|c| weight(c)).sum();
+            let overlap: f64 = columns.iter()
+                .map(|c| state.iter().zip(c.iter()).map(|(a, b)| a.conj() * b).sum::<Complex32>().norm_sqr() as f64)
+                .sum();
+            let infidelity = (1.0 - overlap / total.max(f64::MIN_POSITIVE)) as f32;
+            if infidelity > tolerance {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Discarded qubits are entangled with the rest (1 - <psi|rho|psi> = {:.3e} > tolerance {:.3e}); \
+                     use reduced_density_matrix() instead", infidelity, tolerance)));
+            }
+            Ok(QuantumState { qubits: k, state, phase: self.phase, drift: self.drift })
+        })
+    }
+
+    /// Copy with qubit order[k] relabelled as qubit k; `order` must list every
+    /// qubit once
+    pub fn permute_qubits(&self, order: Vec<usize>) -> PyResult<QuantumState> {
+        if order.len() != self.qubits {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "order must list all {} qubits (got {})", self.qubits, order.len())));
+        }
+        check_subsystem(&order, self.qubits)?;
+        let mut destination = vec![0; self.qubits];
+        for (k, &q) in order.iter().enumerate() {
+            destination[q] = k;
+        }
+        let state = bitorder::permute_qubits(&self.state, &destination);
+        Ok(QuantumState { qubits: self.qubits, state, phase: self.phase, drift: self.drift })
+    }
+
+    /// Copy with qubit q relabelled as qubit n - 1 - q
+    pub fn reverse_bits(&self) -> QuantumState {
+        QuantumState { qubits: self.qubits, state: BitOrder::Big.permute(self.state.clone(), self.qubits), phase: self.phase, drift: self.drift }
+    }
+
+    /// Probability distribution over the given qubits; bit k of the index is
+    /// the value of qubits[k]
+    pub fn marginal_probabilities(&self, py: Python, qubits: Vec<usize>) -> PyResult<Vec<f32>> {
+        check_subsystem(&qubits, self.qubits)?;
+        Ok(py.allow_threads(|| reduction::marginal_probabilities(self.qubits, &qubits, |i| self.state[i].norm_sqr())))
+    }
+
+    /// <Z_q> for every qubit q, from one parallel pass over |amplitude|^2
+    pub fn z_expectations(&self, py: Python) -> Vec<f32> {
+        py.allow_threads(|| reduction::z_correlations(self.qubits, |i| self.state[i].norm_sqr(), false).0)
+    }
+
+    /// The n x n float32 numpy array of <Z_a Z_b> (unit diagonal) from one
+    /// parallel pass over |amplitude|^2; `connected` subtracts <Z_a><Z_b>
+    #[pyo3(signature = (connected=false))]
+    pub fn zz_correlations<'py>(&self, py: Python<'py>, connected: bool) -> PyResult<&'py PyArray2<f32>> {
+        py.import("numpy")?;
+        let zz = py.allow_threads(|| reduction::z_correlations(self.qubits, |i| self.state[i].norm_sqr(), connected).1);
+        zz.to_pyarray(py).reshape([self.qubits, self.qubits])
+    }
+
+    /// Sample `shots` bitstrings from |amplitude|^2 and return a counts dictionary
+    /// Passing a seed makes the outcome reproducible; bit_order="big" writes
+    /// qubit 0 last
+    #[pyo3(signature = (shots, seed=None, bit_order="little"))]
+    pub fn measure(&self, py: Python, shots: usize, seed: Option<u64>, bit_order: &str) -> PyResult<HashMap<String, usize>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        Ok(bit_order.counts(py.allow_threads(|| self.sample(shots, seed))))
+    }
+
+    /// The `k` most probable basis states as (bitstring, |amplitude|^2), most
+    /// probable first; bit_order="big" writes qubit 0 last
+    #[pyo3(signature = (k, bit_order="little"))]
+    pub fn top_k(&self, py: Python, k: usize, bit_order: &str) -> PyResult<Vec<(String, f32)>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        let best = py.allow_threads(|| sampling::top_k(self.state.len(), k, |i| self.state[i].norm_sqr()));
+        Ok(best.into_iter().map(|(i, p)| (bit_order.bitstring(format_bitstring(i, self.qubits)), p)).collect())
+    }
+
+    /// Quantum mutual information I(A:B) = S(A) + S(B) - S(AB) between two qubit subsets
+    pub fn mutual_information(&self, py: Python, subset_a: Vec<usize>, subset_b: Vec<usize>) -> PyResult<f32> {
+        let joint = joint_subsets(&subset_a, &subset_b, self.qubits)?;
+        Ok(py.allow_threads(|| {
+            let rho_ab = reduction::reduce_pure_state(&self.state, self.qubits, &joint);
+            reduction::mutual_information(&rho_ab, subset_a.len(), subset_b.len())
+        }))
+    }
+
+    /// Entanglement entropy S(A) = -Tr(rho_A log2 rho_A) between `partition` and
+    /// the remaining qubits; the smaller side is reduced since S(A) = S(B) for a pure state
+    pub fn entanglement_entropy(&self, py: Python, partition: Vec<usize>) -> PyResult<f32> {
+        check_subsystem(&partition, self.qubits)?;
+        let rest = reduction::complement(&partition, self.qubits);
+        let side = if rest.len() < partition.len() { rest } else { partition };
+        Ok(py.allow_threads(|| {
+            let rho = reduction::reduce_pure_state(&self.state, self.qubits, &side);
+            reduction::von_neumann_entropy(&rho, 1 << side.len())
+        }))
+    }
+
+    /// Schmidt coefficients s_i of |psi> = sum_i s_i |a_i>|b_i> across `partition`
+    /// and the remaining qubits, in decreasing order and with numerically zero
+    /// terms dropped (so their number is the Schmidt rank). With vectors=True
+    /// returns (coefficients, [|a_i>], [|b_i>]) where |a_i> has qubit
+    /// partition[k] as qubit k and |b_i> the remaining qubits in increasing order.
+    #[pyo3(signature = (partition, vectors=false))]
+    pub fn schmidt_decomposition(&self, py: Python, partition: Vec<usize>, vectors: bool) -> PyResult<PyObject> {
+        check_subsystem(&partition, self.qubits)?;
+        let (coefficients, basis_a, basis_b) = py.allow_threads(|| reduction::schmidt_decomposition(&self.state, self.qubits, &partition));
+        if !vectors {
+            return Ok(coefficients.into_py(py));
+        }
+        let to_states = |basis: Vec<Vec<Complex32>>, qubits: usize| -> Vec<QuantumState> {
+            basis.into_iter().map(|state| QuantumState { qubits, state, phase: 0.0, drift: 0.0 }).collect()
+        };
+        let rest = self.qubits - partition.len();
+        Ok((coefficients, to_states(basis_a, partition.len()), to_states(basis_b, rest)).into_py(py))
+    }
+
+    /// Global phase in (-pi, pi] picked up from RZ gates, which differ from the
+    /// phase-gate convention P(phi) = diag(1, e^(i phi)) of S, T and U by
+    /// e^(-i phi / 2). RZ gates inside c_if blocks are not counted.
+    pub fn global_phase(&self) -> f32 {
+        let tau = std::f32::consts::TAU;
+        let r = self.phase.rem_euclid(tau);
+        if r > std::f32::consts::PI { r - tau } else { r }
+    }
+
+    /// Copy with the global phase removed: the largest amplitude (the first of
+    /// those equal to it within 1e-6 in magnitude) becomes real and positive,
+    /// and global_phase is reset to zero. Canonical states from separate runs
+    /// compare equal amplitude by amplitude.
+    pub fn canonicalize(&self) -> QuantumState {
+        let largest = self.state.iter().map(|c| c.norm_sqr()).fold(0.0f32, f32::max);
+        let pivot = self.state.iter().find(|c| c.norm_sqr() >= largest - 1e-6).copied().unwrap_or_default();
+        let rotation = if pivot.norm() > 0.0 { pivot.conj() / pivot.norm() } else { Complex32::new(1.0, 0.0) };
+        let state = self.state.par_iter().map(|&c| c * rotation).collect();
+        QuantumState { qubits: self.qubits, state, phase: 0.0, drift: self.drift }
+    }
+
+    /// Independent copy of the state; gates applied to either one afterwards
+    /// leave the other untouched
+    pub fn clone_state(&self) -> QuantumState {
+        self.clone()
+    }
+
+    /// Squared norm sum_i |psi_i|^2, accumulated in double precision
+    pub fn norm(&self) -> f64 {
+        self.state.par_iter().map(|c| c.norm_sqr() as f64).sum()
+    }
+
+    /// Rescale the amplitudes to unit norm and return the deviation |1 - norm|
+    /// that was removed; a zero vector is left unchanged
+    pub fn renormalize(&mut self) -> f64 {
+        let norm = self.norm();
+        if norm <= 1e-18 {
+            return 0.0;
+        }
+        let scale = norm.sqrt().recip() as f32;
+        self.state.par_iter_mut().for_each(|c| *c *= scale);
+        let deviation = (1.0 - norm).abs();
+        self.drift += deviation;
+        deviation
+    }
+
+    /// Project onto `qubit` = `value` and renormalize, returning the probability
+    /// of that branch. A branch of zero probability raises and leaves the state
+    /// unchanged.
+    pub fn postselect(&mut self, qubit: usize, value: u8) -> PyResult<f64> {
+        let (mask, keep) = postselect_branch(qubit, value, self.qubits)?;
+        let p: f64 = self.state.par_iter().enumerate()
+            .filter(|(i, _)| i & mask == keep)
+            .map(|(_, c)| c.norm_sqr() as f64)
+            .sum();
+        check_branch_probability(p, qubit, value)?;
+        let scale = p.sqrt().recip() as f32;
+        self.state.par_iter_mut().enumerate().for_each(|(i, c)| {
+            if i & mask == keep {
+                *c *= scale;
+            } else {
+                *c = Complex32::new(0.0, 0.0);
+            }
+        });
+        Ok(p)
+    }
+
+    /// Total |1 - norm| removed by renormalization since the state was created,
+    /// by execute's renormalization guard or explicit renormalize() calls
+    #[getter]
+    pub fn norm_drift(&self) -> f64 {
+        self.drift
+    }
+
+    /// State fidelity |<self|other>|^2
+    pub fn fidelity(&self, other: &QuantumState) -> PyResult<f32> {
+        Ok(self.overlap(other)?.norm_sqr().min(1.0))
+    }
+
+    /// Trace distance between the pure states, sqrt(1 - |<self|other>|^2)
+    pub fn trace_distance(&self, other: &QuantumState) -> PyResult<f32> {
+        Ok((1.0 - self.overlap(other)?.norm_sqr()).max(0.0).sqrt())
+    }
+
+    /// Reduced state of the `keep` qubits (qubit keep[k] becomes qubit k),
+    /// tracing out the rest
+    pub fn reduced_density_matrix(&self, py: Python, keep: Vec<usize>) -> PyResult<DensityMatrix> {
+        check_subsystem(&keep, self.qubits)?;
+        let matrix = py.allow_threads(|| reduction::reduce_pure_state(&self.state, self.qubits, &keep));
+        Ok(DensityMatrix { qubits: keep.len(), matrix })
+    }
+
+    /// Wootters concurrence of the two-qubit reduced state of (q0, q1)
+    pub fn concurrence(&self, q0: usize, q1: usize) -> PyResult<f32> {
+        let pair = joint_subsets(&[q0], &[q1], self.qubits)?;
+        let rho = reduction::reduce_pure_state(&self.state, self.qubits, &pair);
+        Ok(reduction::concurrence(&rho))
+    }
+
+    /// Stabilizer 2-Renyi entropy ("magic"): zero for stabilizer states
+    /// M2 = -log2( sum_P <psi|P|psi>^4 / 2^n ), cost grows as 8^n; limited to
+    /// 8 qubits
+    pub fn magic(&self, py: Python) -> PyResult<f32> {
+        if self.qubits > pauli::MAX_MAGIC_QUBITS {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Stabilizer Renyi entropy is limited to {} qubits (state has {})", pauli::MAX_MAGIC_QUBITS, self.qubits)));
+        }
+        Ok(py.allow_threads(|| pauli::stabilizer_renyi_entropy(&self.state, self.qubits)))
+    }
+}
+
+/// Density Matrix for Mixed State Simulation (Quantum Supremacy)
+#[pyclass(module = "quantum_engine")]
+pub struct DensityMatrix {
+    qubits: usize,
+    matrix: Vec<Complex32>, // Flattened dim x dim matrix
+}
+
+#[pymethods]
+impl DensityMatrix {
+    #[new]
+    pub fn new(qubits: usize) -> PyResult<Self> {
+        resources::check_density::<Complex32>(qubits)?;
+        let dim = 1 << qubits;
+        let mut matrix = vec![Complex32::new(0.0, 0.0); dim * dim];
+        matrix[0] = Complex32::new(1.0, 0.0); // |0><0|
+        Ok(DensityMatrix { qubits, matrix })
+    }
+
+    /// Pickle support: unpickling calls DensityMatrix(qubits) and then restores
+    /// the raw row-major matrix buffer
+    pub fn __getnewargs__(&self) -> (usize,) {
+        (self.qubits,)
+    }
+
+    pub fn __getstate__<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &serialize::buffer_to_bytes(&self.matrix))
+    }
+
+    pub fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.matrix = serialize::buffer_from_bytes(state, self.matrix.len())?;
+        Ok(())
+    }
+
+    /// Write rho to a versioned checkpoint file at `path`
+    pub fn save(&self, py: Python, path: &str) -> PyResult<()> {
+        py.allow_threads(|| checkpoint::save(path, checkpoint::Kind::DensityMatrix, self.qubits, checkpoint::Buffer::F32(&self.matrix)))
+    }
+
+    /// Read a density matrix saved by `save` (from either precision)
+    #[staticmethod]
+    pub fn load(py: Python, path: &str) -> PyResult<Self> {
+        let (qubits, amplitudes) = py.allow_threads(|| checkpoint::load(path, checkpoint::Kind::DensityMatrix))?;
+        Ok(DensityMatrix { qubits, matrix: amplitudes })
+    }
+    
+    pub fn from_pure_state(&mut self, state: &QuantumState) {
+        let dim = 1 << self.qubits;
+        // rho = |psi><psi|
+        self.matrix.par_iter_mut().enumerate().for_each(|(idx, val)| {
+            let row = idx / dim;
+            let col = idx % dim;
+            *val = state.state[row] * state.state[col].conj();
+        });
+    }
+
+    /// Build a mixture rho = sum_i w_i |psi_i><psi_i| from an ensemble of pure
+    /// states. The weights must be finite and non-negative with a positive sum,
+    /// and are normalised to sum to one.
+    #[staticmethod]
+    pub fn from_ensemble(states: Vec<PyRef<QuantumState>>, weights: Vec<f32>) -> PyResult<Self> {
+        if states.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err("Ensemble must contain at least one state"));
+        }
+        if states.len() != weights.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err("States and weights must have the same length"));
+        }
+        let qubits = states[0].qubits;
+        if states.iter().any(|s| s.qubits != qubits) {
+            return Err(errors::DimensionMismatchError::new_err("All ensemble states must have the same qubit count"));
+        }
+        for &w in &weights {
+            check_ensemble_weight(w)?;
+        }
+        let total: f64 = weights.iter().map(|&w| w as f64).sum();
+        if total <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("Ensemble weights must not all be zero"));
+        }
+        resources::check_density::<Complex32>(qubits)?;
+
+        let dim = 1 << qubits;
+        let members: Vec<(&[Complex32], f32)> = states.iter()
+            .zip(&weights)
+            .map(|(s, &w)| (s.state.as_slice(), (w as f64 / total) as f32))
+            .collect();
+        let mut matrix = vec![Complex32::new(0.0, 0.0); dim * dim];
+        matrix.par_chunks_mut(dim).enumerate().for_each(|(row, out)| {
+            for (psi, w) in &members {
+                let a = psi[row] * *w;
+                for (col, val) in out.iter_mut().enumerate() {
+                    *val += a * psi[col].conj();
+                }
+            }
+        });
+        Ok(DensityMatrix { qubits, matrix })
+    }
+
+    /// Incrementally add a weighted pure state: rho <- rho + w |psi><psi|, for
+    /// a finite, non-negative w. The trace grows by w, so the running sum is
+    /// only a density matrix once its weights total one.
+    pub fn accumulate(&mut self, state: &QuantumState, weight: f32) -> PyResult<()> {
+        if state.qubits != self.qubits {
+            return Err(errors::DimensionMismatchError::new_err("State and density matrix qubit counts differ"));
+        }
+        check_ensemble_weight(weight)?;
+        let dim = 1 << self.qubits;
+        self.matrix.par_chunks_mut(dim).enumerate().for_each(|(row, out)| {
+            let a = state.state[row] * weight;
+            for (col, val) in out.iter_mut().enumerate() {
+                *val += a * state.state[col].conj();
+            }
+        });
+        Ok(())
+    }
+    
+    /// Apply single-qubit gate: rho -> U rho U^dagger
+    pub fn apply_gate(&mut self, gate_name: &str, target: usize, param: Option<f32>) -> PyResult<()> {
+        let gate_type = match gate_name {
+            "H" => GateType::H,
+            "X" => GateType::X,
+            "Y" => GateType::Y,
+            "Z" => GateType::Z,
+            "S" => GateType::S,
+            "T" => GateType::T,
+            "SDG" => GateType::SDG,
+            "TDG" => GateType::TDG,
+            "RX" => GateType::RX(param.unwrap_or(0.0)),
+            "RY" => GateType::RY(param.unwrap_or(0.0)),
+            "RZ" => GateType::RZ(param.unwrap_or(0.0)),
+            _ => return Err(errors::InvalidGateError::new_err("Unknown gate")),
+        };
+        self.apply_unitary(&Gate { gate_type, target, control: None })
+    }
+
+    /// Apply CNOT gate: rho -> CNOT rho CNOT
+    pub fn apply_cnot(&mut self, control: usize, target: usize) -> PyResult<()> {
+        self.apply_unitary(&Gate { gate_type: GateType::CNOT, target, control: Some(control) })
+    }
+
+    /// Apply Amplitude Damping (Energy Loss / Depression)
+    /// Kraus operators: E0 = [[1, 0], [0, sqrt(1-p)]], E1 = [[0, sqrt(p)], [0, 0]]
+    /// applied to `qubit`, or independently to every qubit when None.
+    /// `simplified=True` restores the legacy global off-diagonal decay (no population transfer).
+    #[pyo3(signature = (prob, qubit=None, simplified=false))]
+    pub fn apply_amplitude_damping(&mut self, prob: f32, qubit: Option<usize>, simplified: bool) -> PyResult<()> {
+        let p = prob.clamp(0.0, 1.0);
+        if simplified {
+            self.apply_simplified_amplitude_damping(p);
+            return Ok(());
+        }
+
+        let zero = Complex32::new(0.0, 0.0);
+        let e0 = [[Complex32::new(1.0, 0.0), zero], [zero, Complex32::new((1.0 - p).sqrt(), 0.0)]];
+        let e1 = [[zero, Complex32::new(p.sqrt(), 0.0)], [zero, zero]];
+        for q in self.channel_targets(qubit)? {
+            self.apply_channel_1q(q, &[e0, e1]);
+        }
+        Ok(())
+    }
+    
+    /// Apply Depolarizing noise on one qubit
+    /// Kraus operators: sqrt(1-p) I, sqrt(p/3) X, sqrt(p/3) Y, sqrt(p/3) Z
+    pub fn apply_depolarizing(&mut self, qubit: usize, prob: f32) -> PyResult<()> {
+        let p = prob.clamp(0.0, 1.0);
+        self.apply_pauli_channel(qubit, [1.0 - p, p / 3.0, p / 3.0, p / 3.0])
+    }
+
+    /// Apply a custom KrausChannel to one qubit
+    pub fn apply_channel(&mut self, channel: PyRef<KrausChannel>, qubit: usize) -> PyResult<()> {
+        if qubit >= self.qubits {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        self.apply_channel_1q(qubit, &channel.ops);
+        Ok(())
+    }
+
+    /// Apply Bit Flip noise on one qubit
+    /// Kraus operators: sqrt(1-p) I, sqrt(p) X
+    pub fn apply_bit_flip(&mut self, qubit: usize, prob: f32) -> PyResult<()> {
+        let p = prob.clamp(0.0, 1.0);
+        self.apply_pauli_channel(qubit, [1.0 - p, p, 0.0, 0.0])
+    }
+
+    /// Apply Phase Flip noise on one qubit
+    /// Kraus operators: sqrt(1-p) I, sqrt(p) Z
+    pub fn apply_phase_flip(&mut self, qubit: usize, prob: f32) -> PyResult<()> {
+        let p = prob.clamp(0.0, 1.0);
+        self.apply_pauli_channel(qubit, [1.0 - p, 0.0, 0.0, p])
+    }
+
+    /// Apply Phase Damping (Dephasing / Anxiety)
+    /// Kraus operators: E0 = [[1, 0], [0, sqrt(1-p)]], E1 = [[0, 0], [0, sqrt(p)]]
+    /// applied to `qubit`, or independently to every qubit when None.
+    /// `simplified=True` restores the legacy global dephasing (every off-diagonal
+    /// element decayed once by sqrt(1-p)).
+    #[pyo3(signature = (prob, qubit=None, simplified=false))]
+    pub fn apply_phase_damping(&mut self, prob: f32, qubit: Option<usize>, simplified: bool) -> PyResult<()> {
+        let p = prob.clamp(0.0, 1.0);
+        if simplified {
+            self.apply_simplified_phase_damping(p);
+            return Ok(());
+        }
+
+        let zero = Complex32::new(0.0, 0.0);
+        let e0 = [[Complex32::new(1.0, 0.0), zero], [zero, Complex32::new((1.0 - p).sqrt(), 0.0)]];
+        let e1 = [[zero, zero], [zero, Complex32::new(p.sqrt(), 0.0)]];
+        for q in self.channel_targets(qubit)? {
+            self.apply_channel_1q(q, &[e0, e1]);
+        }
+        Ok(())
+    }
+
+    /// Conditional state after finding `qubit` = `value`: rho -> P rho P / Tr(P rho)
+    /// with P the projector onto that value. Returns Tr(P rho); a branch of zero
+    /// probability raises and leaves rho unchanged.
+    pub fn postselect(&mut self, qubit: usize, value: u8) -> PyResult<f64> {
+        let (mask, keep) = postselect_branch(qubit, value, self.qubits)?;
+        let dim = 1 << self.qubits;
+        let p: f64 = (0..dim).filter(|i| i & mask == keep).map(|i| self.matrix[i * dim + i].re as f64).sum();
+        check_branch_probability(p, qubit, value)?;
+        let scale = p.recip() as f32;
+        self.matrix.par_iter_mut().enumerate().for_each(|(idx, val)| {
+            if (idx / dim) & mask == keep && (idx % dim) & mask == keep {
+                *val *= scale;
+            } else {
+                *val = Complex32::new(0.0, 0.0);
+            }
+        });
+        Ok(p)
+    }
+    
+    /// Emotional energy 1 - rho_00; `expectation(observable)` gives Tr(rho O)
+    /// for an arbitrary observable
+    pub fn expectation_value(&self) -> f32 {
+        // Trace(rho * H). H is simplified to be related to distance from ground state.
+        // Energy = 1 - <0|rho|0>
+        let prob_0 = self.matrix[0].re; // rho_00
+        1.0 - prob_0
+    }
+    
+    /// Expectation value Tr(rho O) of a PauliString or Observable (weighted Pauli sum)
+    pub fn expectation(&self, observable: &PyAny) -> PyResult<f32> {
+        let dim = 1 << self.qubits;
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        Ok(terms.iter()
+            .map(|t| t.coeff * pauli::expectation_density(&self.matrix, dim, t.masks))
+            .sum())
+    }
+
+    /// Variance Tr(rho O^2) - Tr(rho O)^2 of a PauliString, Observable or Hamiltonian
+    pub fn variance(&self, observable: &PyAny) -> PyResult<f32> {
+        let dim = 1 << self.qubits;
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        let mean: f64 = terms.iter().map(|t| (t.coeff * pauli::expectation_density(&self.matrix, dim, t.masks)) as f64).sum();
+        Ok((pauli::second_moment_density(&self.matrix, dim, &terms) - mean * mean).max(0.0) as f32)
+    }
+
+    /// Shot-based estimate of Tr(rho O) with each Pauli term measured `shots`
+    /// times in its own eigenbasis; returns (mean, standard error)
+    #[pyo3(signature = (observable, shots, seed=None))]
+    pub fn estimate(&self, observable: &PyAny, shots: usize, seed: Option<u64>) -> PyResult<(f32, f32)> {
+        let dim = 1 << self.qubits;
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        observables::estimate(&terms, shots, seed, |masks| pauli::expectation_density(&self.matrix, dim, masks))
+    }
+
+    /// <b|rho|b> for one basis state (character i of the bitstring is qubit i,
+    /// or qubit n - 1 - i with bit_order="big")
+    #[pyo3(signature = (bitstring, bit_order="little"))]
+    pub fn population(&self, bitstring: &str, bit_order: &str) -> PyResult<f32> {
+        let i = parse_ordered(bitstring, self.qubits, bit_order)?;
+        Ok(self.matrix[i * (1 << self.qubits) + i].re)
+    }
+
+    /// Diagonal of rho: the population of every basis state (with qubit 0 as
+    /// the most significant index bit when bit_order="big")
+    #[pyo3(signature = (bit_order="little"))]
+    pub fn probabilities(&self, bit_order: &str) -> PyResult<Vec<f32>> {
+        Ok(BitOrder::from_name(bit_order)?.permute(self.populations(), self.qubits))
+    }
+
+    /// The `k` most populated basis states as (bitstring, <b|rho|b>), most
+    /// probable first; bit_order="big" writes qubit 0 last
+    #[pyo3(signature = (k, bit_order="little"))]
+    pub fn top_k(&self, py: Python, k: usize, bit_order: &str) -> PyResult<Vec<(String, f32)>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        let dim = 1 << self.qubits;
+        let best = py.allow_threads(|| sampling::top_k(dim, k, |i| self.matrix[i * dim + i].re));
+        Ok(best.into_iter().map(|(i, p)| (bit_order.bitstring(format_bitstring(i, self.qubits)), p)).collect())
+    }
+
+    /// Diagonal of rho (basis-state populations), indexed like the state vector
+    pub fn diagonal(&self) -> Vec<f32> {
+        self.populations()
+    }
+
+    /// Sample `shots` computational-basis outcomes from the populations as a
+    /// counts dictionary. A `noise_model` contributes only its readout errors,
+    /// since rho already holds the evolved state; bit_order="big" writes qubit 0 last
+    #[pyo3(signature = (shots, seed=None, noise_model=None, bit_order="little"))]
+    pub fn sample(&self, py: Python, shots: usize, seed: Option<u64>, noise_model: Option<PyRef<NoiseModel>>, bit_order: &str) -> PyResult<HashMap<String, usize>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        let model: Option<&NoiseModel> = noise_model.as_deref();
+        let counts = py.allow_threads(|| sampling::readout_counts(&self.populations(), self.qubits, shots, seed, model))?;
+        Ok(bit_order.counts(counts))
+    }
+
+    /// Copy with qubit q relabelled as qubit n - 1 - q
+    pub fn reverse_bits(&self) -> DensityMatrix {
+        let n = self.qubits;
+        let dim = 1 << n;
+        let matrix = (0..dim * dim)
+            .into_par_iter()
+            .map(|k| self.matrix[bitorder::reverse(k / dim, n) * dim + bitorder::reverse(k % dim, n)])
+            .collect();
+        DensityMatrix { qubits: n, matrix }
+    }
+
+    /// Population distribution over the given qubits; bit k of the index is
+    /// the value of qubits[k]
+    pub fn marginal_probabilities(&self, qubits: Vec<usize>) -> PyResult<Vec<f32>> {
+        check_subsystem(&qubits, self.qubits)?;
+        let dim = 1 << self.qubits;
+        Ok(reduction::marginal_probabilities(self.qubits, &qubits, |i| self.matrix[i * dim + i].re))
+    }
+
+    /// <Z_q> for every qubit q, from the populations
+    pub fn z_expectations(&self) -> Vec<f32> {
+        let dim = 1 << self.qubits;
+        reduction::z_correlations(self.qubits, |i| self.matrix[i * dim + i].re, false).0
+    }
+
+    /// The n x n float32 numpy array of <Z_a Z_b> (unit diagonal) from the
+    /// populations; `connected` subtracts <Z_a><Z_b>
+    #[pyo3(signature = (connected=false))]
+    pub fn zz_correlations<'py>(&self, py: Python<'py>, connected: bool) -> PyResult<&'py PyArray2<f32>> {
+        py.import("numpy")?;
+        let dim = 1 << self.qubits;
+        let zz = reduction::z_correlations(self.qubits, |i| self.matrix[i * dim + i].re, connected).1;
+        zz.to_pyarray(py).reshape([self.qubits, self.qubits])
+    }
+
+    /// Von Neumann entropy S = -Tr(rho log2 rho) from the eigenvalues of rho
+    pub fn entropy(&self) -> f32 {
+        reduction::von_neumann_entropy(&self.matrix, 1 << self.qubits)
+    }
+
+    /// Linear entropy 1 - Tr(rho^2), a cheap O(4^n) proxy for `entropy`
+    pub fn linear_entropy(&self) -> f32 {
+        1.0 - self.purity()
+    }
+
+    /// Tr(rho^2): 1 for pure states, 2^-n for the maximally mixed state
+    pub fn purity(&self) -> f32 {
+        self.matrix.par_iter().map(|c| c.norm_sqr()).sum()
+    }
+
+    /// l1-norm of coherence, sum over i != j of |rho_ij|
+    pub fn l1_coherence(&self) -> f32 {
+        let dim = 1 << self.qubits;
+        self.matrix.par_iter().enumerate()
+            .filter(|(idx, _)| idx / dim != idx % dim)
+            .map(|(_, c)| c.norm())
+            .sum()
+    }
+
+    /// 1 / sum_i rho_ii^2: roughly how many basis states carry the population
+    pub fn participation_ratio(&self) -> f32 {
+        1.0 / self.populations().iter().map(|p| p * p).sum::<f32>()
+    }
+    
+    /// rho as a (2^n, 2^n) complex64 numpy array (a single copy of the matrix buffer)
+    pub fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<Complex32>> {
+        py.import("numpy")?;
+        let dim = 1 << self.qubits;
+        self.matrix.to_pyarray(py).reshape([dim, dim])
+    }
+
+    /// RGB resonance from the FFT of the diagonal (the basis-state populations)
+    pub fn resonance(&self) -> Vec<f32> {
+        spectrum::rgb(self.diagonal_signal())
+    }
+
+    /// `QuantumState.emotional_profile` for a mixed state
+    pub fn emotional_profile(&self, py: Python) -> EmotionalProfile {
+        py.allow_threads(|| EmotionalProfile::from_density(self))
+    }
+
+    /// `QuantumState.resonance_spectrum` over the diagonal of rho
+    #[pyo3(signature = (n_bands=3, window="none", log_scale=false))]
+    pub fn resonance_spectrum(&self, n_bands: usize, window: &str, log_scale: bool) -> PyResult<Vec<f32>> {
+        let window = spectrum::Window::from_name(window)?;
+        spectrum::check_bands(n_bands, 1 << self.qubits)?;
+        Ok(spectrum::bands(self.diagonal_signal(), n_bands, window, log_scale))
+    }
+
+    /// Entanglement negativity across the bipartition `partition | rest`
+    /// N(rho) = sum of |negative eigenvalues| of the partial transpose rho^T_A
+    pub fn negativity(&self, partition: Vec<usize>) -> PyResult<f32> {
+        check_subsystem(&partition, self.qubits)?;
+        let dim = 1 << self.qubits;
+        let mask = qubit_mask(&partition, self.qubits)?;
+        let rho_pt = linalg::to_c64(&self.partial_transpose(mask));
+        let negative: f64 = linalg::eigvalsh(&rho_pt, dim)
+            .into_iter()
+            .filter(|&l| l < 0.0)
+            .map(|l| -l)
+            .sum();
+        Ok(negative as f32)
+    }
+
+    /// Wootters concurrence of the two-qubit reduced state of (q0, q1)
+    pub fn concurrence(&self, q0: usize, q1: usize) -> PyResult<f32> {
+        let pair = joint_subsets(&[q0], &[q1], self.qubits)?;
+        let rho = reduction::partial_trace(&self.matrix, self.qubits, &pair);
+        Ok(reduction::concurrence(&rho))
+    }
+
+    /// Quantum mutual information I(A:B) = S(A) + S(B) - S(AB) between two qubit subsets
+    pub fn mutual_information(&self, subset_a: Vec<usize>, subset_b: Vec<usize>) -> PyResult<f32> {
+        let joint = joint_subsets(&subset_a, &subset_b, self.qubits)?;
+        let rho_ab = reduction::partial_trace(&self.matrix, self.qubits, &joint);
+        Ok(reduction::mutual_information(&rho_ab, subset_a.len(), subset_b.len()))
+    }
+
+    /// Uhlmann fidelity F(rho, sigma) = (Tr sqrt(sqrt(rho) sigma sqrt(rho)))^2
+    pub fn fidelity(&self, other: &DensityMatrix) -> PyResult<f32> {
+        self.check_same_size(other)?;
+        let (rho, sigma) = (linalg::to_c64(&self.matrix), linalg::to_c64(&other.matrix));
+        Ok(linalg::fidelity(&rho, &sigma, 1 << self.qubits) as f32)
+    }
+
+    /// Trace distance 1/2 Tr|rho - sigma|
+    pub fn trace_distance(&self, other: &DensityMatrix) -> PyResult<f32> {
+        self.check_same_size(other)?;
+        let (rho, sigma) = (linalg::to_c64(&self.matrix), linalg::to_c64(&other.matrix));
+        Ok(linalg::trace_distance(&rho, &sigma, 1 << self.qubits) as f32)
+    }
+
+    /// A 2n-qubit pure state whose reduction to qubits 0..n is rho:
+    /// sum_k sqrt(lambda_k) |v_k> (x) |k>, with the ancilla on qubits n..2n
+    pub fn purify(&self, py: Python) -> PyResult<QuantumState> {
+        let n = self.qubits;
+        let dim = 1 << n;
+        // The eigendecomposition works on a double-precision copy of rho
+        resources::check_density::<num_complex::Complex64>(n)?;
+        resources::check_state::<Complex32>(2 * n)?;
+        Ok(py.allow_threads(|| {
+            let (values, vectors) = linalg::eigh(&linalg::to_c64(&self.matrix), dim);
+            // Rounding can leave tiny negative eigenvalues; renormalize after clamping
+            let weights: Vec<f64> = values.iter().map(|&l| l.max(0.0)).collect();
+            let total: f64 = weights.iter().sum();
+            let mut state = vec![Complex32::new(0.0, 0.0); dim * dim];
+            for (k, &w) in weights.iter().enumerate() {
+                let scale = (w / total).sqrt();
+                for i in 0..dim {
+                    let c = vectors[i * dim + k] * scale;
+                    state[i | (k << n)] = Complex32::new(c.re as f32, c.im as f32);
+                }
+            }
+            QuantumState { qubits: 2 * n, state, phase: 0.0, drift: 0.0 }
+        }))
+    }
+
+    /// The dominant eigenvector of rho as a state vector, when rho is pure
+    /// within `tolerance` (1 - Tr(rho^2) <= tolerance); errors otherwise. The
+    /// global phase makes the largest amplitude real and positive
+    #[pyo3(signature = (tolerance=1e-4))]
+    pub fn to_pure_state(&self, py: Python, tolerance: f32) -> PyResult<QuantumState> {
+        let mixedness = 1.0 - self.purity();
+        if mixedness > tolerance {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Density matrix is mixed (1 - purity = {:.3e} > tolerance {:.3e}); use purify() instead",
+                mixedness, tolerance)));
+        }
+        let dim = 1 << self.qubits;
+        Ok(py.allow_threads(|| {
+            let (values, vectors) = linalg::eigh(&linalg::to_c64(&self.matrix), dim);
+            let k = (0..dim).max_by(|&a, &b| values[a].total_cmp(&values[b])).unwrap_or(0);
+            let column: Vec<_> = (0..dim).map(|i| vectors[i * dim + k]).collect();
+            let largest = column.iter().copied().max_by(|a, b| a.norm_sqr().total_cmp(&b.norm_sqr())).unwrap_or_default();
+            let phase = largest.conj() / largest.norm();
+            let norm = column.iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt();
+            let state = column.iter()
+                .map(|&c| {
+                    let c = c * phase / norm;
+                    Complex32::new(c.re as f32, c.im as f32)
+                })
+                .collect();
+            QuantumState { qubits: self.qubits, state, phase: 0.0, drift: 0.0 }
+        }))
+    }
+
+    /// Tr_rest rho over every qubit not in `keep` (qubit keep[k] becomes qubit k)
+    pub fn partial_trace(&self, keep: Vec<usize>) -> PyResult<DensityMatrix> {
+        check_subsystem(&keep, self.qubits)?;
+        let matrix = reduction::partial_trace(&self.matrix, self.qubits, &keep);
+        Ok(DensityMatrix { qubits: keep.len(), matrix })
+    }
+}
+
+// Private helper methods
+impl DensityMatrix {
+    fn check_same_size(&self, other: &DensityMatrix) -> PyResult<()> {
+        if self.qubits != other.qubits {
+            return Err(errors::DimensionMismatchError::new_err("Density matrices have different numbers of qubits"));
+        }
+        Ok(())
+    }
+
+    /// Populations rho_ii, indexed like the state vector
+    fn populations(&self) -> Vec<f32> {
+        let dim = 1 << self.qubits;
+        (0..dim).into_par_iter().map(|i| self.matrix[i * dim + i].re).collect()
+    }
+
+    /// Populations rho_ii as a real-valued complex signal
+    fn diagonal_signal(&self) -> Vec<Complex32> {
+        let dim = 1 << self.qubits;
+        (0..dim).map(|i| Complex32::new(self.matrix[i * dim + i].re, 0.0)).collect()
+    }
+
+    /// Qubits a channel acts on: the given one, or all of them
+    fn channel_targets(&self, qubit: Option<usize>) -> PyResult<Vec<usize>> {
+        match qubit {
+            Some(q) if q >= self.qubits => Err(errors::QubitIndexError::new_err("Qubit index out of range")),
+            Some(q) => Ok(vec![q]),
+            None => Ok((0..self.qubits).collect()),
+        }
+    }
+
+    /// rho -> sum_k E_k rho E_k^dagger for single-qubit Kraus operators on `qubit`
+    fn apply_channel_1q(&mut self, qubit: usize, kraus: &[[[Complex32; 2]; 2]]) {
+        let dim = 1 << self.qubits;
+        kernels::channel_1q(&mut self.matrix, dim, qubit, kraus);
+    }
 
-/// Represents the type of quantum gate
-#[derive(Clone, Debug)]
-enum GateType {
-    H, X, Y, Z, S, T,
-    RX(f32), RY(f32), RZ(f32),
-    CNOT, CRY(f32), CRZ(f32)
+    /// Pauli channel rho -> sum_P p_P P rho P with probabilities [p_I, p_X, p_Y, p_Z]
+    fn apply_pauli_channel(&mut self, qubit: usize, probs: [f32; 4]) -> PyResult<()> {
+        if qubit >= self.qubits {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        let zero = Complex32::new(0.0, 0.0);
+        let one = Complex32::new(1.0, 0.0);
+        let i = Complex32::new(0.0, 1.0);
+        let paulis = [
+            [[one, zero], [zero, one]],
+            [[zero, one], [one, zero]],
+            [[zero, -i], [i, zero]],
+            [[one, zero], [zero, -one]],
+        ];
+        let kraus: Vec<[[Complex32; 2]; 2]> = paulis.iter().zip(probs)
+            .filter(|(_, p)| *p > 0.0)
+            .map(|(m, p)| m.map(|row| row.map(|c| c * p.sqrt())))
+            .collect();
+        self.apply_channel_1q(qubit, &kraus);
+        Ok(())
+    }
+
+    /// Legacy phenomenological damping: decays off-diagonals once per qubit
+    fn apply_simplified_amplitude_damping(&mut self, p: f32) {
+        let dim = 1 << self.qubits;
+        let sqrt_1_minus_p = (1.0 - p).sqrt();
+        for _q in 0..self.qubits {
+            self.matrix.par_iter_mut().enumerate().for_each(|(idx, val)| {
+                let row = idx / dim;
+                let col = idx % dim;
+                if row != col {
+                    *val *= sqrt_1_minus_p;
+                }
+            });
+        }
+    }
+
+    fn apply_simplified_phase_damping(&mut self, p: f32) {
+        let dim = 1 << self.qubits;
+        let factor = (1.0 - p).sqrt();
+        // Dephasing only affects off-diagonal elements
+        self.matrix.par_iter_mut().enumerate().for_each(|(idx, val)| {
+            if idx / dim != idx % dim {
+                *val *= factor;
+            }
+        });
+    }
+
+    /// rho -> U rho U^dagger for a circuit instruction.
+    /// The flattened matrix is treated as a 2n-qubit vector whose high n bits index
+    /// rows: U acts on the row qubits and U* on the column qubits.
+    fn apply_unitary(&mut self, gate: &Gate) -> PyResult<()> {
+        let n = self.qubits;
+        let mut vectorized = QuantumState { qubits: 2 * n, state: std::mem::take(&mut self.matrix), phase: 0.0, drift: 0.0 };
+        let result = vectorized.apply(&gate.shifted(n))
+            .and_then(|_| vectorized.apply_conjugate(gate));
+        self.matrix = vectorized.state;
+        result
+    }
+
+    /// Partial transpose over the qubits set in `mask`
+    fn partial_transpose(&self, mask: usize) -> Vec<Complex32> {
+        let dim = 1 << self.qubits;
+        let mut out = vec![Complex32::new(0.0, 0.0); dim * dim];
+        out.par_iter_mut().enumerate().for_each(|(idx, val)| {
+            let row = idx / dim;
+            let col = idx % dim;
+            // Swap the masked bits between row and column indices
+            let src_row = (row & !mask) | (col & mask);
+            let src_col = (col & !mask) | (row & mask);
+            *val = self.matrix[src_row * dim + src_col];
+        });
+        out
+    }
 }
 
-/// Represents a single gate operation in the circuit
-#[derive(Clone, Debug)]
-struct Gate {
-    gate_type: GateType,
-    target: usize,
-    control: Option<usize>,
+/// Build a bitmask from a list of qubit indices, validating each index
+fn qubit_mask(qubits: &[usize], n_qubits: usize) -> PyResult<usize> {
+    let mut mask = 0;
+    for &q in qubits {
+        if q >= n_qubits {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        mask |= 1 << q;
+    }
+    Ok(mask)
 }
 
-/// A quantum circuit builder that mimics Qiskit/PennyLane architecture
-#[pyclass]
-pub struct QuantumCircuit {
-    qubits: usize,
-    gates: Vec<Gate>,
+/// Controlled-phase angle pi / 2^distance between QFT qubits `distance`
+/// apart. Computed in floating point, so wide registers get vanishing (not
+/// overflowed) angles.
+fn qft_angle(distance: usize) -> f32 {
+    std::f32::consts::PI / 2f32.powi(distance.min(i32::MAX as usize) as i32)
 }
 
-#[pymethods]
-impl QuantumCircuit {
-    #[new]
-    pub fn new(qubits: usize) -> Self {
-        QuantumCircuit {
-            qubits,
-            gates: Vec::new(),
+/// Whether `qubits` are pairwise distinct, raising QubitIndexError for any
+/// index past `n_qubits`. Unlike qubit_mask this works for circuits wider than
+/// a machine word, which only the stabilizer and MPS backends can run.
+fn distinct_qubits(qubits: &[usize], n_qubits: usize) -> PyResult<bool> {
+    let mut seen = HashSet::with_capacity(qubits.len());
+    for &q in qubits {
+        if q >= n_qubits {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        if !seen.insert(q) {
+            return Ok(false);
         }
     }
+    Ok(true)
+}
 
-    pub fn h(&mut self, target: usize) {
-        self.gates.push(Gate { gate_type: GateType::H, target, control: None });
+/// Qubit count of `a` qubits joined with `b` more, erroring when 2^(a + b)
+/// amplitudes could not even be indexed
+fn register_width(a: usize, b: usize) -> PyResult<usize> {
+    match a.checked_add(b) {
+        Some(n) if n < usize::BITS as usize => Ok(n),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "A register of {} + {} qubits is too wide to index", a, b))),
     }
+}
 
-    pub fn x(&mut self, target: usize) {
-        self.gates.push(Gate { gate_type: GateType::X, target, control: None });
+/// Parse a bitstring into a basis-state index; character i is the value of qubit i
+fn parse_bitstring(bits: &str, n_qubits: usize) -> PyResult<usize> {
+    if bits.len() != n_qubits {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            format!("Bitstring '{}' has {} bits, expected {}", bits, bits.len(), n_qubits)));
     }
+    bits.chars().enumerate().try_fold(0, |idx, (q, c)| match c {
+        '0' => Ok(idx),
+        '1' => Ok(idx | (1 << q)),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(
+            format!("Invalid character '{}' in bitstring '{}'", c, bits))),
+    })
+}
+
+/// `parse_bitstring` for a bitstring written in the named bit order
+fn parse_ordered(bits: &str, n_qubits: usize, bit_order: &str) -> PyResult<usize> {
+    Ok(BitOrder::from_name(bit_order)?.index(parse_bitstring(bits, n_qubits)?, n_qubits))
+}
+
+/// (re, im) of any Python object `complex()` accepts
+fn extract_complex(value: &PyAny) -> PyResult<(f32, f32)> {
+    let c: &PyComplex = value.py().get_type::<PyComplex>().call1((value,))?.downcast()?;
+    Ok((c.real() as f32, c.imag() as f32))
+}
 
-    pub fn y(&mut self, target: usize) {
-        self.gates.push(Gate { gate_type: GateType::Y, target, control: None });
+/// Format a basis-state index as a bitstring; character i is the value of qubit i
+fn format_bitstring(idx: usize, n_qubits: usize) -> String {
+    (0..n_qubits).map(|q| if (idx >> q) & 1 == 1 { '1' } else { '0' }).collect()
+}
+
+/// Concatenate two disjoint, non-empty qubit subsets into a single keep-list (A first)
+fn joint_subsets(subset_a: &[usize], subset_b: &[usize], n_qubits: usize) -> PyResult<Vec<usize>> {
+    if subset_a.is_empty() || subset_b.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err("Subsets must be non-empty"));
+    }
+    let mask_a = qubit_mask(subset_a, n_qubits)?;
+    let mask_b = qubit_mask(subset_b, n_qubits)?;
+    if mask_a & mask_b != 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("Subsets must be disjoint"));
+    }
+    if mask_a.count_ones() as usize != subset_a.len() || mask_b.count_ones() as usize != subset_b.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err("Subsets must not contain duplicate qubits"));
     }
+    Ok(subset_a.iter().chain(subset_b).copied().collect())
+}
 
-    pub fn z(&mut self, target: usize) {
-        self.gates.push(Gate { gate_type: GateType::Z, target, control: None });
+/// Reject an empty subsystem or one with out-of-range or repeated qubits
+fn check_subsystem(keep: &[usize], n_qubits: usize) -> PyResult<()> {
+    if keep.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err("Subsystem must be non-empty"));
+    }
+    if qubit_mask(keep, n_qubits)?.count_ones() as usize != keep.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err("Subsystem must not contain duplicate qubits"));
     }
+    Ok(())
+}
 
-    pub fn rx(&mut self, target: usize, theta: f32) {
-        self.gates.push(Gate { gate_type: GateType::RX(theta), target, control: None });
+/// Reject ensemble weights that cannot be a probability: negative, NaN or infinite
+fn check_ensemble_weight(weight: f32) -> PyResult<()> {
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Ensemble weights must be finite and non-negative, got {}", weight)));
     }
+    Ok(())
+}
 
-    pub fn ry(&mut self, target: usize, theta: f32) {
-        self.gates.push(Gate { gate_type: GateType::RY(theta), target, control: None });
+/// (mask, masked bits) selecting the basis states with `qubit` = `value`
+fn postselect_branch(qubit: usize, value: u8, n_qubits: usize) -> PyResult<(usize, usize)> {
+    if qubit >= n_qubits {
+        return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
     }
+    if value > 1 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!("Postselected value must be 0 or 1, got {}", value)));
+    }
+    Ok((1 << qubit, (value as usize) << qubit))
+}
 
-    pub fn rz(&mut self, target: usize, phi: f32) {
-        self.gates.push(Gate { gate_type: GateType::RZ(phi), target, control: None });
+/// Reject postselection on a branch the state (numerically) never reaches
+fn check_branch_probability(p: f64, qubit: usize, value: u8) -> PyResult<()> {
+    if p <= 1e-12 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Qubit {} is {} with probability {}; cannot postselect on it", qubit, value, p)));
     }
+    Ok(())
+}
 
-    pub fn cnot(&mut self, control: usize, target: usize) {
-        self.gates.push(Gate { gate_type: GateType::CNOT, target, control: Some(control) });
+// Private helper methods
+impl QuantumState {
+    /// Counts of `shots` bitstrings sampled from |amplitude|^2
+    fn sample(&self, shots: usize, seed: Option<u64>) -> HashMap<String, usize> {
+        let probs: Vec<f32> = self.state.par_iter().map(|c| c.norm_sqr()).collect();
+        sampling::sample_counts(&probs, shots, seed)
+            .into_iter()
+            .map(|(idx, count)| (format_bitstring(idx, self.qubits), count))
+            .collect()
     }
-    
-    pub fn cry(&mut self, control: usize, target: usize, theta: f32) {
-        self.gates.push(Gate { gate_type: GateType::CRY(theta), target, control: Some(control) });
+
+    /// (<X>, <Y>, <Z>) of each qubit
+    fn bloch_vectors(&self) -> Vec<(f32, f32, f32)> {
+        (0..self.qubits)
+            .map(|q| {
+                let m = 1 << q;
+                let e = |x: usize, z: usize| pauli::expectation(&self.state, pauli::PauliMasks::new(x, z));
+                (e(m, 0), e(m, m), e(0, m))
+            })
+            .collect()
     }
 
-    /// Execute the circuit and return the resulting quantum state
-    pub fn execute(&self) -> PyResult<QuantumState> {
-        let mut state = QuantumState::new(self.qubits);
-        
-        for gate in &self.gates {
-            match &gate.gate_type {
-                GateType::H => state.apply_gate("H", gate.target, None)?,
-                GateType::X => state.apply_gate("X", gate.target, None)?,
-                GateType::Y => state.apply_gate("Y", gate.target, None)?,
-                GateType::Z => state.apply_gate("Z", gate.target, None)?,
-                GateType::S => state.apply_gate("S", gate.target, None)?,
-                GateType::T => state.apply_gate("T", gate.target, None)?,
-                GateType::RX(theta) => state.apply_gate("RX", gate.target, Some(*theta))?,
-                GateType::RY(theta) => state.apply_gate("RY", gate.target, Some(*theta))?,
-                GateType::RZ(phi) => state.apply_gate("RZ", gate.target, Some(*phi))?,
-                GateType::CNOT => state.apply_cnot(gate.control.unwrap(), gate.target)?,
-                GateType::CRY(theta) => state.apply_controlled_ry(gate.control.unwrap(), gate.target, *theta)?,
-                GateType::CRZ(_) => return Err(pyo3::exceptions::PyNotImplementedError::new_err("CRZ not implemented yet")),
-            }
+    /// <self|other>
+    fn overlap(&self, other: &QuantumState) -> PyResult<Complex32> {
+        if self.qubits != other.qubits {
+            return Err(errors::DimensionMismatchError::new_err("States have different numbers of qubits"));
         }
-        
-        Ok(state)
+        Ok(self.state.par_iter().zip(other.state.par_iter()).map(|(a, b)| a.conj() * b).sum())
     }
-    /// Execute the circuit with noise and return a Density Matrix
-    pub fn execute_noisy(&self, noise_params: (f32, f32)) -> PyResult<DensityMatrix> {
-        let mut dm = DensityMatrix::new(self.qubits);
-        let (amp_damping, phase_damping) = noise_params;
-        
-        // 1. Evolve pure state first (approximation for efficiency)
-        let mut state = QuantumState::new(self.qubits);
-        for gate in &self.gates {
-             match &gate.gate_type {
-                GateType::H => state.apply_gate("H", gate.target, None)?,
-                GateType::X => state.apply_gate("X", gate.target, None)?,
-                GateType::Y => state.apply_gate("Y", gate.target, None)?,
-                GateType::Z => state.apply_gate("Z", gate.target, None)?,
-                GateType::S => state.apply_gate("S", gate.target, None)?,
-                GateType::T => state.apply_gate("T", gate.target, None)?,
-                GateType::RX(theta) => state.apply_gate("RX", gate.target, Some(*theta))?,
-                GateType::RY(theta) => state.apply_gate("RY", gate.target, Some(*theta))?,
-                GateType::RZ(phi) => state.apply_gate("RZ", gate.target, Some(*phi))?,
-                GateType::CNOT => state.apply_cnot(gate.control.unwrap(), gate.target)?,
-                GateType::CRY(theta) => state.apply_controlled_ry(gate.control.unwrap(), gate.target, *theta)?,
-                GateType::CRZ(_) => return Err(pyo3::exceptions::PyNotImplementedError::new_err("CRZ not implemented yet")),
+
+    /// Dispatch a circuit instruction onto the state vector
+    fn apply(&mut self, gate: &Gate) -> PyResult<()> {
+        match &gate.gate_type {
+            GateType::H => self.apply_gate("H", gate.target, None),
+            GateType::X => self.apply_gate("X", gate.target, None),
+            GateType::Y => self.apply_gate("Y", gate.target, None),
+            GateType::Z => self.apply_gate("Z", gate.target, None),
+            GateType::S => self.apply_gate("S", gate.target, None),
+            GateType::T => self.apply_gate("T", gate.target, None),
+            GateType::SDG => self.apply_gate("SDG", gate.target, None),
+            GateType::TDG => self.apply_gate("TDG", gate.target, None),
+            GateType::RX(theta) => self.apply_gate("RX", gate.target, Some(*theta)),
+            GateType::RY(theta) => self.apply_gate("RY", gate.target, Some(*theta)),
+            GateType::RZ(phi) => self.apply_gate("RZ", gate.target, Some(*phi)),
+            GateType::U(..) | GateType::P(_) => {
+                kernels::validate(gate, self.qubits)?;
+                self.apply_matrix(gate.target, kernels::matrix(&gate.gate_type).unwrap());
+                Ok(())
             }
+            GateType::CNOT => self.apply_cnot(gate.control.unwrap(), gate.target),
+            GateType::CRX(_) | GateType::CRY(_) | GateType::CRZ(_) | GateType::CH | GateType::CPHASE(_) => {
+                let matrix = kernels::matrix(&gate.gate_type).unwrap();
+                self.apply_controlled_1q(gate.control.unwrap(), gate.target, matrix)
+            }
+            GateType::MCX(controls) => self.apply_mcx(controls.clone(), gate.target),
+            GateType::SWAP(a) => self.apply_swap(*a, gate.target),
+            GateType::ISWAP(a) => self.apply_iswap(*a, gate.target),
+            GateType::RXX(..) | GateType::RYY(..) | GateType::RZZ(..) | GateType::CONTROLLED(..) => {
+                kernels::validate(gate, self.qubits)?;
+                kernels::apply(&mut self.state, gate, false);
+                Ok(())
+            }
+            GateType::UNITARY(targets, matrix) => {
+                kernels::validate(gate, self.qubits)?;
+                kernels::apply_k(&mut self.state, targets, matrix);
+                Ok(())
+            }
+            GateType::MEASURE(_) | GateType::RESET | GateType::IF(..) | GateType::RESTORE(_) => Err(dynamic::unsupported()),
+            GateType::SNAPSHOT(_) => Ok(()),
         }
-        
-        // 2. Convert to Density Matrix
-        dm.from_pure_state(&state);
-        
-        // 3. Apply Noise Channels (Decoherence)
-        if amp_damping > 0.0 {
-            dm.apply_amplitude_damping(amp_damping);
-        }
-        if phase_damping > 0.0 {
-            dm.apply_phase_damping(phase_damping);
-        }
-        
-        Ok(dm)
     }
-}
-
-/// High-performance quantum state with SIMD optimization
-#[pyclass]
-pub struct QuantumState {
-    qubits: usize,
-    state: Vec<Complex32>,
-}
 
-#[pymethods]
-impl QuantumState {
-    #[new]
-    pub fn new(qubits: usize) -> Self {
-        let dim = 1 << qubits;
-        let mut state = vec![Complex32::new(0.0, 0.0); dim];
-        state[0] = Complex32::new(1.0, 0.0); // |0...0>
-        QuantumState { qubits, state }
+    /// Apply the entrywise complex conjugate U* of a circuit instruction
+    fn apply_conjugate(&mut self, gate: &Gate) -> PyResult<()> {
+        let conj = |m: [[Complex32; 2]; 2]| m.map(|row| row.map(|c| c.conj()));
+        let t = gate.target;
+        match &gate.gate_type {
+            GateType::Y => self.apply_matrix(t, conj(self.y_gate())),
+            GateType::S => self.apply_matrix(t, conj(self.s_gate())),
+            GateType::T => self.apply_matrix(t, conj(self.t_gate())),
+            GateType::SDG => self.apply_matrix(t, self.s_gate()),
+            GateType::TDG => self.apply_matrix(t, self.t_gate()),
+            GateType::RX(theta) => self.apply_matrix(t, conj(self.rx_gate(*theta))),
+            GateType::RZ(phi) => self.apply_matrix(t, conj(self.rz_gate(*phi))),
+            GateType::U(..) | GateType::P(_) => {
+                kernels::validate(gate, self.qubits)?;
+                self.apply_matrix(t, conj(kernels::matrix(&gate.gate_type).unwrap()));
+            }
+            GateType::CRX(_) | GateType::CRZ(_) | GateType::CPHASE(_) => {
+                let matrix = conj(kernels::matrix(&gate.gate_type).unwrap());
+                self.apply_controlled_1q(gate.control.unwrap(), t, matrix)?
+            }
+            GateType::ISWAP(a) => self.apply_swap_phased(*a, t, Complex32::new(0.0, -1.0))?,
+            GateType::RXX(..) | GateType::RYY(..) | GateType::RZZ(..) | GateType::CONTROLLED(..) => {
+                kernels::validate(gate, self.qubits)?;
+                kernels::apply(&mut self.state, gate, true);
+            }
+            GateType::UNITARY(targets, matrix) => {
+                kernels::validate(gate, self.qubits)?;
+                let conj: Vec<Complex32> = matrix.iter().map(|c| c.conj()).collect();
+                kernels::apply_k(&mut self.state, targets, &conj);
+            }
+            // Remaining gates have real matrices
+            _ => self.apply(gate)?,
+        }
+        Ok(())
     }
 
-    /// Apply single-qubit gate with SIMD optimization
-    pub fn apply_gate(&mut self, gate_name: &str, target: usize, param: Option<f32>) -> PyResult<()> {
-        let dim = 1 << self.qubits;
-        let mut new_state = vec![Complex32::new(0.0, 0.0); dim];
-        
-        let gate = match gate_name {
+    /// 2x2 matrix of a named single-qubit gate
+    fn gate_matrix(&self, gate_name: &str, param: Option<f32>) -> PyResult<[[Complex32; 2]; 2]> {
+        Ok(match gate_name {
             "H" => self.h_gate(),
             "X" => self.x_gate(),
             "Y" => self.y_gate(),
             "Z" => self.z_gate(),
             "S" => self.s_gate(),
             "T" => self.t_gate(),
+            "SDG" => self.s_gate().map(|row| row.map(|c| c.conj())),
+            "TDG" => self.t_gate().map(|row| row.map(|c| c.conj())),
             "RX" => self.rx_gate(param.unwrap_or(0.0)),
             "RY" => self.ry_gate(param.unwrap_or(0.0)),
             "RZ" => self.rz_gate(param.unwrap_or(0.0)),
-            _ => return Err(pyo3::exceptions::PyValueError::new_err("Unknown gate")),
-        };
-
-        let step = 1 << target;
-        
-        // Parallel processing for large states
-        if dim > 1024 {
-            new_state.par_chunks_mut(step * 2)
-                .enumerate()
-                .for_each(|(chunk_idx, chunk)| {
-                    let base = chunk_idx * step * 2;
-                    for j in 0..step {
-                        let idx0 = j;
-                        let idx1 = idx0 + step;
-                        
-                        if idx1 < chunk.len() {
-                            let a = self.state[base + idx0];
-                            let b = self.state[base + idx1];
-                            
-                            chunk[idx0] = gate[0][0] * a + gate[0][1] * b;
-                            chunk[idx1] = gate[1][0] * a + gate[1][1] * b;
-                        }
-                    }
-                });
-        } else {
-            // Sequential for small states
-            for i in (0..dim).step_by(step * 2) {
-                for j in 0..step {
-                    let idx0 = i + j;
-                    let idx1 = idx0 + step;
-                    
-                    let a = self.state[idx0];
-                    let b = self.state[idx1];
-                    
-                    new_state[idx0] = gate[0][0] * a + gate[0][1] * b;
-                    new_state[idx1] = gate[1][0] * a + gate[1][1] * b;
-                }
-            }
-        }
-        
-        self.state = new_state;
-        Ok(())
+            _ => return Err(errors::InvalidGateError::new_err("Unknown gate")),
+        })
     }
 
-    /// Apply CNOT gate with optimized permutation
-    pub fn apply_cnot(&mut self, control: usize, target: usize) -> PyResult<()> {
+    /// Apply a 2x2 matrix to the target qubit where the control qubit is |1>
+    fn apply_controlled_1q(&mut self, control: usize, target: usize, gate: [[Complex32; 2]; 2]) -> PyResult<()> {
         if control >= self.qubits || target >= self.qubits {
-            return Err(pyo3::exceptions::PyValueError::new_err("Qubit index out of range"));
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
         }
         if control == target {
-            return Err(pyo3::exceptions::PyValueError::new_err("Control and target must be different"));
+            return Err(errors::InvalidGateError::new_err("Control and target must be different"));
         }
-
-        let dim = 1 << self.qubits;
-        // Use LSB indexing to match apply_gate
-        let control_mask = 1 << control;
-        let target_mask = 1 << target;
-        
-        // Parallel CNOT for large states
-        if dim > 1024 {
-            let mut new_state = vec![Complex32::new(0.0, 0.0); dim];
-            new_state.par_iter_mut()
-                .enumerate()
-                .for_each(|(i, val)| {
-                    if (i & control_mask) != 0 {
-                        let flipped = i ^ target_mask;
-                        *val = self.state[flipped];
-                    } else {
-                        *val = self.state[i];
-                    }
-                });
-            self.state = new_state;
-        } else {
-            // Sequential for small states
-            let mut new_state = self.state.clone();
-            for i in 0..dim {
-                if (i & control_mask) != 0 {
-                    let flipped = i ^ target_mask;
-                    new_state[i] = self.state[flipped];
-                }
-            }
-            self.state = new_state;
-        }
-        
+        kernels::apply_1q(&mut self.state, target, gate, 1 << control);
         Ok(())
     }
 
-    /// Apply controlled RY gate
-    pub fn apply_controlled_ry(&mut self, control: usize, target: usize, theta: f32) -> PyResult<()> {
-        if control >= self.qubits || target >= self.qubits {
-            return Err(pyo3::exceptions::PyValueError::new_err("Qubit index out of range"));
+    /// Apply a 2x2 matrix to the target qubit in place
+    fn apply_matrix(&mut self, target: usize, gate: [[Complex32; 2]; 2]) {
+        kernels::apply_1q(&mut self.state, target, gate, 0);
+    }
+
+    /// Exchange amplitudes whose bits a and b differ, multiplying them by `phase`
+    fn apply_swap_phased(&mut self, a: usize, b: usize, phase: Complex32) -> PyResult<()> {
+        if a >= self.qubits || b >= self.qubits {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
         }
-        if control == target {
-            return Err(pyo3::exceptions::PyValueError::new_err("Control and target must be different"));
+        if a == b {
+            return Err(errors::InvalidGateError::new_err("Swapped qubits must be different"));
         }
 
-        let dim = 1 << self.qubits;
-        let mut new_state = self.state.clone();
-        
-        // Use LSB indexing
-        let control_mask = 1 << control;
-        let target_step = 1 << target;
-        
-        let ry = self.ry_gate(theta);
-        
-        for i in 0..dim {
-            if (i & control_mask) != 0 && (i & target_step) == 0 {
-                let idx0 = i;
-                let idx1 = i | target_step;
-                
-                let a = self.state[idx0];
-                let b = self.state[idx1];
-                
-                new_state[idx0] = ry[0][0] * a + ry[0][1] * b;
-                new_state[idx1] = ry[1][0] * a + ry[1][1] * b;
-            }
-        }
-        
-        self.state = new_state;
+        kernels::swap_phased(&mut self.state, a, b, phase);
         Ok(())
     }
 
-    /// Calculate energy expectation value
-    pub fn expectation_value(&self) -> f32 {
-        self.calculate_energy()
-    }
-    
-    /// Get state vector for Python
-    pub fn get_state_vector(&self) -> Vec<(f32, f32)> {
-        self.state.iter()
-            .map(|c| (c.re, c.im))
-            .collect()
-    }
-    
-    /// Calculate entropy
-    pub fn entropy(&self) -> f32 {
-        self.calculate_entropy()
-    }
-    
-    /// Calculate resonance
-    pub fn resonance(&self) -> Vec<f32> {
-        self.calculate_resonance()
-    }
-}
-
-/// Density Matrix for Mixed State Simulation (Quantum Supremacy)
-#[pyclass]
-pub struct DensityMatrix {
-    qubits: usize,
-    matrix: Vec<Complex32>, // Flattened dim x dim matrix
-}
-
-#[pymethods]
-impl DensityMatrix {
-    #[new]
-    pub fn new(qubits: usize) -> Self {
-        let dim = 1 << qubits;
-        let mut matrix = vec![Complex32::new(0.0, 0.0); dim * dim];
-        matrix[0] = Complex32::new(1.0, 0.0); // |0><0|
-        DensityMatrix { qubits, matrix }
-    }
-    
-    pub fn from_pure_state(&mut self, state: &QuantumState) {
-        let dim = 1 << self.qubits;
-        // rho = |psi><psi|
-        self.matrix.par_iter_mut().enumerate().for_each(|(idx, val)| {
-            let row = idx / dim;
-            let col = idx % dim;
-            *val = state.state[row] * state.state[col].conj();
-        });
-    }
-    
-    /// Apply Amplitude Damping (Energy Loss / Depression)
-    /// Kraus operators: E0 = [[1, 0], [0, sqrt(1-p)]], E1 = [[0, sqrt(p)], [0, 0]]
-    pub fn apply_amplitude_damping(&mut self, prob: f32) {
-        let dim = 1 << self.qubits;
-        let p = prob.clamp(0.0, 1.0);
-        let sqrt_p = p.sqrt();
-        let sqrt_1_minus_p = (1.0 - p).sqrt();
-        
-        // Apply to each qubit independently (approximation for global noise)
-        for q in 0..self.qubits {
-             // Construct Kraus maps for this qubit... 
-             // For simplicity in this version, we apply a global damping factor to off-diagonal elements
-             // and population transfer to ground state.
-             
-             // Simplified global amplitude damping model for performance
-             // Decay off-diagonals
-             self.matrix.par_iter_mut().enumerate().for_each(|(idx, val)| {
-                 let row = idx / dim;
-                 let col = idx % dim;
-                 if row != col {
-                     *val *= sqrt_1_minus_p;
-                 }
-             });
-             
-             // Population transfer (simplified)
-             // In a full simulation, we'd apply Kraus ops tensor products.
-             // Here we model the phenomenological effect: energy decreases.
-        }
-    }
-    
-    /// Apply Phase Damping (Dephasing / Anxiety)
-    /// Kraus operators: E0 = [[1, 0], [0, sqrt(1-p)]], E1 = [[0, 0], [0, sqrt(p)]]
-    pub fn apply_phase_damping(&mut self, prob: f32) {
-        let dim = 1 << self.qubits;
-        let p = prob.clamp(0.0, 1.0);
-        let factor = (1.0 - p).sqrt();
-        
-        // Dephasing only affects off-diagonal elements
-        self.matrix.par_iter_mut().enumerate().for_each(|(idx, val)| {
-            let row = idx / dim;
-            let col = idx % dim;
-            if row != col {
-                *val *= factor;
-            }
-        });
-    }
-    
-    pub fn expectation_value(&self) -> f32 {
-        // Trace(rho * H). H is simplified to be related to distance from ground state.
-        // Energy = 1 - <0|rho|0>
-        let dim = 1 << self.qubits;
-        let prob_0 = self.matrix[0].re; // rho_00
-        1.0 - prob_0
-    }
-    
-    pub fn entropy(&self) -> f32 {
-        // Von Neumann Entropy S = -Tr(rho log rho)
-        // Hard to compute for large matrices. Use linear entropy S_lin = 1 - Tr(rho^2) as proxy
-        let purity: f32 = self.matrix.par_iter().map(|c| c.norm_sqr()).sum();
-        1.0 - purity
-    }
-    
-    pub fn resonance(&self) -> Vec<f32> {
-        // Extract diagonal (probabilities) for resonance
-        let dim = 1 << self.qubits;
-        let probs: Vec<f32> = (0..dim).map(|i| self.matrix[i * dim + i].re).collect();
-        
-        // Binning
-        let chunk_size = probs.len() / 3;
-        let r = probs[..chunk_size].iter().sum::<f32>() * 2.0;
-        let g = probs[chunk_size..2*chunk_size].iter().sum::<f32>() * 2.0;
-        let b = probs[2*chunk_size..].iter().sum::<f32>() * 2.0;
-        
-        vec![r.min(1.0), g.min(1.0), b.min(1.0)]
-    }
-}
-
-// Private helper methods
-impl QuantumState {
     fn h_gate(&self) -> [[Complex32; 2]; 2] {
         let s = 1.0 / 2.0_f32.sqrt();
         [
@@ -489,17 +3414,6 @@ impl QuantumState {
         ]
     }
 
-    fn normalize(&mut self) {
-        let norm: f32 = self.state.iter()
-            .map(|c| c.norm_sqr())
-            .sum::<f32>()
-            .sqrt();
-        
-        if norm > 1e-9 {
-            self.state.iter_mut().for_each(|c| *c /= norm);
-        }
-    }
-
     fn calculate_energy(&self) -> f32 {
         // Energy = 1 - |<0...0|psi>|^2
         let prob_0 = self.state[0].norm_sqr();
@@ -521,36 +3435,83 @@ impl QuantumState {
     }
 
     fn calculate_resonance(&self) -> Vec<f32> {
-        // FFT-based resonance spectrum
-        let dim = self.state.len();
-        let mut planner = FftPlanner::new();
-        let fft = planner.plan_fft_forward(dim);
-        
-        let mut buffer: Vec<Complex<f32>> = self.state.iter()
-            .map(|c| Complex::new(c.re, c.im))
-            .collect();
-        
-        fft.process(&mut buffer);
-        
-        // Bin spectrum into 3 bands (RGB)
-        let spectrum: Vec<f32> = buffer.iter()
-            .map(|c| c.norm())
-            .collect();
-        
-        let chunk_size = spectrum.len() / 3;
-        let r = spectrum[..chunk_size].iter().sum::<f32>() / chunk_size as f32 * 2.0;
-        let g = spectrum[chunk_size..2*chunk_size].iter().sum::<f32>() / chunk_size as f32 * 2.0;
-        let b = spectrum[2*chunk_size..].iter().sum::<f32>() / (spectrum.len() - 2*chunk_size) as f32 * 2.0;
-        
-        vec![r.min(1.0), g.min(1.0), b.min(1.0)]
+        // FFT-based resonance spectrum, binned into 3 bands (RGB)
+        spectrum::rgb(self.state.clone())
     }
 }
 
+/// Linear cross-entropy benchmarking fidelity F = 2^n <p_ideal(x_i)> - 1
+/// of sampled bitstrings against the ideal output distribution of `circuit`
+#[pyfunction]
+pub fn xeb_fidelity(circuit: &QuantumCircuit, sampled_bitstrings: Vec<String>) -> PyResult<f32> {
+    if sampled_bitstrings.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err("No sampled bitstrings provided"));
+    }
+    let state = circuit.simulate(&mut dynamic::ClassicalRegister::new(circuit.clbits, None), 1)?;
+    let indices = sampled_bitstrings.iter()
+        .map(|bits| parse_bitstring(bits, state.qubits))
+        .collect::<PyResult<Vec<usize>>>()?;
+
+    let total: f64 = indices.par_iter()
+        .map(|&i| state.state[i].norm_sqr() as f64)
+        .sum();
+    let mean = total / indices.len() as f64;
+    Ok(((1u64 << state.qubits) as f64 * mean - 1.0) as f32)
+}
+
 /// Python module initialization
 #[pymodule]
-fn quantum_engine(_py: Python, m: &PyModule) -> PyResult<()> {
+fn quantum_engine(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<QuantumCircuit>()?;
     m.add_class::<QuantumState>()?;
+    m.add_class::<ExecutionResult>()?;
+    m.add_class::<AmplitudeIterator>()?;
     m.add_class::<DensityMatrix>()?;
+    m.add_class::<PauliString>()?;
+    m.add_class::<Observable>()?;
+    m.add_class::<Hamiltonian>()?;
+    m.add_class::<CouplingMap>()?;
+    m.add_class::<NoiseModel>()?;
+    m.add_class::<KrausChannel>()?;
+    m.add_class::<LindbladSolver>()?;
+    m.add_class::<LindbladResult>()?;
+    m.add_class::<QuantumStateF64>()?;
+    m.add_class::<DensityMatrixF64>()?;
+    m.add_class::<TrajectoryResult>()?;
+    m.add_class::<StabilizerState>()?;
+    m.add_class::<MPSState>()?;
+    m.add_class::<SparseState>()?;
+    m.add_class::<QuditCircuit>()?;
+    m.add_class::<QuditState>()?;
+    m.add_class::<DiskState>()?;
+    m.add_class::<ExecutionHandle>()?;
+    m.add_class::<VQE>()?;
+    m.add_class::<VQEResult>()?;
+    m.add_class::<ExpectationCache>()?;
+    m.add_class::<OptimizerBase>()?;
+    m.add_class::<SPSA>()?;
+    m.add_class::<GradientDescent>()?;
+    m.add_class::<Adam>()?;
+    m.add_class::<GroverOperator>()?;
+    m.add_class::<AmplitudeEstimationResult>()?;
+    m.add_class::<ZNEResult>()?;
+    m.add_class::<EmotionalProfile>()?;
+    m.add_class::<EchoDevice>()?;
+    errors::register(py, m)?;
+    m.add_function(wrap_pyfunction!(xeb_fidelity, m)?)?;
+    m.add_function(wrap_pyfunction!(batch::execute_many, m)?)?;
+    m.add_function(wrap_pyfunction!(grover::grover_search, m)?)?;
+    m.add_function(wrap_pyfunction!(distributed::run_worker, m)?)?;
+    m.add_function(wrap_pyfunction!(rng::set_seed, m)?)?;
+    m.add_function(wrap_pyfunction!(resources::set_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(resources::set_max_memory, m)?)?;
+    m.add_function(wrap_pyfunction!(simd::set_simd, m)?)?;
+    m.add_function(wrap_pyfunction!(tomography::tomography_circuits, m)?)?;
+    m.add_function(wrap_pyfunction!(tomography::reconstruct_state, m)?)?;
+    m.add_function(wrap_pyfunction!(observables::expectation_from_counts, m)?)?;
+    m.add_function(wrap_pyfunction!(qkernel::kernel_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(amplitude::amplitude_amplification, m)?)?;
+    m.add_function(wrap_pyfunction!(amplitude::amplitude_estimation, m)?)?;
+    m.add_function(wrap_pyfunction!(mitigation::zne, m)?)?;
     Ok(())
 }
@@ -0,0 +1,208 @@
+//! Small dense linear algebra helpers for Hermitian matrices.
+//!
+//! Matrices are flattened row-major `n x n` buffers, matching the layout used
+//! by `DensityMatrix`. Work is done in double precision so that eigenvalues of
+//! f32 density matrices stay accurate near zero.
+
+use num_complex::{Complex32, Complex64};
+
+const JACOBI_MAX_SWEEPS: usize = 100;
+const JACOBI_TOLERANCE: f64 = 1e-14;
+
+/// Widen an f32 matrix buffer to f64
+pub fn to_c64(matrix: &[Complex32]) -> Vec<Complex64> {
+    matrix.iter().map(|c| Complex64::new(c.re as f64, c.im as f64)).collect()
+}
+
+/// Eigen-decomposition of a Hermitian matrix via cyclic complex Jacobi rotations.
+/// Returns (eigenvalues, eigenvectors) with eigenvector k stored in column k.
+pub fn eigh(matrix: &[Complex64], n: usize) -> (Vec<f64>, Vec<Complex64>) {
+    let mut a = matrix.to_vec();
+    let mut v = vec![Complex64::new(0.0, 0.0); n * n];
+    for i in 0..n {
+        v[i * n + i] = Complex64::new(1.0, 0.0);
+    }
+
+    let scale: f64 = a.iter().map(|c| c.norm_sqr()).sum::<f64>().max(1e-300);
+
+    for _ in 0..JACOBI_MAX_SWEEPS {
+        let off: f64 = (0..n)
+            .flat_map(|r| (0..n).filter(move |&c| c != r).map(move |c| (r, c)))
+            .map(|(r, c)| a[r * n + c].norm_sqr())
+            .sum();
+        if off <= JACOBI_TOLERANCE * JACOBI_TOLERANCE * scale {
+            break;
+        }
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                let apq = a[p * n + q];
+                let b = apq.norm();
+                if b < 1e-300 {
+                    continue;
+                }
+                // Phase-rotate the (p, q) block to a real symmetric one, then apply
+                // the classic Jacobi rotation that annihilates the off-diagonal.
+                let phase = apq / b;
+                let app = a[p * n + p].re;
+                let aqq = a[q * n + q].re;
+                let tau = (aqq - app) / (2.0 * b);
+                let t = if tau >= 0.0 {
+                    1.0 / (tau + (1.0 + tau * tau).sqrt())
+                } else {
+                    -1.0 / (-tau + (1.0 + tau * tau).sqrt())
+                };
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                let s = t * c;
+
+                // J = [[c, s], [-s * conj(phase), c * conj(phase)]] on columns (p, q)
+                let jpp = Complex64::new(c, 0.0);
+                let jpq = Complex64::new(s, 0.0);
+                let jqp = -phase.conj() * s;
+                let jqq = phase.conj() * c;
+
+                // A <- A J
+                for r in 0..n {
+                    let arp = a[r * n + p];
+                    let arq = a[r * n + q];
+                    a[r * n + p] = arp * jpp + arq * jqp;
+                    a[r * n + q] = arp * jpq + arq * jqq;
+                }
+                // A <- J^dagger A
+                for col in 0..n {
+                    let apc = a[p * n + col];
+                    let aqc = a[q * n + col];
+                    a[p * n + col] = jpp.conj() * apc + jqp.conj() * aqc;
+                    a[q * n + col] = jpq.conj() * apc + jqq.conj() * aqc;
+                }
+                a[p * n + q] = Complex64::new(0.0, 0.0);
+                a[q * n + p] = Complex64::new(0.0, 0.0);
+
+                // V <- V J
+                for r in 0..n {
+                    let vrp = v[r * n + p];
+                    let vrq = v[r * n + q];
+                    v[r * n + p] = vrp * jpp + vrq * jqp;
+                    v[r * n + q] = vrp * jpq + vrq * jqq;
+                }
+            }
+        }
+    }
+
+    let eigenvalues = (0..n).map(|i| a[i * n + i].re).collect();
+    (eigenvalues, v)
+}
+
+/// Eigenvalues of a Hermitian matrix
+pub fn eigvalsh(matrix: &[Complex64], n: usize) -> Vec<f64> {
+    eigh(matrix, n).0
+}
+
+/// Dense product of two `n x n` matrices
+pub fn matmul(a: &[Complex64], b: &[Complex64], n: usize) -> Vec<Complex64> {
+    let mut out = vec![Complex64::new(0.0, 0.0); n * n];
+    for r in 0..n {
+        for k in 0..n {
+            let ark = a[r * n + k];
+            if ark == Complex64::new(0.0, 0.0) {
+                continue;
+            }
+            for c in 0..n {
+                out[r * n + c] += ark * b[k * n + c];
+            }
+        }
+    }
+    out
+}
+
+/// True when U^dagger U is within `tol` of the identity, entry by entry
+pub fn is_unitary(u: &[Complex64], n: usize, tol: f64) -> bool {
+    (0..n).all(|r| {
+        (0..n).all(|c| {
+            let dot: Complex64 = (0..n).map(|k| u[k * n + r].conj() * u[k * n + c]).sum();
+            let expected = if r == c { 1.0 } else { 0.0 };
+            (dot - expected).norm() <= tol
+        })
+    })
+}
+
+/// Thin SVD M = U diag(s) V^dagger of a row-major `rows x cols` matrix, from the
+/// eigen-decomposition of the smaller Gram matrix. Returns (U as rows x k, s, V^dagger
+/// as k x cols) with k = min(rows, cols) and s descending; singular vectors of
+/// numerically zero singular values are left as zeros.
+pub fn svd(m: &[Complex64], rows: usize, cols: usize) -> (Vec<Complex64>, Vec<f64>, Vec<Complex64>) {
+    let zero = Complex64::new(0.0, 0.0);
+    let k = rows.min(cols);
+    let wide = rows <= cols;
+    // Gram matrix M M^dagger (rows x rows) or M^dagger M (cols x cols)
+    let gram: Vec<Complex64> = (0..k * k)
+        .map(|idx| {
+            let (i, j) = (idx / k, idx % k);
+            if wide {
+                (0..cols).map(|c| m[i * cols + c] * m[j * cols + c].conj()).sum()
+            } else {
+                (0..rows).map(|r| m[r * cols + i].conj() * m[r * cols + j]).sum()
+            }
+        })
+        .collect();
+    let (eigenvalues, vectors) = eigh(&gram, k);
+    let mut order: Vec<usize> = (0..k).collect();
+    order.sort_by(|&a, &b| eigenvalues[b].partial_cmp(&eigenvalues[a]).unwrap_or(std::cmp::Ordering::Equal));
+    let s: Vec<f64> = order.iter().map(|&o| eigenvalues[o].max(0.0).sqrt()).collect();
+    let floor = s.first().copied().unwrap_or(0.0) * 1e-14;
+
+    let mut u = vec![zero; rows * k];
+    let mut vh = vec![zero; k * cols];
+    for (n, &o) in order.iter().enumerate() {
+        let vec_entry = |i: usize| vectors[i * k + o];
+        if wide {
+            for r in 0..rows {
+                u[r * k + n] = vec_entry(r);
+            }
+            if s[n] > floor {
+                for c in 0..cols {
+                    vh[n * cols + c] = (0..rows).map(|r| vec_entry(r).conj() * m[r * cols + c]).sum::<Complex64>() / s[n];
+                }
+            }
+        } else {
+            for c in 0..cols {
+                vh[n * cols + c] = vec_entry(c).conj();
+            }
+            if s[n] > floor {
+                for r in 0..rows {
+                    u[r * k + n] = (0..cols).map(|c| m[r * cols + c] * vec_entry(c)).sum::<Complex64>() / s[n];
+                }
+            }
+        }
+    }
+    (u, s, vh)
+}
+
+/// Uhlmann fidelity F = (Tr sqrt(sqrt(rho) sigma sqrt(rho)))^2
+pub fn fidelity(rho: &[Complex64], sigma: &[Complex64], n: usize) -> f64 {
+    let sqrt_rho = sqrtm_psd(rho, n);
+    let inner = matmul(&matmul(&sqrt_rho, sigma, n), &sqrt_rho, n);
+    let root_trace: f64 = eigvalsh(&inner, n).into_iter().map(|l| l.max(0.0).sqrt()).sum();
+    (root_trace * root_trace).min(1.0)
+}
+
+/// Trace distance D = 1/2 Tr|rho - sigma|, the half-sum of |eigenvalues| of the difference
+pub fn trace_distance(rho: &[Complex64], sigma: &[Complex64], n: usize) -> f64 {
+    let diff: Vec<Complex64> = rho.iter().zip(sigma).map(|(a, b)| a - b).collect();
+    0.5 * eigvalsh(&diff, n).into_iter().map(f64::abs).sum::<f64>()
+}
+
+/// Principal square root of a positive semi-definite Hermitian matrix
+pub fn sqrtm_psd(matrix: &[Complex64], n: usize) -> Vec<Complex64> {
+    let (eigenvalues, v) = eigh(matrix, n);
+    let roots: Vec<f64> = eigenvalues.iter().map(|&l| l.max(0.0).sqrt()).collect();
+    let mut out = vec![Complex64::new(0.0, 0.0); n * n];
+    for r in 0..n {
+        for c in 0..n {
+            out[r * n + c] = (0..n)
+                .map(|k| v[r * n + k] * roots[k] * v[c * n + k].conj())
+                .sum();
+        }
+    }
+    out
+}
@@ -0,0 +1,347 @@
+//! Continuous-time open-system evolution under the Lindblad master equation
+//!
+//! ```text
+//! drho/dt = -i[H, rho] + sum_k (L_k rho L_k^dagger - {L_k^dagger L_k, rho} / 2)
+//! ```
+//!
+//! H and every collapse operator L_k are Pauli sums (a Hamiltonian may carry
+//! complex coefficients, so sigma^- = (X + iY) / 2 is "X" + 1j * "Y" halved).
+//! With the effective Hamiltonian G = H - (i/2) sum_k L_k^dagger L_k, multiplied
+//! out once, the right-hand side is -i(G rho - rho G^dagger) + sum_k L_k rho
+//! L_k^dagger, and a Pauli string on either side of rho only permutes and signs
+//! its entries, so no dense operator matrix is built: one evaluation costs
+//! (terms of G + term pairs of the L_k) * 4^n.
+//!
+//! "rk4" takes equal steps of at most `dt` between sample times; "dopri5"
+//! (Dormand-Prince 5(4)) starts from `dt` and adapts the step to keep the RMS
+//! local error under atol + rtol * |rho|, shortening the last step before each
+//! sample time to land on it exactly.
+
+use num_complex::{Complex32, Complex64};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::hamiltonian::{self, Hamiltonian};
+use crate::observables::{self, PauliTerm};
+use crate::pauli::{self, PauliMasks};
+use crate::{errors, linalg, DensityMatrix};
+
+/// Adaptive steps attempted before giving up on reaching the next sample time
+const MAX_STEPS: usize = 10_000_000;
+
+type PauliSum = Vec<(PauliMasks, Complex64)>;
+
+/// Dormand-Prince 5(4) tableau. Row s of DOPRI_A weights the earlier stages
+/// for stage s + 2, and the last row is the fifth-order solution; DOPRI_E is
+/// the fifth-order weights minus the embedded fourth-order ones
+const DOPRI_A: [[f64; 6]; 6] = [
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0, 0.0, 0.0],
+    [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0, 0.0],
+    [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0],
+];
+const DOPRI_E: [f64; 7] = [
+    71.0 / 57600.0, 0.0, -71.0 / 16695.0, 71.0 / 1920.0, -17253.0 / 339200.0, 22.0 / 525.0, -1.0 / 40.0,
+];
+
+/// Pauli sum of a Hamiltonian (complex coefficients kept), PauliString or Observable
+fn pauli_sum(obj: &PyAny) -> PyResult<PauliSum> {
+    if let Ok(h) = obj.extract::<PyRef<Hamiltonian>>() {
+        return Ok(h.pauli_terms().to_vec());
+    }
+    Ok(observables::extract_terms(obj, usize::BITS as usize)?.into_iter()
+        .map(|t| (t.masks, Complex64::new(t.coeff as f64, 0.0)))
+        .collect())
+}
+
+/// (P rho)_ab = left(P, a) rho_(a ^ x) b
+fn left(p: PauliMasks, a: usize) -> Complex64 {
+    let phase = p.y_phase::<f64>();
+    if ((a ^ p.x) & p.z).count_ones().is_multiple_of(2) { phase } else { -phase }
+}
+
+/// (rho P)_ab = rho_a (b ^ x) right(P, b)
+fn right(p: PauliMasks, b: usize) -> Complex64 {
+    let phase = p.y_phase::<f64>();
+    if (b & p.z).count_ones().is_multiple_of(2) { phase } else { -phase }
+}
+
+/// y + sum_j h w_j k_j
+fn combine<S: AsRef<[Complex64]> + Sync>(y: &[Complex64], h: f64, weights: &[f64], stages: &[S]) -> Vec<Complex64> {
+    y.par_iter().enumerate()
+        .map(|(i, &v)| v + weights.iter().zip(stages).map(|(&w, k)| k.as_ref()[i] * (h * w)).sum::<Complex64>())
+        .collect()
+}
+
+/// Outcome of `LindbladSolver.evolve`
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct LindbladResult {
+    /// Sample times, as requested
+    #[pyo3(get)]
+    times: Vec<f64>,
+    /// expectations[i][k] = Tr(rho(times[i]) O_k)
+    #[pyo3(get)]
+    expectations: Vec<Vec<f64>>,
+    /// Integration steps taken (accepted steps for dopri5)
+    #[pyo3(get)]
+    steps: usize,
+    qubits: usize,
+    matrix: Vec<Complex32>,
+}
+
+#[pymethods]
+impl LindbladResult {
+    /// rho at the last sample time
+    #[getter]
+    pub fn final_state(&self) -> DensityMatrix {
+        DensityMatrix { qubits: self.qubits, matrix: self.matrix.clone() }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("LindbladResult(samples={}, steps={})", self.times.len(), self.steps)
+    }
+}
+
+/// Generator of Lindblad dynamics for a Hamiltonian and collapse operators
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone, Debug)]
+pub struct LindbladSolver {
+    /// G = H - (i/2) sum_k L_k^dagger L_k
+    effective: PauliSum,
+    /// (P, Q, c) with sum_k L_k rho L_k^dagger = sum c P rho Q
+    jumps: Vec<(PauliMasks, PauliMasks, Complex64)>,
+    /// One more than the highest qubit any operator acts on
+    width: usize,
+    n_collapse: usize,
+}
+
+#[pymethods]
+impl LindbladSolver {
+    /// `hamiltonian` and each collapse operator are a Hamiltonian, PauliString
+    /// or Observable; collapse operator k is scaled by sqrt(rates[k]) (rates
+    /// default to 1)
+    #[new]
+    #[pyo3(signature = (hamiltonian, collapse_operators=Vec::new(), rates=None))]
+    pub fn new(hamiltonian: &PyAny, collapse_operators: Vec<&PyAny>, rates: Option<Vec<f64>>) -> PyResult<Self> {
+        let h = hamiltonian::merge(pauli_sum(hamiltonian)?.into_iter(), 0.0);
+        if let Some((_, c)) = h.iter().find(|(_, c)| c.im.abs() > 1e-10) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "The Hamiltonian must be Hermitian, but has the coefficient {}", c)));
+        }
+        let rates = rates.unwrap_or_else(|| vec![1.0; collapse_operators.len()]);
+        if rates.len() != collapse_operators.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Got {} rates for {} collapse operators", rates.len(), collapse_operators.len())));
+        }
+        if let Some(r) = rates.iter().find(|r| !(r.is_finite() && **r >= 0.0)) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!("Rate {} is not a non-negative number", r)));
+        }
+
+        let mut decay: PauliSum = Vec::new();
+        let mut jumps: HashMap<(PauliMasks, PauliMasks), Complex64> = HashMap::new();
+        let mut order = Vec::new();
+        for (op, rate) in collapse_operators.into_iter().zip(&rates) {
+            let l: PauliSum = pauli_sum(op)?.into_iter().map(|(m, c)| (m, c * rate.sqrt())).collect();
+            for &(p, a) in &l {
+                for &(q, b) in &l {
+                    // L^dagger L = sum conj(a) b P Q, and L rho L^dagger = sum a conj(b) P rho Q
+                    let (phase, r) = hamiltonian::multiply(p, q);
+                    decay.push((r, a.conj() * b * phase * Complex64::new(0.0, -0.5)));
+                    let entry = jumps.entry((p, q)).or_insert_with(|| {
+                        order.push((p, q));
+                        Complex64::new(0.0, 0.0)
+                    });
+                    *entry += a * b.conj();
+                }
+            }
+        }
+        let effective = hamiltonian::merge(h.into_iter().chain(decay), 0.0);
+        let jumps: Vec<(PauliMasks, PauliMasks, Complex64)> = order.into_iter()
+            .map(|key| (key.0, key.1, jumps[&key]))
+            .filter(|(_, _, c)| c.norm() > 0.0)
+            .collect();
+        let reach = |m: PauliMasks| (usize::BITS - (m.x | m.z).leading_zeros()) as usize;
+        let width = effective.iter().map(|&(m, _)| reach(m))
+            .chain(jumps.iter().flat_map(|&(p, q, _)| [reach(p), reach(q)]))
+            .max()
+            .unwrap_or(0);
+        Ok(LindbladSolver { effective, jumps, width, n_collapse: rates.len() })
+    }
+
+    /// The right-hand side drho/dt at `rho`, as a (traceless, Hermitian) matrix
+    pub fn derivative(&self, py: Python, rho: &DensityMatrix) -> PyResult<DensityMatrix> {
+        self.check_width(rho.qubits)?;
+        let y = linalg::to_c64(&rho.matrix);
+        let dy = py.allow_threads(|| self.rhs(&y, 1 << rho.qubits));
+        Ok(DensityMatrix { qubits: rho.qubits, matrix: dy.iter().map(|c| Complex32::new(c.re as f32, c.im as f32)).collect() })
+    }
+
+    /// Evolve `rho` from t = 0 through the increasing sample `times`, recording
+    /// Tr(rho O) for each of `observables` (PauliString, Observable or
+    /// Hamiltonian) at every sample time. The integration runs in double
+    /// precision; `rho` itself is left unchanged.
+    #[pyo3(signature = (rho, times, observables=Vec::new(), method="dopri5", dt=0.01, rtol=1e-6, atol=1e-8))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn evolve(&self, py: Python, rho: &DensityMatrix, times: Vec<f64>, observables: Vec<&PyAny>, method: &str, dt: f64,
+        rtol: f64, atol: f64) -> PyResult<LindbladResult> {
+        self.check_width(rho.qubits)?;
+        let adaptive = match method.to_ascii_lowercase().as_str() {
+            "dopri5" => true,
+            "rk4" => false,
+            _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown method '{}', expected 'dopri5' or 'rk4'", method))),
+        };
+        if !(dt.is_finite() && dt > 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err("dt must be positive"));
+        }
+        if adaptive && !(rtol > 0.0 || atol > 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err("rtol or atol must be positive"));
+        }
+        if times.iter().any(|t| !(t.is_finite() && *t >= 0.0)) || times.windows(2).any(|w| w[1] < w[0]) {
+            return Err(pyo3::exceptions::PyValueError::new_err("times must be non-negative and non-decreasing"));
+        }
+        let terms = observables.into_iter()
+            .map(|o| observables::extract_terms(o, rho.qubits))
+            .collect::<PyResult<Vec<_>>>()?;
+        let dim = 1 << rho.qubits;
+        let y = linalg::to_c64(&rho.matrix);
+        let (y, expectations, steps) = py.allow_threads(|| {
+            if adaptive {
+                self.integrate_adaptive(y, dim, &times, &terms, dt, rtol.max(0.0), atol.max(0.0))
+            } else {
+                self.integrate_fixed(y, dim, &times, &terms, dt)
+            }
+        })?;
+        Ok(LindbladResult {
+            times,
+            expectations,
+            steps,
+            qubits: rho.qubits,
+            matrix: y.iter().map(|c| Complex32::new(c.re as f32, c.im as f32)).collect(),
+        })
+    }
+
+    #[getter]
+    pub fn num_collapse_operators(&self) -> usize {
+        self.n_collapse
+    }
+
+    fn __repr__(&self) -> String {
+        format!("LindbladSolver(effective_terms={}, jump_terms={}, collapse_operators={})",
+            self.effective.len(), self.jumps.len(), self.n_collapse)
+    }
+}
+
+impl LindbladSolver {
+    fn check_width(&self, qubits: usize) -> PyResult<()> {
+        if self.width > qubits {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "The operators act on {} qubits but the density matrix has {}", self.width, qubits)));
+        }
+        Ok(())
+    }
+
+    /// -i(G rho - rho G^dagger) + sum c P rho Q for row-major `rho`
+    fn rhs(&self, rho: &[Complex64], dim: usize) -> Vec<Complex64> {
+        let minus_i = Complex64::new(0.0, -1.0);
+        let mut out = vec![Complex64::new(0.0, 0.0); dim * dim];
+        out.par_chunks_mut(dim).enumerate().for_each(|(a, row)| {
+            for (b, value) in row.iter_mut().enumerate() {
+                let mut acc = Complex64::new(0.0, 0.0);
+                for &(p, c) in &self.effective {
+                    acc += minus_i * c * left(p, a) * rho[(a ^ p.x) * dim + b];
+                    acc -= minus_i * c.conj() * right(p, b) * rho[a * dim + (b ^ p.x)];
+                }
+                for &(p, q, c) in &self.jumps {
+                    acc += c * left(p, a) * right(q, b) * rho[(a ^ p.x) * dim + (b ^ q.x)];
+                }
+                *value = acc;
+            }
+        });
+        out
+    }
+
+    fn record(rho: &[Complex64], dim: usize, terms: &[Vec<PauliTerm>]) -> Vec<f64> {
+        terms.iter()
+            .map(|t| t.iter().map(|t| t.coeff as f64 * pauli::expectation_density(rho, dim, t.masks)).sum())
+            .collect()
+    }
+
+    /// Classical RK4 with equal steps of at most `dt` between sample times
+    fn integrate_fixed(&self, mut y: Vec<Complex64>, dim: usize, times: &[f64], terms: &[Vec<PauliTerm>], dt: f64)
+        -> PyResult<(Vec<Complex64>, Vec<Vec<f64>>, usize)> {
+        let (mut t, mut steps) = (0.0, 0);
+        let mut expectations = Vec::with_capacity(times.len());
+        for &target in times {
+            let n = ((target - t) / dt).ceil() as usize;
+            let h = if n > 0 { (target - t) / n as f64 } else { 0.0 };
+            for _ in 0..n {
+                let k1 = self.rhs(&y, dim);
+                let k2 = self.rhs(&combine(&y, h / 2.0, &[1.0], &[&k1]), dim);
+                let k3 = self.rhs(&combine(&y, h / 2.0, &[1.0], &[&k2]), dim);
+                let k4 = self.rhs(&combine(&y, h, &[1.0], &[&k3]), dim);
+                y = combine(&y, h / 6.0, &[1.0, 2.0, 2.0, 1.0], &[&k1, &k2, &k3, &k4]);
+                steps += 1;
+            }
+            t = target;
+            expectations.push(Self::record(&y, dim, terms));
+        }
+        Ok((y, expectations, steps))
+    }
+
+    /// Dormand-Prince 5(4) with error control, landing on every sample time
+    #[allow(clippy::too_many_arguments)]
+    fn integrate_adaptive(&self, mut y: Vec<Complex64>, dim: usize, times: &[f64], terms: &[Vec<PauliTerm>], dt: f64,
+        rtol: f64, atol: f64) -> PyResult<(Vec<Complex64>, Vec<Vec<f64>>, usize)> {
+        let (mut t, mut h, mut steps, mut attempts) = (0.0, dt, 0, 0);
+        let mut expectations = Vec::with_capacity(times.len());
+        // First-same-as-last: the last stage of an accepted step is f(y) at its end
+        let mut k1 = self.rhs(&y, dim);
+        for &target in times {
+            while target - t > 1e-12 * target.max(1.0) {
+                attempts += 1;
+                if attempts > MAX_STEPS {
+                    return Err(pyo3::exceptions::PyRuntimeError::new_err(format!(
+                        "dopri5 took {} steps without reaching t = {}; the dynamics may be too stiff", MAX_STEPS, target)));
+                }
+                let clipped = h > target - t;
+                let step = h.min(target - t);
+                let mut stages = vec![k1.clone()];
+                let mut y_new = Vec::new();
+                // The last row holds the fifth-order weights, so the last stage is f(y_new)
+                for a in &DOPRI_A {
+                    y_new = combine(&y, step, &a[..stages.len()], &stages);
+                    stages.push(self.rhs(&y_new, dim));
+                }
+                let error = (y.par_iter().zip(&y_new).enumerate()
+                    .map(|(i, (old, new))| {
+                        let e: Complex64 = DOPRI_E.iter().zip(&stages).map(|(&w, k)| k[i] * (step * w)).sum();
+                        let scale = atol + rtol * old.norm().max(new.norm());
+                        e.norm_sqr() / (scale * scale)
+                    })
+                    .sum::<f64>() / y.len() as f64)
+                    .sqrt();
+                let factor = if error == 0.0 { 5.0 } else { (0.9 * error.powf(-0.2)).clamp(0.2, 5.0) };
+                if error <= 1.0 {
+                    t += step;
+                    y = y_new;
+                    k1 = stages.pop().unwrap_or_default();
+                    steps += 1;
+                    // A step clipped to the sample time says nothing about the step size the local error allows
+                    if !clipped {
+                        h *= factor;
+                    }
+                } else {
+                    h = step * factor;
+                }
+            }
+            t = target;
+            expectations.push(Self::record(&y, dim, terms));
+        }
+        Ok((y, expectations, steps))
+    }
+}
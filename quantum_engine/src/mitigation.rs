@@ -0,0 +1,136 @@
+//! Zero-noise extrapolation.
+//!
+//! Each scale factor lambda gets a variant of the circuit in which gates are
+//! folded, G -> G (G^dagger G)^k, so the variant implements the same unitary
+//! while running about lambda times as many noisy gates. Every gate is folded
+//! floor((lambda - 1) / 2) times and the last few once more to reach fractional
+//! factors; the factor reported is the one actually reached, not the one asked
+//! for, since a short circuit can only scale in steps of 2 / gate count. The
+//! variants are evolved as density matrices under the noise model, and the
+//! expectation values are extrapolated back to lambda = 0: "richardson" fits
+//! the polynomial through every point, "linear" the least-squares line.
+
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::noise::NoiseModel;
+use crate::observables::{self, PauliTerm};
+use crate::{dynamic, pauli, Gate, QuantumCircuit};
+
+/// Outcome of `zne`
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct ZNEResult {
+    /// Extrapolated zero-noise expectation value
+    #[pyo3(get)]
+    estimate: f64,
+    /// Noise scale factor reached by each folded variant
+    #[pyo3(get)]
+    scale_factors: Vec<f64>,
+    /// Noisy expectation value measured at each scale factor
+    #[pyo3(get)]
+    values: Vec<f64>,
+    #[pyo3(get)]
+    method: String,
+}
+
+#[pymethods]
+impl ZNEResult {
+    fn __repr__(&self) -> String {
+        format!("ZNEResult(estimate={}, scale_factors={:?}, values={:?}, method='{}')",
+            self.estimate, self.scale_factors, self.values, self.method)
+    }
+}
+
+/// `gates` folded to noise scale `scale`, with the scale actually reached
+fn fold(gates: &[Gate], scale: f64) -> (Vec<Gate>, f64) {
+    let foldable = gates.iter().filter(|g| !g.is_barrier()).count();
+    if foldable == 0 {
+        return (gates.to_vec(), 1.0);
+    }
+    let every = ((scale - 1.0) / 2.0).floor() as usize;
+    let extra = ((scale - 1.0 - 2.0 * every as f64) / 2.0 * foldable as f64).round() as usize;
+    let mut out = Vec::new();
+    let mut seen = 0;
+    for gate in gates {
+        out.push(gate.clone());
+        if gate.is_barrier() {
+            continue;
+        }
+        let folds = every + usize::from(seen >= foldable - extra);
+        seen += 1;
+        for _ in 0..folds {
+            out.extend(gate.inverse());
+            out.push(gate.clone());
+        }
+    }
+    let reached = 1.0 + 2.0 * (every * foldable + extra) as f64 / foldable as f64;
+    (out, reached)
+}
+
+/// Tr(rho O) after evolving `circuit` with `gates` in place of its own under `model`
+fn noisy_expectation(circuit: &QuantumCircuit, gates: &[Gate], model: &NoiseModel, terms: &[PauliTerm]) -> PyResult<f64> {
+    let mut dm = circuit.initial_density()?;
+    for gate in gates {
+        dm.apply_unitary(gate)?;
+        model.apply_after(gate, &mut dm)?;
+    }
+    let dim = 1 << circuit.qubits;
+    Ok(terms.iter().map(|t| (t.coeff * pauli::expectation_density(&dm.matrix, dim, t.masks)) as f64).sum())
+}
+
+/// Value at 0 of the polynomial through every (x, y)
+fn richardson(x: &[f64], y: &[f64]) -> f64 {
+    (0..x.len())
+        .map(|i| y[i] * (0..x.len()).filter(|&j| j != i).map(|j| x[j] / (x[j] - x[i])).product::<f64>())
+        .sum()
+}
+
+/// Intercept of the least-squares line through (x, y)
+fn linear(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let (mx, my) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+    let sxy: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+    let sxx: f64 = x.iter().map(|a| (a - mx) * (a - mx)).sum();
+    my - sxy / sxx * mx
+}
+
+/// Zero-noise estimate of <observable> for `circuit` under `noise_model`,
+/// extrapolated ("richardson" or "linear") from gate-folded variants at each
+/// of `scale_factors` (all at least 1)
+#[pyfunction]
+#[pyo3(signature = (circuit, observable, noise_model, scale_factors=vec![1.0, 3.0, 5.0], method="richardson"))]
+pub fn zne(py: Python, circuit: &QuantumCircuit, observable: &PyAny, noise_model: PyRef<NoiseModel>, scale_factors: Vec<f64>,
+    method: &str) -> PyResult<ZNEResult> {
+    let extrapolate = match method {
+        "richardson" => richardson,
+        "linear" => linear,
+        _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown extrapolation method '{}', expected 'richardson' or 'linear'", method))),
+    };
+    if dynamic::is_dynamic(&circuit.gates) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "zne needs a unitary circuit (no measurement, reset, c_if or restore)"));
+    }
+    if let Some(s) = scale_factors.iter().find(|s| !(s.is_finite() && **s >= 1.0)) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!("Scale factor {} is below 1", s)));
+    }
+    let terms = observables::extract_terms(observable, circuit.qubits)?;
+    let gates = circuit.bound_gates()?;
+    let folded: Vec<(Vec<Gate>, f64)> = scale_factors.iter().map(|&s| fold(gates, s)).collect();
+    let reached: Vec<f64> = folded.iter().map(|(_, s)| *s).collect();
+    if reached.len() < 2 {
+        return Err(pyo3::exceptions::PyValueError::new_err("zne needs at least two scale factors"));
+    }
+    for (i, a) in reached.iter().enumerate() {
+        if reached[..i].iter().any(|b| (a - b).abs() < 1e-9) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Two scale factors fold to the same circuit (scale {}); use factors further apart", a)));
+        }
+    }
+    let model: &NoiseModel = &noise_model;
+    let values = py.allow_threads(|| folded.par_iter()
+        .map(|(gates, _)| noisy_expectation(circuit, gates, model, &terms))
+        .collect::<PyResult<Vec<f64>>>())?;
+    Ok(ZNEResult { estimate: extrapolate(&reached, &values), scale_factors: reached, values, method: method.to_string() })
+}
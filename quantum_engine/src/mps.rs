@@ -0,0 +1,441 @@
+//! Matrix product state backend for low-entanglement circuits.
+//!
+//! Site i holds a rank-3 tensor A[a, s, b] (left bond, physical bit, right bond)
+//! and the chain is kept in mixed-canonical form around `center`, so the
+//! singular values seen when splitting the center give optimal truncations.
+//! A k-qubit gate first brings its qubits onto adjacent sites with SWAPs (the
+//! site -> qubit assignment is tracked rather than undone), contracts those
+//! sites, applies the 2^k x 2^k matrix and splits the result back with
+//! truncated SVDs. Memory is O(n chi^2) for bond dimension chi instead of O(2^n).
+
+use num_complex::Complex64;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::{kernels, linalg, observables, resources, rng, Gate};
+
+/// Singular values below this fraction of the largest are always discarded
+const RELATIVE_CUTOFF: f64 = 1e-8;
+
+/// One site tensor, stored row-major over (left, physical, right)
+#[derive(Clone, Debug)]
+struct Site {
+    l: usize,
+    r: usize,
+    data: Vec<Complex64>,
+}
+
+/// Pure state as a matrix product state with bounded bond dimension
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone)]
+pub struct MPSState {
+    qubits: usize,
+    max_bond_dim: usize,
+    sites: Vec<Site>,
+    /// Qubit stored at each site
+    qubit_at: Vec<usize>,
+    center: usize,
+    truncation_error: f64,
+}
+
+#[pymethods]
+impl MPSState {
+    /// Sample `shots` computational-basis outcomes as a counts dictionary
+    #[pyo3(signature = (shots, seed=None))]
+    pub fn measure(&self, shots: usize, seed: Option<u64>) -> HashMap<String, usize> {
+        let mut mps = self.clone();
+        mps.move_center(0);
+        let mut rng = rng::seeded(seed);
+        let mut counts = HashMap::new();
+        for _ in 0..shots {
+            *counts.entry(mps.sample(&mut rng)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Expectation value of a PauliString or Observable
+    pub fn expectation(&self, observable: &PyAny) -> PyResult<f64> {
+        let norm = self.pauli_expectation("").re;
+        Ok(observables::extract_labels(observable, self.qubits)?
+            .into_iter()
+            .map(|(coeff, label)| coeff as f64 * self.pauli_expectation(&label).re / norm)
+            .sum())
+    }
+
+    /// Bond dimension between each pair of neighbouring sites
+    #[getter]
+    pub fn bond_dimensions(&self) -> Vec<usize> {
+        self.sites[..self.qubits - 1].iter().map(|s| s.r).collect()
+    }
+
+    #[getter]
+    pub fn max_bond_dim(&self) -> usize {
+        self.max_bond_dim
+    }
+
+    /// Share of the squared norm discarded by truncations, 1 - prod(1 - w) over
+    /// the weight w each one dropped relative to the state it truncated; 0 when
+    /// the simulation is exact and at most 1
+    #[getter]
+    pub fn truncation_error(&self) -> f64 {
+        self.truncation_error
+    }
+
+    /// Contract the chain into the dense 2^n state vector (small registers only)
+    pub fn get_state_vector(&self) -> PyResult<Vec<(f64, f64)>> {
+        resources::check_state::<Complex64>(self.qubits)?;
+        // Contract left to right; the site-order index has site 0 most significant
+        let mut acc = vec![Complex64::new(1.0, 0.0)];
+        let mut bond = 1;
+        for site in &self.sites {
+            let configs = acc.len() / bond;
+            let mut next = vec![Complex64::new(0.0, 0.0); configs * 2 * site.r];
+            for p in 0..configs {
+                for a in 0..bond {
+                    let w = acc[p * bond + a];
+                    for s in 0..2 {
+                        for b in 0..site.r {
+                            next[(p * 2 + s) * site.r + b] += w * site.data[(a * 2 + s) * site.r + b];
+                        }
+                    }
+                }
+            }
+            acc = next;
+            bond = site.r;
+        }
+
+        let mut out = vec![(0.0, 0.0); 1 << self.qubits];
+        for (p, amp) in acc.iter().enumerate() {
+            let idx = (0..self.qubits)
+                .filter(|&i| (p >> (self.qubits - 1 - i)) & 1 == 1)
+                .fold(0, |acc, i| acc | (1 << self.qubit_at[i]));
+            out[idx] = (amp.re, amp.im);
+        }
+        Ok(out)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("MPSState(qubits={}, bond_dimensions={:?}, truncation_error={:e})",
+            self.qubits, self.bond_dimensions(), self.truncation_error)
+    }
+}
+
+impl MPSState {
+    /// |0...0> as a product state
+    fn new(qubits: usize, max_bond_dim: usize) -> Self {
+        let zero = Site { l: 1, r: 1, data: vec![Complex64::new(1.0, 0.0), Complex64::new(0.0, 0.0)] };
+        MPSState {
+            qubits,
+            max_bond_dim,
+            sites: vec![zero; qubits],
+            qubit_at: (0..qubits).collect(),
+            center: 0,
+            truncation_error: 0.0,
+        }
+    }
+
+    /// Apply a unitary circuit instruction
+    fn apply(&mut self, gate: &Gate) -> PyResult<()> {
+        if gate.is_snapshot() {
+            return Ok(());
+        }
+        let qubits = gate.qubits();
+        if qubits.iter().any(|&q| q >= self.qubits) {
+            return Err(crate::errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        let matrix = local_matrix(gate, &qubits)?;
+        let k = qubits.len();
+
+        // Gather the gate's qubits onto the sites p..p+k, keeping their current order
+        let mut sites: Vec<usize> = qubits.iter().map(|&q| self.site_of(q)).collect();
+        sites.sort_unstable();
+        let p = sites[0];
+        for (j, &site) in sites.iter().enumerate() {
+            for i in (p + j..site).rev() {
+                self.swap_sites(i);
+            }
+        }
+
+        // Physical bit of window site p + j (j = 0 most significant) -> bit of the gate matrix
+        let gate_bit: Vec<usize> = (0..k)
+            .map(|j| qubits.iter().position(|&q| q == self.qubit_at[p + j]).unwrap_or(0))
+            .collect();
+        let to_gate = |phys: usize| -> usize {
+            (0..k).filter(|&j| (phys >> (k - 1 - j)) & 1 == 1).fold(0, |acc, j| acc | (1 << gate_bit[j]))
+        };
+        let dim = 1 << k;
+        let index: Vec<usize> = (0..dim).map(to_gate).collect();
+        let local: Vec<Complex64> = (0..dim * dim)
+            .map(|idx| matrix[index[idx / dim] * dim + index[idx % dim]])
+            .collect();
+
+        if k == 1 {
+            let site = &mut self.sites[p];
+            apply_physical(&mut site.data, site.l, 2, site.r, &local);
+            return Ok(());
+        }
+        self.move_center(p);
+        let (mut theta, l, r) = self.merge(p, k);
+        apply_physical(&mut theta, l, dim, r, &local);
+        self.split(p, k, theta, l, r);
+        Ok(())
+    }
+
+    fn site_of(&self, qubit: usize) -> usize {
+        self.qubit_at.iter().position(|&q| q == qubit).unwrap_or(0)
+    }
+
+    /// Exchange the qubits held by sites i and i + 1
+    fn swap_sites(&mut self, i: usize) {
+        self.move_center(i);
+        let (mut theta, l, r) = self.merge(i, 2);
+        let swap = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]
+            .map(|x| Complex64::new(x, 0.0));
+        apply_physical(&mut theta, l, 4, r, &swap);
+        self.split(i, 2, theta, l, r);
+        self.qubit_at.swap(i, i + 1);
+    }
+
+    /// Contract sites p..p+k into one tensor (l, 2^k, r)
+    fn merge(&self, p: usize, k: usize) -> (Vec<Complex64>, usize, usize) {
+        let l = self.sites[p].l;
+        let mut theta = self.sites[p].data.clone();
+        let mut phys = 2;
+        let mut r = self.sites[p].r;
+        for site in &self.sites[p + 1..p + k] {
+            let mut next = vec![Complex64::new(0.0, 0.0); l * phys * 2 * site.r];
+            for row in 0..l * phys {
+                for b in 0..r {
+                    let w = theta[row * r + b];
+                    if w == Complex64::new(0.0, 0.0) {
+                        continue;
+                    }
+                    for s in 0..2 {
+                        for c in 0..site.r {
+                            next[(row * 2 + s) * site.r + c] += w * site.data[(b * 2 + s) * site.r + c];
+                        }
+                    }
+                }
+            }
+            theta = next;
+            phys *= 2;
+            r = site.r;
+        }
+        (theta, l, r)
+    }
+
+    /// Split a (l, 2^k, r) tensor back into sites p..p+k by successive truncated
+    /// SVDs, leaving the orthogonality center on the last of them
+    fn split(&mut self, p: usize, k: usize, theta: Vec<Complex64>, l: usize, r: usize) {
+        let mut rest = theta;
+        let mut left = l;
+        for j in 0..k - 1 {
+            let cols = (1usize << (k - 1 - j)) * r;
+            let (u, s, vh) = linalg::svd(&rest, left * 2, cols);
+            let rank = s.len();
+            let keep = self.truncate(&s);
+            let kept_norm = s[..keep].iter().map(|x| x * x).sum::<f64>().sqrt();
+
+            let mut site = vec![Complex64::new(0.0, 0.0); left * 2 * keep];
+            for row in 0..left * 2 {
+                site[row * keep..(row + 1) * keep].copy_from_slice(&u[row * rank..row * rank + keep]);
+            }
+            self.sites[p + j] = Site { l: left, r: keep, data: site };
+            rest = (0..keep * cols)
+                .map(|idx| vh[idx] * (s[idx / cols] / kept_norm))
+                .collect();
+            left = keep;
+        }
+        self.sites[p + k - 1] = Site { l: left, r, data: rest };
+        self.center = p + k - 1;
+    }
+
+    /// Number of singular values to keep, recording the discarded weight
+    fn truncate(&mut self, s: &[f64]) -> usize {
+        let total: f64 = s.iter().map(|x| x * x).sum();
+        let floor = s[0] * RELATIVE_CUTOFF;
+        let keep = s.iter().take_while(|&&x| x > floor).count().clamp(1, self.max_bond_dim);
+        if total > 0.0 {
+            // The kept part is renormalized, so each truncation removes its
+            // relative weight from what earlier ones left
+            let dropped = s[keep..].iter().map(|x| x * x).sum::<f64>() / total;
+            self.truncation_error += dropped * (1.0 - self.truncation_error);
+        }
+        keep
+    }
+
+    /// Move the orthogonality center to site `to` with exact (untruncated) SVDs
+    fn move_center(&mut self, to: usize) {
+        while self.center < to {
+            let i = self.center;
+            let Site { l, r, data } = self.sites[i].clone();
+            let (u, s, vh) = linalg::svd(&data, l * 2, r);
+            let rank = s.len();
+            let keep = s.iter().take_while(|&&x| x > s[0] * 1e-14).count().max(1);
+            let mut left = vec![Complex64::new(0.0, 0.0); l * 2 * keep];
+            for row in 0..l * 2 {
+                left[row * keep..(row + 1) * keep].copy_from_slice(&u[row * rank..row * rank + keep]);
+            }
+            self.sites[i] = Site { l, r: keep, data: left };
+
+            // Absorb diag(s) V^dagger into the next site
+            let next = &self.sites[i + 1];
+            let mut data = vec![Complex64::new(0.0, 0.0); keep * 2 * next.r];
+            for a in 0..keep {
+                for b in 0..r {
+                    let w = vh[a * r + b] * s[a];
+                    for sc in 0..2 * next.r {
+                        data[a * 2 * next.r + sc] += w * next.data[b * 2 * next.r + sc];
+                    }
+                }
+            }
+            self.sites[i + 1] = Site { l: keep, r: next.r, data };
+            self.center += 1;
+        }
+        while self.center > to {
+            let i = self.center;
+            let Site { l, r, data } = self.sites[i].clone();
+            let (u, s, vh) = linalg::svd(&data, l, 2 * r);
+            let rank = s.len();
+            let keep = s.iter().take_while(|&&x| x > s[0] * 1e-14).count().max(1);
+            self.sites[i] = Site { l: keep, r, data: vh[..keep * 2 * r].to_vec() };
+
+            // Absorb U diag(s) into the previous site
+            let prev = &self.sites[i - 1];
+            let mut data = vec![Complex64::new(0.0, 0.0); prev.l * 2 * keep];
+            for row in 0..prev.l * 2 {
+                for b in 0..l {
+                    let w = prev.data[row * l + b];
+                    for c in 0..keep {
+                        data[row * keep + c] += w * u[b * rank + c] * s[c];
+                    }
+                }
+            }
+            self.sites[i - 1] = Site { l: prev.l, r: keep, data };
+            self.center -= 1;
+        }
+    }
+
+    /// Draw one bitstring; requires the orthogonality center on site 0
+    fn sample(&self, rng: &mut StdRng) -> String {
+        let mut bits = vec!['0'; self.qubits];
+        let mut v = vec![Complex64::new(1.0, 0.0)];
+        for (i, site) in self.sites.iter().enumerate() {
+            let branch = |s: usize| -> Vec<Complex64> {
+                (0..site.r)
+                    .map(|b| (0..site.l).map(|a| v[a] * site.data[(a * 2 + s) * site.r + b]).sum())
+                    .collect()
+            };
+            let (w0, w1) = (branch(0), branch(1));
+            let p0: f64 = w0.iter().map(|c| c.norm_sqr()).sum();
+            let p1: f64 = w1.iter().map(|c| c.norm_sqr()).sum();
+            let (bit, w, p) = if rng.gen::<f64>() * (p0 + p1) < p0 { (0, w0, p0) } else { (1, w1, p1) };
+            if bit == 1 {
+                bits[self.qubit_at[i]] = '1';
+            }
+            let scale = 1.0 / p.max(1e-300).sqrt();
+            v = w.into_iter().map(|c| c * scale).collect();
+        }
+        bits.into_iter().collect()
+    }
+
+    /// <psi|P|psi> for a Pauli label (character i acts on qubit i, missing characters are I)
+    fn pauli_expectation(&self, label: &str) -> Complex64 {
+        let (zero, one, i) = (Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0), Complex64::new(0.0, 1.0));
+        let mut env = vec![one];
+        let mut bond = 1;
+        for (site_idx, site) in self.sites.iter().enumerate() {
+            let op = match label.as_bytes().get(self.qubit_at[site_idx]) {
+                Some(b'X') => [[zero, one], [one, zero]],
+                Some(b'Y') => [[zero, -i], [i, zero]],
+                Some(b'Z') => [[one, zero], [zero, -one]],
+                _ => [[one, zero], [zero, one]],
+            };
+            let (l, r) = (site.l, site.r);
+            // t[a, s', b'] = sum_a' env[a, a'] A[a', s', b']
+            let mut t = vec![zero; bond * 2 * r];
+            for a in 0..bond {
+                for a2 in 0..l {
+                    let e = env[a * bond + a2];
+                    if e == zero {
+                        continue;
+                    }
+                    for sb in 0..2 * r {
+                        t[a * 2 * r + sb] += e * site.data[a2 * 2 * r + sb];
+                    }
+                }
+            }
+            // env'[b, b'] = sum_{a, s, s'} conj(A[a, s, b]) op[s][s'] t[a, s', b']
+            let mut next = vec![zero; r * r];
+            for a in 0..l {
+                for (s, row) in op.iter().enumerate() {
+                    for (s2, &o) in row.iter().enumerate() {
+                        if o == zero {
+                            continue;
+                        }
+                        for b in 0..r {
+                            let bra = site.data[(a * 2 + s) * r + b].conj() * o;
+                            for b2 in 0..r {
+                                next[b * r + b2] += bra * t[(a * 2 + s2) * r + b2];
+                            }
+                        }
+                    }
+                }
+            }
+            env = next;
+            bond = r;
+        }
+        env[0]
+    }
+}
+
+/// Dense matrix of `gate` over `qubits` (bit i of the index is qubits[i]), built by
+/// applying the gate to each basis state of a k-qubit register
+pub fn local_matrix(gate: &Gate, qubits: &[usize]) -> PyResult<Vec<Complex64>> {
+    let local = gate.remapped(|q| qubits.iter().position(|&x| x == q).unwrap_or(0));
+    let k = qubits.len();
+    kernels::validate(&local, k)?;
+    let dim = 1 << k;
+    let mut matrix = vec![Complex64::new(0.0, 0.0); dim * dim];
+    for col in 0..dim {
+        let mut basis = vec![Complex64::new(0.0, 0.0); dim];
+        basis[col] = Complex64::new(1.0, 0.0);
+        kernels::apply(&mut basis, &local, false);
+        for (row, amp) in basis.into_iter().enumerate() {
+            matrix[row * dim + col] = amp;
+        }
+    }
+    Ok(matrix)
+}
+
+/// theta[a, p, b] <- sum_p' m[p][p'] theta[a, p', b] for a (l, phys, r) tensor
+fn apply_physical(theta: &mut [Complex64], l: usize, phys: usize, r: usize, m: &[Complex64]) {
+    let mut column = vec![Complex64::new(0.0, 0.0); phys];
+    for a in 0..l {
+        for b in 0..r {
+            for (p, val) in column.iter_mut().enumerate() {
+                *val = theta[(a * phys + p) * r + b];
+            }
+            for p in 0..phys {
+                theta[(a * phys + p) * r + b] = (0..phys).map(|q| m[p * phys + q] * column[q]).sum();
+            }
+        }
+    }
+}
+
+/// Run a unitary circuit from |0...0> as an MPS with bond dimension at most `max_bond_dim`
+pub fn run(qubits: usize, gates: &[Gate], max_bond_dim: usize) -> PyResult<MPSState> {
+    if max_bond_dim == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("max_bond_dim must be positive"));
+    }
+    if qubits == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("execute_mps needs at least one qubit"));
+    }
+    let mut mps = MPSState::new(qubits, max_bond_dim);
+    for gate in gates {
+        mps.apply(gate)?;
+    }
+    Ok(mps)
+}
@@ -0,0 +1,587 @@
+//! Noise models consulted gate-by-gate during density-matrix execution.
+
+use num_complex::{Complex32, Complex64};
+use numpy::{PyArray2, ToPyArray};
+use pyo3::prelude::*;
+use rand::Rng;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::pauli::PauliMasks;
+use crate::superop::{self, Vectorization};
+use crate::{format_bitstring, parse_bitstring, rng, DensityMatrix, Gate};
+
+/// A single-qubit error channel with its strength
+#[derive(Clone, Debug)]
+pub enum NoiseChannel {
+    AmplitudeDamping(f32),
+    PhaseDamping(f32),
+    Depolarizing(f32),
+    BitFlip(f32),
+    PhaseFlip(f32),
+    /// Relaxation over `time` with energy relaxation time T1 and dephasing time T2
+    ThermalRelaxation { t1: f32, t2: f32, time: f32 },
+}
+
+impl NoiseChannel {
+    pub fn from_name(name: &str, prob: f32) -> PyResult<Self> {
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "amplitude_damping" => NoiseChannel::AmplitudeDamping(prob),
+            "phase_damping" => NoiseChannel::PhaseDamping(prob),
+            "depolarizing" => NoiseChannel::Depolarizing(prob),
+            "bit_flip" => NoiseChannel::BitFlip(prob),
+            "phase_flip" => NoiseChannel::PhaseFlip(prob),
+            _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown noise channel '{}' (expected amplitude_damping, phase_damping, depolarizing, bit_flip or phase_flip)",
+                name))),
+        })
+    }
+
+    /// Thermal relaxation as (amplitude damping gamma, phase damping p): populations
+    /// relax as 1 - exp(-t / T1) and coherences decay as exp(-t / T2), the part
+    /// not already explained by T1 coming from pure dephasing at rate 1/T2 - 1/(2 T1)
+    fn thermal_probabilities(t1: f32, t2: f32, time: f32) -> (f32, f32) {
+        let gamma = 1.0 - (-time / t1).exp();
+        let dephasing_rate = (1.0 / t2 - 0.5 / t1).max(0.0);
+        (gamma, 1.0 - (-2.0 * time * dephasing_rate).exp())
+    }
+
+    /// Single-qubit Kraus operators of the channel (the per-qubit form for phase damping)
+    pub fn kraus(&self) -> Vec<[[Complex32; 2]; 2]> {
+        let zero = Complex32::new(0.0, 0.0);
+        let c = |x: f32| Complex32::new(x, 0.0);
+        let pauli = |p_i: f32, p_x: f32, p_y: f32, p_z: f32| {
+            let i = Complex32::new(0.0, 1.0);
+            [
+                (p_i, [[c(1.0), zero], [zero, c(1.0)]]),
+                (p_x, [[zero, c(1.0)], [c(1.0), zero]]),
+                (p_y, [[zero, -i], [i, zero]]),
+                (p_z, [[c(1.0), zero], [zero, c(-1.0)]]),
+            ]
+            .into_iter()
+            .filter(|(p, _)| *p > 0.0)
+            .map(|(p, m)| m.map(|row| row.map(|e| e * p.sqrt())))
+            .collect()
+        };
+        match *self {
+            NoiseChannel::AmplitudeDamping(p) => {
+                let p = p.clamp(0.0, 1.0);
+                vec![[[c(1.0), zero], [zero, c((1.0 - p).sqrt())]], [[zero, c(p.sqrt())], [zero, zero]]]
+            }
+            NoiseChannel::PhaseDamping(p) => {
+                let p = p.clamp(0.0, 1.0);
+                vec![[[c(1.0), zero], [zero, c((1.0 - p).sqrt())]], [[zero, zero], [zero, c(p.sqrt())]]]
+            }
+            NoiseChannel::Depolarizing(p) => {
+                let p = p.clamp(0.0, 1.0);
+                pauli(1.0 - p, p / 3.0, p / 3.0, p / 3.0)
+            }
+            NoiseChannel::BitFlip(p) => {
+                let p = p.clamp(0.0, 1.0);
+                pauli(1.0 - p, p, 0.0, 0.0)
+            }
+            NoiseChannel::PhaseFlip(p) => {
+                let p = p.clamp(0.0, 1.0);
+                pauli(1.0 - p, 0.0, 0.0, p)
+            }
+            NoiseChannel::ThermalRelaxation { t1, t2, time } => {
+                // Phase damping after amplitude damping: products of the two Kraus sets
+                let (gamma, p) = Self::thermal_probabilities(t1, t2, time);
+                let damping = NoiseChannel::AmplitudeDamping(gamma).kraus();
+                let dephasing = NoiseChannel::PhaseDamping(p).kraus();
+                let mul = |a: &[[Complex32; 2]; 2], b: &[[Complex32; 2]; 2]| {
+                    let mut m = [[zero; 2]; 2];
+                    for (r, row) in m.iter_mut().enumerate() {
+                        for (c, e) in row.iter_mut().enumerate() {
+                            *e = a[r][0] * b[0][c] + a[r][1] * b[1][c];
+                        }
+                    }
+                    m
+                };
+                dephasing.iter()
+                    .flat_map(|d| damping.iter().map(move |a| mul(d, a)))
+                    .filter(|m| m.iter().flatten().any(|e| e.norm_sqr() > 0.0))
+                    .collect()
+            }
+        }
+    }
+
+    /// Apply the channel to one qubit of a density matrix
+    pub fn apply(&self, dm: &mut DensityMatrix, qubit: usize) -> PyResult<()> {
+        match self {
+            NoiseChannel::AmplitudeDamping(p) => dm.apply_amplitude_damping(*p, Some(qubit), false),
+            NoiseChannel::PhaseDamping(p) => dm.apply_phase_damping(*p, Some(qubit), false),
+            NoiseChannel::Depolarizing(p) => dm.apply_depolarizing(qubit, *p),
+            NoiseChannel::BitFlip(p) => dm.apply_bit_flip(qubit, *p),
+            NoiseChannel::PhaseFlip(p) => dm.apply_phase_flip(qubit, *p),
+            NoiseChannel::ThermalRelaxation { t1, t2, time } => {
+                let (gamma, p) = Self::thermal_probabilities(*t1, *t2, *time);
+                dm.apply_amplitude_damping(gamma, Some(qubit), false)?;
+                dm.apply_phase_damping(p, Some(qubit), false)
+            }
+        }
+    }
+}
+
+/// A correlated error on a pair of qubits; both are Pauli channels, so
+/// rho -> sum_P p_P P rho P
+#[derive(Clone, Debug)]
+pub enum PairChannel {
+    /// Each of the 15 non-identity two-qubit Paulis with probability p / 15
+    Depolarizing(f32),
+    /// ZZ crosstalk as correlated dephasing: Z on both qubits with probability p
+    ZZ(f32),
+}
+
+impl PairChannel {
+    pub fn from_name(name: &str, prob: f32) -> PyResult<Self> {
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "depolarizing" => PairChannel::Depolarizing(prob),
+            "zz" => PairChannel::ZZ(prob),
+            _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown pair channel '{}' (expected depolarizing or zz)", name))),
+        })
+    }
+
+    /// (probability, Pauli string) terms on qubits `a` and `b`, identity first;
+    /// the probabilities sum to one
+    pub fn terms(&self, a: usize, b: usize) -> Vec<(f32, PauliMasks)> {
+        // (x, z) bits of I, X, Y, Z
+        let single = |p: usize, q: usize| match p {
+            1 => (1 << q, 0),
+            2 => (1 << q, 1 << q),
+            3 => (0, 1 << q),
+            _ => (0, 0),
+        };
+        let pauli = |pa: usize, pb: usize| {
+            let ((xa, za), (xb, zb)) = (single(pa, a), single(pb, b));
+            PauliMasks::new(xa | xb, za | zb)
+        };
+        let terms = match *self {
+            PairChannel::Depolarizing(p) => {
+                let p = p.clamp(0.0, 1.0);
+                (0..16).map(|k| (if k == 0 { 1.0 - p } else { p / 15.0 }, pauli(k / 4, k % 4))).collect()
+            }
+            PairChannel::ZZ(p) => {
+                let p = p.clamp(0.0, 1.0);
+                vec![(1.0 - p, pauli(0, 0)), (p, pauli(3, 3))]
+            }
+        };
+        terms.into_iter().filter(|(p, _)| *p > 0.0).collect()
+    }
+
+    /// Apply the channel to qubits `a` and `b` of a density matrix
+    pub fn apply(&self, dm: &mut DensityMatrix, a: usize, b: usize) {
+        let dim = 1 << dm.qubits;
+        let terms = self.terms(a, b);
+        let sign = |i: usize, z: usize| if (i & z).count_ones().is_multiple_of(2) { 1.0 } else { -1.0 };
+        // (P rho P)[r][c] = +-rho[r ^ x][c ^ x]; the i^n_y phases cancel
+        let old = std::mem::take(&mut dm.matrix);
+        dm.matrix = (0..dim * dim).into_par_iter()
+            .map(|k| {
+                let (r, c) = (k / dim, k % dim);
+                terms.iter()
+                    .map(|&(p, m)| old[(r ^ m.x) * dim + (c ^ m.x)] * (p * sign(r ^ m.x, m.z) * sign(c ^ m.x, m.z)))
+                    .sum()
+            })
+            .collect();
+    }
+}
+
+/// Single-qubit Kraus operator, row-major
+pub type Kraus2 = [[Complex32; 2]; 2];
+
+/// A row-major `dim x dim` buffer as a numpy array
+fn to_numpy_2d<T: numpy::Element>(py: Python<'_>, values: Vec<T>, dim: usize) -> PyResult<&PyArray2<T>> {
+    // Import first so a missing numpy raises ImportError instead of panicking
+    py.import("numpy")?;
+    values.to_pyarray(py).reshape([dim, dim])
+}
+
+/// A user-supplied single-qubit channel rho -> sum_k E_k rho E_k^dagger
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct KrausChannel {
+    pub(crate) ops: Vec<Kraus2>,
+}
+
+#[pymethods]
+impl KrausChannel {
+    /// `ops` is a list of 2 x 2 matrices of (re, im) entries, which must satisfy
+    /// sum_k E_k^dagger E_k = I to within `tolerance` entry by entry
+    #[new]
+    #[pyo3(signature = (ops, tolerance=1e-4))]
+    pub fn new(ops: Vec<Vec<Vec<(f32, f32)>>>, tolerance: f64) -> PyResult<Self> {
+        if ops.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err("A Kraus channel needs at least one operator"));
+        }
+        let ops = ops.iter()
+            .map(|m| {
+                if m.len() != 2 || m.iter().any(|row| row.len() != 2) {
+                    return Err(pyo3::exceptions::PyValueError::new_err("Kraus operators must be 2 x 2"));
+                }
+                Ok([0, 1].map(|r| [0, 1].map(|c| Complex32::new(m[r][c].0, m[r][c].1))))
+            })
+            .collect::<PyResult<Vec<Kraus2>>>()?;
+
+        for r in 0..2 {
+            for c in 0..2 {
+                let sum: Complex64 = ops.iter()
+                    .flat_map(|e| (0..2).map(move |k| e[k][r].conj() * e[k][c]))
+                    .map(|z| Complex64::new(z.re as f64, z.im as f64))
+                    .sum();
+                let expected = if r == c { 1.0 } else { 0.0 };
+                if (sum - expected).norm() > tolerance {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "Kraus operators are not complete (sum of E^dagger E differs from I)"));
+                }
+            }
+        }
+        Ok(KrausChannel { ops })
+    }
+
+    /// The operators as 2 x 2 matrices of (re, im) entries
+    pub fn ops(&self) -> Vec<Vec<Vec<(f32, f32)>>> {
+        self.ops.iter()
+            .map(|m| m.iter().map(|row| row.iter().map(|c| (c.re, c.im)).collect()).collect())
+            .collect()
+    }
+
+    /// 4 x 4 Choi matrix as a complex numpy array: "column" vectorization
+    /// (QuTiP) gives sum_ab |a><b| (x) E(|a><b|), "row" (forest-benchmarking)
+    /// sum_ab E(|a><b|) (x) |a><b|
+    #[pyo3(signature = (vectorization="column"))]
+    pub fn choi<'py>(&self, py: Python<'py>, vectorization: &str) -> PyResult<&'py PyArray2<Complex64>> {
+        let vectorization = Vectorization::from_name(vectorization)?;
+        to_numpy_2d(py, superop::choi(1, &[(0, self.ops.clone())], vectorization), 4)
+    }
+
+    /// 4 x 4 Pauli transfer matrix R_ij = Tr(P_i E(P_j)) / 2 over (I, X, Y, Z)
+    pub fn ptm<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<f64>> {
+        to_numpy_2d(py, superop::ptm(1, &[(0, self.ops.clone())]), 4)
+    }
+
+    fn __len__(&self) -> usize {
+        self.ops.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("KrausChannel({} operators)", self.ops.len())
+    }
+}
+
+/// Per-gate and per-qubit error channels applied during noisy execution.
+///
+/// After each gate, channels registered for that gate type act on every qubit the
+/// gate touches, then channels registered for a qubit act on it (idle noise that
+/// accumulates once per circuit step whether or not the qubit was used).
+/// With thermal relaxation, every qubit a gate touches then relaxes for the
+/// gate's duration. Pair errors come last, on every registered pair that shares
+/// a qubit with the gate (crosstalk from driving either neighbour). Readout
+/// errors are not channels: they flip bits of sampled outcomes.
+#[pyclass]
+#[derive(Clone, Debug, Default)]
+pub struct NoiseModel {
+    gate_errors: HashMap<String, Vec<NoiseChannel>>,
+    qubit_errors: HashMap<usize, Vec<NoiseChannel>>,
+    /// Keyed by (low qubit, high qubit)
+    pair_errors: HashMap<(usize, usize), Vec<PairChannel>>,
+    /// qubit -> (p(read 1 | actual 0), p(read 0 | actual 1))
+    readout_errors: HashMap<usize, (f32, f32)>,
+    /// (T1, T2) for qubits without their own entry in `thermal_qubits`
+    thermal_default: Option<(f32, f32)>,
+    thermal_qubits: HashMap<usize, (f32, f32)>,
+    gate_durations: HashMap<String, f32>,
+}
+
+/// Durations of gates without a `set_gate_duration` entry, in the unit of T1
+/// and T2 (nanoseconds for the typical superconducting numbers these mimic)
+const DEFAULT_1Q_DURATION: f32 = 50.0;
+const DEFAULT_MULTI_QUBIT_DURATION: f32 = 300.0;
+
+/// Canonical gate name used as a map key ("cx" is an alias of "cnot")
+fn gate_key(gate: &str) -> String {
+    match gate.to_ascii_lowercase().as_str() {
+        "cx" => "cnot".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[pymethods]
+impl NoiseModel {
+    #[new]
+    pub fn new() -> Self {
+        NoiseModel::default()
+    }
+
+    /// Register a channel applied to the qubits of every `gate` (e.g. "cnot", "h", "rx")
+    pub fn add_gate_error(&mut self, gate: &str, channel: &str, prob: f32) -> PyResult<()> {
+        let channel = NoiseChannel::from_name(channel, prob)?;
+        self.gate_errors.entry(gate_key(gate)).or_default().push(channel);
+        Ok(())
+    }
+
+    /// Relax qubits towards |0> after every gate they take part in, over the
+    /// gate's duration: T1 is the energy relaxation time and T2 <= 2 T1 the
+    /// dephasing time. Applies to every qubit unless `qubit` is given.
+    #[pyo3(signature = (t1, t2, qubit=None))]
+    pub fn add_thermal_relaxation(&mut self, t1: f32, t2: f32, qubit: Option<usize>) -> PyResult<()> {
+        if !(t1 > 0.0 && t2 > 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err("T1 and T2 must be positive"));
+        }
+        if t2 > 2.0 * t1 {
+            return Err(pyo3::exceptions::PyValueError::new_err("T2 cannot exceed 2 * T1"));
+        }
+        match qubit {
+            Some(q) => {
+                self.thermal_qubits.insert(q, (t1, t2));
+            }
+            None => self.thermal_default = Some((t1, t2)),
+        }
+        Ok(())
+    }
+
+    /// Duration of `gate` (e.g. "cnot", "h") for thermal relaxation, in the
+    /// unit of T1 and T2. Unset single-qubit gates take 50 and larger ones 300.
+    pub fn set_gate_duration(&mut self, gate: &str, duration: f32) -> PyResult<()> {
+        if !(duration >= 0.0 && duration.is_finite()) {
+            return Err(pyo3::exceptions::PyValueError::new_err("Gate duration must be non-negative"));
+        }
+        self.gate_durations.insert(gate_key(gate), duration);
+        Ok(())
+    }
+
+    /// Register a channel applied to `qubit` after every circuit step
+    pub fn add_qubit_error(&mut self, qubit: usize, channel: &str, prob: f32) -> PyResult<()> {
+        let channel = NoiseChannel::from_name(channel, prob)?;
+        self.qubit_errors.entry(qubit).or_default().push(channel);
+        Ok(())
+    }
+
+    /// Register a correlated channel ("depolarizing" or "zz") on the pair
+    /// `qubits`, applied after every gate that acts on either of them
+    pub fn add_pair_error(&mut self, qubits: (usize, usize), channel: &str, prob: f32) -> PyResult<()> {
+        let (a, b) = qubits;
+        if a == b {
+            return Err(pyo3::exceptions::PyValueError::new_err("A pair error needs two different qubits"));
+        }
+        let channel = PairChannel::from_name(channel, prob)?;
+        self.pair_errors.entry((a.min(b), a.max(b))).or_default().push(channel);
+        Ok(())
+    }
+
+    /// Set the readout confusion of `qubit`: a 0 is read as 1 with probability
+    /// `p1_given0` and a 1 as 0 with probability `p0_given1`
+    pub fn add_readout_error(&mut self, qubit: usize, p1_given0: f32, p0_given1: f32) -> PyResult<()> {
+        if !(0.0..=1.0).contains(&p1_given0) || !(0.0..=1.0).contains(&p0_given1) {
+            return Err(pyo3::exceptions::PyValueError::new_err("Readout error probabilities must be in [0, 1]"));
+        }
+        self.readout_errors.insert(qubit, (p1_given0, p0_given1));
+        Ok(())
+    }
+
+    /// Pass sampled counts (character i is qubit i) through the readout errors,
+    /// flipping each bit of each shot independently
+    #[pyo3(signature = (counts, seed=None))]
+    pub fn apply_readout(&self, counts: HashMap<String, usize>, seed: Option<u64>) -> PyResult<HashMap<String, usize>> {
+        let n = match counts.keys().next() {
+            Some(bits) => bits.len(),
+            None => return Ok(counts),
+        };
+        let counts = counts.iter()
+            .map(|(bits, &c)| Ok((parse_bitstring(bits, n)?, c)))
+            .collect::<PyResult<HashMap<usize, usize>>>()?;
+        Ok(self.readout(counts, n, seed)?
+            .into_iter()
+            .map(|(idx, c)| (format_bitstring(idx, n), c))
+            .collect())
+    }
+
+    /// Choi matrix of the noise the model adds after `gate` on `qubits` (the
+    /// gate itself excluded), over those qubits with qubits[k] as qubit k.
+    /// Idle errors of other qubits and pair errors are left out. `vectorization` is "column"
+    /// (QuTiP) or "row" (forest-benchmarking), as for KrausChannel.choi
+    #[pyo3(signature = (gate, qubits, vectorization="column"))]
+    pub fn choi<'py>(&self, py: Python<'py>, gate: &str, qubits: Vec<usize>, vectorization: &str) -> PyResult<&'py PyArray2<Complex64>> {
+        let vectorization = Vectorization::from_name(vectorization)?;
+        let channels = self.channels_on(gate, &qubits)?;
+        let n = qubits.len();
+        to_numpy_2d(py, py.allow_threads(|| superop::choi(n, &channels, vectorization)), 1 << (2 * n))
+    }
+
+    /// Pauli transfer matrix of the noise the model adds after `gate` on
+    /// `qubits`, over Pauli labels in lexicographic order (II, IX, ..., ZZ)
+    /// whose character k acts on qubits[k]; pair errors are left out
+    pub fn ptm<'py>(&self, py: Python<'py>, gate: &str, qubits: Vec<usize>) -> PyResult<&'py PyArray2<f64>> {
+        let channels = self.channels_on(gate, &qubits)?;
+        let n = qubits.len();
+        to_numpy_2d(py, py.allow_threads(|| superop::ptm(n, &channels)), 1 << (2 * n))
+    }
+
+    /// True when no channels or readout errors are registered
+    pub fn is_ideal(&self) -> bool {
+        !self.has_channels() && !self.has_readout_errors()
+    }
+
+    fn __repr__(&self) -> String {
+        let gate_count: usize = self.gate_errors.values().map(|c| c.len()).sum();
+        let qubit_count: usize = self.qubit_errors.values().map(|c| c.len()).sum();
+        let pair_count: usize = self.pair_errors.values().map(|c| c.len()).sum();
+        let thermal = if self.has_thermal_relaxation() { ", thermal relaxation" } else { "" };
+        format!("NoiseModel({} gate errors, {} qubit errors, {} pair errors, {} readout errors{})",
+            gate_count, qubit_count, pair_count, self.readout_errors.len(), thermal)
+    }
+}
+
+impl NoiseModel {
+    /// True when any gate or idle channel is registered
+    pub(crate) fn has_channels(&self) -> bool {
+        self.gate_errors.values().any(|c| !c.is_empty())
+            || self.qubit_errors.values().any(|c| !c.is_empty())
+            || self.pair_errors.values().any(|c| !c.is_empty())
+            || self.has_thermal_relaxation()
+    }
+
+    fn has_thermal_relaxation(&self) -> bool {
+        self.thermal_default.is_some() || !self.thermal_qubits.is_empty()
+    }
+
+    /// How long a gate named `name` on `n_qubits` qubits takes, for thermal relaxation
+    fn duration(&self, name: &str, n_qubits: usize) -> f32 {
+        match self.gate_durations.get(name) {
+            Some(&d) => d,
+            None if n_qubits == 1 => DEFAULT_1Q_DURATION,
+            None => DEFAULT_MULTI_QUBIT_DURATION,
+        }
+    }
+
+    /// The errors of gate `name` and thermal relaxation over its duration, on each of `qubits`
+    fn gate_channels(&self, name: &str, qubits: &[usize]) -> Vec<(usize, NoiseChannel)> {
+        let mut out = Vec::new();
+        if let Some(channels) = self.gate_errors.get(name) {
+            for &q in qubits {
+                out.extend(channels.iter().map(|c| (q, c.clone())));
+            }
+        }
+        if self.has_thermal_relaxation() {
+            let time = self.duration(name, qubits.len());
+            for &q in qubits {
+                if let Some(&(t1, t2)) = self.thermal_qubits.get(&q).or(self.thermal_default.as_ref()) {
+                    out.push((q, NoiseChannel::ThermalRelaxation { t1, t2, time }));
+                }
+            }
+        }
+        out
+    }
+
+    /// The channel the model applies after `gate` restricted to `qubits`, as
+    /// (position in `qubits`, Kraus operators) in application order; idle
+    /// errors of other qubits are left out
+    fn channels_on(&self, gate: &str, qubits: &[usize]) -> PyResult<Vec<(usize, Vec<Kraus2>)>> {
+        if qubits.is_empty() || (1..qubits.len()).any(|i| qubits[..i].contains(&qubits[i])) {
+            return Err(pyo3::exceptions::PyValueError::new_err("qubits must be a non-empty list of distinct qubits"));
+        }
+        let mut channels = self.gate_channels(&gate_key(gate), qubits);
+        let mut idle: Vec<_> = self.qubit_errors.iter().filter(|(q, _)| qubits.contains(q)).collect();
+        idle.sort_by_key(|(q, _)| **q);
+        for (&q, errors) in idle {
+            channels.extend(errors.iter().map(|c| (q, c.clone())));
+        }
+        Ok(channels.iter()
+            .map(|(q, c)| (qubits.iter().position(|p| p == q).unwrap_or(0), c.kraus()))
+            .collect())
+    }
+
+    pub(crate) fn has_readout_errors(&self) -> bool {
+        self.readout_errors.values().any(|&(p10, p01)| p10 > 0.0 || p01 > 0.0)
+    }
+
+    /// Apply the readout errors shot by shot to counts of n-qubit basis-state
+    /// indices. Outcomes are visited in index order so seeded results are
+    /// reproducible.
+    pub(crate) fn readout(&self, counts: HashMap<usize, usize>, n_qubits: usize, seed: Option<u64>) -> PyResult<HashMap<usize, usize>> {
+        let mut errors: Vec<(usize, f32, f32)> = self.readout_errors.iter()
+            .filter(|(_, &(p10, p01))| p10 > 0.0 || p01 > 0.0)
+            .map(|(&q, &(p10, p01))| (q, p10, p01))
+            .collect();
+        if errors.is_empty() {
+            return Ok(counts);
+        }
+        errors.sort_by_key(|e| e.0);
+        if let Some(&(q, _, _)) = errors.iter().find(|e| e.0 >= n_qubits) {
+            return Err(crate::errors::QubitIndexError::new_err(
+                format!("Noise model references qubit {} but the circuit has {}", q, n_qubits)));
+        }
+
+        let mut rng = rng::seeded(seed);
+        let mut outcomes: Vec<(usize, usize)> = counts.into_iter().collect();
+        outcomes.sort_unstable();
+        let mut noisy = HashMap::new();
+        for (idx, count) in outcomes {
+            for _ in 0..count {
+                let mut read = idx;
+                for &(q, p10, p01) in &errors {
+                    let flip = if (idx >> q) & 1 == 0 { p10 } else { p01 };
+                    if rng.gen::<f32>() < flip {
+                        read ^= 1 << q;
+                    }
+                }
+                *noisy.entry(read).or_insert(0) += 1;
+            }
+        }
+        Ok(noisy)
+    }
+
+    /// (qubit, channel) pairs to apply after `gate`, in application order
+    pub(crate) fn channels_after(&self, gate: &Gate, n_qubits: usize) -> PyResult<Vec<(usize, NoiseChannel)>> {
+        // Snapshots take no time, so not even idle errors follow them
+        if gate.is_snapshot() {
+            return Ok(Vec::new());
+        }
+        let mut out = self.gate_channels(gate.gate_type.name(), &gate.qubits());
+        let mut idle: Vec<_> = self.qubit_errors.iter().collect();
+        idle.sort_by_key(|(q, _)| **q);
+        for (&q, channels) in idle {
+            if q >= n_qubits {
+                return Err(crate::errors::QubitIndexError::new_err(
+                    format!("Noise model references qubit {} but the circuit has {}", q, n_qubits)));
+            }
+            out.extend(channels.iter().map(|c| (q, c.clone())));
+        }
+        if let Some(&q) = self.thermal_qubits.keys().find(|&&q| q >= n_qubits) {
+            return Err(crate::errors::QubitIndexError::new_err(
+                format!("Noise model references qubit {} but the circuit has {}", q, n_qubits)));
+        }
+        Ok(out)
+    }
+
+    /// (a, b, channel) pair errors to apply after `gate`, after its
+    /// single-qubit channels, in pair order
+    pub(crate) fn pair_channels_after(&self, gate: &Gate, n_qubits: usize) -> PyResult<Vec<(usize, usize, PairChannel)>> {
+        let qubits = gate.qubits();
+        let mut pairs: Vec<_> = self.pair_errors.iter()
+            .filter(|((a, b), _)| qubits.contains(a) || qubits.contains(b))
+            .collect();
+        pairs.sort_by_key(|(pair, _)| **pair);
+        let mut out = Vec::new();
+        for (&(a, b), channels) in pairs {
+            if b >= n_qubits {
+                return Err(crate::errors::QubitIndexError::new_err(
+                    format!("Noise model references qubit {} but the circuit has {}", b, n_qubits)));
+            }
+            out.extend(channels.iter().map(|c| (a, b, c.clone())));
+        }
+        Ok(out)
+    }
+
+    /// Apply all channels the model attaches to `gate`
+    pub(crate) fn apply_after(&self, gate: &Gate, dm: &mut DensityMatrix) -> PyResult<()> {
+        for (q, channel) in self.channels_after(gate, dm.qubits)? {
+            channel.apply(dm, q)?;
+        }
+        for (a, b, channel) in self.pair_channels_after(gate, dm.qubits)? {
+            channel.apply(dm, a, b);
+        }
+        Ok(())
+    }
+}
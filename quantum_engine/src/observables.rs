@@ -0,0 +1,201 @@
+//! Pauli-string observables exposed to Python.
+
+use pyo3::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::hamiltonian::Hamiltonian;
+use crate::pauli::PauliMasks;
+
+/// A weighted Pauli string such as 0.5 * "XZIY"; character i acts on qubit i
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct PauliString {
+    label: String,
+    coeff: f32,
+    masks: PauliMasks,
+}
+
+#[pymethods]
+impl PauliString {
+    #[new]
+    #[pyo3(signature = (label, coeff=1.0))]
+    pub fn new(label: &str, coeff: f32) -> PyResult<Self> {
+        let label = label.to_ascii_uppercase();
+        let masks = parse_label(&label)?;
+        Ok(PauliString { label, coeff, masks })
+    }
+
+    #[getter]
+    pub fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    #[getter]
+    pub fn coeff(&self) -> f32 {
+        self.coeff
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PauliString('{}', {})", self.label, self.coeff)
+    }
+}
+
+/// A Hermitian observable given as a weighted sum of Pauli strings
+#[pyclass]
+#[derive(Clone, Debug, Default)]
+pub struct Observable {
+    terms: Vec<PauliString>,
+}
+
+#[pymethods]
+impl Observable {
+    #[new]
+    #[pyo3(signature = (terms=None))]
+    pub fn new(terms: Option<Vec<PauliString>>) -> Self {
+        Observable { terms: terms.unwrap_or_default() }
+    }
+
+    /// Append the term coeff * label
+    #[pyo3(signature = (label, coeff=1.0))]
+    pub fn add_term(&mut self, label: &str, coeff: f32) -> PyResult<()> {
+        self.terms.push(PauliString::new(label, coeff)?);
+        Ok(())
+    }
+
+    pub fn terms(&self) -> Vec<PauliString> {
+        self.terms.clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.terms.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let terms: Vec<String> = self.terms.iter()
+            .map(|t| format!("{} * {}", t.coeff, t.label))
+            .collect();
+        format!("Observable({})", terms.join(" + "))
+    }
+}
+
+/// Bitmasks of a Pauli label such as "XZIY"; character i acts on qubit i.
+/// Characters past usize::BITS are validated but get no mask bit, since only
+/// the label-based backends accept strings that long
+pub fn parse_label(label: &str) -> PyResult<PauliMasks> {
+    let mut x = 0;
+    let mut z = 0;
+    for (q, c) in label.chars().enumerate() {
+        let bit = 1usize.checked_shl(q as u32).unwrap_or(0);
+        match c.to_ascii_uppercase() {
+            'I' => {}
+            'X' => x |= bit,
+            'Y' => { x |= bit; z |= bit; }
+            'Z' => z |= bit,
+            _ => return Err(pyo3::exceptions::PyValueError::new_err(
+                format!("Invalid Pauli character '{}' in '{}'", c, label))),
+        }
+    }
+    Ok(PauliMasks::new(x, z))
+}
+
+/// A single (coefficient, Pauli masks) term of an observable
+#[derive(Clone, Copy, Debug)]
+pub struct PauliTerm {
+    pub coeff: f32,
+    pub masks: PauliMasks,
+}
+
+/// Flatten a Python PauliString, Observable or Hamiltonian into Pauli terms acting on `n_qubits`
+pub fn extract_terms(obj: &PyAny, n_qubits: usize) -> PyResult<Vec<PauliTerm>> {
+    Ok(extract_strings(obj, n_qubits)?.iter()
+        .map(|p| PauliTerm { coeff: p.coeff, masks: p.masks })
+        .collect())
+}
+
+/// (coefficient, label) pairs of a PauliString, Observable or Hamiltonian, for backends that
+/// are not limited to the width of a bitmask
+pub fn extract_labels(obj: &PyAny, n_qubits: usize) -> PyResult<Vec<(f32, String)>> {
+    Ok(extract_strings(obj, n_qubits)?.into_iter().map(|p| (p.coeff, p.label)).collect())
+}
+
+fn extract_strings(obj: &PyAny, n_qubits: usize) -> PyResult<Vec<PauliString>> {
+    let strings: Vec<PauliString> = if let Ok(p) = obj.extract::<PauliString>() {
+        vec![p]
+    } else if let Ok(o) = obj.extract::<PyRef<Observable>>() {
+        o.terms.clone()
+    } else if let Ok(h) = obj.extract::<PyRef<Hamiltonian>>() {
+        h.real_terms()?.into_iter()
+            .map(|(coeff, label)| PauliString::new(&label, coeff))
+            .collect::<PyResult<_>>()?
+    } else {
+        return Err(pyo3::exceptions::PyTypeError::new_err("Expected a PauliString, Observable or Hamiltonian"));
+    };
+
+    if let Some(p) = strings.iter().find(|p| p.label.len() > n_qubits) {
+        return Err(crate::errors::DimensionMismatchError::new_err(
+            format!("Pauli string '{}' acts on more than {} qubits", p.label, n_qubits)));
+    }
+    Ok(strings)
+}
+
+fn check_shots(shots: usize) -> PyResult<()> {
+    if shots < 2 {
+        return Err(pyo3::exceptions::PyValueError::new_err("A standard error needs at least 2 shots"));
+    }
+    Ok(())
+}
+
+/// (mean, standard error) of `shots` simulated measurements of each term in
+/// its own eigenbasis; a term with expectation e reads +1 with probability
+/// (1 + e) / 2, and identity terms are exact
+pub fn estimate(terms: &[PauliTerm], shots: usize, seed: Option<u64>, expectation: impl Fn(PauliMasks) -> f32) -> PyResult<(f32, f32)> {
+    check_shots(shots)?;
+    let mut rng = crate::rng::seeded(seed);
+    let (mut mean, mut variance) = (0.0f64, 0.0f64);
+    for t in terms {
+        let coeff = t.coeff as f64;
+        if t.masks == PauliMasks::new(0, 0) {
+            mean += coeff;
+            continue;
+        }
+        let p_plus = ((1.0 + expectation(t.masks) as f64) / 2.0).clamp(0.0, 1.0);
+        let plus = (0..shots).filter(|_| rng.gen::<f64>() < p_plus).count();
+        let m = (2.0 * plus as f64 - shots as f64) / shots as f64;
+        // Unbiased sample variance of the +-1 outcomes
+        let s2 = (1.0 - m * m) * shots as f64 / (shots - 1) as f64;
+        mean += coeff * m;
+        variance += coeff * coeff * s2 / shots as f64;
+    }
+    Ok((mean as f32, variance.sqrt() as f32))
+}
+
+/// (mean, standard error) of a diagonal (I/Z) PauliString, Observable or
+/// Hamiltonian estimated from computational-basis counts, such as those of
+/// `run()`. Every term is read from the same shots, so the error includes
+/// their covariance
+#[pyfunction]
+#[pyo3(signature = (counts, observable, bit_order="little"))]
+pub fn expectation_from_counts(counts: HashMap<String, usize>, observable: &PyAny, bit_order: &str) -> PyResult<(f32, f32)> {
+    let n = counts.keys().next().map_or(0, |k| k.len());
+    let strings = extract_strings(observable, n)?;
+    if let Some(p) = strings.iter().find(|p| p.masks.x != 0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Counts only estimate I/Z terms, got '{}'; measure X and Y factors in a rotated basis", p.label)));
+    }
+    let terms: Vec<PauliTerm> = strings.iter().map(|p| PauliTerm { coeff: p.coeff, masks: p.masks }).collect();
+    let shots: usize = counts.values().sum();
+    check_shots(shots)?;
+    let (mut sum, mut sum_sq) = (0.0f64, 0.0f64);
+    for (bits, &count) in &counts {
+        let idx = crate::parse_ordered(bits, n, bit_order)?;
+        let value: f64 = terms.iter()
+            .map(|t| if (idx & t.masks.z).count_ones() % 2 == 0 { t.coeff as f64 } else { -t.coeff as f64 })
+            .sum();
+        sum += value * count as f64;
+        sum_sq += value * value * count as f64;
+    }
+    let mean = sum / shots as f64;
+    let variance = ((sum_sq - shots as f64 * mean * mean) / (shots - 1) as f64).max(0.0);
+    Ok((mean as f32, (variance / shots as f64).sqrt() as f32))
+}
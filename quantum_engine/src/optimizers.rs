@@ -0,0 +1,289 @@
+//! Classical optimizers driving the variational loops.
+//!
+//! Each optimizer owns its state (moments, iteration counter) and turns the
+//! current parameters into the next ones, calling back for energies or
+//! gradients as it needs them.
+//!
+//! The same optimizers are exposed to Python as `SPSA`, `GradientDescent` and
+//! `Adam`, subclasses of `Optimizer`, so a loop written in Python can keep its
+//! own circuit and objective while the update rule, its moments and the
+//! convergence test stay in Rust: `step(parameters, objective, gradient)`
+//! advances one iteration with Python callables for f(theta) and grad f(theta),
+//! and `minimize` runs the whole loop. An instance can also be handed to `VQE`
+//! in place of an optimizer name.
+
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::rng;
+
+/// Objective callbacks handed to `Optimizer::step`
+pub trait Objective {
+    fn energy(&self, theta: &[f32]) -> PyResult<f32>;
+    fn gradient(&self, theta: &[f32]) -> PyResult<Vec<f32>>;
+}
+
+#[derive(Clone, Debug)]
+pub enum Optimizer {
+    GradientDescent { learning_rate: f32 },
+    Adam { learning_rate: f32, beta1: f32, beta2: f32, epsilon: f32, m: Vec<f32>, v: Vec<f32>, t: i32 },
+    /// Simultaneous perturbation stochastic approximation with the standard gain decay
+    Spsa { a: f32, c: f32, stability: f32, k: i32 },
+}
+
+impl Optimizer {
+    pub fn from_name(name: &str, learning_rate: f32, max_iterations: usize) -> PyResult<Self> {
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "gd" | "gradient_descent" => Optimizer::GradientDescent { learning_rate },
+            "adam" => Optimizer::Adam {
+                learning_rate, beta1: 0.9, beta2: 0.999, epsilon: 1e-8, m: Vec::new(), v: Vec::new(), t: 0,
+            },
+            "spsa" => Optimizer::Spsa { a: learning_rate, c: 0.1, stability: 0.1 * max_iterations as f32, k: 0 },
+            _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown optimizer '{}' (expected spsa, gradient_descent or adam)", name))),
+        })
+    }
+
+    /// Forget the moments and iteration count, keeping the hyperparameters
+    pub fn reset(&mut self) {
+        match self {
+            Optimizer::GradientDescent { .. } => {}
+            Optimizer::Adam { m, v, t, .. } => {
+                m.clear();
+                v.clear();
+                *t = 0;
+            }
+            Optimizer::Spsa { k, .. } => *k = 0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Optimizer::GradientDescent { .. } => "gradient_descent",
+            Optimizer::Adam { .. } => "adam",
+            Optimizer::Spsa { .. } => "spsa",
+        }
+    }
+
+    /// Update `theta` in place by one iteration
+    pub fn step(&mut self, theta: &mut [f32], objective: &dyn Objective, rng: &mut StdRng) -> PyResult<()> {
+        match self {
+            Optimizer::GradientDescent { learning_rate } => {
+                let grad = objective.gradient(theta)?;
+                theta.iter_mut().zip(grad).for_each(|(x, g)| *x -= *learning_rate * g);
+            }
+            Optimizer::Adam { learning_rate, beta1, beta2, epsilon, m, v, t } => {
+                let grad = objective.gradient(theta)?;
+                if m.len() != theta.len() {
+                    *m = vec![0.0; theta.len()];
+                    *v = vec![0.0; theta.len()];
+                }
+                *t += 1;
+                let bias1 = 1.0 - beta1.powi(*t);
+                let bias2 = 1.0 - beta2.powi(*t);
+                for i in 0..theta.len() {
+                    m[i] = *beta1 * m[i] + (1.0 - *beta1) * grad[i];
+                    v[i] = *beta2 * v[i] + (1.0 - *beta2) * grad[i] * grad[i];
+                    theta[i] -= *learning_rate * (m[i] / bias1) / ((v[i] / bias2).sqrt() + *epsilon);
+                }
+            }
+            Optimizer::Spsa { a, c, stability, k } => {
+                let a_k = *a / (*k as f32 + 1.0 + *stability).powf(0.602);
+                let c_k = *c / (*k as f32 + 1.0).powf(0.101);
+                *k += 1;
+
+                let delta: Vec<f32> = theta.iter().map(|_| if rng.gen::<bool>() { 1.0 } else { -1.0 }).collect();
+                let plus: Vec<f32> = theta.iter().zip(&delta).map(|(x, d)| x + c_k * d).collect();
+                let minus: Vec<f32> = theta.iter().zip(&delta).map(|(x, d)| x - c_k * d).collect();
+                let diff = objective.energy(&plus)? - objective.energy(&minus)?;
+                for (x, d) in theta.iter_mut().zip(&delta) {
+                    *x -= a_k * diff / (2.0 * c_k * d);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Python callables f(theta) -> float and grad f(theta) -> list of floats
+struct Callbacks<'py> {
+    objective: &'py PyAny,
+    gradient: Option<&'py PyAny>,
+    optimizer: &'static str,
+}
+
+impl Objective for Callbacks<'_> {
+    fn energy(&self, theta: &[f32]) -> PyResult<f32> {
+        self.objective.call1((theta.to_vec(),))?.extract()
+    }
+
+    fn gradient(&self, theta: &[f32]) -> PyResult<Vec<f32>> {
+        let Some(gradient) = self.gradient else {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "{} needs a gradient callable", self.optimizer)));
+        };
+        let grad: Vec<f32> = gradient.call1((theta.to_vec(),))?.extract()?;
+        if grad.len() != theta.len() {
+            return Err(crate::errors::DimensionMismatchError::new_err(format!(
+                "Gradient has {} entries for {} parameters", grad.len(), theta.len())));
+        }
+        Ok(grad)
+    }
+}
+
+/// Base class of the Python-facing optimizers: one update rule plus the
+/// convergence test |f(theta_k+1) - f(theta_k)| < tolerance
+#[pyclass(subclass, name = "Optimizer", module = "quantum_engine")]
+#[derive(Clone, Debug)]
+pub struct OptimizerBase {
+    optimizer: Optimizer,
+    tolerance: f32,
+    /// Draws SPSA's perturbation directions
+    rng: StdRng,
+    /// Objective value after the latest step (or at the starting point)
+    value: Option<f32>,
+    converged: bool,
+    iterations: usize,
+}
+
+impl OptimizerBase {
+    fn new(optimizer: Optimizer, tolerance: f32, seed: Option<u64>) -> Self {
+        OptimizerBase { optimizer, tolerance, rng: rng::seeded(seed), value: None, converged: false, iterations: 0 }
+    }
+
+    /// The update rule with its state cleared, for a driver that starts its own run
+    pub fn fresh(&self) -> Optimizer {
+        let mut optimizer = self.optimizer.clone();
+        optimizer.reset();
+        optimizer
+    }
+}
+
+#[pymethods]
+impl OptimizerBase {
+    /// One iteration from `parameters`, returning the new parameters.
+    /// `objective(theta)` gives f, and gradient-based optimizers also need
+    /// `gradient(theta)`; f is evaluated at the new point for the convergence test.
+    #[pyo3(signature = (parameters, objective, gradient=None))]
+    pub fn step(&mut self, parameters: Vec<f32>, objective: &PyAny, gradient: Option<&PyAny>) -> PyResult<Vec<f32>> {
+        let callbacks = Callbacks { objective, gradient, optimizer: self.optimizer.name() };
+        let previous = match self.value {
+            Some(value) => value,
+            None => callbacks.energy(&parameters)?,
+        };
+        let mut theta = parameters;
+        self.optimizer.step(&mut theta, &callbacks, &mut self.rng)?;
+        let value = callbacks.energy(&theta)?;
+        self.value = Some(value);
+        self.converged = (value - previous).abs() < self.tolerance;
+        self.iterations += 1;
+        Ok(theta)
+    }
+
+    /// Whether the latest step changed the objective by less than the tolerance
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// Step from `parameters` until converged or `max_iterations` steps have
+    /// run. Returns the final parameters and the objective history (the
+    /// starting value followed by the value after each step). Starts afresh.
+    #[pyo3(signature = (objective, parameters, gradient=None, max_iterations=100))]
+    pub fn minimize(&mut self, objective: &PyAny, parameters: Vec<f32>, gradient: Option<&PyAny>, max_iterations: usize)
+        -> PyResult<(Vec<f32>, Vec<f32>)> {
+        self.reset();
+        let mut history = vec![objective.call1((parameters.clone(),))?.extract()?];
+        self.value = history.first().copied();
+        let mut theta = parameters;
+        for _ in 0..max_iterations {
+            theta = self.step(theta, objective, gradient)?;
+            history.push(self.value.unwrap_or_default());
+            if self.converged {
+                break;
+            }
+        }
+        Ok((theta, history))
+    }
+
+    /// Clear the moments, counters and convergence state
+    pub fn reset(&mut self) {
+        self.optimizer.reset();
+        self.value = None;
+        self.converged = false;
+        self.iterations = 0;
+    }
+
+    #[getter]
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Objective value after the latest step, None before the first
+    #[getter]
+    pub fn value(&self) -> Option<f32> {
+        self.value
+    }
+
+    #[getter]
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    fn __repr__(&self) -> String {
+        let rule = match &self.optimizer {
+            Optimizer::GradientDescent { learning_rate } => format!("GradientDescent(learning_rate={}", learning_rate),
+            Optimizer::Adam { learning_rate, beta1, beta2, epsilon, .. } => format!(
+                "Adam(learning_rate={}, beta1={}, beta2={}, epsilon={}", learning_rate, beta1, beta2, epsilon),
+            Optimizer::Spsa { a, c, stability, .. } => format!("SPSA(a={}, c={}, stability={}", a, c, stability),
+        };
+        format!("{}, tolerance={}, iterations={})", rule, self.tolerance, self.iterations)
+    }
+}
+
+/// theta -= learning_rate * grad f(theta)
+#[pyclass(extends = OptimizerBase, module = "quantum_engine")]
+pub struct GradientDescent {}
+
+#[pymethods]
+impl GradientDescent {
+    #[new]
+    #[pyo3(signature = (learning_rate=0.1, tolerance=1e-6))]
+    pub fn new(learning_rate: f32, tolerance: f32) -> (Self, OptimizerBase) {
+        (GradientDescent {}, OptimizerBase::new(Optimizer::GradientDescent { learning_rate }, tolerance, None))
+    }
+}
+
+/// Adam (Kingma and Ba, 2015) with bias-corrected first and second moments
+#[pyclass(extends = OptimizerBase, module = "quantum_engine")]
+pub struct Adam {}
+
+#[pymethods]
+impl Adam {
+    #[new]
+    #[pyo3(signature = (learning_rate=0.1, beta1=0.9, beta2=0.999, epsilon=1e-8, tolerance=1e-6))]
+    pub fn new(learning_rate: f32, beta1: f32, beta2: f32, epsilon: f32, tolerance: f32) -> PyResult<(Self, OptimizerBase)> {
+        if !(0.0..1.0).contains(&beta1) || !(0.0..1.0).contains(&beta2) {
+            return Err(pyo3::exceptions::PyValueError::new_err("beta1 and beta2 must lie in [0, 1)"));
+        }
+        let optimizer = Optimizer::Adam { learning_rate, beta1, beta2, epsilon, m: Vec::new(), v: Vec::new(), t: 0 };
+        Ok((Adam {}, OptimizerBase::new(optimizer, tolerance, None)))
+    }
+}
+
+/// SPSA (Spall, 1992): two objective evaluations per step along a random +-1
+/// direction, with gains a / (k + 1 + stability)^0.602 and c / (k + 1)^0.101
+#[pyclass(extends = OptimizerBase, module = "quantum_engine")]
+pub struct SPSA {}
+
+#[pymethods]
+impl SPSA {
+    #[new]
+    #[pyo3(signature = (a=0.1, c=0.1, stability=10.0, tolerance=1e-6, seed=None))]
+    pub fn new(a: f32, c: f32, stability: f32, tolerance: f32, seed: Option<u64>) -> PyResult<(Self, OptimizerBase)> {
+        if c <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("The perturbation size c must be positive"));
+        }
+        Ok((SPSA {}, OptimizerBase::new(Optimizer::Spsa { a, c, stability, k: 0 }, tolerance, seed)))
+    }
+}
@@ -0,0 +1,65 @@
+//! Symbolic rotation angles for variational circuits.
+//!
+//! Parameterized gates are stored in the gate list like any other rotation
+//! (with a placeholder angle) plus a `ParamRef` naming the symbol to read the
+//! angle from, so binding only rewrites angles and never rebuilds the circuit.
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::Gate;
+
+/// Gate `gate` of the circuit takes its angle from parameter `name`, as `scale * value`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParamRef {
+    pub gate: usize,
+    pub name: String,
+    pub scale: f32,
+}
+
+/// Distinct parameter names in order of first use
+pub fn names(refs: &[ParamRef]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for r in refs {
+        if !out.contains(&r.name) {
+            out.push(r.name.clone());
+        }
+    }
+    out
+}
+
+/// Substitute the angles named in `values`, returning the updated gates and the
+/// references that are still unbound
+pub fn bind(gates: &[Gate], refs: &[ParamRef], values: &HashMap<String, f32>) -> PyResult<(Vec<Gate>, Vec<ParamRef>)> {
+    if let Some(unknown) = values.keys().find(|k| !refs.iter().any(|r| &r.name == *k)) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!("Unknown parameter '{}'", unknown)));
+    }
+
+    let mut gates = gates.to_vec();
+    let mut unbound = Vec::new();
+    for r in refs {
+        match values.get(&r.name) {
+            Some(&value) => {
+                if let Some(angle) = gates[r.gate].gate_type.angle_mut() {
+                    *angle = r.scale * value;
+                }
+            }
+            None => unbound.push(r.clone()),
+        }
+    }
+    Ok((gates, unbound))
+}
+
+/// Copy of `gates` with every reference bound positionally: `values[i]` is the
+/// value of `names[i]`
+pub fn assign(gates: &[Gate], refs: &[ParamRef], names: &[String], values: &[f32]) -> Vec<Gate> {
+    let mut gates = gates.to_vec();
+    for r in refs {
+        let slot = names.iter().position(|n| n == &r.name).unwrap_or(0);
+        if let Some(angle) = gates[r.gate].gate_type.angle_mut() {
+            *angle = r.scale * values[slot];
+        }
+    }
+    gates
+}
@@ -0,0 +1,245 @@
+//! State-vector hooks for a PennyLane device plugin.
+//!
+//! `EchoDevice` holds one state that operations are applied to as they
+//! arrive, which is the shape of PennyLane's QubitDevice interface: a thin
+//! Python subclass forwards `apply`, `expval`, `sample` and `reset`, and
+//! PennyLane itself takes care of decompositions, shot-based estimators and
+//! gradients. Operations arrive as (name, wires, params) with PennyLane
+//! names; engine gate names are accepted too. Device wire w is engine qubit
+//! w. Multi-wire matrices and amplitude vectors follow PennyLane's order, in
+//! which the first wire is the most significant bit, and are converted on
+//! the way in and out.
+
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::bitorder::BitOrder;
+use crate::{errors, extract_complex, introspect, observables, pauli, rng, QuantumCircuit, QuantumState};
+
+/// PennyLane operation names `apply` understands
+const OPERATIONS: [&str; 31] = [
+    "Identity", "PauliX", "PauliY", "PauliZ", "Hadamard", "S", "T", "Adjoint(S)", "Adjoint(T)", "SX",
+    "RX", "RY", "RZ", "PhaseShift", "Rot", "U3", "CNOT", "CZ", "CY", "SWAP", "ISWAP", "CRX", "CRY", "CRZ",
+    "ControlledPhaseShift", "Toffoli", "MultiControlledX", "IsingXX", "IsingYY", "IsingZZ", "QubitUnitary",
+];
+
+/// Operations that replace the whole state rather than act on it
+const STATE_PREPARATIONS: [&str; 3] = ["BasisState", "StatePrep", "QubitStateVector"];
+
+/// A state vector driven operation by operation, for a PennyLane device shim
+#[pyclass(module = "quantum_engine")]
+pub struct EchoDevice {
+    #[pyo3(get)]
+    wires: usize,
+    /// Default shot count for `sample`; None means analytic
+    #[pyo3(get, set)]
+    shots: Option<usize>,
+    state: QuantumState,
+    rng: StdRng,
+}
+
+#[pymethods]
+impl EchoDevice {
+    #[new]
+    #[pyo3(signature = (wires, shots=None, seed=None))]
+    pub fn new(wires: usize, shots: Option<usize>, seed: Option<u64>) -> PyResult<Self> {
+        Ok(EchoDevice { wires, shots, state: QuantumState::new(wires)?, rng: rng::seeded(seed) })
+    }
+
+    /// Every operation and state preparation name `apply` accepts
+    #[staticmethod]
+    pub fn operations() -> Vec<&'static str> {
+        OPERATIONS.iter().chain(STATE_PREPARATIONS.iter()).copied().collect()
+    }
+
+    /// Apply a list of (name, wires, params) operations in order
+    pub fn apply(&mut self, py: Python, operations: Vec<(String, Vec<usize>, Vec<&PyAny>)>) -> PyResult<()> {
+        let mut circuit = QuantumCircuit::new(self.wires, 0);
+        for (name, wires, params) in operations {
+            if STATE_PREPARATIONS.contains(&name.as_str()) {
+                self.run(py, &mut circuit)?;
+                self.state = self.prepare(&name, &wires, &params)?;
+            } else {
+                push(&mut circuit, &name, &wires, &params)?;
+            }
+        }
+        self.run(py, &mut circuit)
+    }
+
+    /// Analytic expectation value of a PauliString, an Observable, or a tensor
+    /// product given as (["PauliZ", "PauliX", ...], wires)
+    pub fn expval(&self, py: Python, observable: &PyAny) -> PyResult<f32> {
+        let terms = match observable.extract::<(Vec<String>, Vec<usize>)>() {
+            Ok((names, wires)) => vec![observables::PauliTerm { coeff: 1.0, masks: tensor_masks(&names, &wires, self.wires)? }],
+            Err(_) => observables::extract_terms(observable, self.wires)?,
+        };
+        Ok(py.allow_threads(|| terms.iter().map(|t| t.coeff * pauli::expectation(&self.state.state, t.masks)).sum()))
+    }
+
+    /// `shots` computational-basis samples (the device's shot count when
+    /// omitted), one row of wire values per shot
+    #[pyo3(signature = (shots=None))]
+    pub fn sample(&mut self, py: Python, shots: Option<usize>) -> PyResult<Vec<Vec<u8>>> {
+        let shots = shots.or(self.shots).ok_or_else(|| pyo3::exceptions::PyValueError::new_err(
+            "sample needs a shot count (pass shots or set the device's shots)"))?;
+        let seed = self.rng.gen();
+        let state = &self.state;
+        Ok(py.allow_threads(|| {
+            let probs: Vec<f32> = state.state.iter().map(|c| c.norm_sqr()).collect();
+            let mut rng = rng::seeded(Some(seed));
+            let mut cdf = Vec::with_capacity(probs.len());
+            let mut acc = 0.0f64;
+            for &p in &probs {
+                acc += p as f64;
+                cdf.push(acc);
+            }
+            (0..shots)
+                .map(|_| {
+                    let r = rng.gen::<f64>() * acc;
+                    let idx = cdf.partition_point(|&c| c <= r).min(probs.len() - 1);
+                    (0..state.qubits).map(|q| ((idx >> q) & 1) as u8).collect()
+                })
+                .collect()
+        }))
+    }
+
+    /// Probabilities of the basis states of `wires` (every wire when omitted),
+    /// indexed with wires[0] as the most significant bit
+    #[pyo3(signature = (wires=None))]
+    pub fn probability(&self, py: Python, wires: Option<Vec<usize>>) -> PyResult<Vec<f32>> {
+        let wires = wires.unwrap_or_else(|| (0..self.wires).collect());
+        let probs = self.state.marginal_probabilities(py, wires.clone())?;
+        Ok(BitOrder::Big.permute(probs, wires.len()))
+    }
+
+    /// Amplitudes as (re, im) pairs, indexed with wire 0 as the most significant bit
+    pub fn state(&self) -> Vec<(f32, f32)> {
+        self.state.reverse_bits().get_state_vector()
+    }
+
+    /// Return to |0...0>
+    pub fn reset(&mut self) -> PyResult<()> {
+        self.state = QuantumState::new(self.wires)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        let shots = self.shots.map_or("None".to_string(), |s| s.to_string());
+        format!("EchoDevice(wires={}, shots={})", self.wires, shots)
+    }
+}
+
+impl EchoDevice {
+    /// Apply and clear the operations collected in `circuit`
+    fn run(&mut self, py: Python, circuit: &mut QuantumCircuit) -> PyResult<()> {
+        let state = &mut self.state;
+        let gates = std::mem::take(&mut circuit.gates);
+        py.allow_threads(|| gates.iter().try_for_each(|gate| state.apply(gate)))
+    }
+
+    /// State set by a BasisState or StatePrep operation on every wire
+    fn prepare(&self, name: &str, wires: &[usize], params: &[&PyAny]) -> PyResult<QuantumState> {
+        if wires != (0..self.wires).collect::<Vec<_>>() || params.len() != 1 {
+            return Err(errors::InvalidGateError::new_err(format!(
+                "{} must act on every device wire in order and take one parameter", name)));
+        }
+        if name == "BasisState" {
+            let bits: Vec<u8> = params[0].extract()?;
+            if bits.len() != self.wires || bits.iter().any(|&b| b > 1) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "BasisState needs {} values, each 0 or 1", self.wires)));
+            }
+            let mut state = QuantumState::new(self.wires)?;
+            let idx = bits.iter().enumerate().fold(0, |acc, (q, &b)| acc | ((b as usize) << q));
+            state.state.swap(0, idx);
+            return Ok(state);
+        }
+        let amplitudes: Vec<(f32, f32)> = params[0].iter()?.map(|a| extract_complex(a?)).collect::<PyResult<_>>()?;
+        if amplitudes.len() != 1 << self.wires {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "{} needs {} amplitudes, got {}", name, 1usize << self.wires, amplitudes.len())));
+        }
+        Ok(QuantumState::from_amplitudes(amplitudes)?.reverse_bits())
+    }
+}
+
+/// Pauli masks of a tensor product of named single-wire observables
+fn tensor_masks(names: &[String], wires: &[usize], n: usize) -> PyResult<pauli::PauliMasks> {
+    if names.len() != wires.len() {
+        return Err(errors::DimensionMismatchError::new_err("Observable needs one wire per factor"));
+    }
+    let (mut x, mut z) = (0, 0);
+    for (name, &w) in names.iter().zip(wires) {
+        let bit = crate::qubit_mask(&[w], n)?;
+        if (x | z) & bit != 0 {
+            return Err(errors::InvalidGateError::new_err(format!("Observable acts twice on wire {}", w)));
+        }
+        match name.as_str() {
+            "Identity" => {}
+            "PauliX" => x |= bit,
+            "PauliY" => {
+                x |= bit;
+                z |= bit;
+            }
+            "PauliZ" => z |= bit,
+            other => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unsupported observable '{}' (expected PauliX, PauliY, PauliZ or Identity)", other))),
+        }
+    }
+    Ok(pauli::PauliMasks::new(x, z))
+}
+
+/// Append the engine gates of one PennyLane operation to `circuit`
+fn push(circuit: &mut QuantumCircuit, name: &str, wires: &[usize], params: &[&PyAny]) -> PyResult<()> {
+    if name == "QubitUnitary" {
+        let [matrix] = params else {
+            return Err(errors::InvalidGateError::new_err("QubitUnitary takes one matrix parameter"));
+        };
+        let rows = matrix.iter()?
+            .map(|row| row?.iter()?.map(|entry| extract_complex(entry?)).collect::<PyResult<Vec<_>>>())
+            .collect::<PyResult<Vec<_>>>()?;
+        // PennyLane's first wire is the most significant bit of the matrix index
+        return circuit.unitary(rows, wires.iter().rev().copied().collect());
+    }
+    let angles: Vec<f32> = params.iter().map(|p| p.extract()).collect::<PyResult<_>>()?;
+    let engine_name = match name {
+        "Identity" => return Ok(()),
+        "PauliX" => "x",
+        "PauliY" => "y",
+        "PauliZ" => "z",
+        "Hadamard" => "h",
+        "Adjoint(S)" => "sdg",
+        "Adjoint(T)" => "tdg",
+        "SX" => {
+            // sqrt(X) including its global phase e^(i pi/4)
+            let (a, b) = ((0.5, 0.5), (0.5, -0.5));
+            return circuit.unitary(vec![vec![a, b], vec![b, a]], wires.to_vec());
+        }
+        "PhaseShift" => "p",
+        "Rot" => {
+            // Rot(phi, theta, omega) = RZ(omega) RY(theta) RZ(phi)
+            if angles.len() != 3 || wires.len() != 1 {
+                return Err(errors::InvalidGateError::new_err("Rot takes 1 wire and 3 parameters"));
+            }
+            circuit.rz(wires[0], angles[0])?;
+            circuit.ry(wires[0], angles[1])?;
+            return circuit.rz(wires[0], angles[2]);
+        }
+        "U3" => "u",
+        "CNOT" => "cnot",
+        "CZ" => "controlled_z",
+        "CY" => "controlled_y",
+        "ControlledPhaseShift" => "cphase",
+        "Toffoli" | "MultiControlledX" => "mcx",
+        "IsingXX" => "rxx",
+        "IsingYY" => "ryy",
+        "IsingZZ" => "rzz",
+        // S, T, RX, ..., SWAP, CRZ are the engine names in upper case
+        other if OPERATIONS.contains(&other) => other,
+        other => return Err(errors::InvalidGateError::new_err(format!(
+            "Unsupported operation '{}' (EchoDevice.operations() lists the supported ones)", other))),
+    };
+    let gate = introspect::from_description(&(engine_name.to_ascii_lowercase(), wires.to_vec(), angles), circuit.qubits)?;
+    circuit.push(gate)
+}
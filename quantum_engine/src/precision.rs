@@ -0,0 +1,307 @@
+//! Double-precision (Complex64) state vector and density matrix.
+//!
+//! `QuantumCircuit.execute(precision="f64")` and `execute_density(..., precision="f64")`
+//! return these classes. They share the gate kernels with the f32 classes, so
+//! behaviour matches; f32 stays the default because it halves memory traffic.
+//! Gate angles are stored as f32 in the circuit, so only accumulated rounding
+//! error (not parameter resolution) improves.
+
+use num_complex::{Complex32, Complex64};
+use numpy::{PyArray1, PyArray2, ToPyArray};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::bitorder::BitOrder;
+use crate::noise::NoiseModel;
+use crate::{checkpoint, dynamic, format_bitstring, kernels, observables, pauli, reduction, resources, sampling, DensityMatrix, Gate, GateType, QuantumState};
+
+/// Simulation precision selected by the `precision` argument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision {
+    F32,
+    F64,
+}
+
+impl Precision {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "f32" | "single" => Ok(Precision::F32),
+            "f64" | "double" => Ok(Precision::F64),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(
+                format!("Unknown precision '{}' (expected 'f32' or 'f64')", name))),
+        }
+    }
+}
+
+/// Pure state vector in double precision
+#[pyclass]
+#[derive(Clone)]
+pub struct QuantumStateF64 {
+    qubits: usize,
+    state: Vec<Complex64>,
+}
+
+#[pymethods]
+impl QuantumStateF64 {
+    #[new]
+    pub fn new(qubits: usize) -> PyResult<Self> {
+        resources::check_state::<Complex64>(qubits)?;
+        let mut state = vec![Complex64::new(0.0, 0.0); 1 << qubits];
+        state[0] = Complex64::new(1.0, 0.0);
+        Ok(QuantumStateF64 { qubits, state })
+    }
+
+    /// Write the amplitudes to a versioned checkpoint file at `path`
+    pub fn save(&self, py: Python, path: &str) -> PyResult<()> {
+        py.allow_threads(|| checkpoint::save(path, checkpoint::Kind::StateVector, self.qubits, checkpoint::Buffer::F64(&self.state)))
+    }
+
+    /// Read a state saved by `save` (from either precision)
+    #[staticmethod]
+    pub fn load(py: Python, path: &str) -> PyResult<Self> {
+        let (qubits, state) = py.allow_threads(|| checkpoint::load(path, checkpoint::Kind::StateVector))?;
+        Ok(QuantumStateF64 { qubits, state })
+    }
+
+    /// Apply a named single-qubit gate (H, X, Y, Z, S, T, SDG, TDG, RX, RY, RZ)
+    #[pyo3(signature = (gate_name, target, param=None))]
+    pub fn apply_gate(&mut self, gate_name: &str, target: usize, param: Option<f32>) -> PyResult<()> {
+        let theta = param.unwrap_or(0.0);
+        let gate_type = match gate_name {
+            "H" => GateType::H,
+            "X" => GateType::X,
+            "Y" => GateType::Y,
+            "Z" => GateType::Z,
+            "S" => GateType::S,
+            "T" => GateType::T,
+            "SDG" => GateType::SDG,
+            "TDG" => GateType::TDG,
+            "RX" => GateType::RX(theta),
+            "RY" => GateType::RY(theta),
+            "RZ" => GateType::RZ(theta),
+            _ => return Err(crate::errors::InvalidGateError::new_err("Unknown gate")),
+        };
+        self.apply(&Gate { gate_type, target, control: None })
+    }
+
+    pub fn apply_cnot(&mut self, control: usize, target: usize) -> PyResult<()> {
+        self.apply(&Gate { gate_type: GateType::CNOT, target, control: Some(control) })
+    }
+
+    pub fn get_state_vector(&self) -> Vec<(f64, f64)> {
+        self.state.iter().map(|c| (c.re, c.im)).collect()
+    }
+
+    /// State vector as a complex128 numpy array
+    pub fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray1<Complex64>> {
+        py.import("numpy")?;
+        Ok(self.state.to_pyarray(py))
+    }
+
+    /// Expectation value <psi|O|psi> of a PauliString or Observable
+    pub fn expectation(&self, observable: &PyAny) -> PyResult<f64> {
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        Ok(terms.iter()
+            .map(|t| t.coeff as f64 * pauli::expectation(&self.state, t.masks))
+            .sum())
+    }
+
+    /// Sample `shots` computational-basis outcomes as a counts dictionary
+    #[pyo3(signature = (shots, seed=None))]
+    pub fn measure(&self, shots: usize, seed: Option<u64>) -> HashMap<String, usize> {
+        let probs: Vec<f32> = self.state.par_iter().map(|c| c.norm_sqr() as f32).collect();
+        sampling::sample_counts(&probs, shots, seed)
+            .into_iter()
+            .map(|(idx, count)| (format_bitstring(idx, self.qubits), count))
+            .collect()
+    }
+
+    /// <psi|psi>, which stays 1 up to rounding
+    pub fn norm(&self) -> f64 {
+        self.state.par_iter().map(|c| c.norm_sqr()).sum()
+    }
+
+    /// Round down to a single-precision QuantumState
+    pub fn to_f32(&self) -> QuantumState {
+        QuantumState {
+            qubits: self.qubits,
+            state: self.state.iter().map(|c| Complex32::new(c.re as f32, c.im as f32)).collect(),
+            phase: 0.0,
+            drift: 0.0,
+        }
+    }
+}
+
+impl QuantumStateF64 {
+    /// Exact widening of a single-precision state
+    pub(crate) fn widened(state: &QuantumState) -> Self {
+        QuantumStateF64 {
+            qubits: state.qubits,
+            state: state.state.iter().map(|c| Complex64::new(c.re as f64, c.im as f64)).collect(),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, gate: &Gate) -> PyResult<()> {
+        kernels::validate(gate, self.qubits)?;
+        kernels::apply(&mut self.state, gate, false);
+        Ok(())
+    }
+
+    /// Run gates that may include mid-circuit measurements, recording outcomes in `creg`
+    pub(crate) fn run_dynamic(&mut self, gates: &[Gate], creg: &mut dynamic::ClassicalRegister) -> PyResult<()> {
+        dynamic::run(&mut self.state, self.qubits, gates, creg)
+    }
+}
+
+/// Density matrix in double precision
+#[pyclass]
+#[derive(Clone)]
+pub struct DensityMatrixF64 {
+    qubits: usize,
+    matrix: Vec<Complex64>, // Flattened dim x dim matrix
+}
+
+#[pymethods]
+impl DensityMatrixF64 {
+    #[new]
+    pub fn new(qubits: usize) -> PyResult<Self> {
+        resources::check_density::<Complex64>(qubits)?;
+        let dim = 1 << qubits;
+        let mut matrix = vec![Complex64::new(0.0, 0.0); dim * dim];
+        matrix[0] = Complex64::new(1.0, 0.0);
+        Ok(DensityMatrixF64 { qubits, matrix })
+    }
+
+    /// Write rho to a versioned checkpoint file at `path`
+    pub fn save(&self, py: Python, path: &str) -> PyResult<()> {
+        py.allow_threads(|| checkpoint::save(path, checkpoint::Kind::DensityMatrix, self.qubits, checkpoint::Buffer::F64(&self.matrix)))
+    }
+
+    /// Read a density matrix saved by `save` (from either precision)
+    #[staticmethod]
+    pub fn load(py: Python, path: &str) -> PyResult<Self> {
+        let (qubits, matrix) = py.allow_threads(|| checkpoint::load(path, checkpoint::Kind::DensityMatrix))?;
+        Ok(DensityMatrixF64 { qubits, matrix })
+    }
+
+    /// rho = |psi><psi|
+    pub fn from_pure_state(&mut self, state: &QuantumStateF64) -> PyResult<()> {
+        if state.qubits != self.qubits {
+            return Err(crate::errors::DimensionMismatchError::new_err("State and density matrix sizes differ"));
+        }
+        let dim = 1 << self.qubits;
+        self.matrix.par_iter_mut().enumerate().for_each(|(idx, val)| {
+            *val = state.state[idx / dim] * state.state[idx % dim].conj();
+        });
+        Ok(())
+    }
+
+    /// Kraus amplitude damping on `qubit` (every qubit when None)
+    #[pyo3(signature = (prob, qubit=None))]
+    pub fn apply_amplitude_damping(&mut self, prob: f32, qubit: Option<usize>) -> PyResult<()> {
+        let p = prob.clamp(0.0, 1.0) as f64;
+        let zero = Complex64::new(0.0, 0.0);
+        let e0 = [[Complex64::new(1.0, 0.0), zero], [zero, Complex64::new((1.0 - p).sqrt(), 0.0)]];
+        let e1 = [[zero, Complex64::new(p.sqrt(), 0.0)], [zero, zero]];
+        let dim = 1 << self.qubits;
+        for q in self.channel_targets(qubit)? {
+            kernels::channel_1q(&mut self.matrix, dim, q, &[e0, e1]);
+        }
+        Ok(())
+    }
+
+    /// Kraus phase damping on `qubit` (every qubit when None)
+    #[pyo3(signature = (prob, qubit=None))]
+    pub fn apply_phase_damping(&mut self, prob: f32, qubit: Option<usize>) -> PyResult<()> {
+        let p = prob.clamp(0.0, 1.0) as f64;
+        let zero = Complex64::new(0.0, 0.0);
+        let e0 = [[Complex64::new(1.0, 0.0), zero], [zero, Complex64::new((1.0 - p).sqrt(), 0.0)]];
+        let e1 = [[zero, zero], [zero, Complex64::new(p.sqrt(), 0.0)]];
+        let dim = 1 << self.qubits;
+        for q in self.channel_targets(qubit)? {
+            kernels::channel_1q(&mut self.matrix, dim, q, &[e0, e1]);
+        }
+        Ok(())
+    }
+
+    /// Tr(rho O) for a PauliString or Observable
+    pub fn expectation(&self, observable: &PyAny) -> PyResult<f64> {
+        let terms = observables::extract_terms(observable, self.qubits)?;
+        let dim = 1 << self.qubits;
+        Ok(terms.iter()
+            .map(|t| t.coeff as f64 * pauli::expectation_density(&self.matrix, dim, t.masks))
+            .sum())
+    }
+
+    pub fn trace(&self) -> f64 {
+        let dim = 1 << self.qubits;
+        (0..dim).map(|i| self.matrix[i * dim + i].re).sum()
+    }
+
+    /// Tr(rho^2)
+    pub fn purity(&self) -> f64 {
+        self.matrix.par_iter().map(|c| c.norm_sqr()).sum()
+    }
+
+    /// Von Neumann entropy S = -Tr(rho log2 rho), matching DensityMatrix.entropy
+    pub fn entropy(&self) -> f64 {
+        reduction::von_neumann_entropy_c64(&self.matrix, 1 << self.qubits)
+    }
+
+    /// Linear entropy 1 - Tr(rho^2)
+    pub fn linear_entropy(&self) -> f64 {
+        1.0 - self.purity()
+    }
+
+    /// Diagonal of rho (basis-state populations)
+    pub fn diagonal(&self) -> Vec<f64> {
+        let dim = 1 << self.qubits;
+        (0..dim).map(|i| self.matrix[i * dim + i].re).collect()
+    }
+
+    /// Sample `shots` computational-basis outcomes from the populations as a
+    /// counts dictionary, with the readout errors of `noise_model` when given
+    #[pyo3(signature = (shots, seed=None, noise_model=None, bit_order="little"))]
+    pub fn sample(&self, py: Python, shots: usize, seed: Option<u64>, noise_model: Option<PyRef<NoiseModel>>, bit_order: &str) -> PyResult<HashMap<String, usize>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        let model: Option<&NoiseModel> = noise_model.as_deref();
+        let probs: Vec<f32> = self.diagonal().iter().map(|&p| p as f32).collect();
+        let counts = py.allow_threads(|| sampling::readout_counts(&probs, self.qubits, shots, seed, model))?;
+        Ok(bit_order.counts(counts))
+    }
+
+    /// rho as a (2^n, 2^n) complex128 numpy array
+    pub fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray2<Complex64>> {
+        py.import("numpy")?;
+        let dim = 1 << self.qubits;
+        self.matrix.to_pyarray(py).reshape([dim, dim])
+    }
+
+    /// Round down to a single-precision DensityMatrix
+    pub fn to_f32(&self) -> DensityMatrix {
+        DensityMatrix {
+            qubits: self.qubits,
+            matrix: self.matrix.iter().map(|c| Complex32::new(c.re as f32, c.im as f32)).collect(),
+        }
+    }
+}
+
+impl DensityMatrixF64 {
+    /// rho -> U rho U^dagger, treating the flattened rho as a 2n-qubit vector
+    /// (U on the row qubits, U* on the column qubits)
+    pub(crate) fn apply_unitary(&mut self, gate: &Gate) -> PyResult<()> {
+        kernels::validate(gate, self.qubits)?;
+        kernels::apply(&mut self.matrix, &gate.shifted(self.qubits), false);
+        kernels::apply(&mut self.matrix, gate, true);
+        Ok(())
+    }
+
+    fn channel_targets(&self, qubit: Option<usize>) -> PyResult<Vec<usize>> {
+        match qubit {
+            Some(q) if q >= self.qubits => Err(crate::errors::QubitIndexError::new_err("Qubit index out of range")),
+            Some(q) => Ok(vec![q]),
+            None => Ok((0..self.qubits).collect()),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Per-gate timing for `QuantumCircuit.execute_profiled`.
+//!
+//! Each instruction runs on its own through `dynamic::run` and is timed by the
+//! wall clock, so gate-type and layer totals add up to the execution time less
+//! the timer overhead. Layers are those of `introspect::depth`. The memory
+//! estimate is the state vector plus the largest scratch copy a gate kernel
+//! allocates; the process high-water mark and CPU time are read from
+//! /proc/self on Linux and are None elsewhere (utilization also for runs
+//! under MIN_UTILIZATION_S).
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use crate::dynamic::{self, ClassicalRegister};
+use crate::{introspect, Gate, GateType, QuantumState};
+
+/// Shortest run whose thread utilization is reported: CPU time comes in
+/// 10 ms ticks, so shorter runs would mostly measure the rounding
+const MIN_UTILIZATION_S: f64 = 0.1;
+
+/// Timings and resource use of one profiled execution
+pub struct Report {
+    qubits: usize,
+    gates: usize,
+    threads: usize,
+    total_s: f64,
+    /// Gate name -> (count, total seconds)
+    by_type: BTreeMap<String, (usize, f64)>,
+    layers_s: Vec<f64>,
+    state_bytes: usize,
+    scratch_bytes: usize,
+    peak_rss_bytes: Option<u64>,
+    cpu_s: Option<f64>,
+}
+
+impl Report {
+    pub fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let gate_types = pyo3::types::PyDict::new(py);
+        for (name, &(count, total)) in &self.by_type {
+            let entry = pyo3::types::PyDict::new(py);
+            entry.set_item("count", count)?;
+            entry.set_item("total_s", total)?;
+            entry.set_item("mean_s", total / count as f64)?;
+            gate_types.set_item(name, entry)?;
+        }
+        let dict = pyo3::types::PyDict::new(py);
+        dict.set_item("qubits", self.qubits)?;
+        dict.set_item("gates", self.gates)?;
+        dict.set_item("threads", self.threads)?;
+        dict.set_item("total_time_s", self.total_s)?;
+        dict.set_item("gate_types", gate_types)?;
+        dict.set_item("layer_times_s", self.layers_s.clone())?;
+        dict.set_item("state_bytes", self.state_bytes)?;
+        dict.set_item("peak_memory_bytes", self.state_bytes + self.scratch_bytes)?;
+        dict.set_item("peak_rss_bytes", self.peak_rss_bytes)?;
+        dict.set_item("cpu_time_s", self.cpu_s)?;
+        let utilization = self.cpu_s.filter(|_| self.total_s >= MIN_UTILIZATION_S)
+            .map(|cpu| cpu / (self.total_s * self.threads as f64));
+        dict.set_item("thread_utilization", utilization)?;
+        Ok(dict.into())
+    }
+}
+
+/// Whether the kernel for `gate` copies the whole state before writing it back
+fn copies_state(gate: &Gate) -> bool {
+    match &gate.gate_type {
+        GateType::UNITARY(targets, _) => targets.len() > 1,
+        GateType::RXX(..) | GateType::RYY(..) => true,
+        GateType::IF(_, _, body) => body.iter().any(copies_state),
+        _ => false,
+    }
+}
+
+/// Process CPU time (user + system, all threads) in seconds
+fn cpu_seconds() -> Option<f64> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // Fields after the parenthesized command name start at the state (field 3);
+    // utime and stime are fields 14 and 15, in USER_HZ = 100 ticks per second
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let ticks: u64 = fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?;
+    Some(ticks as f64 / 100.0)
+}
+
+/// Peak resident set size of the process in bytes
+fn peak_rss() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Run `gates` on `state`, timing every instruction
+pub fn run(state: &mut QuantumState, clbits: usize, gates: &[Gate], creg: &mut ClassicalRegister) -> PyResult<Report> {
+    let n = state.qubits;
+    let levels = introspect::levels(gates, n, clbits);
+    let mut by_type: BTreeMap<String, (usize, f64)> = BTreeMap::new();
+    let mut layers_s = vec![0.0; levels.iter().copied().max().unwrap_or(0)];
+    let cpu_start = cpu_seconds();
+    let start = Instant::now();
+    for (gate, &level) in gates.iter().zip(&levels) {
+        let t0 = Instant::now();
+        dynamic::run(&mut state.state, n, std::slice::from_ref(gate), creg)?;
+        let elapsed = t0.elapsed().as_secs_f64();
+        let entry = by_type.entry(introspect::name(&gate.gate_type)).or_insert((0, 0.0));
+        entry.0 += 1;
+        entry.1 += elapsed;
+        if level > 0 {
+            layers_s[level - 1] += elapsed;
+        }
+    }
+    let total_s = start.elapsed().as_secs_f64();
+    let state_bytes = state.state.len() * std::mem::size_of::<Complex32>();
+    Ok(Report {
+        qubits: n,
+        gates: gates.len(),
+        threads: rayon::current_num_threads(),
+        total_s,
+        by_type,
+        layers_s,
+        state_bytes,
+        scratch_bytes: if gates.iter().any(copies_state) { state_bytes } else { 0 },
+        peak_rss_bytes: peak_rss(),
+        cpu_s: cpu_start.zip(cpu_seconds()).map(|(a, b)| b - a),
+    })
+}
@@ -0,0 +1,83 @@
+//! QAOA ansatz construction from a diagonal (I/Z) cost Hamiltonian.
+
+use pyo3::prelude::*;
+
+use crate::observables::PauliTerm;
+use crate::{Gate, GateType, QuantumCircuit};
+
+/// A layer angle: a fixed value or the name of a circuit parameter
+enum Angle {
+    Value(f32),
+    Symbol(String),
+}
+
+fn layer_angles(values: Option<Vec<f32>>, p_layers: usize, prefix: &str) -> PyResult<Vec<Angle>> {
+    match values {
+        Some(v) if v.len() != p_layers => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Expected {} {} values (one per layer), got {}", p_layers, prefix, v.len()))),
+        Some(v) => Ok(v.into_iter().map(Angle::Value).collect()),
+        None => Ok((0..p_layers).map(|k| Angle::Symbol(format!("{}_{}", prefix, k))).collect()),
+    }
+}
+
+/// Append a rotation whose angle is `scale * angle`
+fn push_rotation(circuit: &mut QuantumCircuit, gate_type: GateType, target: usize, angle: &Angle, scale: f32) -> PyResult<()> {
+    match angle {
+        Angle::Value(v) => {
+            let mut gate_type = gate_type;
+            if let Some(a) = gate_type.angle_mut() {
+                *a = scale * v;
+            }
+            circuit.push(Gate { gate_type, target, control: None })
+        }
+        Angle::Symbol(name) => circuit.push_scaled_param(Gate { gate_type, target, control: None }, name, scale),
+    }
+}
+
+/// Number of qubits spanned by the terms (highest qubit + 1)
+pub fn width(terms: &[PauliTerm]) -> usize {
+    terms.iter()
+        .map(|t| usize::BITS - (t.masks.x | t.masks.z).leading_zeros())
+        .max()
+        .unwrap_or(0) as usize
+}
+
+pub fn build(
+    terms: &[PauliTerm],
+    qubits: usize,
+    p_layers: usize,
+    gammas: Option<Vec<f32>>,
+    betas: Option<Vec<f32>>,
+) -> PyResult<QuantumCircuit> {
+    if terms.iter().any(|t| t.masks.x != 0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "QAOA cost Hamiltonian must contain only I and Z factors"));
+    }
+    let gammas = layer_angles(gammas, p_layers, "gamma")?;
+    let betas = layer_angles(betas, p_layers, "beta")?;
+
+    let mut circuit = QuantumCircuit::new(qubits, 0);
+    for q in 0..qubits {
+        circuit.h(q)?;
+    }
+
+    for (gamma, beta) in gammas.iter().zip(&betas) {
+        // exp(-i gamma c Z_S): parity of S onto its last qubit, RZ(2 gamma c), uncompute
+        for term in terms.iter().filter(|t| t.masks.z != 0) {
+            let support: Vec<usize> = (0..qubits).filter(|q| term.masks.z >> q & 1 == 1).collect();
+            let (&last, rest) = support.split_last().unwrap();
+            for &q in rest {
+                circuit.cnot(q, last)?;
+            }
+            push_rotation(&mut circuit, GateType::RZ(0.0), last, gamma, 2.0 * term.coeff)?;
+            for &q in rest.iter().rev() {
+                circuit.cnot(q, last)?;
+            }
+        }
+        // Mixer exp(-i beta sum X)
+        for q in 0..qubits {
+            push_rotation(&mut circuit, GateType::RX(0.0), q, beta, 2.0)?;
+        }
+    }
+    Ok(circuit)
+}
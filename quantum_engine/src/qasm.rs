@@ -0,0 +1,428 @@
+//! OpenQASM 2.0 import and export for QuantumCircuit.
+//!
+//! Covers the qelib1 subset this engine simulates natively, plus `creg`,
+//! `measure` and `reset`: classical registers are laid out one after another
+//! in the circuit's classical register and measurements become MEASURE gates,
+//! so a terminal measure is what `run()` samples and a mid-circuit one
+//! collapses the state. `barrier` is accepted and dropped; `if` is not
+//! supported, since it compares a whole register rather than one bit.
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::{Gate, GateType};
+
+/// Largest total qubit (and classical bit) count a program may declare; far
+/// beyond what any backend here holds, but it keeps a typo like
+/// `qreg q[99999999999];` from reaching an allocation
+const MAX_QUBITS: usize = 1 << 16;
+
+fn err(line: usize, msg: impl std::fmt::Display) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("QASM line {}: {}", line, msg))
+}
+
+/// Serialize a gate list as an OpenQASM 2.0 program, with measurements into `c`
+pub fn emit(qubits: usize, clbits: usize, gates: &[Gate]) -> PyResult<String> {
+    let mut out = String::from("OPENQASM 2.0;\ninclude \"qelib1.inc\";\n");
+    out.push_str(&format!("qreg q[{}];\n", qubits));
+    if clbits > 0 {
+        out.push_str(&format!("creg c[{}];\n", clbits));
+    }
+
+    for (i, gate) in gates.iter().enumerate() {
+        let t = gate.target;
+        let line = match &gate.gate_type {
+            GateType::H => format!("h q[{}];", t),
+            GateType::X => format!("x q[{}];", t),
+            GateType::Y => format!("y q[{}];", t),
+            GateType::Z => format!("z q[{}];", t),
+            GateType::S => format!("s q[{}];", t),
+            GateType::T => format!("t q[{}];", t),
+            GateType::SDG => format!("sdg q[{}];", t),
+            GateType::TDG => format!("tdg q[{}];", t),
+            GateType::RX(theta) => format!("rx({}) q[{}];", theta, t),
+            GateType::RY(theta) => format!("ry({}) q[{}];", theta, t),
+            GateType::RZ(phi) => format!("rz({}) q[{}];", phi, t),
+            GateType::U(theta, phi, lambda) => format!("u3({},{},{}) q[{}];", theta, phi, lambda, t),
+            GateType::P(lambda) => format!("u1({}) q[{}];", lambda, t),
+            GateType::CNOT => format!("cx q[{}],q[{}];", gate.control.unwrap(), t),
+            GateType::CRX(theta) => format!("crx({}) q[{}],q[{}];", theta, gate.control.unwrap(), t),
+            GateType::CRY(theta) => format!("cry({}) q[{}],q[{}];", theta, gate.control.unwrap(), t),
+            GateType::CRZ(phi) => format!("crz({}) q[{}],q[{}];", phi, gate.control.unwrap(), t),
+            GateType::CH => format!("ch q[{}],q[{}];", gate.control.unwrap(), t),
+            GateType::CPHASE(phi) => format!("cu1({}) q[{}],q[{}];", phi, gate.control.unwrap(), t),
+            GateType::MCX(controls) if controls.len() == 1 => format!("cx q[{}],q[{}];", controls[0], t),
+            GateType::MCX(controls) if controls.len() == 2 => {
+                format!("ccx q[{}],q[{}],q[{}];", controls[0], controls[1], t)
+            }
+            GateType::CONTROLLED(controls, inner) if controls.len() == 1 => {
+                let c = controls[0];
+                match **inner {
+                    GateType::X => format!("cx q[{}],q[{}];", c, t),
+                    GateType::Y => format!("cy q[{}],q[{}];", c, t),
+                    GateType::Z => format!("cz q[{}],q[{}];", c, t),
+                    GateType::H => format!("ch q[{}],q[{}];", c, t),
+                    GateType::RX(theta) => format!("crx({}) q[{}],q[{}];", theta, c, t),
+                    GateType::RY(theta) => format!("cry({}) q[{}],q[{}];", theta, c, t),
+                    GateType::RZ(phi) => format!("crz({}) q[{}],q[{}];", phi, c, t),
+                    GateType::P(lambda) => format!("cu1({}) q[{}],q[{}];", lambda, c, t),
+                    GateType::U(theta, phi, lambda) => format!("cu3({},{},{}) q[{}],q[{}];", theta, phi, lambda, c, t),
+                    _ => return Err(pyo3::exceptions::PyValueError::new_err(
+                        format!("Gate {} ({:?}) has no OpenQASM 2.0 equivalent", i, gate.gate_type))),
+                }
+            }
+            GateType::CONTROLLED(controls, inner) if controls.len() == 2 && matches!(**inner, GateType::X) => {
+                format!("ccx q[{}],q[{}],q[{}];", controls[0], controls[1], t)
+            }
+            GateType::SWAP(a) => format!("swap q[{}],q[{}];", a, t),
+            GateType::SNAPSHOT(label) => format!("// snapshot {:?}", label),
+            GateType::MEASURE(bit) => format!("measure q[{}] -> c[{}];", t, bit),
+            GateType::RESET => format!("reset q[{}];", t),
+            GateType::RXX(a, theta) => format!("rxx({}) q[{}],q[{}];", theta, a, t),
+            GateType::RZZ(a, theta) => format!("rzz({}) q[{}],q[{}];", theta, a, t),
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    format!("Gate {} ({:?}) has no OpenQASM 2.0 equivalent", i, other)));
+            }
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Parse an OpenQASM 2.0 program into (qubit count, classical bit count, gate list)
+pub fn parse(src: &str) -> PyResult<(usize, usize, Vec<Gate>)> {
+    // Strip comments, keeping track of the line each statement starts on
+    let mut statements: Vec<(usize, String)> = Vec::new();
+    let mut current = String::new();
+    let mut start_line = 1;
+    for (lineno, raw) in src.lines().enumerate() {
+        let code = raw.split("//").next().unwrap_or("");
+        for ch in code.chars() {
+            if current.trim().is_empty() {
+                start_line = lineno + 1;
+            }
+            if ch == ';' {
+                statements.push((start_line, current.trim().to_string()));
+                current.clear();
+            } else if ch == '{' || ch == '}' {
+                return Err(err(lineno + 1, "Gate definitions and blocks are not supported"));
+            } else {
+                current.push(ch);
+            }
+        }
+        current.push(' ');
+    }
+    if !current.trim().is_empty() {
+        return Err(err(start_line, "Missing ';' at end of statement"));
+    }
+
+    let mut registers: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut cregs: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut qubits: usize = 0;
+    let mut clbits: usize = 0;
+    let mut gates = Vec::new();
+    let mut seen_header = false;
+
+    for (line, stmt) in statements {
+        if stmt.is_empty() {
+            continue;
+        }
+        let (head, rest) = split_head(&stmt);
+        match head.as_str() {
+            "OPENQASM" => {
+                if rest.trim() != "2.0" {
+                    return Err(err(line, format!("Unsupported OpenQASM version '{}'", rest.trim())));
+                }
+                seen_header = true;
+            }
+            "include" => {}
+            "qreg" | "creg" => {
+                let (name, size) = parse_indexed(rest.trim(), line)?;
+                if registers.contains_key(&name) || cregs.contains_key(&name) {
+                    return Err(err(line, format!("Register '{}' is already declared", name)));
+                }
+                let (count, map) = if head == "qreg" { (&mut qubits, &mut registers) } else { (&mut clbits, &mut cregs) };
+                *count = match count.checked_add(size) {
+                    Some(total) if total <= MAX_QUBITS => total,
+                    _ => return Err(err(line, format!(
+                        "{} {}[{}] takes the program past the {}-bit limit", head, name, size, MAX_QUBITS))),
+                };
+                map.insert(name, (*count - size, size));
+            }
+            "barrier" => {}
+            "measure" => {
+                let (source, dest) = rest.split_once("->")
+                    .ok_or_else(|| err(line, "Expected 'measure qubit -> bit'"))?;
+                let targets = resolve_operand(source, line, &registers)?;
+                let bits = resolve_operand(dest, line, &cregs)?;
+                if targets.len() != bits.len() {
+                    return Err(err(line, format!(
+                        "Cannot measure {} qubit(s) into {} bit(s)", targets.len(), bits.len())));
+                }
+                for (target, bit) in targets.into_iter().zip(bits) {
+                    gates.push(Gate { gate_type: GateType::MEASURE(bit), target, control: None });
+                }
+            }
+            "reset" => {
+                for target in resolve_operand(rest, line, &registers)? {
+                    gates.push(Gate { gate_type: GateType::RESET, target, control: None });
+                }
+            }
+            "if" | "gate" | "opaque" => {
+                return Err(err(line, format!("'{}' statements are not supported", head)));
+            }
+            _ => {
+                if !seen_header {
+                    return Err(err(line, "Missing 'OPENQASM 2.0;' header"));
+                }
+                parse_gate(&head, rest, line, &registers, &mut gates)?;
+            }
+        }
+    }
+
+    if !seen_header {
+        return Err(err(1, "Missing 'OPENQASM 2.0;' header"));
+    }
+    Ok((qubits, clbits, gates))
+}
+
+/// Split a statement into its keyword (gate name, without parameters) and the remainder
+fn split_head(stmt: &str) -> (String, &str) {
+    let end = stmt.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(stmt.len());
+    (stmt[..end].to_string(), &stmt[end..])
+}
+
+/// Parse `name[size]`
+fn parse_indexed(s: &str, line: usize) -> PyResult<(String, usize)> {
+    let open = s.find('[').ok_or_else(|| err(line, format!("Expected 'name[index]', got '{}'", s)))?;
+    let close = s.find(']').ok_or_else(|| err(line, format!("Missing ']' in '{}'", s)))?;
+    let index = s[open + 1..close].trim().parse::<usize>()
+        .map_err(|_| err(line, format!("Invalid index in '{}'", s)))?;
+    Ok((s[..open].trim().to_string(), index))
+}
+
+/// Resolve a gate argument into global qubit indices (a bare register broadcasts)
+fn resolve_operand(s: &str, line: usize, registers: &HashMap<String, (usize, usize)>) -> PyResult<Vec<usize>> {
+    let s = s.trim();
+    if s.contains('[') {
+        let (name, index) = parse_indexed(s, line)?;
+        let &(offset, size) = registers.get(&name)
+            .ok_or_else(|| err(line, format!("Unknown register '{}'", name)))?;
+        if index >= size {
+            return Err(err(line, format!("Index {} out of range for register '{}[{}]'", index, name, size)));
+        }
+        Ok(vec![offset + index])
+    } else {
+        let &(offset, size) = registers.get(s)
+            .ok_or_else(|| err(line, format!("Unknown register '{}'", s)))?;
+        Ok((offset..offset + size).collect())
+    }
+}
+
+fn parse_gate(
+    name: &str,
+    rest: &str,
+    line: usize,
+    registers: &HashMap<String, (usize, usize)>,
+    gates: &mut Vec<Gate>,
+) -> PyResult<()> {
+    let rest = rest.trim();
+    let (params, operands) = if let Some(stripped) = rest.strip_prefix('(') {
+        let close = matching_paren(stripped).ok_or_else(|| err(line, "Unbalanced parentheses"))?;
+        let params = stripped[..close].split(',')
+            .map(|p| eval_expr(p, line))
+            .collect::<PyResult<Vec<f32>>>()?;
+        (params, &stripped[close + 1..])
+    } else {
+        (Vec::new(), rest)
+    };
+    let args = operands.split(',')
+        .map(|a| resolve_operand(a, line, registers))
+        .collect::<PyResult<Vec<Vec<usize>>>>()?;
+
+    let expect = |n_params: usize, n_args: usize| -> PyResult<()> {
+        if params.len() != n_params || args.len() != n_args {
+            return Err(err(line, format!(
+                "'{}' expects {} parameter(s) and {} qubit argument(s)", name, n_params, n_args)));
+        }
+        Ok(())
+    };
+
+    let single = |gate_type: GateType, gates: &mut Vec<Gate>| {
+        for &target in &args[0] {
+            gates.push(Gate { gate_type: gate_type.clone(), target, control: None });
+        }
+    };
+
+    match name {
+        "h" => { expect(0, 1)?; single(GateType::H, gates); }
+        "x" => { expect(0, 1)?; single(GateType::X, gates); }
+        "y" => { expect(0, 1)?; single(GateType::Y, gates); }
+        "z" => { expect(0, 1)?; single(GateType::Z, gates); }
+        "s" => { expect(0, 1)?; single(GateType::S, gates); }
+        "t" => { expect(0, 1)?; single(GateType::T, gates); }
+        "sdg" => { expect(0, 1)?; single(GateType::SDG, gates); }
+        "tdg" => { expect(0, 1)?; single(GateType::TDG, gates); }
+        "rx" => { expect(1, 1)?; single(GateType::RX(params[0]), gates); }
+        "ry" => { expect(1, 1)?; single(GateType::RY(params[0]), gates); }
+        "rz" => { expect(1, 1)?; single(GateType::RZ(params[0]), gates); }
+        "u3" | "u" | "U" => { expect(3, 1)?; single(GateType::U(params[0], params[1], params[2]), gates); }
+        "u2" => { expect(2, 1)?; single(GateType::U(std::f32::consts::FRAC_PI_2, params[0], params[1]), gates); }
+        "u1" | "p" => { expect(1, 1)?; single(GateType::P(params[0]), gates); }
+        "cx" | "CX" => {
+            expect(0, 2)?;
+            let (c, t) = two_qubits(&args, line)?;
+            gates.push(Gate { gate_type: GateType::CNOT, target: t, control: Some(c) });
+        }
+        "crx" => {
+            expect(1, 2)?;
+            let (c, t) = two_qubits(&args, line)?;
+            gates.push(Gate { gate_type: GateType::CRX(params[0]), target: t, control: Some(c) });
+        }
+        "cry" => {
+            expect(1, 2)?;
+            let (c, t) = two_qubits(&args, line)?;
+            gates.push(Gate { gate_type: GateType::CRY(params[0]), target: t, control: Some(c) });
+        }
+        "crz" => {
+            expect(1, 2)?;
+            let (c, t) = two_qubits(&args, line)?;
+            gates.push(Gate { gate_type: GateType::CRZ(params[0]), target: t, control: Some(c) });
+        }
+        "ch" => {
+            expect(0, 2)?;
+            let (c, t) = two_qubits(&args, line)?;
+            gates.push(Gate { gate_type: GateType::CH, target: t, control: Some(c) });
+        }
+        "cu1" | "cp" => {
+            expect(1, 2)?;
+            let (c, t) = two_qubits(&args, line)?;
+            gates.push(Gate { gate_type: GateType::CPHASE(params[0]), target: t, control: Some(c) });
+        }
+        "cy" | "cz" | "cu3" => {
+            let inner = match name {
+                "cy" => { expect(0, 2)?; GateType::Y }
+                "cz" => { expect(0, 2)?; GateType::Z }
+                _ => { expect(3, 2)?; GateType::U(params[0], params[1], params[2]) }
+            };
+            let (c, t) = two_qubits(&args, line)?;
+            gates.push(Gate { gate_type: GateType::CONTROLLED(vec![c], Box::new(inner)), target: t, control: None });
+        }
+        "swap" => {
+            expect(0, 2)?;
+            let (a, b) = two_qubits(&args, line)?;
+            gates.push(Gate { gate_type: GateType::SWAP(a), target: b, control: None });
+        }
+        "rxx" | "ryy" | "rzz" => {
+            expect(1, 2)?;
+            let (a, b) = two_qubits(&args, line)?;
+            let gate_type = match name {
+                "rxx" => GateType::RXX(a, params[0]),
+                "ryy" => GateType::RYY(a, params[0]),
+                _ => GateType::RZZ(a, params[0]),
+            };
+            gates.push(Gate { gate_type, target: b, control: None });
+        }
+        "ccx" => {
+            expect(0, 3)?;
+            if args.iter().any(|a| a.len() != 1) {
+                return Err(err(line, "Register broadcasting is only supported for single-qubit gates"));
+            }
+            gates.push(Gate { gate_type: GateType::MCX(vec![args[0][0], args[1][0]]), target: args[2][0], control: None });
+        }
+        _ => return Err(err(line, format!("Unsupported gate '{}'", name))),
+    }
+    Ok(())
+}
+
+fn two_qubits(args: &[Vec<usize>], line: usize) -> PyResult<(usize, usize)> {
+    if args[0].len() != 1 || args[1].len() != 1 {
+        return Err(err(line, "Register broadcasting is only supported for single-qubit gates"));
+    }
+    Ok((args[0][0], args[1][0]))
+}
+
+/// Index of the ')' closing an already-opened parenthesis
+fn matching_paren(s: &str) -> Option<usize> {
+    let mut depth = 1;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Evaluate a QASM parameter expression (numbers, pi, + - * /, parentheses)
+fn eval_expr(src: &str, line: usize) -> PyResult<f32> {
+    let tokens: Vec<char> = src.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut pos = 0;
+    let value = expr(&tokens, &mut pos).ok_or_else(|| err(line, format!("Invalid parameter '{}'", src.trim())))?;
+    if pos != tokens.len() {
+        return Err(err(line, format!("Invalid parameter '{}'", src.trim())));
+    }
+    Ok(value as f32)
+}
+
+fn expr(t: &[char], pos: &mut usize) -> Option<f64> {
+    let mut value = term(t, pos)?;
+    while *pos < t.len() && (t[*pos] == '+' || t[*pos] == '-') {
+        let op = t[*pos];
+        *pos += 1;
+        let rhs = term(t, pos)?;
+        value = if op == '+' { value + rhs } else { value - rhs };
+    }
+    Some(value)
+}
+
+fn term(t: &[char], pos: &mut usize) -> Option<f64> {
+    let mut value = factor(t, pos)?;
+    while *pos < t.len() && (t[*pos] == '*' || t[*pos] == '/') {
+        let op = t[*pos];
+        *pos += 1;
+        let rhs = factor(t, pos)?;
+        value = if op == '*' { value * rhs } else { value / rhs };
+    }
+    Some(value)
+}
+
+fn factor(t: &[char], pos: &mut usize) -> Option<f64> {
+    match t.get(*pos)? {
+        '-' => { *pos += 1; factor(t, pos).map(|v| -v) }
+        '+' => { *pos += 1; factor(t, pos) }
+        '(' => {
+            *pos += 1;
+            let v = expr(t, pos)?;
+            if t.get(*pos) != Some(&')') {
+                return None;
+            }
+            *pos += 1;
+            Some(v)
+        }
+        'p' => {
+            if t.get(*pos + 1) == Some(&'i') {
+                *pos += 2;
+                Some(std::f64::consts::PI)
+            } else {
+                None
+            }
+        }
+        _ => {
+            let start = *pos;
+            while *pos < t.len() && (t[*pos].is_ascii_digit() || t[*pos] == '.' || t[*pos] == 'e'
+                || ((t[*pos] == '-' || t[*pos] == '+') && *pos > start && t[*pos - 1] == 'e')) {
+                *pos += 1;
+            }
+            t[start..*pos].iter().collect::<String>().parse().ok()
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Conversion to and from qiskit.QuantumCircuit objects.
+//!
+//! Import walks `qc.data` and maps each instruction onto the engine's gate
+//! names; standard gates without an engine equivalent (sx, ecr, cswap, ...)
+//! come in as dense unitaries through their `to_matrix()`. Parameters enter
+//! as symbolic angles when the expression is a multiple of a single
+//! Parameter, and `if_else` blocks without an else branch become c_if bodies.
+//! Barriers and delays are dropped. Export calls the qiskit builder methods
+//! in gate order; snapshots become labelled barriers. Both sides number
+//! qubits little-endian, so amplitudes and unitaries carry over unchanged.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyComplex, PyDict, PyList, PyTuple};
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_2;
+
+use crate::{errors, extract_complex, introspect, Gate, GateType, QuantumCircuit, QuantumState};
+
+/// Indices of the Qubit or Clbit objects `bits` within `qc`
+fn indices(qc: &PyAny, bits: &PyAny) -> PyResult<Vec<usize>> {
+    bits.iter()?.map(|bit| qc.call_method1("find_bit", (bit?,))?.getattr("index")?.extract()).collect()
+}
+
+/// (name, scale) of a parameter expression `scale * name`
+fn symbol(expr: &PyAny) -> PyResult<(String, f32)> {
+    let unsupported = || errors::InvalidGateError::new_err(format!(
+        "Angle '{}' is neither a number nor a multiple of a single Parameter", expr));
+    let parameters: Vec<&PyAny> = expr.getattr("parameters").map_err(|_| unsupported())?.iter()?.collect::<PyResult<_>>()?;
+    let [parameter] = parameters[..] else {
+        return Err(unsupported());
+    };
+    let scale: f64 = expr.call_method1("gradient", (parameter,))?.extract().map_err(|_| unsupported())?;
+    let at_zero = PyDict::new(expr.py());
+    at_zero.set_item(parameter, 0.0)?;
+    let offset: f64 = expr.call_method1("bind", (at_zero,))?.extract().map_err(|_| unsupported())?;
+    if offset != 0.0 {
+        return Err(unsupported());
+    }
+    Ok((parameter.getattr("name")?.extract()?, scale as f32))
+}
+
+/// Build an engine circuit from a qiskit.QuantumCircuit
+pub fn from_qiskit(qc: &PyAny) -> PyResult<QuantumCircuit> {
+    let mut circuit = QuantumCircuit::new(qc.getattr("num_qubits")?.extract()?, qc.getattr("num_clbits")?.extract()?);
+    let qubits: Vec<usize> = (0..circuit.qubits).collect();
+    let clbits: Vec<usize> = (0..circuit.clbits).collect();
+    append_all(&mut circuit, qc, &qubits, &clbits)?;
+    Ok(circuit)
+}
+
+/// Append the instructions of `qc`, whose qubit i is `qubit_map[i]` of
+/// `circuit` (and clbit j is `clbit_map[j]`)
+fn append_all(circuit: &mut QuantumCircuit, qc: &PyAny, qubit_map: &[usize], clbit_map: &[usize]) -> PyResult<()> {
+    for instruction in qc.getattr("data")?.iter()? {
+        let instruction = instruction?;
+        let qubits: Vec<usize> = indices(qc, instruction.getattr("qubits")?)?.into_iter().map(|q| qubit_map[q]).collect();
+        let clbits: Vec<usize> = indices(qc, instruction.getattr("clbits")?)?.into_iter().map(|c| clbit_map[c]).collect();
+        append(circuit, qc, instruction.getattr("operation")?, &qubits, &clbits, clbit_map)?;
+    }
+    Ok(())
+}
+
+fn append(
+    circuit: &mut QuantumCircuit,
+    qc: &PyAny,
+    op: &PyAny,
+    qubits: &[usize],
+    clbits: &[usize],
+    clbit_map: &[usize],
+) -> PyResult<()> {
+    let name: String = op.getattr("name")?.extract()?;
+    let n = circuit.qubits;
+    match name.as_str() {
+        "barrier" | "delay" | "id" => return Ok(()),
+        "measure" => {
+            let gate = introspect::from_description(&("measure".to_string(), qubits.to_vec(), vec![clbits[0] as f32]), n)?;
+            return circuit.push(gate);
+        }
+        "if_else" => {
+            let blocks: Vec<&PyAny> = op.getattr("blocks")?.extract()?;
+            if blocks.len() > 1 && !blocks[1].is_none() {
+                return Err(errors::InvalidGateError::new_err("if_else blocks with an else branch are not supported"));
+            }
+            let condition: &PyTuple = op.getattr("condition")?.downcast()
+                .map_err(|_| errors::InvalidGateError::new_err("Only (clbit, value) conditions are supported"))?;
+            let mut target = condition.get_item(0)?;
+            if target.hasattr("size")? {
+                // A one-bit ClassicalRegister
+                if target.getattr("size")?.extract::<usize>()? != 1 {
+                    return Err(errors::InvalidGateError::new_err("Conditions on multi-bit registers are not supported"));
+                }
+                target = target.get_item(0)?;
+            }
+            let bit = clbit_map[qc.call_method1("find_bit", (target,))?.getattr("index")?.extract::<usize>()?];
+            let mut body = QuantumCircuit::new(n, circuit.clbits);
+            append_all(&mut body, blocks[0], qubits, clbits)?;
+            return circuit.c_if(bit, &body, condition.get_item(1)?.extract()?);
+        }
+        "initialize" | "state_preparation" => {
+            if !circuit.gates.is_empty() || circuit.initial.is_some() || qubits != (0..n).collect::<Vec<_>>() {
+                return Err(errors::InvalidGateError::new_err(format!(
+                    "{} is only supported as the first instruction, on every qubit in order", name)));
+            }
+            let amplitudes = op.getattr("params")?.iter()?.map(|a| extract_complex(a?)).collect::<PyResult<_>>()?;
+            circuit.initial = Some(QuantumState::from_amplitudes(amplitudes)?.state);
+            return Ok(());
+        }
+        "unitary" => return append_matrix(circuit, op, qubits),
+        _ => {}
+    }
+
+    let mut angles = Vec::new();
+    let mut parameter = None;
+    for value in op.getattr("params")?.iter()? {
+        let value = value?;
+        match value.extract::<f64>() {
+            Ok(angle) => angles.push(angle as f32),
+            Err(_) if parameter.is_none() => {
+                parameter = Some(symbol(value)?);
+                angles.push(0.0);
+            }
+            Err(_) => return Err(errors::InvalidGateError::new_err(format!("{}: only one angle may be symbolic", name))),
+        }
+    }
+    let (engine_name, angles, phase) = match name.as_str() {
+        "cx" => ("cnot", angles, None),
+        "cy" => ("controlled_y", angles, None),
+        "cs" => ("controlled_s", angles, None),
+        "csdg" => ("controlled_sdg", angles, None),
+        "cz" | "ccz" => ("controlled_z", angles, None),
+        "ccx" | "mcx" => ("mcx", angles, None),
+        "cp" | "cu1" => ("cphase", angles, None),
+        "u3" => ("u", angles, None),
+        "u1" => ("p", angles, None),
+        "u2" if angles.len() == 2 => ("u", vec![FRAC_PI_2, angles[0], angles[1]], None),
+        "cu3" => ("controlled_u", angles, None),
+        // cu(theta, phi, lambda, gamma) is controlled e^(i gamma) U, a CU and a phase on the control
+        "cu" if angles.len() == 4 => ("controlled_u", angles[..3].to_vec(), Some(angles[3])),
+        other => (other, angles, None),
+    };
+    let gate = match introspect::from_description(&(engine_name.to_string(), qubits.to_vec(), angles), n) {
+        Ok(gate) => gate,
+        Err(_) if parameter.is_none() && op.hasattr("to_matrix")? => return append_matrix(circuit, op, qubits),
+        Err(e) => return Err(e),
+    };
+    match parameter {
+        Some((symbol, scale)) => {
+            let mut gate = gate;
+            if gate.gate_type.angle_mut().is_none() {
+                return Err(errors::InvalidGateError::new_err(format!("{}: symbolic angles are not supported on this gate", name)));
+            }
+            circuit.push_scaled_param(gate, &symbol, scale)?;
+        }
+        None => circuit.push(gate)?,
+    }
+    if let Some(gamma) = phase.filter(|&g| g != 0.0) {
+        circuit.p(qubits[0], gamma)?;
+    }
+    Ok(())
+}
+
+/// Append `op` as a dense unitary from its `to_matrix()`
+fn append_matrix(circuit: &mut QuantumCircuit, op: &PyAny, qubits: &[usize]) -> PyResult<()> {
+    let rows: Vec<&PyAny> = op.call_method0("to_matrix")?.call_method0("tolist")?.extract()?;
+    let matrix = rows.into_iter()
+        .map(|row| row.iter()?.map(|entry| extract_complex(entry?)).collect::<PyResult<Vec<_>>>())
+        .collect::<PyResult<Vec<_>>>()?;
+    circuit.unitary(matrix, qubits.to_vec())
+}
+
+/// Class in qiskit.circuit.library of an engine single-qubit gate
+fn library_class(gate_type: &GateType) -> &'static str {
+    match gate_type {
+        GateType::H => "HGate",
+        GateType::X => "XGate",
+        GateType::Y => "YGate",
+        GateType::Z => "ZGate",
+        GateType::S => "SGate",
+        GateType::T => "TGate",
+        GateType::SDG => "SdgGate",
+        GateType::TDG => "TdgGate",
+        GateType::RX(_) => "RXGate",
+        GateType::RY(_) => "RYGate",
+        GateType::RZ(_) => "RZGate",
+        GateType::P(_) => "PhaseGate",
+        _ => "UGate",
+    }
+}
+
+/// Build a qiskit.QuantumCircuit with the same gates, parameters and initial state
+pub fn to_qiskit(py: Python, circuit: &QuantumCircuit) -> PyResult<PyObject> {
+    let qiskit = py.import("qiskit")?;
+    let library = py.import("qiskit.circuit.library")?;
+    let qc = qiskit.getattr("QuantumCircuit")?.call1((circuit.qubits, circuit.clbits))?;
+
+    if let Some(initial) = &circuit.initial {
+        let amplitudes: Vec<&PyComplex> = initial.iter().map(|a| PyComplex::from_doubles(py, a.re as f64, a.im as f64)).collect();
+        qc.call_method1("initialize", (amplitudes, qc.getattr("qubits")?))?;
+    }
+
+    let mut parameters: HashMap<&str, &PyAny> = HashMap::new();
+    let mut symbolic: HashMap<usize, &PyAny> = HashMap::new();
+    for r in &circuit.params {
+        let parameter = match parameters.get(r.name.as_str()) {
+            Some(&p) => p,
+            None => {
+                let p = qiskit.getattr("circuit")?.getattr("Parameter")?.call1((&r.name,))?;
+                parameters.insert(&r.name, p);
+                p
+            }
+        };
+        let angle = if r.scale == 1.0 { parameter } else { parameter.call_method1("__mul__", (r.scale as f64,))? };
+        symbolic.insert(r.gate, angle);
+    }
+    for (i, gate) in circuit.gates.iter().enumerate() {
+        emit(py, qc, library, gate, symbolic.get(&i).copied())?;
+    }
+    Ok(qc.into())
+}
+
+fn call(qc: &PyAny, method: &str, args: impl IntoPy<Py<PyTuple>>) -> PyResult<()> {
+    qc.call_method1(method, args)?;
+    Ok(())
+}
+
+/// Append `gate` to the qiskit circuit `qc`, with `symbolic` replacing its angle
+fn emit(py: Python, qc: &PyAny, library: &PyModule, gate: &Gate, symbolic: Option<&PyAny>) -> PyResult<()> {
+    let t = gate.target;
+    let angle = |a: f32| symbolic.map_or_else(|| a.into_py(py), |s| s.into_py(py));
+    let control = gate.control.unwrap_or(0);
+    let name = gate.gate_type.name();
+    match &gate.gate_type {
+        GateType::H | GateType::X | GateType::Y | GateType::Z | GateType::S | GateType::T | GateType::SDG | GateType::TDG => {
+            call(qc, name, (t,))
+        }
+        GateType::RX(a) | GateType::RY(a) | GateType::RZ(a) | GateType::P(a) => call(qc, name, (angle(*a), t)),
+        GateType::U(theta, phi, lambda) => call(qc, "u", (*theta, *phi, *lambda, t)),
+        GateType::CNOT => call(qc, "cx", (control, t)),
+        GateType::CH => call(qc, "ch", (control, t)),
+        GateType::CRX(a) | GateType::CRY(a) | GateType::CRZ(a) => call(qc, name, (angle(*a), control, t)),
+        GateType::CPHASE(a) => call(qc, "cp", (angle(*a), control, t)),
+        GateType::MCX(controls) => call(qc, "mcx", (controls.clone(), t)),
+        GateType::CONTROLLED(controls, inner) => {
+            let params: Vec<PyObject> = match **inner {
+                GateType::RX(a) | GateType::RY(a) | GateType::RZ(a) | GateType::P(a) => vec![angle(a)],
+                GateType::U(theta, phi, lambda) => vec![theta.into_py(py), phi.into_py(py), lambda.into_py(py)],
+                _ => Vec::new(),
+            };
+            let base = library.getattr(library_class(inner))?.call1(PyTuple::new(py, params))?;
+            call(qc, "append", (base.call_method1("control", (controls.len(),))?, gate.qubits()))
+        }
+        GateType::SWAP(a) => call(qc, "swap", (*a, t)),
+        GateType::ISWAP(a) => call(qc, "iswap", (*a, t)),
+        GateType::RXX(a, theta) | GateType::RYY(a, theta) | GateType::RZZ(a, theta) => call(qc, name, (angle(*theta), *a, t)),
+        GateType::UNITARY(targets, matrix) => {
+            let dim = 1 << targets.len();
+            let rows = PyList::empty(py);
+            for row in matrix.chunks(dim) {
+                rows.append(PyList::new(py, row.iter().map(|c| PyComplex::from_doubles(py, c.re as f64, c.im as f64))))?;
+            }
+            // Single-precision matrices fail qiskit's default 1e-8 unitarity check
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("check_input", false)?;
+            let unitary = library.getattr("UnitaryGate")?.call((rows,), Some(kwargs))?;
+            call(qc, "append", (unitary, targets.clone()))
+        }
+        GateType::MEASURE(bit) => call(qc, "measure", (t, *bit)),
+        GateType::RESET => call(qc, "reset", (t,)),
+        GateType::IF(bit, value, body) => {
+            let (qubits, clbits) = (qc.getattr("qubits")?, qc.getattr("clbits")?);
+            let body_qc = qc.getattr("__class__")?.call1((qubits, clbits))?;
+            for inner in body {
+                emit(py, body_qc, library, inner, None)?;
+            }
+            call(qc, "if_test", ((clbits.get_item(*bit)?, *value as u8), body_qc, qubits, clbits))
+        }
+        GateType::SNAPSHOT(label) => {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("label", label)?;
+            qc.call_method("barrier", (), Some(kwargs))?;
+            Ok(())
+        }
+        GateType::RESTORE(label) => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "restore({:?}) has no qiskit equivalent", label))),
+    }
+}
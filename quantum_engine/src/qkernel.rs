@@ -0,0 +1,77 @@
+//! Fidelity kernels for quantum machine learning.
+//!
+//! Entry (i, j) of the kernel is |<psi_i|psi_j>|^2 for the states the feature
+//! circuits prepare from |0...0>. Each circuit runs once (in parallel), and
+//! the overlaps are accumulated in double precision, so an N x N kernel costs
+//! N executions and N(N+1)/2 inner products instead of N^2 round trips
+//! through Python.
+
+use num_complex::Complex32;
+use numpy::{PyArray2, ToPyArray};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::{dynamic, errors, QuantumCircuit};
+
+/// Final state vectors of `circuits`, which must all act on `qubits` qubits
+fn states(circuits: &[QuantumCircuit], qubits: usize) -> PyResult<Vec<Vec<Complex32>>> {
+    for (i, c) in circuits.iter().enumerate() {
+        if c.qubits != qubits {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "Feature circuit {} has {} qubits, expected {}", i, c.qubits, qubits)));
+        }
+        if dynamic::is_dynamic(&c.gates) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Feature circuit {} contains measurement, reset or c_if; kernels need unitary circuits", i)));
+        }
+    }
+    circuits.par_iter()
+        .map(|c| Ok(c.simulate(&mut dynamic::ClassicalRegister::new(c.clbits, None), 1)?.state))
+        .collect()
+}
+
+fn fidelity(a: &[Complex32], b: &[Complex32]) -> f64 {
+    let overlap = a.iter().zip(b).fold((0.0f64, 0.0f64), |(re, im), (x, y)| {
+        let p = x.conj() * y;
+        (re + p.re as f64, im + p.im as f64)
+    });
+    overlap.0 * overlap.0 + overlap.1 * overlap.1
+}
+
+/// N x M fidelity kernel K[i][j] = |<psi_i|phi_j>|^2 as a float64 numpy array,
+/// between `feature_circuits` and `other_circuits` (the Gram matrix of
+/// `feature_circuits` itself when omitted)
+#[pyfunction]
+#[pyo3(signature = (feature_circuits, other_circuits=None))]
+pub fn kernel_matrix<'py>(py: Python<'py>, feature_circuits: Vec<QuantumCircuit>, other_circuits: Option<Vec<QuantumCircuit>>)
+    -> PyResult<&'py PyArray2<f64>> {
+    let Some(qubits) = feature_circuits.first().map(|c| c.qubits) else {
+        return Err(pyo3::exceptions::PyValueError::new_err("kernel_matrix needs at least one feature circuit"));
+    };
+    // Import first so a missing numpy raises ImportError instead of panicking
+    py.import("numpy")?;
+    let (rows, cols, values) = py.allow_threads(|| -> PyResult<(usize, usize, Vec<f64>)> {
+        let left = states(&feature_circuits, qubits)?;
+        match &other_circuits {
+            Some(other) => {
+                let right = states(other, qubits)?;
+                let values = (0..left.len() * right.len()).into_par_iter()
+                    .map(|k| fidelity(&left[k / right.len()], &right[k % right.len()]))
+                    .collect();
+                Ok((left.len(), right.len(), values))
+            }
+            None => {
+                let n = left.len();
+                let pairs: Vec<(usize, usize)> = (0..n).flat_map(|i| (i..n).map(move |j| (i, j))).collect();
+                let upper: Vec<f64> = pairs.par_iter().map(|&(i, j)| fidelity(&left[i], &left[j])).collect();
+                let mut values = vec![0.0; n * n];
+                for (&(i, j), &v) in pairs.iter().zip(&upper) {
+                    values[i * n + j] = v;
+                    values[j * n + i] = v;
+                }
+                Ok((n, n, values))
+            }
+        }
+    })?;
+    values.to_pyarray(py).reshape([rows, cols])
+}
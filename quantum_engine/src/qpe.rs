@@ -0,0 +1,81 @@
+//! Quantum phase estimation around a user-supplied unitary circuit.
+//!
+//! The estimation circuit puts the counting register on qubits 0..m (qubit 0
+//! least significant) and the unitary's qubits above it. Counting qubit k
+//! controls U^(2^k), applied as 2^k repetitions of the controlled gate list, and
+//! the inverse QFT leaves y ~ 2^m phi in the counting register for an
+//! eigenstate with eigenvalue e^(2 pi i phi).
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+
+use crate::{dynamic, mps, Gate, GateType, QuantumCircuit};
+
+pub fn build(unitary: &QuantumCircuit, precision: usize) -> PyResult<QuantumCircuit> {
+    if precision == 0 || precision >= usize::BITS as usize {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "precision_qubits must be between 1 and {}", usize::BITS - 1)));
+    }
+    if dynamic::is_dynamic(&unitary.gates) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Circuits with measurement, reset or c_if cannot be controlled"));
+    }
+    if unitary.initial.is_some() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Circuits with an initialized state cannot be controlled"));
+    }
+    if !unitary.params.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Bind the unitary's parameters before phase estimation"));
+    }
+
+    let mut circuit = QuantumCircuit::new(precision + unitary.qubits, 0);
+    for k in 0..precision {
+        circuit.h(k)?;
+    }
+    let body: Vec<Gate> = unitary.gates.iter().filter(|g| !g.is_snapshot()).map(|g| g.shifted(precision)).collect();
+    for k in 0..precision {
+        let step = body.iter().map(|g| controlled(g, k)).collect::<PyResult<Vec<_>>>()?;
+        for _ in 0..1usize << k {
+            circuit.gates.extend(step.iter().cloned());
+        }
+    }
+    circuit.iqft((0..precision).collect(), true)?;
+    Ok(circuit)
+}
+
+/// `gate` applied only when `control` is |1>
+fn controlled(gate: &Gate, control: usize) -> PyResult<Gate> {
+    let with_controls = |mut controls: Vec<usize>, inner: GateType| {
+        controls.push(control);
+        Gate { gate_type: GateType::CONTROLLED(controls, Box::new(inner)), target: gate.target, control: None }
+    };
+    let existing = gate.control.into_iter().collect();
+    Ok(match &gate.gate_type {
+        g if g.is_single_qubit() => with_controls(Vec::new(), g.clone()),
+        GateType::CONTROLLED(controls, inner) => with_controls(controls.clone(), (**inner).clone()),
+        GateType::MCX(controls) => with_controls(controls.clone(), GateType::X),
+        GateType::CNOT => with_controls(existing, GateType::X),
+        GateType::CH => with_controls(existing, GateType::H),
+        GateType::CRX(a) => with_controls(existing, GateType::RX(*a)),
+        GateType::CRY(a) => with_controls(existing, GateType::RY(*a)),
+        GateType::CRZ(a) => with_controls(existing, GateType::RZ(*a)),
+        GateType::CPHASE(a) => with_controls(existing, GateType::P(*a)),
+        _ => {
+            // Two-qubit and dense gates: diag(I, U) with the control as the top index bit
+            let mut targets = gate.qubits();
+            let m = mps::local_matrix(gate, &targets)?;
+            let dim = 1 << targets.len();
+            let mut matrix = vec![Complex32::new(0.0, 0.0); 4 * dim * dim];
+            for r in 0..dim {
+                matrix[r * 2 * dim + r] = Complex32::new(1.0, 0.0);
+                for c in 0..dim {
+                    let e = m[r * dim + c];
+                    matrix[(dim + r) * 2 * dim + dim + c] = Complex32::new(e.re as f32, e.im as f32);
+                }
+            }
+            targets.push(control);
+            Gate { target: targets[0], gate_type: GateType::UNITARY(targets, matrix), control: None }
+        }
+    })
+}
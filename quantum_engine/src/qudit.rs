@@ -0,0 +1,396 @@
+//! d-level (qudit) circuits for `QuantumCircuit.new_qudit`.
+//!
+//! A register of n qudits with d levels each is a vector of d^n amplitudes in
+//! which qudit k is digit k of the base-d index (qudit 0 least significant, as
+//! qubit 0 is bit 0 of a qubit index). The gate set is the Weyl-Heisenberg one:
+//! X|j> = |j + 1 mod d>, Z|j> = w^j |j> with w = e^(2 pi i / d), the Fourier
+//! gate F|j> = d^(-1/2) sum_k w^(jk) |k> in the role of Hadamard, and the
+//! controlled forms SUM|a, b> = |a, b + a mod d> and CZ|a, b> = w^(ab) |a, b>.
+//! Level phases, two-level rotations and arbitrary d x d unitaries cover the
+//! rest. Single-qudit gates are stored as their d x d matrix and applied in one
+//! parallel pass; levels are capped at 10 so a basis state is a digit string.
+
+use num_complex::{Complex32, Complex64};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{errors, linalg, resources, sampling};
+
+/// Most levels per qudit, so every level is one decimal digit
+const MAX_LEVELS: usize = 10;
+
+#[derive(Clone, Debug)]
+enum QuditGate {
+    /// Row-major d x d matrix on one qudit
+    Single { target: usize, matrix: Vec<Complex32>, name: &'static str },
+    /// |a, b> -> |a, b + power * a mod d>
+    Sum { control: usize, target: usize, power: usize },
+    /// |a, b> -> w^(power * a * b) |a, b>
+    Cz { control: usize, target: usize, power: usize },
+}
+
+impl QuditGate {
+    fn name(&self) -> &'static str {
+        match self {
+            QuditGate::Single { name, .. } => name,
+            QuditGate::Sum { .. } => "cx",
+            QuditGate::Cz { .. } => "cz",
+        }
+    }
+}
+
+/// w^k for k = 0..d
+fn roots(d: usize) -> Vec<Complex32> {
+    (0..d)
+        .map(|k| {
+            let w = Complex64::from_polar(1.0, 2.0 * std::f64::consts::PI * k as f64 / d as f64);
+            Complex32::new(w.re as f32, w.im as f32)
+        })
+        .collect()
+}
+
+/// Circuit on qudits of `levels` levels each
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone, Debug)]
+pub struct QuditCircuit {
+    #[pyo3(get)]
+    qudits: usize,
+    #[pyo3(get)]
+    levels: usize,
+    gates: Vec<QuditGate>,
+}
+
+impl QuditCircuit {
+    fn check_qudit(&self, qudit: usize) -> PyResult<()> {
+        if qudit >= self.qudits {
+            return Err(errors::QubitIndexError::new_err(format!(
+                "Qudit index {} out of range for {} qudits", qudit, self.qudits)));
+        }
+        Ok(())
+    }
+
+    fn check_pair(&self, control: usize, target: usize) -> PyResult<()> {
+        self.check_qudit(control)?;
+        self.check_qudit(target)?;
+        if control == target {
+            return Err(errors::InvalidGateError::new_err("Control and target must be different qudits"));
+        }
+        Ok(())
+    }
+
+    /// `power` reduced into 0..d
+    fn exponent(&self, power: i64) -> usize {
+        power.rem_euclid(self.levels as i64) as usize
+    }
+
+    fn single(&mut self, target: usize, matrix: Vec<Complex32>, name: &'static str) -> PyResult<()> {
+        self.check_qudit(target)?;
+        self.gates.push(QuditGate::Single { target, matrix, name });
+        Ok(())
+    }
+
+    /// Matrix of F (or F^dagger), F[k][j] = w^(jk) / sqrt(d)
+    fn fourier(&self, inverse: bool) -> Vec<Complex32> {
+        let d = self.levels;
+        let w = roots(d);
+        let scale = 1.0 / (d as f32).sqrt();
+        (0..d * d)
+            .map(|idx| {
+                let k = (idx / d) * (idx % d) % d;
+                w[if inverse { (d - k) % d } else { k }] * scale
+            })
+            .collect()
+    }
+}
+
+#[pymethods]
+impl QuditCircuit {
+    /// `qudits` qudits of `levels` levels each (2 to 10), all starting in |0>
+    #[new]
+    pub fn new(qudits: usize, levels: usize) -> PyResult<Self> {
+        if !(2..=MAX_LEVELS).contains(&levels) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "A qudit has 2 to {} levels, got {}", MAX_LEVELS, levels)));
+        }
+        Ok(QuditCircuit { qudits, levels, gates: Vec::new() })
+    }
+
+    /// Generalized X^power: |j> -> |j + power mod d>
+    #[pyo3(signature = (qudit, power=1))]
+    pub fn x(&mut self, qudit: usize, power: i64) -> PyResult<()> {
+        let d = self.levels;
+        let shift = self.exponent(power);
+        let matrix = (0..d * d)
+            .map(|idx| if idx / d == (idx % d + shift) % d { Complex32::new(1.0, 0.0) } else { Complex32::new(0.0, 0.0) })
+            .collect();
+        self.single(qudit, matrix, "x")
+    }
+
+    /// Generalized Z^power: |j> -> w^(power * j) |j>
+    #[pyo3(signature = (qudit, power=1))]
+    pub fn z(&mut self, qudit: usize, power: i64) -> PyResult<()> {
+        let d = self.levels;
+        let (w, p) = (roots(d), self.exponent(power));
+        let matrix = (0..d * d)
+            .map(|idx| if idx / d == idx % d { w[p * (idx % d) % d] } else { Complex32::new(0.0, 0.0) })
+            .collect();
+        self.single(qudit, matrix, "z")
+    }
+
+    /// Fourier gate, the d-level Hadamard: |j> -> d^(-1/2) sum_k w^(jk) |k>
+    pub fn h(&mut self, qudit: usize) -> PyResult<()> {
+        let matrix = self.fourier(false);
+        self.single(qudit, matrix, "h")
+    }
+
+    /// Inverse Fourier gate
+    pub fn hdg(&mut self, qudit: usize) -> PyResult<()> {
+        let matrix = self.fourier(true);
+        self.single(qudit, matrix, "hdg")
+    }
+
+    /// Phase e^(i theta) on level `level` of `qudit`
+    pub fn phase(&mut self, qudit: usize, level: usize, theta: f32) -> PyResult<()> {
+        let d = self.levels;
+        if level >= d {
+            return Err(errors::InvalidGateError::new_err(format!("Level {} out of range for d={}", level, d)));
+        }
+        let matrix = (0..d * d)
+            .map(|idx| match (idx / d, idx % d) {
+                (r, c) if r == c && r == level => Complex32::from_polar(1.0, theta),
+                (r, c) if r == c => Complex32::new(1.0, 0.0),
+                _ => Complex32::new(0.0, 0.0),
+            })
+            .collect();
+        self.single(qudit, matrix, "phase")
+    }
+
+    /// Rotation by `theta` about the axis (cos phi, sin phi, 0) in the
+    /// two-level subspace {|level_a>, |level_b>}, leaving the other levels alone
+    #[pyo3(signature = (qudit, level_a, level_b, theta, phi=0.0))]
+    pub fn rotation(&mut self, qudit: usize, level_a: usize, level_b: usize, theta: f32, phi: f32) -> PyResult<()> {
+        let d = self.levels;
+        if level_a >= d || level_b >= d || level_a == level_b {
+            return Err(errors::InvalidGateError::new_err(format!(
+                "Rotation needs two different levels below d={}, got {} and {}", d, level_a, level_b)));
+        }
+        let (c, s) = ((theta / 2.0).cos(), (theta / 2.0).sin());
+        let mut matrix: Vec<Complex32> = (0..d * d)
+            .map(|idx| if idx / d == idx % d { Complex32::new(1.0, 0.0) } else { Complex32::new(0.0, 0.0) })
+            .collect();
+        matrix[level_a * d + level_a] = Complex32::new(c, 0.0);
+        matrix[level_b * d + level_b] = Complex32::new(c, 0.0);
+        matrix[level_a * d + level_b] = Complex32::new(0.0, -s) * Complex32::from_polar(1.0, -phi);
+        matrix[level_b * d + level_a] = Complex32::new(0.0, -s) * Complex32::from_polar(1.0, phi);
+        self.single(qudit, matrix, "rotation")
+    }
+
+    /// Arbitrary d x d unitary of (re, im) entries on one qudit
+    pub fn unitary(&mut self, qudit: usize, matrix: Vec<Vec<(f32, f32)>>) -> PyResult<()> {
+        let d = self.levels;
+        if matrix.len() != d || matrix.iter().any(|row| row.len() != d) {
+            return Err(errors::DimensionMismatchError::new_err(format!("Matrix for a d={} qudit must be {} x {}", d, d, d)));
+        }
+        let flat: Vec<Complex32> = matrix.iter().flatten().map(|&(re, im)| Complex32::new(re, im)).collect();
+        if !linalg::is_unitary(&linalg::to_c64(&flat), d, 1e-4) {
+            return Err(errors::InvalidGateError::new_err("Matrix is not unitary"));
+        }
+        self.single(qudit, flat, "unitary")
+    }
+
+    /// Generalized CNOT (SUM) raised to `power`: |a, b> -> |a, b + power * a mod d>
+    #[pyo3(signature = (control, target, power=1))]
+    pub fn cx(&mut self, control: usize, target: usize, power: i64) -> PyResult<()> {
+        self.check_pair(control, target)?;
+        let power = self.exponent(power);
+        self.gates.push(QuditGate::Sum { control, target, power });
+        Ok(())
+    }
+
+    /// Generalized CZ raised to `power`: |a, b> -> w^(power * a * b) |a, b>
+    #[pyo3(signature = (control, target, power=1))]
+    pub fn cz(&mut self, control: usize, target: usize, power: i64) -> PyResult<()> {
+        self.check_pair(control, target)?;
+        let power = self.exponent(power);
+        self.gates.push(QuditGate::Cz { control, target, power });
+        Ok(())
+    }
+
+    /// Number of instructions by gate name
+    pub fn count_ops(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for gate in &self.gates {
+            *counts.entry(gate.name().to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Run the circuit from |0...0>
+    pub fn execute(&self, py: Python) -> PyResult<QuditState> {
+        let mut state = QuditState::zero(self.qudits, self.levels)?;
+        py.allow_threads(|| {
+            for gate in &self.gates {
+                state.apply(gate);
+            }
+        });
+        Ok(state)
+    }
+
+    fn __len__(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("QuditCircuit(qudits={}, levels={}, gates={})", self.qudits, self.levels, self.gates.len())
+    }
+}
+
+/// Pure state of a qudit register
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone, Debug)]
+pub struct QuditState {
+    #[pyo3(get)]
+    qudits: usize,
+    #[pyo3(get)]
+    levels: usize,
+    state: Vec<Complex32>,
+}
+
+impl QuditState {
+    fn zero(qudits: usize, levels: usize) -> PyResult<Self> {
+        resources::check_qudit_state::<Complex32>(qudits, levels)?;
+        let mut state = vec![Complex32::new(0.0, 0.0); levels.pow(qudits as u32)];
+        state[0] = Complex32::new(1.0, 0.0);
+        Ok(QuditState { qudits, levels, state })
+    }
+
+    fn stride(&self, qudit: usize) -> usize {
+        self.levels.pow(qudit as u32)
+    }
+
+    fn digit(&self, idx: usize, stride: usize) -> usize {
+        idx / stride % self.levels
+    }
+
+    fn apply(&mut self, gate: &QuditGate) {
+        let d = self.levels;
+        match gate {
+            QuditGate::Single { target, matrix, .. } => {
+                let s = self.stride(*target);
+                let state = &self.state;
+                self.state = (0..state.len()).into_par_iter()
+                    .map(|i| {
+                        let row = i / s % d;
+                        let base = i - row * s;
+                        (0..d).map(|k| matrix[row * d + k] * state[base + k * s]).sum()
+                    })
+                    .collect();
+            }
+            QuditGate::Sum { control, target, power } => {
+                let (sc, st) = (self.stride(*control), self.stride(*target));
+                let state = &self.state;
+                self.state = (0..state.len()).into_par_iter()
+                    .map(|i| {
+                        let (a, b) = (i / sc % d, i / st % d);
+                        let source = (b + d - power * a % d) % d;
+                        state[i - b * st + source * st]
+                    })
+                    .collect();
+            }
+            QuditGate::Cz { control, target, power } => {
+                let (sc, st) = (self.stride(*control), self.stride(*target));
+                let w = roots(d);
+                self.state.par_iter_mut().enumerate().for_each(|(i, amp)| {
+                    *amp *= w[power * (i / sc % d) * (i / st % d) % d];
+                });
+            }
+        }
+    }
+
+    /// Index of a digit string (character k is the level of qudit k)
+    fn index(&self, digits: &str) -> PyResult<usize> {
+        if digits.chars().count() != self.qudits {
+            return Err(errors::DimensionMismatchError::new_err(format!(
+                "Expected {} digits, got '{}'", self.qudits, digits)));
+        }
+        digits.chars().rev().try_fold(0, |idx, c| match c.to_digit(10) {
+            Some(level) if (level as usize) < self.levels => Ok(idx * self.levels + level as usize),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "'{}' is not a level of a d={} qudit", c, self.levels))),
+        })
+    }
+
+    fn digits(&self, idx: usize) -> String {
+        (0..self.qudits)
+            .map(|q| char::from_digit(self.digit(idx, self.stride(q)) as u32, 10).unwrap_or('?'))
+            .collect()
+    }
+}
+
+#[pymethods]
+impl QuditState {
+    /// (re, im) amplitude of one basis state given as a digit string, character
+    /// k being the level of qudit k
+    pub fn amplitude(&self, digits: &str) -> PyResult<(f32, f32)> {
+        let c = self.state[self.index(digits)?];
+        Ok((c.re, c.im))
+    }
+
+    /// |amplitude|^2 of one basis state
+    pub fn probability(&self, digits: &str) -> PyResult<f32> {
+        Ok(self.state[self.index(digits)?].norm_sqr())
+    }
+
+    /// Every amplitude as (re, im), indexed with qudit k as base-d digit k
+    pub fn amplitudes(&self) -> Vec<(f32, f32)> {
+        self.state.iter().map(|c| (c.re, c.im)).collect()
+    }
+
+    /// |amplitude|^2 of every basis state, indexed like `amplitudes`
+    pub fn probabilities(&self, py: Python) -> Vec<f32> {
+        py.allow_threads(|| self.state.par_iter().map(|c| c.norm_sqr()).collect())
+    }
+
+    /// Population of each of the d levels of `qudit`
+    pub fn level_populations(&self, py: Python, qudit: usize) -> PyResult<Vec<f32>> {
+        if qudit >= self.qudits {
+            return Err(errors::QubitIndexError::new_err(format!(
+                "Qudit index {} out of range for {} qudits", qudit, self.qudits)));
+        }
+        let s = self.stride(qudit);
+        Ok(py.allow_threads(|| {
+            self.state.par_iter().enumerate()
+                .fold(|| vec![0.0f64; self.levels], |mut acc, (i, c)| {
+                    acc[self.digit(i, s)] += c.norm_sqr() as f64;
+                    acc
+                })
+                .reduce(|| vec![0.0f64; self.levels], |mut a, b| {
+                    a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+                    a
+                })
+                .into_iter()
+                .map(|p| p as f32)
+                .collect()
+        }))
+    }
+
+    /// Sample `shots` basis states as a counts dictionary of digit strings
+    #[pyo3(signature = (shots, seed=None))]
+    pub fn measure(&self, py: Python, shots: usize, seed: Option<u64>) -> HashMap<String, usize> {
+        let counts = py.allow_threads(|| {
+            let probs: Vec<f32> = self.state.par_iter().map(|c| c.norm_sqr()).collect();
+            sampling::sample_counts(&probs, shots, seed)
+        });
+        counts.into_iter().map(|(idx, n)| (self.digits(idx), n)).collect()
+    }
+
+    /// Sum of |amplitude|^2
+    pub fn norm(&self) -> f32 {
+        self.state.par_iter().map(|c| c.norm_sqr()).sum()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("QuditState(qudits={}, levels={})", self.qudits, self.levels)
+    }
+}
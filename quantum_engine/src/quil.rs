@@ -0,0 +1,129 @@
+//! Quil export for QuantumCircuit.
+//!
+//! Standard gates are written by their Quil names, with CONTROLLED and
+//! DAGGER modifiers where Quil has no dedicated gate. U, RXX, RYY and dense
+//! unitaries become DEFGATE matrices declared at the top of the program;
+//! Quil takes the first qubit argument as the most significant bit of a gate
+//! matrix, so those gates list their qubits in reverse. Measurements write
+//! the `ro` register, c_if bodies are skipped with JUMP-UNLESS / JUMP-WHEN,
+//! and snapshots become comments.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+
+use crate::{unitary, Gate, GateType};
+
+/// Quil literal for a matrix entry: 0.5, -1i, 0.5-0.5i
+fn number(c: Complex32) -> String {
+    if c.im == 0.0 {
+        format!("{}", c.re)
+    } else if c.re == 0.0 {
+        format!("{}i", c.im)
+    } else {
+        format!("{}{}{}i", c.re, if c.im < 0.0 { "-" } else { "+" }, c.im.abs())
+    }
+}
+
+/// Serializer state: the program body and the DEFGATE blocks it refers to
+struct Writer {
+    body: Vec<String>,
+    definitions: Vec<String>,
+    labels: usize,
+}
+
+impl Writer {
+    /// Declare a gate from `gate`'s matrix and return its name; the matrix
+    /// index has gate.qubits()[i] as bit i, so callers pass the qubits reversed
+    fn define(&mut self, gate: &Gate) -> PyResult<String> {
+        let matrix = unitary::gate_matrix(gate)?;
+        let dim = 1 << gate.qubits().len();
+        let name = format!("U_{}", self.definitions.len());
+        let mut def = format!("DEFGATE {}:\n", name);
+        for r in 0..dim {
+            let row: Vec<String> = (0..dim).map(|c| number(matrix[r * dim + c])).collect();
+            def.push_str(&format!("    {}\n", row.join(", ")));
+        }
+        self.definitions.push(def);
+        Ok(name)
+    }
+
+    /// Quil form of a single-qubit gate (without its qubit argument)
+    fn single(&mut self, gate_type: &GateType, target: usize) -> PyResult<String> {
+        Ok(match gate_type {
+            GateType::H => "H".to_string(),
+            GateType::X => "X".to_string(),
+            GateType::Y => "Y".to_string(),
+            GateType::Z => "Z".to_string(),
+            GateType::S => "S".to_string(),
+            GateType::T => "T".to_string(),
+            GateType::SDG => "DAGGER S".to_string(),
+            GateType::TDG => "DAGGER T".to_string(),
+            GateType::RX(theta) => format!("RX({})", theta),
+            GateType::RY(theta) => format!("RY({})", theta),
+            GateType::RZ(phi) => format!("RZ({})", phi),
+            GateType::P(lambda) => format!("PHASE({})", lambda),
+            other => self.define(&Gate { gate_type: other.clone(), target, control: None })?,
+        })
+    }
+
+    fn push(&mut self, gate: &Gate) -> PyResult<()> {
+        let t = gate.target;
+        let controlled = |this: &mut Self, controls: &[usize], inner: &GateType| -> PyResult<String> {
+            let args: Vec<String> = controls.iter().chain([&t]).map(|q| q.to_string()).collect();
+            Ok(format!("{}{} {}", "CONTROLLED ".repeat(controls.len()), this.single(inner, t)?, args.join(" ")))
+        };
+        let line = match &gate.gate_type {
+            GateType::CNOT => format!("CNOT {} {}", gate.control.unwrap(), t),
+            GateType::CPHASE(phi) => format!("CPHASE({}) {} {}", phi, gate.control.unwrap(), t),
+            GateType::CRX(theta) => controlled(self, &[gate.control.unwrap()], &GateType::RX(*theta))?,
+            GateType::CRY(theta) => controlled(self, &[gate.control.unwrap()], &GateType::RY(*theta))?,
+            GateType::CRZ(phi) => controlled(self, &[gate.control.unwrap()], &GateType::RZ(*phi))?,
+            GateType::CH => controlled(self, &[gate.control.unwrap()], &GateType::H)?,
+            GateType::MCX(controls) if controls.len() == 2 => format!("CCNOT {} {} {}", controls[0], controls[1], t),
+            GateType::MCX(controls) => controlled(self, controls, &GateType::X)?,
+            GateType::CONTROLLED(controls, inner) => controlled(self, controls, inner)?,
+            GateType::SWAP(a) => format!("SWAP {} {}", a, t),
+            GateType::ISWAP(a) => format!("ISWAP {} {}", a, t),
+            GateType::RXX(..) | GateType::RYY(..) | GateType::RZZ(..) | GateType::UNITARY(..) => {
+                let args: Vec<String> = gate.qubits().iter().rev().map(|q| q.to_string()).collect();
+                format!("{} {}", self.define(gate)?, args.join(" "))
+            }
+            GateType::MEASURE(bit) => format!("MEASURE {} ro[{}]", t, bit),
+            GateType::RESET => format!("RESET {}", t),
+            GateType::IF(bit, value, body) => {
+                let label = format!("@skip_{}", self.labels);
+                self.labels += 1;
+                self.body.push(format!("{} {} ro[{}]", if *value { "JUMP-UNLESS" } else { "JUMP-WHEN" }, label, bit));
+                for inner in body {
+                    self.push(inner)?;
+                }
+                format!("LABEL {}", label)
+            }
+            GateType::SNAPSHOT(label) => format!("# snapshot {:?}", label),
+            single => format!("{} {}", self.single(single, t)?, t),
+        };
+        self.body.push(line);
+        Ok(())
+    }
+}
+
+/// Serialize a gate list as a Quil program
+pub fn emit(clbits: usize, gates: &[Gate]) -> PyResult<String> {
+    let mut writer = Writer { body: Vec::new(), definitions: Vec::new(), labels: 0 };
+    for gate in gates {
+        writer.push(gate)?;
+    }
+    let mut out = String::new();
+    if clbits > 0 {
+        out.push_str(&format!("DECLARE ro BIT[{}]\n", clbits));
+    }
+    for def in &writer.definitions {
+        out.push_str(def);
+        out.push('\n');
+    }
+    for line in &writer.body {
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
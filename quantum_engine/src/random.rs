@@ -0,0 +1,67 @@
+//! Layered random circuits for `QuantumCircuit.random`.
+//!
+//! Each of `depth` layers puts a gate drawn uniformly from the single-qubit
+//! part of the gate set on every qubit, with every angle uniform in [0, 2pi),
+//! then a gate drawn from the two-qubit part on a brickwork of neighbouring
+//! pairs: (0, 1), (2, 3), ... in even layers and (1, 2), (3, 4), ... in odd
+//! ones, so correlations reach across n qubits in about n layers.
+
+use pyo3::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+use crate::{introspect, rng, QuantumCircuit};
+
+/// Gate set when none is given: Euler-style rotations and a CNOT brickwork
+pub const DEFAULT_GATE_SET: [&str; 4] = ["rx", "ry", "rz", "cnot"];
+
+/// (qubits, angles) of the gates a random circuit can draw
+fn arity(name: &str) -> Option<(usize, usize)> {
+    Some(match name {
+        "h" | "x" | "y" | "z" | "s" | "t" | "sdg" | "tdg" => (1, 0),
+        "rx" | "ry" | "rz" | "p" => (1, 1),
+        "u" => (1, 3),
+        "cnot" | "cx" | "ch" | "swap" | "iswap" => (2, 0),
+        "crx" | "cry" | "crz" | "cphase" | "rxx" | "ryy" | "rzz" => (2, 1),
+        _ => return None,
+    })
+}
+
+pub fn build(qubits: usize, depth: usize, seed: Option<u64>, gate_set: &[String]) -> PyResult<QuantumCircuit> {
+    if gate_set.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err("gate_set must name at least one gate"));
+    }
+    let mut one_qubit = Vec::new();
+    let mut two_qubit = Vec::new();
+    for name in gate_set {
+        let name = name.to_ascii_lowercase();
+        match arity(&name) {
+            Some((1, angles)) => one_qubit.push((name, angles)),
+            Some((_, angles)) => two_qubit.push((name, angles)),
+            None => return Err(crate::errors::InvalidGateError::new_err(format!(
+                "Gate '{}' cannot be drawn in a random circuit (expected single-qubit gates h, x, y, z, s, t, sdg, \
+                 tdg, rx, ry, rz, p, u and two-qubit gates cnot, cx, ch, swap, iswap, crx, cry, crz, cphase, rxx, ryy, rzz)",
+                name))),
+        }
+    }
+
+    let mut rng = rng::seeded(seed);
+    let mut circuit = QuantumCircuit::new(qubits, 0);
+    for layer in 0..depth {
+        if !one_qubit.is_empty() {
+            for q in 0..qubits {
+                let (name, angles) = &one_qubit[rng.gen_range(0..one_qubit.len())];
+                let params = (0..*angles).map(|_| rng.gen::<f32>() * TAU).collect();
+                circuit.push(introspect::from_description(&(name.clone(), vec![q], params), qubits)?)?;
+            }
+        }
+        if !two_qubit.is_empty() {
+            for q in (layer % 2..qubits.saturating_sub(1)).step_by(2) {
+                let (name, angles) = &two_qubit[rng.gen_range(0..two_qubit.len())];
+                let params = (0..*angles).map(|_| rng.gen::<f32>() * TAU).collect();
+                circuit.push(introspect::from_description(&(name.clone(), vec![q, q + 1], params), qubits)?)?;
+            }
+        }
+    }
+    Ok(circuit)
+}
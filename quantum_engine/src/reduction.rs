@@ -0,0 +1,192 @@
+//! Subsystem reductions (partial traces) shared by QuantumState and DensityMatrix.
+//!
+//! Reduced matrices are flattened row-major with bit k of the reduced index
+//! corresponding to qubit `keep[k]` of the parent register.
+
+use num_complex::{Complex32, Complex64};
+use rayon::prelude::*;
+
+use crate::linalg;
+
+/// Map each index of a `qubits.len()`-qubit register onto the parent register positions
+pub fn scatter_indices(qubits: &[usize]) -> Vec<usize> {
+    (0..1usize << qubits.len())
+        .map(|m| {
+            qubits.iter().enumerate()
+                .filter(|(k, _)| (m >> k) & 1 == 1)
+                .fold(0, |acc, (_, &q)| acc | (1 << q))
+        })
+        .collect()
+}
+
+/// Qubits of an `n`-qubit register not present in `keep`
+pub fn complement(keep: &[usize], n: usize) -> Vec<usize> {
+    (0..n).filter(|q| !keep.contains(q)).collect()
+}
+
+/// rho_keep = Tr_rest |psi><psi|
+pub fn reduce_pure_state(state: &[Complex32], n: usize, keep: &[usize]) -> Vec<Complex32> {
+    let keep_idx = scatter_indices(keep);
+    let trace_idx = scatter_indices(&complement(keep, n));
+    let dim = keep_idx.len();
+
+    let mut out = vec![Complex32::new(0.0, 0.0); dim * dim];
+    out.par_iter_mut().enumerate().for_each(|(idx, val)| {
+        let row = keep_idx[idx / dim];
+        let col = keep_idx[idx % dim];
+        *val = trace_idx.iter()
+            .map(|&e| state[row | e] * state[col | e].conj())
+            .sum();
+    });
+    out
+}
+
+/// rho_keep = Tr_rest rho for a flattened `2^n x 2^n` density matrix
+pub fn partial_trace(matrix: &[Complex32], n: usize, keep: &[usize]) -> Vec<Complex32> {
+    let full_dim = 1 << n;
+    let keep_idx = scatter_indices(keep);
+    let trace_idx = scatter_indices(&complement(keep, n));
+    let dim = keep_idx.len();
+
+    let mut out = vec![Complex32::new(0.0, 0.0); dim * dim];
+    out.par_iter_mut().enumerate().for_each(|(idx, val)| {
+        let row = keep_idx[idx / dim];
+        let col = keep_idx[idx % dim];
+        *val = trace_idx.iter()
+            .map(|&e| matrix[(row | e) * full_dim + (col | e)])
+            .sum();
+    });
+    out
+}
+
+/// Marginal distribution of the `keep` qubits, given the probability `p(i)` of
+/// each of the 2^n basis states
+pub fn marginal_probabilities(n: usize, keep: &[usize], p: impl Fn(usize) -> f32 + Sync) -> Vec<f32> {
+    let keep_idx = scatter_indices(keep);
+    let trace_idx = scatter_indices(&complement(keep, n));
+    keep_idx.par_iter()
+        .map(|&k| trace_idx.par_iter().map(|&e| p(k | e)).sum())
+        .collect()
+}
+
+/// <Z_q> for every qubit and <Z_a Z_b> for every pair (flattened n x n,
+/// symmetric with a unit diagonal) in one pass over the probabilities `p(i)` of
+/// the 2^n basis states. With `connected`, <Z_a Z_b> - <Z_a><Z_b> instead.
+pub fn z_correlations(n: usize, p: impl Fn(usize) -> f32 + Sync, connected: bool) -> (Vec<f32>, Vec<f32>) {
+    // Accumulator: the n single-qubit sums, then the pair sums with a < b row-major
+    let pairs = n * n.saturating_sub(1) / 2;
+    let sums = (0..1usize << n).into_par_iter()
+        .fold(|| vec![0.0f64; n + pairs], |mut acc, i| {
+            let weight = p(i) as f64;
+            if weight == 0.0 {
+                return acc;
+            }
+            let (single, pair) = acc.split_at_mut(n);
+            let mut k = 0;
+            for (a, total) in single.iter_mut().enumerate() {
+                *total += if (i >> a) & 1 == 0 { weight } else { -weight };
+                for b in a + 1..n {
+                    pair[k] += if (i >> a ^ i >> b) & 1 == 0 { weight } else { -weight };
+                    k += 1;
+                }
+            }
+            acc
+        })
+        .reduce(|| vec![0.0f64; n + pairs], |mut a, b| {
+            a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+            a
+        });
+
+    let (z, pair) = sums.split_at(n);
+    let mut zz = vec![0.0f32; n * n];
+    let mut k = 0;
+    for a in 0..n {
+        zz[a * n + a] = if connected { (1.0 - z[a] * z[a]) as f32 } else { 1.0 };
+        for b in a + 1..n {
+            let value = if connected { pair[k] - z[a] * z[b] } else { pair[k] } as f32;
+            zz[a * n + b] = value;
+            zz[b * n + a] = value;
+            k += 1;
+        }
+    }
+    (z.iter().map(|&v| v as f32).collect(), zz)
+}
+
+/// Von Neumann entropy S = -Tr(rho log2 rho) of a flattened `dim x dim` density matrix
+pub fn von_neumann_entropy(matrix: &[Complex32], dim: usize) -> f32 {
+    von_neumann_entropy_c64(&linalg::to_c64(matrix), dim) as f32
+}
+
+/// Double-precision form of `von_neumann_entropy`
+pub fn von_neumann_entropy_c64(matrix: &[Complex64], dim: usize) -> f64 {
+    let entropy: f64 = linalg::eigvalsh(matrix, dim)
+        .into_iter()
+        .filter(|&l| l > 1e-12)
+        .map(|l| -l * l.log2())
+        .sum();
+    entropy.max(0.0)
+}
+
+/// I(A:B) = S(A) + S(B) - S(AB) from the joint reduced state rho_AB,
+/// where the first `n_a` qubits of rho_AB form subsystem A
+pub fn mutual_information(rho_ab: &[Complex32], n_a: usize, n_b: usize) -> f32 {
+    let a: Vec<usize> = (0..n_a).collect();
+    let b: Vec<usize> = (n_a..n_a + n_b).collect();
+    let rho_a = partial_trace(rho_ab, n_a + n_b, &a);
+    let rho_b = partial_trace(rho_ab, n_a + n_b, &b);
+
+    let s_a = von_neumann_entropy(&rho_a, 1 << n_a);
+    let s_b = von_neumann_entropy(&rho_b, 1 << n_b);
+    let s_ab = von_neumann_entropy(rho_ab, 1 << (n_a + n_b));
+    (s_a + s_b - s_ab).max(0.0)
+}
+
+/// Wootters concurrence of a two-qubit density matrix (flattened 4 x 4)
+/// C = max(0, l1 - l2 - l3 - l4), l_i the decreasing eigenvalues of sqrt(sqrt(rho) rho~ sqrt(rho))
+pub fn concurrence(rho: &[Complex32]) -> f32 {
+    let rho = linalg::to_c64(rho);
+    // Spin flip: rho~ = (Y x Y) rho* (Y x Y)
+    let sign = [-1.0, 1.0, 1.0, -1.0];
+    let mut rho_tilde = vec![Complex64::new(0.0, 0.0); 16];
+    for i in 0..4 {
+        for j in 0..4 {
+            rho_tilde[i * 4 + j] = rho[(3 - i) * 4 + (3 - j)].conj() * (sign[i] * sign[j]);
+        }
+    }
+
+    let sqrt_rho = linalg::sqrtm_psd(&rho, 4);
+    let inner = linalg::matmul(&linalg::matmul(&sqrt_rho, &rho_tilde, 4), &sqrt_rho, 4);
+    let mut lambdas: Vec<f64> = linalg::eigvalsh(&inner, 4)
+        .into_iter()
+        .map(|l| l.max(0.0).sqrt())
+        .collect();
+    lambdas.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+    (lambdas[0] - lambdas[1] - lambdas[2] - lambdas[3]).max(0.0) as f32
+}
+
+/// Schmidt decomposition |psi> = sum_i s_i |a_i>|b_i> across `partition` and
+/// the remaining qubits, from the SVD of the 2^|A| x 2^|B| amplitude matrix.
+/// Returns the coefficients in decreasing order with the terms of
+/// s_i^2 <= 1e-12 dropped, and the basis vectors as rows (bit k of a row
+/// index is qubit partition[k], resp. qubit k of the complement).
+pub fn schmidt_decomposition(state: &[Complex32], n: usize, partition: &[usize]) -> (Vec<f32>, Vec<Vec<Complex32>>, Vec<Vec<Complex32>>) {
+    let rows_idx = scatter_indices(partition);
+    let cols_idx = scatter_indices(&complement(partition, n));
+    let (rows, cols) = (rows_idx.len(), cols_idx.len());
+    let matrix: Vec<Complex64> = (0..rows * cols)
+        .into_par_iter()
+        .map(|idx| {
+            let a = state[rows_idx[idx / cols] | cols_idx[idx % cols]];
+            Complex64::new(a.re as f64, a.im as f64)
+        })
+        .collect();
+
+    let (u, s, vh) = linalg::svd(&matrix, rows, cols);
+    let k = rows.min(cols);
+    let rank = s.iter().take_while(|&&s| s * s > 1e-12).count();
+    let c32 = |c: &Complex64| Complex32::new(c.re as f32, c.im as f32);
+    let basis_a = (0..rank).map(|i| (0..rows).map(|r| c32(&u[r * k + i])).collect()).collect();
+    let basis_b = (0..rank).map(|i| vh[i * cols..(i + 1) * cols].iter().map(c32).collect()).collect();
+    (s[..rank].iter().map(|&s| s as f32).collect(), basis_a, basis_b)
+}
@@ -0,0 +1,105 @@
+//! Thread and memory limits for shared machines.
+//!
+//! `set_num_threads` sizes rayon's global pool, which every parallel kernel
+//! runs on; rayon builds that pool on first use, so the call has to come
+//! before anything is simulated (RAYON_NUM_THREADS does the same from the
+//! environment). The memory guard checks the size of a state vector, qudit
+//! state or density matrix before it is allocated and raises MemoryError with
+//! the bytes it would need, rather than letting the allocator abort the process
+//! or the kernel's OOM killer end it. The limit is `set_max_memory`'s, or the
+//! machine's physical memory when none is set.
+
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// Byte limit set by `set_max_memory`; 0 when unset
+static MAX_MEMORY: AtomicUsize = AtomicUsize::new(0);
+
+/// MemTotal from /proc/meminfo, None where it is unavailable
+fn physical_memory() -> Option<usize> {
+    static TOTAL: OnceLock<Option<usize>> = OnceLock::new();
+    *TOTAL.get_or_init(|| {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+        let kib: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+        kib.checked_mul(1024)
+    })
+}
+
+/// Bytes a single buffer may take
+fn limit() -> Option<usize> {
+    match MAX_MEMORY.load(Ordering::Relaxed) {
+        0 => physical_memory(),
+        bytes => Some(bytes),
+    }
+}
+
+/// Fail unless `bytes` (None when it overflows) fit the limit
+fn check_bytes(bytes: Option<usize>, what: &str) -> PyResult<()> {
+    let Some(bytes) = bytes else {
+        return Err(pyo3::exceptions::PyMemoryError::new_err(format!(
+            "A {} needs more than {} bytes", what, usize::MAX)));
+    };
+    match limit() {
+        Some(limit) if bytes > limit => Err(pyo3::exceptions::PyMemoryError::new_err(format!(
+            "A {} needs {} bytes, over the {}-byte limit (see set_max_memory)", what, bytes, limit))),
+        _ => Ok(()),
+    }
+}
+
+/// Fail unless 2^(qubits * exponent) elements of `element` bytes fit the limit
+fn check(qubits: usize, exponent: u32, element: usize, what: &str) -> PyResult<()> {
+    let bytes = u32::try_from(qubits).ok()
+        .and_then(|q| q.checked_mul(exponent))
+        .and_then(|bits| 1usize.checked_shl(bits))
+        .and_then(|len| len.checked_mul(element));
+    check_bytes(bytes, &format!("{}-qubit {}", qubits, what))
+}
+
+/// Fail unless a `qubits`-qubit state vector of T amplitudes fits the limit
+pub fn check_state<T>(qubits: usize) -> PyResult<()> {
+    check(qubits, 1, std::mem::size_of::<T>(), "state vector")
+}
+
+/// Fail unless a `qubits`-qubit density matrix of T entries fits the limit
+pub fn check_density<T>(qubits: usize) -> PyResult<()> {
+    check(qubits, 2, std::mem::size_of::<T>(), "density matrix")
+}
+
+/// Fail unless a state vector of `qudits` `levels`-level qudits, d^n
+/// amplitudes of T, fits the limit
+pub fn check_qudit_state<T>(qudits: usize, levels: usize) -> PyResult<()> {
+    let bytes = u32::try_from(qudits).ok()
+        .and_then(|n| levels.checked_pow(n))
+        .and_then(|len| len.checked_mul(std::mem::size_of::<T>()));
+    check_bytes(bytes, &format!("{}-qudit (d={}) state vector", qudits, levels))
+}
+
+/// Run every parallel kernel on `n` threads. Must be called before the first
+/// simulation, since rayon's pool cannot be resized once built.
+#[pyfunction]
+pub fn set_num_threads(n: usize) -> PyResult<()> {
+    if n == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("num_threads must be positive"));
+    }
+    rayon::ThreadPoolBuilder::new().num_threads(n).build_global().map_err(|_| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!(
+            "The thread pool is already running on {} threads; call set_num_threads before the first simulation \
+             or set RAYON_NUM_THREADS", rayon::current_num_threads()))
+    })
+}
+
+/// Largest state vector or density matrix, in bytes, that may be allocated;
+/// set_max_memory(None) returns to the machine's physical memory. Returns the
+/// limit now in force (None when it cannot be determined).
+#[pyfunction]
+#[pyo3(signature = (max_bytes))]
+pub fn set_max_memory(max_bytes: Option<usize>) -> PyResult<Option<usize>> {
+    if max_bytes == Some(0) {
+        return Err(pyo3::exceptions::PyValueError::new_err("max_bytes must be positive"));
+    }
+    MAX_MEMORY.store(max_bytes.unwrap_or(0), Ordering::Relaxed);
+    Ok(limit())
+}
+
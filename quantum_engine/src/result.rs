@@ -0,0 +1,199 @@
+//! `QuantumCircuit.execute_detailed`: one execution bundled with what is known
+//! about it.
+//!
+//! The final state and the snapshot records come from a single pass over the
+//! gates (fused at the requested level when noiseless); counts are sampled from
+//! that state unless the circuit is dynamic or the noise model acts on
+//! sampling, in which case they come from `run`'s per-shot path. A trace, when
+//! asked for, is a separate unfused run as in `execute_traced`. The elapsed
+//! time covers all of it.
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::bitorder::BitOrder;
+use crate::noise::NoiseModel;
+use crate::{dynamic, introspect, pauli, rz_phase, trace, transpile, DensityMatrix, GateType, QuantumCircuit, QuantumState, Snapshot};
+
+/// Outcome of `QuantumCircuit.execute_detailed`
+#[pyclass(module = "quantum_engine")]
+pub struct ExecutionResult {
+    /// Final QuantumState, or DensityMatrix when a noise model was given
+    #[pyo3(get)]
+    state: PyObject,
+    /// Wall-clock seconds for the whole call
+    #[pyo3(get)]
+    elapsed_s: f64,
+    /// Instructions of the circuit as written, by gate name
+    #[pyo3(get)]
+    gate_counts: HashMap<String, usize>,
+    /// Instructions actually run after the optimizations
+    #[pyo3(get)]
+    executed_gates: usize,
+    /// Optimization passes that ran before execution
+    #[pyo3(get)]
+    optimizations: Vec<String>,
+    /// Sampled counts, None when no shots were requested
+    #[pyo3(get)]
+    counts: Option<HashMap<String, usize>>,
+    /// (label, per-qubit (<X>, <Y>, <Z>)) at each snapshot, in circuit order
+    #[pyo3(get)]
+    snapshots: Vec<Snapshot>,
+    /// Gate counts at which the trace was taken (empty without a trace)
+    #[pyo3(get)]
+    trace_steps: Vec<usize>,
+    /// One series per traced observable, as `execute_traced` returns them
+    #[pyo3(get)]
+    trace: Vec<PyObject>,
+}
+
+#[pymethods]
+impl ExecutionResult {
+    /// Total number of instructions in the circuit as written
+    #[getter]
+    pub fn gates(&self) -> usize {
+        self.gate_counts.values().sum()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ExecutionResult(gates={}, executed_gates={}, elapsed_s={:.6}, shots={}, snapshots={}, traced={})",
+            self.gates(), self.executed_gates, self.elapsed_s,
+            self.counts.as_ref().map_or(0, |c| c.values().sum()), self.snapshots.len(), self.trace.len())
+    }
+}
+
+/// State left by `evolve`
+enum Final {
+    Pure(QuantumState),
+    Mixed(DensityMatrix),
+}
+
+/// (<X>, <Y>, <Z>) of each qubit of a density matrix
+fn bloch_vectors(dm: &DensityMatrix) -> Vec<(f32, f32, f32)> {
+    let dim = 1 << dm.qubits;
+    (0..dm.qubits)
+        .map(|q| {
+            let m = 1 << q;
+            let e = |x: usize, z: usize| pauli::expectation_density(&dm.matrix, dim, pauli::PauliMasks::new(x, z));
+            (e(m, 0), e(m, m), e(0, m))
+        })
+        .collect()
+}
+
+/// Final state and snapshots of one pass over `circuit`, with the
+/// optimizations applied and the number of instructions run
+fn evolve(py: Python, circuit: &QuantumCircuit, seed: Option<u64>, transpile_level: usize, model: Option<&NoiseModel>)
+    -> PyResult<(Final, Vec<Snapshot>, Vec<String>, usize)> {
+    let source = circuit.bound_gates()?;
+    let mut snapshots = Vec::new();
+    if let Some(model) = model.filter(|m| !m.is_ideal()) {
+        if dynamic::is_dynamic(source) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Noisy execution does not support measurement, reset, c_if or restore"));
+        }
+        let dm = py.allow_threads(|| -> PyResult<DensityMatrix> {
+            let mut dm = circuit.initial_density()?;
+            for gate in source {
+                if let GateType::SNAPSHOT(label) = &gate.gate_type {
+                    snapshots.push((label.clone(), bloch_vectors(&dm)));
+                }
+                dm.apply_unitary(gate)?;
+                model.apply_after(gate, &mut dm)?;
+            }
+            Ok(dm)
+        })?;
+        return Ok((Final::Mixed(dm), snapshots, Vec::new(), source.len()));
+    }
+
+    let gates = transpile::fuse(source, transpile_level);
+    let mut optimizations = Vec::new();
+    if transpile_level >= 1 {
+        optimizations.push("single-qubit fusion".to_string());
+    }
+    if transpile_level >= 2 {
+        optimizations.push("controlled-phase merging".to_string());
+    }
+    let state = py.allow_threads(|| {
+        let mut state = circuit.initial_state()?;
+        let mut creg = dynamic::ClassicalRegister::new(circuit.clbits, seed);
+        let dynamic = dynamic::is_dynamic(&gates);
+        for gate in &gates {
+            if let GateType::SNAPSHOT(label) = &gate.gate_type {
+                snapshots.push((label.clone(), state.bloch_vectors()));
+            }
+            if dynamic {
+                dynamic::run(&mut state.state, circuit.qubits, std::slice::from_ref(gate), &mut creg)?;
+            } else {
+                state.apply(gate)?;
+            }
+        }
+        // As in `simulate`: book the RZ phases fusion or the dynamic path skipped
+        let applied = if dynamic { 0.0 } else { rz_phase(&gates) };
+        state.phase += rz_phase(source) - applied;
+        Ok::<_, PyErr>(state)
+    })?;
+    Ok((Final::Pure(state), snapshots, optimizations, gates.len()))
+}
+
+/// Execute `circuit` once and gather the state, timing, gate counts,
+/// optimizations, optional counts and snapshots, and an optional trace
+#[allow(clippy::too_many_arguments)]
+pub fn execute_detailed(
+    py: Python,
+    circuit: &QuantumCircuit,
+    shots: Option<usize>,
+    seed: Option<u64>,
+    transpile_level: usize,
+    noise_model: Option<&NoiseModel>,
+    observables: Vec<&PyAny>,
+    trace_every: usize,
+    bit_order: &str,
+) -> PyResult<ExecutionResult> {
+    let start = Instant::now();
+    transpile::check_level(transpile_level)?;
+    let bit_order = BitOrder::from_name(bit_order)?;
+    let noisy = noise_model.is_some_and(|m| !m.is_ideal());
+    let probes = observables.into_iter()
+        .map(|o| trace::Probe::extract(o, circuit.qubits))
+        .collect::<PyResult<Vec<_>>>()?;
+    if noisy && !probes.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err("Traces are recorded on noiseless runs only"));
+    }
+
+    let source = circuit.bound_gates()?;
+    let (state, snapshots, optimizations, executed_gates) = evolve(py, circuit, seed, transpile_level, noise_model)?;
+    let counts = match (shots, &state) {
+        (None, _) => None,
+        // A static circuit's final state is the distribution to sample; dynamic
+        // circuits and noisy readout need a fresh run per shot
+        (Some(shots), Final::Pure(state)) if !dynamic::is_dynamic(source) => {
+            Some(py.allow_threads(|| state.sample(shots, seed)))
+        }
+        (Some(shots), _) => Some(py.allow_threads(|| circuit.sample_counts(shots, seed, noise_model))?),
+    }.map(|c| bit_order.counts(c));
+    let state = match state {
+        Final::Pure(state) => state.into_py(py),
+        Final::Mixed(dm) => dm.into_py(py),
+    };
+    let (trace_steps, trace) = if probes.is_empty() {
+        (Vec::new(), Vec::new())
+    } else {
+        let (steps, series) = py.allow_threads(|| {
+            trace::run(&circuit.initial_state()?, circuit.clbits, source, &probes, trace_every, seed)
+        })?;
+        (steps, series.into_iter().map(|s| s.into_py(py)).collect())
+    };
+
+    Ok(ExecutionResult {
+        state,
+        elapsed_s: start.elapsed().as_secs_f64(),
+        gate_counts: introspect::count_ops(source),
+        executed_gates,
+        optimizations,
+        counts,
+        snapshots,
+        trace_steps,
+        trace,
+    })
+}
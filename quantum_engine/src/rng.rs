@@ -0,0 +1,42 @@
+//! Seeds for every stochastic API.
+//!
+//! Sampling, trajectories, mid-circuit measurement and the other random
+//! methods take an optional `seed`. Without one they call `resolve(None)`,
+//! which draws from OS entropy by default, or from a module-wide generator
+//! after `quantum_engine.set_seed(s)`, so a script that seeds the module once
+//! replays call for call. Parallel work never shares a generator: each call
+//! resolves one seed and hands every rayon task its own stream from `derive`,
+//! so results are independent of the thread count and scheduling.
+
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Mutex;
+
+static GLOBAL: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// `seed`, or the next seed from the module generator (entropy when unset)
+pub fn resolve(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| match GLOBAL.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        Some(rng) => rng.gen(),
+        None => rand::random(),
+    })
+}
+
+/// Generator for an optional per-call seed
+pub fn seeded(seed: Option<u64>) -> StdRng {
+    StdRng::seed_from_u64(resolve(seed))
+}
+
+/// Seed of independent stream `index` under `base` (task, shot or chunk number)
+pub fn derive(base: u64, index: u64) -> u64 {
+    base.wrapping_add(index.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// Seed the module-wide generator used by every call made without a `seed`;
+/// set_seed(None) returns to fresh OS entropy
+#[pyfunction]
+#[pyo3(signature = (seed))]
+pub fn set_seed(seed: Option<u64>) {
+    *GLOBAL.lock().unwrap_or_else(|e| e.into_inner()) = seed.map(StdRng::seed_from_u64);
+}
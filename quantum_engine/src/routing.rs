@@ -0,0 +1,272 @@
+//! Qubit routing onto a restricted connectivity graph.
+//!
+//! A CouplingMap lists the physical qubit pairs that support two-qubit gates.
+//! Routing walks the circuit with a layout (virtual qubit -> physical qubit)
+//! and, before each two-qubit gate whose qubits are not adjacent, swaps the
+//! first qubit along a shortest path until it neighbours the second. This is
+//! the greedy strategy of Qiskit's BasicSwap: no lookahead, so the SWAP count
+//! is an upper bound on what a smarter router would need. Swaps inside a c_if
+//! body are undone before the block ends, keeping the layout independent of
+//! the branch taken.
+
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+
+use crate::{errors, Gate, GateType};
+
+/// Undirected connectivity graph over physical qubits 0..n
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone, Debug)]
+pub struct CouplingMap {
+    neighbours: Vec<Vec<usize>>,
+}
+
+#[pymethods]
+impl CouplingMap {
+    /// Map on `num_qubits` qubits with the given (a, b) edges; direction is ignored
+    #[new]
+    pub fn new(num_qubits: usize, edges: Vec<(usize, usize)>) -> PyResult<Self> {
+        let mut neighbours = vec![Vec::new(); num_qubits];
+        for (a, b) in edges {
+            if a >= num_qubits || b >= num_qubits {
+                return Err(errors::QubitIndexError::new_err(format!(
+                    "Edge ({}, {}) is outside a {}-qubit coupling map", a, b, num_qubits)));
+            }
+            if a == b {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!("Edge ({}, {}) is a self-loop", a, b)));
+            }
+            if !neighbours[a].contains(&b) {
+                neighbours[a].push(b);
+                neighbours[b].push(a);
+            }
+        }
+        for list in &mut neighbours {
+            list.sort_unstable();
+        }
+        Ok(CouplingMap { neighbours })
+    }
+
+    /// Qubits 0 - 1 - ... - (n-1)
+    #[staticmethod]
+    pub fn line(num_qubits: usize) -> PyResult<Self> {
+        CouplingMap::new(num_qubits, (1..num_qubits).map(|q| (q - 1, q)).collect())
+    }
+
+    /// A line with qubit n-1 also joined to qubit 0
+    #[staticmethod]
+    pub fn ring(num_qubits: usize) -> PyResult<Self> {
+        let mut edges: Vec<(usize, usize)> = (1..num_qubits).map(|q| (q - 1, q)).collect();
+        if num_qubits > 2 {
+            edges.push((num_qubits - 1, 0));
+        }
+        CouplingMap::new(num_qubits, edges)
+    }
+
+    /// `rows` x `cols` square lattice; qubit r * cols + c sits at row r, column c
+    #[staticmethod]
+    pub fn grid(rows: usize, cols: usize) -> PyResult<Self> {
+        let mut edges = Vec::new();
+        for r in 0..rows {
+            for c in 0..cols {
+                let q = r * cols + c;
+                if c + 1 < cols {
+                    edges.push((q, q + 1));
+                }
+                if r + 1 < rows {
+                    edges.push((q, q + cols));
+                }
+            }
+        }
+        CouplingMap::new(rows * cols, edges)
+    }
+
+    #[getter]
+    pub fn num_qubits(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    /// Every edge once, as (a, b) with a < b, in increasing order
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        self.neighbours.iter().enumerate()
+            .flat_map(|(a, list)| list.iter().filter(move |&&b| b > a).map(move |&b| (a, b)))
+            .collect()
+    }
+
+    pub fn neighbors(&self, qubit: usize) -> PyResult<Vec<usize>> {
+        self.check(qubit)?;
+        Ok(self.neighbours[qubit].clone())
+    }
+
+    pub fn are_connected(&self, a: usize, b: usize) -> PyResult<bool> {
+        self.check(a)?;
+        self.check(b)?;
+        Ok(self.neighbours[a].contains(&b))
+    }
+
+    /// Number of edges on a shortest path from `a` to `b`; None when disconnected
+    pub fn distance(&self, a: usize, b: usize) -> PyResult<Option<usize>> {
+        self.check(a)?;
+        self.check(b)?;
+        Ok(self.path(a, b).map(|p| p.len() - 1))
+    }
+
+    /// True when every qubit can reach every other
+    pub fn is_connected(&self) -> bool {
+        self.neighbours.is_empty() || self.distances_from(0).iter().all(Option::is_some)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CouplingMap({} qubits, {} edges)", self.num_qubits(), self.edges().len())
+    }
+}
+
+impl CouplingMap {
+    fn check(&self, qubit: usize) -> PyResult<()> {
+        if qubit >= self.neighbours.len() {
+            return Err(errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        Ok(())
+    }
+
+    /// BFS distance from `source` to every qubit
+    fn distances_from(&self, source: usize) -> Vec<Option<usize>> {
+        let mut dist = vec![None; self.neighbours.len()];
+        dist[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(q) = queue.pop_front() {
+            for &next in &self.neighbours[q] {
+                if dist[next].is_none() {
+                    dist[next] = Some(dist[q].unwrap() + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Shortest path from `a` to `b`, both included
+    fn path(&self, a: usize, b: usize) -> Option<Vec<usize>> {
+        let dist = self.distances_from(b);
+        dist[a]?;
+        let mut path = vec![a];
+        let mut q = a;
+        while q != b {
+            // Lowest-numbered neighbour one step closer to b
+            q = *self.neighbours[q].iter().find(|&&n| dist[n].is_some_and(|d| Some(d + 1) == dist[q]))?;
+            path.push(q);
+        }
+        Some(path)
+    }
+}
+
+/// Routing state: the physical qubit of each virtual qubit and its inverse
+struct Router<'a> {
+    map: &'a CouplingMap,
+    physical: Vec<usize>,
+    virtual_of: Vec<Option<usize>>,
+    /// Physical pairs swapped so far, in order
+    log: Vec<(usize, usize)>,
+}
+
+impl Router<'_> {
+    fn swap(&mut self, a: usize, b: usize, out: &mut Vec<Gate>) {
+        out.push(Gate { gate_type: GateType::SWAP(a), target: b, control: None });
+        let (va, vb) = (self.virtual_of[a], self.virtual_of[b]);
+        if let Some(v) = va {
+            self.physical[v] = b;
+        }
+        if let Some(v) = vb {
+            self.physical[v] = a;
+        }
+        self.virtual_of.swap(a, b);
+        self.log.push((a, b));
+    }
+
+    /// Swap the first qubit of a pair towards the second until they are adjacent
+    fn bring_together(&mut self, a: usize, b: usize, out: &mut Vec<Gate>) -> PyResult<()> {
+        let path = self.map.path(self.physical[a], self.physical[b]).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Physical qubits {} and {} are not connected in the coupling map", self.physical[a], self.physical[b]))
+        })?;
+        for step in path.windows(2).take(path.len().saturating_sub(2)) {
+            self.swap(step[0], step[1], out);
+        }
+        Ok(())
+    }
+
+    fn route(&mut self, gates: &[Gate], out: &mut Vec<Gate>) -> PyResult<()> {
+        for gate in gates {
+            if let GateType::IF(bit, value, body) = &gate.gate_type {
+                let mark = self.log.len();
+                let mut routed = Vec::new();
+                self.route(body, &mut routed)?;
+                // Undo the block's swaps so both branches leave the same layout
+                let undo: Vec<(usize, usize)> = self.log.drain(mark..).rev().collect();
+                for (a, b) in undo {
+                    self.swap(a, b, &mut routed);
+                }
+                self.log.truncate(mark);
+                out.push(Gate { gate_type: GateType::IF(*bit, *value, routed), ..gate.clone() });
+                continue;
+            }
+            let qubits = gate.qubits();
+            match qubits.len() {
+                0 | 1 => {}
+                2 => {
+                    let (a, b) = (qubits[0], qubits[1]);
+                    if !self.map.neighbours[self.physical[a]].contains(&self.physical[b]) {
+                        self.bring_together(a, b, out)?;
+                    }
+                }
+                k => {
+                    return Err(errors::InvalidGateError::new_err(format!(
+                        "Cannot route the {}-qubit gate {}; decompose the circuit into one- and two-qubit gates first",
+                        k, gate.gate_type.name())));
+                }
+            }
+            let physical = &self.physical;
+            out.push(gate.remapped(|q| physical[q]));
+        }
+        Ok(())
+    }
+}
+
+/// `gates` on `map`'s physical qubits starting from `layout` (virtual qubit v
+/// on physical qubit layout[v]); returns the routed gates, the final layout
+/// and the number of SWAPs inserted
+pub fn route(gates: &[Gate], qubits: usize, map: &CouplingMap, layout: Option<Vec<usize>>) -> PyResult<(Vec<Gate>, Vec<usize>, usize)> {
+    let n = map.num_qubits();
+    if qubits > n {
+        return Err(errors::DimensionMismatchError::new_err(format!(
+            "Circuit has {} qubits but the coupling map only {}", qubits, n)));
+    }
+    let physical = layout.unwrap_or_else(|| (0..qubits).collect());
+    if physical.len() != qubits {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "initial_layout must place all {} qubits (got {})", qubits, physical.len())));
+    }
+    let mut virtual_of = vec![None; n];
+    for (v, &p) in physical.iter().enumerate() {
+        if p >= n {
+            return Err(errors::QubitIndexError::new_err(format!("initial_layout places qubit {} on {}, outside the coupling map", v, p)));
+        }
+        if virtual_of[p].replace(v).is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!("initial_layout uses physical qubit {} twice", p)));
+        }
+    }
+    let mut router = Router { map, physical, virtual_of, log: Vec::new() };
+    let mut out = Vec::with_capacity(gates.len());
+    router.route(gates, &mut out)?;
+    let swaps = count_swaps(&out) - count_swaps(gates);
+    Ok((out, router.physical, swaps))
+}
+
+fn count_swaps(gates: &[Gate]) -> usize {
+    gates.iter()
+        .map(|g| match &g.gate_type {
+            GateType::SWAP(_) => 1,
+            GateType::IF(_, _, body) => count_swaps(body),
+            _ => 0,
+        })
+        .sum()
+}
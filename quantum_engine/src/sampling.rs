@@ -0,0 +1,87 @@
+//! Shot-based sampling from basis-state probability distributions.
+
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::noise::NoiseModel;
+use crate::{format_bitstring, rng};
+
+/// Shots drawn per rayon task; fixed so seeded results don't depend on thread count
+const SHOTS_PER_CHUNK: usize = 1 << 16;
+
+/// Basis states scanned per rayon task by `top_k`
+const SELECT_CHUNK: usize = 1 << 14;
+
+/// Draw `shots` basis-state indices from `probs` by binary search on the cumulative
+/// distribution, returning a map of index -> occurrence count
+pub fn sample_counts(probs: &[f32], shots: usize, seed: Option<u64>) -> HashMap<usize, usize> {
+    let mut cdf = Vec::with_capacity(probs.len());
+    let mut acc = 0.0f64;
+    for &p in probs {
+        acc += p as f64;
+        cdf.push(acc);
+    }
+    let total = acc;
+    let base_seed = rng::resolve(seed);
+    let n_chunks = shots.div_ceil(SHOTS_PER_CHUNK);
+
+    (0..n_chunks).into_par_iter()
+        .map(|chunk| {
+            let chunk_seed = rng::derive(base_seed, chunk as u64);
+            let mut rng = StdRng::seed_from_u64(chunk_seed);
+            let n = SHOTS_PER_CHUNK.min(shots - chunk * SHOTS_PER_CHUNK);
+            let mut counts = HashMap::new();
+            for _ in 0..n {
+                let r = rng.gen::<f64>() * total;
+                let idx = cdf.partition_point(|&c| c <= r).min(probs.len() - 1);
+                *counts.entry(idx).or_insert(0) += 1;
+            }
+            counts
+        })
+        .reduce(HashMap::new, |mut a, b| {
+            for (k, v) in b {
+                *a.entry(k).or_insert(0) += v;
+            }
+            a
+        })
+}
+
+/// Bitstring counts of `shots` draws from `probs` on `n_qubits`, passed through
+/// the readout errors of `model` when one is given
+pub fn readout_counts(probs: &[f32], n_qubits: usize, shots: usize, seed: Option<u64>, model: Option<&NoiseModel>) -> PyResult<HashMap<String, usize>> {
+    let mut counts = sample_counts(probs, shots, seed);
+    if let Some(model) = model {
+        // Decorrelate the readout stream from the sampling stream
+        counts = model.readout(counts, n_qubits, seed.map(|s| rng::derive(s, 1)))?;
+    }
+    Ok(counts.into_iter().map(|(idx, count)| (format_bitstring(idx, n_qubits), count)).collect())
+}
+
+/// The `k` largest of `dim` probabilities as (index, probability), most
+/// probable first with ties going to the lower index. Every chunk keeps only
+/// its own k best by partial selection, so just those candidates are sorted.
+pub fn top_k(dim: usize, k: usize, prob: impl Fn(usize) -> f32 + Sync) -> Vec<(usize, f32)> {
+    if k == 0 {
+        return Vec::new();
+    }
+    let order = |a: &(usize, f32), b: &(usize, f32)| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0));
+    let keep = |mut best: Vec<(usize, f32)>| {
+        if best.len() > k {
+            best.select_nth_unstable_by(k - 1, order);
+            best.truncate(k);
+        }
+        best
+    };
+    let candidates: Vec<(usize, f32)> = (0..dim.div_ceil(SELECT_CHUNK)).into_par_iter()
+        .flat_map_iter(|chunk| {
+            let start = chunk * SELECT_CHUNK;
+            keep((start..(start + SELECT_CHUNK).min(dim)).map(|i| (i, prob(i))).collect())
+        })
+        .collect();
+    let mut best = keep(candidates);
+    best.sort_unstable_by(order);
+    best
+}
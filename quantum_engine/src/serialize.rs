@@ -0,0 +1,311 @@
+//! JSON and compact binary serialization of circuits.
+//!
+//! Both formats carry the same serde document, `{"version": 1, "circuit": {...}}`.
+//! The binary form is a tagged encoding of that document's value tree: LEB128
+//! lengths and integers, 4-byte floats wherever a number survives the round
+//! trip through f32, and object keys interned after their first occurrence.
+//! State vectors and density matrices travel as raw amplitude buffers.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+use crate::{Gate, GateType, QuantumCircuit};
+
+pub const FORMAT_VERSION: u32 = 1;
+const MAGIC: &[u8; 4] = b"QEC\x01";
+const MAX_DEPTH: usize = 128;
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_UINT: u8 = 3;
+const TAG_NEG_INT: u8 = 4;
+const TAG_F32: u8 = 5;
+const TAG_F64: u8 = 6;
+const TAG_STRING: u8 = 7;
+const TAG_ARRAY: u8 = 8;
+const TAG_OBJECT: u8 = 9;
+
+#[derive(Serialize)]
+struct DocumentRef<'a> {
+    version: u32,
+    circuit: &'a QuantumCircuit,
+}
+
+#[derive(Deserialize)]
+struct Document {
+    version: u32,
+    circuit: QuantumCircuit,
+}
+
+fn invalid(message: String) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(message)
+}
+
+fn document(circuit: &QuantumCircuit) -> PyResult<Value> {
+    serde_json::to_value(DocumentRef { version: FORMAT_VERSION, circuit })
+        .map_err(|e| invalid(format!("Could not serialize circuit: {}", e)))
+}
+
+pub fn to_json(circuit: &QuantumCircuit) -> PyResult<String> {
+    // Written directly rather than through a Value so f32 angles print at f32 precision
+    serde_json::to_string(&DocumentRef { version: FORMAT_VERSION, circuit })
+        .map_err(|e| invalid(format!("Could not serialize circuit: {}", e)))
+}
+
+pub fn from_json(src: &str) -> PyResult<QuantumCircuit> {
+    let doc: Document = serde_json::from_str(src).map_err(|e| invalid(format!("Invalid circuit JSON: {}", e)))?;
+    checked(doc)
+}
+
+pub fn to_bytes(circuit: &QuantumCircuit) -> PyResult<Vec<u8>> {
+    let mut out = MAGIC.to_vec();
+    Encoder { out: &mut out, keys: Vec::new() }.value(&document(circuit)?);
+    Ok(out)
+}
+
+pub fn from_bytes(data: &[u8]) -> PyResult<QuantumCircuit> {
+    let body = data.strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| invalid("Not a serialized circuit (bad header)".to_string()))?;
+    let mut decoder = Decoder { data: body, pos: 0, keys: Vec::new() };
+    let value = decoder.value(0)?;
+    if decoder.pos != body.len() {
+        return Err(invalid("Trailing bytes after serialized circuit".to_string()));
+    }
+    let doc: Document = serde_json::from_value(value).map_err(|e| invalid(format!("Invalid circuit data: {}", e)))?;
+    checked(doc)
+}
+
+/// Amplitudes as consecutive little-endian f32 (re, im) pairs
+pub fn buffer_to_bytes(buffer: &[Complex32]) -> Vec<u8> {
+    buffer.iter().flat_map(|c| c.re.to_le_bytes().into_iter().chain(c.im.to_le_bytes())).collect()
+}
+
+/// Inverse of `buffer_to_bytes`, requiring exactly `len` amplitudes
+pub fn buffer_from_bytes(data: &[u8], len: usize) -> PyResult<Vec<Complex32>> {
+    if data.len() != 8 * len {
+        return Err(invalid(format!("Expected {} bytes of amplitudes, got {}", 8 * len, data.len())));
+    }
+    Ok(data.chunks_exact(8)
+        .map(|c| Complex32::new(f32::from_le_bytes(c[..4].try_into().unwrap()), f32::from_le_bytes(c[4..].try_into().unwrap())))
+        .collect())
+}
+
+/// Reject documents from other format versions, and anything the builder
+/// methods would never produce that could make the kernels index out of bounds
+fn checked(doc: Document) -> PyResult<QuantumCircuit> {
+    if doc.version != FORMAT_VERSION {
+        return Err(invalid(format!(
+            "Unsupported circuit format version {} (this build reads version {})", doc.version, FORMAT_VERSION)));
+    }
+    let circuit = doc.circuit;
+    if let Some(amps) = &circuit.initial {
+        if 1usize.checked_shl(circuit.qubits as u32) != Some(amps.len()) {
+            return Err(invalid(format!("Initial state has {} amplitudes for {} qubits", amps.len(), circuit.qubits)));
+        }
+    }
+    check_gates(&circuit.gates)?;
+    for gate in &circuit.gates {
+        circuit.check_gate(gate)?;
+    }
+    circuit.check_clbits(&circuit.gates)?;
+    for r in &circuit.params {
+        let takes_angle = circuit.gates.get(r.gate).is_some_and(|g| g.gate_type.clone().angle_mut().is_some());
+        if !takes_angle {
+            return Err(invalid(format!("Parameter '{}' refers to gate {}, which has no angle", r.name, r.gate)));
+        }
+    }
+    Ok(circuit)
+}
+
+fn check_gates(gates: &[Gate]) -> PyResult<()> {
+    for gate in gates {
+        match &gate.gate_type {
+            GateType::UNITARY(targets, m) => {
+                let expected = 1usize.checked_shl(2 * targets.len() as u32);
+                if targets.is_empty() || expected != Some(m.len()) {
+                    return Err(invalid(format!(
+                        "Unitary on {} qubit(s) has {} matrix entries", targets.len(), m.len())));
+                }
+            }
+            GateType::CONTROLLED(_, inner) if !inner.is_single_qubit() => {
+                return Err(invalid(format!("Controlled {} is not a single-qubit gate", inner.name())));
+            }
+            GateType::IF(_, _, body) => check_gates(body)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+struct Encoder<'a> {
+    out: &'a mut Vec<u8>,
+    keys: Vec<String>,
+}
+
+impl Encoder<'_> {
+    fn varint(&mut self, mut n: u64) {
+        while n >= 0x80 {
+            self.out.push((n as u8) | 0x80);
+            n >>= 7;
+        }
+        self.out.push(n as u8);
+    }
+
+    fn string(&mut self, s: &str) {
+        self.varint(s.len() as u64);
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Key as 1 + its table index once seen, or 0 followed by the literal
+    fn key(&mut self, key: &str) {
+        match self.keys.iter().position(|k| k == key) {
+            Some(i) => self.varint(i as u64 + 1),
+            None => {
+                self.varint(0);
+                self.string(key);
+                self.keys.push(key.to_string());
+            }
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.out.push(TAG_NULL),
+            Value::Bool(false) => self.out.push(TAG_FALSE),
+            Value::Bool(true) => self.out.push(TAG_TRUE),
+            Value::Number(n) => self.number(n),
+            Value::String(s) => {
+                self.out.push(TAG_STRING);
+                self.string(s);
+            }
+            Value::Array(items) => {
+                self.out.push(TAG_ARRAY);
+                self.varint(items.len() as u64);
+                for item in items {
+                    self.value(item);
+                }
+            }
+            Value::Object(fields) => {
+                self.out.push(TAG_OBJECT);
+                self.varint(fields.len() as u64);
+                for (key, item) in fields {
+                    self.key(key);
+                    self.value(item);
+                }
+            }
+        }
+    }
+
+    fn number(&mut self, n: &Number) {
+        if let Some(u) = n.as_u64() {
+            self.out.push(TAG_UINT);
+            self.varint(u);
+        } else if let Some(i) = n.as_i64() {
+            self.out.push(TAG_NEG_INT);
+            self.varint(!(i as u64));
+        } else {
+            let x = n.as_f64().unwrap_or(0.0);
+            if (x as f32) as f64 == x {
+                self.out.push(TAG_F32);
+                self.out.extend_from_slice(&(x as f32).to_le_bytes());
+            } else {
+                self.out.push(TAG_F64);
+                self.out.extend_from_slice(&x.to_le_bytes());
+            }
+        }
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    keys: Vec<String>,
+}
+
+impl Decoder<'_> {
+    fn truncated() -> PyErr {
+        invalid("Serialized circuit is truncated or corrupt".to_string())
+    }
+
+    fn take(&mut self, len: usize) -> PyResult<&[u8]> {
+        let end = self.pos.checked_add(len).filter(|&e| e <= self.data.len()).ok_or_else(Self::truncated)?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> PyResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> PyResult<u64> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            n |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(Self::truncated())
+    }
+
+    fn length(&mut self) -> PyResult<usize> {
+        let n = self.varint()?;
+        // Every element takes at least one byte, which bounds allocations on corrupt input
+        usize::try_from(n).ok().filter(|&n| n <= self.data.len() - self.pos).ok_or_else(Self::truncated)
+    }
+
+    fn string(&mut self) -> PyResult<String> {
+        let len = self.length()?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| Self::truncated())
+    }
+
+    fn key(&mut self) -> PyResult<String> {
+        match self.varint()? {
+            0 => {
+                let key = self.string()?;
+                self.keys.push(key.clone());
+                Ok(key)
+            }
+            i => self.keys.get(i as usize - 1).cloned().ok_or_else(Self::truncated),
+        }
+    }
+
+    fn float(x: f64) -> Value {
+        Number::from_f64(x).map_or(Value::Null, Value::Number)
+    }
+
+    fn value(&mut self, depth: usize) -> PyResult<Value> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("Serialized circuit is nested too deeply".to_string()));
+        }
+        Ok(match self.byte()? {
+            TAG_NULL => Value::Null,
+            TAG_FALSE => Value::Bool(false),
+            TAG_TRUE => Value::Bool(true),
+            TAG_UINT => Value::from(self.varint()?),
+            TAG_NEG_INT => Value::from(!self.varint()? as i64),
+            TAG_F32 => Self::float(f32::from_le_bytes(self.take(4)?.try_into().unwrap()) as f64),
+            TAG_F64 => Self::float(f64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            TAG_STRING => Value::String(self.string()?),
+            TAG_ARRAY => {
+                let len = self.length()?;
+                Value::Array((0..len).map(|_| self.value(depth + 1)).collect::<PyResult<_>>()?)
+            }
+            TAG_OBJECT => {
+                let len = self.length()?;
+                let mut fields = Map::new();
+                for _ in 0..len {
+                    let key = self.key()?;
+                    fields.insert(key, self.value(depth + 1)?);
+                }
+                Value::Object(fields)
+            }
+            _ => return Err(Self::truncated()),
+        })
+    }
+}
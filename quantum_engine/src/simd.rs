@@ -0,0 +1,415 @@
+//! Explicit SIMD for the two inner loops of single-qubit gates: the 2x2
+//! butterfly over amplitude runs and the complex scaling used by diagonal gates.
+//!
+//! Complex numbers are stored as interleaved (re, im) pairs, so a vector of
+//! amplitudes times a broadcast constant a is re(a) * x plus im(a) times x with
+//! each pair swapped, subtracted in the real lanes and added in the imaginary
+//! ones. x86_64 uses AVX2 + FMA (4 f32 or 2 f64 amplitudes per register) when
+//! the CPU reports both at runtime; aarch64 always has NEON (2 f32 or 1 f64).
+//! Other targets, and the tail of every run, take the scalar loop, as does
+//! everything after `set_simd(False)`, which is there to measure the kernels
+//! against (see `benches/simd.rs`) and to rule them out when chasing a
+//! numerical difference.
+
+use num_complex::Complex;
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::kernels::{Matrix2, Real};
+
+/// Shortest amplitude run worth handing to the vector kernels
+pub const MIN_RUN: usize = 4;
+
+/// Cleared by `set_simd(False)`
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turn the explicit SIMD kernels on or off for every later gate (on by
+/// default; off forces the scalar loop). Returns whether vector kernels are
+/// in use, which is False on a CPU without them whatever `enabled` says.
+#[pyfunction]
+pub fn set_simd(enabled: bool) -> bool {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    enabled && available()
+}
+
+fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// (a, b) <- (m00 a + m01 b, m10 a + m11 b) elementwise over two runs of equal length
+pub fn butterfly_scalar<T: Real>(lo: &mut [Complex<T>], hi: &mut [Complex<T>], m: &Matrix2<T>) {
+    for (a, b) in lo.iter_mut().zip(hi.iter_mut()) {
+        let (x, y) = (*a, *b);
+        *a = m[0][0] * x + m[0][1] * y;
+        *b = m[1][0] * x + m[1][1] * y;
+    }
+}
+
+/// values <- factor * values
+pub fn scale_scalar<T: Real>(values: &mut [Complex<T>], factor: Complex<T>) {
+    values.iter_mut().for_each(|v| *v = factor * *v);
+}
+
+pub fn butterfly_f32(lo: &mut [Complex<f32>], hi: &mut [Complex<f32>], m: &Matrix2<f32>) {
+    let done = if enabled() { vector_butterfly_f32(lo, hi, m) } else { 0 };
+    butterfly_scalar(&mut lo[done..], &mut hi[done..], m);
+}
+
+pub fn butterfly_f64(lo: &mut [Complex<f64>], hi: &mut [Complex<f64>], m: &Matrix2<f64>) {
+    let done = if enabled() { vector_butterfly_f64(lo, hi, m) } else { 0 };
+    butterfly_scalar(&mut lo[done..], &mut hi[done..], m);
+}
+
+pub fn scale_f32(values: &mut [Complex<f32>], factor: Complex<f32>) {
+    let done = if enabled() { vector_scale_f32(values, factor) } else { 0 };
+    scale_scalar(&mut values[done..], factor);
+}
+
+pub fn scale_f64(values: &mut [Complex<f64>], factor: Complex<f64>) {
+    let done = if enabled() { vector_scale_f64(values, factor) } else { 0 };
+    scale_scalar(&mut values[done..], factor);
+}
+
+// Each vector_* entry point returns how many leading amplitudes it processed
+
+#[cfg(target_arch = "x86_64")]
+fn has_avx2() -> bool {
+    is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma")
+}
+
+/// Whether this CPU has the vector kernels
+fn available() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    return has_avx2();
+    #[cfg(not(target_arch = "x86_64"))]
+    return cfg!(target_arch = "aarch64");
+}
+
+#[cfg(target_arch = "x86_64")]
+fn vector_butterfly_f32(lo: &mut [Complex<f32>], hi: &mut [Complex<f32>], m: &Matrix2<f32>) -> usize {
+    if !has_avx2() {
+        return 0;
+    }
+    // SAFETY: the CPU supports AVX2 and FMA
+    unsafe { avx2::butterfly_f32(lo, hi, m) }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn vector_butterfly_f64(lo: &mut [Complex<f64>], hi: &mut [Complex<f64>], m: &Matrix2<f64>) -> usize {
+    if !has_avx2() {
+        return 0;
+    }
+    // SAFETY: the CPU supports AVX2 and FMA
+    unsafe { avx2::butterfly_f64(lo, hi, m) }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn vector_scale_f32(values: &mut [Complex<f32>], factor: Complex<f32>) -> usize {
+    if !has_avx2() {
+        return 0;
+    }
+    // SAFETY: the CPU supports AVX2 and FMA
+    unsafe { avx2::scale_f32(values, factor) }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn vector_scale_f64(values: &mut [Complex<f64>], factor: Complex<f64>) -> usize {
+    if !has_avx2() {
+        return 0;
+    }
+    // SAFETY: the CPU supports AVX2 and FMA
+    unsafe { avx2::scale_f64(values, factor) }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn vector_butterfly_f32(lo: &mut [Complex<f32>], hi: &mut [Complex<f32>], m: &Matrix2<f32>) -> usize {
+    // SAFETY: NEON is part of the aarch64 baseline
+    unsafe { neon::butterfly_f32(lo, hi, m) }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn vector_butterfly_f64(lo: &mut [Complex<f64>], hi: &mut [Complex<f64>], m: &Matrix2<f64>) -> usize {
+    // SAFETY: NEON is part of the aarch64 baseline
+    unsafe { neon::butterfly_f64(lo, hi, m) }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn vector_scale_f32(values: &mut [Complex<f32>], factor: Complex<f32>) -> usize {
+    // SAFETY: NEON is part of the aarch64 baseline
+    unsafe { neon::scale_f32(values, factor) }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn vector_scale_f64(values: &mut [Complex<f64>], factor: Complex<f64>) -> usize {
+    // SAFETY: NEON is part of the aarch64 baseline
+    unsafe { neon::scale_f64(values, factor) }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn vector_butterfly_f32(_: &mut [Complex<f32>], _: &mut [Complex<f32>], _: &Matrix2<f32>) -> usize {
+    0
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn vector_butterfly_f64(_: &mut [Complex<f64>], _: &mut [Complex<f64>], _: &Matrix2<f64>) -> usize {
+    0
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn vector_scale_f32(_: &mut [Complex<f32>], _: Complex<f32>) -> usize {
+    0
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn vector_scale_f64(_: &mut [Complex<f64>], _: Complex<f64>) -> usize {
+    0
+}
+
+/// AVX2 + FMA kernels. Complex<T> is #[repr(C)] { re, im }, so a slice of
+/// amplitudes is a flat run of interleaved floats.
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use num_complex::Complex;
+    use std::arch::x86_64::*;
+
+    use crate::kernels::Matrix2;
+
+    /// Broadcast (re, im) of a constant
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn splat_ps(a: Complex<f32>) -> (__m256, __m256) {
+        (_mm256_set1_ps(a.re), _mm256_set1_ps(a.im))
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn splat_pd(a: Complex<f64>) -> (__m256d, __m256d) {
+        (_mm256_set1_pd(a.re), _mm256_set1_pd(a.im))
+    }
+
+    /// a * x for a broadcast constant a and 4 interleaved amplitudes x
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn mul_ps(a: (__m256, __m256), x: __m256) -> __m256 {
+        _mm256_fmaddsub_ps(a.0, x, _mm256_mul_ps(a.1, _mm256_permute_ps(x, 0b1011_0001)))
+    }
+
+    /// a * x for a broadcast constant a and 2 interleaved amplitudes x
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn mul_pd(a: (__m256d, __m256d), x: __m256d) -> __m256d {
+        _mm256_fmaddsub_pd(a.0, x, _mm256_mul_pd(a.1, _mm256_permute_pd(x, 0b0101)))
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn butterfly_f32(lo: &mut [Complex<f32>], hi: &mut [Complex<f32>], m: &Matrix2<f32>) -> usize {
+        let (m00, m01, m10, m11) = (splat_ps(m[0][0]), splat_ps(m[0][1]), splat_ps(m[1][0]), splat_ps(m[1][1]));
+        let len = lo.len().min(hi.len()) / 4 * 4;
+        let (pa, pb) = (lo.as_mut_ptr() as *mut f32, hi.as_mut_ptr() as *mut f32);
+        for i in (0..2 * len).step_by(8) {
+            let a = _mm256_loadu_ps(pa.add(i));
+            let b = _mm256_loadu_ps(pb.add(i));
+            _mm256_storeu_ps(pa.add(i), _mm256_add_ps(mul_ps(m00, a), mul_ps(m01, b)));
+            _mm256_storeu_ps(pb.add(i), _mm256_add_ps(mul_ps(m10, a), mul_ps(m11, b)));
+        }
+        len
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn butterfly_f64(lo: &mut [Complex<f64>], hi: &mut [Complex<f64>], m: &Matrix2<f64>) -> usize {
+        let (m00, m01, m10, m11) = (splat_pd(m[0][0]), splat_pd(m[0][1]), splat_pd(m[1][0]), splat_pd(m[1][1]));
+        let len = lo.len().min(hi.len()) / 2 * 2;
+        let (pa, pb) = (lo.as_mut_ptr() as *mut f64, hi.as_mut_ptr() as *mut f64);
+        for i in (0..2 * len).step_by(4) {
+            let a = _mm256_loadu_pd(pa.add(i));
+            let b = _mm256_loadu_pd(pb.add(i));
+            _mm256_storeu_pd(pa.add(i), _mm256_add_pd(mul_pd(m00, a), mul_pd(m01, b)));
+            _mm256_storeu_pd(pb.add(i), _mm256_add_pd(mul_pd(m10, a), mul_pd(m11, b)));
+        }
+        len
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn scale_f32(values: &mut [Complex<f32>], factor: Complex<f32>) -> usize {
+        let f = splat_ps(factor);
+        let len = values.len() / 4 * 4;
+        let p = values.as_mut_ptr() as *mut f32;
+        for i in (0..2 * len).step_by(8) {
+            _mm256_storeu_ps(p.add(i), mul_ps(f, _mm256_loadu_ps(p.add(i))));
+        }
+        len
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn scale_f64(values: &mut [Complex<f64>], factor: Complex<f64>) -> usize {
+        let f = splat_pd(factor);
+        let len = values.len() / 2 * 2;
+        let p = values.as_mut_ptr() as *mut f64;
+        for i in (0..2 * len).step_by(4) {
+            _mm256_storeu_pd(p.add(i), mul_pd(f, _mm256_loadu_pd(p.add(i))));
+        }
+        len
+    }
+}
+
+/// NEON kernels; the same product with the pair swap done by vrev64q_f32 /
+/// vextq_f64 and the sign folded into the broadcast imaginary part
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use num_complex::Complex;
+    use std::arch::aarch64::*;
+
+    use crate::kernels::Matrix2;
+
+    /// (re, re) and (-im, im) of a constant, for 2 f32 amplitudes
+    #[target_feature(enable = "neon")]
+    unsafe fn splat_f32(a: Complex<f32>) -> (float32x4_t, float32x4_t) {
+        let signed = [-a.im, a.im, -a.im, a.im];
+        (vdupq_n_f32(a.re), vld1q_f32(signed.as_ptr()))
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn splat_f64(a: Complex<f64>) -> (float64x2_t, float64x2_t) {
+        let signed = [-a.im, a.im];
+        (vdupq_n_f64(a.re), vld1q_f64(signed.as_ptr()))
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn mul_f32(a: (float32x4_t, float32x4_t), x: float32x4_t) -> float32x4_t {
+        vfmaq_f32(vmulq_f32(a.0, x), a.1, vrev64q_f32(x))
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn mul_f64(a: (float64x2_t, float64x2_t), x: float64x2_t) -> float64x2_t {
+        vfmaq_f64(vmulq_f64(a.0, x), a.1, vextq_f64::<1>(x, x))
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn butterfly_f32(lo: &mut [Complex<f32>], hi: &mut [Complex<f32>], m: &Matrix2<f32>) -> usize {
+        let (m00, m01, m10, m11) = (splat_f32(m[0][0]), splat_f32(m[0][1]), splat_f32(m[1][0]), splat_f32(m[1][1]));
+        let len = lo.len().min(hi.len()) / 2 * 2;
+        let (pa, pb) = (lo.as_mut_ptr() as *mut f32, hi.as_mut_ptr() as *mut f32);
+        for i in (0..2 * len).step_by(4) {
+            let a = vld1q_f32(pa.add(i));
+            let b = vld1q_f32(pb.add(i));
+            vst1q_f32(pa.add(i), vaddq_f32(mul_f32(m00, a), mul_f32(m01, b)));
+            vst1q_f32(pb.add(i), vaddq_f32(mul_f32(m10, a), mul_f32(m11, b)));
+        }
+        len
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn butterfly_f64(lo: &mut [Complex<f64>], hi: &mut [Complex<f64>], m: &Matrix2<f64>) -> usize {
+        let (m00, m01, m10, m11) = (splat_f64(m[0][0]), splat_f64(m[0][1]), splat_f64(m[1][0]), splat_f64(m[1][1]));
+        let len = lo.len().min(hi.len());
+        let (pa, pb) = (lo.as_mut_ptr() as *mut f64, hi.as_mut_ptr() as *mut f64);
+        for i in (0..2 * len).step_by(2) {
+            let a = vld1q_f64(pa.add(i));
+            let b = vld1q_f64(pb.add(i));
+            vst1q_f64(pa.add(i), vaddq_f64(mul_f64(m00, a), mul_f64(m01, b)));
+            vst1q_f64(pb.add(i), vaddq_f64(mul_f64(m10, a), mul_f64(m11, b)));
+        }
+        len
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn scale_f32(values: &mut [Complex<f32>], factor: Complex<f32>) -> usize {
+        let f = splat_f32(factor);
+        let len = values.len() / 2 * 2;
+        let p = values.as_mut_ptr() as *mut f32;
+        for i in (0..2 * len).step_by(4) {
+            vst1q_f32(p.add(i), mul_f32(f, vld1q_f32(p.add(i))));
+        }
+        len
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn scale_f64(values: &mut [Complex<f64>], factor: Complex<f64>) -> usize {
+        let f = splat_f64(factor);
+        let len = values.len();
+        let p = values.as_mut_ptr() as *mut f64;
+        for i in (0..2 * len).step_by(2) {
+            vst1q_f64(p.add(i), mul_f64(f, vld1q_f64(p.add(i))));
+        }
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn amplitudes<T: Real>(rng: &mut StdRng, len: usize) -> Vec<Complex<T>> {
+        (0..len).map(|_| Complex::new(T::from_f32(rng.gen_range(-1.0..1.0)), T::from_f32(rng.gen_range(-1.0..1.0)))).collect()
+    }
+
+    fn matrix<T: Real>(rng: &mut StdRng) -> Matrix2<T> {
+        let v = amplitudes(rng, 4);
+        [[v[0], v[1]], [v[2], v[3]]]
+    }
+
+    fn assert_close<T: Real>(got: &[Complex<T>], want: &[Complex<T>], len: usize) {
+        for (i, (g, w)) in got.iter().zip(want).enumerate() {
+            assert!((*g - *w).norm() < T::from_f32(1e-5), "run of {}: amplitude {} is {:?} not {:?}", len, i, g, w);
+        }
+    }
+
+    // Run lengths cover every remainder modulo the vector widths (4 and 2
+    // amplitudes on AVX2, 2 and 1 on NEON), so the scalar tails are exercised
+    #[test]
+    fn butterfly_matches_scalar() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for len in 0..=19 {
+            let (lo, hi) = (amplitudes::<f32>(&mut rng, len), amplitudes::<f32>(&mut rng, len));
+            let m = matrix(&mut rng);
+            let (mut a, mut b) = (lo.clone(), hi.clone());
+            let (mut ra, mut rb) = (lo, hi);
+            butterfly_f32(&mut a, &mut b, &m);
+            butterfly_scalar(&mut ra, &mut rb, &m);
+            assert_close(&a, &ra, len);
+            assert_close(&b, &rb, len);
+
+            let (lo, hi) = (amplitudes::<f64>(&mut rng, len), amplitudes::<f64>(&mut rng, len));
+            let m = matrix(&mut rng);
+            let (mut a, mut b) = (lo.clone(), hi.clone());
+            let (mut ra, mut rb) = (lo, hi);
+            butterfly_f64(&mut a, &mut b, &m);
+            butterfly_scalar(&mut ra, &mut rb, &m);
+            assert_close(&a, &ra, len);
+            assert_close(&b, &rb, len);
+        }
+    }
+
+    #[test]
+    fn scale_matches_scalar() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for len in 0..=19 {
+            let values = amplitudes::<f32>(&mut rng, len);
+            let factor = amplitudes::<f32>(&mut rng, 1)[0];
+            let (mut got, mut want) = (values.clone(), values);
+            scale_f32(&mut got, factor);
+            scale_scalar(&mut want, factor);
+            assert_close(&got, &want, len);
+
+            let values = amplitudes::<f64>(&mut rng, len);
+            let factor = amplitudes::<f64>(&mut rng, 1)[0];
+            let (mut got, mut want) = (values.clone(), values);
+            scale_f64(&mut got, factor);
+            scale_scalar(&mut want, factor);
+            assert_close(&got, &want, len);
+        }
+    }
+
+    // The vector entry points alone must stop on a whole number of registers
+    // and leave the rest untouched for the scalar tail
+    #[test]
+    fn vector_kernels_leave_the_tail() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for len in 0..=11 {
+            let (lo, hi) = (amplitudes::<f32>(&mut rng, len), amplitudes::<f32>(&mut rng, len));
+            let (mut a, mut b) = (lo.clone(), hi.clone());
+            let done = vector_butterfly_f32(&mut a, &mut b, &matrix(&mut rng));
+            assert!(done <= len);
+            assert_eq!(&a[done..], &lo[done..]);
+            assert_eq!(&b[done..], &hi[done..]);
+        }
+    }
+}
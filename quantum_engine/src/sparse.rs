@@ -0,0 +1,288 @@
+//! Sparse state-vector backend for `QuantumCircuit.execute(backend="sparse")`.
+//!
+//! Only non-zero amplitudes are stored, in a map from basis index to value, so
+//! circuits that stay on a few basis states (oracles, reversible arithmetic,
+//! X/CNOT/SWAP-heavy constructions) cost memory in proportion to the states
+//! they touch rather than 2^n. A gate groups the stored indices by their bits
+//! outside the gate's qubits, multiplies each group by the gate's local matrix
+//! and keeps results with |amplitude|^2 above CUTOFF; permutation gates leave
+//! the count unchanged. Once more than DENSE_FILL of the 2^n amplitudes are
+//! stored the state switches to a dense vector and the regular kernels.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::bitorder::BitOrder;
+use crate::dynamic::{self, ClassicalRegister};
+use crate::{format_bitstring, kernels, mps, parse_bitstring, reduction, resources, sampling, Gate, GateType, QuantumState};
+
+/// |amplitude|^2 below which an amplitude is dropped
+const CUTOFF: f32 = 1e-12;
+/// Fraction of stored basis states past which the state is densified
+const DENSE_FILL: f64 = 0.125;
+/// Largest register `to_state` will expand to a dense vector
+const MAX_DENSE_QUBITS: usize = 30;
+
+#[derive(Clone)]
+enum Amplitudes {
+    Sparse(HashMap<usize, Complex32>),
+    Dense(Vec<Complex32>),
+}
+
+/// Pure state holding only its non-zero amplitudes (dense once mostly filled)
+#[pyclass]
+#[derive(Clone)]
+pub struct SparseState {
+    #[pyo3(get)]
+    qubits: usize,
+    amplitudes: Amplitudes,
+}
+
+#[pymethods]
+impl SparseState {
+    /// Number of non-zero amplitudes
+    pub fn nnz(&self) -> usize {
+        match &self.amplitudes {
+            Amplitudes::Sparse(map) => map.len(),
+            Amplitudes::Dense(amps) => amps.iter().filter(|c| c.norm_sqr() > CUTOFF).count(),
+        }
+    }
+
+    /// True once the state has switched to dense storage
+    #[getter]
+    pub fn is_dense(&self) -> bool {
+        matches!(self.amplitudes, Amplitudes::Dense(_))
+    }
+
+    /// (re, im) amplitude of one basis state (character i of the bitstring is
+    /// qubit i, or qubit n - 1 - i with bit_order="big")
+    #[pyo3(signature = (bitstring, bit_order="little"))]
+    pub fn amplitude(&self, bitstring: &str, bit_order: &str) -> PyResult<(f32, f32)> {
+        let idx = BitOrder::from_name(bit_order)?.index(parse_bitstring(bitstring, self.qubits)?, self.qubits);
+        let c = self.get(idx);
+        Ok((c.re, c.im))
+    }
+
+    /// |amplitude|^2 of one basis state
+    #[pyo3(signature = (bitstring, bit_order="little"))]
+    pub fn probability(&self, bitstring: &str, bit_order: &str) -> PyResult<f32> {
+        let idx = BitOrder::from_name(bit_order)?.index(parse_bitstring(bitstring, self.qubits)?, self.qubits);
+        Ok(self.get(idx).norm_sqr())
+    }
+
+    /// Sample `shots` computational-basis outcomes as a counts dictionary
+    #[pyo3(signature = (shots, seed=None, bit_order="little"))]
+    pub fn measure(&self, py: Python, shots: usize, seed: Option<u64>, bit_order: &str) -> PyResult<HashMap<String, usize>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        Ok(py.allow_threads(|| {
+            let entries = self.entries();
+            let probs: Vec<f32> = entries.iter().map(|e| e.1.norm_sqr()).collect();
+            sampling::sample_counts(&probs, shots, seed)
+                .into_iter()
+                .map(|(k, count)| (bit_order.bitstring(format_bitstring(entries[k].0, self.qubits)), count))
+                .collect()
+        }))
+    }
+
+    /// Non-zero amplitudes as {bitstring: (re, im)}
+    #[pyo3(signature = (bit_order="little"))]
+    pub fn to_dict(&self, bit_order: &str) -> PyResult<HashMap<String, (f32, f32)>> {
+        let bit_order = BitOrder::from_name(bit_order)?;
+        Ok(self.entries().into_iter()
+            .map(|(idx, c)| (bit_order.bitstring(format_bitstring(idx, self.qubits)), (c.re, c.im)))
+            .collect())
+    }
+
+    /// Dense QuantumState with the same amplitudes
+    pub fn to_state(&self) -> PyResult<QuantumState> {
+        if self.qubits > MAX_DENSE_QUBITS {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "A {}-qubit state is too large to densify (limit {})", self.qubits, MAX_DENSE_QUBITS)));
+        }
+        resources::check_state::<Complex32>(self.qubits)?;
+        let state = match &self.amplitudes {
+            Amplitudes::Dense(amps) => amps.clone(),
+            Amplitudes::Sparse(map) => {
+                let mut amps = vec![Complex32::new(0.0, 0.0); 1 << self.qubits];
+                for (&idx, &c) in map {
+                    amps[idx] = c;
+                }
+                amps
+            }
+        };
+        Ok(QuantumState { qubits: self.qubits, state, phase: 0.0, drift: 0.0 })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SparseState(qubits={}, nnz={}{})", self.qubits, self.nnz(), if self.is_dense() { ", dense" } else { "" })
+    }
+}
+
+impl SparseState {
+    fn from_dense(qubits: usize, amps: &[Complex32]) -> Self {
+        let map = amps.iter().enumerate()
+            .filter(|(_, c)| c.norm_sqr() > CUTOFF)
+            .map(|(i, &c)| (i, c))
+            .collect();
+        let mut state = SparseState { qubits, amplitudes: Amplitudes::Sparse(map) };
+        state.densify_if_full();
+        state
+    }
+
+    fn get(&self, idx: usize) -> Complex32 {
+        match &self.amplitudes {
+            Amplitudes::Sparse(map) => map.get(&idx).copied().unwrap_or_default(),
+            Amplitudes::Dense(amps) => amps[idx],
+        }
+    }
+
+    /// Non-zero (index, amplitude) pairs in index order, so sampling is reproducible
+    fn entries(&self) -> Vec<(usize, Complex32)> {
+        let mut entries: Vec<(usize, Complex32)> = match &self.amplitudes {
+            Amplitudes::Sparse(map) => map.iter().map(|(&i, &c)| (i, c)).collect(),
+            Amplitudes::Dense(amps) => amps.iter().copied().enumerate().filter(|(_, c)| c.norm_sqr() > CUTOFF).collect(),
+        };
+        entries.sort_unstable_by_key(|e| e.0);
+        entries
+    }
+
+    fn densify_if_full(&mut self) {
+        if let Amplitudes::Sparse(map) = &self.amplitudes {
+            // Past the memory limit the state stays sparse however full it is
+            if map.len() as f64 > DENSE_FILL * (self.qubits as f64).exp2()
+                && resources::check_state::<Complex32>(self.qubits).is_ok() {
+                let mut amps = vec![Complex32::new(0.0, 0.0); 1 << self.qubits];
+                for (&idx, &c) in map {
+                    amps[idx] = c;
+                }
+                self.amplitudes = Amplitudes::Dense(amps);
+            }
+        }
+    }
+
+    /// Apply a unitary instruction
+    fn apply(&mut self, gate: &Gate) -> PyResult<()> {
+        kernels::validate(gate, self.qubits)?;
+        match &mut self.amplitudes {
+            Amplitudes::Dense(amps) => kernels::apply(amps, gate, false),
+            Amplitudes::Sparse(_) if gate.is_snapshot() => {}
+            Amplitudes::Sparse(map) => match kernels::matrix::<f32>(&gate.gate_type) {
+                Some(m) => transform(map, &[gate.target], &[m[0][0], m[0][1], m[1][0], m[1][1]], kernels::control_mask(gate)),
+                None => {
+                    let qubits = gate.qubits();
+                    let m: Vec<Complex32> = mps::local_matrix(gate, &qubits)?.iter()
+                        .map(|c| Complex32::new(c.re as f32, c.im as f32))
+                        .collect();
+                    transform(map, &qubits, &m, 0)
+                }
+            },
+        }
+        self.densify_if_full();
+        Ok(())
+    }
+
+    /// Z-basis measurement of `qubit` with outcome 1 when `r` < P(1); collapses
+    /// and renormalizes the state and returns the outcome
+    fn collapse(&mut self, qubit: usize, r: f64) -> bool {
+        let Amplitudes::Sparse(map) = &mut self.amplitudes else {
+            unreachable!("dense states are measured by dynamic::run");
+        };
+        let mask = 1 << qubit;
+        let mut ones: Vec<(usize, f64)> = map.iter()
+            .filter(|(&i, _)| i & mask != 0)
+            .map(|(&i, c)| (i, c.norm_sqr() as f64))
+            .collect();
+        ones.sort_unstable_by_key(|e| e.0);
+        let p1: f64 = ones.iter().map(|e| e.1).sum();
+        let outcome = r < p1;
+        let p = if outcome { p1 } else { 1.0 - p1 };
+        let scale = (1.0 / p.max(1e-300).sqrt()) as f32;
+        map.retain(|&i, c| {
+            *c *= scale;
+            (i & mask != 0) == outcome
+        });
+        outcome
+    }
+}
+
+/// Multiply every group of stored amplitudes that agree outside `targets` (and
+/// have all of `control_mask` set) by `matrix`, row-major with bit i of the
+/// local index on targets[i]
+fn transform(map: &mut HashMap<usize, Complex32>, targets: &[usize], matrix: &[Complex32], control_mask: usize) {
+    let k_dim = 1 << targets.len();
+    let offsets = reduction::scatter_indices(targets);
+    let target_mask = offsets[k_dim - 1];
+    let local = |i: usize| targets.iter().enumerate().fold(0, |r, (b, &q)| r | (((i >> q) & 1) << b));
+
+    let mut groups: HashMap<usize, Vec<Complex32>> = HashMap::new();
+    map.retain(|&i, c| {
+        if i & control_mask != control_mask {
+            return true;
+        }
+        groups.entry(i & !target_mask).or_insert_with(|| vec![Complex32::new(0.0, 0.0); k_dim])[local(i)] = *c;
+        false
+    });
+    for (base, column) in groups {
+        for (row, offset) in offsets.iter().enumerate() {
+            let amp: Complex32 = (0..k_dim).map(|col| matrix[row * k_dim + col] * column[col]).sum();
+            if amp.norm_sqr() > CUTOFF {
+                map.insert(base | offset, amp);
+            }
+        }
+    }
+}
+
+/// Run `gates` from `initial` (|0...0> when None, without allocating a dense
+/// vector), sampling measurements into `creg`
+pub fn run(qubits: usize, initial: Option<&[Complex32]>, gates: &[Gate], creg: &mut ClassicalRegister) -> PyResult<SparseState> {
+    if qubits >= usize::BITS as usize {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "The sparse backend supports at most {} qubits", usize::BITS - 1)));
+    }
+    let mut state = match initial {
+        Some(amps) => SparseState::from_dense(qubits, amps),
+        None => {
+            let map = HashMap::from([(0, Complex32::new(1.0, 0.0))]);
+            let mut state = SparseState { qubits, amplitudes: Amplitudes::Sparse(map) };
+            state.densify_if_full();
+            state
+        }
+    };
+    evolve(&mut state, gates, creg)?;
+    Ok(state)
+}
+
+fn evolve(state: &mut SparseState, gates: &[Gate], creg: &mut ClassicalRegister) -> PyResult<()> {
+    let n = state.qubits;
+    for gate in gates {
+        if let Amplitudes::Dense(amps) = &mut state.amplitudes {
+            dynamic::run(amps, n, std::slice::from_ref(gate), creg)?;
+            continue;
+        }
+        match &gate.gate_type {
+            GateType::MEASURE(_) | GateType::RESET if gate.target >= n => {
+                return Err(crate::errors::QubitIndexError::new_err("Qubit index out of range"));
+            }
+            GateType::MEASURE(bit) => {
+                creg.read(*bit)?;
+                let r = creg.draw();
+                let outcome = state.collapse(gate.target, r);
+                creg.write(*bit, outcome)?;
+            }
+            GateType::RESET => {
+                let r = creg.draw();
+                if state.collapse(gate.target, r) {
+                    state.apply(&Gate { gate_type: GateType::X, target: gate.target, control: None })?;
+                }
+            }
+            GateType::IF(bit, value, body) => {
+                if creg.read(*bit)? == *value {
+                    evolve(state, body, creg)?;
+                }
+            }
+            _ => state.apply(gate)?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! Banded FFT magnitude spectra behind `resonance` and `resonance_spectrum`.
+//!
+//! The signal (state amplitudes, or the diagonal of a density matrix) is
+//! optionally Hann-windowed, transformed, and its magnitudes averaged over
+//! `n_bands` contiguous bins: the first n_bands - 1 bands hold len / n_bands
+//! bins each and the last one takes the remainder.
+//!
+//! `sonify` renders the same spectrum as audio: the strongest bins become sine
+//! partials, bin k of N sounding at BASE_HZ * 2^(OCTAVES k / N) with its FFT
+//! magnitude as amplitude and its FFT phase as starting phase.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use rustfft::FftPlanner;
+
+/// Pitch of bin 0 (A2) and the number of octaves the bins are spread over
+const BASE_HZ: f32 = 110.0;
+const OCTAVES: f32 = 4.0;
+/// Partials kept when synthesizing; weaker bins are dropped
+const MAX_PARTIALS: usize = 16;
+/// Upper bound on a rendered buffer (10 minutes at 48 kHz)
+const MAX_SAMPLES: usize = 48_000 * 600;
+
+/// Taper applied to the signal before the transform
+#[derive(Clone, Copy)]
+pub enum Window {
+    None,
+    Hann,
+}
+
+impl Window {
+    pub fn from_name(name: &str) -> PyResult<Window> {
+        match name {
+            "none" => Ok(Window::None),
+            "hann" => Ok(Window::Hann),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown window '{}' (expected 'hann' or 'none')", name))),
+        }
+    }
+
+    /// Periodic window weight of sample k out of n
+    fn weight(self, k: usize, n: usize) -> f32 {
+        match self {
+            Window::None => 1.0,
+            Window::Hann => 0.5 * (1.0 - (std::f32::consts::TAU * k as f32 / n as f32).cos()),
+        }
+    }
+}
+
+pub fn check_bands(n_bands: usize, len: usize) -> PyResult<()> {
+    if n_bands == 0 || n_bands > len {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "n_bands must be between 1 and {} (the number of basis states)", len)));
+    }
+    Ok(())
+}
+
+/// Mean FFT magnitude of `signal` in each of `n_bands` bands, as ln(1 + x) when
+/// `log_scale`. Bands that get no bins (n_bands > len) read 0.
+pub fn bands(mut signal: Vec<Complex32>, n_bands: usize, window: Window, log_scale: bool) -> Vec<f32> {
+    let len = signal.len();
+    for (k, s) in signal.iter_mut().enumerate() {
+        *s *= window.weight(k, len);
+    }
+    FftPlanner::new().plan_fft_forward(len).process(&mut signal);
+    let magnitude: Vec<f32> = signal.iter().map(|c| c.norm()).collect();
+
+    let width = len / n_bands;
+    (0..n_bands)
+        .map(|b| {
+            let end = if b + 1 == n_bands { len } else { (b + 1) * width };
+            let band = &magnitude[(b * width).min(end)..end];
+            let mean = if band.is_empty() { 0.0 } else { band.iter().sum::<f32>() / band.len() as f32 };
+            if log_scale { mean.ln_1p() } else { mean }
+        })
+        .collect()
+}
+
+/// The legacy RGB resonance: three unwindowed bands, doubled and capped at 1
+pub fn rgb(signal: Vec<Complex32>) -> Vec<f32> {
+    bands(signal, 3, Window::None, false).into_iter().map(|x| (2.0 * x).min(1.0)).collect()
+}
+
+/// Additive synthesis of the dominant FFT bins of `signal` as mono PCM in
+/// [-1, 1]. Partials at or above Nyquist are skipped.
+pub fn sonify(signal: Vec<Complex32>, duration_s: f32, sample_rate: u32) -> PyResult<Vec<f32>> {
+    if !(duration_s.is_finite() && duration_s > 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err("duration_s must be positive"));
+    }
+    if sample_rate == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("sample_rate must be positive"));
+    }
+    let n_samples = (duration_s as f64 * sample_rate as f64).round() as usize;
+    if n_samples > MAX_SAMPLES {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "duration_s * sample_rate exceeds {} samples", MAX_SAMPLES)));
+    }
+
+    let mut spectrum = signal;
+    let len = spectrum.len();
+    FftPlanner::new().plan_fft_forward(len).process(&mut spectrum);
+    let nyquist = sample_rate as f32 / 2.0;
+    let mut partials: Vec<(f32, f32, f32)> = spectrum.iter().enumerate()
+        .map(|(k, c)| (BASE_HZ * (OCTAVES * k as f32 / len as f32).exp2(), c.norm(), c.arg()))
+        .filter(|&(freq, magnitude, _)| freq < nyquist && magnitude > 1e-6)
+        .collect();
+    partials.sort_by(|a, b| b.1.total_cmp(&a.1));
+    partials.truncate(MAX_PARTIALS);
+
+    // Normalize by the total weight so the sum of sines never clips
+    let total: f32 = partials.iter().map(|p| p.1).sum();
+    if total == 0.0 {
+        return Ok(vec![0.0; n_samples]);
+    }
+    let dt = 1.0 / sample_rate as f64;
+    Ok((0..n_samples).into_par_iter()
+        .map(|i| {
+            let t = i as f64 * dt;
+            partials.iter()
+                .map(|&(freq, magnitude, phase)| {
+                    magnitude * (std::f64::consts::TAU * freq as f64 * t + phase as f64).sin() as f32
+                })
+                .sum::<f32>() / total
+        })
+        .collect())
+}
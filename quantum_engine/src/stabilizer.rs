@@ -0,0 +1,336 @@
+//! Clifford simulation on a stabilizer tableau (Aaronson & Gottesman, 2004).
+//!
+//! An n-qubit stabilizer state is tracked as n destabilizer and n stabilizer
+//! generators, each a signed n-qubit Pauli row, so H, S, CNOT and the Paulis
+//! cost O(n) and a measurement O(n^2) instead of O(2^n). Rows are bit-packed
+//! into u64 words, which keeps circuits on hundreds of qubits cheap.
+
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::{dynamic, observables, rng, Gate, GateType};
+
+/// Tableau of a pure stabilizer state
+#[pyclass(module = "quantum_engine")]
+#[derive(Clone)]
+pub struct StabilizerState {
+    qubits: usize,
+    words: usize,
+    /// X and Z bits of rows 0..n (destabilizers), n..2n (stabilizers) and a scratch row 2n
+    xs: Vec<u64>,
+    zs: Vec<u64>,
+    signs: Vec<bool>,
+}
+
+#[pymethods]
+impl StabilizerState {
+    /// |0...0>, stabilized by Z on every qubit
+    #[new]
+    pub fn new(qubits: usize) -> Self {
+        let words = qubits.div_ceil(64).max(1);
+        let rows = 2 * qubits + 1;
+        let mut state = StabilizerState {
+            qubits,
+            words,
+            xs: vec![0; rows * words],
+            zs: vec![0; rows * words],
+            signs: vec![false; rows],
+        };
+        for q in 0..qubits {
+            state.set(q, q, true, false);
+            state.set(q + qubits, q, false, true);
+        }
+        state
+    }
+
+    /// Apply a named Clifford gate (H, X, Y, Z, S, SDG)
+    #[pyo3(signature = (gate_name, target, param=None))]
+    pub fn apply_gate(&mut self, gate_name: &str, target: usize, param: Option<f32>) -> PyResult<()> {
+        self.check_qubit(target)?;
+        if param.is_some() {
+            return Err(crate::errors::InvalidGateError::new_err(format!(
+                "Gate {} takes no parameter on a stabilizer state", gate_name)));
+        }
+        match gate_name {
+            "H" => self.h(target),
+            "X" => self.pauli(target, false, true),
+            "Y" => self.pauli(target, true, true),
+            "Z" => self.pauli(target, true, false),
+            "S" => self.s(target),
+            "SDG" => {
+                self.s(target);
+                self.pauli(target, true, false);
+            }
+            _ => return Err(crate::errors::InvalidGateError::new_err(format!(
+                "Gate {} is not a Clifford gate", gate_name))),
+        }
+        Ok(())
+    }
+
+    pub fn apply_cnot(&mut self, control: usize, target: usize) -> PyResult<()> {
+        self.check_pair(control, target)?;
+        self.cnot(control, target);
+        Ok(())
+    }
+
+    pub fn apply_swap(&mut self, a: usize, b: usize) -> PyResult<()> {
+        self.check_pair(a, b)?;
+        self.cnot(a, b);
+        self.cnot(b, a);
+        self.cnot(a, b);
+        Ok(())
+    }
+
+    /// Sample `shots` computational-basis outcomes as a counts dictionary
+    #[pyo3(signature = (shots, seed=None))]
+    pub fn measure(&self, shots: usize, seed: Option<u64>) -> HashMap<String, usize> {
+        let mut rng = rng::seeded(seed);
+        let mut counts = HashMap::new();
+        for _ in 0..shots {
+            let mut shot = self.clone();
+            let bits: String = (0..self.qubits)
+                .map(|q| if shot.measure_z(q, &mut rng) { '1' } else { '0' })
+                .collect();
+            *counts.entry(bits).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Expectation value of a PauliString or Observable; each term is 0 or +-coeff
+    pub fn expectation(&self, observable: &PyAny) -> PyResult<f32> {
+        let mut work = self.clone();
+        let mut total = 0.0;
+        for (coeff, label) in observables::extract_labels(observable, self.qubits)? {
+            total += coeff * work.pauli_expectation(&label) as f32;
+        }
+        Ok(total)
+    }
+
+    /// Stabilizer generators as signed Pauli labels, e.g. "+XX" and "+ZZ" for a Bell pair
+    pub fn stabilizers(&self) -> Vec<String> {
+        (self.qubits..2 * self.qubits)
+            .map(|row| {
+                let sign = if self.signs[row] { '-' } else { '+' };
+                std::iter::once(sign)
+                    .chain((0..self.qubits).map(|q| match self.get(row, q) {
+                        (false, false) => 'I',
+                        (true, false) => 'X',
+                        (true, true) => 'Y',
+                        (false, true) => 'Z',
+                    }))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl StabilizerState {
+    fn get(&self, row: usize, q: usize) -> (bool, bool) {
+        let (w, b) = (row * self.words + q / 64, q % 64);
+        ((self.xs[w] >> b) & 1 == 1, (self.zs[w] >> b) & 1 == 1)
+    }
+
+    fn set(&mut self, row: usize, q: usize, x: bool, z: bool) {
+        let (w, bit) = (row * self.words + q / 64, 1u64 << (q % 64));
+        self.xs[w] = if x { self.xs[w] | bit } else { self.xs[w] & !bit };
+        self.zs[w] = if z { self.zs[w] | bit } else { self.zs[w] & !bit };
+    }
+
+    fn check_qubit(&self, q: usize) -> PyResult<()> {
+        if q >= self.qubits {
+            return Err(crate::errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        Ok(())
+    }
+
+    fn check_pair(&self, a: usize, b: usize) -> PyResult<()> {
+        self.check_qubit(a)?;
+        self.check_qubit(b)?;
+        if a == b {
+            return Err(crate::errors::InvalidGateError::new_err("Control and target must be different"));
+        }
+        Ok(())
+    }
+
+    /// Conjugate every generator by a Pauli on `q`: the sign flips where the
+    /// generator anticommutes with it (X part for a Z, Z part for an X)
+    fn pauli(&mut self, q: usize, flip_on_x: bool, flip_on_z: bool) {
+        for row in 0..2 * self.qubits {
+            let (x, z) = self.get(row, q);
+            if (flip_on_x && x) != (flip_on_z && z) {
+                self.signs[row] = !self.signs[row];
+            }
+        }
+    }
+
+    fn h(&mut self, q: usize) {
+        for row in 0..2 * self.qubits {
+            let (x, z) = self.get(row, q);
+            if x && z {
+                self.signs[row] = !self.signs[row];
+            }
+            self.set(row, q, z, x);
+        }
+    }
+
+    fn s(&mut self, q: usize) {
+        for row in 0..2 * self.qubits {
+            let (x, z) = self.get(row, q);
+            if x && z {
+                self.signs[row] = !self.signs[row];
+            }
+            self.set(row, q, x, z ^ x);
+        }
+    }
+
+    fn cnot(&mut self, control: usize, target: usize) {
+        for row in 0..2 * self.qubits {
+            let (xc, zc) = self.get(row, control);
+            let (xt, zt) = self.get(row, target);
+            if xc && zt && (xt == zc) {
+                self.signs[row] = !self.signs[row];
+            }
+            self.set(row, target, xt ^ xc, zt);
+            self.set(row, control, xc, zc ^ zt);
+        }
+    }
+
+    /// Row h <- row i * row h, tracking the sign through the i-phases of each qubit
+    fn rowsum(&mut self, h: usize, i: usize) {
+        let mut phase = 2 * self.signs[h] as i32 + 2 * self.signs[i] as i32;
+        for w in 0..self.words {
+            let (x1, z1) = (self.xs[i * self.words + w], self.zs[i * self.words + w]);
+            let (x2, z2) = (self.xs[h * self.words + w], self.zs[h * self.words + w]);
+            // Qubits where P1 P2 picks up +i (YZ, XY, ZX) and -i (YX, XZ, ZY)
+            let plus = (x1 & z1 & !x2 & z2) | (x1 & !z1 & x2 & z2) | (!x1 & z1 & x2 & !z2);
+            let minus = (x1 & z1 & x2 & !z2) | (x1 & !z1 & !x2 & z2) | (!x1 & z1 & x2 & z2);
+            phase += plus.count_ones() as i32 - minus.count_ones() as i32;
+            self.xs[h * self.words + w] = x1 ^ x2;
+            self.zs[h * self.words + w] = z1 ^ z2;
+        }
+        self.signs[h] = phase.rem_euclid(4) == 2;
+    }
+
+    fn copy_row(&mut self, from: usize, to: usize) {
+        for w in 0..self.words {
+            self.xs[to * self.words + w] = self.xs[from * self.words + w];
+            self.zs[to * self.words + w] = self.zs[from * self.words + w];
+        }
+        self.signs[to] = self.signs[from];
+    }
+
+    fn clear_row(&mut self, row: usize) {
+        for w in 0..self.words {
+            self.xs[row * self.words + w] = 0;
+            self.zs[row * self.words + w] = 0;
+        }
+        self.signs[row] = false;
+    }
+
+    /// Projective Z measurement of `q`, collapsing the tableau
+    fn measure_z(&mut self, q: usize, rng: &mut StdRng) -> bool {
+        let n = self.qubits;
+        if let Some(p) = (n..2 * n).find(|&row| self.get(row, q).0) {
+            // Random outcome: a stabilizer anticommutes with Z_q
+            for row in 0..2 * n {
+                if row != p && self.get(row, q).0 {
+                    self.rowsum(row, p);
+                }
+            }
+            self.copy_row(p, p - n);
+            self.clear_row(p);
+            let outcome = rng.gen::<bool>();
+            self.set(p, q, false, true);
+            self.signs[p] = outcome;
+            outcome
+        } else {
+            // Deterministic: Z_q is (up to sign) a product of stabilizers
+            let scratch = 2 * n;
+            self.clear_row(scratch);
+            for row in 0..n {
+                if self.get(row, q).0 {
+                    self.rowsum(scratch, row + n);
+                }
+            }
+            self.signs[scratch]
+        }
+    }
+
+    /// <P> for a Pauli label: 0 unless +-P is in the stabilizer group
+    fn pauli_expectation(&mut self, label: &str) -> i32 {
+        let n = self.qubits;
+        let target: Vec<(bool, bool)> = (0..n)
+            .map(|q| match label.as_bytes().get(q) {
+                Some(b'X') => (true, false),
+                Some(b'Y') => (true, true),
+                Some(b'Z') => (false, true),
+                _ => (false, false),
+            })
+            .collect();
+        let anticommutes = |state: &Self, row: usize| {
+            target.iter().enumerate()
+                .filter(|(q, &(x, z))| {
+                    let (rx, rz) = state.get(row, *q);
+                    (x && rz) != (z && rx)
+                })
+                .count() % 2 == 1
+        };
+        if (n..2 * n).any(|row| anticommutes(self, row)) {
+            return 0;
+        }
+        // P commutes with every stabilizer, so it is the product of the stabilizers
+        // whose paired destabilizers anticommute with it
+        let scratch = 2 * n;
+        self.clear_row(scratch);
+        for row in 0..n {
+            if anticommutes(self, row) {
+                self.rowsum(scratch, row + n);
+            }
+        }
+        if self.signs[scratch] { -1 } else { 1 }
+    }
+}
+
+/// Run a Clifford circuit from |0...0>, rejecting any non-Clifford instruction
+pub fn run(qubits: usize, gates: &[Gate]) -> PyResult<StabilizerState> {
+    let mut state = StabilizerState::new(qubits);
+    for gate in gates {
+        // Checked by hand: kernels::validate builds a usize mask, which caps it at 64 qubits
+        let mut touched = gate.qubits();
+        if touched.iter().any(|&q| q >= qubits) {
+            return Err(crate::errors::QubitIndexError::new_err("Qubit index out of range"));
+        }
+        touched.sort_unstable();
+        touched.dedup();
+        if touched.len() != gate.qubits().len() {
+            return Err(crate::errors::InvalidGateError::new_err("Control and target must be different"));
+        }
+        let t = gate.target;
+        match &gate.gate_type {
+            GateType::H => state.h(t),
+            GateType::X => state.pauli(t, false, true),
+            GateType::Y => state.pauli(t, true, true),
+            GateType::Z => state.pauli(t, true, false),
+            GateType::S => state.s(t),
+            GateType::SDG => {
+                state.s(t);
+                state.pauli(t, true, false);
+            }
+            GateType::CNOT => state.cnot(gate.control.unwrap(), t),
+            GateType::MCX(controls) if controls.len() == 1 => state.cnot(controls[0], t),
+            GateType::SWAP(a) => {
+                state.cnot(*a, t);
+                state.cnot(t, *a);
+                state.cnot(*a, t);
+            }
+            GateType::MEASURE(_) | GateType::RESET | GateType::IF(..) | GateType::RESTORE(_) => return Err(dynamic::unsupported()),
+            GateType::SNAPSHOT(_) => {}
+            other => return Err(crate::errors::InvalidGateError::new_err(format!(
+                "Gate {} is not a Clifford gate (execute_stabilizer supports h, x, y, z, s, sdg, cnot and swap)",
+                other.name()))),
+        }
+    }
+    Ok(state)
+}
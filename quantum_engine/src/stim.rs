@@ -0,0 +1,306 @@
+//! Stim import and export for Clifford circuits.
+//!
+//! Covers Stim's unitary Clifford gates, M / MR / R, classically controlled
+//! Paulis (`CX rec[-1] 2`) and REPEAT blocks, which are unrolled up to
+//! MAX_INSTRUCTIONS instructions in total. Stim has no
+//! classical register: the k-th measurement on import writes classical bit k,
+//! and on export each c_if refers back to the latest measurement into its bit.
+//! Annotations (TICK, DETECTOR, OBSERVABLE_INCLUDE, coordinates) are dropped
+//! on import; noise channels and non-Clifford gates are rejected.
+
+use pyo3::prelude::*;
+
+use crate::{Gate, GateType};
+
+fn err(line: usize, msg: impl std::fmt::Display) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("Stim line {}: {}", line, msg))
+}
+
+/// Most instructions an imported circuit may hold once REPEAT blocks are unrolled
+const MAX_INSTRUCTIONS: usize = 1 << 22;
+
+/// Instructions that only annotate the circuit for decoders and plotting
+const ANNOTATIONS: [&str; 6] = ["TICK", "DETECTOR", "OBSERVABLE_INCLUDE", "QUBIT_COORDS", "SHIFT_COORDS", "MPAD"];
+
+/// Stim name of a single-qubit Pauli, for classically controlled bodies
+fn pauli_name(gate_type: &GateType) -> Option<&'static str> {
+    match gate_type {
+        GateType::X => Some("X"),
+        GateType::Y => Some("Y"),
+        GateType::Z => Some("Z"),
+        _ => None,
+    }
+}
+
+/// Serialize a Clifford gate list as a Stim circuit; the error lists every
+/// instruction that has no Stim equivalent
+pub fn emit(gates: &[Gate]) -> PyResult<String> {
+    let mut out = String::new();
+    let mut offending = Vec::new();
+    // Measurement record position of the latest measurement into each classical bit
+    let mut latest: Vec<Option<usize>> = Vec::new();
+    let mut measurements = 0;
+    for (i, gate) in gates.iter().enumerate() {
+        let t = gate.target;
+        let line = match &gate.gate_type {
+            GateType::H => format!("H {}", t),
+            GateType::X => format!("X {}", t),
+            GateType::Y => format!("Y {}", t),
+            GateType::Z => format!("Z {}", t),
+            GateType::S => format!("S {}", t),
+            GateType::SDG => format!("S_DAG {}", t),
+            GateType::CNOT => format!("CX {} {}", gate.control.unwrap(), t),
+            GateType::MCX(controls) if controls.len() == 1 => format!("CX {} {}", controls[0], t),
+            GateType::CONTROLLED(controls, inner) if controls.len() == 1 && pauli_name(inner).is_some() => {
+                format!("C{} {} {}", pauli_name(inner).unwrap(), controls[0], t)
+            }
+            GateType::SWAP(a) => format!("SWAP {} {}", a, t),
+            GateType::ISWAP(a) => format!("ISWAP {} {}", a, t),
+            GateType::MEASURE(bit) => {
+                if latest.len() <= *bit {
+                    latest.resize(bit + 1, None);
+                }
+                latest[*bit] = Some(measurements);
+                measurements += 1;
+                format!("M {}", t)
+            }
+            GateType::RESET => format!("R {}", t),
+            GateType::IF(bit, true, body) if body.iter().all(|g| pauli_name(&g.gate_type).is_some()) => {
+                let Some(position) = latest.get(*bit).copied().flatten() else {
+                    offending.push(format!("{} (c_if on classical bit {} before it is measured)", i, bit));
+                    continue;
+                };
+                let lookback = measurements - position;
+                body.iter()
+                    .map(|g| format!("C{} rec[-{}] {}", pauli_name(&g.gate_type).unwrap(), lookback, g.target))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            GateType::IF(..) => {
+                offending.push(format!("{} (c_if other than Paulis applied on outcome 1)", i));
+                continue;
+            }
+            GateType::SNAPSHOT(label) => format!("# snapshot {:?}", label),
+            other => {
+                offending.push(format!("{} ({})", i, other.name()));
+                continue;
+            }
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    if !offending.is_empty() {
+        return Err(crate::errors::InvalidGateError::new_err(format!(
+            "Circuit is not Clifford; gates with no Stim equivalent: {}", offending.join(", "))));
+    }
+    Ok(out)
+}
+
+/// One instruction with its source line
+#[derive(Clone)]
+struct Instruction {
+    line: usize,
+    name: String,
+    has_args: bool,
+    targets: Vec<String>,
+}
+
+/// Read instructions up to the end of input, or up to the closing '}' when nested,
+/// unrolling REPEAT blocks
+fn read_block(lines: &[(usize, &str)], pos: &mut usize, nested: bool) -> PyResult<Vec<Instruction>> {
+    let mut out = Vec::new();
+    while *pos < lines.len() {
+        let (line, text) = lines[*pos];
+        *pos += 1;
+        if text == "}" {
+            if !nested {
+                return Err(err(line, "'}' without a matching REPEAT"));
+            }
+            return Ok(out);
+        }
+        let mut words = text.split_whitespace();
+        let head = words.next().unwrap_or("");
+        if head.eq_ignore_ascii_case("REPEAT") {
+            let count: usize = words.next().and_then(|c| c.parse().ok())
+                .ok_or_else(|| err(line, "REPEAT needs a repetition count"))?;
+            if words.next() != Some("{") || words.next().is_some() {
+                return Err(err(line, "Expected 'REPEAT <count> {'"));
+            }
+            let body = read_block(lines, pos, true)?;
+            let total = count.checked_mul(body.len())
+                .and_then(|n| n.checked_add(out.len()))
+                .filter(|&n| n <= MAX_INSTRUCTIONS);
+            if total.is_none() {
+                return Err(err(line, format!(
+                    "REPEAT {} of a {}-instruction body unrolls past the {}-instruction limit",
+                    count, body.len(), MAX_INSTRUCTIONS)));
+            }
+            for _ in 0..count {
+                out.extend(body.iter().cloned());
+            }
+            continue;
+        }
+        // NAME(args) targets...
+        let head_end = text.find(char::is_whitespace).unwrap_or(text.len());
+        let (name, has_args, rest) = match text.find('(') {
+            Some(open) if open < head_end => {
+                let close = text.find(')').ok_or_else(|| err(line, "Unbalanced parentheses"))?;
+                (&text[..open], true, &text[close + 1..])
+            }
+            _ => (&text[..head_end], false, &text[head_end..]),
+        };
+        out.push(Instruction {
+            line,
+            name: name.to_ascii_uppercase(),
+            has_args,
+            targets: rest.split_whitespace().map(str::to_string).collect(),
+        });
+    }
+    if nested {
+        return Err(err(lines.last().map_or(0, |l| l.0), "REPEAT block is missing its closing '}'"));
+    }
+    Ok(out)
+}
+
+fn qubit(target: &str, line: usize) -> PyResult<usize> {
+    target.parse().map_err(|_| err(line, format!("Expected a qubit index, got '{}'", target)))
+}
+
+/// Parse a Stim circuit into (qubits, classical bits, gates)
+pub fn parse(src: &str) -> PyResult<(usize, usize, Vec<Gate>)> {
+    let lines: Vec<(usize, &str)> = src.lines().enumerate()
+        .map(|(i, raw)| (i + 1, raw.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, text)| !text.is_empty())
+        .collect();
+    let instructions = read_block(&lines, &mut 0, false)?;
+
+    let mut gates = Vec::new();
+    let mut qubits = 0;
+    let mut measurements = 0;
+    let single = |t: usize, gate_type: GateType| Gate { gate_type, target: t, control: None };
+    for ins in instructions {
+        let line = ins.line;
+        let name = ins.name.as_str();
+        if ANNOTATIONS.contains(&name) {
+            if name == "QUBIT_COORDS" {
+                for t in &ins.targets {
+                    qubits = qubits.max(qubit(t, line)? + 1);
+                }
+            }
+            continue;
+        }
+        if ins.has_args {
+            return Err(err(line, format!(
+                "'{}' takes arguments; noise channels and noisy measurements are not supported", name)));
+        }
+        // Measurement targets may be inverted with '!'; rec[-k] only controls Paulis
+        let mut parsed = Vec::with_capacity(ins.targets.len());
+        for t in &ins.targets {
+            let (inverted, index) = match t.strip_prefix('!') {
+                Some(rest) if matches!(name, "M" | "MZ" | "MR" | "MRZ") => (true, rest),
+                _ => (false, t.as_str()),
+            };
+            if let Some(k) = index.strip_prefix("rec[-").and_then(|r| r.strip_suffix(']')) {
+                let k: usize = k.parse().map_err(|_| err(line, format!("Invalid record target '{}'", t)))?;
+                if k == 0 || k > measurements {
+                    return Err(err(line, format!("'{}' refers to a measurement that has not happened", t)));
+                }
+                parsed.push(Err(measurements - k));
+            } else {
+                let q = qubit(index, line)?;
+                qubits = qubits.max(q + 1);
+                parsed.push(Ok((q, inverted)));
+            }
+        }
+        let plain = |parsed: &[Result<(usize, bool), usize>]| -> PyResult<Vec<usize>> {
+            parsed.iter().map(|p| match p {
+                Ok((q, _)) => Ok(*q),
+                Err(_) => Err(err(line, format!("'{}' does not take measurement record targets", name))),
+            }).collect()
+        };
+        match name {
+            "I" => {}
+            "X" | "Y" | "Z" | "H" | "H_XZ" | "S" | "SQRT_Z" | "S_DAG" | "SQRT_Z_DAG" | "SQRT_X" | "SQRT_X_DAG" => {
+                for t in plain(&parsed)? {
+                    match name {
+                        "X" => gates.push(single(t, GateType::X)),
+                        "Y" => gates.push(single(t, GateType::Y)),
+                        "Z" => gates.push(single(t, GateType::Z)),
+                        "H" | "H_XZ" => gates.push(single(t, GateType::H)),
+                        "S" | "SQRT_Z" => gates.push(single(t, GateType::S)),
+                        "S_DAG" | "SQRT_Z_DAG" => gates.push(single(t, GateType::SDG)),
+                        // sqrt(X) = H S H
+                        _ => {
+                            let phase = if name == "SQRT_X" { GateType::S } else { GateType::SDG };
+                            gates.extend([single(t, GateType::H), single(t, phase), single(t, GateType::H)]);
+                        }
+                    }
+                }
+            }
+            "CX" | "CNOT" | "ZCX" | "CY" | "ZCY" | "CZ" | "ZCZ" | "SWAP" | "ISWAP" => {
+                if parsed.len() % 2 != 0 {
+                    return Err(err(line, format!("'{}' needs an even number of targets", name)));
+                }
+                for pair in parsed.chunks(2) {
+                    // CZ is symmetric, so a record target may come second
+                    let (a, b) = match (pair[0], pair[1]) {
+                        (Ok(q), Err(r)) if matches!(name, "CZ" | "ZCZ") => (Err(r), Ok(q)),
+                        pair => pair,
+                    };
+                    let inner = match name {
+                        "CX" | "CNOT" | "ZCX" => GateType::X,
+                        "CY" | "ZCY" => GateType::Y,
+                        _ => GateType::Z,
+                    };
+                    let gate = match (a, b) {
+                        (Err(bit), Ok((t, _))) if !matches!(name, "SWAP" | "ISWAP") => {
+                            let body = vec![single(t, inner)];
+                            Gate { gate_type: GateType::IF(bit, true, body), target: t, control: None }
+                        }
+                        (Ok((a, _)), Ok((t, _))) => {
+                            if a == t {
+                                return Err(err(line, format!("'{}' acts twice on qubit {}", name, a)));
+                            }
+                            match name {
+                                "CX" | "CNOT" | "ZCX" => Gate { gate_type: GateType::CNOT, target: t, control: Some(a) },
+                                "SWAP" => single(t, GateType::SWAP(a)),
+                                "ISWAP" => single(t, GateType::ISWAP(a)),
+                                _ => single(t, GateType::CONTROLLED(vec![a], Box::new(inner))),
+                            }
+                        }
+                        _ => return Err(err(line, format!("Unsupported measurement record target in '{}'", name))),
+                    };
+                    gates.push(gate);
+                }
+            }
+            "M" | "MZ" | "MR" | "MRZ" => {
+                for p in &parsed {
+                    let &Ok((t, inverted)) = p else {
+                        return Err(err(line, format!("'{}' does not take measurement record targets", name)));
+                    };
+                    // X before and after flips the recorded outcome and leaves the state as Stim does
+                    if inverted {
+                        gates.push(single(t, GateType::X));
+                    }
+                    gates.push(single(t, GateType::MEASURE(measurements)));
+                    measurements += 1;
+                    if inverted {
+                        gates.push(single(t, GateType::X));
+                    }
+                    if name.starts_with("MR") {
+                        gates.push(single(t, GateType::RESET));
+                    }
+                }
+            }
+            "R" | "RZ" => {
+                for t in plain(&parsed)? {
+                    gates.push(single(t, GateType::RESET));
+                }
+            }
+            other => return Err(err(line, format!(
+                "Unsupported Stim instruction '{}' (supported: I, X, Y, Z, H, S, S_DAG, SQRT_X, SQRT_X_DAG, \
+                 CX, CY, CZ, SWAP, ISWAP, M, MR, R and REPEAT)", other))),
+        }
+    }
+    Ok((qubits, measurements, gates))
+}
@@ -0,0 +1,52 @@
+//! Lazy iteration over the amplitudes of a state vector.
+//!
+//! `QuantumState.iter_amplitudes` hands Python an iterator that scans the
+//! amplitude buffer in place and yields (index, re, im) for each amplitude
+//! whose magnitude exceeds the threshold, so the dominant components of a
+//! large state can be read without building a 2^n-element list. The iterator
+//! keeps a reference to the state rather than a copy: gates applied while it
+//! is running show up in the amplitudes it has not reached yet.
+
+use pyo3::prelude::*;
+
+use crate::QuantumState;
+
+/// Iterator over (index, re, im) of the amplitudes with |amplitude| > threshold
+#[pyclass]
+pub struct AmplitudeIterator {
+    state: Py<QuantumState>,
+    /// Squared magnitude an amplitude must exceed
+    cutoff: f32,
+    /// First basis index not yet scanned
+    position: usize,
+}
+
+impl AmplitudeIterator {
+    pub fn new(state: Py<QuantumState>, threshold: f32) -> Self {
+        AmplitudeIterator { state, cutoff: threshold * threshold, position: 0 }
+    }
+}
+
+#[pymethods]
+impl AmplitudeIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> Option<(usize, f32, f32)> {
+        let state = self.state.borrow(py);
+        let amplitudes = &state.state[self.position.min(state.state.len())..];
+        match amplitudes.iter().position(|c| c.norm_sqr() > self.cutoff) {
+            Some(offset) => {
+                let index = self.position + offset;
+                self.position = index + 1;
+                let c = amplitudes[offset];
+                Some((index, c.re, c.im))
+            }
+            None => {
+                self.position = state.state.len();
+                None
+            }
+        }
+    }
+}
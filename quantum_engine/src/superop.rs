@@ -0,0 +1,130 @@
+//! Choi matrices and Pauli transfer matrices of noise channels.
+//!
+//! A channel here is a sequence of single-qubit Kraus channels on an n-qubit
+//! register, the form every NoiseModel error and KrausChannel takes. Both
+//! representations are built from the images E(|a><b|) of the matrix units,
+//! with basis indices in the engine's order (qubit q is bit q). The Choi matrix
+//! is unnormalized (trace 2^n) and comes in the two vectorization conventions
+//! in use: "column" stacking as in QuTiP, sum_ab |a><b| (x) E(|a><b|), and
+//! "row" stacking as in forest-benchmarking, sum_ab E(|a><b|) (x) |a><b|.
+//! The PTM entry R_ij = Tr(P_i E(P_j)) / 2^n indexes Pauli labels in
+//! lexicographic order (I, X, Y, Z on one qubit; II, IX, ..., ZZ on two), with
+//! label character q acting on qubit q.
+
+use num_complex::{Complex32, Complex64};
+use pyo3::prelude::*;
+
+use crate::kernels;
+use crate::noise::Kraus2;
+use crate::pauli::PauliMasks;
+
+/// Choi matrix layout selected by the `vectorization` argument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vectorization {
+    Column,
+    Row,
+}
+
+impl Vectorization {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "column" => Ok(Vectorization::Column),
+            "row" => Ok(Vectorization::Row),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown vectorization '{}' (expected 'column' or 'row')", name))),
+        }
+    }
+}
+
+fn widen(op: &Kraus2) -> [[Complex64; 2]; 2] {
+    op.map(|row| row.map(|c: Complex32| Complex64::new(c.re as f64, c.im as f64)))
+}
+
+/// E(|a><b|) for every matrix unit, at index a * 2^n + b
+fn images(n: usize, channels: &[(usize, Vec<Kraus2>)]) -> Vec<Vec<Complex64>> {
+    let dim = 1 << n;
+    let channels: Vec<(usize, Vec<[[Complex64; 2]; 2]>)> = channels.iter()
+        .map(|(q, ops)| (*q, ops.iter().map(widen).collect()))
+        .collect();
+    (0..dim * dim)
+        .map(|unit| {
+            let mut matrix = vec![Complex64::new(0.0, 0.0); dim * dim];
+            matrix[unit] = Complex64::new(1.0, 0.0);
+            for (q, ops) in &channels {
+                kernels::channel_1q(&mut matrix, dim, *q, ops);
+            }
+            matrix
+        })
+        .collect()
+}
+
+/// Row-major 4^n x 4^n Choi matrix of the channel
+pub fn choi(n: usize, channels: &[(usize, Vec<Kraus2>)], vectorization: Vectorization) -> Vec<Complex64> {
+    let dim = 1 << n;
+    let images = images(n, channels);
+    let mut out = vec![Complex64::new(0.0, 0.0); dim * dim * dim * dim];
+    for a in 0..dim {
+        for b in 0..dim {
+            let image = &images[a * dim + b];
+            for i in 0..dim {
+                for j in 0..dim {
+                    let (row, col) = match vectorization {
+                        Vectorization::Column => (a * dim + i, b * dim + j),
+                        Vectorization::Row => (i * dim + a, j * dim + b),
+                    };
+                    out[row * dim * dim + col] = image[i * dim + j];
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Masks of the Pauli string at position `index` in lexicographic label order
+fn pauli_at(index: usize, n: usize) -> PauliMasks {
+    let (mut x, mut z) = (0, 0);
+    for q in 0..n {
+        // Label character q is digit n - 1 - q in base 4, with I, X, Y, Z as 0..3
+        match (index >> (2 * (n - 1 - q))) & 3 {
+            1 => x |= 1 << q,
+            2 => {
+                x |= 1 << q;
+                z |= 1 << q;
+            }
+            3 => z |= 1 << q,
+            _ => {}
+        }
+    }
+    PauliMasks::new(x, z)
+}
+
+/// Entry <a ^ x| P |a> of a Pauli string: i^n_y (-1)^|a & z|
+fn pauli_entry(p: PauliMasks, a: usize) -> Complex64 {
+    let phase = p.y_phase::<f64>();
+    if (a & p.z).count_ones().is_multiple_of(2) { phase } else { -phase }
+}
+
+/// Row-major 4^n x 4^n Pauli transfer matrix of the channel
+pub fn ptm(n: usize, channels: &[(usize, Vec<Kraus2>)]) -> Vec<f64> {
+    let dim = 1 << n;
+    let size = dim * dim;
+    let images = images(n, channels);
+    let paulis: Vec<PauliMasks> = (0..size).map(|k| pauli_at(k, n)).collect();
+    let mut out = vec![0.0; size * size];
+    for (j, &pj) in paulis.iter().enumerate() {
+        // E(P_j) = sum_a <a ^ x|P_j|a> E(|a ^ x><a|)
+        let mut image = vec![Complex64::new(0.0, 0.0); size];
+        for a in 0..dim {
+            let w = pauli_entry(pj, a);
+            for (e, &v) in image.iter_mut().zip(&images[(a ^ pj.x) * dim + a]) {
+                *e += w * v;
+            }
+        }
+        for (i, &pi) in paulis.iter().enumerate() {
+            // Tr(P_i M) = sum_c <c ^ x|P_i|c> M[c][c ^ x]
+            let trace: Complex64 = (0..dim).map(|c| pauli_entry(pi, c) * image[c * dim + (c ^ pi.x)]).sum();
+            out[i * size + j] = trace.re / dim as f64;
+        }
+    }
+    out
+}
@@ -0,0 +1,212 @@
+//! State tomography: measurement-basis circuits and density-matrix reconstruction.
+//!
+//! An n-qubit state is measured in the 3^n product bases of X, Y and Z.
+//! Setting i measures qubit q in "XYZ"[(i / 3^q) % 3], rotating X onto Z with H
+//! and Y onto Z with Sdg then H, so outcome 0 is the +1 eigenstate in every
+//! basis. `reconstruct_state` takes the counts of those circuits in the same
+//! order and inverts them: "linear" sums the estimated Pauli expectations into
+//! rho = 2^-n sum_P <P> P (unbiased but possibly not positive), "mle" runs
+//! the R rho R iteration of Hradil et al. from the maximally mixed state,
+//! which stays positive and converges to the maximum-likelihood state.
+
+use num_complex::{Complex32, Complex64};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::pauli::PauliMasks;
+use crate::{linalg, parse_ordered, DensityMatrix, QuantumCircuit};
+
+/// Iteration cap and convergence threshold (largest entry change) of "mle"
+const MLE_MAX_ITERATIONS: usize = 5000;
+const MLE_TOLERANCE: f64 = 1e-10;
+
+/// Basis (0 = X, 1 = Y, 2 = Z) in which `setting` measures qubit `q`
+fn basis(setting: usize, q: usize) -> usize {
+    setting / 3usize.pow(q as u32) % 3
+}
+
+/// Circuits measuring the output of `base_circuit` in each of the 3^n
+/// product bases (see the module docs for the order)
+#[pyfunction]
+pub fn tomography_circuits(base_circuit: &QuantumCircuit) -> PyResult<Vec<QuantumCircuit>> {
+    let n = base_circuit.qubits;
+    (0..3usize.pow(n as u32))
+        .map(|setting| {
+            let mut circuit = base_circuit.clone();
+            for q in 0..n {
+                match basis(setting, q) {
+                    0 => circuit.h(q)?,
+                    1 => {
+                        circuit.sdg(q)?;
+                        circuit.h(q)?;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(circuit)
+        })
+        .collect()
+}
+
+/// Density matrix estimated from the counts of every `tomography_circuits`
+/// variant, in order. `method` is "linear" or "mle"; `bit_order` is that of
+/// the bitstrings, as in `run`.
+#[pyfunction]
+#[pyo3(signature = (counts_list, method="mle", bit_order="little"))]
+pub fn reconstruct_state(py: Python, counts_list: Vec<HashMap<String, usize>>, method: &str, bit_order: &str) -> PyResult<DensityMatrix> {
+    let (mut n, mut settings) = (0, 1);
+    while settings < counts_list.len() {
+        n += 1;
+        settings *= 3;
+    }
+    if n == 0 || settings != counts_list.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Expected counts for 3^n measurement settings, got {}", counts_list.len())));
+    }
+    let dim = 1 << n;
+
+    // Outcome frequencies of each setting
+    let mut frequencies = vec![0.0f64; counts_list.len() * dim];
+    for (setting, counts) in counts_list.iter().enumerate() {
+        let shots: usize = counts.values().sum();
+        if shots == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!("Setting {} has no counts", setting)));
+        }
+        for (bits, &count) in counts {
+            frequencies[setting * dim + parse_ordered(bits, n, bit_order)?] += count as f64 / shots as f64;
+        }
+    }
+
+    let rho = match method.to_ascii_lowercase().as_str() {
+        "linear" => py.allow_threads(|| linear_inversion(&frequencies, n)),
+        "mle" => py.allow_threads(|| maximum_likelihood(&frequencies, n)),
+        _ => return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown reconstruction method '{}' (expected 'linear' or 'mle')", method))),
+    };
+    let matrix = rho.iter().map(|c| Complex32::new(c.re as f32, c.im as f32)).collect();
+    Ok(DensityMatrix { qubits: n, matrix })
+}
+
+/// rho = 2^-n sum_P <P> P, each <P> averaged over the settings that measure
+/// its support in the right bases
+fn linear_inversion(frequencies: &[f64], n: usize) -> Vec<Complex64> {
+    let dim = 1 << n;
+    let settings = frequencies.len() / dim;
+    // Sums of <P> estimates, indexed by x * dim + z of the Pauli masks
+    let mut sums = vec![0.0f64; dim * dim];
+    for setting in 0..settings {
+        let f = &frequencies[setting * dim..(setting + 1) * dim];
+        let (mut x_bases, mut z_bases) = (0, 0);
+        for q in 0..n {
+            match basis(setting, q) {
+                0 => x_bases |= 1 << q,
+                1 => {
+                    x_bases |= 1 << q;
+                    z_bases |= 1 << q;
+                }
+                _ => z_bases |= 1 << q,
+            }
+        }
+        for support in 0..dim {
+            let parity: f64 = f.iter().enumerate()
+                .map(|(x, &p)| if (x & support).count_ones() & 1 == 0 { p } else { -p })
+                .sum();
+            sums[(support & x_bases) * dim + (support & z_bases)] += parity;
+        }
+    }
+
+    let mut rho = vec![Complex64::new(0.0, 0.0); dim * dim];
+    for x in 0..dim {
+        for z in 0..dim {
+            let support = x | z;
+            let expectation = sums[x * dim + z] / 3f64.powi((n - support.count_ones() as usize) as i32);
+            let p = PauliMasks::new(x, z);
+            let phase = p.y_phase::<f64>() * (expectation / dim as f64);
+            // P|j> = i^n_y (-1)^|j & z| |j ^ x>
+            for j in 0..dim {
+                let sign = if (j & z).count_ones() & 1 == 0 { 1.0 } else { -1.0 };
+                rho[(j ^ x) * dim + j] += phase * sign;
+            }
+        }
+    }
+    rho
+}
+
+/// Single-qubit rotation taking basis `b` onto Z
+fn rotation(b: usize) -> [Complex64; 4] {
+    let (zero, one, i) = (Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0), Complex64::new(0.0, 1.0));
+    let r = std::f64::consts::FRAC_1_SQRT_2;
+    match b {
+        0 => [one * r, one * r, one * r, -one * r],
+        1 => [one * r, -i * r, one * r, i * r],
+        _ => [one, zero, zero, one],
+    }
+}
+
+/// (u on qubit q) rho (u on qubit q)^dagger for a flattened `dim x dim` matrix
+fn conjugate(rho: &mut [Complex64], dim: usize, q: usize, u: &[Complex64; 4]) {
+    let bit = 1 << q;
+    for i in (0..dim).filter(|i| i & bit == 0) {
+        for c in 0..dim {
+            let (a, b) = (rho[i * dim + c], rho[(i | bit) * dim + c]);
+            rho[i * dim + c] = u[0] * a + u[1] * b;
+            rho[(i | bit) * dim + c] = u[2] * a + u[3] * b;
+        }
+    }
+    for r in 0..dim {
+        for j in (0..dim).filter(|j| j & bit == 0) {
+            let (a, b) = (rho[r * dim + j], rho[r * dim + (j | bit)]);
+            rho[r * dim + j] = a * u[0].conj() + b * u[1].conj();
+            rho[r * dim + (j | bit)] = a * u[2].conj() + b * u[3].conj();
+        }
+    }
+}
+
+/// R rho R iteration with R = sum_{s, x} f_s(x) / p_s(x) Pi_{s, x}, where
+/// Pi_{s, x} is the projector of outcome x in setting s and p_s(x) = Tr(rho Pi)
+fn maximum_likelihood(frequencies: &[f64], n: usize) -> Vec<Complex64> {
+    let dim = 1 << n;
+    let settings = frequencies.len() / dim;
+    let rotations: Vec<Vec<[Complex64; 4]>> = (0..settings)
+        .map(|s| (0..n).map(|q| rotation(basis(s, q))).collect())
+        .collect();
+    let daggers: Vec<Vec<[Complex64; 4]>> = rotations.iter()
+        .map(|us| us.iter().map(|u| [u[0].conj(), u[2].conj(), u[1].conj(), u[3].conj()]).collect())
+        .collect();
+
+    let mut rho = vec![Complex64::new(0.0, 0.0); dim * dim];
+    for j in 0..dim {
+        rho[j * dim + j] = Complex64::new(1.0 / dim as f64, 0.0);
+    }
+    for _ in 0..MLE_MAX_ITERATIONS {
+        let mut r = vec![Complex64::new(0.0, 0.0); dim * dim];
+        for s in 0..settings {
+            // Outcome probabilities are the diagonal of U_s rho U_s^dagger
+            let mut rotated = rho.clone();
+            for (q, u) in rotations[s].iter().enumerate() {
+                conjugate(&mut rotated, dim, q, u);
+            }
+            let mut term = vec![Complex64::new(0.0, 0.0); dim * dim];
+            for x in 0..dim {
+                let f = frequencies[s * dim + x];
+                if f > 0.0 {
+                    term[x * dim + x] = Complex64::new(f / rotated[x * dim + x].re.max(1e-15), 0.0);
+                }
+            }
+            for (q, u) in daggers[s].iter().enumerate() {
+                conjugate(&mut term, dim, q, u);
+            }
+            r.iter_mut().zip(&term).for_each(|(a, b)| *a += b);
+        }
+
+        let mut next = linalg::matmul(&linalg::matmul(&r, &rho, dim), &r, dim);
+        let trace: f64 = (0..dim).map(|j| next[j * dim + j].re).sum();
+        next.iter_mut().for_each(|c| *c /= trace);
+        let change = next.iter().zip(&rho).map(|(a, b)| (a - b).norm()).fold(0.0, f64::max);
+        rho = next;
+        if change < MLE_TOLERANCE {
+            break;
+        }
+    }
+    rho
+}
@@ -0,0 +1,177 @@
+//! Observable time series for `QuantumCircuit.execute_traced` and
+//! `QuantumCircuit.layer_observables` (the probes also feed
+//! `execute_feedback`'s callback).
+//!
+//! The circuit runs once, gate by gate; every `every` gates (and at the start
+//! and end) each probe is evaluated on the current state, so a full trace costs
+//! one execution instead of one per prefix. The layered variant runs the gates
+//! layer by layer (as `depth` counts them) and evaluates after each layer.
+
+use pyo3::prelude::*;
+
+use crate::observables::{self, PauliTerm};
+use crate::{dynamic, introspect, pauli, Gate, GateType, QuantumState};
+
+/// One quantity to record after each traced step
+pub enum Probe {
+    Energy,
+    Entropy,
+    Resonance,
+    Terms(Vec<PauliTerm>),
+}
+
+/// Values of one probe over the trace
+pub enum Series {
+    Scalar(Vec<f32>),
+    Vector(Vec<Vec<f32>>),
+}
+
+/// Value of one probe at one point: a float, or [r, g, b] for resonance
+pub enum Reading {
+    Scalar(f32),
+    Vector(Vec<f32>),
+}
+
+impl Probe {
+    /// "energy", "entropy", "resonance", or a PauliString / Observable
+    pub fn extract(obj: &PyAny, n_qubits: usize) -> PyResult<Probe> {
+        if let Ok(name) = obj.extract::<&str>() {
+            return match name {
+                "energy" => Ok(Probe::Energy),
+                "entropy" => Ok(Probe::Entropy),
+                "resonance" => Ok(Probe::Resonance),
+                _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown traced quantity '{}' (expected 'energy', 'entropy', 'resonance', a PauliString or an Observable)",
+                    name))),
+            };
+        }
+        observables::extract_terms(obj, n_qubits).map(Probe::Terms)
+    }
+
+    fn empty_series(&self) -> Series {
+        match self {
+            Probe::Resonance => Series::Vector(Vec::new()),
+            _ => Series::Scalar(Vec::new()),
+        }
+    }
+
+    /// Value of a scalar probe (anything but resonance) on `state`
+    fn scalar(&self, state: &QuantumState) -> f32 {
+        match self {
+            Probe::Energy => state.calculate_energy(),
+            Probe::Entropy => state.calculate_entropy(),
+            Probe::Terms(terms) => terms.iter().map(|t| t.coeff * pauli::expectation(&state.state, t.masks)).sum(),
+            Probe::Resonance => unreachable!(),
+        }
+    }
+
+    pub fn read(&self, state: &QuantumState) -> Reading {
+        match self {
+            Probe::Resonance => Reading::Vector(state.calculate_resonance()),
+            _ => Reading::Scalar(self.scalar(state)),
+        }
+    }
+
+    fn record(&self, state: &QuantumState, series: &mut Series) {
+        match (self.read(state), series) {
+            (Reading::Vector(value), Series::Vector(values)) => values.push(value),
+            (Reading::Scalar(value), Series::Scalar(values)) => values.push(value),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl IntoPy<PyObject> for Reading {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            Reading::Scalar(value) => value.into_py(py),
+            Reading::Vector(value) => value.into_py(py),
+        }
+    }
+}
+
+impl IntoPy<PyObject> for Series {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            Series::Scalar(values) => values.into_py(py),
+            Series::Vector(values) => values.into_py(py),
+        }
+    }
+}
+
+/// Run `gates` from `initial`, returning the gate counts at which the probes
+/// were recorded and one series per probe. Snapshots run (so restores can
+/// return to them) but do not count as steps.
+pub fn run(
+    initial: &QuantumState,
+    clbits: usize,
+    gates: &[Gate],
+    probes: &[Probe],
+    every: usize,
+    seed: Option<u64>,
+) -> PyResult<(Vec<usize>, Vec<Series>)> {
+    if every == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("every must be positive"));
+    }
+    let mut state = initial.clone();
+    let mut creg = dynamic::ClassicalRegister::new(clbits, seed);
+    let mut steps = vec![0];
+    let mut series: Vec<Series> = probes.iter().map(|p| p.empty_series()).collect();
+    for (probe, values) in probes.iter().zip(series.iter_mut()) {
+        probe.record(&state, values);
+    }
+
+    let total = gates.iter().filter(|g| !g.is_snapshot()).count();
+    let mut done = 0;
+    for gate in gates {
+        dynamic::run(&mut state.state, state.qubits, std::slice::from_ref(gate), &mut creg)?;
+        if gate.is_snapshot() {
+            continue;
+        }
+        done += 1;
+        if done % every == 0 || done == total {
+            steps.push(done);
+            for (probe, values) in probes.iter().zip(series.iter_mut()) {
+                probe.record(&state, values);
+            }
+        }
+    }
+    Ok((steps, series))
+}
+
+/// Run `gates` from `initial` one layer at a time, returning the number of
+/// layers and the probe values after each, row-major (layers x probes).
+/// Gates run in layer order, which respects every qubit and classical-bit
+/// dependency; snapshots take no layer and are skipped.
+pub fn layers(
+    initial: &QuantumState,
+    clbits: usize,
+    gates: &[Gate],
+    probes: &[Probe],
+    seed: Option<u64>,
+) -> PyResult<(usize, Vec<f32>)> {
+    if probes.iter().any(|p| matches!(p, Probe::Resonance)) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "resonance is an [r, g, b] vector; layer_observables takes scalar observables only"));
+    }
+    if gates.iter().any(|g| matches!(g.gate_type, GateType::RESTORE(_))) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Restores act on the whole state and cannot be scheduled by layer"));
+    }
+    let levels = introspect::levels(gates, initial.qubits, clbits);
+    let depth = levels.iter().copied().max().unwrap_or(0);
+    let mut order: Vec<usize> = (0..gates.len()).filter(|&i| levels[i] > 0).collect();
+    order.sort_by_key(|&i| levels[i]);
+
+    let mut state = initial.clone();
+    let mut creg = dynamic::ClassicalRegister::new(clbits, seed);
+    let mut values = Vec::with_capacity(depth * probes.len());
+    let mut next = order.iter().peekable();
+    for layer in 1..=depth {
+        while let Some(&i) = next.next_if(|&&i| levels[i] == layer) {
+            dynamic::run(&mut state.state, state.qubits, std::slice::from_ref(&gates[i]), &mut creg)?;
+        }
+        values.extend(probes.iter().map(|p| p.scalar(&state)));
+    }
+    Ok((depth, values))
+}
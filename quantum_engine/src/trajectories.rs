@@ -0,0 +1,165 @@
+//! Monte Carlo quantum-trajectory (stochastic wavefunction) simulation.
+//!
+//! Each trajectory evolves a pure state and, after every gate, picks one Kraus
+//! operator per noise channel with probability ||E_k psi||^2. Pair errors are
+//! Pauli channels, so their Pauli is drawn from the fixed term probabilities.
+//! Averaging over trajectories reproduces the density-matrix result in O(2^n)
+//! memory.
+
+use num_complex::Complex32;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::noise::NoiseModel;
+use crate::observables::PauliTerm;
+use crate::pauli::PauliMasks;
+use crate::{pauli, rng, Gate, QuantumState};
+
+/// Trajectory-averaged outputs of `QuantumCircuit.execute_trajectories`
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct TrajectoryResult {
+    n_trajectories: usize,
+    probabilities: Vec<f32>,
+    expectations: Vec<f32>,
+}
+
+#[pymethods]
+impl TrajectoryResult {
+    #[getter]
+    pub fn n_trajectories(&self) -> usize {
+        self.n_trajectories
+    }
+
+    /// Averaged basis-state populations (the diagonal of the equivalent rho)
+    #[getter]
+    pub fn probabilities(&self) -> Vec<f32> {
+        self.probabilities.clone()
+    }
+
+    /// Averaged expectation value of each requested observable
+    #[getter]
+    pub fn expectations(&self) -> Vec<f32> {
+        self.expectations.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TrajectoryResult(n_trajectories={}, expectations={:?})", self.n_trajectories, self.expectations)
+    }
+}
+
+/// Pick and apply one Kraus operator on `qubit`, renormalizing the state
+pub fn apply_random_kraus(state: &mut QuantumState, qubit: usize, kraus: &[[[Complex32; 2]; 2]], rng: &mut StdRng) {
+    let step = 1 << qubit;
+    let weights: Vec<f64> = kraus.iter()
+        .map(|e| {
+            (0..state.state.len())
+                .filter(|i| i & step == 0)
+                .map(|i| {
+                    let (a, b) = (state.state[i], state.state[i | step]);
+                    ((e[0][0] * a + e[0][1] * b).norm_sqr() + (e[1][0] * a + e[1][1] * b).norm_sqr()) as f64
+                })
+                .sum()
+        })
+        .collect();
+
+    let total: f64 = weights.iter().sum();
+    let mut r = rng.gen::<f64>() * total;
+    let mut chosen = kraus.len() - 1;
+    for (k, w) in weights.iter().enumerate() {
+        if r < *w {
+            chosen = k;
+            break;
+        }
+        r -= w;
+    }
+
+    state.apply_matrix(qubit, kraus[chosen]);
+    let norm = weights[chosen].sqrt() as f32;
+    if norm > 1e-12 {
+        state.state.iter_mut().for_each(|c| *c /= norm);
+    }
+}
+
+/// Draw one Pauli from (probability, Pauli) terms and apply it, up to the
+/// global phase i^n_y, which no trajectory output can see
+fn apply_random_pauli(state: &mut QuantumState, terms: &[(f32, PauliMasks)], rng: &mut StdRng) {
+    let mut r = rng.gen::<f32>();
+    let mut chosen = terms[terms.len() - 1].1;
+    for &(p, masks) in terms {
+        if r < p {
+            chosen = masks;
+            break;
+        }
+        r -= p;
+    }
+    if chosen.z != 0 {
+        state.state.par_iter_mut().enumerate()
+            .filter(|(i, _)| (i & chosen.z).count_ones() % 2 == 1)
+            .for_each(|(_, c)| *c = -*c);
+    }
+    if chosen.x != 0 {
+        let amplitudes = std::mem::take(&mut state.state);
+        state.state = (0..amplitudes.len()).into_par_iter().map(|i| amplitudes[i ^ chosen.x]).collect();
+    }
+}
+
+/// Run `n_trajectories` noisy pure-state evolutions in parallel and average their outputs
+pub fn run(
+    initial: &QuantumState,
+    gates: &[Gate],
+    model: &NoiseModel,
+    n_trajectories: usize,
+    seed: u64,
+    observables: &[Vec<PauliTerm>],
+) -> PyResult<TrajectoryResult> {
+    let qubits = initial.qubits;
+    // Resolve the channel schedule once; it is identical for every trajectory
+    let schedule = gates.iter()
+        .map(|g| -> PyResult<_> {
+            let channels: Vec<_> = model.channels_after(g, qubits)?.into_iter().map(|(q, ch)| (q, ch.kraus())).collect();
+            let pairs: Vec<_> = model.pair_channels_after(g, qubits)?.into_iter().map(|(a, b, ch)| ch.terms(a, b)).collect();
+            Ok((channels, pairs))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let dim = 1 << qubits;
+    let (probabilities, expectations) = (0..n_trajectories).into_par_iter()
+        .map(|t| -> PyResult<(Vec<f64>, Vec<f64>)> {
+            let mut rng = StdRng::seed_from_u64(rng::derive(seed, t as u64));
+            let mut state = initial.clone();
+            for (gate, (channels, pairs)) in gates.iter().zip(&schedule) {
+                state.apply(gate)?;
+                for (q, kraus) in channels {
+                    apply_random_kraus(&mut state, *q, kraus, &mut rng);
+                }
+                for terms in pairs {
+                    apply_random_pauli(&mut state, terms, &mut rng);
+                }
+            }
+            let probs = state.state.iter().map(|c| c.norm_sqr() as f64).collect();
+            let exps = observables.iter()
+                .map(|terms| terms.iter()
+                    .map(|t| (t.coeff * pauli::expectation(&state.state, t.masks)) as f64)
+                    .sum())
+                .collect();
+            Ok((probs, exps))
+        })
+        .try_reduce(
+            || (vec![0.0; dim], vec![0.0; observables.len()]),
+            |(mut pa, mut ea), (pb, eb)| {
+                pa.iter_mut().zip(pb).for_each(|(a, b)| *a += b);
+                ea.iter_mut().zip(eb).for_each(|(a, b)| *a += b);
+                Ok((pa, ea))
+            },
+        )?;
+
+    let n = n_trajectories.max(1) as f64;
+    Ok(TrajectoryResult {
+        n_trajectories,
+        probabilities: probabilities.into_iter().map(|p| (p / n) as f32).collect(),
+        expectations: expectations.into_iter().map(|e| (e / n) as f32).collect(),
+    })
+}
@@ -0,0 +1,240 @@
+//! Gate fusion run before state-vector execution.
+//!
+//! Level 1 multiplies each run of uncontrolled single-qubit gates on a qubit
+//! (diagonal ones included) into one 2x2 `UNITARY`, so the run costs a single
+//! sweep over the state. Level 2 also merges back-to-back CPHASE / CRZ gates on
+//! the same qubits by adding their angles. Products are formed in f64 and
+//! stored as the circuit's f32 matrices.
+
+use num_complex::{Complex32, Complex64};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::kernels::{self, Matrix2};
+use crate::{Gate, GateType};
+
+pub const MAX_LEVEL: usize = 2;
+
+/// Single-qubit gates pending on one qubit, with their product so far
+struct Run {
+    gates: Vec<Gate>,
+    product: Matrix2<f64>,
+}
+
+pub fn check_level(level: usize) -> pyo3::PyResult<()> {
+    if level > MAX_LEVEL {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown transpile level {} (expected 0, 1 or 2)", level)));
+    }
+    Ok(())
+}
+
+/// Fused copy of `gates` at the given level (0 returns them unchanged)
+pub fn fuse(gates: &[Gate], level: usize) -> Vec<Gate> {
+    if level == 0 {
+        return gates.to_vec();
+    }
+    let mut out: Vec<Gate> = Vec::with_capacity(gates.len());
+    let mut pending: BTreeMap<usize, Run> = BTreeMap::new();
+
+    for gate in gates {
+        if let Some(m) = single_qubit_matrix(gate) {
+            let run = pending.entry(gate.target).or_insert_with(|| Run { gates: Vec::new(), product: identity() });
+            run.product = mul(&m, &run.product);
+            run.gates.push(gate.clone());
+            continue;
+        }
+        if gate.is_barrier() {
+            // Snapshots observe every qubit and restores replace it, so nothing fuses across them
+            for (q, run) in std::mem::take(&mut pending) {
+                flush(&mut out, q, run);
+            }
+        }
+        for q in gate.qubits() {
+            if let Some(run) = pending.remove(&q) {
+                flush(&mut out, q, run);
+            }
+        }
+        let gate = match &gate.gate_type {
+            GateType::IF(bit, value, body) => Gate { gate_type: GateType::IF(*bit, *value, fuse(body, level)), ..gate.clone() },
+            _ => gate.clone(),
+        };
+        if level >= 2 {
+            if let Some(merged) = out.last().and_then(|last| merge_phases(last, &gate)) {
+                *out.last_mut().unwrap() = merged;
+                continue;
+            }
+        }
+        out.push(gate);
+    }
+    for (q, run) in pending {
+        flush(&mut out, q, run);
+    }
+    out
+}
+
+/// Matrix of an uncontrolled single-qubit gate; None for anything that must
+/// stay in place (controlled, multi-qubit, measurement, reset, c_if)
+fn single_qubit_matrix(gate: &Gate) -> Option<Matrix2<f64>> {
+    match &gate.gate_type {
+        GateType::UNITARY(targets, m) if targets.len() == 1 => {
+            let e = |i: usize| Complex64::new(m[i].re as f64, m[i].im as f64);
+            Some([[e(0), e(1)], [e(2), e(3)]])
+        }
+        GateType::CNOT | GateType::MCX(_) | GateType::CONTROLLED(..) => None,
+        _ if gate.control.is_some() => None,
+        other => kernels::matrix::<f64>(other),
+    }
+}
+
+/// Emit a run as its original gate when it has one, otherwise as the fused matrix
+fn flush(out: &mut Vec<Gate>, qubit: usize, mut run: Run) {
+    if run.gates.len() == 1 {
+        out.append(&mut run.gates);
+        return;
+    }
+    let flat = run.product.iter().flatten().map(|e| Complex32::new(e.re as f32, e.im as f32)).collect();
+    out.push(Gate { gate_type: GateType::UNITARY(vec![qubit], flat), target: qubit, control: None });
+}
+
+/// Single gate equal to `a` followed by `b` when both are CPHASE on the same
+/// qubit pair (in either order) or CRZ with the same control and target
+fn merge_phases(a: &Gate, b: &Gate) -> Option<Gate> {
+    let same_pair = |x: &Gate, y: &Gate| x.control == y.control && x.target == y.target;
+    let gate_type = match (&a.gate_type, &b.gate_type) {
+        (GateType::CPHASE(x), GateType::CPHASE(y))
+            if same_pair(a, b) || (a.control == Some(b.target) && b.control == Some(a.target)) => GateType::CPHASE(x + y),
+        (GateType::CRZ(x), GateType::CRZ(y)) if same_pair(a, b) => GateType::CRZ(x + y),
+        _ => return None,
+    };
+    Some(Gate { gate_type, ..a.clone() })
+}
+
+fn identity() -> Matrix2<f64> {
+    let (zero, one) = (Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0));
+    [[one, zero], [zero, one]]
+}
+
+fn mul(a: &Matrix2<f64>, b: &Matrix2<f64>) -> Matrix2<f64> {
+    let entry = |r: usize, c: usize| a[r][0] * b[0][c] + a[r][1] * b[1][c];
+    [[entry(0, 0), entry(0, 1)], [entry(1, 0), entry(1, 1)]]
+}
+
+/// Gates removed by each `optimize` pass
+#[derive(Default)]
+pub struct OptimizeStats {
+    pub cancelled: usize,
+    pub merged: usize,
+    pub dropped: usize,
+}
+
+/// Cancel adjacent inverse pairs, merge consecutive same-axis rotations and drop
+/// rotations within `tolerance` of the identity (up to global phase). Two gates
+/// are adjacent when they act on the same qubits and nothing between them
+/// touches any of those qubits.
+pub fn optimize(gates: &[Gate], tolerance: f32) -> (Vec<Gate>, OptimizeStats) {
+    let mut stats = OptimizeStats::default();
+    let mut out: Vec<Option<Gate>> = Vec::with_capacity(gates.len());
+    // Per qubit, indices into `out` of the live gates on that wire, oldest first
+    let mut wires: HashMap<usize, Vec<usize>> = HashMap::new();
+
+    for gate in gates {
+        let qubits = sorted_qubits(gate);
+        let previous = qubits.iter()
+            .map(|q| wires.get(q).and_then(|w| w.last().copied()))
+            .reduce(|a, b| if a == b { a } else { None })
+            .flatten()
+            .filter(|&j| out[j].as_ref().is_some_and(|g| sorted_qubits(g) == qubits));
+
+        if let Some(j) = previous {
+            let prev = out[j].as_ref().unwrap();
+            let combined = if cancels(prev, gate) {
+                stats.cancelled += 2;
+                Some(None)
+            } else if let Some(gate_type) = merged_rotation(&prev.gate_type, &gate.gate_type) {
+                stats.merged += 1;
+                let merged = Gate { gate_type, ..prev.clone() };
+                if is_identity(&merged, tolerance) {
+                    stats.dropped += 1;
+                    Some(None)
+                } else {
+                    Some(Some(merged))
+                }
+            } else {
+                None
+            };
+            if let Some(replacement) = combined {
+                if replacement.is_none() {
+                    for q in &qubits {
+                        wires.get_mut(q).unwrap().pop();
+                    }
+                }
+                out[j] = replacement;
+                continue;
+            }
+        }
+        if is_identity(gate, tolerance) {
+            stats.dropped += 1;
+            continue;
+        }
+        if gate.is_barrier() {
+            // Nothing may cancel or merge across a snapshot or restore
+            wires.clear();
+        }
+        for &q in &qubits {
+            wires.entry(q).or_default().push(out.len());
+        }
+        out.push(Some(gate.clone()));
+    }
+    (out.into_iter().flatten().collect(), stats)
+}
+
+fn sorted_qubits(gate: &Gate) -> Vec<usize> {
+    let mut qubits = gate.qubits();
+    qubits.sort_unstable();
+    qubits
+}
+
+/// True when `b` undoes `a`, given that both act on the same qubits
+fn cancels(a: &Gate, b: &Gate) -> bool {
+    use GateType::*;
+    let same_roles = a.target == b.target && a.control == b.control;
+    match (&a.gate_type, &b.gate_type) {
+        (H, H) | (X, X) | (Y, Y) | (Z, Z) | (S, SDG) | (SDG, S) | (T, TDG) | (TDG, T) => true,
+        (CNOT, CNOT) | (CH, CH) => same_roles,
+        (MCX(_), MCX(_)) => a.target == b.target,
+        (CONTROLLED(_, x), CONTROLLED(_, y)) => {
+            let inner = |gate_type: &GateType| Gate { gate_type: gate_type.clone(), target: a.target, control: None };
+            a.target == b.target && cancels(&inner(x), &inner(y))
+        }
+        (SWAP(_), SWAP(_)) => true,
+        _ => false,
+    }
+}
+
+/// Single rotation equal to `a` followed by `b` about the same axis
+fn merged_rotation(a: &GateType, b: &GateType) -> Option<GateType> {
+    Some(match (a, b) {
+        (GateType::RX(x), GateType::RX(y)) => GateType::RX(x + y),
+        (GateType::RY(x), GateType::RY(y)) => GateType::RY(x + y),
+        (GateType::RZ(x), GateType::RZ(y)) => GateType::RZ(x + y),
+        (GateType::P(x), GateType::P(y)) => GateType::P(x + y),
+        (GateType::RXX(a, x), GateType::RXX(_, y)) => GateType::RXX(*a, x + y),
+        (GateType::RYY(a, x), GateType::RYY(_, y)) => GateType::RYY(*a, x + y),
+        (GateType::RZZ(a, x), GateType::RZZ(_, y)) => GateType::RZZ(*a, x + y),
+        _ => return None,
+    })
+}
+
+/// True for a rotation within `tolerance` of a multiple of its identity period:
+/// 2pi when the remainder is only a global phase, 4pi for controlled rotations
+fn is_identity(gate: &Gate, tolerance: f32) -> bool {
+    let tau = std::f32::consts::TAU;
+    let (angle, period) = match gate.gate_type {
+        GateType::RX(a) | GateType::RY(a) | GateType::RZ(a) | GateType::P(a) | GateType::CPHASE(a)
+        | GateType::RXX(_, a) | GateType::RYY(_, a) | GateType::RZZ(_, a) => (a, tau),
+        GateType::CRX(a) | GateType::CRY(a) | GateType::CRZ(a) => (a, 2.0 * tau),
+        _ => return false,
+    };
+    let r = angle.rem_euclid(period);
+    r.min(period - r) <= tolerance
+}
@@ -0,0 +1,136 @@
+//! Pauli twirling of two-qubit Clifford gates.
+//!
+//! A twirled instance surrounds every two-qubit Clifford gate G with a random
+//! two-qubit Pauli P before it and P' = G P G^dagger after it, so the instance
+//! implements the same unitary up to global phase while whatever error rides
+//! along with G is conjugated by a random Pauli. Averaging over instances turns
+//! that error into a Pauli channel: coherent over-rotations become stochastic
+//! flips, and amplitude damping loses the off-diagonal terms of its Pauli
+//! transfer matrix. Single-qubit and non-Clifford gates are left alone, since
+//! their P' would not be a Pauli. The inserted Paulis are ordinary x / y / z
+//! gates, so a noise model treats them as the extra gates they are on hardware.
+
+use num_complex::Complex64;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::noise::NoiseModel;
+use crate::{mps, rng, DensityMatrix, Gate, GateType, QuantumCircuit};
+
+/// 2x2 matrices of I, X, Y, Z
+fn pauli_matrix(p: usize) -> [[Complex64; 2]; 2] {
+    let (o, l, i) = (Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0), Complex64::new(0.0, 1.0));
+    match p {
+        1 => [[o, l], [l, o]],
+        2 => [[o, -i], [i, o]],
+        3 => [[l, o], [o, -l]],
+        _ => [[l, o], [o, l]],
+    }
+}
+
+/// Row-major 4x4 matrix of Pauli pa on local qubit 0 and pb on local qubit 1
+fn pauli_pair(pa: usize, pb: usize) -> Vec<Complex64> {
+    let (a, b) = (pauli_matrix(pa), pauli_matrix(pb));
+    // Bit 0 of the index is local qubit 0
+    (0..16).map(|k| {
+        let (r, c) = (k / 4, k % 4);
+        a[r & 1][c & 1] * b[r >> 1][c >> 1]
+    }).collect()
+}
+
+fn mul(a: &[Complex64], b: &[Complex64]) -> Vec<Complex64> {
+    (0..16).map(|k| (0..4).map(|j| a[k / 4 * 4 + j] * b[j * 4 + k % 4]).sum()).collect()
+}
+
+/// The Pauli pair (pa, pb) with G P G^dagger = phase * (pa x pb), if there is one
+fn conjugate(g: &[Complex64], pa: usize, pb: usize) -> Option<(usize, usize)> {
+    let dagger: Vec<Complex64> = (0..16).map(|k| g[k % 4 * 4 + k / 4].conj()).collect();
+    let image = mul(&mul(g, &pauli_pair(pa, pb)), &dagger);
+    (0..16).map(|k| (k / 4, k % 4)).find(|&(qa, qb)| {
+        // |Tr(Q^dagger image)| / 4 is one exactly when image is a multiple of Q
+        let q = pauli_pair(qa, qb);
+        let overlap: Complex64 = q.iter().zip(&image).map(|(x, y)| x.conj() * y).sum();
+        (overlap.norm() / 4.0 - 1.0).abs() < 1e-6
+    })
+}
+
+fn pauli_gate(p: usize, target: usize) -> Option<Gate> {
+    let gate_type = match p {
+        1 => GateType::X,
+        2 => GateType::Y,
+        3 => GateType::Z,
+        _ => return None,
+    };
+    Some(Gate { gate_type, target, control: None })
+}
+
+/// Local matrix of `gate` when it is a two-qubit Clifford (every Pauli maps to a Pauli)
+fn clifford_matrix(gate: &Gate) -> PyResult<Option<(Vec<usize>, Vec<Complex64>)>> {
+    let qubits = gate.qubits();
+    if qubits.len() != 2 || matches!(gate.gate_type, GateType::IF(..) | GateType::MEASURE(_) | GateType::RESET) {
+        return Ok(None);
+    }
+    let matrix = mps::local_matrix(gate, &qubits)?;
+    // Images of the generators X, Z on each qubit decide it
+    let clifford = [(1, 0), (3, 0), (0, 1), (0, 3)].iter().all(|&(pa, pb)| conjugate(&matrix, pa, pb).is_some());
+    Ok(clifford.then_some((qubits, matrix)))
+}
+
+/// One twirled instance of `gates`, drawing a Pauli pair per Clifford gate from `rng`
+fn twirl_gates(gates: &[Gate], rng: &mut StdRng) -> PyResult<Vec<Gate>> {
+    let mut out = Vec::with_capacity(gates.len());
+    for gate in gates {
+        if let GateType::IF(bit, value, body) = &gate.gate_type {
+            out.push(Gate { gate_type: GateType::IF(*bit, *value, twirl_gates(body, rng)?), ..gate.clone() });
+            continue;
+        }
+        let Some((qubits, matrix)) = clifford_matrix(gate)? else {
+            out.push(gate.clone());
+            continue;
+        };
+        let (pa, pb) = (rng.gen_range(0..4), rng.gen_range(0..4));
+        let (qa, qb) = conjugate(&matrix, pa, pb).unwrap_or((pa, pb));
+        out.extend(pauli_gate(pa, qubits[0]));
+        out.extend(pauli_gate(pb, qubits[1]));
+        out.push(gate.clone());
+        out.extend(pauli_gate(qa, qubits[0]));
+        out.extend(pauli_gate(qb, qubits[1]));
+    }
+    Ok(out)
+}
+
+/// `instances` twirled copies of `circuit`, drawn in order from one seeded stream
+pub fn instances(circuit: &QuantumCircuit, instances: usize, seed: Option<u64>) -> PyResult<Vec<QuantumCircuit>> {
+    let gates = circuit.bound_gates()?;
+    let mut rng = rng::seeded(seed);
+    (0..instances)
+        .map(|_| Ok(QuantumCircuit { gates: twirl_gates(gates, &mut rng)?, ..circuit.clone() }))
+        .collect()
+}
+
+/// Density matrix averaged over `instances` twirled copies, each evolved under `model`
+pub fn average(circuit: &QuantumCircuit, model: &NoiseModel, instances: usize, seed: Option<u64>) -> PyResult<DensityMatrix> {
+    if instances == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("num_instances must be positive"));
+    }
+    let mut total: Option<DensityMatrix> = None;
+    for instance in self::instances(circuit, instances, seed)? {
+        let mut dm = instance.initial_density()?;
+        for gate in &instance.gates {
+            dm.apply_unitary(gate)?;
+            model.apply_after(gate, &mut dm)?;
+        }
+        match &mut total {
+            Some(sum) => sum.matrix.iter_mut().zip(&dm.matrix).for_each(|(a, b)| *a += b),
+            None => total = Some(dm),
+        }
+    }
+    let mut average = match total {
+        Some(total) => total,
+        None => circuit.initial_density()?,
+    };
+    let scale = 1.0 / instances as f32;
+    average.matrix.iter_mut().for_each(|a| *a *= scale);
+    Ok(average)
+}
@@ -0,0 +1,120 @@
+//! Dense unitaries of whole circuits.
+//!
+//! Column j of U is the circuit applied to basis state |j>; the columns are
+//! independent, so they are simulated in parallel. Equivalence up to global
+//! phase compares full unitaries for small circuits and otherwise probes both
+//! circuits with the same random states: |<psi|A^dagger B|psi>| = 1 for a
+//! random psi only when A^dagger B is a multiple of the identity.
+
+use num_complex::{Complex32, Complex64};
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::{dynamic, rng, transpile, Gate, QuantumCircuit, QuantumState};
+
+/// Largest circuit whose 4^n-entry unitary is built (12 qubits is 128 MiB)
+pub const MAX_QUBITS: usize = 12;
+/// Largest circuit `equivalent` compares entry by entry
+const FULL_COMPARE_QUBITS: usize = 8;
+/// Random states probed for larger circuits
+const PROBES: usize = 16;
+
+/// Fused gate list of a circuit that has a unitary
+fn unitary_gates(circuit: &QuantumCircuit) -> PyResult<Vec<Gate>> {
+    if dynamic::is_dynamic(&circuit.gates) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Circuits with measurement, reset or c_if have no unitary"));
+    }
+    if circuit.initial.is_some() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Circuits with an initialized state have no unitary"));
+    }
+    Ok(transpile::fuse(circuit.bound_gates()?, 1))
+}
+
+fn evolve(mut state: QuantumState, gates: &[Gate]) -> PyResult<Vec<Complex32>> {
+    for gate in gates {
+        state.apply(gate)?;
+    }
+    Ok(state.state)
+}
+
+/// Row-major 2^n x 2^n unitary implemented by `circuit`
+pub fn matrix(circuit: &QuantumCircuit) -> PyResult<Vec<Complex32>> {
+    if circuit.qubits > MAX_QUBITS {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unitary extraction is limited to {} qubits (circuit has {})", MAX_QUBITS, circuit.qubits)));
+    }
+    let gates = unitary_gates(circuit)?;
+    let n = circuit.qubits;
+    let dim = 1 << n;
+
+    let columns = (0..dim).into_par_iter()
+        .map(|j| {
+            let mut state = vec![Complex32::new(0.0, 0.0); dim];
+            state[j] = Complex32::new(1.0, 0.0);
+            evolve(QuantumState { qubits: n, state, phase: 0.0, drift: 0.0 }, &gates)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let mut u = vec![Complex32::new(0.0, 0.0); dim * dim];
+    for (j, column) in columns.into_iter().enumerate() {
+        for (i, amp) in column.into_iter().enumerate() {
+            u[i * dim + j] = amp;
+        }
+    }
+    Ok(u)
+}
+
+/// Row-major matrix of `gate` on its own qubits: bit i of the index is
+/// qubit `gate.qubits()[i]`
+pub fn gate_matrix(gate: &Gate) -> PyResult<Vec<Complex32>> {
+    let qubits = gate.qubits();
+    let local = gate.remapped(|q| qubits.iter().position(|&p| p == q).unwrap_or(q));
+    matrix(&QuantumCircuit { qubits: qubits.len(), clbits: 0, gates: vec![local], params: Vec::new(), initial: None })
+}
+
+/// True when `a` and `b` implement the same unitary up to global phase: every
+/// entry of B within `tolerance` of e^(i phi) A, or (above 8 qubits) every
+/// probe overlap within `tolerance` of 1 in magnitude
+pub fn equivalent(a: &QuantumCircuit, b: &QuantumCircuit, tolerance: f32, seed: Option<u64>) -> PyResult<bool> {
+    if a.qubits != b.qubits {
+        return Ok(false);
+    }
+    if a.qubits <= FULL_COMPARE_QUBITS {
+        let (ua, ub) = (matrix(a)?, matrix(b)?);
+        // Global phase from the largest entry of A
+        let (k, pivot) = ua.iter().enumerate()
+            .max_by(|x, y| x.1.norm_sqr().total_cmp(&y.1.norm_sqr()))
+            .unwrap();
+        let phase = ub[k] / pivot;
+        let phase = phase / phase.norm().max(f32::MIN_POSITIVE);
+        return Ok(ua.iter().zip(&ub).all(|(x, y)| (y - phase * x).norm() <= tolerance));
+    }
+
+    let (ga, gb) = (unitary_gates(a)?, unitary_gates(b)?);
+    let n = a.qubits;
+    let base_seed = rng::resolve(seed);
+    let overlaps = (0..PROBES).into_par_iter()
+        .map(|p| {
+            let mut rng = StdRng::seed_from_u64(rng::derive(base_seed, p as u64));
+            let mut state: Vec<Complex32> = (0..1usize << n)
+                .map(|_| Complex32::from_polar(rng.gen::<f32>(), rng.gen::<f32>() * std::f32::consts::TAU))
+                .collect();
+            let norm = state.iter().map(|c| c.norm_sqr()).sum::<f32>().sqrt();
+            state.iter_mut().for_each(|c| *c /= norm);
+            let sa = evolve(QuantumState { qubits: n, state: state.clone(), phase: 0.0, drift: 0.0 }, &ga)?;
+            let sb = evolve(QuantumState { qubits: n, state, phase: 0.0, drift: 0.0 }, &gb)?;
+            let overlap: Complex64 = sa.iter().zip(&sb)
+                .map(|(x, y)| {
+                    let z = x.conj() * y;
+                    Complex64::new(z.re as f64, z.im as f64)
+                })
+                .sum();
+            Ok(overlap.norm())
+        })
+        .collect::<PyResult<Vec<f64>>>()?;
+    Ok(overlaps.iter().all(|&o| 1.0 - o <= tolerance as f64))
+}
@@ -0,0 +1,177 @@
+//! Variational quantum eigensolver loop run entirely on the Rust side.
+
+use pyo3::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::cache::{self, ExpectationCache};
+use crate::observables::{self, PauliTerm};
+use crate::optimizers::{Objective, Optimizer, OptimizerBase};
+use crate::{rng, QuantumCircuit};
+
+/// Outcome of `VQE.run`
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct VQEResult {
+    #[pyo3(get)]
+    energy: f32,
+    #[pyo3(get)]
+    parameters: HashMap<String, f32>,
+    /// Energy before the first iteration followed by the energy after each one
+    #[pyo3(get)]
+    history: Vec<f32>,
+    #[pyo3(get)]
+    iterations: usize,
+    #[pyo3(get)]
+    converged: bool,
+}
+
+#[pymethods]
+impl VQEResult {
+    fn __repr__(&self) -> String {
+        format!("VQEResult(energy={}, iterations={}, converged={})",
+            self.energy, self.iterations, if self.converged { "True" } else { "False" })
+    }
+}
+
+/// Minimizes <H> over the parameters of an ansatz circuit
+#[pyclass]
+pub struct VQE {
+    ansatz: QuantumCircuit,
+    hamiltonian: Vec<PauliTerm>,
+    /// Update rule in its initial state, cloned for each run
+    optimizer: Optimizer,
+    max_iterations: usize,
+    tolerance: f32,
+    seed: Option<u64>,
+    cache: Option<Py<ExpectationCache>>,
+}
+
+struct Energy<'a> {
+    ansatz: &'a QuantumCircuit,
+    hamiltonian: &'a [PauliTerm],
+    names: &'a [String],
+    cache: Option<(&'a ExpectationCache, cache::Scope)>,
+}
+
+impl Energy<'_> {
+    fn values(&self, theta: &[f32]) -> HashMap<String, f32> {
+        self.names.iter().cloned().zip(theta.iter().copied()).collect()
+    }
+}
+
+impl Objective for Energy<'_> {
+    fn energy(&self, theta: &[f32]) -> PyResult<f32> {
+        let Some((cache, scope)) = self.cache else {
+            return self.ansatz.energy_at(self.hamiltonian, &self.values(theta));
+        };
+        let key = scope.key(theta);
+        if let Some(energy) = cache.get(&key) {
+            return Ok(energy);
+        }
+        let energy = self.ansatz.energy_at(self.hamiltonian, &self.values(theta))?;
+        cache.insert(key, energy);
+        Ok(energy)
+    }
+
+    fn gradient(&self, theta: &[f32]) -> PyResult<Vec<f32>> {
+        self.ansatz.adjoint_gradients(self.hamiltonian, &self.values(theta))
+    }
+}
+
+#[pymethods]
+impl VQE {
+    /// `optimizer` is "spsa", "gradient_descent" or "adam" (the default), with `learning_rate`,
+    /// or an `SPSA`, `GradientDescent` or `Adam` instance carrying its own
+    /// hyperparameters; gradient-based optimizers use adjoint differentiation.
+    /// Energies are memoized in `cache` when one is given.
+    #[new]
+    #[pyo3(signature = (ansatz, hamiltonian, optimizer=None, learning_rate=0.1, max_iterations=100, tolerance=1e-6, seed=None,
+        cache=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        ansatz: &QuantumCircuit,
+        hamiltonian: &PyAny,
+        optimizer: Option<&PyAny>,
+        learning_rate: f32,
+        max_iterations: usize,
+        tolerance: f32,
+        seed: Option<u64>,
+        cache: Option<Py<ExpectationCache>>,
+    ) -> PyResult<Self> {
+        if ansatz.parameters().is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err("Ansatz has no parameters to optimize"));
+        }
+        let optimizer = match optimizer {
+            None => Optimizer::from_name("adam", learning_rate, max_iterations)?,
+            Some(obj) => match obj.extract::<&str>() {
+                Ok(name) => Optimizer::from_name(name, learning_rate, max_iterations)?,
+                Err(_) => obj.extract::<PyRef<OptimizerBase>>()?.fresh(),
+            },
+        };
+        Ok(VQE {
+            ansatz: ansatz.clone(),
+            hamiltonian: observables::extract_terms(hamiltonian, ansatz.qubits)?,
+            optimizer,
+            max_iterations,
+            tolerance,
+            seed,
+            cache,
+        })
+    }
+
+    /// Run the optimization from `initial_parameters` (in `ansatz.parameters()` order),
+    /// or from angles drawn uniformly in [-pi, pi) when omitted
+    #[pyo3(signature = (initial_parameters=None))]
+    pub fn run(&self, py: Python, initial_parameters: Option<Vec<f32>>) -> PyResult<VQEResult> {
+        let names = self.ansatz.parameters();
+        let mut rng = rng::seeded(self.seed);
+        let mut theta = match initial_parameters {
+            Some(t) if t.len() != names.len() => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Expected {} initial parameters, got {}", names.len(), t.len())));
+            }
+            Some(t) => t,
+            None => names.iter().map(|_| rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI)).collect(),
+        };
+
+        let cache = self.cache.as_ref().map(|c| c.borrow(py));
+        let scope = match &cache {
+            Some(_) => Some(cache::Scope::new(&self.ansatz, &self.hamiltonian)?),
+            None => None,
+        };
+        let objective = Energy {
+            ansatz: &self.ansatz,
+            hamiltonian: &self.hamiltonian,
+            names: &names,
+            cache: cache.as_deref().zip(scope),
+        };
+        let mut optimizer = self.optimizer.clone();
+        let mut history = vec![objective.energy(&theta)?];
+        let mut converged = false;
+
+        for _ in 0..self.max_iterations {
+            optimizer.step(&mut theta, &objective, &mut rng)?;
+            let energy = objective.energy(&theta)?;
+            let previous = history[history.len() - 1];
+            history.push(energy);
+            if (energy - previous).abs() < self.tolerance {
+                converged = true;
+                break;
+            }
+        }
+
+        Ok(VQEResult {
+            energy: history[history.len() - 1],
+            parameters: objective.values(&theta),
+            iterations: history.len() - 1,
+            history,
+            converged,
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("VQE(optimizer={}, parameters={}, max_iterations={})",
+            self.optimizer.name(), self.ansatz.parameters().len(), self.max_iterations)
+    }
+}
@@ -0,0 +1,295 @@
+import math
+import random
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+TOLERANCE = 1e-4
+SHOTS = 2000
+
+
+def check(label, value, expected, tolerance=TOLERANCE):
+    print(f"   {label}: {value:.4f} (expected {expected:.4f})")
+    return abs(value - expected) < tolerance
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_xeb():
+    print("\nTesting linear cross-entropy benchmarking...")
+    n, shots = 8, 5000
+    circuit = QuantumCircuit.random(n, 20, seed=5)
+    probabilities = circuit.execute().probabilities()
+    # Sampling the ideal distribution gives F = 2^n sum_x p(x)^2 - 1 in expectation
+    expected = (1 << n) * sum(p * p for p in probabilities) - 1
+    ideal = [bits for bits, count in circuit.run(shots, seed=1).items() for _ in range(count)]
+    rng = random.Random(2)
+    uniform = [format(rng.getrandbits(n), f"0{n}b") for _ in range(shots)]
+    # One sample x scores exactly 2^n p(x) - 1 (character i of the bitstring is qubit i)
+    single = "10110001"
+    index = sum(1 << q for q, bit in enumerate(single) if bit == "1")
+    return all([
+        check("ideal samples", quantum_engine.xeb_fidelity(circuit, ideal), expected, 0.1),
+        check("uniform samples", quantum_engine.xeb_fidelity(circuit, uniform), 0.0, 0.1),
+        check("one sample", quantum_engine.xeb_fidelity(circuit, [single]), (1 << n) * probabilities[index] - 1),
+        raises("wrong bitstring width", ValueError, lambda: quantum_engine.xeb_fidelity(circuit, ["0101"])),
+        raises("no samples", ValueError, lambda: quantum_engine.xeb_fidelity(circuit, [])),
+    ])
+
+
+def test_qaoa():
+    print("\nTesting the QAOA builder against hand-built layers...")
+    terms = [(1.0, "ZZI"), (0.5, "IZZ"), (-0.7, "ZIZ"), (0.3, "ZII")]
+    cost = quantum_engine.Hamiltonian(terms)
+    gammas, betas = [0.3, -0.8], [0.2, 0.65]
+
+    # exp(-i gamma c Z..Z) is RZZ(2 gamma c) or RZ(2 gamma c); the mixer is RX(2 beta) everywhere
+    reference = QuantumCircuit(3)
+    for q in range(3):
+        reference.h(q)
+    for gamma, beta in zip(gammas, betas):
+        for c, label in terms:
+            qubits = [q for q, p in enumerate(label) if p == "Z"]
+            if len(qubits) == 2:
+                reference.rzz(qubits[0], qubits[1], 2 * gamma * c)
+            else:
+                reference.rz(qubits[0], 2 * gamma * c)
+        for q in range(3):
+            reference.rx(q, 2 * beta)
+    expected = reference.execute()
+    built = QuantumCircuit.qaoa(cost, 2, gammas, betas).execute()
+
+    # Symbolic angles bind to the same state
+    symbolic = QuantumCircuit.qaoa(cost, 2)
+    names = symbolic.parameters()
+    values = {"gamma_0": gammas[0], "beta_0": betas[0], "gamma_1": gammas[1], "beta_1": betas[1]}
+    bound = symbolic.execute_bound(values)
+    print(f"   symbolic parameters {names}")
+    return all([
+        check("fidelity with fixed angles", built.fidelity(expected), 1.0),
+        check("fidelity with bound symbols", bound.fidelity(expected), 1.0),
+        check("<C> with fixed angles", built.expectation(cost), expected.expectation(cost)),
+        names == ["gamma_0", "beta_0", "gamma_1", "beta_1"],
+        raises("non-diagonal cost", ValueError,
+               lambda: QuantumCircuit.qaoa(quantum_engine.Hamiltonian([(1.0, "XZI")]), 1)),
+        raises("wrong number of gammas", ValueError, lambda: QuantumCircuit.qaoa(cost, 2, [0.1], betas)),
+    ])
+
+
+def qpe_distribution(phi, m):
+    """P(y) = |sum_x e^(2 pi i x (phi - y / 2^m))|^2 / 4^m for the counting register"""
+    probabilities = []
+    for y in range(1 << m):
+        delta = phi - y / (1 << m)
+        total = sum(complex(math.cos(2 * math.pi * x * delta), math.sin(2 * math.pi * x * delta)) for x in range(1 << m))
+        probabilities.append(abs(total) ** 2 / (1 << 2 * m))
+    return probabilities
+
+
+def test_phase_estimation():
+    print("\nTesting phase estimation against the exact counting distribution...")
+    ok = True
+    # Two-qubit unitary P(a) (x) P(b) with eigenstate |11> and phase (a + b) / 2 pi
+    for phi, m in ((3 / 8, 3), (0.3, 4), (0.71, 5)):
+        unitary = QuantumCircuit(2)
+        unitary.p(0, 2 * math.pi * phi * 0.25)
+        unitary.p(1, 2 * math.pi * phi * 0.75)
+        circuit = QuantumCircuit(m + 2)
+        circuit.x(m)
+        circuit.x(m + 1)
+        circuit.compose(QuantumCircuit.phase_estimation(unitary, m))
+        counting = circuit.execute().marginal_probabilities(list(range(m)))
+        expected = qpe_distribution(phi, m)
+        error = max(abs(a - b) for a, b in zip(counting, expected))
+        peak = counting.index(max(counting))
+        print(f"   phi = {phi}, {m} counting qubits: peak at y = {peak} (2^m phi = {phi * (1 << m):.2f}), "
+              f"max probability error {error:.2e}")
+        ok &= error < TOLERANCE and peak == round(phi * (1 << m))
+    return ok and all([
+        raises("no counting qubits", ValueError, lambda: QuantumCircuit.phase_estimation(QuantumCircuit(1), 0)),
+    ])
+
+
+def test_grover():
+    print("\nTesting Grover iterations against sin^2((2k + 1) theta)...")
+    ok = True
+    for marked, n in ((["1011"], 4), (["001", "110"], 3), (["10110"], 5)):
+        grover = quantum_engine.GroverOperator(marked if len(marked) > 1 else marked[0])
+        theta = math.asin((len(marked) / (1 << n)) ** 0.5)
+        for k in range(4):
+            state = grover.search_circuit(k).execute()
+            found = sum(state.probability(bits) for bits in marked)
+            ok &= check(f"{marked}, {k} iterations", found, math.sin((2 * k + 1) * theta) ** 2)
+        ok &= grover.optimal_iterations() == math.floor(math.pi / (4 * theta))
+
+    # The diffusion operator is 2|s><s| - I up to global phase
+    start = QuantumCircuit.random(3, 4, seed=50)
+    amps = [complex(*a) for a in start.execute().get_state_vector()]
+    mean = sum(amps) / len(amps)
+    expected = [2 * mean - a for a in amps]
+    start.compose(quantum_engine.GroverOperator("101").diffusion())
+    reference = quantum_engine.QuantumState.from_amplitudes([(a.real, a.imag) for a in expected])
+    ok &= check("diffusion fidelity with 2|s><s| - I", start.execute().fidelity(reference), 1.0)
+
+    # A circuit oracle: CCZ marks |111>
+    oracle = QuantumCircuit(3)
+    oracle.mcz([0, 1], 2)
+    counts = quantum_engine.grover_search(oracle, iterations=2, shots=SHOTS, seed=3)
+    print(f"   circuit oracle: {counts.get('111', 0)} of {SHOTS} shots on '111'")
+    ok &= abs(counts.get("111", 0) / SHOTS - math.sin(5 * math.asin(1 / 8 ** 0.5)) ** 2) < 0.03
+    return ok and all([
+        raises("circuit oracle without iterations", ValueError, lambda: quantum_engine.grover_search(oracle)),
+        raises("marked strings of different widths", ValueError, lambda: quantum_engine.GroverOperator(["01", "110"])),
+    ])
+
+
+def exact_counts(circuit, total=10 ** 6):
+    """Counts proportional to the ideal output distribution (character i is qubit i)"""
+    amps = circuit.execute().get_state_vector()
+    n = len(amps).bit_length() - 1
+    return {"".join(str(i >> q & 1) for q in range(n)): round(total * (re * re + im * im))
+            for i, (re, im) in enumerate(amps)}
+
+
+def test_tomography():
+    print("\nTesting tomography circuits and linear / MLE state reconstruction...")
+    base = QuantumCircuit.random(2, 3, seed=79)
+    truth = base.execute_density()
+    variants = quantum_engine.tomography_circuits(base)
+    # Setting i measures qubit q in "XYZ"[(i / 3^q) % 3]; H and Sdg H are the basis rotations
+    basis_gates = {0: ["h"], 1: ["sdg", "h"], 2: []}
+    layout = len(variants) == 9 and all(
+        [name for name, _, _ in v.gates()[len(base.gates()):]]
+        == [g for q in range(2) for g in basis_gates[i // 3 ** q % 3]] for i, v in enumerate(variants))
+    print(f"   {len(variants)} variants, basis rotations in the documented order: {layout}")
+    ok = layout
+    exact = [exact_counts(v) for v in variants]
+    sampled = [v.run(SHOTS, seed=i) for i, v in enumerate(variants)]
+    for method in ("linear", "mle"):
+        rho = quantum_engine.reconstruct_state(exact, method=method)
+        ok &= check(f"{method}, exact counts: trace distance", rho.trace_distance(truth), 0.0, 1e-3)
+        noisy = quantum_engine.reconstruct_state(sampled, method=method)
+        ok &= check(f"{method}, {SHOTS} shots per basis: fidelity", noisy.fidelity(truth), 1.0, 0.05)
+    purity = quantum_engine.reconstruct_state(sampled, method="mle").purity()
+    print(f"   mle from {SHOTS} shots stays physical: purity {purity:.4f}")
+    ok &= purity <= 1.0 + TOLERANCE
+    bell = quantum_engine.tomography_circuits(QuantumCircuit.bell())
+    rho = quantum_engine.reconstruct_state([exact_counts(v) for v in bell], method="mle")
+    ok &= check("reconstructed Bell pair: concurrence", rho.concurrence(0, 1), 1.0, 1e-3)
+    return (ok and raises("wrong number of settings", ValueError,
+                          lambda: quantum_engine.reconstruct_state(exact[:-1]))
+            and raises("unknown method", ValueError,
+                       lambda: quantum_engine.reconstruct_state(exact, method="bayes")))
+
+
+def feature_map(x):
+    """Angle encoding of a 3-feature point followed by an entangling layer and a second encoding"""
+    circuit = QuantumCircuit(3)
+    for q, value in enumerate(x):
+        circuit.h(q)
+        circuit.rz(q, value)
+    circuit.cnot(0, 1)
+    circuit.cnot(1, 2)
+    for q, value in enumerate(x):
+        circuit.ry(q, value * x[(q + 1) % 3])
+    return circuit
+
+
+def test_kernel_matrix():
+    print("\nTesting fidelity kernel matrices against pairwise state overlaps...")
+    points = [(0.1, 0.5, -0.3), (1.2, -0.7, 0.4), (0.1, 0.5, -0.3), (-2.0, 0.3, 0.9)]
+    others = [(0.0, 0.0, 0.0), (0.6, 0.6, -1.1)]
+    circuits = [feature_map(x) for x in points]
+    other_circuits = [feature_map(x) for x in others]
+    try:
+        import numpy
+    except ImportError:
+        print("   numpy not installed; checking the ImportError path only")
+        return raises("kernel_matrix", ImportError, lambda: quantum_engine.kernel_matrix(circuits))
+    states = [c.execute() for c in circuits]
+    other_states = [c.execute() for c in other_circuits]
+    gram = quantum_engine.kernel_matrix(circuits)
+    cross = quantum_engine.kernel_matrix(circuits, other_circuits)
+    expected_gram = numpy.array([[a.fidelity(b) for b in states] for a in states])
+    expected_cross = numpy.array([[a.fidelity(b) for b in other_states] for a in states])
+    gram_error = float(numpy.abs(gram - expected_gram).max())
+    cross_error = float(numpy.abs(cross - expected_cross).max())
+    print(f"   Gram {gram.shape} {gram.dtype}: max error {gram_error:.2e}; "
+          f"cross {cross.shape}: max error {cross_error:.2e}")
+    # Repeated points give identical rows; the diagonal is one and the matrix symmetric
+    return (gram.dtype == numpy.float64 and gram.shape == (4, 4) and cross.shape == (4, 2)
+            and gram_error < TOLERANCE and cross_error < TOLERANCE
+            and numpy.allclose(gram, gram.T) and numpy.allclose(numpy.diag(gram), 1.0, atol=TOLERANCE)
+            and numpy.allclose(gram[0], gram[2], atol=TOLERANCE))
+
+
+def test_amplitude_amplification():
+    print("\nTesting amplitude amplification and estimation against a = sin^2(t/2)...")
+    # The good states have qubit 0 set; RY(t) gives them probability a = sin^2(t/2)
+    t = 1.1
+    prep = QuantumCircuit(2)
+    prep.ry(0, t)
+    prep.h(1)
+    oracle = QuantumCircuit(2)
+    oracle.z(0)
+    a = math.sin(t / 2) ** 2
+    theta = math.asin(math.sqrt(a))
+    ok = True
+    for k in range(4):
+        state = quantum_engine.amplitude_amplification(prep, oracle, k).execute()
+        good = state.probability("10") + state.probability("11")
+        ok &= check(f"{k} iterations: good-state probability", good, math.sin((2 * k + 1) * theta) ** 2)
+    for method in ("iterative", "mle"):
+        result = quantum_engine.amplitude_estimation(prep, oracle, method=method, epsilon=0.01, seed=97)
+        low, high = result.confidence_interval
+        queries = sum(result.powers) * result.shots // len(result.powers)
+        print(f"   {method}: a = {result.estimation:.4f} in ({low:.4f}, {high:.4f}), powers {result.powers}, "
+              f"{result.oracle_queries} oracle queries")
+        ok &= (low <= a <= high and low <= result.estimation <= high and (high - low) / 2 <= 0.01 + 1e-9
+               and result.oracle_queries == queries)
+    # Marked bitstrings are an oracle too, and a seed makes the run reproducible
+    marked = quantum_engine.amplitude_estimation(prep, ["10", "11"], seed=97)
+    again = quantum_engine.amplitude_estimation(prep, oracle, seed=97)
+    ok &= marked.estimation == again.estimation
+    return ok and raises("unknown method", ValueError,
+                         lambda: quantum_engine.amplitude_estimation(prep, oracle, method="canonical"))
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Algorithm Builders and Estimators")
+    print("============================================================")
+
+    results = [
+        test_xeb(),
+        test_qaoa(),
+        test_phase_estimation(),
+        test_grover(),
+        test_tomography(),
+        test_kernel_matrix(),
+        test_amplitude_amplification(),
+    ]
+
+    if all(results):
+        print("\nALGORITHMS VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
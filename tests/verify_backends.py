@@ -0,0 +1,194 @@
+import os
+import sys
+import tempfile
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+QUBITS = 6
+DEPTH = 8
+SEEDS = (1, 2, 3)
+TOLERANCE = 1e-4
+
+
+def max_error(amplitudes, expected):
+    return max(abs(complex(*a) - complex(*b)) for a, b in zip(amplitudes, expected))
+
+
+def report(label, error):
+    print(f"   {label}: max amplitude error {error:.2e}")
+    return error < TOLERANCE
+
+
+# Clifford gates the stabilizer tableau accepts
+CLIFFORD = ["h", "s", "sdg", "x", "y", "z", "cnot", "swap"]
+
+
+def test_stabilizer():
+    print(f"\nTesting stabilizer tableaus of random {QUBITS}-qubit Clifford circuits...")
+    ok = True
+    for seed in SEEDS:
+        circuit = QuantumCircuit.random(QUBITS, DEPTH, seed=seed, gate_set=CLIFFORD)
+        tableau = circuit.execute_stabilizer()
+        state = circuit.execute()
+        # Every stabilizer of the tableau must have expectation equal to its sign
+        worst = 0.0
+        for label in tableau.stabilizers():
+            sign = -1.0 if label[0] == "-" else 1.0
+            pauli = quantum_engine.PauliString(label[1:], 1.0)
+            worst = max(worst, abs(state.expectation(pauli) - sign))
+        print(f"   seed {seed}: worst stabilizer expectation error {worst:.2e}")
+        ok &= worst < TOLERANCE
+
+    counts = QuantumCircuit.ghz(40).execute_stabilizer().measure(200, seed=4)
+    print(f"   40-qubit GHZ outcomes: {sorted(counts)}")
+    ok &= sorted(counts) == ["0" * 40, "1" * 40]
+
+    circuit = QuantumCircuit(1)
+    circuit.t(0)
+    try:
+        circuit.execute_stabilizer()
+    except quantum_engine.QuantumEngineError:
+        print("   non-Clifford circuit rejected")
+        return ok
+    print("   non-Clifford circuit accepted")
+    return False
+
+
+def test_mps():
+    print(f"\nTesting MPS runs of random {QUBITS}-qubit circuits and the truncation bookkeeping...")
+    ok = True
+    for seed in SEEDS:
+        circuit = QuantumCircuit.random(QUBITS, DEPTH, seed=seed)
+        mps = circuit.execute_mps(max_bond_dim=1 << (QUBITS // 2))
+        ok &= report(f"seed {seed}", max_error(mps.get_state_vector(), circuit.execute().get_state_vector()))
+
+    circuit = QuantumCircuit.random(QUBITS, DEPTH, seed=SEEDS[0])
+    exact = circuit.execute_mps(max_bond_dim=1 << (QUBITS // 2))
+    truncated = circuit.execute_mps(max_bond_dim=2)
+    error = truncated.truncation_error
+    state = quantum_engine.QuantumState.from_amplitudes(truncated.get_state_vector())
+    infidelity = 1.0 - state.fidelity(circuit.execute())
+    print(f"   exact truncation_error={exact.truncation_error:.2e}; chi=2 truncation_error={error:.4f}, "
+          f"infidelity {infidelity:.4f}, bond dimensions {truncated.bond_dimensions}")
+    print(f"   reported as {type(truncated).__module__}.{type(truncated).__name__}")
+
+    ghz = QuantumCircuit.ghz(40).execute_mps(max_bond_dim=4)
+    zz = ghz.expectation(quantum_engine.PauliString("Z" + "I" * 38 + "Z", 1.0))
+    print(f"   40-qubit GHZ: bond dimensions at most {max(ghz.bond_dimensions)}, <Z0 Z39> = {zz:.4f}")
+    return (ok and exact.truncation_error < TOLERANCE
+            and 0.0 < error <= 1.0
+            and max(truncated.bond_dimensions) <= 2
+            and type(truncated).__module__ == "quantum_engine"
+            and max(ghz.bond_dimensions) == 2 and abs(zz - 1.0) < TOLERANCE)
+
+
+def test_sparse():
+    print(f"\nTesting the sparse backend on random {QUBITS}-qubit circuits...")
+    ok = True
+    for seed in SEEDS:
+        circuit = QuantumCircuit.random(QUBITS, DEPTH, seed=seed)
+        sparse = circuit.execute(backend="sparse")
+        ok &= report(f"seed {seed}", max_error(sparse.to_state().get_state_vector(),
+                                               circuit.execute().get_state_vector()))
+    ghz = QuantumCircuit.ghz(30).execute(backend="sparse")
+    print(f"   30-qubit GHZ: {ghz.nnz()} non-zero amplitudes, dense={ghz.is_dense}")
+    return ok and ghz.nnz() == 2 and not ghz.is_dense and abs(ghz.probability("1" * 30) - 0.5) < TOLERANCE
+
+
+def test_disk():
+    print(f"\nTesting out-of-core runs of random {QUBITS}-qubit circuits...")
+    ok = True
+    with tempfile.TemporaryDirectory() as directory:
+        for seed in SEEDS:
+            circuit = QuantumCircuit.random(QUBITS, DEPTH, seed=seed)
+            path = os.path.join(directory, f"state{seed}.bin")
+            disk = circuit.execute_on_disk(path, block_qubits=2)
+            ok &= report(f"seed {seed}", max_error(disk.to_state().get_state_vector(),
+                                                   circuit.execute().get_state_vector()))
+            # The file is an f32 checkpoint QuantumState.load reads back
+            ok &= max_error(quantum_engine.QuantumState.load(path).get_state_vector(),
+                            disk.to_state().get_state_vector()) < TOLERANCE
+    return ok
+
+
+def test_distributed():
+    print(f"\nTesting sharded runs of random {QUBITS}-qubit circuits across worker processes...")
+    ok = True
+    for seed in SEEDS:
+        circuit = QuantumCircuit.random(QUBITS, DEPTH, seed=seed)
+        expected = circuit.execute().get_state_vector()
+        for ranks in (2, 4):
+            state = circuit.execute_distributed(ranks)
+            ok &= report(f"seed {seed}, {ranks} ranks", max_error(state.get_state_vector(), expected))
+    return ok
+
+
+def gpu_available():
+    """None when this build has no GPU backend, otherwise whether an adapter answered"""
+    try:
+        QuantumCircuit(1).execute(device="gpu")
+    except ValueError as error:
+        print(f"   {error}")
+        return None
+    except RuntimeError as error:
+        print(f"   GPU backend built but no adapter: {error}")
+        return False
+    return True
+
+
+def test_gpu():
+    print(f"\nTesting device='gpu' on random {QUBITS}-qubit circuits...")
+    circuit = QuantumCircuit.random(QUBITS, DEPTH, seed=SEEDS[0])
+    available = gpu_available()
+    rejected = []
+    for label, kwargs in (("unknown device", {"device": "tpu"}), ("CUDA", {"device": "cuda"})):
+        try:
+            circuit.execute(**kwargs)
+            rejected.append(False)
+        except ValueError as error:
+            print(f"   {label}: {error}")
+            rejected.append(True)
+    ok = all(rejected)
+    if not available:
+        # Without the feature or an adapter there is nothing to compare against
+        return ok
+    for seed in SEEDS:
+        circuit = QuantumCircuit.random(QUBITS, DEPTH, seed=seed)
+        circuit.mcx([0, 2], 4)
+        circuit.cry(1, 5, 0.6)
+        ok &= report(f"seed {seed}", max_error(circuit.execute(device="gpu").get_state_vector(),
+                                               circuit.execute().get_state_vector()))
+    try:
+        circuit.execute(device="gpu", precision="f64")
+        return False
+    except ValueError as error:
+        print(f"   f64 on the GPU: {error}")
+    return ok
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Alternative Backends Against the State Vector")
+    print("============================================================")
+
+    results = [
+        test_stabilizer(),
+        test_mps(),
+        test_sparse(),
+        test_disk(),
+        test_distributed(),
+        test_gpu(),
+    ]
+
+    if all(results):
+        print("\nBACKENDS VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
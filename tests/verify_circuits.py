@@ -0,0 +1,186 @@
+import math
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+TOLERANCE = 1e-4
+
+
+def amplitudes(state):
+    return [complex(*a) for a in state.get_state_vector()]
+
+
+def columns(circuit, qubits):
+    """Column j of the circuit's unitary: the circuit run on the basis state |j>"""
+    out = []
+    for j in range(1 << qubits):
+        prepared = QuantumCircuit(qubits)
+        prepared.initialize(format(j, f"0{qubits}b")[::-1])
+        prepared.compose(circuit)
+        out.append(amplitudes(prepared.execute()))
+    return out
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_to_unitary():
+    print("\nTesting to_unitary against the circuit run on every basis state...")
+    circuit = QuantumCircuit.random(3, 5, seed=60)
+    circuit.ccx(0, 1, 2)
+    circuit.u(1, 0.3, -0.4, 1.2)
+    try:
+        import numpy
+    except ImportError:
+        # Without numpy the matrix cannot be returned, which must raise ImportError rather than crash
+        print("   numpy not installed; checking the ImportError path only")
+        return raises("to_unitary", ImportError, circuit.to_unitary)
+    unitary = circuit.to_unitary()
+    reference = columns(circuit, 3)
+    error = max(abs(complex(unitary[i, j]) - reference[j][i]) for i in range(8) for j in range(8))
+    identity = numpy.abs(unitary.conj().T @ unitary - numpy.eye(8)).max()
+    print(f"   {unitary.shape} {unitary.dtype}: max entry error {error:.2e}, |U^dagger U - I| {identity:.2e}")
+    return (unitary.shape == (8, 8) and unitary.dtype == numpy.complex64 and error < TOLERANCE and identity < TOLERANCE
+            and raises("13 qubits", ValueError, QuantumCircuit(13).to_unitary))
+
+
+def test_equivalent():
+    print("\nTesting circuit equivalence on identities and near misses...")
+    hxh, z = QuantumCircuit(1), QuantumCircuit(1)
+    hxh.h(0)
+    hxh.x(0)
+    hxh.h(0)
+    z.z(0)
+    cnot, hczh = QuantumCircuit(2), QuantumCircuit(2)
+    cnot.cnot(0, 1)
+    hczh.h(1)
+    hczh.cphase(0, 1, math.pi)
+    hczh.h(1)
+    # RZ and P differ only by a global phase
+    rz, p = QuantumCircuit(1), QuantumCircuit(1)
+    rz.rz(0, 0.7)
+    p.p(0, 0.7)
+    near = QuantumCircuit(1)
+    near.rz(0, 0.71)
+
+    # Past 8 qubits the check probes random states instead of comparing unitaries
+    wide = QuantumCircuit.random(10, 6, seed=61)
+    nudged = QuantumCircuit.random(10, 6, seed=61)
+    nudged.rx(7, 0.05)
+    measured = QuantumCircuit(1, 1)
+    measured.measure_qubit(0, 0)
+    cases = [
+        ("H X H against Z", hxh.equivalent(z), True),
+        ("CNOT against H CZ H", cnot.equivalent(hczh), True),
+        ("RZ against P", rz.equivalent(p), True),
+        ("RZ(0.7) against RZ(0.71)", rz.equivalent(near), False),
+        ("RZ(0.7) against RZ(0.71) at tolerance 1e-3", rz.equivalent(near, tolerance=1e-3), False),
+        ("RZ(0.7) against RZ(0.71) at tolerance 0.1", rz.equivalent(near, tolerance=0.1), True),
+        ("10 qubits against its decomposition", wide.equivalent(wide.decompose(), seed=1), True),
+        ("10 qubits against a nudged copy", wide.equivalent(nudged, seed=1), False),
+        ("different widths", cnot.equivalent(z), False),
+    ]
+    ok = True
+    for label, value, expected in cases:
+        print(f"   {label}: {value}")
+        ok &= value == expected
+    return ok and raises("circuit with measurement", ValueError, lambda: measured.equivalent(measured))
+
+
+EXPECTED_DRAWING = """\
+q0: ----[H]------*--x--*--
+                 |  |  |
+q1: ------------[X]-|--*--
+                    |  |
+q2: -[RZ(0.50)]-----x-[X]-"""
+
+
+def test_draw():
+    print("\nTesting the text drawer against a reference diagram...")
+    circuit = QuantumCircuit(3)
+    circuit.h(0)
+    circuit.cnot(0, 1)
+    circuit.rz(2, 0.5)
+    circuit.swap(0, 2)
+    circuit.ccx(0, 1, 2)
+    drawing = circuit.draw()
+    print("\n".join("      " + line for line in drawing.split("\n")))
+    lines = drawing.split("\n")
+    # Every wire is padded to the same width so the columns line up
+    empty = QuantumCircuit(2).draw().split("\n")
+    print(f"   empty 2-qubit circuit: {empty}")
+    return (drawing.rstrip("\n") == EXPECTED_DRAWING and len({len(line) for line in lines if line.startswith("q")}) == 1
+            and [line.split(":")[0] for line in empty if ":" in line] == ["q0", "q1"])
+
+
+def test_introspection():
+    print("\nTesting gates, depth, count_ops and editing...")
+    circuit = QuantumCircuit(3)
+    circuit.h(0)
+    circuit.h(1)
+    circuit.cnot(0, 1)
+    circuit.rz(2, 0.5)
+    circuit.cnot(1, 2)
+    circuit.cphase(0, 2, 0.25)
+    gates = circuit.gates()
+    print(f"   gates {gates}")
+    print(f"   depth {circuit.depth()}, count_ops {circuit.count_ops()}")
+    ok = (gates[0] == ("h", [0], []) and gates[2] == ("cnot", [0, 1], []) and gates[3][0] == "rz"
+          and abs(gates[3][2][0] - 0.5) < TOLERANCE and len(circuit) == 6)
+    # h h rz | cnot | cnot | cphase: the rz on qubit 2 runs alongside the Hadamards
+    ok &= circuit.depth() == 4 and circuit.count_ops() == {"h": 2, "cnot": 2, "rz": 1, "cphase": 1}
+
+    # Rebuilding from gates() via insert gives the same circuit
+    rebuilt = QuantumCircuit(3)
+    for gate in gates:
+        rebuilt.insert(len(rebuilt), gate)
+    ok &= rebuilt.gates() == gates and rebuilt.equivalent(circuit)
+
+    # remove returns the gate and insert puts it back where it was
+    removed = circuit.remove(2)
+    ok &= removed == gates[2] and len(circuit) == 5 and not circuit.equivalent(rebuilt)
+    circuit.insert(2, removed)
+    ok &= circuit.gates() == gates
+    return ok and all([
+        raises("remove past the end", IndexError, lambda: circuit.remove(6)),
+        raises("insert past the end", IndexError, lambda: circuit.insert(8, ("h", [0], []))),
+        raises("insert an unknown gate", quantum_engine.InvalidGateError, lambda: circuit.insert(0, ("foo", [0], []))),
+        raises("insert on a missing qubit", quantum_engine.QubitIndexError, lambda: circuit.insert(0, ("h", [3], []))),
+        circuit.gates() == gates,
+    ])
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Circuit Unitaries, Equivalence, Drawing and Introspection")
+    print("============================================================")
+
+    results = [
+        test_to_unitary(),
+        test_equivalent(),
+        test_draw(),
+        test_introspection(),
+    ]
+
+    if all(results):
+        print("\nCIRCUITS VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
@@ -0,0 +1,199 @@
+import cmath
+import math
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+TOLERANCE = 1e-4
+
+
+def dft(signal):
+    """X_k = sum_n x_n e^(-2 pi i k n / N), the convention of the engine's FFT"""
+    n = len(signal)
+    return [sum(x * cmath.exp(-2j * cmath.pi * k * m / n) for m, x in enumerate(signal)) for k in range(n)]
+
+
+def reference_bands(signal, n_bands, window="none", log_scale=False):
+    """Mean DFT magnitude over n_bands contiguous bins, the last band taking the remainder"""
+    n = len(signal)
+    if window == "hann":
+        signal = [x * 0.5 * (1 - math.cos(2 * math.pi * k / n)) for k, x in enumerate(signal)]
+    magnitude = [abs(x) for x in dft(signal)]
+    width = n // n_bands
+    bands = []
+    for b in range(n_bands):
+        band = magnitude[b * width:n if b + 1 == n_bands else (b + 1) * width]
+        mean = sum(band) / len(band)
+        bands.append(math.log1p(mean) if log_scale else mean)
+    return bands
+
+
+def check(label, value, expected, tolerance=TOLERANCE):
+    print(f"   {label}: {value:.4f} (expected {expected:.4f})")
+    return abs(value - expected) < tolerance
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_resonance_spectrum():
+    print("\nTesting resonance spectra against a reference DFT...")
+    state = QuantumCircuit.random(5, 6, seed=53).execute()
+    amps = [complex(*a) for a in state.get_state_vector()]
+    rho = QuantumCircuit.random(4, 6, seed=54).execute_noisy((0.1, 0.2))
+    diagonal = [complex(p) for p in rho.diagonal()]
+    worst = 0.0
+    for n_bands, window, log_scale in ((3, "none", False), (5, "hann", False), (8, "none", True), (32, "hann", True)):
+        bands = state.resonance_spectrum(n_bands, window, log_scale)
+        expected = reference_bands(amps, n_bands, window, log_scale)
+        worst = max(worst, max(abs(a - b) for a, b in zip(bands, expected)))
+        if n_bands <= 16:
+            mixed = rho.resonance_spectrum(n_bands, window, log_scale)
+            expected = reference_bands(diagonal, n_bands, window, log_scale)
+            worst = max(worst, max(abs(a - b) for a, b in zip(mixed, expected)))
+    print(f"   four band layouts on a state and a density matrix: worst error {worst:.2e}")
+    # The legacy RGB resonance is the unwindowed 3-band spectrum, doubled and capped at 1
+    rgb = [min(2 * x, 1.0) for x in reference_bands(amps, 3)]
+    rgb_error = max(abs(a - b) for a, b in zip(state.resonance(), rgb))
+    rgb_mixed = [min(2 * x, 1.0) for x in reference_bands(diagonal, 3)]
+    rgb_error = max(rgb_error, max(abs(a - b) for a, b in zip(rho.resonance(), rgb_mixed)))
+    print(f"   RGB resonance: worst error {rgb_error:.2e}")
+    return worst < TOLERANCE and rgb_error < TOLERANCE and all([
+        raises("zero bands", ValueError, lambda: state.resonance_spectrum(0)),
+        raises("more bands than basis states", ValueError, lambda: state.resonance_spectrum(33)),
+        raises("unknown window", ValueError, lambda: state.resonance_spectrum(3, "kaiser")),
+    ])
+
+
+def reference_sonify(amps, duration_s, sample_rate):
+    """Sum of the 16 strongest DFT bins as sines at 110 * 2^(4 k / N) Hz, over their total magnitude"""
+    n = len(amps)
+    partials = [(110.0 * 2 ** (4 * k / n), abs(x), cmath.phase(x)) for k, x in enumerate(dft(amps))]
+    partials = [p for p in partials if p[0] < sample_rate / 2 and p[1] > 1e-6]
+    partials = sorted(partials, key=lambda p: -p[1])[:16]
+    total = sum(p[1] for p in partials)
+    samples = round(duration_s * sample_rate)
+    return [sum(m * math.sin(2 * math.pi * f * i / sample_rate + phase) for f, m, phase in partials) / total
+            for i in range(samples)]
+
+
+def test_sonify():
+    print("\nTesting sonify against a reference additive synthesis...")
+    state = QuantumCircuit.random(4, 5, seed=54).execute()
+    try:
+        import numpy
+    except ImportError:
+        # Without numpy the buffer cannot be returned, which must raise ImportError rather than crash
+        print("   numpy not installed; checking the ImportError path only")
+        return raises("sonify", ImportError, state.sonify)
+    buffer = state.sonify(0.02, 8000)
+    expected = reference_sonify([complex(*a) for a in state.get_state_vector()], 0.02, 8000)
+    error = max(abs(float(a) - b) for a, b in zip(buffer, expected))
+    print(f"   {len(buffer)} samples of {buffer.dtype}: max error {error:.2e}, peak {float(abs(buffer).max()):.3f}")
+    return (buffer.dtype == numpy.float32 and len(buffer) == 160 and error < 1e-3 and float(abs(buffer).max()) <= 1.0
+            and all([
+                raises("zero duration", ValueError, lambda: state.sonify(0.0, 8000)),
+                raises("zero sample rate", ValueError, lambda: state.sonify(1.0, 0)),
+                raises("absurd length", ValueError, lambda: state.sonify(1e6, 48000)),
+            ]))
+
+
+def reference_profile(n, populations, bloch, off_diagonal, purity):
+    """The formulas of the EmotionalProfile table from its raw ingredients"""
+    d = 1 << n
+    entropy = -sum(p * math.log2(p) for p in populations if p > 0) / n
+    normalized_purity = (d * purity - 1) / (d - 1)
+    return {
+        "energy": 1 - populations[0],
+        "entropy": entropy,
+        "valence": sum(z for _, _, z in bloch) / n,
+        "arousal": sum(math.hypot(x, y) for x, y, _ in bloch) / n,
+        "coherence": off_diagonal / (d - 1),
+        "purity": normalized_purity,
+        "stability": normalized_purity * (1 - entropy),
+    }
+
+
+def bloch_vectors(state, n):
+    return [tuple(state.expectation(quantum_engine.PauliString("".join(axis if k == q else "I" for k in range(n)), 1.0))
+                  for axis in "XYZ") for q in range(n)]
+
+
+def compare(label, profile, expected):
+    values = profile.to_dict()
+    error = max(abs(values[key] - expected[key]) for key in expected)
+    print(f"   {label}: " + ", ".join(f"{key} {values[key]:.3f}" for key in expected) + f"; max error {error:.2e}")
+    return error < TOLERANCE
+
+
+def test_emotional_profile():
+    print("\nTesting EmotionalProfile against its documented formulas...")
+    n = 3
+    state = QuantumCircuit.random(n, 5, seed=55).execute()
+    amps = [complex(*a) for a in state.get_state_vector()]
+    magnitudes = sum(abs(a) for a in amps)
+    pure = reference_profile(n, [abs(a) ** 2 for a in amps], bloch_vectors(state, n), magnitudes ** 2 - 1, 1.0)
+    ok = compare("pure state", quantum_engine.EmotionalProfile(state), pure)
+    ok &= compare("QuantumState.emotional_profile", state.emotional_profile(), pure)
+    ok &= state.emotional_profile().resonance == state.resonance()
+
+    rho = QuantumCircuit.random(n, 5, seed=55).execute_noisy((0.2, 0.3))
+    mixed = reference_profile(n, rho.diagonal(), bloch_vectors(rho, n), rho.l1_coherence(), rho.purity())
+    ok &= compare("noisy density matrix", rho.emotional_profile(), mixed)
+
+    # Depression (amplitude damping) relaxes towards |0>: valence rises, arousal falls
+    excited = QuantumCircuit(n)
+    for q in range(n):
+        excited.ry(q, 2.2)
+
+    def damped(channel):
+        rho = quantum_engine.DensityMatrix(n)
+        rho.from_pure_state(excited.execute())
+        if channel:
+            getattr(rho, channel)(0.6)
+        return rho.emotional_profile()
+
+    calm, depressed = damped(None), damped("apply_amplitude_damping")
+    # Anxiety (phase damping) leaves valence alone but erodes arousal and coherence
+    anxious = damped("apply_phase_damping")
+    print(f"   valence {calm.valence:.3f} -> {depressed.valence:.3f} under damping, "
+          f"arousal {calm.arousal:.3f} -> {anxious.arousal:.3f} under dephasing")
+    return (ok and depressed.valence > calm.valence and depressed.arousal < calm.arousal
+            and abs(anxious.valence - calm.valence) < TOLERANCE and anxious.arousal < calm.arousal
+            and anxious.coherence < calm.coherence
+            and raises("profile of a circuit", TypeError, lambda: quantum_engine.EmotionalProfile(excited)))
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Resonance Spectra, Sonification and Emotional Profiles")
+    print("============================================================")
+
+    results = [
+        test_resonance_spectrum(),
+        test_sonify(),
+        test_emotional_profile(),
+    ]
+
+    if all(results):
+        print("\nEMOTION VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
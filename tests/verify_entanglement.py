@@ -0,0 +1,399 @@
+import itertools
+import math
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+QuantumState = quantum_engine.QuantumState
+TOLERANCE = 1e-4
+
+
+def check(label, value, expected):
+    print(f"   {label}: {value:.4f} (expected {expected:.4f})")
+    return abs(value - expected) < TOLERANCE
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_negativity():
+    print("\nTesting negativity of the partial transpose...")
+    bell = QuantumCircuit.bell().execute_density()
+    product = QuantumCircuit(2)
+    product.h(0)
+    product.h(1)
+    noisy = QuantumCircuit.bell().execute_noisy((0.0, 0.0))
+    ghz = QuantumCircuit.ghz(3).execute_density()
+    return all([
+        check("Bell pair", bell.negativity([0]), 0.5),
+        check("Bell pair, other side", bell.negativity([1]), 0.5),
+        check("product state", product.execute_density().negativity([0]), 0.0),
+        check("noiseless execute_noisy Bell pair", noisy.negativity([0]), 0.5),
+        check("GHZ(3), one qubit against two", ghz.negativity([0]), 0.5),
+        raises("repeated qubit", ValueError, lambda: bell.negativity([0, 0])),
+        raises("qubit out of range", quantum_engine.QubitIndexError, lambda: bell.negativity([2])),
+        raises("empty partition", ValueError, lambda: bell.negativity([])),
+    ])
+
+
+def test_mutual_information():
+    print("\nTesting mutual information between qubit subsets...")
+    bell = QuantumCircuit.bell()
+    ghz = QuantumCircuit.ghz(3)
+    product = QuantumCircuit(2)
+    product.h(0)
+    product.ry(1, 0.7)
+    return all([
+        check("Bell pair I(0:1), state", bell.execute().mutual_information([0], [1]), 2.0),
+        check("Bell pair I(0:1), density matrix", bell.execute_density().mutual_information([0], [1]), 2.0),
+        check("GHZ(3) I(0:1)", ghz.execute().mutual_information([0], [1]), 1.0),
+        check("GHZ(3) I(0:12)", ghz.execute().mutual_information([0], [1, 2]), 2.0),
+        check("product state I(0:1)", product.execute().mutual_information([0], [1]), 0.0),
+        raises("overlapping subsets", ValueError, lambda: bell.execute().mutual_information([0], [0])),
+        raises("qubit out of range", quantum_engine.QubitIndexError, lambda: bell.execute().mutual_information([0], [2])),
+    ])
+
+
+def test_concurrence():
+    print("\nTesting Wootters concurrence...")
+    ok = True
+    for theta in (0.0, 0.6, math.pi / 2):
+        # cos(theta/2)|00> + sin(theta/2)|11> has concurrence sin(theta)
+        circuit = QuantumCircuit(3)
+        circuit.ry(0, theta)
+        circuit.cnot(0, 2)
+        state = circuit.execute()
+        ok &= check(f"theta={theta:.2f}, state", state.concurrence(0, 2), math.sin(theta))
+        ok &= check(f"theta={theta:.2f}, density matrix", circuit.execute_density().concurrence(2, 0), math.sin(theta))
+    # Tracing out the third GHZ qubit leaves a classically correlated, unentangled pair
+    ok &= check("GHZ(3) qubits 0 and 1", QuantumCircuit.ghz(3).execute().concurrence(0, 1), 0.0)
+    return ok and all([
+        raises("same qubit twice", ValueError, lambda: QuantumCircuit.bell().execute().concurrence(1, 1)),
+        raises("qubit out of range", quantum_engine.QubitIndexError,
+               lambda: QuantumCircuit.bell().execute().concurrence(0, 4)),
+    ])
+
+
+def test_magic():
+    print("\nTesting the stabilizer Renyi entropy...")
+    t_state = QuantumCircuit(1)
+    t_state.h(0)
+    t_state.t(0)
+    clifford = QuantumCircuit.random(4, 10, seed=3, gate_set=["h", "s", "x", "cnot", "swap"])
+    two_t = QuantumCircuit(2)
+    for q in range(2):
+        two_t.h(q)
+        two_t.t(q)
+    # T|+> has <X> = <Y> = 1/sqrt(2), so M2 = -log2((1 + 1/4 + 1/4) / 2) = log2(4/3)
+    return all([
+        check("|0>", QuantumState(1).magic(), 0.0),
+        check("random 4-qubit Clifford circuit", clifford.execute().magic(), 0.0),
+        check("T|+>", t_state.execute().magic(), math.log2(4 / 3)),
+        check("T|+> (x) T|+> is additive", two_t.execute().magic(), 2 * math.log2(4 / 3)),
+        raises("9 qubits", ValueError, lambda: QuantumState(9).magic()),
+    ])
+
+
+def reference_reduced(amps, qubits, keep):
+    """rho_keep[i][j] = sum over the traced-out bits of psi[i, e] psi*[j, e]"""
+    rest = [q for q in range(qubits) if q not in keep]
+    dim = 1 << len(keep)
+
+    def index(local, env):
+        full = sum((local >> k & 1) << q for k, q in enumerate(keep))
+        return full | sum((env >> k & 1) << q for k, q in enumerate(rest))
+
+    return [[sum(amps[index(i, e)] * amps[index(j, e)].conjugate() for e in range(1 << len(rest)))
+             for j in range(dim)] for i in range(dim)]
+
+
+def test_partial_trace():
+    print("\nTesting reduced density matrices against a reference partial trace...")
+    state = QuantumCircuit.random(4, 6, seed=50).execute()
+    amps = [complex(*a) for a in state.get_state_vector()]
+    ok = True
+    for keep in ([1], [2, 0], [3, 1, 2]):
+        reduced = state.reduced_density_matrix(keep)
+        mixed = quantum_engine.DensityMatrix(4)
+        mixed.from_pure_state(state)
+        traced = mixed.partial_trace(keep)
+        reference = reference_reduced(amps, 4, keep)
+        populations = [reference[i][i].real for i in range(1 << len(keep))]
+        # Every Pauli on the kept qubits must agree with the same Pauli on the full state
+        worst = 0.0
+        for labels in itertools.product("IXYZ", repeat=len(keep)):
+            local = quantum_engine.PauliString("".join(labels), 1.0)
+            full = ["I"] * 4
+            for k, q in enumerate(keep):
+                full[q] = labels[k]
+            expected = state.expectation(quantum_engine.PauliString("".join(full), 1.0))
+            worst = max(worst, abs(reduced.expectation(local) - expected), abs(traced.expectation(local) - expected))
+        error = max(max(abs(a - b) for a, b in zip(reduced.diagonal(), populations)),
+                    max(abs(a - b) for a, b in zip(traced.diagonal(), populations)))
+        purity = sum(abs(reference[i][j]) ** 2 for i in range(len(reference)) for j in range(len(reference)))
+        print(f"   keep {keep}: population error {error:.2e}, worst Pauli error {worst:.2e}, purity {reduced.purity():.4f}")
+        ok &= error < TOLERANCE and worst < TOLERANCE and abs(reduced.purity() - purity) < TOLERANCE
+    return ok and all([
+        raises("repeated qubit", ValueError, lambda: state.reduced_density_matrix([1, 1])),
+        raises("qubit out of range", quantum_engine.QubitIndexError, lambda: state.reduced_density_matrix([4])),
+        raises("nothing kept", ValueError, lambda: state.reduced_density_matrix([])),
+    ])
+
+
+def binary_entropy(p):
+    return -sum(x * math.log2(x) for x in (p, 1 - p) if x > 0)
+
+
+def test_entanglement_entropy():
+    print("\nTesting entanglement entropy and mutual information on known states...")
+    theta = 0.4
+    schmidt = QuantumCircuit(2)
+    schmidt.ry(0, 2 * theta)
+    schmidt.cnot(0, 1)
+    partial = schmidt.execute()
+    bell = QuantumCircuit.bell().execute()
+    ghz = QuantumCircuit.ghz(4).execute()
+    product = QuantumCircuit.random(3, 4, seed=60, gate_set=["rx", "ry", "rz"]).execute()
+    random_state = QuantumCircuit.random(5, 8, seed=61).execute()
+    return all([
+        check("Bell pair S(0)", bell.entanglement_entropy([0]), 1.0),
+        check("cos|00> + sin|11> S(0)", partial.entanglement_entropy([0]), binary_entropy(math.cos(theta) ** 2)),
+        check("GHZ(4) S(0, 1)", ghz.entanglement_entropy([0, 1]), 1.0),
+        check("GHZ(4) S(3)", ghz.entanglement_entropy([3]), 1.0),
+        check("product state S(1)", product.entanglement_entropy([1]), 0.0),
+        # S(A) = S(B) for a pure state, whichever side is given
+        check("random state S(0, 2) against S(1, 3, 4)", random_state.entanglement_entropy([0, 2]),
+              random_state.entanglement_entropy([1, 3, 4])),
+        check("Bell pair I(0:1)", bell.mutual_information([0], [1]), 2.0),
+        check("GHZ(4) I(0:1)", ghz.mutual_information([0], [1]), 1.0),
+        check("product state I(0:2)", product.mutual_information([0], [2]), 0.0),
+        # I(A:B) = S(A) + S(B) - S(AB), and for a pure state S(AB) is S of the rest
+        check("random state I(0:1, 2)", random_state.mutual_information([0], [1, 2]),
+              random_state.entanglement_entropy([0]) + random_state.entanglement_entropy([1, 2])
+              - random_state.entanglement_entropy([3, 4])),
+        raises("overlapping subsets", ValueError, lambda: bell.mutual_information([0], [0])),
+        raises("partition out of range", quantum_engine.QubitIndexError, lambda: bell.entanglement_entropy([2])),
+    ])
+
+
+def test_von_neumann_entropy():
+    print("\nTesting DensityMatrix.entropy against known spectra...")
+    DensityMatrix = quantum_engine.DensityMatrix
+    zero = QuantumState(1)
+    plus = QuantumState(1)
+    plus.apply_gate("H", 0, None)
+    # Equal mixture of |0> and |+> has eigenvalues (1 +- 1/sqrt(2)) / 2, not its diagonal
+    overlap = DensityMatrix.from_ensemble([zero, plus], [0.5, 0.5])
+    spectrum = [(1 + 0.5 ** 0.5) / 2, (1 - 0.5 ** 0.5) / 2]
+    basis = []
+    for bits in ("00", "10", "01", "11"):
+        circuit = QuantumCircuit(2)
+        circuit.initialize(bits)
+        basis.append(circuit.execute())
+    weights = [0.5, 0.3, 0.2]
+    orthogonal = DensityMatrix.from_ensemble(basis[:3], weights)
+    maximal = DensityMatrix.from_ensemble(basis, [0.25] * 4)
+    pure = DensityMatrix(3)
+    pure.from_pure_state(QuantumCircuit.random(3, 5, seed=70).execute())
+    noisy = QuantumCircuit.ghz(3).execute_noisy((0.2, 0.1))
+    wide = quantum_engine.DensityMatrixF64(3)
+    excited = quantum_engine.QuantumStateF64(3)
+    excited.apply_gate("X", 0)
+    wide.from_pure_state(excited)
+    wide.apply_amplitude_damping(0.3, 0)
+    return all([
+        check("|0>, |+> mixture", overlap.entropy(), binary_entropy(spectrum[0])),
+        check("orthogonal mixture", orthogonal.entropy(), -sum(w * math.log2(w) for w in weights)),
+        check("maximally mixed pair", maximal.entropy(), 2.0),
+        check("pure state", pure.entropy(), 0.0),
+        check("linear_entropy of the |0>, |+> mixture", overlap.linear_entropy(), 1 - sum(p * p for p in spectrum)),
+        check("linear_entropy is 1 - purity", noisy.linear_entropy(), 1 - noisy.purity()),
+        # Damping |1> with p = 0.3 leaves populations 0.3 and 0.7 and no coherence
+        check("DensityMatrixF64 after damping |1>", wide.entropy(), binary_entropy(0.3)),
+    ])
+
+
+def qubit_mixture(angles, weights):
+    """Mixture of the single-qubit states RY(a) RZ(a / 2)|0>"""
+    states = []
+    for angle in angles:
+        state = QuantumState(1)
+        state.apply_gate("RY", 0, angle)
+        state.apply_gate("RZ", 0, angle / 2)
+        states.append(state)
+    return quantum_engine.DensityMatrix.from_ensemble(states, weights)
+
+
+def bloch_vector(rho):
+    return [rho.expectation(quantum_engine.PauliString(p, 1.0)) for p in "XYZ"]
+
+
+def test_fidelity_and_trace_distance():
+    print("\nTesting fidelity and trace distance against closed forms...")
+    a = QuantumCircuit.random(3, 5, seed=80).execute()
+    b = QuantumCircuit.random(3, 5, seed=81).execute()
+    overlap = abs(sum(x.conjugate() * y for x, y in zip(
+        [complex(*v) for v in a.get_state_vector()], [complex(*v) for v in b.get_state_vector()]))) ** 2
+    ok = all([
+        check("pure fidelity", a.fidelity(b), overlap),
+        check("pure trace distance", a.trace_distance(b), (1 - overlap) ** 0.5),
+        check("fidelity with itself", a.fidelity(a), 1.0),
+    ])
+
+    # Single qubits: F = (1 + r.s + sqrt((1 - |r|^2)(1 - |s|^2))) / 2 and D = |r - s| / 2
+    rho = qubit_mixture([0.3, 2.0], [0.7, 0.3])
+    sigma = qubit_mixture([-1.1, 0.9, 2.6], [0.2, 0.5, 0.3])
+    r, s = bloch_vector(rho), bloch_vector(sigma)
+    dot = sum(x * y for x, y in zip(r, s))
+    fidelity = (1 + dot + ((1 - sum(x * x for x in r)) * (1 - sum(y * y for y in s))) ** 0.5) / 2
+    distance = sum((x - y) ** 2 for x, y in zip(r, s)) ** 0.5 / 2
+    ok &= all([
+        check("Uhlmann fidelity of two mixed qubits", rho.fidelity(sigma), fidelity),
+        check("... in the other order", sigma.fidelity(rho), fidelity),
+        check("trace distance of two mixed qubits", rho.trace_distance(sigma), distance),
+    ])
+
+    # Against a pure state the Uhlmann fidelity reduces to <psi|sigma|psi>, here the
+    # average of sigma over the GHZ stabilizer group
+    noisy = QuantumCircuit.ghz(3).execute_noisy((0.1, 0.05))
+    ideal = quantum_engine.DensityMatrix(3)
+    ideal.from_pure_state(QuantumCircuit.ghz(3).execute())
+    group = [(1, "III"), (1, "ZZI"), (1, "IZZ"), (1, "ZIZ"), (1, "XXX"), (-1, "YYX"), (-1, "YXY"), (-1, "XYY")]
+    projector = quantum_engine.Hamiltonian([(sign / 8, label) for sign, label in group])
+    return ok and all([
+        check("noisy GHZ against the ideal state", noisy.fidelity(ideal), noisy.expectation(projector)),
+        check("... in the other order", ideal.fidelity(noisy), noisy.expectation(projector)),
+        check("identical matrices", noisy.trace_distance(noisy), 0.0),
+        raises("fidelity across widths", ValueError, lambda: noisy.fidelity(rho)),
+    ])
+
+
+def test_coherence_metrics():
+    print("\nTesting purity, l1 coherence and participation ratio on both state types...")
+    state = QuantumCircuit.random(4, 5, seed=56).execute()
+    amps = [complex(*a) for a in state.get_state_vector()]
+    populations = [abs(a) ** 2 for a in amps]
+    l1 = sum(abs(a) * abs(b) for i, a in enumerate(amps) for j, b in enumerate(amps) if i != j)
+    ratio = 1 / sum(p * p for p in populations)
+    rho = quantum_engine.DensityMatrix(4)
+    rho.from_pure_state(state)
+    # (|0><0| + |+><+|) / 2 = [[3/4, 1/4], [1/4, 1/4]]
+    plus = QuantumState(1)
+    plus.apply_gate("H", 0, None)
+    mixture = quantum_engine.DensityMatrix.from_ensemble([QuantumState(1), plus], [0.5, 0.5])
+    return all([
+        check("pure purity", state.purity(), 1.0),
+        check("pure l1 coherence", state.l1_coherence(), l1),
+        check("pure participation ratio", state.participation_ratio(), ratio),
+        check("density matrix of the same state: purity", rho.purity(), 1.0),
+        check("... l1 coherence", rho.l1_coherence(), l1),
+        check("... participation ratio", rho.participation_ratio(), ratio),
+        check("mixture purity", mixture.purity(), 0.75),
+        check("mixture l1 coherence", mixture.l1_coherence(), 0.5),
+        check("mixture participation ratio", mixture.participation_ratio(), 1 / (0.75 ** 2 + 0.25 ** 2)),
+        check("basis state l1 coherence", QuantumState(3).l1_coherence(), 0.0),
+    ])
+
+
+def product_amplitudes(a, b, qubits, partition):
+    """Amplitudes of |a> on `partition` (qubit k of a is partition[k]) times |b> on the rest, in order"""
+    rest = [q for q in range(qubits) if q not in partition]
+    out = [0j] * (1 << qubits)
+    for i, x in enumerate(a):
+        for j, y in enumerate(b):
+            index = sum((i >> k & 1) << q for k, q in enumerate(partition))
+            out[index | sum((j >> k & 1) << q for k, q in enumerate(rest))] = x * y
+    return out
+
+
+def test_schmidt_decomposition():
+    print("\nTesting Schmidt decompositions by rebuilding the state from them...")
+    ok = True
+    state = QuantumCircuit.random(5, 6, seed=77).execute()
+    amps = [complex(*a) for a in state.get_state_vector()]
+    for partition in ([0], [3, 1], [4, 0, 2]):
+        coefficients, left, right = state.schmidt_decomposition(partition, vectors=True)
+        left = [[complex(*a) for a in v.get_state_vector()] for v in left]
+        right = [[complex(*a) for a in v.get_state_vector()] for v in right]
+        rebuilt = [0j] * len(amps)
+        for s, a, b in zip(coefficients, left, right):
+            rebuilt = [r + s * p for r, p in zip(rebuilt, product_amplitudes(a, b, 5, partition))]
+        error = max(abs(x - y) for x, y in zip(rebuilt, amps))
+        overlap = max(abs(sum(x.conjugate() * y for x, y in zip(u, v)) - (i == j))
+                      for vs in (left, right) for i, u in enumerate(vs) for j, v in enumerate(vs))
+        entropy = -sum(s * s * math.log2(s * s) for s in coefficients)
+        print(f"   partition {partition}: coefficients {[round(s, 4) for s in coefficients]}, "
+              f"rebuild error {error:.2e}, worst orthonormality error {overlap:.2e}")
+        ok &= (error < TOLERANCE and overlap < TOLERANCE
+               and coefficients == sorted(coefficients, reverse=True)
+               and state.schmidt_decomposition(partition) == coefficients
+               and check(f"partition {partition}, entropy", entropy, state.entanglement_entropy(partition)))
+    # A product state has Schmidt rank one, a Bell pair rank two with equal weights
+    product = QuantumCircuit.random(3, 2, seed=78, gate_set=["rx", "ry"]).execute().schmidt_decomposition([1])
+    bell = QuantumCircuit.bell(0, 2).execute().schmidt_decomposition([2])
+    print(f"   product state {product}, Bell pair {bell}")
+    return (ok and len(product) == 1 and abs(product[0] - 1.0) < TOLERANCE
+            and len(bell) == 2 and all(abs(s - 1 / math.sqrt(2)) < TOLERANCE for s in bell)
+            and raises("qubit out of range", quantum_engine.QubitIndexError,
+                       lambda: state.schmidt_decomposition([5])))
+
+
+def test_mixed_concurrence():
+    print("\nTesting concurrence of Bell pairs under noise against closed forms...")
+    ok = True
+    for p in (0.0, 0.1, 0.3, 0.6):
+        # Depolarizing one half leaves a Bell-diagonal state of fidelity 1 - p
+        rho = QuantumCircuit.bell(0, 2, qubits=3).execute_density()
+        rho.apply_depolarizing(2, p)
+        ok &= check(f"depolarizing p={p}", rho.concurrence(0, 2), max(0.0, 1.0 - 2.0 * p))
+    for gamma in (0.2, 0.5, 0.9):
+        # Damping one half keeps the X-state coherence sqrt(1 - gamma)/2 and gives no |01> population
+        rho = QuantumCircuit.bell().execute_density()
+        rho.apply_amplitude_damping(gamma, qubit=1)
+        ok &= check(f"amplitude damping gamma={gamma}", rho.concurrence(1, 0), math.sqrt(1.0 - gamma))
+    rho = QuantumCircuit.bell().execute_density()
+    rho.apply_phase_flip(0, 0.5)
+    return ok and check("fully dephased Bell pair", rho.concurrence(0, 1), 0.0)
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Entanglement and Information Measures")
+    print("============================================================")
+
+    results = [
+        test_negativity(),
+        test_mutual_information(),
+        test_concurrence(),
+        test_magic(),
+        test_partial_trace(),
+        test_entanglement_entropy(),
+        test_von_neumann_entropy(),
+        test_fidelity_and_trace_distance(),
+        test_coherence_metrics(),
+        test_schmidt_decomposition(),
+        test_mixed_concurrence(),
+    ]
+
+    if all(results):
+        print("\nENTANGLEMENT VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
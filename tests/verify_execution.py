@@ -0,0 +1,556 @@
+import collections
+import math
+import sys
+import threading
+import time
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+TOLERANCE = 1e-4
+
+
+def amplitudes(state):
+    return [complex(*a) for a in state.get_state_vector()]
+
+
+def max_error(a, b):
+    return max(abs(x - y) for x, y in zip(a, b))
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_double_precision():
+    print("\nTesting double-precision execution on a deep circuit followed by its inverse...")
+    # Angles are stored as f32, but the inverse negates exactly the same values,
+    # so U^dagger U is the identity up to the rounding of the simulation itself
+    circuit = QuantumCircuit.random(8, 200, seed=9)
+    circuit.compose(circuit.inverse())
+    start = [1.0] + [0.0] * 255
+    wide = circuit.execute(precision="f64")
+    error64 = max_error(amplitudes(wide), start)
+    error32 = max_error(amplitudes(circuit.execute(transpile_level=0)), start)
+    print(f"   {type(wide).__name__}: max amplitude error {error64:.2e} (f32: {error32:.2e}), "
+          f"norm - 1 = {wide.norm() - 1:.1e}")
+    rho = QuantumCircuit.ghz(3).execute_density(precision="f64")
+    print(f"   {type(rho).__name__}: trace - 1 = {rho.trace() - 1:.1e}, purity {rho.purity():.12f}")
+    return (type(wide).__name__ == "QuantumStateF64" and error64 < 1e-11 < error32
+            and abs(wide.norm() - 1) < 1e-12
+            and abs(rho.trace() - 1) < 1e-12 and abs(rho.purity() - 1) < 1e-12
+            and raises("unknown precision", ValueError, lambda: circuit.execute(precision="f16")))
+
+
+def test_numpy_interop():
+    print("\nTesting numpy views of states and density matrices...")
+    state = QuantumCircuit.random(3, 4, seed=6).execute()
+    rho = QuantumCircuit.bell().execute_density()
+    try:
+        import numpy
+    except ImportError:
+        # Without numpy the conversions must raise ImportError rather than crash
+        print("   numpy not installed; checking the ImportError path only")
+        return all([
+            raises("QuantumState.to_numpy", ImportError, state.to_numpy),
+            raises("DensityMatrix.to_numpy", ImportError, rho.to_numpy),
+        ])
+    vector = state.to_numpy()
+    matrix = rho.to_numpy()
+    wide = QuantumCircuit.bell().execute(precision="f64").to_numpy()
+    error = max(abs(complex(a) - b) for a, b in zip(vector, amplitudes(state)))
+    print(f"   state {vector.dtype} {vector.shape}, density matrix {matrix.dtype} {matrix.shape}, f64 {wide.dtype}; "
+          f"max amplitude error {error:.2e}")
+    return (vector.dtype == numpy.complex64 and vector.shape == (8,) and error < TOLERANCE
+            and matrix.dtype == numpy.complex64 and matrix.shape == (4, 4)
+            and abs(matrix[0, 3] - 0.5) < TOLERANCE and abs(numpy.trace(matrix) - 1) < TOLERANCE
+            and wide.dtype == numpy.complex128)
+
+
+def test_initial_states():
+    print("\nTesting from_amplitudes and QuantumCircuit.initialize...")
+    norm = 30 ** 0.5
+    custom = [(1 / norm, 2 / norm), (0.0, -3 / norm), (4 / norm, 0.0), (0.0, 0.0)]
+    state = quantum_engine.QuantumState.from_amplitudes(custom)
+    ok = max_error(amplitudes(state), [complex(*a) for a in custom]) < TOLERANCE
+
+    # A bitstring starts from that basis state (character i is qubit i)
+    circuit = QuantumCircuit(3)
+    circuit.initialize("101")
+    probabilities = circuit.execute().probabilities()
+    print(f"   initialize('101') populates index {probabilities.index(max(probabilities))}")
+    ok &= abs(probabilities[0b101] - 1.0) < TOLERANCE
+
+    # Amplitudes or a QuantumState run the gates on that input
+    after = QuantumCircuit.random(2, 4, seed=7)
+    expected = QuantumCircuit(2)
+    expected.initialize(custom)
+    expected.compose(after)
+    from_state = QuantumCircuit(2)
+    from_state.initialize(state)
+    from_state.compose(after)
+    reference = quantum_engine.QuantumState.from_amplitudes(custom)
+    for name, qubits, params in after.gates():
+        if len(qubits) == 2:
+            reference.apply_cnot(qubits[0], qubits[1])
+        else:
+            reference.apply_gate(name.upper(), qubits[0], params[0] if params else None)
+    error = max(max_error(amplitudes(expected.execute()), amplitudes(reference)),
+                max_error(amplitudes(from_state.execute()), amplitudes(reference)))
+    print(f"   gates after initialize: max amplitude error {error:.2e}")
+    ok &= error < TOLERANCE
+
+    wrong = QuantumCircuit(2)
+    return ok and all([
+        raises("unnormalized amplitudes", ValueError,
+               lambda: quantum_engine.QuantumState.from_amplitudes([(1.0, 0.0), (1.0, 0.0)])),
+        raises("length not a power of two", ValueError,
+               lambda: quantum_engine.QuantumState.from_amplitudes([(1.0, 0.0), (0.0, 0.0), (0.0, 0.0)])),
+        raises("bitstring of the wrong width", ValueError, lambda: wrong.initialize("101")),
+        raises("bitstring with a stray character", ValueError, lambda: wrong.initialize("1x")),
+        raises("amplitudes for the wrong width", ValueError, lambda: wrong.initialize([(1.0, 0.0), (0.0, 0.0)])),
+    ])
+
+
+def longest_stall(run):
+    """Longest gap between ticks of a Python thread while `run()` executes,
+    and the duration of the run"""
+    ticks, stop = [], threading.Event()
+
+    def ticker():
+        while not stop.is_set():
+            ticks.append(time.perf_counter())
+            time.sleep(0.002)
+
+    thread = threading.Thread(target=ticker)
+    thread.start()
+    time.sleep(0.02)
+    start = time.perf_counter()
+    run()
+    end = time.perf_counter()
+    stop.set()
+    thread.join()
+    inside = [start] + [t for t in ticks if start < t < end] + [end]
+    return max(b - a for a, b in zip(inside, inside[1:])), end - start
+
+
+def test_gil_release():
+    print("\nTesting that long executions let other Python threads run...")
+    ok = True
+    runs = (
+        ("execute", QuantumCircuit.random(14, 20, seed=38).execute),
+        ("execute_noisy", lambda: QuantumCircuit.random(9, 30, seed=38).execute_noisy((0.01, 0.01))),
+    )
+    for label, run in runs:
+        stall, duration = longest_stall(run)
+        # Holding the GIL would stall the ticker for the whole run
+        print(f"   {label}: {duration * 1000:.0f} ms, longest stall {stall * 1000:.1f} ms")
+        ok &= stall < 0.25 * duration
+    return ok
+
+
+def test_execute_async():
+    print("\nTesting background execution, progress callbacks and cancellation...")
+    circuit = QuantumCircuit.random(6, 10, seed=39)
+    total = len(circuit.gates())
+    progress = []
+    handle = circuit.execute_async(callback=lambda done, count, entropy: progress.append((done, count, entropy)),
+                                   callback_every=7)
+    state = handle.result()
+    expected = circuit.execute()
+    error = max_error(amplitudes(state), amplitudes(expected))
+    done = [d for d, _, _ in progress]
+    print(f"   {total} gates: callbacks at {done}, max amplitude error {error:.2e}")
+    # Every 7 gates and once more after the last, with the running entropy
+    ok = (error < TOLERANCE and handle.is_done() and not handle.cancelled
+          and done == list(range(7, total, 7)) + [total]
+          and all(count == total for _, count, _ in progress)
+          and abs(progress[-1][2] - expected.entropy()) < TOLERANCE)
+
+    # Cancelling from the callback stops the run before the next gate
+    long_circuit = QuantumCircuit.random(10, 200, seed=40)
+    seen, handles, ready = [], [], threading.Event()
+
+    def stop_early(done, count, entropy):
+        seen.append(done)
+        if done >= 20:
+            ready.wait()
+            handles[0].cancel()
+
+    handles.append(long_circuit.execute_async(callback=stop_early, callback_every=10))
+    ready.set()
+    handle = handles[0]
+    ok &= raises("result after cancel", RuntimeError, handle.result)
+    print(f"   cancelled after {seen[-1]} of {len(long_circuit.gates())} gates")
+    return ok and handle.cancelled and handle.is_done() and seen[-1] == 20
+
+
+def prefix(circuit, gates):
+    """Copy of `circuit` keeping only its first `gates` instructions"""
+    copy = circuit.bind({})
+    while len(copy.gates()) > gates:
+        copy.remove(len(copy.gates()) - 1)
+    return copy
+
+
+def test_execute_traced():
+    print("\nTesting observable traces against executions of each circuit prefix...")
+    circuit = QuantumCircuit.random(4, 5, seed=52)
+    pauli = quantum_engine.PauliString("ZIXI", 1.0)
+    observable = quantum_engine.Observable([quantum_engine.PauliString("XXII", 0.5),
+                                            quantum_engine.PauliString("IIZY", -1.0)])
+    total = len(circuit.gates())
+    steps, series = circuit.execute_traced(["energy", "entropy", "resonance", pauli, observable], every=3)
+    energy, entropy, resonance, paulis, sums = series
+    ok = steps == list(range(0, total, 3)) + [total]
+    worst = 0.0
+    for k, count in enumerate(steps):
+        state = prefix(circuit, count).execute()
+        worst = max(worst, abs(energy[k] - state.expectation_value()), abs(entropy[k] - state.entropy()),
+                    max(abs(a - b) for a, b in zip(resonance[k], state.resonance())),
+                    abs(paulis[k] - state.expectation(pauli)), abs(sums[k] - state.expectation(observable)))
+    print(f"   {total} gates traced at {steps}: worst error against prefix runs {worst:.2e}")
+    return ok and worst < TOLERANCE and all([
+        raises("unknown observable name", ValueError, lambda: circuit.execute_traced(["happiness"])),
+        raises("every = 0", ValueError, lambda: circuit.execute_traced(["energy"], every=0)),
+    ])
+
+
+def test_profiled():
+    print("\nTesting the execute_profiled report against the circuit it timed...")
+    circuit = QuantumCircuit.random(8, 6, seed=73)
+    report = circuit.execute_profiled(seed=1, transpile_level=0)
+    counts = {name: entry["count"] for name, entry in report["gate_types"].items()}
+    gate_time = sum(entry["total_s"] for entry in report["gate_types"].values())
+    means = all(abs(entry["mean_s"] * entry["count"] - entry["total_s"]) < 1e-9
+                for entry in report["gate_types"].values())
+    print(f"   counts {counts}, {len(report['layer_times_s'])} layers for depth {circuit.depth()}, "
+          f"{report['gates']} gates, state {report['state_bytes']} B, peak {report['peak_memory_bytes']} B")
+    ok = (counts == circuit.count_ops() and report["gates"] == len(circuit.gates()) and means
+          and len(report["layer_times_s"]) == circuit.depth() and report["qubits"] == 8
+          and report["state_bytes"] == 8 << 8 and report["peak_memory_bytes"] >= report["state_bytes"]
+          and gate_time <= report["total_time_s"]
+          and abs(sum(report["layer_times_s"]) - gate_time) < 1e-6)
+
+    # Long enough for the CPU time to mean something
+    long = QuantumCircuit.random(16, 10, seed=74).execute_profiled(seed=1, transpile_level=0)
+    utilization = long["thread_utilization"]
+    print(f"   16 qubits: {long['total_time_s']:.3f} s wall, {long['cpu_time_s']} s CPU on "
+          f"{long['threads']} thread(s), utilization {utilization}")
+    if utilization is not None:
+        ok &= 0.0 < utilization <= 1.05
+    return ok and long["threads"] >= 1
+
+
+def expected_layers(qubits, depth, single, double):
+    """(gate names allowed, qubits) of every instruction QuantumCircuit.random emits, in order"""
+    slots = []
+    for layer in range(depth):
+        if single:
+            slots += [(single, [q]) for q in range(qubits)]
+        if double:
+            slots += [(double, [a, a + 1]) for a in range(layer % 2, qubits - 1, 2)]
+    return slots
+
+
+def test_random_circuits():
+    print("\nTesting the layout, gate set and seeding of QuantumCircuit.random...")
+    ok = True
+    for qubits, depth, gate_set in ((5, 4, None), (6, 3, ["h", "t", "u", "iswap", "crx"]), (4, 2, ["rzz"]), (3, 3, ["s", "z"])):
+        names = gate_set or ["rx", "ry", "rz", "cnot"]
+        single = {n for n in names if n in ("h", "t", "s", "z", "u", "rx", "ry", "rz")}
+        double = set(names) - single
+        circuit = QuantumCircuit.random(qubits, depth, seed=75, gate_set=gate_set)
+        gates = circuit.gates()
+        slots = expected_layers(qubits, depth, single, double)
+        layout = len(gates) == len(slots) and all(
+            name in allowed and targets == wires for (name, targets, _), (allowed, wires) in zip(gates, slots))
+        angles = all(0.0 <= p < 2 * math.pi for _, _, params in gates for p in params)
+        norm = sum(abs(a) ** 2 for a in amplitudes(circuit.execute()))
+        print(f"   {qubits} qubits, depth {depth}, {names}: {len(gates)} gates, layout {layout}, "
+              f"angles in [0, 2pi) {angles}, norm {norm:.6f}")
+        ok &= layout and angles and abs(norm - 1.0) < TOLERANCE
+        ok &= QuantumCircuit.random(qubits, depth, seed=75, gate_set=gate_set).gates() == gates
+        ok &= QuantumCircuit.random(qubits, depth, seed=76, gate_set=gate_set).gates() != gates
+    return (ok and raises("two-qubit gate outside the drawable set", quantum_engine.InvalidGateError,
+                          lambda: QuantumCircuit.random(3, 2, gate_set=["cz"]))
+            and raises("empty gate set", ValueError, lambda: QuantumCircuit.random(3, 2, gate_set=[])))
+
+
+DEVICE_SHOTS = 2000
+
+
+def reverse_index(index, qubits):
+    return int(format(index, f"0{qubits}b")[::-1], 2)
+
+
+def test_echo_device():
+    print("\nTesting the EchoDevice hooks against the same circuit built directly...")
+    operations = [
+        ("Hadamard", [0], []), ("PauliX", [3], []), ("RX", [1], [0.3]), ("Rot", [2], [0.1, 0.9, -0.4]),
+        ("CNOT", [0, 2], []), ("IsingZZ", [1, 3], [0.7]), ("CRY", [3, 0], [1.2]), ("Toffoli", [0, 1, 3], []),
+        ("Adjoint(T)", [2], []), ("SX", [1], []), ("ControlledPhaseShift", [2, 1], [0.5]),
+    ]
+    circuit = QuantumCircuit(4)
+    circuit.h(0)
+    circuit.x(3)
+    circuit.rx(1, 0.3)
+    # Rot(phi, theta, omega) = RZ(omega) RY(theta) RZ(phi)
+    circuit.rz(2, 0.1)
+    circuit.ry(2, 0.9)
+    circuit.rz(2, -0.4)
+    circuit.cnot(0, 2)
+    circuit.rzz(1, 3, 0.7)
+    circuit.cry(3, 0, 1.2)
+    circuit.ccx(0, 1, 3)
+    circuit.tdg(2)
+    circuit.rx(1, math.pi / 2)
+    circuit.cphase(2, 1, 0.5)
+    expected = amplitudes(circuit.execute())
+    device = quantum_engine.EchoDevice(4, shots=DEVICE_SHOTS, seed=81)
+    device.apply(operations)
+    # The device puts wire 0 in the most significant bit, the engine in the least
+    state = [complex(*a) for a in device.state()]
+    largest = max(range(len(expected)), key=lambda i: abs(expected[i]))
+    phase = expected[largest] / state[reverse_index(largest, 4)]
+    error = max(abs(state[reverse_index(i, 4)] * phase - a) for i, a in enumerate(expected))
+    print(f"   state up to the global phase of SX: max amplitude error {error:.2e}")
+    ok = error < TOLERANCE and abs(abs(phase) - 1) < TOLERANCE
+
+    engine = circuit.execute()
+    zx = device.expval((["PauliZ", "PauliX"], [3, 0]))
+    ok &= abs(zx - engine.expectation(quantum_engine.PauliString("XIIZ", 1.0))) < TOLERANCE
+    marginal = device.probability([2, 0])
+    reference = [sum(abs(a) ** 2 for i, a in enumerate(expected) if (i >> 2 & 1, i & 1) == (b >> 1, b & 1))
+                 for b in range(4)]
+    ok &= max(abs(p - r) for p, r in zip(marginal, reference)) < TOLERANCE
+    samples = device.sample()
+    frequencies = collections.Counter(tuple(row) for row in samples)
+    probabilities = device.probability()
+    worst = max(abs(frequencies[tuple(int(c) for c in format(i, "04b"))] / DEVICE_SHOTS - p) for i, p in enumerate(probabilities))
+    print(f"   <Z3 X0> {zx:.4f}, marginal of wires [2, 0] {[round(p, 4) for p in marginal]}, "
+          f"{len(samples)} samples, worst frequency error {worst:.4f}")
+    ok &= len(samples) == DEVICE_SHOTS and all(len(row) == 4 for row in samples) and worst < 0.05
+    device.reset()
+    return (ok and device.probability()[0] == 1.0 and "QubitUnitary" in quantum_engine.EchoDevice.operations()
+            and raises("unsupported operation", quantum_engine.InvalidGateError,
+                       lambda: device.apply([("Frobnicate", [0], [])])))
+
+
+def test_renormalization():
+    print("\nTesting norm(), renormalize() and the renormalization guard of execute...")
+    state = quantum_engine.QuantumState.from_amplitudes([(0.6, 0.0), (0.80003, 0.0)])
+    before = state.norm()
+    removed = state.renormalize()
+    print(f"   norm {before:.7f}, renormalize() removed {removed:.2e}, norm now {state.norm():.7f}, "
+          f"drift {state.norm_drift:.2e}")
+    ok = (abs(removed - abs(1 - before)) < 1e-7 and abs(state.norm() - 1) < 1e-6
+          and abs(state.norm_drift - removed) < 1e-9 and state.renormalize() < 1e-6)
+
+    circuit = QuantumCircuit.random(8, 150, seed=91)
+    plain = circuit.execute()
+    guarded = circuit.execute(renormalize_every=5, renormalize_tolerance=0.0)
+    loose = circuit.execute(renormalize_every=5, renormalize_tolerance=1.0)
+    error = max_error(amplitudes(guarded), amplitudes(plain))
+    print(f"   {len(circuit.gates())} gates: unguarded norm {plain.norm():.8f}, guarded norm {guarded.norm():.8f}, "
+          f"drift removed {guarded.norm_drift:.2e}, amplitude change {error:.2e}")
+    ok &= (plain.norm_drift == 0.0 and guarded.norm_drift > 0.0 and loose.norm_drift == 0.0
+           and abs(guarded.norm() - 1) < 1e-6 and error < TOLERANCE)
+    return ok and all([
+        raises("guard in double precision", ValueError,
+               lambda: circuit.execute(precision="f64", renormalize_every=5)),
+        raises("guard on the sparse backend", ValueError,
+               lambda: circuit.execute(backend="sparse", renormalize_every=5)),
+    ])
+
+
+def test_layer_observables():
+    print("\nTesting per-layer expectation values against runs of each circuit prefix...")
+    steps = [("h", (0,)), ("cnot", (0, 1)), ("ry", (1, 0.4))]
+    circuit = QuantumCircuit(2)
+    for name, args in steps:
+        getattr(circuit, name)(*args)
+    labels = ["ZI", "ZZ", "XX"]
+    observables = [quantum_engine.PauliString(l) for l in labels] + ["entropy"]
+    ok = all([
+        raises("unknown quantity", ValueError, lambda: circuit.layer_observables(["bogus"])),
+        raises("Pauli string too long", quantum_engine.DimensionMismatchError,
+               lambda: circuit.layer_observables([quantum_engine.PauliString("ZZZ")])),
+        raises("not an observable", TypeError, lambda: circuit.layer_observables([3])),
+    ])
+    try:
+        import numpy
+    except ImportError:
+        print("   numpy not installed; checking the ImportError path only")
+        return ok and raises("layer_observables", ImportError, lambda: circuit.layer_observables(observables))
+    table = circuit.layer_observables(observables)
+    # One layer per gate here, so row k is the state after the first k + 1 gates
+    expected = []
+    for layer in range(len(steps)):
+        prefix = QuantumCircuit(2)
+        for name, args in steps[:layer + 1]:
+            getattr(prefix, name)(*args)
+        state = prefix.execute()
+        expected.append([state.expectation(o) for o in observables[:3]] + [state.entropy()])
+    error = float(numpy.max(numpy.abs(table - numpy.array(expected))))
+    print(f"   {table.dtype} table of shape {table.shape}: max error {error:.2e}")
+    return ok and table.dtype == numpy.float32 and table.shape == (3, 4) and error < TOLERANCE
+
+
+def test_execute_many():
+    print("\nTesting execute_many against one-by-one runs of each circuit...")
+    circuits = [QuantumCircuit.random(3, 4, seed=seed) for seed in range(4)]
+    states = quantum_engine.execute_many(circuits)
+    error = max(max_error(amplitudes(s), amplitudes(c.execute())) for s, c in zip(states, circuits))
+    print(f"   {len(states)} states in input order: max amplitude error {error:.2e}")
+    ok = len(states) == len(circuits) and error < TOLERANCE and quantum_engine.execute_many([]) == []
+    shots = 2000
+    counts = quantum_engine.execute_many(circuits, shots=shots, seed=106)
+    worst = 0.0
+    for histogram, circuit in zip(counts, circuits):
+        state = circuit.execute()
+        ok &= sum(histogram.values()) == shots
+        worst = max(worst, max(abs(histogram.get(format(i, "03b")[::-1], 0) / shots - p)
+                               for i, p in enumerate(state.probabilities())))
+    print(f"   {shots} shots each: worst frequency error {worst:.4f}")
+    ok &= worst < 0.05 and counts == quantum_engine.execute_many(circuits, shots=shots, seed=106)
+    big = quantum_engine.execute_many(circuits, shots=shots, seed=106, bit_order="big")
+    ok &= all({k[::-1]: v for k, v in b.items()} == c for b, c in zip(big, counts))
+    # Full damping after x returns every qubit to |0>
+    flip = QuantumCircuit(2)
+    flip.x(0)
+    flip.x(1)
+    model = quantum_engine.NoiseModel()
+    model.add_gate_error("x", "amplitude_damping", 1.0)
+    noisy = quantum_engine.execute_many([flip, flip], shots=50, seed=106, noise_model=model)
+    print(f"   damped copies: {noisy}")
+    return ok and noisy == [{"00": 50}, {"00": 50}] and all([
+        raises("unknown bit order", ValueError, lambda: quantum_engine.execute_many(circuits, shots=10, bit_order="x")),
+    ])
+
+
+def test_execute_detailed():
+    print("\nTesting ExecutionResult against the executors it bundles...")
+    circuit = QuantumCircuit(2)
+    circuit.h(0)
+    circuit.h(0)
+    circuit.snapshot("mid")
+    circuit.rx(1, 0.3)
+    circuit.ry(0, 0.8)
+    circuit.cnot(0, 1)
+    zi = quantum_engine.PauliString("ZI")
+    result = circuit.execute_detailed(shots=500, seed=114, trace=[zi], trace_every=2)
+    print(f"   {result}: optimizations {result.optimizations}")
+    error = max_error(amplitudes(result.state), amplitudes(circuit.execute()))
+    steps, series = circuit.execute_traced([zi], every=2)
+    snapshots = circuit.execute_snapshots()[1]
+    snapshot_error = max(abs(a - b) for (_, x), (_, y) in zip(result.snapshots, snapshots)
+                         for p, r in zip(x, y) for a, b in zip(p, r))
+    ok = all([
+        error < TOLERANCE, result.counts == circuit.run(500, seed=114),
+        result.gate_counts == circuit.count_ops(), result.gates == len(circuit) == 6,
+        # The two h gates fuse into one
+        result.executed_gates == result.gates - 1, result.optimizations != [],
+        result.trace_steps == steps, max_error(result.trace[0], series[0]) < TOLERANCE,
+        [label for label, _ in result.snapshots] == ["mid"], snapshot_error < TOLERANCE,
+        result.elapsed_s > 0,
+    ])
+    plain = circuit.execute_detailed(transpile_level=0)
+    ok &= (plain.counts is None and plain.executed_gates == plain.gates and plain.optimizations == []
+           and plain.trace == [] and plain.trace_steps == [])
+    # Under noise the state is a density matrix and fusion is skipped
+    model = quantum_engine.NoiseModel()
+    model.add_gate_error("cnot", "depolarizing", 0.1)
+    noisy = circuit.execute_detailed(noise_model=model, shots=200, seed=114)
+    print(f"   under noise: {type(noisy.state).__name__}, optimizations {noisy.optimizations}, "
+          f"{sum(noisy.counts.values())} shots")
+    return (ok and isinstance(noisy.state, quantum_engine.DensityMatrix) and noisy.optimizations == []
+            and sum(noisy.counts.values()) == 200)
+
+
+def test_execute_feedback():
+    print("\nTesting closed-loop feedback against traced runs and the gates and noise it returns...")
+    steps = [("h", (0,)), ("cnot", (0, 1)), ("rx", (1, 0.3)), ("ry", (0, 0.5)), ("x", (1,))]
+    circuit = QuantumCircuit(2)
+    for name, args in steps:
+        getattr(circuit, name)(*args)
+    observables = [quantum_engine.PauliString("ZX"), "entropy"]
+    calls = []
+    state = circuit.execute_feedback(lambda done, readings: calls.append((done, readings)), every=2,
+                                     observables=observables)
+    traced_steps, series = circuit.execute_traced(observables, every=2)
+    # The trace also reads the state before the first gate; feedback starts after it
+    expected = [(done, [s[i] for s in series]) for i, done in enumerate(traced_steps) if done > 0]
+    error = max(abs(a - b) for (_, x), (_, y) in zip(calls, expected) for a, b in zip(x, y))
+    print(f"   callbacks at {[done for done, _ in calls]}: max reading error {error:.2e}")
+    ok = ([done for done, _ in calls] == [2, 4, 5] and error < TOLERANCE
+          and max_error(amplitudes(state), amplitudes(circuit.execute())) < TOLERANCE)
+    # Gates returned after the second gate run before the third
+    extra = QuantumCircuit(2)
+    extra.x(0)
+    extra.rz(1, 0.8)
+    inserted = circuit.execute_feedback(lambda done, readings: extra if done == 2 else None, every=2)
+    reference = QuantumCircuit(2)
+    for name, args in steps[:2] + [("x", (0,)), ("rz", (1, 0.8))] + steps[2:]:
+        getattr(reference, name)(*args)
+    ok &= max_error(amplitudes(inserted), amplitudes(reference.execute())) < TOLERANCE
+    # Full damping on every qubit after the last gate leaves |00>. In the pair, extra undoes its own x(0)
+    # and a bit flip of strength 1 is X on both qubits
+    damped = circuit.execute_feedback(lambda done, readings: {"amplitude_damping": 1.0} if done == 5 else None)
+    ok &= abs(damped.probability("00") - 1) < TOLERANCE
+    paired = extra.execute_feedback(lambda done, readings: (extra, {"bit_flip": 1.0}) if done == 2 else None)
+    print(f"   damped P(00) = {damped.probability('00'):.4f}, x x plus a full bit flip: P(11) = {paired.probability('11'):.4f}")
+    ok &= abs(paired.probability("11") - 1) < TOLERANCE
+
+    def failing(done, readings):
+        raise RuntimeError("from the callback")
+
+    return ok and all([
+        raises("callback error", RuntimeError, lambda: circuit.execute_feedback(failing)),
+        raises("unsupported return value", TypeError, lambda: circuit.execute_feedback(lambda done, readings: 5)),
+        raises("unknown channel", ValueError, lambda: circuit.execute_feedback(lambda done, readings: {"bogus": 0.1})),
+    ])
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Execution Modes, Results and Runtime Controls")
+    print("============================================================")
+
+    results = [
+        test_double_precision(),
+        test_numpy_interop(),
+        test_initial_states(),
+        test_gil_release(),
+        test_execute_async(),
+        test_execute_traced(),
+        test_profiled(),
+        test_random_circuits(),
+        test_echo_device(),
+        test_renormalization(),
+        test_layer_observables(),
+        test_execute_many(),
+        test_execute_detailed(),
+        test_execute_feedback(),
+    ]
+
+    if all(results):
+        print("\nEXECUTION VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
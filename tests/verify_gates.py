@@ -0,0 +1,715 @@
+import cmath
+import math
+import pickle
+import random
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+TOLERANCE = 1e-4
+
+
+def amplitudes(state):
+    return [complex(*a) for a in state.get_state_vector()]
+
+
+def max_error(a, b):
+    return max(abs(x - y) for x, y in zip(a, b))
+
+
+def report(label, error):
+    print(f"   {label}: max amplitude error {error:.2e}")
+    return error < TOLERANCE
+
+
+def apply(amps, matrix, targets, controls=()):
+    """Reference action of `matrix` on `targets` (bit i of its index is
+    targets[i]) where every control qubit is 1, on a list of amplitudes"""
+    out = list(amps)
+    k = len(targets)
+    control_mask = sum(1 << c for c in controls)
+    target_mask = sum(1 << t for t in targets)
+    for base in range(len(amps)):
+        if base & target_mask or base & control_mask != control_mask:
+            continue
+        index = [base | sum(1 << targets[i] for i in range(k) if j >> i & 1) for j in range(1 << k)]
+        for row in range(1 << k):
+            out[index[row]] = sum(matrix[row][col] * amps[index[col]] for col in range(1 << k))
+    return out
+
+
+def rotation(axis, theta):
+    """exp(-i theta/2 P) for P = X, Y or Z"""
+    c, s = cmath.cos(theta / 2), cmath.sin(theta / 2)
+    return {
+        "x": [[c, -1j * s], [-1j * s, c]],
+        "y": [[c, -s], [s, c]],
+        "z": [[cmath.exp(-0.5j * theta), 0], [0, cmath.exp(0.5j * theta)]],
+    }[axis]
+
+
+def random_input(qubits, seed):
+    """A random circuit and the amplitudes it prepares"""
+    circuit = QuantumCircuit.random(qubits, 6, seed=seed)
+    return circuit, amplitudes(circuit.execute())
+
+
+def basis_circuit(qubits, index):
+    """Circuit preparing the basis state |index> (qubit 0 least significant)"""
+    circuit = QuantumCircuit(qubits)
+    for q in range(qubits):
+        if index >> q & 1:
+            circuit.x(q)
+    return circuit
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_qft():
+    print("\nTesting qft and iqft against the discrete Fourier transform...")
+    ok = True
+    m = 4
+    dim = 1 << m
+    for x in (0, 3, 10):
+        circuit = basis_circuit(m, x)
+        circuit.qft(list(range(m)))
+        expected = [cmath.exp(2j * math.pi * x * y / dim) / math.sqrt(dim) for y in range(dim)]
+        ok &= report(f"QFT|{x}>", max_error(amplitudes(circuit.execute()), expected))
+
+        roundtrip = basis_circuit(m, x)
+        roundtrip.qft(list(range(m)), False)
+        roundtrip.iqft(list(range(m)), False)
+        ok &= report(f"iqft(qft|{x}>)", max_error(amplitudes(roundtrip.execute()),
+                                                  [1.0 if y == x else 0.0 for y in range(dim)]))
+
+    # Phases between qubits 31 or more apart used to overflow an integer shift
+    wide = QuantumCircuit(40)
+    wide.qft(list(range(40)), True)
+    wide.iqft(list(range(40)), True)
+    angles = [params[0] for name, _, params in wide.gates() if params]
+    smallest = min(abs(a) for a in angles)
+    print(f"   40-qubit qft + iqft: {len(wide.gates())} gates, smallest phase {smallest:.3e}")
+    return (ok and len(angles) == 2 * 40 * 39 // 2
+            and all(math.isfinite(a) for a in angles)
+            and abs(smallest - math.pi / 2 ** 39) < 1e-15)
+
+
+def test_global_phase():
+    print("\nTesting global phase bookkeeping and canonicalize...")
+    theta = 1.0
+    rz = QuantumCircuit(2)
+    rz.h(0)
+    rz.rz(0, theta)
+    rz.cnot(0, 1)
+    phase = QuantumCircuit(2)
+    phase.h(0)
+    phase.p(0, theta)
+    phase.cnot(0, 1)
+    a, b = rz.execute(), phase.execute()
+    # RZ(theta) = e^(-i theta / 2) P(theta)
+    restored = [x * cmath.exp(-1j * a.global_phase()) for x in amplitudes(a)]
+    print(f"   global_phase() after RZ({theta}): {a.global_phase():.4f}; after P({theta}): {b.global_phase():.4f}")
+    ok = (abs(a.global_phase() + theta / 2) < TOLERANCE and abs(b.global_phase()) < TOLERANCE
+          and report("RZ state with its global phase divided out vs P", max_error(restored, amplitudes(b))))
+    ok &= report("canonicalized RZ vs P", max_error(amplitudes(a.canonicalize()), amplitudes(b.canonicalize())))
+    ok &= a.canonicalize().global_phase() == 0.0
+    copy = pickle.loads(pickle.dumps(a))
+    print(f"   global_phase() after a pickle round trip: {copy.global_phase():.4f}")
+    return ok and copy.global_phase() == a.global_phase()
+
+
+def test_crz():
+    print("\nTesting CRZ against its reference matrix...")
+    ok = True
+    for seed, (control, target), phi in ((1, (0, 1), 0.7), (2, (2, 0), -1.9), (3, (1, 2), 3.0)):
+        circuit, before = random_input(3, seed)
+        circuit.crz(control, target, phi)
+        rz = [[cmath.exp(-0.5j * phi), 0], [0, cmath.exp(0.5j * phi)]]
+        expected = apply(before, rz, [target], [control])
+        ok &= report(f"crz({control}, {target}, {phi})", max_error(amplitudes(circuit.execute()), expected))
+    return ok
+
+
+X = [[0, 1], [1, 0]]
+
+
+def test_toffoli_and_mcx():
+    print("\nTesting CCX and multi-controlled X against the reference...")
+    ok = True
+    for seed, controls, target in ((4, [0, 1], 2), (5, [2, 0], 1), (6, [0, 1, 3], 2), (7, [3, 2, 1], 0)):
+        circuit, before = random_input(4, seed)
+        if len(controls) == 2:
+            circuit.ccx(controls[0], controls[1], target)
+            label = f"ccx({controls[0]}, {controls[1]}, {target})"
+        else:
+            circuit.mcx(controls, target)
+            label = f"mcx({controls}, {target})"
+        ok &= report(label, max_error(amplitudes(circuit.execute()), apply(before, X, [target], controls)))
+    # All four controls set flips the fifth qubit of a basis state
+    flipped = basis_circuit(5, 0b01111)
+    flipped.mcx([0, 1, 2, 3], 4)
+    return ok and abs(flipped.execute().probability("11111") - 1.0) < TOLERANCE
+
+
+SWAP = [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]]
+ISWAP = [[1, 0, 0, 0], [0, 0, 1j, 0], [0, 1j, 0, 0], [0, 0, 0, 1]]
+
+
+def test_swap_and_iswap():
+    print("\nTesting SWAP and iSWAP against their reference matrices...")
+    ok = True
+    for seed, (a, b) in ((8, (0, 2)), (9, (2, 1))):
+        for name, matrix in (("swap", SWAP), ("iswap", ISWAP)):
+            circuit, before = random_input(3, seed)
+            getattr(circuit, name)(a, b)
+            expected = apply(before, matrix, [a, b])
+            ok &= report(f"{name}({a}, {b})", max_error(amplitudes(circuit.execute()), expected))
+            state = quantum_engine.QuantumState.from_amplitudes([(x.real, x.imag) for x in before])
+            getattr(state, f"apply_{name}")(a, b)
+            ok &= report(f"QuantumState.apply_{name}({a}, {b})", max_error(amplitudes(state), expected))
+    return ok
+
+
+def test_controlled_rotations():
+    print("\nTesting controlled rotations against the reference, on both sides of the parallel threshold...")
+    ok = True
+    # 12 qubits is past the 1024-amplitude threshold where the kernels go parallel
+    for qubits, seed, control, target in ((3, 10, 0, 2), (3, 11, 2, 1), (12, 12, 1, 11), (12, 13, 11, 0)):
+        for axis, theta in (("x", 0.8), ("y", -1.3), ("z", 2.1)):
+            circuit, before = random_input(qubits, seed)
+            getattr(circuit, f"cr{axis}")(control, target, theta)
+            expected = apply(before, rotation(axis, theta), [target], [control])
+            ok &= report(f"{qubits} qubits, cr{axis}({control}, {target})", max_error(amplitudes(circuit.execute()), expected))
+            state = quantum_engine.QuantumState.from_amplitudes([(a.real, a.imag) for a in before])
+            getattr(state, f"apply_controlled_r{axis}")(control, target, theta)
+            ok &= max_error(amplitudes(state), expected) < TOLERANCE
+    return ok
+
+
+def random_unitary(dim, seed):
+    """Gram-Schmidt on a random complex matrix; column j is U|j>"""
+    rng = random.Random(seed)
+    columns = []
+    for _ in range(dim):
+        v = [complex(rng.gauss(0, 1), rng.gauss(0, 1)) for _ in range(dim)]
+        for u in columns:
+            overlap = sum(a.conjugate() * b for a, b in zip(u, v))
+            v = [b - overlap * a for a, b in zip(u, v)]
+        norm = sum(abs(b) ** 2 for b in v) ** 0.5
+        columns.append([b / norm for b in v])
+    return [[columns[col][row] for col in range(dim)] for row in range(dim)]
+
+
+def pairs(matrix):
+    return [[(z.real, z.imag) for z in row] for row in matrix]
+
+
+def test_custom_unitary():
+    print("\nTesting user-supplied unitaries against the reference...")
+    ok = True
+    for qubits, seed, targets in ((3, 20, [1]), (3, 21, [2, 0]), (4, 22, [3, 1, 0]), (12, 23, [0, 11])):
+        matrix = random_unitary(1 << len(targets), seed)
+        circuit, before = random_input(qubits, seed)
+        circuit.unitary(pairs(matrix), targets)
+        ok &= report(f"{qubits} qubits, targets {targets}",
+                     max_error(amplitudes(circuit.execute()), apply(before, matrix, targets)))
+
+    circuit = QuantumCircuit(3)
+    skewed = [[1, 0], [0, 2]]
+    return ok and all([
+        raises("non-unitary matrix", quantum_engine.InvalidGateError, lambda: circuit.unitary(pairs(skewed), [0])),
+        raises("matrix of the wrong size", quantum_engine.DimensionMismatchError,
+               lambda: circuit.unitary(pairs(random_unitary(2, 1)), [0, 1])),
+        raises("repeated target", quantum_engine.InvalidGateError,
+               lambda: circuit.unitary(pairs(random_unitary(4, 1)), [1, 1])),
+        raises("target out of range", quantum_engine.QubitIndexError,
+               lambda: circuit.unitary(pairs(random_unitary(2, 1)), [3])),
+        len(circuit.gates()) == 0,
+    ])
+
+
+def every_gate(qubits=4):
+    """One of each gate family the builder offers, on `qubits` qubits"""
+    circuit = QuantumCircuit.random(qubits, 4, seed=30)
+    circuit.h(0)
+    circuit.s(1)
+    circuit.sdg(2)
+    circuit.t(3)
+    circuit.tdg(0)
+    circuit.y(1)
+    circuit.p(2, 0.7)
+    circuit.u(3, 0.4, -1.1, 2.3)
+    circuit.ch(0, 2)
+    circuit.cphase(1, 3, 0.9)
+    circuit.crx(2, 0, -0.6)
+    circuit.crz(3, 1, 1.4)
+    circuit.swap(0, 3)
+    circuit.iswap(1, 2)
+    circuit.rxx(0, 1, 0.3)
+    circuit.ryy(2, 3, -0.8)
+    circuit.rzz(1, 3, 1.2)
+    circuit.ccx(0, 1, 2)
+    circuit.mcz([0, 1, 3], 2)
+    circuit.unitary(pairs(random_unitary(4, 31)), [3, 0])
+    return circuit
+
+
+def test_inverse():
+    print("\nTesting circuit inverses...")
+    circuit = every_gate()
+    _, start = random_input(4, 32)
+    # U followed by U^dagger is the identity on an arbitrary input
+    round_trip = QuantumCircuit.random(4, 6, seed=32)
+    round_trip.compose(circuit)
+    round_trip.compose(circuit.inverse())
+    ok = report("U then inverse(U) on a random input", max_error(amplitudes(round_trip.execute()), start))
+    ok &= round_trip.equivalent(QuantumCircuit.random(4, 6, seed=32))
+    # inverse(inverse(U)) is U again
+    ok &= circuit.inverse().inverse().equivalent(circuit)
+
+    # Symbolic angles stay symbolic, with their sign flipped
+    symbolic = QuantumCircuit(2)
+    symbolic.ry_param(0, "theta")
+    symbolic.crz_param(0, 1, "phi")
+    inverse = symbolic.inverse()
+    fixed = QuantumCircuit(2)
+    fixed.crz(0, 1, -0.5)
+    fixed.ry(0, -0.3)
+    ok &= inverse.parameters() == ["phi", "theta"]
+    ok &= inverse.bind({"theta": 0.3, "phi": 0.5}).equivalent(fixed)
+
+    measured = QuantumCircuit(1, 1)
+    measured.h(0)
+    measured.measure_qubit(0, 0)
+    return ok and raises("circuit with measurement", ValueError, measured.inverse)
+
+
+def test_compose_and_tensor():
+    print("\nTesting circuit composition and tensor products...")
+    first = QuantumCircuit.random(3, 5, seed=40)
+    second = QuantumCircuit.random(2, 5, seed=41)
+
+    # compose with a qubit map equals running the second circuit's matrix on those qubits
+    combined = QuantumCircuit.random(3, 5, seed=40)
+    combined.compose(second, [2, 0])
+    before = amplitudes(first.execute())
+    columns = []
+    for j in range(4):
+        column = basis_circuit(2, j)
+        column.compose(second)
+        columns.append(amplitudes(column.execute()))
+    matrix = [[columns[c][r] for c in range(4)] for r in range(4)]
+    ok = report("compose onto qubits [2, 0]", max_error(amplitudes(combined.execute()), apply(before, matrix, [2, 0])))
+
+    # tensor puts the other circuit on the qubits above
+    wide = first.tensor(second)
+    expected = first.execute().kron_with(second.execute())
+    ok &= len(wide.execute().get_state_vector()) == 32
+    ok &= report("tensor against kron_with", max_error(amplitudes(wide.execute()), amplitudes(expected)))
+
+    # Symbolic parameters come along with the gates
+    symbolic = QuantumCircuit(1)
+    symbolic.rx_param(0, "alpha")
+    host = QuantumCircuit(2)
+    host.compose(symbolic, [1])
+    ok &= host.parameters() == ["alpha"] and first.tensor(symbolic).parameters() == ["alpha"]
+
+    return ok and all([
+        raises("wider circuit composed onto a narrower one", quantum_engine.QubitIndexError, lambda: second.compose(first)),
+        raises("qubit map of the wrong length", quantum_engine.DimensionMismatchError, lambda: first.compose(second, [0])),
+        raises("qubit map with a repeated qubit", quantum_engine.InvalidGateError, lambda: first.compose(second, [1, 1])),
+        raises("qubit map out of range", quantum_engine.QubitIndexError, lambda: first.compose(second, [0, 7])),
+    ])
+
+
+def u3(theta, phi, lam):
+    c, s = cmath.cos(theta / 2), cmath.sin(theta / 2)
+    return [[c, -cmath.exp(1j * lam) * s], [cmath.exp(1j * phi) * s, cmath.exp(1j * (phi + lam)) * c]]
+
+
+def test_u_and_p():
+    print("\nTesting U3 and the phase gate against their reference matrices...")
+    ok = True
+    for qubits, seed, target, (theta, phi, lam) in ((3, 50, 0, (0.7, -1.2, 2.5)), (3, 51, 2, (2.9, 0.4, -0.3)),
+                                                    (12, 52, 7, (-1.6, 3.0, 1.1))):
+        circuit, before = random_input(qubits, seed)
+        circuit.u(target, theta, phi, lam)
+        ok &= report(f"{qubits} qubits, u({target}, {theta}, {phi}, {lam})",
+                     max_error(amplitudes(circuit.execute()), apply(before, u3(theta, phi, lam), [target])))
+        circuit, before = random_input(qubits, seed)
+        circuit.p(target, lam)
+        ok &= report(f"{qubits} qubits, p({target}, {lam})",
+                     max_error(amplitudes(circuit.execute()), apply(before, [[1, 0], [0, cmath.exp(1j * lam)]], [target])))
+
+    # u(theta, -pi/2, pi/2) is RX(theta) and u(theta, 0, 0) is RY(theta) exactly
+    rx = QuantumCircuit(1)
+    rx.u(0, 0.9, -cmath.pi / 2, cmath.pi / 2)
+    ry = QuantumCircuit(1)
+    ry.u(0, 0.9, 0.0, 0.0)
+    rx_amps = apply([1, 0], rotation("x", 0.9), [0])
+    ry_amps = apply([1, 0], rotation("y", 0.9), [0])
+    return (ok and report("u as RX", max_error(amplitudes(rx.execute()), rx_amps))
+            and report("u as RY", max_error(amplitudes(ry.execute()), ry_amps)))
+
+
+def ising(axis, theta):
+    """exp(-i theta/2 P (x) P) on two qubits: cos(theta/2) I - i sin(theta/2) P (x) P"""
+    pauli = {"x": [[0, 1], [1, 0]], "y": [[0, -1j], [1j, 0]], "z": [[1, 0], [0, -1]]}[axis]
+    c, s = cmath.cos(theta / 2), cmath.sin(theta / 2)
+    # Bit 0 of the index is the first target
+    return [[c * (row == col) - 1j * s * pauli[row >> 1][col >> 1] * pauli[row & 1][col & 1]
+             for col in range(4)] for row in range(4)]
+
+
+def test_ising_gates():
+    print("\nTesting RXX, RYY and RZZ against their reference matrices...")
+    ok = True
+    for qubits, seed, a, b in ((3, 60, 0, 2), (3, 61, 2, 1), (12, 62, 3, 10)):
+        for axis, theta in (("x", 0.8), ("y", -1.7), ("z", 2.6)):
+            circuit, before = random_input(qubits, seed)
+            getattr(circuit, f"r{axis}{axis}")(a, b, theta)
+            ok &= report(f"{qubits} qubits, r{axis}{axis}({a}, {b}, {theta})",
+                         max_error(amplitudes(circuit.execute()), apply(before, ising(axis, theta), [a, b])))
+    circuit = QuantumCircuit(2)
+    return ok and raises("repeated qubit", quantum_engine.InvalidGateError, lambda: circuit.rzz(1, 1, 0.3))
+
+
+def test_controlled_modifier():
+    print("\nTesting the generic controlled modifier against the reference...")
+    s = [[1, 0], [0, 1j]]
+    h = [[2 ** -0.5, 2 ** -0.5], [2 ** -0.5, -(2 ** -0.5)]]
+    cases = (
+        ("h", [], h, [0], 1),
+        ("s", [], s, [2, 0], 1),
+        ("z", [], [[1, 0], [0, -1]], [0, 1], 2),
+        ("rx", [0.7], rotation("x", 0.7), [3], 1),
+        ("u", [0.4, -1.0, 2.2], u3(0.4, -1.0, 2.2), [1, 3, 0], 2),
+    )
+    ok = True
+    for qubits, seed in ((4, 70), (12, 71)):
+        for gate, params, matrix, controls, target in cases:
+            circuit, before = random_input(qubits, seed)
+            circuit.controlled(gate, controls, target, params)
+            ok &= report(f"{qubits} qubits, controlled({gate!r}, {controls}, {target})",
+                         max_error(amplitudes(circuit.execute()), apply(before, matrix, [target], controls)))
+    circuit = QuantumCircuit(3)
+    return ok and all([
+        raises("control equal to the target", quantum_engine.InvalidGateError,
+               lambda: circuit.controlled("h", [1], 1)),
+        raises("unknown gate", quantum_engine.InvalidGateError, lambda: circuit.controlled("frobnicate", [0], 1)),
+        raises("missing rotation angle", quantum_engine.InvalidGateError, lambda: circuit.controlled("rx", [0], 1)),
+        raises("control out of range", quantum_engine.QubitIndexError, lambda: circuit.controlled("x", [5], 1)),
+    ])
+
+
+def diag(*entries):
+    return [[entries[row] if row == col else 0 for col in range(len(entries))] for row in range(len(entries))]
+
+
+X_MATRIX = [[0, 1], [1, 0]]
+SWAP_MATRIX = [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]]
+# (label, builder call, reference matrix, targets, controls) on 12 qubits;
+# targets 0 and 1 take the paired loop, higher ones the runs
+FAST_PATH_CASES = [
+    ("z(0)", lambda c: c.z(0), diag(1, -1), [0], ()),
+    ("s(1)", lambda c: c.s(1), diag(1, 1j), [1], ()),
+    ("t(9)", lambda c: c.t(9), diag(1, cmath.exp(0.25j * cmath.pi)), [9], ()),
+    ("rz(11, 0.7)", lambda c: c.rz(11, 0.7), rotation("z", 0.7), [11], ()),
+    ("cphase(3, 0, 1.1)", lambda c: c.cphase(3, 0, 1.1), diag(1, cmath.exp(1.1j)), [0], (3,)),
+    ("mcz([2, 7], 10)", lambda c: c.mcz([2, 7], 10), diag(1, -1), [10], (2, 7)),
+    ("x(0)", lambda c: c.x(0), X_MATRIX, [0], ()),
+    ("x(8)", lambda c: c.x(8), X_MATRIX, [8], ()),
+    ("cnot(5, 1)", lambda c: c.cnot(5, 1), X_MATRIX, [1], (5,)),
+    ("cnot(0, 11)", lambda c: c.cnot(0, 11), X_MATRIX, [11], (0,)),
+    ("mcx([1, 4, 9], 6)", lambda c: c.mcx([1, 4, 9], 6), X_MATRIX, [6], (1, 4, 9)),
+    ("y(7)", lambda c: c.y(7), [[0, -1j], [1j, 0]], [7], ()),
+    ("swap(2, 10)", lambda c: c.swap(2, 10), SWAP_MATRIX, [2, 10], ()),
+    ("iswap(0, 5)", lambda c: c.iswap(0, 5), [[1, 0, 0, 0], [0, 0, 1j, 0], [0, 1j, 0, 0], [0, 0, 0, 1]], [0, 5], ()),
+    ("rzz(1, 8, 0.9)", lambda c: c.rzz(1, 8, 0.9),
+     diag(cmath.exp(-0.45j), cmath.exp(0.45j), cmath.exp(0.45j), cmath.exp(-0.45j)), [1, 8], ()),
+]
+
+
+def test_fast_paths():
+    print("\nTesting the diagonal and permutation kernels, with and without SIMD...")
+    ok = True
+    for simd in (True, False):
+        quantum_engine.set_simd(simd)
+        for label, build, matrix, targets, controls in FAST_PATH_CASES:
+            circuit, before = random_input(12, 70)
+            build(circuit)
+            ok &= report(f"simd={simd}, {label}",
+                         max_error(amplitudes(circuit.execute()), apply(before, matrix, targets, controls)))
+        # The state-level permutations skip the circuit entirely
+        circuit, before = random_input(12, 71)
+        state = circuit.execute()
+        state.apply_cnot(10, 3)
+        state.apply_mcx([0, 6], 11)
+        state.apply_swap(4, 1)
+        expected = apply(before, X_MATRIX, [3], (10,))
+        expected = apply(expected, X_MATRIX, [11], (0, 6))
+        expected = apply(expected, SWAP_MATRIX, [4, 1])
+        ok &= report(f"simd={simd}, apply_cnot, apply_mcx, apply_swap", max_error(amplitudes(state), expected))
+    quantum_engine.set_simd(True)
+    return ok
+
+
+def basis_superposition(qubits, indices):
+    """Equal superposition of the basis states |i> for i in `indices`"""
+    amps = [0j] * (1 << qubits)
+    for i in indices:
+        amps[i] = complex(1 / math.sqrt(len(indices)))
+    return amps
+
+
+def test_entangled_presets():
+    print("\nTesting the Bell, GHZ and W state builders against their target states...")
+    ok = True
+    cases = [
+        ("bell()", QuantumCircuit.bell(), basis_superposition(2, [0b00, 0b11])),
+        ("bell(1, 3)", QuantumCircuit.bell(1, 3), basis_superposition(4, [0, 0b1010])),
+        ("bell(2, 0, qubits=5)", QuantumCircuit.bell(2, 0, qubits=5), basis_superposition(5, [0, 0b101])),
+    ]
+    for n in (1, 2, 5, 9):
+        cases.append((f"ghz({n})", QuantumCircuit.ghz(n), basis_superposition(n, [0, (1 << n) - 1])))
+    for n in (1, 2, 3, 7):
+        cases.append((f"w_state({n})", QuantumCircuit.w_state(n), basis_superposition(n, [1 << k for k in range(n)])))
+    for label, circuit, expected in cases:
+        ok &= report(label, max_error(amplitudes(circuit.execute()), expected))
+    return (ok and raises("bell on one qubit", quantum_engine.InvalidGateError, lambda: QuantumCircuit.bell(1, 1))
+            and raises("bell outside the register", quantum_engine.QubitIndexError,
+                       lambda: QuantumCircuit.bell(0, 4, qubits=3)))
+
+
+def test_multi_controlled_phases():
+    print("\nTesting mcz and mcp against reference diagonal matrices...")
+    ok = True
+    for controls, target, lam in (([0], 3, 0.4), ([2, 5], 1, -1.3), ([0, 3, 4, 6], 7, 2.2), ([], 2, 0.9)):
+        for name, build, phase in (("mcz", lambda c: c.mcz(controls, target), -1),
+                                   ("mcp", lambda c: c.mcp(controls, target, lam), cmath.exp(1j * lam))):
+            circuit, before = random_input(8, 98)
+            build(circuit)
+            expected = apply(before, [[1, 0], [0, phase]], [target], controls)
+            ok &= report(f"{name}({controls}, {target})", max_error(amplitudes(circuit.execute()), expected))
+    # mcp(controls, t, pi) is mcz(controls, t), and the diagonal is symmetric in its qubits
+    a, _ = random_input(6, 99)
+    a.mcp([1, 4], 2, cmath.pi)
+    c, _ = random_input(6, 99)
+    c.mcz([2, 4], 1)
+    ok &= report("mcp(controls, t, pi) against mcz with t swapped into the controls",
+                 max_error(amplitudes(a.execute()), amplitudes(c.execute())))
+    return ok and raises("target among the controls", quantum_engine.InvalidGateError,
+                         lambda: QuantumCircuit(3).mcz([0, 2], 2))
+
+
+def qudit_reference(d, n, gates):
+    """Amplitudes of |0...0> after `gates`, each a map from the digits of a basis
+    state (digit k is qudit k) to {new digits: coefficient}"""
+    amps = {(0,) * n: 1 + 0j}
+    for gate in gates:
+        out = {}
+        for digits, a in amps.items():
+            for new, coeff in gate(digits).items():
+                out[new] = out.get(new, 0j) + coeff * a
+        amps = out
+    index = [tuple(i // d ** k % d for k in range(n)) for i in range(d ** n)]
+    return [amps.get(digits, 0j) for digits in index]
+
+
+def single(matrix, target):
+    """Map of a d x d matrix on one qudit"""
+    def gate(digits):
+        column = digits[target]
+        return {digits[:target] + (row,) + digits[target + 1:]: matrix[row][column]
+                for row in range(len(matrix)) if matrix[row][column] != 0}
+    return gate
+
+
+def test_qudits():
+    print("\nTesting qudit gates against reference d-level matrices...")
+    ok = True
+    for d in (3, 4):
+        n = 2
+        w = cmath.exp(2j * cmath.pi / d)
+        fourier = [[w ** (j * k) / math.sqrt(d) for k in range(d)] for j in range(d)]
+        theta, phi = 0.9, 0.4
+        c, s = math.cos(theta / 2), math.sin(theta / 2)
+        # exp(-i theta/2 (cos phi X + sin phi Y)) on levels 0 and 2, identity elsewhere
+        rotation = [[1 if r == k else 0 for k in range(d)] for r in range(d)]
+        rotation[0][0] = rotation[2][2] = c
+        rotation[0][2] = -1j * s * cmath.exp(-1j * phi)
+        rotation[2][0] = -1j * s * cmath.exp(1j * phi)
+        # A cyclic permutation with phases
+        cycle = [[(1j ** r if k == (r + 1) % d else 0) for k in range(d)] for r in range(d)]
+        circuit = quantum_engine.QuantumCircuit.new_qudit(n, d)
+        circuit.h(0)
+        circuit.x(1, power=2)
+        circuit.cx(0, 1)
+        circuit.rotation(1, 0, 2, theta, phi)
+        circuit.z(0, power=d - 1)
+        circuit.phase(1, 1, 0.7)
+        circuit.unitary(0, [[(z.real, z.imag) for z in row] for row in cycle])
+        circuit.cz(1, 0, power=2)
+        circuit.h(1)
+        circuit.hdg(0)
+        expected = qudit_reference(d, n, [
+            single(fourier, 0),
+            lambda x: {(x[0], (x[1] + 2) % d): 1},
+            lambda x: {(x[0], (x[1] + x[0]) % d): 1},
+            single(rotation, 1),
+            lambda x: {x: w ** ((d - 1) * x[0])},
+            lambda x: {x: cmath.exp(0.7j) if x[1] == 1 else 1},
+            single(cycle, 0),
+            lambda x: {x: w ** (2 * x[0] * x[1])},
+            single(fourier, 1),
+            single([[z.conjugate() for z in row] for row in zip(*fourier)], 0),
+        ])
+        state = circuit.execute()
+        error = max(abs(complex(*a) - b) for a, b in zip(state.amplitudes(), expected))
+        ok &= report(f"d={d}, {len(circuit)} gates", error)
+        ok &= abs(state.norm() - 1) < TOLERANCE and len(state.amplitudes()) == d ** n
+        populations = state.level_populations(1)
+        ok &= max(abs(p - sum(abs(expected[i]) ** 2 for i in range(d ** n) if i // d % d == level))
+                  for level, p in enumerate(populations)) < TOLERANCE
+    # With d = 2 the generalized gates are the qubit ones
+    qubit = QuantumCircuit(2)
+    qubit.h(0)
+    qubit.cnot(0, 1)
+    qubit.z(1)
+    two = quantum_engine.QuantumCircuit.new_qudit(2, 2)
+    two.h(0)
+    two.cx(0, 1)
+    two.z(1)
+    ok &= report("d=2 against QuantumCircuit", max_error([complex(*a) for a in two.execute().amplitudes()],
+                                                       amplitudes(qubit.execute())))
+    # A qutrit GHZ state gives three equally likely outcomes
+    ghz = quantum_engine.QuantumCircuit.new_qudit(3, 3)
+    ghz.h(0)
+    ghz.cx(0, 1)
+    ghz.cx(0, 2)
+    counts = ghz.execute().measure(900, seed=115)
+    print(f"   qutrit GHZ counts: {counts}")
+    ok &= sorted(counts) == ["000", "111", "222"] and all(abs(v - 300) < 60 for v in counts.values())
+    small = quantum_engine.QuantumCircuit.new_qudit(2, 3)
+    return ok and all([
+        raises("one level", ValueError, lambda: quantum_engine.QuantumCircuit.new_qudit(2, 1)),
+        raises("eleven levels", ValueError, lambda: quantum_engine.QuantumCircuit.new_qudit(2, 11)),
+        raises("qudit out of range", quantum_engine.QubitIndexError, lambda: small.h(2)),
+        raises("level out of range", quantum_engine.InvalidGateError, lambda: small.phase(0, 3, 0.1)),
+        raises("control equal to target", quantum_engine.InvalidGateError, lambda: small.cx(1, 1)),
+        raises("non-unitary matrix", quantum_engine.InvalidGateError, lambda: small.unitary(0, [[(1, 0)] * 3] * 3)),
+    ])
+
+
+def test_in_place_apply_gate():
+    print("\nTesting QuantumState.apply_gate on every target against the reference butterfly...")
+    qubits = 10
+    h = 1 / cmath.sqrt(2)
+    gates = [("H", None, [[h, h], [h, -h]]), ("X", None, [[0, 1], [1, 0]]),
+             ("RX", 0.7, rotation("x", 0.7)), ("RY", -1.2, rotation("y", -1.2)), ("RZ", 0.4, rotation("z", 0.4))]
+    ok = True
+    for precision in ("f32", "f64"):
+        worst = 0.0
+        for target in range(qubits):
+            for name, param, matrix in gates:
+                circuit, before = random_input(qubits, 12 + target)
+                state = circuit.execute(precision=precision)
+                state.apply_gate(name, target, param)
+                after = [complex(*a) for a in state.get_state_vector()]
+                worst = max(worst, max_error(after, apply(before, matrix, [target])))
+                ok &= len(after) == 1 << qubits
+        ok &= report(f"{precision}, {len(gates)} gates on each of {qubits} targets", worst)
+    # A sequence applied to one state equals the product of the steps
+    state = quantum_engine.QuantumState(3)
+    expected = amplitudes(state)
+    for target, (name, param, matrix) in zip([0, 2, 1, 2, 0], gates):
+        state.apply_gate(name, target, param)
+        expected = apply(expected, matrix, [target])
+    return ok and report("five gates in sequence on one state", max_error(amplitudes(state), expected)) and all([
+        raises("target out of range", quantum_engine.QubitIndexError, lambda: state.apply_gate("H", 3)),
+        raises("unknown gate", quantum_engine.InvalidGateError, lambda: state.apply_gate("FOO", 0)),
+    ])
+
+
+def test_simd_butterflies():
+    print("\nTesting the SIMD butterfly against the scalar loop and the reference matrices...")
+    qubits = 12
+    h = 1 / cmath.sqrt(2)
+    cases = [
+        ("h", lambda c, t: c.h(t), [[h, h], [h, -h]]),
+        ("rx(0.7)", lambda c, t: c.rx(t, 0.7), rotation("x", 0.7)),
+        ("ry(-1.2)", lambda c, t: c.ry(t, -1.2), rotation("y", -1.2)),
+        ("u(0.3, 1.1, -0.4)", lambda c, t: c.u(t, 0.3, 1.1, -0.4),
+         [[cmath.cos(0.15), -cmath.exp(-0.4j) * cmath.sin(0.15)],
+          [cmath.exp(1.1j) * cmath.sin(0.15), cmath.exp(0.7j) * cmath.cos(0.15)]]),
+    ]
+    ok = True
+    vectorized = quantum_engine.set_simd(True)
+    print(f"   vector kernels available: {vectorized}")
+    for label, build, matrix in cases:
+        worst, gap = 0.0, 0.0
+        for target in range(qubits):
+            results = []
+            for simd in (True, False):
+                quantum_engine.set_simd(simd)
+                circuit, before = random_input(qubits, 700 + target)
+                build(circuit, target)
+                results.append(amplitudes(circuit.execute()))
+            worst = max(worst, max_error(results[0], apply(before, matrix, [target])))
+            gap = max(gap, max_error(results[0], results[1]))
+        ok &= report(f"{label} on targets 0-{qubits - 1}, {gap:.2e} from the scalar loop", worst) and gap < TOLERANCE
+    # Turning the kernels off is always honoured
+    ok &= quantum_engine.set_simd(False) is False
+    quantum_engine.set_simd(True)
+    return ok
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Gate Builders Against Reference Matrices")
+    print("============================================================")
+
+    results = [
+        test_qft(),
+        test_global_phase(),
+        test_crz(),
+        test_toffoli_and_mcx(),
+        test_swap_and_iswap(),
+        test_controlled_rotations(),
+        test_custom_unitary(),
+        test_inverse(),
+        test_compose_and_tensor(),
+        test_u_and_p(),
+        test_ising_gates(),
+        test_controlled_modifier(),
+        test_fast_paths(),
+        test_entangled_presets(),
+        test_multi_controlled_phases(),
+        test_qudits(),
+        test_in_place_apply_gate(),
+        test_simd_butterflies(),
+    ]
+
+    if all(results):
+        print("\nGATES VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
@@ -0,0 +1,363 @@
+import math
+import random
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+Hamiltonian = quantum_engine.Hamiltonian
+TOLERANCE = 1e-4
+# Central differences in f32 are only good to about sqrt(eps)
+FD_STEP = 1e-2
+FD_TOLERANCE = 2e-3
+
+HAMILTONIAN = Hamiltonian([(0.5, "ZZI"), (-1.2, "XIX"), (0.3, "IYZ"), (0.8, "ZIZ")])
+
+
+def ansatz():
+    """Three-qubit hardware-efficient ansatz; "a" drives two gates, so its
+    derivative is the sum of both contributions"""
+    circuit = QuantumCircuit(3)
+    circuit.ry_param(0, "a")
+    circuit.rx_param(1, "b")
+    circuit.h(2)
+    circuit.cnot(0, 1)
+    circuit.cry_param(1, 2, "c")
+    circuit.rz_param(2, "d")
+    circuit.cnot(2, 0)
+    circuit.ry_param(1, "a")
+    circuit.rx_param(0, "e")
+    return circuit
+
+
+VALUES = {"a": 0.3, "b": -0.7, "c": 1.1, "d": 0.45, "e": -1.9}
+
+
+def energy(circuit, values, observable=HAMILTONIAN):
+    return circuit.execute_bound(values).expectation(observable)
+
+
+def finite_difference(circuit, values, observable=HAMILTONIAN):
+    grads = []
+    for name in circuit.parameters():
+        up = dict(values, **{name: values[name] + FD_STEP})
+        down = dict(values, **{name: values[name] - FD_STEP})
+        grads.append((energy(circuit, up, observable) - energy(circuit, down, observable)) / (2 * FD_STEP))
+    return grads
+
+
+def check(label, value, expected, tolerance=TOLERANCE):
+    print(f"   {label}: {value:.4f} (expected {expected:.4f})")
+    return abs(value - expected) < tolerance
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_parameters_and_bind():
+    print("\nTesting symbolic parameters, bind and execute_bound...")
+    circuit = ansatz()
+    names = circuit.parameters()
+    print(f"   parameters {names}")
+    ok = names == ["a", "b", "c", "d", "e"]
+
+    # A partial bind keeps the rest symbolic and leaves the original untouched
+    partial = circuit.bind({"a": VALUES["a"], "c": VALUES["c"]})
+    print(f"   after binding a and c: {partial.parameters()}; original still {circuit.parameters()}")
+    ok &= partial.parameters() == ["b", "d", "e"] and circuit.parameters() == names
+
+    # Binding in two steps, in one step and rebuilding with fixed angles all agree
+    staged = partial.bind({k: VALUES[k] for k in ("b", "d", "e")}).execute()
+    direct = circuit.execute_bound(VALUES)
+    fixed = QuantumCircuit(3)
+    fixed.ry(0, VALUES["a"])
+    fixed.rx(1, VALUES["b"])
+    fixed.h(2)
+    fixed.cnot(0, 1)
+    fixed.cry(1, 2, VALUES["c"])
+    fixed.rz(2, VALUES["d"])
+    fixed.cnot(2, 0)
+    fixed.ry(1, VALUES["a"])
+    fixed.rx(0, VALUES["e"])
+    reference = fixed.execute()
+    error = max(abs(complex(*x) - complex(*y)) for x, y in zip(staged.get_state_vector(), reference.get_state_vector()))
+    error = max(error, max(abs(complex(*x) - complex(*y))
+                           for x, y in zip(direct.get_state_vector(), reference.get_state_vector())))
+    print(f"   bound circuits against fixed angles: max amplitude error {error:.2e}")
+    ok &= error < TOLERANCE
+
+    # Rebinding the same circuit with new values must not reuse the old ones
+    moved = dict(VALUES, b=0.2)
+    ok &= abs(energy(circuit, moved) - energy(circuit, VALUES)) > 1e-3
+    return ok and all([
+        raises("unknown parameter", ValueError, lambda: circuit.bind({"zeta": 1.0})),
+        raises("executing with a parameter unbound", ValueError, lambda: circuit.execute_bound({"a": 0.1})),
+    ])
+
+
+def test_parameter_shift():
+    print("\nTesting parameter-shift gradients against central differences...")
+    circuit = ansatz()
+    ok = True
+    for label, values in (("VALUES", VALUES), ("shifted", {k: v + 0.8 for k, v in VALUES.items()})):
+        grads = circuit.gradients(HAMILTONIAN, values)
+        reference = finite_difference(circuit, values)
+        error = max(abs(g - r) for g, r in zip(grads, reference))
+        print(f"   {label}: {[round(g, 4) for g in grads]}, max error against finite differences {error:.2e}")
+        ok &= len(grads) == len(circuit.parameters()) and error < FD_TOLERANCE
+
+    # A single Pauli string works as the observable too
+    pauli = quantum_engine.PauliString("ZIX", 1.0)
+    error = max(abs(g - r) for g, r in zip(circuit.gradients(pauli, VALUES), finite_difference(circuit, VALUES, pauli)))
+    print(f"   PauliString observable: max error {error:.2e}")
+    return ok and error < FD_TOLERANCE and all([
+        raises("missing parameter value", ValueError, lambda: circuit.gradients(HAMILTONIAN, {"a": 0.1})),
+        raises("observable on too many qubits", ValueError,
+               lambda: circuit.gradients(Hamiltonian([(1.0, "ZZZZ")]), VALUES)),
+    ])
+
+
+def test_adjoint():
+    print("\nTesting adjoint gradients against parameter shift and central differences...")
+    circuit = ansatz()
+    adjoint = circuit.gradients_adjoint(HAMILTONIAN, VALUES)
+    shift = circuit.gradients(HAMILTONIAN, VALUES)
+    reference = finite_difference(circuit, VALUES)
+    shift_error = max(abs(a - s) for a, s in zip(adjoint, shift))
+    fd_error = max(abs(a - r) for a, r in zip(adjoint, reference))
+    print(f"   {[round(g, 4) for g in adjoint]}: parameter shift error {shift_error:.2e}, "
+          f"finite differences {fd_error:.2e}")
+    ok = len(adjoint) == len(shift) and shift_error < TOLERANCE and fd_error < FD_TOLERANCE
+
+    # Without symbols there is one derivative per rotation gate, in gate order
+    fixed = QuantumCircuit(2)
+    fixed.ry(0, 0.4)
+    fixed.cnot(0, 1)
+    fixed.rx(1, -0.9)
+    fixed.h(0)
+    fixed.rz(0, 1.3)
+    observable = Hamiltonian([(1.0, "XZ"), (0.5, "YY")])
+    grads = fixed.gradients_adjoint(observable)
+    angles = [0.4, -0.9, 1.3]
+    reference = []
+    for i in range(3):
+        shifted = []
+        for sign in (1, -1):
+            theta = list(angles)
+            theta[i] += sign * FD_STEP
+            c = QuantumCircuit(2)
+            c.ry(0, theta[0])
+            c.cnot(0, 1)
+            c.rx(1, theta[1])
+            c.h(0)
+            c.rz(0, theta[2])
+            shifted.append(c.execute().expectation(observable))
+        reference.append((shifted[0] - shifted[1]) / (2 * FD_STEP))
+    error = max(abs(g - r) for g, r in zip(grads, reference))
+    print(f"   unparameterized circuit: {len(grads)} derivatives, max error {error:.2e}")
+    return ok and len(grads) == 3 and error < FD_TOLERANCE
+
+
+PAULI = {"I": ((1, 0), (0, 1)), "X": ((0, 1), (1, 0)), "Y": ((0, -1j), (1j, 0)), "Z": ((1, 0), (0, -1))}
+
+
+def ground_energy(terms, qubits):
+    """Lowest eigenvalue of sum_k c_k P_k by power iteration on shift - H"""
+    dim = 1 << qubits
+    matrix = [[0j] * dim for _ in range(dim)]
+    for c, label in terms:
+        for col in range(dim):
+            row, phase = 0, c
+            for q, p in enumerate(label):
+                bit = col >> q & 1
+                out = next(r for r in range(2) if PAULI[p][r][bit] != 0)
+                phase *= PAULI[p][out][bit]
+                row |= out << q
+            matrix[row][col] += phase
+    shift = sum(abs(c) for c, _ in terms)
+    vector = [1.0 / (i + 1) for i in range(dim)]
+    for _ in range(2000):
+        vector = [shift * vector[r] - sum(matrix[r][k] * vector[k] for k in range(dim)) for r in range(dim)]
+        norm = sum(abs(v) ** 2 for v in vector) ** 0.5
+        vector = [v / norm for v in vector]
+    return sum(complex(vector[r]).conjugate() * matrix[r][k] * vector[k]
+               for r in range(dim) for k in range(dim)).real
+
+
+def test_vqe():
+    print("\nTesting VQE on a two-qubit transverse-field Ising model...")
+    terms = [(-1.0, "ZZ"), (-0.5, "XI"), (-0.5, "IX")]
+    exact = ground_energy(terms, 2)
+    circuit = QuantumCircuit(2)
+    circuit.ry_param(0, "t0")
+    circuit.ry_param(1, "t1")
+    circuit.cnot(0, 1)
+    circuit.ry_param(0, "t2")
+    circuit.ry_param(1, "t3")
+    hamiltonian = Hamiltonian(terms)
+    start = [0.4, -0.3, 0.2, 0.1]
+    initial = energy(circuit, dict(zip(circuit.parameters(), start)), hamiltonian)
+    ok = True
+    # SPSA's step is tiny whenever its two probes happen to agree, so it runs
+    # its whole budget rather than stop on a small energy change
+    runs = (("adam", 0.1, 1e-6, 1e-3), ("gradient_descent", 0.1, 1e-6, 1e-3), ("spsa", 0.3, 0.0, 2e-2))
+    for name, rate, stop, tolerance in runs:
+        vqe = quantum_engine.VQE(circuit, hamiltonian, optimizer=name, learning_rate=rate,
+                                 max_iterations=400, tolerance=stop, seed=3)
+        result = vqe.run(start)
+        history = result.history
+        ok &= check(f"{name} after {result.iterations} iterations", result.energy, exact, tolerance)
+        # The history starts at the initial energy and ends at the reported one,
+        # which the returned parameters reproduce
+        ok &= (len(history) == result.iterations + 1 and abs(history[0] - initial) < TOLERANCE
+               and abs(history[-1] - result.energy) < TOLERANCE
+               and sorted(result.parameters) == circuit.parameters()
+               and abs(energy(circuit, result.parameters, hamiltonian) - result.energy) < TOLERANCE)
+
+    # An optimizer instance carries its own hyperparameters
+    adam = quantum_engine.VQE(circuit, hamiltonian, optimizer=quantum_engine.Adam(learning_rate=0.05),
+                              max_iterations=400).run(start)
+    ok &= check("Adam(learning_rate=0.05) instance", adam.energy, exact, 1e-3)
+    fixed = QuantumCircuit(2)
+    fixed.h(0)
+    return ok and all([
+        raises("unknown optimizer", ValueError, lambda: quantum_engine.VQE(circuit, hamiltonian, optimizer="newton")),
+        raises("ansatz without parameters", ValueError, lambda: quantum_engine.VQE(fixed, hamiltonian)),
+    ])
+
+
+def test_execute_batch():
+    print("\nTesting batched parameter sweeps against one execute_bound per set...")
+    circuit = ansatz()
+    names = circuit.parameters()
+    rng = random.Random(5)
+    sets = [[rng.uniform(-3.14, 3.14) for _ in names] for _ in range(64)]
+    batch = circuit.execute_batch(sets, HAMILTONIAN)
+    expected = [energy(circuit, dict(zip(names, values))) for values in sets]
+    error = max(abs(a - b) for a, b in zip(batch, expected))
+    print(f"   {len(batch)} sets: max error {error:.2e}")
+    return len(batch) == len(sets) and error < TOLERANCE and circuit.execute_batch([], HAMILTONIAN) == [] and all([
+        raises("set of the wrong length", ValueError, lambda: circuit.execute_batch([[0.1, 0.2]], HAMILTONIAN)),
+        raises("observable on too many qubits", ValueError,
+               lambda: circuit.execute_batch(sets, Hamiltonian([(1.0, "ZZZZ")]))),
+    ])
+
+
+def test_optimizers():
+    print("\nTesting single steps of GradientDescent, Adam and SPSA against their update rules...")
+
+    def f(theta):
+        return sum((x - 1) ** 2 for x in theta)
+
+    def grad(theta):
+        return [2 * (x - 1) for x in theta]
+
+    start = [0.0, 3.0]
+    gd = quantum_engine.GradientDescent(learning_rate=0.1)
+    step = gd.step(start, f, grad)
+    ok = all(check(f"gradient descent theta[{i}]", s, x - 0.1 * g) for i, (s, x, g) in enumerate(zip(step, start, grad(start))))
+    ok &= gd.iterations == 1 and abs(gd.value - f(step)) < TOLERANCE and not gd.converged()
+    # Adam's bias-corrected first step is learning_rate * sign(g) whatever the gradient's size
+    step = quantum_engine.Adam(learning_rate=0.1).step(start, f, grad)
+    ok &= all(check(f"Adam theta[{i}]", s, x - 0.1 * math.copysign(1.0, g))
+              for i, (s, x, g) in enumerate(zip(step, start, grad(start))))
+    # SPSA moves every parameter by a_k diff / (2 c_k delta_i) with delta_i = +-1, so the move recovers
+    # delta up to a sign that cancels in diff / delta
+    calls = []
+    step = quantum_engine.SPSA(a=0.1, c=0.1, stability=10.0, seed=111).step(start, lambda t: calls.append(t) or f(t))
+    a_k, c_k = 0.1 / 11 ** 0.602, 0.1
+    delta = [-math.copysign(1.0, s - x) for s, x in zip(step, start)]
+    diff = f([x + c_k * d for x, d in zip(start, delta)]) - f([x - c_k * d for x, d in zip(start, delta)])
+    ok &= all(check(f"SPSA theta[{i}]", s, x - a_k * diff / (2 * c_k * d), 1e-3)
+              for i, (s, x, d) in enumerate(zip(step, start, delta)))
+    ok &= step == quantum_engine.SPSA(a=0.1, c=0.1, stability=10.0, seed=111).step(start, f)
+    # The two perturbed points, plus f before and after the first step for the convergence test
+    print(f"   SPSA evaluated the objective {len(calls)} times on its first step")
+    ok &= len(calls) == 4
+    theta, history = gd.minimize(f, start, grad, max_iterations=200)
+    print(f"   gradient descent minimize: {len(history) - 1} steps to {[round(x, 4) for x in theta]}")
+    ok &= (gd.converged() and len(history) == gd.iterations + 1 and abs(history[0] - f(start)) < TOLERANCE
+           and all(abs(x - 1) < 1e-2 for x in theta))
+    return ok and all([
+        raises("gradient descent without a gradient", ValueError, lambda: gd.step(start, f)),
+        raises("gradient of the wrong length", quantum_engine.DimensionMismatchError,
+               lambda: gd.step(start, f, lambda t: [0.0])),
+        raises("beta1 of 1", ValueError, lambda: quantum_engine.Adam(beta1=1.0)),
+        raises("SPSA perturbation of 0", ValueError, lambda: quantum_engine.SPSA(c=0.0)),
+    ])
+
+
+def test_expectation_cache():
+    print("\nTesting ExpectationCache hits, keys and eviction in execute_batch and VQE...")
+    circuit = ansatz()
+    names = circuit.parameters()
+    a, b, c = ([VALUES[n] + shift for n in names] for shift in (0.0, 0.1, 0.2))
+    cache = quantum_engine.ExpectationCache()
+    uncached = circuit.execute_batch([a, b, a], HAMILTONIAN)
+    first = circuit.execute_batch([a, b, a], HAMILTONIAN, cache=cache)
+    # The repeat inside one batch is simulated alongside the first, so only later batches hit
+    ok = first == uncached and (cache.hits, cache.misses, len(cache)) == (0, 3, 2)
+    ok &= circuit.execute_batch([a, b, a], HAMILTONIAN, cache=cache) == first and cache.hits == 3
+    print(f"   repeated batch: {cache.hits} hits, {cache.misses} misses, hit rate {cache.hit_rate:.2f}")
+    # Another observable or another circuit at the same parameters is a different key
+    z = quantum_engine.PauliString("ZZZ")
+    ok &= check("other observable", circuit.execute_batch([a], z, cache=cache)[0], energy(circuit, VALUES, z))
+    other = ansatz()
+    other.h(1)
+    ok &= check("other circuit", other.execute_batch([a], HAMILTONIAN, cache=cache)[0], energy(other, VALUES))
+    ok &= cache.hits == 3 and cache.misses == 5
+    # Capacity 2 keeps the two most recently used entries
+    small = quantum_engine.ExpectationCache(capacity=2)
+    hits = []
+    for values in (a, b, c, b, a, b, c):
+        before = small.hits
+        circuit.execute_batch([values], HAMILTONIAN, cache=small)
+        hits.append(small.hits > before)
+    print(f"   capacity 2 over a b c b a b c: hits {hits}")
+    ok &= hits == [False, False, False, True, False, True, False] and len(small) == 2
+    # A second identical VQE run is answered entirely from the cache
+    shared = quantum_engine.ExpectationCache()
+    runs = [quantum_engine.VQE(circuit, HAMILTONIAN, max_iterations=5, cache=shared).run(a) for _ in range(2)]
+    print(f"   two VQE runs: {shared.hits} hits, {shared.misses} misses")
+    ok &= shared.hits == shared.misses > 0 and runs[0].energy == runs[1].energy
+    shared.clear()
+    ok &= (shared.hits, shared.misses, len(shared), shared.hit_rate) == (0, 0, 0, 0.0)
+    return ok and raises("capacity 0", ValueError, lambda: quantum_engine.ExpectationCache(0))
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Parameterized Circuits, Gradients and Optimizers")
+    print("============================================================")
+
+    results = [
+        test_parameters_and_bind(),
+        test_parameter_shift(),
+        test_adjoint(),
+        test_vqe(),
+        test_execute_batch(),
+        test_optimizers(),
+        test_expectation_cache(),
+    ]
+
+    if all(results):
+        print("\nGRADIENTS VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
@@ -0,0 +1,459 @@
+import math
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+TOLERANCE = 1e-4
+SHOTS = 2000
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_mid_circuit_feedback():
+    print("\nTesting mid-circuit measurement, reset and c_if...")
+    # Copy a random measurement outcome onto qubit 1 through the classical bit
+    circuit = QuantumCircuit(2)
+    circuit.h(0)
+    circuit.measure_qubit(0, 0)
+    flip = QuantumCircuit(2)
+    flip.x(1)
+    circuit.c_if(0, flip)
+    circuit.measure_qubit(1, 1)
+    counts = circuit.run(SHOTS, seed=7)
+    print(f"   measured-and-copied register: {counts}")
+    ok = sorted(counts) == ["00", "11"] and abs(counts["11"] / SHOTS - 0.5) < 0.05
+
+    # Reset returns a measured |1> to |0> whatever the outcome
+    reset = QuantumCircuit(1)
+    reset.h(0)
+    reset.measure_qubit(0, 0)
+    reset.reset(0)
+    state, bits = reset.execute_measured(seed=3)
+    ok &= abs(state.probability("0") - 1.0) < TOLERANCE and bits in ("0", "1")
+
+    # Classical bits inside a c_if body are checked when the body is added
+    target = QuantumCircuit(2, 1)
+    measuring = QuantumCircuit(2)
+    measuring.measure_qubit(1, 3)
+    inner = QuantumCircuit(2, 2)
+    nested_measure = QuantumCircuit(2)
+    nested_measure.x(1)
+    inner.c_if(1, nested_measure)
+    ok &= all([
+        raises("c_if on a missing classical bit", ValueError, lambda: target.c_if(4, flip)),
+        raises("c_if body measuring past the register", ValueError, lambda: target.c_if(0, measuring)),
+        raises("nested c_if on a missing classical bit", ValueError, lambda: target.c_if(0, inner)),
+    ])
+    return ok and target.gates() == []
+
+
+def test_seeded_sampling():
+    print("\nTesting shot sampling and its seeding...")
+    circuit = QuantumCircuit(2)
+    circuit.ry(0, 1.2)
+    circuit.h(1)
+    state = circuit.execute()
+    probabilities = state.probabilities()
+    counts = state.measure(SHOTS, seed=11)
+    ok = sum(counts.values()) == SHOTS and counts == state.measure(SHOTS, seed=11)
+    ok &= counts != state.measure(SHOTS, seed=12)
+    worst = 0.0
+    for bits, count in counts.items():
+        index = sum(1 << q for q, bit in enumerate(bits) if bit == "1")
+        # Five standard errors of a binomial frequency
+        sigma = (probabilities[index] * (1 - probabilities[index]) / SHOTS) ** 0.5
+        worst = max(worst, abs(count / SHOTS - probabilities[index]) / sigma)
+    print(f"   {len(counts)} outcomes, worst deviation {worst:.2f} standard errors; same seed reproduces the counts")
+    ok &= worst < 5 and circuit.run(SHOTS, seed=11) == circuit.run(SHOTS, seed=11)
+    never = QuantumCircuit(2)
+    never.x(1)
+    return ok and never.execute().measure(100, seed=1) == {"01": 100}
+
+
+def reverse_index(index, qubits):
+    return int(format(index, f"0{qubits}b")[::-1], 2)
+
+
+def test_probabilities():
+    print("\nTesting probabilities and marginals against the amplitudes...")
+    n = 12
+    state = QuantumCircuit.random(n, 8, seed=43).execute()
+    squared = [re * re + im * im for re, im in state.get_state_vector()]
+    probabilities = state.probabilities()
+    error = max(abs(p - q) for p, q in zip(probabilities, squared))
+    big = state.probabilities(bit_order="big")
+    big_error = max(abs(big[reverse_index(i, n)] - squared[i]) for i in range(1 << n))
+    print(f"   {len(probabilities)} probabilities: max error {error:.2e}, big-endian {big_error:.2e}, "
+          f"total {sum(probabilities):.6f}")
+    ok = error < TOLERANCE and big_error < TOLERANCE and abs(sum(probabilities) - 1) < TOLERANCE
+
+    for qubits in ([0], [5, 2], [11, 0, 7]):
+        marginal = state.marginal_probabilities(qubits)
+        expected = [0.0] * (1 << len(qubits))
+        for index, p in enumerate(squared):
+            expected[sum((index >> q & 1) << k for k, q in enumerate(qubits))] += p
+        error = max(abs(a - b) for a, b in zip(marginal, expected))
+        print(f"   marginal over {qubits}: max error {error:.2e}")
+        ok &= len(marginal) == len(expected) and error < TOLERANCE
+
+    # The density-matrix marginal agrees with the pure one
+    small = QuantumCircuit.random(6, 8, seed=44).execute()
+    rho = quantum_engine.DensityMatrix(6)
+    rho.from_pure_state(small)
+    error = max(abs(a - b) for a, b in zip(rho.marginal_probabilities([5, 2]), small.marginal_probabilities([5, 2])))
+    return ok and error < TOLERANCE and all([
+        raises("repeated qubit", ValueError, lambda: state.marginal_probabilities([1, 1])),
+        raises("qubit out of range", quantum_engine.QubitIndexError, lambda: state.marginal_probabilities([n])),
+        raises("unknown bit order", ValueError, lambda: state.probabilities(bit_order="middle")),
+    ])
+
+
+def test_bitstring_queries():
+    print("\nTesting amplitude, probability and population queries by bitstring...")
+    n = 10
+    state = QuantumCircuit.random(n, 8, seed=45).execute()
+    vector = state.get_state_vector()
+    rho = quantum_engine.DensityMatrix(6)
+    small = QuantumCircuit.random(6, 8, seed=46).execute()
+    rho.from_pure_state(small)
+    small_probabilities = small.probabilities()
+    worst = 0.0
+    for index in (0, 1, 37, 512, 1023):
+        little = format(index, f"0{n}b")[::-1]
+        big = format(index, f"0{n}b")
+        re, im = vector[index]
+        for bits, order in ((little, "little"), (big, "big")):
+            a = state.amplitude(bits, bit_order=order)
+            worst = max(worst, abs(complex(*a) - complex(re, im)),
+                        abs(state.probability(bits, bit_order=order) - (re * re + im * im)))
+        if index < 64:
+            worst = max(worst, abs(rho.population(format(index, "06b")[::-1]) - small_probabilities[index]),
+                        abs(rho.population(format(index, "06b"), bit_order="big") - small_probabilities[index]))
+    print(f"   worst error over five basis states in both bit orders: {worst:.2e}")
+    return worst < TOLERANCE and all([
+        raises("bitstring of the wrong width", ValueError, lambda: state.amplitude("0101")),
+        raises("bitstring with a stray character", ValueError, lambda: state.probability("01" * 4 + "2x")),
+        raises("population of a wrong-width bitstring", ValueError, lambda: rho.population("01")),
+    ])
+
+
+def test_basis_measurement_and_snapshots():
+    print("\nTesting X and Y basis measurement and snapshots...")
+    theta = 0.8
+    ok = True
+    # RY(theta)|0> has <X> = sin(theta); RX(theta)|0> has <Y> = -sin(theta)
+    for basis, rotate, mean in (("X", "ry", math.sin(theta)), ("Y", "rx", -math.sin(theta)), ("Z", "ry", math.cos(theta))):
+        circuit = QuantumCircuit(2, 1)
+        getattr(circuit, rotate)(0, theta)
+        circuit.measure_basis(0, basis, 0)
+        circuit.snapshot("after")
+        counts = circuit.run(SHOTS, seed=51)
+        plus = counts.get("0", 0) / SHOTS
+        print(f"   {basis} basis: P(+1) = {plus:.3f} (expected {(1 + mean) / 2:.3f})")
+        ok &= abs(plus - (1 + mean) / 2) < 0.03
+        # The qubit is left in the observed eigenstate of that basis
+        _, snapshots = circuit.execute_snapshots(seed=52)
+        axis = "XYZ".index(basis)
+        ok &= abs(abs(snapshots[0][1][0][axis]) - 1) < TOLERANCE
+
+    # Snapshots record <X>, <Y>, <Z> per qubit without collapsing anything
+    circuit = QuantumCircuit.random(4, 3, seed=53)
+    circuit.snapshot("middle")
+    prefix = QuantumCircuit.random(4, 3, seed=53).execute()
+    circuit.compose(QuantumCircuit.random(4, 3, seed=54))
+    circuit.snapshot("end")
+    state, snapshots = circuit.execute_snapshots()
+    worst = 0.0
+    for (label, readings), reference in zip(snapshots, (prefix, state)):
+        for q, values in enumerate(readings):
+            for axis, value in zip("XYZ", values):
+                pauli = quantum_engine.PauliString("".join(axis if k == q else "I" for k in range(4)), 1.0)
+                worst = max(worst, abs(value - reference.expectation(pauli)))
+    print(f"   snapshots {[label for label, _ in snapshots]}: worst error {worst:.2e}")
+    ok &= [label for label, _ in snapshots] == ["middle", "end"] and worst < TOLERANCE
+    plain = circuit.execute().get_state_vector()
+    ok &= max(abs(complex(*a) - complex(*b)) for a, b in zip(state.get_state_vector(), plain)) < TOLERANCE
+    return ok and raises("unknown basis", ValueError, lambda: QuantumCircuit(1, 1).measure_basis(0, "W", 0))
+
+
+def test_bit_order():
+    print("\nTesting big- and little-endian output and reverse_bits...")
+    n = 4
+    # |q0 q1 q2 q3> = |1 1 0 0> reads "1100" little-endian and "0011" big-endian
+    circuit = QuantumCircuit(n, n)
+    circuit.x(0)
+    circuit.x(1)
+    for q in range(n):
+        circuit.measure_qubit(q, q)
+    little = circuit.run(50, seed=66)
+    big = circuit.run(50, seed=66, bit_order="big")
+    _, register = circuit.execute_measured(seed=66, bit_order="big")
+    print(f"   run: little {little}, big {big}; execute_measured big {register!r}")
+    ok = little == {"1100": 50} and big == {"0011": 50} and register == "0011"
+
+    # State-level queries agree in either convention
+    state = QuantumCircuit.random(n, 5, seed=67).execute()
+    ok &= abs(state.probability("1011") - state.probability("1101", bit_order="big")) < TOLERANCE
+    ok &= state.top_k(3, bit_order="big") == [(bits[::-1], p) for bits, p in state.top_k(3)]
+    sampled = state.measure(500, seed=68)
+    ok &= state.measure(500, seed=68, bit_order="big") == {bits[::-1]: c for bits, c in sampled.items()}
+
+    # reverse_bits relabels qubit q as n - 1 - q, so little-endian output reads like the big-endian original
+    reversed_state = state.reverse_bits()
+    error = max(abs(a - b) for a, b in zip(reversed_state.probabilities(), state.probabilities(bit_order="big")))
+    print(f"   reverse_bits against big-endian probabilities: max error {error:.2e}")
+    flipped = circuit.reverse_bits()
+    ok &= error < TOLERANCE and flipped.run(50, seed=66) == big
+    rho = quantum_engine.DensityMatrix(n)
+    rho.from_pure_state(state)
+    ok &= max(abs(a - b) for a, b in zip(rho.reverse_bits().probabilities(), reversed_state.probabilities())) < TOLERANCE
+    return ok and raises("unknown bit order", ValueError, lambda: circuit.run(10, bit_order="middle"))
+
+
+def draws():
+    """One call of each stochastic API that was not given a seed"""
+    circuit = QuantumCircuit.random(4, 3)
+    circuit.h(0)
+    mid = QuantumCircuit(3)
+    for q in range(3):
+        mid.h(q)
+        mid.measure_qubit(q, q)
+    batch = quantum_engine.execute_many([circuit, QuantumCircuit.ghz(4)], shots=200)
+    shots = [mid.execute_measured()[1] for _ in range(8)]
+    return [circuit.gates(), circuit.run(SHOTS), circuit.execute().measure(SHOTS), batch, shots]
+
+
+def test_global_seed():
+    print("\nTesting that set_seed makes every unseeded call reproducible...")
+    quantum_engine.set_seed(1074)
+    first = draws()
+    quantum_engine.set_seed(1074)
+    second = draws()
+    quantum_engine.set_seed(1075)
+    other = draws()
+    same = [a == b for a, b in zip(first, second)]
+    differ = [a != b for a, b in zip(first, other)]
+    print(f"   same seed reproduces {same}, another seed changes {differ}")
+
+    # An explicit seed wins over the global generator, whatever its state
+    ghz = QuantumCircuit.ghz(5)
+    quantum_engine.set_seed(1)
+    pinned = ghz.run(SHOTS, seed=99)
+    quantum_engine.set_seed(2)
+    pinned_again = ghz.run(SHOTS, seed=99)
+    quantum_engine.set_seed(None)
+    fresh = [QuantumCircuit.random(6, 4).gates() for _ in range(2)]
+    print(f"   seed=99 under two global seeds equal: {pinned == pinned_again}; "
+          f"OS entropy after set_seed(None) gives distinct circuits: {fresh[0] != fresh[1]}")
+    return all(same) and all(differ) and pinned == pinned_again and fresh[0] != fresh[1]
+
+
+def confused(populations, qubits, flips):
+    """Populations after independent readout errors; flips[q] = (p1_given0, p0_given1)"""
+    out = [0.0] * len(populations)
+    for i, p in enumerate(populations):
+        for j in range(len(populations)):
+            weight = p
+            for q in range(qubits):
+                p10, p01 = flips.get(q, (0.0, 0.0))
+                bit, read = i >> q & 1, j >> q & 1
+                weight *= (p01 if read == 0 else 1 - p01) if bit else (p10 if read else 1 - p10)
+            out[j] += weight
+    return out
+
+
+def worst_deviation(counts, expected, qubits, shots):
+    """Largest |frequency - p| in units of the binomial standard deviation"""
+    key = lambda i: "".join(str(i >> q & 1) for q in range(qubits))
+    return max(abs(counts.get(key(i), 0) / shots - p) / math.sqrt(max(p * (1 - p), 1e-12) / shots)
+               for i, p in enumerate(expected) if p > 1e-9)
+
+
+def test_density_sampling():
+    print("\nTesting DensityMatrix.sample against the populations, with and without readout errors...")
+    rho = QuantumCircuit.random(3, 4, seed=87).execute_density()
+    rho.apply_depolarizing(1, 0.3)
+    populations = rho.diagonal()
+    shots = 20000
+    counts = rho.sample(shots, seed=5)
+    ideal = worst_deviation(counts, populations, 3, shots)
+    flips = {0: (0.1, 0.2), 2: (0.05, 0.0)}
+    noise = quantum_engine.NoiseModel()
+    for q, (p10, p01) in flips.items():
+        noise.add_readout_error(q, p10, p01)
+    noisy = worst_deviation(rho.sample(shots, seed=6, noise_model=noise), confused(populations, 3, flips), 3, shots)
+    print(f"   worst deviation in standard deviations: ideal {ideal:.2f}, with readout errors {noisy:.2f}")
+    big = rho.sample(500, seed=5, bit_order="big")
+    return (ideal < 4.5 and noisy < 4.5 and sum(counts.values()) == shots
+            and big == {k[::-1]: v for k, v in rho.sample(500, seed=5).items()}
+            and rho.sample(500, seed=7) == rho.sample(500, seed=7))
+
+
+def test_snapshot_restore():
+    print("\nTesting restore() branches and QuantumState.clone_state...")
+    prefix = QuantumCircuit.random(4, 4, seed=99)
+
+    def continuation(circuit, seed):
+        for name, targets, params in QuantumCircuit.random(4, 3, seed=seed).gates():
+            getattr(circuit, name)(*targets, *params)
+
+    branched = prefix.bind({})
+    branched.snapshot("fork")
+    continuation(branched, 100)
+    branched.restore("fork")
+    continuation(branched, 101)
+    direct = prefix.bind({})
+    continuation(direct, 101)
+    error = max(abs(complex(*a) - complex(*b))
+                for a, b in zip(branched.execute().get_state_vector(), direct.execute().get_state_vector()))
+    print(f"   prefix + first branch, restore, second branch vs prefix + second branch: max error {error:.2e}")
+
+    # Classical bits written before restore keep their values: q0 was |1> when measured
+    measured = QuantumCircuit(2, 2)
+    measured.snapshot("start")
+    measured.x(0)
+    measured.measure_qubit(0, 0)
+    measured.restore("start")
+    measured.measure_qubit(1, 1)
+    counts = measured.run(50, seed=1)
+    print(f"   counts after restoring past a measurement: {counts}")
+
+    state = prefix.execute()
+    copy = state.clone_state()
+    copy.apply_gate("X", 0)
+    untouched = max(abs(complex(*a) - complex(*b))
+                    for a, b in zip(state.get_state_vector(), prefix.execute().get_state_vector()))
+    return (error < TOLERANCE and counts == {"10": 50} and untouched == 0.0
+            and copy.get_state_vector() != state.get_state_vector()
+            and raises("restore without a snapshot", ValueError, lambda: QuantumCircuit(1).restore("nowhere"))
+            and raises("restore in a density-matrix run", ValueError, branched.execute_density))
+
+
+def test_iter_amplitudes():
+    print("\nTesting iter_amplitudes against the filtered state vector...")
+    state = QuantumCircuit.random(6, 3, seed=110).execute()
+    vector = state.get_state_vector()
+    ok = True
+    for threshold in (0.0, 0.05, 0.2):
+        streamed = list(state.iter_amplitudes(threshold))
+        expected = [(i, re, im) for i, (re, im) in enumerate(vector) if (re * re + im * im) ** 0.5 > threshold]
+        print(f"   threshold {threshold}: {len(streamed)} of {len(vector)} amplitudes")
+        ok &= streamed == expected
+    iterator = state.iter_amplitudes(0.2)
+    ok &= iter(iterator) is iterator and next(iterator) == list(state.iter_amplitudes(0.2))[0]
+    ghz = list(QuantumCircuit.ghz(20).execute().iter_amplitudes(1e-3))
+    print(f"   20-qubit GHZ above 1e-3: {[(i, round(re, 4)) for i, re, _ in ghz]}")
+    return ok and [i for i, _, _ in ghz] == [0, (1 << 20) - 1]
+
+
+def test_top_k():
+    print("\nTesting top_k of states and density matrices against a full sort...")
+    circuit = QuantumCircuit.random(5, 4, seed=111)
+    state = circuit.execute()
+    rho = circuit.execute_density()
+    rho.apply_depolarizing(2, 0.2)
+    ok = True
+    for label, source, populations in (("state", state, [re * re + im * im for re, im in state.get_state_vector()]),
+                                       ("density matrix", rho, rho.diagonal())):
+        ranked = sorted(range(32), key=lambda i: -populations[i])
+        for k in (1, 5, 32):
+            top = source.top_k(k)
+            bits = ["".join(str(i >> q & 1) for q in range(5)) for i in ranked[:k]]
+            error = max(abs(p - populations[int(b[::-1], 2)]) for b, p in top)
+            ok &= len(top) == k and [b for b, _ in top] == bits and error < TOLERANCE
+        big = source.top_k(3, bit_order="big")
+        ok &= [b for b, _ in big] == [b[::-1] for b, _ in source.top_k(3)]
+        print(f"   {label}: top 3 {[(b, round(p, 4)) for b, p in source.top_k(3)]}")
+    return ok and len(state.top_k(100)) == 32 and state.top_k(0) == []
+
+
+def postselected(state, qubit, value):
+    """Amplitudes of state with `qubit` = `value` kept and renormalized, and that branch's probability"""
+    kept = [complex(*a) if (i >> qubit) & 1 == value else 0j for i, a in enumerate(state.get_state_vector())]
+    p = sum(abs(a) ** 2 for a in kept)
+    return [a / math.sqrt(p) for a in kept], p
+
+
+def test_postselect():
+    print("\nTesting postselection of states and mixtures against projected amplitudes...")
+    ok = True
+    a = QuantumCircuit.random(3, 4, seed=108).execute()
+    b = QuantumCircuit.random(3, 4, seed=109).execute()
+    for qubit, value in [(0, 0), (1, 1), (2, 1)]:
+        expected, p = postselected(a, qubit, value)
+        state = a.clone_state()
+        prob = state.postselect(qubit, value)
+        error = max(abs(complex(*x) - y) for x, y in zip(state.get_state_vector(), expected))
+        print(f"   state, qubit {qubit} = {value}: probability {prob:.4f} (expected {p:.4f}), max amplitude error {error:.2e}")
+        ok &= abs(prob - p) < TOLERANCE and error < TOLERANCE
+        # A mixture w|a><a| + (1 - w)|b><b| keeps each branch weighted by its own probability
+        w = 0.3
+        rho = quantum_engine.DensityMatrix.from_ensemble([a, b], [w, 1 - w])
+        prob = rho.postselect(qubit, value)
+        expected_b, pb = postselected(b, qubit, value)
+        total = w * p + (1 - w) * pb
+        reference = quantum_engine.DensityMatrix.from_ensemble(
+            [quantum_engine.QuantumState.from_amplitudes([(x.real, x.imag) for x in expected]),
+             quantum_engine.QuantumState.from_amplitudes([(x.real, x.imag) for x in expected_b])],
+            [w * p / total, (1 - w) * pb / total])
+        distance = rho.trace_distance(reference)
+        print(f"   mixture: probability {prob:.4f} (expected {total:.4f}), trace distance {distance:.2e}")
+        ok &= abs(prob - total) < TOLERANCE and distance < TOLERANCE
+    flip = QuantumCircuit(2)
+    flip.x(0)
+    state = flip.execute()
+    rho = flip.execute_density()
+    ok &= all([
+        raises("state branch of probability 0", ValueError, lambda: state.postselect(0, 0)),
+        raises("density branch of probability 0", ValueError, lambda: rho.postselect(0, 0)),
+        raises("qubit out of range", quantum_engine.QubitIndexError, lambda: state.postselect(5, 1)),
+        raises("value 2", ValueError, lambda: rho.postselect(0, 2)),
+    ])
+    # The failed projections leave both untouched
+    return (ok and abs(complex(*state.get_state_vector()[1]) - 1) < TOLERANCE
+            and abs(rho.expectation(quantum_engine.PauliString("ZI")) + 1) < TOLERANCE)
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Measurement, Feedback and Sampling")
+    print("============================================================")
+
+    results = [
+        test_mid_circuit_feedback(),
+        test_seeded_sampling(),
+        test_probabilities(),
+        test_bitstring_queries(),
+        test_basis_measurement_and_snapshots(),
+        test_bit_order(),
+        test_global_seed(),
+        test_density_sampling(),
+        test_snapshot_restore(),
+        test_iter_amplitudes(),
+        test_top_k(),
+        test_postselect(),
+    ]
+
+    if all(results):
+        print("\nMEASUREMENT VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
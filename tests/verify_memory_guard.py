@@ -0,0 +1,103 @@
+import os
+import sys
+import tempfile
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+QuantumState = quantum_engine.QuantumState
+
+# 1 MiB holds a 16-qubit f32 state vector (2^16 * 8 bytes = 512 KiB) but not
+# an 18-qubit one
+LIMIT = 1 << 20
+SMALL = 16
+LARGE = 18
+
+
+def raises_memory_error(label, allocate):
+    try:
+        allocate()
+    except MemoryError as error:
+        print(f"   {label}: MemoryError ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of MemoryError ({error})")
+        return False
+    print(f"   {label}: allocated")
+    return False
+
+
+def test_default_limit():
+    print("\nTesting registers past physical memory under the default limit...")
+    quantum_engine.set_max_memory(None)
+    return all([
+        raises_memory_error("QuantumState(60)", lambda: QuantumState(60)),
+        raises_memory_error("DensityMatrix(40)", lambda: quantum_engine.DensityMatrix(40)),
+        raises_memory_error("40-qubit GHZ MPS to a dense vector",
+                            lambda: QuantumCircuit.ghz(40).execute_mps().get_state_vector()),
+        raises_memory_error("QuantumState(2).add_qubits(40)", lambda: QuantumState(2).add_qubits(40)),
+        raises_memory_error("QuantumState(20).kron_with(QuantumState(20))",
+                            lambda: QuantumState(20).kron_with(QuantumState(20))),
+    ])
+
+
+def test_configured_limit():
+    print(f"\nTesting a {LIMIT}-byte limit set with set_max_memory...")
+    with tempfile.TemporaryDirectory() as directory:
+        # Files written before the limit is lowered, read back under it
+        checkpoint = os.path.join(directory, "large.qeck")
+        QuantumState(LARGE).save(checkpoint)
+        disk = QuantumCircuit.ghz(LARGE).execute_on_disk(os.path.join(directory, "large.bin"), block_qubits=10)
+        mixed = quantum_engine.DensityMatrix(LARGE // 2)
+
+        returned = quantum_engine.set_max_memory(LIMIT)
+        try:
+            small = QuantumState(SMALL)
+            print(f"   set_max_memory returned {returned}; a {SMALL}-qubit state still fits "
+                  f"({len(small.get_state_vector())} amplitudes)")
+            prepared = QuantumCircuit(2)
+            prepared.initialize("01")
+            return returned == LIMIT and all([
+                raises_memory_error("QuantumState", lambda: QuantumState(LARGE)),
+                raises_memory_error("QuantumStateF64", lambda: quantum_engine.QuantumStateF64(LARGE - 1)),
+                raises_memory_error("DensityMatrix", lambda: quantum_engine.DensityMatrix(LARGE // 2)),
+                raises_memory_error("execute", lambda: QuantumCircuit.ghz(LARGE).execute()),
+                raises_memory_error("execute_density", lambda: QuantumCircuit.ghz(LARGE // 2).execute_density()),
+                raises_memory_error("MPS to a dense vector",
+                                    lambda: QuantumCircuit.ghz(LARGE).execute_mps().get_state_vector()),
+                raises_memory_error("DiskState.to_state", disk.to_state),
+                raises_memory_error("checkpoint load", lambda: QuantumState.load(checkpoint)),
+                raises_memory_error("add_qubits", lambda: QuantumState(SMALL).add_qubits(LARGE - SMALL)),
+                raises_memory_error("kron_with", lambda: QuantumState(LARGE // 2).kron_with(QuantumState(LARGE // 2))),
+                raises_memory_error("initialize from a bitstring", lambda: QuantumCircuit(LARGE).initialize("0" * LARGE)),
+                raises_memory_error("remap padding an initial state",
+                                    lambda: prepared.remap({0: 0, 1: LARGE - 1})),
+                raises_memory_error("purify", mixed.purify),
+                raises_memory_error("sparse to_state",
+                                    lambda: QuantumCircuit.ghz(LARGE).execute(backend="sparse").to_state()),
+            ])
+        finally:
+            quantum_engine.set_max_memory(None)
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying the Memory Guard on Dense Allocations")
+    print("============================================================")
+
+    results = [
+        test_default_limit(),
+        test_configured_limit(),
+    ]
+
+    if all(results):
+        print("\nMEMORY GUARD VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
@@ -0,0 +1,668 @@
+import math
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+QuantumState = quantum_engine.QuantumState
+DensityMatrix = quantum_engine.DensityMatrix
+TOLERANCE = 1e-4
+
+
+def bloch(rho, qubit=0, qubits=1):
+    """(<X>, <Y>, <Z>) of one qubit of rho"""
+    def label(p):
+        return "".join(p if q == qubit else "I" for q in range(qubits))
+    return [rho.expectation(quantum_engine.PauliString(label(p))) for p in "XYZ"]
+
+
+def pauli_labels(qubits):
+    labels = [""]
+    for _ in range(qubits):
+        labels = [l + p for l in labels for p in "IXYZ"]
+    return labels
+
+
+def close(label, values, expected):
+    error = max(abs(a - b) for a, b in zip(values, expected))
+    print(f"   {label}: {[round(v, 4) for v in values]} (max error {error:.2e})")
+    return len(values) == len(expected) and error < TOLERANCE
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_ensembles():
+    print("\nTesting from_ensemble and accumulate...")
+    zero = QuantumState.from_amplitudes([(1.0, 0.0), (0.0, 0.0)])
+    one = QuantumState.from_amplitudes([(0.0, 0.0), (1.0, 0.0)])
+    plus = QuantumState.from_amplitudes([(0.5 ** 0.5, 0.0), (0.5 ** 0.5, 0.0)])
+    # Weights 3 : 1 are normalised to 3/4 and 1/4
+    mixture = DensityMatrix.from_ensemble([zero, one], [3.0, 1.0])
+    ok = close("3:1 mixture of |0> and |1>", mixture.probabilities(), [0.75, 0.25])
+    ok &= close("its purity", [mixture.purity()], [0.75 ** 2 + 0.25 ** 2])
+    ok &= close("zero-weight member dropped", DensityMatrix.from_ensemble([plus, one], [2.0, 0.0]).probabilities(),
+                [0.5, 0.5])
+    # accumulate adds w |psi><psi| onto the matrix as it stands
+    running = DensityMatrix.from_ensemble([zero], [1.0])
+    running.accumulate(one, 0.5)
+    ok &= close("|0><0| + 0.5 |1><1|", running.probabilities(), [1.0, 0.5])
+    return ok and all([
+        raises("negative weight", ValueError, lambda: DensityMatrix.from_ensemble([zero, one], [1.5, -0.5])),
+        raises("NaN weight", ValueError, lambda: DensityMatrix.from_ensemble([zero], [float("nan")])),
+        raises("infinite weight", ValueError, lambda: DensityMatrix.from_ensemble([zero, one], [float("inf"), 1.0])),
+        raises("all-zero weights", ValueError, lambda: DensityMatrix.from_ensemble([zero, one], [0.0, 0.0])),
+        raises("fewer weights than states", ValueError, lambda: DensityMatrix.from_ensemble([zero, one], [1.0])),
+        raises("mixed qubit counts", quantum_engine.DimensionMismatchError,
+               lambda: DensityMatrix.from_ensemble([zero, QuantumState(2)], [1.0, 1.0])),
+        raises("accumulate with a negative weight", ValueError, lambda: running.accumulate(one, -1.0)),
+        raises("accumulate with a NaN weight", ValueError, lambda: running.accumulate(one, float("nan"))),
+    ])
+
+
+def test_density_matrix_gates():
+    print("\nTesting gates applied directly to a DensityMatrix against the pure evolution...")
+    rho = DensityMatrix(2)
+    rho.apply_gate("H", 0)
+    rho.apply_gate("RX", 1, 0.4)
+    rho.apply_cnot(0, 1)
+    rho.apply_gate("T", 1)
+    rho.apply_gate("RZ", 0, -0.9)
+    circuit = QuantumCircuit(2)
+    circuit.h(0)
+    circuit.rx(1, 0.4)
+    circuit.cnot(0, 1)
+    circuit.t(1)
+    circuit.rz(0, -0.9)
+    state = circuit.execute()
+    labels = pauli_labels(2)
+    # Every two-qubit Pauli expectation pins down the whole 4 x 4 matrix
+    ok = close("all 16 Pauli expectations", [rho.expectation(quantum_engine.PauliString(l)) for l in labels],
+               [state.expectation(quantum_engine.PauliString(l)) for l in labels])
+    ok &= close("purity", [rho.purity()], [1.0])
+    return ok and all([
+        raises("unknown gate name", quantum_engine.InvalidGateError, lambda: rho.apply_gate("FOO", 0)),
+        raises("target out of range", quantum_engine.QubitIndexError, lambda: rho.apply_gate("H", 2)),
+        raises("cnot onto itself", quantum_engine.InvalidGateError, lambda: rho.apply_cnot(1, 1)),
+    ])
+
+
+def prepared():
+    """One qubit with every Bloch component non-zero: RZ(0.6) RY(1.0) |0>"""
+    circuit = QuantumCircuit(1)
+    circuit.ry(0, 1.0)
+    circuit.rz(0, 0.6)
+    return circuit
+
+
+def test_amplitude_damping():
+    print("\nTesting Kraus amplitude damping against the damped Bloch vector...")
+    gamma = 0.3
+    x, y, z = bloch(prepared().execute_density())
+    rho = prepared().execute_density()
+    rho.apply_amplitude_damping(gamma, 0)
+    # x, y shrink by sqrt(1 - gamma); z relaxes towards +1 (|0>)
+    damped = [x * math.sqrt(1 - gamma), y * math.sqrt(1 - gamma), gamma + (1 - gamma) * z]
+    ok = close("Bloch vector after damping qubit 0", bloch(rho), damped)
+
+    # Damping one qubit of a pair leaves the other untouched
+    pair = QuantumCircuit(2)
+    pair.h(0)
+    pair.x(1)
+    rho = pair.execute_density()
+    rho.apply_amplitude_damping(1.0, 1)
+    ok &= close("full damping of qubit 1 of |+>|1>", rho.probabilities(), [0.5, 0.5, 0.0, 0.0])
+    ok &= close("qubit 0 keeps <X>", [bloch(rho, 0, 2)[0]], [1.0])
+    every = pair.execute_density()
+    every.apply_amplitude_damping(gamma)
+    ok &= close("all qubits damped: <Z> of each", [bloch(every, q, 2)[2] for q in range(2)], [gamma, -1 + 2 * gamma])
+    return ok and raises("qubit out of range", quantum_engine.QubitIndexError, lambda: rho.apply_amplitude_damping(0.1, 2))
+
+
+def test_pauli_channels():
+    print("\nTesting depolarizing, bit-flip, phase-flip and phase damping channels...")
+    p = 0.2
+    x, y, z = bloch(prepared().execute_density())
+    shrink = 1 - 2 * p
+    cases = [
+        ("bit flip", lambda rho: rho.apply_bit_flip(0, p), [x, shrink * y, shrink * z]),
+        ("phase flip", lambda rho: rho.apply_phase_flip(0, p), [shrink * x, shrink * y, z]),
+        # sqrt(1-p) I, sqrt(p/3) X, Y, Z shrinks the whole Bloch vector by 1 - 4p/3
+        ("depolarizing", lambda rho: rho.apply_depolarizing(0, p), [(1 - 4 * p / 3) * c for c in (x, y, z)]),
+        ("phase damping", lambda rho: rho.apply_phase_damping(p, 0), [(1 - p) ** 0.5 * x, (1 - p) ** 0.5 * y, z]),
+    ]
+    ok = True
+    for label, channel, expected in cases:
+        rho = prepared().execute_density()
+        channel(rho)
+        ok &= close(label, bloch(rho), expected)
+        ok &= close(f"{label} keeps the trace", [sum(rho.probabilities())], [1.0])
+
+    # Phase damping without a qubit dephases each qubit once, so a Bell pair's
+    # coherence decays by (1 - p), not sqrt(1 - p)
+    bell = QuantumCircuit.bell().execute_density()
+    bell.apply_phase_damping(p)
+    ok &= close("Bell <XX> after phase damping every qubit", [bell.expectation(quantum_engine.PauliString("XX"))], [1 - p])
+    return ok
+
+
+def test_noise_model():
+    print("\nTesting NoiseModel channels against the same channels applied by hand...")
+    p, q = 0.1, 0.05
+    model = quantum_engine.NoiseModel()
+    ok = model.is_ideal()
+    model.add_gate_error("cnot", "depolarizing", p)
+    model.add_qubit_error(1, "bit_flip", q)
+    ok &= not model.is_ideal()
+    circuit = QuantumCircuit(2)
+    circuit.h(0)
+    circuit.cnot(0, 1)
+    noisy = circuit.execute_noisy(model)
+
+    # Gate channels on the qubits the gate touched, then idle channels, after every gate
+    rho = DensityMatrix(2)
+    rho.apply_gate("H", 0)
+    rho.apply_bit_flip(1, q)
+    rho.apply_cnot(0, 1)
+    rho.apply_depolarizing(0, p)
+    rho.apply_depolarizing(1, p)
+    rho.apply_bit_flip(1, q)
+    labels = pauli_labels(2)
+    ok &= close("all 16 Pauli expectations", [noisy.expectation(quantum_engine.PauliString(l)) for l in labels],
+                [rho.expectation(quantum_engine.PauliString(l)) for l in labels])
+    ideal = circuit.execute_noisy(quantum_engine.NoiseModel())
+    ok &= close("an empty model leaves the state pure", [ideal.purity()], [1.0])
+    return ok and all([
+        raises("unknown channel", ValueError, lambda: model.add_gate_error("h", "teleport", 0.1)),
+    ])
+
+
+def test_trajectories():
+    print("\nTesting Monte Carlo trajectories against the density matrix...")
+    model = quantum_engine.NoiseModel()
+    model.add_gate_error("h", "amplitude_damping", 0.2)
+    model.add_gate_error("cnot", "depolarizing", 0.1)
+    circuit = QuantumCircuit.ghz(3)
+    circuit.h(2)
+    exact = circuit.execute_noisy(model)
+    observable = quantum_engine.PauliString("ZZI")
+    n = 3000
+    result = circuit.execute_trajectories(model, n, seed=4, observables=[observable])
+    worst = max(abs(a - b) for a, b in zip(result.probabilities, exact.probabilities()))
+    print(f"   {n} trajectories: worst population error {worst:.4f}, "
+          f"<ZZI> {result.expectations[0]:.4f} vs {exact.expectation(observable):.4f}")
+    again = circuit.execute_trajectories(model, n, seed=4, observables=[observable])
+    # Each population is an average of n Bernoulli-like weights, so errors shrink as 1/sqrt(n)
+    return (worst < 4 / n ** 0.5 and abs(result.expectations[0] - exact.expectation(observable)) < 6 / n ** 0.5
+            and again.probabilities == result.probabilities)
+
+
+def flip_rates(counts, qubits, actual):
+    """Per-qubit fraction of shots whose bit differs from `actual` (character i is qubit i)"""
+    shots = sum(counts.values())
+    return [sum(c for bits, c in counts.items() if bits[q] != actual[q]) / shots for q in range(qubits)]
+
+
+def test_readout_errors():
+    print("\nTesting readout confusion on sampled counts...")
+    shots = 20000
+    model = quantum_engine.NoiseModel()
+    model.add_readout_error(0, 0.1, 0.0)
+    model.add_readout_error(1, 0.0, 0.25)
+    model.add_readout_error(2, 0.05, 0.15)
+    # Qubit 2 prepared in |1> flips with p0_given1, qubits 0 and 1 in |0> with p1_given0
+    circuit = QuantumCircuit(3)
+    circuit.x(2)
+    expected = [0.1, 0.0, 0.15]
+    rates = flip_rates(circuit.run(shots, seed=57, noise_model=model), 3, "001")
+    ok = max(abs(a - b) for a, b in zip(rates, expected)) < 0.01
+    print(f"   run(noise_model=...): flip rates {[round(r, 3) for r in rates]} (expected {expected})")
+
+    excited = QuantumCircuit(3)
+    for q in range(3):
+        excited.x(q)
+    rates = flip_rates(excited.execute_density().sample(shots, seed=58, noise_model=model), 3, "111")
+    print(f"   DensityMatrix.sample of |111>: flip rates {[round(r, 3) for r in rates]} (expected [0.0, 0.25, 0.15])")
+    ok &= max(abs(a - b) for a, b in zip(rates, [0.0, 0.25, 0.15])) < 0.01
+
+    # apply_readout post-processes existing counts and keeps the shot total
+    noisy = model.apply_readout({"000": shots}, seed=59)
+    rates = flip_rates(noisy, 3, "000")
+    print(f"   apply_readout on {shots} shots of '000': flip rates {[round(r, 3) for r in rates]}")
+    ok &= sum(noisy.values()) == shots and max(abs(a - b) for a, b in zip(rates, [0.1, 0.0, 0.05])) < 0.01
+    return ok and not model.is_ideal() and quantum_engine.NoiseModel().is_ideal() and all([
+        raises("probability above one", ValueError, lambda: model.add_readout_error(0, 1.5, 0.0)),
+        raises("qubit out of range for the counts", quantum_engine.QubitIndexError, lambda: model.apply_readout({"00": 10})),
+    ])
+
+
+def test_thermal_relaxation():
+    print("\nTesting T1/T2 relaxation against exponential decay in physical time...")
+    t1, t2 = 400.0, 300.0
+    model = quantum_engine.NoiseModel()
+    model.add_thermal_relaxation(t1, t2)
+    model.set_gate_duration("z", 20.0)
+    ok = True
+    for steps in (1, 5, 20):
+        # X (default 50) then Z gates (20 each) leave only relaxation acting on the populations
+        excited = QuantumCircuit(1)
+        excited.x(0)
+        for _ in range(steps):
+            excited.z(0)
+        # H then Z gates flip the sign of the coherence, whose length decays with T2
+        plus = QuantumCircuit(1)
+        plus.h(0)
+        for _ in range(steps):
+            plus.z(0)
+        elapsed = 50.0 + 20.0 * steps
+        _, _, z = bloch(excited.execute_noisy(model))
+        transverse = math.hypot(*bloch(plus.execute_noisy(model))[:2])
+        print(f"   t = {elapsed:.0f}: P(1) = {(1 - z) / 2:.4f} (expected {math.exp(-elapsed / t1):.4f}), "
+              f"coherence {transverse:.4f} (expected {math.exp(-elapsed / t2):.4f})")
+        ok &= abs((1 - z) / 2 - math.exp(-elapsed / t1)) < TOLERANCE
+        ok &= abs(transverse - math.exp(-elapsed / t2)) < TOLERANCE
+
+    # Gates on other qubits do not age a qubit that takes no part in them
+    idle = QuantumCircuit(2)
+    idle.x(0)
+    for _ in range(10):
+        idle.h(1)
+    _, _, z = bloch(idle.execute_noisy(model), 0, 2)
+    ok &= abs((1 - z) / 2 - math.exp(-50.0 / t1)) < TOLERANCE
+    return ok and all([
+        raises("T2 above 2 T1", ValueError, lambda: quantum_engine.NoiseModel().add_thermal_relaxation(100.0, 250.0)),
+        raises("non-positive T1", ValueError, lambda: quantum_engine.NoiseModel().add_thermal_relaxation(0.0, 0.0)),
+        raises("negative duration", ValueError, lambda: model.set_gate_duration("h", -1.0)),
+    ])
+
+
+def test_kraus_channel():
+    print("\nTesting user Kraus channels against built-in channels and ensembles...")
+    p = 0.35
+    damping = quantum_engine.KrausChannel([
+        [[(1.0, 0.0), (0.0, 0.0)], [(0.0, 0.0), ((1 - p) ** 0.5, 0.0)]],
+        [[(0.0, 0.0), (p ** 0.5, 0.0)], [(0.0, 0.0), (0.0, 0.0)]],
+    ])
+    state = QuantumCircuit.random(2, 5, seed=59).execute()
+    custom = DensityMatrix(2)
+    custom.from_pure_state(state)
+    custom.apply_channel(damping, 1)
+    builtin = DensityMatrix(2)
+    builtin.from_pure_state(state)
+    builtin.apply_amplitude_damping(p, 1)
+    labels = pauli_labels(2)
+    ok = close("amplitude damping as Kraus operators, every Pauli",
+               [custom.expectation(quantum_engine.PauliString(l)) for l in labels],
+               [builtin.expectation(quantum_engine.PauliString(l)) for l in labels])
+
+    # sqrt(0.7) RY(0.4) and sqrt(0.3) Z on qubit 0 give the ensemble of the two rotated states
+    c, s = math.cos(0.2), math.sin(0.2)
+    mixing = quantum_engine.KrausChannel([
+        [[(0.7 ** 0.5 * c, 0.0), (-(0.7 ** 0.5) * s, 0.0)], [(0.7 ** 0.5 * s, 0.0), (0.7 ** 0.5 * c, 0.0)]],
+        [[(0.3 ** 0.5, 0.0), (0.0, 0.0)], [(0.0, 0.0), (-(0.3 ** 0.5), 0.0)]],
+    ])
+    rho = DensityMatrix(2)
+    rho.from_pure_state(state)
+    rho.apply_channel(mixing, 0)
+    rotated = QuantumState.from_amplitudes(state.get_state_vector())
+    rotated.apply_gate("RY", 0, 0.4)
+    flipped = QuantumState.from_amplitudes(state.get_state_vector())
+    flipped.apply_gate("Z", 0, None)
+    ensemble = DensityMatrix.from_ensemble([rotated, flipped], [0.7, 0.3])
+    ok &= close("unitary mixture, every Pauli",
+                [rho.expectation(quantum_engine.PauliString(l)) for l in labels],
+                [ensemble.expectation(quantum_engine.PauliString(l)) for l in labels])
+    ok &= len(mixing) == 2 and abs(mixing.ops()[1][1][1][0] + 0.3 ** 0.5) < TOLERANCE
+
+    lossy = [[[(0.9, 0.0), (0.0, 0.0)], [(0.0, 0.0), (0.9, 0.0)]]]
+    return ok and all([
+        raises("operators that do not sum to the identity", ValueError, lambda: quantum_engine.KrausChannel(lossy)),
+        raises("not 2 x 2", ValueError, lambda: quantum_engine.KrausChannel([[[(1.0, 0.0)]]])),
+        raises("no operators", ValueError, lambda: quantum_engine.KrausChannel([])),
+        raises("qubit out of range", quantum_engine.QubitIndexError, lambda: rho.apply_channel(damping, 2)),
+    ])
+
+
+def test_purification():
+    print("\nTesting purify() and to_pure_state() against the density matrices they came from...")
+    rho = QuantumCircuit.random(3, 4, seed=88).execute_density()
+    rho.apply_depolarizing(0, 0.2)
+    rho.apply_amplitude_damping(0.3, qubit=2)
+    purified = rho.purify()
+    amps = purified.get_state_vector()
+    norm = sum(re * re + im * im for re, im in amps)
+    # Every Pauli on qubits 0..2 of the purification must match Tr(rho P)
+    labels = pauli_labels(3)
+    reduced = [purified.expectation(quantum_engine.PauliString(label + "III")) for label in labels]
+    error = max(abs(a - rho.expectation(quantum_engine.PauliString(label))) for a, label in zip(reduced, labels))
+    print(f"   purification: {len(amps)} amplitudes, norm {norm:.6f}, "
+          f"max Pauli expectation error on the system {error:.2e}")
+    ok = len(amps) == 64 and abs(norm - 1.0) < TOLERANCE and error < TOLERANCE
+    ok &= abs(purified.reduced_density_matrix([0, 1, 2]).trace_distance(rho)) < TOLERANCE
+
+    circuit = QuantumCircuit.random(3, 4, seed=89)
+    pure = circuit.execute_density().to_pure_state()
+    overlap = pure.fidelity(circuit.execute())
+    largest = max(pure.get_state_vector(), key=lambda a: a[0] ** 2 + a[1] ** 2)
+    print(f"   to_pure_state: fidelity {overlap:.6f}, largest amplitude {largest}")
+    ok &= abs(overlap - 1.0) < TOLERANCE and largest[0] > 0 and abs(largest[1]) < TOLERANCE
+    # Weak noise passes with a loose tolerance and fails with the default one
+    weak = circuit.execute_density()
+    weak.apply_depolarizing(1, 0.01)
+    ok &= weak.to_pure_state(tolerance=0.05).fidelity(circuit.execute()) > 0.98
+    return ok and raises("mixed state", ValueError, lambda: rho.to_pure_state())
+
+
+PAULI_MATRICES = [[[1, 0], [0, 1]], [[0, 1], [1, 0]], [[0, -1j], [1j, 0]], [[1, 0], [0, -1]]]
+
+
+def mat2(a, b):
+    return [[sum(a[r][k] * b[k][c] for k in range(2)) for c in range(2)] for r in range(2)]
+
+
+def apply_kraus(ops, m):
+    """sum_k E_k m E_k^dagger for 2 x 2 matrices"""
+    out = [[0j, 0j], [0j, 0j]]
+    for e in ops:
+        image = mat2(mat2(e, m), [[e[c][r].conjugate() for c in range(2)] for r in range(2)])
+        out = [[out[r][c] + image[r][c] for c in range(2)] for r in range(2)]
+    return out
+
+
+def reference_choi(ops, vectorization):
+    """Choi matrix with "column" (sum_ab |a><b| (x) E(|a><b|)) or "row" (E(|a><b|) (x) |a><b|) vectorization"""
+    choi = [[0j] * 4 for _ in range(4)]
+    for a in range(2):
+        for b in range(2):
+            image = apply_kraus(ops, [[float(r == a and c == b) for c in range(2)] for r in range(2)])
+            for i in range(2):
+                for j in range(2):
+                    row, col = (2 * a + i, 2 * b + j) if vectorization == "column" else (2 * i + a, 2 * j + b)
+                    choi[row][col] = image[i][j]
+    return choi
+
+
+def reference_ptm(ops):
+    """R_ij = Tr(P_i E(P_j)) / 2 over (I, X, Y, Z)"""
+    return [[sum(mat2(pi, apply_kraus(ops, pj))[k][k] for k in range(2)).real / 2 for pj in PAULI_MATRICES]
+            for pi in PAULI_MATRICES]
+
+
+def kraus_pairs(ops):
+    return [[[(complex(x).real, complex(x).imag) for x in row] for row in op] for op in ops]
+
+
+def test_superoperator_export():
+    print("\nTesting Choi and Pauli transfer matrix export of Kraus channels and noise models...")
+    gamma, theta = 0.3, 0.5
+    # Amplitude damping followed by a small unitary rotation, so the Choi matrix has complex entries
+    c, s = math.cos(theta / 2), math.sin(theta / 2)
+    ops = [mat2([[c, -1j * s], [-1j * s, c]], e) for e in
+           ([[1, 0], [0, math.sqrt(1 - gamma)]], [[0, math.sqrt(gamma)], [0, 0]])]
+    channel = quantum_engine.KrausChannel(kraus_pairs(ops))
+    p, q = 0.1, 0.2
+    model = quantum_engine.NoiseModel()
+    model.add_gate_error("cnot", "depolarizing", p)
+    model.add_qubit_error(1, "phase_flip", q)
+    try:
+        import numpy
+    except ImportError:
+        print("   numpy not installed; checking the ImportError path only")
+        return all([
+            raises("KrausChannel.choi", ImportError, channel.choi),
+            raises("KrausChannel.ptm", ImportError, channel.ptm),
+            raises("NoiseModel.choi", ImportError, lambda: model.choi("cnot", [0, 1])),
+            raises("NoiseModel.ptm", ImportError, lambda: model.ptm("cnot", [0, 1])),
+        ])
+    ok = True
+    for vectorization in ("column", "row"):
+        choi = channel.choi(vectorization=vectorization)
+        error = float(numpy.abs(choi - numpy.array(reference_choi(ops, vectorization))).max())
+        print(f"   KrausChannel {vectorization} Choi {choi.shape}: max error {error:.2e}")
+        ok &= choi.shape == (4, 4) and error < TOLERANCE
+    ptm = channel.ptm()
+    error = float(numpy.abs(ptm - numpy.array(reference_ptm(ops))).max())
+    print(f"   KrausChannel PTM: max error {error:.2e}")
+    ok &= error < TOLERANCE
+
+    # Per qubit: depolarizing scales X, Y, Z by 1 - 4p/3, the phase flip scales X and Y by 1 - 2q.
+    # Label character k acts on qubits[k], so qubit 0 is the first Kronecker factor
+    depolarized = numpy.diag([1.0] + [1 - 4 * p / 3] * 3)
+    dephased = depolarized @ numpy.diag([1.0, 1 - 2 * q, 1 - 2 * q, 1.0])
+    ptm = model.ptm("cnot", [0, 1])
+    error = float(numpy.abs(ptm - numpy.kron(depolarized, dephased)).max())
+    choi = model.choi("cnot", [0, 1])
+    trace = complex(numpy.trace(choi))
+    print(f"   NoiseModel PTM {ptm.shape}: max error {error:.2e}; Choi {choi.shape}, trace {trace:.4f}")
+    return ok and ptm.shape == (16, 16) and error < TOLERANCE and choi.shape == (16, 16) and abs(trace - 4) < TOLERANCE
+
+
+def test_pair_errors():
+    print("\nTesting two-qubit depolarizing and ZZ crosstalk against closed forms on a Bell pair...")
+    circuit = QuantumCircuit(3)
+    circuit.h(0)
+    circuit.cnot(0, 1)
+    circuit.x(2)
+    labels = ["XXI", "YYI", "ZZI", "IIZ"]
+    ok = True
+    p = 0.12
+    # Each of the 15 Paulis anticommutes with 8 others, so every non-identity expectation scales by
+    # 1 - 16p/15 per application; the channel follows h and cnot and commutes with the Clifford
+    f = (1 - 16 * p / 15) ** 2
+    depolarizing = quantum_engine.NoiseModel()
+    depolarizing.add_pair_error((0, 1), "depolarizing", p)
+    rho = circuit.execute_noisy(depolarizing)
+    ok &= close(f"depolarizing p={p}", [rho.expectation(quantum_engine.PauliString(l)) for l in labels],
+                [f, -f, f, -1.0])
+    # ZZ after h dephases |+0>; the Bell state is invariant under the ZZ after cnot. x(2) is off the pair
+    zz = quantum_engine.NoiseModel()
+    zz.add_pair_error((0, 1), "zz", p)
+    rho = circuit.execute_noisy(zz)
+    expected = [1 - 2 * p, -(1 - 2 * p), 1.0, -1.0]
+    ok &= close(f"ZZ crosstalk p={p}", [rho.expectation(quantum_engine.PauliString(l)) for l in labels], expected)
+    trajectories = circuit.execute_trajectories(zz, 4000, seed=102,
+                                                observables=[quantum_engine.PauliString(l) for l in labels])
+    error = max(abs(a - b) for a, b in zip(trajectories.expectations, expected))
+    print(f"   {trajectories.n_trajectories} trajectories: max expectation error {error:.4f}")
+    return ok and error < 0.05 and all([
+        raises("pair on one qubit", ValueError, lambda: zz.add_pair_error((1, 1), "zz", 0.1)),
+        raises("unknown pair channel", ValueError, lambda: zz.add_pair_error((0, 1), "amplitude_damping", 0.1)),
+    ])
+
+
+def test_twirling():
+    print("\nTesting Pauli-twirled instances and execute_twirled against the twirled amplitude damping channel...")
+    circuit = QuantumCircuit(2)
+    circuit.h(0)
+    circuit.cnot(0, 1)
+    circuit.swap(1, 0)
+    ok = True
+    instances = circuit.twirl(8, seed=103)
+    for instance in instances:
+        ops = instance.count_ops()
+        # Only Paulis are added around the two-qubit gates, and each copy keeps the unitary
+        extra = {name: n for name, n in ops.items() if name not in circuit.count_ops()}
+        ok &= (all(ops[name] == n for name, n in circuit.count_ops().items())
+               and set(extra) <= {"x", "y", "z"} and instance.equivalent(circuit))
+    print(f"   {len(instances)} instances: sizes {[len(i) for i in instances]}, all equivalent: {ok}")
+    labels = ["ZI", "IZ", "ZZ"]
+    pure = circuit.execute_twirled(quantum_engine.NoiseModel(), num_instances=4, seed=103)
+    state = circuit.execute()
+    ok &= close("noiseless", [pure.expectation(quantum_engine.PauliString(l)) for l in ["XX", "ZZ", "YY"]],
+                [state.expectation(quantum_engine.PauliString(l)) for l in ["XX", "ZZ", "YY"]])
+    # x(0), cnot leaves |11>; damping gamma after cnot gives <Z> = 2 gamma - 1 per qubit. Twirling drops the
+    # decay towards |0>, leaving the Pauli channel with <Z> = -(1 - gamma), so the average over instances
+    # lands there to within the sampling of the frames
+    gamma = 0.3
+    flip = QuantumCircuit(2)
+    flip.x(0)
+    flip.cnot(0, 1)
+    damping = quantum_engine.NoiseModel()
+    damping.add_gate_error("cnot", "amplitude_damping", gamma)
+    rho = flip.execute_noisy(damping)
+    ok &= close("untwirled", [rho.expectation(quantum_engine.PauliString(l)) for l in labels],
+                [2 * gamma - 1, 2 * gamma - 1, (2 * gamma - 1) ** 2])
+    twirled = flip.execute_twirled(damping, num_instances=400, seed=103)
+    values = [twirled.expectation(quantum_engine.PauliString(l)) for l in labels]
+    expected = [gamma - 1, gamma - 1, (1 - gamma) ** 2]
+    error = max(abs(a - b) for a, b in zip(values, expected))
+    print(f"   400 twirled instances: {[round(v, 4) for v in values]} against {[round(e, 4) for e in expected]} (max error {error:.4f})")
+    again = flip.execute_twirled(damping, num_instances=400, seed=103)
+    same = all(abs(again.expectation(quantum_engine.PauliString(l)) - v) < TOLERANCE for l, v in zip(labels, values))
+    print(f"   same seed repeats the average: {same}")
+    return ok and error < 0.05 and same
+
+
+def test_zne():
+    print("\nTesting zero-noise extrapolation of folded circuits against closed forms and both fits...")
+    p = 0.05
+    circuit = QuantumCircuit(1)
+    for _ in range(4):
+        circuit.x(0)
+    model = quantum_engine.NoiseModel()
+    model.add_gate_error("x", "depolarizing", p)
+    z = quantum_engine.PauliString("Z")
+    # Each x (or its inverse) is followed by depolarizing noise, which scales <Z> by 1 - 4p/3; the folded
+    # circuit at scale s holds 4s of them and is still the identity
+    shrink = 1 - 4 * p / 3
+    scales = [1.0, 1.5, 2.0, 3.0]
+    ok = True
+    for method in ("richardson", "linear"):
+        result = quantum_engine.zne(circuit, z, model, scales, method=method)
+        expected = [shrink ** (4 * s) for s in scales]
+        ok &= close(f"{method} values", result.values, expected)
+        n = len(scales)
+        if method == "richardson":
+            fit = sum(expected[i] * math.prod(scales[j] / (scales[j] - scales[i]) for j in range(n) if j != i)
+                      for i in range(n))
+        else:
+            mx, my = sum(scales) / n, sum(expected) / n
+            slope = (sum((x - mx) * (y - my) for x, y in zip(scales, expected))
+                     / sum((x - mx) ** 2 for x in scales))
+            fit = my - slope * mx
+        ok &= close(f"{method} estimate", [result.estimate], [fit])
+        ok &= result.scale_factors == scales and result.method == method
+        ok &= abs(result.estimate - 1.0) < abs(result.values[0] - 1.0)
+    # A single gate only folds to odd scales, so 2 is rounded to one already in use
+    single = QuantumCircuit(1)
+    single.x(0)
+    return ok and all([
+        raises("scale below 1", ValueError, lambda: quantum_engine.zne(circuit, z, model, [0.5, 1.0])),
+        raises("scales folding alike", ValueError, lambda: quantum_engine.zne(single, z, model, [1.0, 2.0, 3.0])),
+        raises("unknown method", ValueError, lambda: quantum_engine.zne(circuit, z, model, method="cubic")),
+    ])
+
+
+def test_open_system():
+    print("\nTesting environment qubits traced out by execute_open against Kraus damping and dephasing...")
+    theta = 0.5
+    gamma = math.sin(theta) ** 2
+    # Exchange rotates |10> into |01> by theta, so the environment carries off sin^2(theta) of the
+    # excitation: amplitude damping with gamma = sin^2(theta). Qubit 2 lands on reduced qubit 1
+    circuit = QuantumCircuit(3)
+    circuit.ry(0, 1.1)
+    circuit.x(2)
+    circuit.couple(0, 1, theta, interaction="xy")
+    reduced = circuit.execute_open([1])
+    reference = QuantumCircuit(2)
+    reference.ry(0, 1.1)
+    reference.x(1)
+    kraus = reference.execute_density()
+    kraus.apply_amplitude_damping(gamma, qubit=0)
+    distance = reduced.trace_distance(kraus)
+    print(f"   exchange coupling, gamma = {gamma:.4f}: trace distance to Kraus damping {distance:.2e}")
+    ok = distance < TOLERANCE
+    # ZZ against an environment in |+> leaves the branches of |+> with overlap cos(theta)
+    dephasing = QuantumCircuit(2)
+    dephasing.h(0)
+    dephasing.h(1)
+    dephasing.couple(0, 1, theta, interaction="zz")
+    ok &= close("ZZ coupling", bloch(dephasing.execute_open([1])), [math.cos(theta), 0.0, 0.0])
+    return ok and all([
+        raises("environment of every qubit", ValueError, lambda: circuit.execute_open([0, 1, 2])),
+        raises("duplicate environment qubit", ValueError, lambda: circuit.execute_open([1, 1])),
+        raises("environment out of range", quantum_engine.QubitIndexError, lambda: circuit.execute_open([4])),
+        raises("unknown interaction", quantum_engine.InvalidGateError,
+               lambda: circuit.couple(0, 1, theta, interaction="ab")),
+    ])
+
+
+def test_lindblad():
+    print("\nTesting LindbladSolver against the closed-form decay and dephasing of a precessing qubit...")
+    omega, gamma, kappa = 1.0, 0.4, 0.2
+    hamiltonian = quantum_engine.Hamiltonian([(omega / 2, "Z")])
+    lowering = quantum_engine.Hamiltonian([(0.5, "X"), (0.5j, "Y")])
+    solver = quantum_engine.LindbladSolver(hamiltonian, [lowering, quantum_engine.PauliString("Z")], [gamma, kappa])
+    plus = QuantumCircuit(1)
+    plus.h(0)
+    rho = plus.execute_density()
+    times = [0.0, 0.5, 1.0, 2.0]
+    # From |+>: the population relaxes to |0> at gamma, and the coherence precesses at omega while
+    # decaying at gamma / 2 + 2 kappa
+    decay = gamma / 2 + 2 * kappa
+    expected = [[math.exp(-decay * t) * math.cos(omega * t), math.exp(-decay * t) * math.sin(omega * t),
+                 1 - math.exp(-gamma * t)] for t in times]
+    ok = True
+    for method in ("dopri5", "rk4"):
+        result = solver.evolve(rho, times, [quantum_engine.PauliString(p) for p in "XYZ"], method=method)
+        ok &= close(f"{method} at t = {times[-1]} ({result.steps} steps)", result.expectations[-1], expected[-1])
+        ok &= all(close(f"   t = {t}", row, e) for t, row, e in zip(times[:-1], result.expectations, expected))
+        ok &= result.times == times and close("   final state", bloch(result.final_state), expected[-1])
+    # rho itself is left at |+>, where <X> falls at the coherence decay rate
+    derivative = solver.derivative(rho)
+    ok &= close("derivative at |+>", bloch(derivative), [-decay, omega, gamma]) and close("rho", bloch(rho), [1.0, 0.0, 0.0])
+    return ok and all([
+        raises("non-Hermitian Hamiltonian", ValueError,
+               lambda: quantum_engine.LindbladSolver(quantum_engine.Hamiltonian([(0.5j, "Z")]))),
+        raises("rate count", ValueError, lambda: quantum_engine.LindbladSolver(hamiltonian, [lowering], [1.0, 2.0])),
+        raises("negative rate", ValueError, lambda: quantum_engine.LindbladSolver(hamiltonian, [lowering], [-1.0])),
+        raises("decreasing times", ValueError, lambda: solver.evolve(rho, [1.0, 0.5])),
+        raises("unknown method", ValueError, lambda: solver.evolve(rho, [1.0], method="euler")),
+    ])
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Noise Channels and Mixed-State Aggregation")
+    print("============================================================")
+
+    results = [
+        test_ensembles(),
+        test_density_matrix_gates(),
+        test_amplitude_damping(),
+        test_pauli_channels(),
+        test_noise_model(),
+        test_trajectories(),
+        test_readout_errors(),
+        test_thermal_relaxation(),
+        test_kraus_channel(),
+        test_purification(),
+        test_superoperator_export(),
+        test_pair_errors(),
+        test_twirling(),
+        test_zne(),
+        test_open_system(),
+        test_lindblad(),
+    ]
+
+    if all(results):
+        print("\nNOISE VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
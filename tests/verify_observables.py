@@ -0,0 +1,265 @@
+import math
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+PauliString = quantum_engine.PauliString
+TOLERANCE = 1e-4
+
+PAULI = {
+    "I": [[1, 0], [0, 1]],
+    "X": [[0, 1], [1, 0]],
+    "Y": [[0, -1j], [1j, 0]],
+    "Z": [[1, 0], [0, -1]],
+}
+
+
+def amplitudes(state):
+    return [complex(*a) for a in state.get_state_vector()]
+
+
+def apply_pauli(amps, label):
+    """P|psi> for a Pauli label whose character q acts on qubit q"""
+    out = [0j] * len(amps)
+    for index, a in enumerate(amps):
+        target, phase = index, 1
+        for q, p in enumerate(label):
+            bit = index >> q & 1
+            column = PAULI[p]
+            row = next(r for r in range(2) if column[r][bit] != 0)
+            phase *= column[row][bit]
+            target = target & ~(1 << q) | row << q
+        out[target] += phase * a
+    return out
+
+
+def reference_expectation(amps, terms):
+    """<psi| sum_k c_k P_k |psi> for (coefficient, label) terms"""
+    return sum(c * sum(a.conjugate() * b for a, b in zip(amps, apply_pauli(amps, label))) for c, label in terms).real
+
+
+def check(label, value, expected, tolerance=TOLERANCE):
+    print(f"   {label}: {value:.4f} (expected {expected:.4f})")
+    return abs(value - expected) < tolerance
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_pauli_expectations():
+    print("\nTesting Pauli-string expectation values...")
+    bell = QuantumCircuit.bell().execute()
+    ok = all([
+        check("Bell <ZZ>", bell.expectation(PauliString("ZZ")), 1.0),
+        check("Bell <XX>", bell.expectation(PauliString("XX")), 1.0),
+        check("Bell <YY>", bell.expectation(PauliString("YY")), -1.0),
+        check("Bell <ZI>", bell.expectation(PauliString("ZI")), 0.0),
+        check("Bell 0.5 <XX>", bell.expectation(PauliString("XX", 0.5)), 0.5),
+    ])
+    state = QuantumCircuit.random(4, 6, seed=21).execute()
+    amps = amplitudes(state)
+    for label in ("XYZI", "ZZZZ", "IYXY", "XIIX"):
+        ok &= check(f"random state <{label}>", state.expectation(PauliString(label, -1.5)),
+                    reference_expectation(amps, [(-1.5, label)]))
+    return ok and all([
+        raises("unknown Pauli letter", ValueError, lambda: PauliString("XQ")),
+        raises("string longer than the state", quantum_engine.DimensionMismatchError,
+               lambda: bell.expectation(PauliString("ZZZ"))),
+    ])
+
+
+def dense(hamiltonian, qubits):
+    """Matrix of a Hamiltonian from its terms, built column by column with apply_pauli"""
+    dim = 1 << qubits
+    matrix = [[0j] * dim for _ in range(dim)]
+    for coeff, label in hamiltonian.terms():
+        for col in range(dim):
+            for row, a in enumerate(apply_pauli([1.0 if i == col else 0.0 for i in range(dim)], label)):
+                matrix[row][col] += coeff * a
+    return matrix
+
+
+def matmul(a, b):
+    return [[sum(a[r][k] * b[k][c] for k in range(len(b))) for c in range(len(b[0]))] for r in range(len(a))]
+
+
+def distance(a, b):
+    return max(abs(a[r][c] - b[r][c]) for r in range(len(a)) for c in range(len(a)))
+
+
+def test_hamiltonian_algebra():
+    print("\nTesting Hamiltonian sums, scaling, simplification and commutators against dense matrices...")
+    a = quantum_engine.Hamiltonian([(0.5, "ZZI"), (1.2, "XIY"), (-0.4, "IYZ")])
+    b = quantum_engine.Hamiltonian([(0.3, "YII"), (-0.7, "ZXI"), (0.2, "ZZI")])
+    da, db = dense(a, 3), dense(b, 3)
+    combined = [[x + y for x, y in zip(r, s)] for r, s in zip(da, db)]
+    cases = [
+        ("a + b", a + b, combined),
+        ("a - b", a - b, [[x - y for x, y in zip(r, s)] for r, s in zip(da, db)]),
+        ("2.5 * a", 2.5 * a, [[2.5 * x for x in r] for r in da]),
+        ("a * -1", a * -1, [[-x for x in r] for r in da]),
+        ("(a + b).simplify()", (a + b).simplify(), combined),
+        ("[a, b]", a.commutator(b), [[x - y for x, y in zip(r, s)] for r, s in zip(matmul(da, db), matmul(db, da))]),
+    ]
+    ok = True
+    for label, value, expected in cases:
+        error = distance(dense(value, 3), expected)
+        print(f"   {label}: {len(value)} terms, max matrix error {error:.2e}")
+        ok &= error < TOLERANCE
+    merged = dict((label, coeff) for coeff, label in (a + b).simplify().terms())
+    print(f"   simplified terms {merged}")
+    ok &= len(a + b) == 6 and len(merged) == 5 and abs(merged["ZZI"] - 0.7) < TOLERANCE
+    ok &= len((a - a).simplify()) == 0 and a.num_qubits == 3
+    # [a, b] of Hermitian operators is anti-Hermitian: i[a, b] is Hermitian
+    ok &= a.is_hermitian() and not a.commutator(b).is_hermitian() and (a.commutator(b) * 1j).is_hermitian()
+    # Pauli strings commute when they anticommute on an even number of qubits
+    x_chain = quantum_engine.Hamiltonian([(1.0, "XXI"), (0.5, "IXX")])
+    ok &= x_chain.commutes_with(quantum_engine.Hamiltonian([(2.0, "ZZZ")]))
+    ok &= not x_chain.commutes_with(quantum_engine.Hamiltonian([(0.3, "ZZI"), (0.1, "XII")]))
+    return ok and raises("unknown Pauli letter", ValueError, lambda: quantum_engine.Hamiltonian([(1.0, "XQ")]))
+
+
+ESTIMATE_SHOTS = 4000
+
+
+def test_variance_and_estimates():
+    print("\nTesting exact variances and shot-based (mean, standard error) estimates...")
+    circuit = QuantumCircuit.random(3, 5, seed=85)
+    state = circuit.execute()
+    amps = amplitudes(state)
+    terms = [(0.7, "ZXI"), (-1.1, "IYY"), (0.4, "XIZ")]
+    observable = quantum_engine.Hamiltonian(terms)
+    mean = reference_expectation(amps, terms)
+    # <O^2> = |O psi|^2
+    o_psi = [sum(c * x for c, x in zip([t[0] for t in terms], column))
+             for column in zip(*[apply_pauli(amps, label) for _, label in terms])]
+    variance = sum(abs(x) ** 2 for x in o_psi) - mean ** 2
+    ok = check("variance", state.variance(observable), variance)
+    ok &= check("density matrix variance", circuit.execute_density().variance(observable), variance)
+    ok &= check("variance of a single Pauli", state.variance(PauliString("ZXI", 2.0)),
+                4.0 * (1.0 - reference_expectation(amps, [(1.0, "ZXI")]) ** 2))
+
+    # Each term is measured ESTIMATE_SHOTS times on its own, so the errors add in quadrature
+    estimate, stderr = state.estimate(observable, ESTIMATE_SHOTS, seed=1)
+    expected_stderr = math.sqrt(sum(c * c * (1.0 - reference_expectation(amps, [(1.0, label)]) ** 2)
+                                    for c, label in terms) / ESTIMATE_SHOTS)
+    print(f"   estimate {estimate:.4f} +- {stderr:.4f} for <O> = {mean:.4f} (expected error {expected_stderr:.4f})")
+    ok &= abs(estimate - mean) < 4 * expected_stderr and abs(stderr - expected_stderr) < 0.1 * expected_stderr
+
+    # Diagonal terms read from one set of counts: the error is sqrt(Var(O) / shots)
+    diagonal = [(0.5, "ZZI"), (-0.8, "IZZ"), (0.3, "ZIZ")]
+    counts = circuit.run(ESTIMATE_SHOTS, seed=2)
+    from_counts, counts_stderr = quantum_engine.expectation_from_counts(counts, quantum_engine.Hamiltonian(diagonal))
+    diagonal_mean = reference_expectation(amps, diagonal)
+    diagonal_stderr = math.sqrt(state.variance(quantum_engine.Hamiltonian(diagonal)) / ESTIMATE_SHOTS)
+    print(f"   from counts {from_counts:.4f} +- {counts_stderr:.4f} for {diagonal_mean:.4f} "
+          f"(expected error {diagonal_stderr:.4f})")
+    ok &= abs(from_counts - diagonal_mean) < 4 * diagonal_stderr
+    ok &= abs(counts_stderr - diagonal_stderr) < 0.1 * diagonal_stderr
+    return ok and raises("off-diagonal observable from counts", ValueError,
+                         lambda: quantum_engine.expectation_from_counts(counts, PauliString("XII")))
+
+
+def test_density_matrix_observables():
+    print("\nTesting DensityMatrix expectation values and populations on a mixed ensemble...")
+    states = [QuantumCircuit.random(3, 4, seed=seed).execute() for seed in (86, 87, 88)]
+    weights = [0.5, 0.3, 0.2]
+    rho = quantum_engine.DensityMatrix.from_ensemble(states, weights)
+    ensemble = [amplitudes(s) for s in states]
+    terms = [(0.6, "XYZ"), (-0.9, "ZIZ"), (0.25, "IXI")]
+    ok = check("Tr(rho O) of a Hamiltonian", rho.expectation(quantum_engine.Hamiltonian(terms)),
+               sum(w * reference_expectation(a, terms) for w, a in zip(weights, ensemble)))
+    ok &= check("Tr(rho O) of an Observable", rho.expectation(quantum_engine.Observable([PauliString(label, c) for c, label in terms])),
+                sum(w * reference_expectation(a, terms) for w, a in zip(weights, ensemble)))
+    ok &= check("Tr(rho P) of a PauliString", rho.expectation(PauliString("YYI", -0.5)),
+                sum(w * reference_expectation(a, [(-0.5, "YYI")]) for w, a in zip(weights, ensemble)))
+    populations = [sum(w * abs(a[i]) ** 2 for w, a in zip(weights, ensemble)) for i in range(8)]
+    error = max(abs(p - q) for p, q in zip(rho.diagonal(), populations))
+    print(f"   diagonal: max population error {error:.2e}")
+    ok &= error < TOLERANCE and check("expectation_value = 1 - rho_00", rho.expectation_value(), 1.0 - populations[0])
+    return ok and raises("observable wider than the matrix", quantum_engine.DimensionMismatchError,
+                         lambda: rho.expectation(PauliString("ZZZZ")))
+
+
+def test_z_correlations():
+    print("\nTesting per-qubit <Z> and the <Z_a Z_b> matrix against Pauli expectations...")
+    qubits = 3
+    circuit = QuantumCircuit(qubits)
+    for q, angle in enumerate([0.4, 1.3, 2.2]):
+        circuit.ry(q, angle)
+    circuit.cnot(0, 1)
+    circuit.cnot(1, 2)
+    circuit.rx(2, 0.7)
+    state = circuit.execute()
+    other = QuantumCircuit.random(qubits, 4, seed=113).execute()
+    w = 0.25
+    rho = quantum_engine.DensityMatrix.from_ensemble([state, other], [w, 1 - w])
+
+    def label(*positions):
+        return "".join("Z" if q in positions else "I" for q in range(qubits))
+
+    def reference(*positions):
+        terms = [(1.0, label(*positions))]
+        return (w * reference_expectation(amplitudes(state), terms)
+                + (1 - w) * reference_expectation(amplitudes(other), terms))
+
+    z = [reference_expectation(amplitudes(state), [(1.0, label(q))]) for q in range(qubits)]
+    ok = all(check(f"state <Z{q}>", v, e) for q, (v, e) in enumerate(zip(state.z_expectations(), z)))
+    ok &= all(check(f"mixture <Z{q}>", v, reference(q)) for q, v in enumerate(rho.z_expectations()))
+    try:
+        import numpy
+    except ImportError:
+        print("   numpy not installed; checking the ImportError path only")
+        return ok and all([
+            raises("QuantumState.zz_correlations", ImportError, state.zz_correlations),
+            raises("DensityMatrix.zz_correlations", ImportError, rho.zz_correlations),
+        ])
+    zz = numpy.array([[reference_expectation(amplitudes(state), [(1.0, label(a, b))]) if a != b else 1.0
+                       for b in range(qubits)] for a in range(qubits)])
+    mixed = numpy.array([[reference(a, b) if a != b else 1.0 for b in range(qubits)] for a in range(qubits)])
+    mixed_z = numpy.array([reference(q) for q in range(qubits)])
+    errors = [
+        numpy.max(numpy.abs(state.zz_correlations() - zz)),
+        numpy.max(numpy.abs(state.zz_correlations(connected=True) - (zz - numpy.outer(z, z)))),
+        numpy.max(numpy.abs(rho.zz_correlations() - mixed)),
+        numpy.max(numpy.abs(rho.zz_correlations(connected=True) - (mixed - numpy.outer(mixed_z, mixed_z)))),
+    ]
+    print(f"   state, connected state, mixture, connected mixture: max errors {[f'{e:.2e}' for e in errors]}")
+    return ok and state.zz_correlations().dtype == numpy.float32 and max(errors) < TOLERANCE
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Observables, Hamiltonians and Estimators")
+    print("============================================================")
+
+    results = [
+        test_pauli_expectations(),
+        test_hamiltonian_algebra(),
+        test_variance_and_estimates(),
+        test_density_matrix_observables(),
+        test_z_correlations(),
+    ]
+
+    if all(results):
+        print("\nOBSERVABLES VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
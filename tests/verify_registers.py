@@ -0,0 +1,109 @@
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+QuantumState = quantum_engine.QuantumState
+TOLERANCE = 1e-4
+
+
+def amplitudes(state):
+    return [complex(*a) for a in state.get_state_vector()]
+
+
+def max_error(a, b):
+    return max(abs(x - y) for x, y in zip(a, b))
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def relabelled(amps, qubits, image):
+    """Amplitudes with qubit q moved to image[q] in a register of `qubits` qubits"""
+    out = [0j] * (1 << qubits)
+    for i, a in enumerate(amps):
+        out[sum((i >> q & 1) << image[q] for q in range(len(image)))] = a
+    return out
+
+
+def test_remap_and_permute():
+    print("\nTesting QuantumCircuit.remap and QuantumState.permute_qubits against relabelled amplitudes...")
+    circuit = QuantumCircuit.random(4, 5, seed=94)
+    circuit.ccx(0, 1, 3)
+    amps = amplitudes(circuit.execute())
+    ok = True
+    for mapping in ({0: 2, 2: 0}, {0: 1, 1: 2, 2: 3, 3: 0}, {1: 3, 3: 1, 0: 0}):
+        image = [mapping.get(q, q) for q in range(4)]
+        expected = relabelled(amps, 4, image)
+        remapped = max_error(amplitudes(circuit.remap(mapping).execute()), expected)
+        # permute_qubits puts old qubit order[k] at k, so order is the inverse of the image
+        order = [image.index(k) for k in range(4)]
+        permuted = max_error(amplitudes(circuit.execute().permute_qubits(order)), expected)
+        print(f"   mapping {mapping}: remap error {remapped:.2e}, permute_qubits({order}) error {permuted:.2e}")
+        ok &= remapped < TOLERANCE and permuted < TOLERANCE
+
+    # Mapping past the last qubit widens the register, the new qubits starting in |0>
+    wide = circuit.remap({0: 5})
+    error = max_error(amplitudes(wide.execute()), relabelled(amps, 6, [5, 1, 2, 3]))
+    print(f"   widened to {len(wide.execute().get_state_vector()).bit_length() - 1} qubits: error {error:.2e}")
+    return ok and error < TOLERANCE and all([
+        raises("two qubits onto one", quantum_engine.InvalidGateError, lambda: circuit.remap({0: 1})),
+        raises("order missing a qubit", ValueError, lambda: circuit.execute().permute_qubits([0, 1, 1, 3])),
+    ])
+
+
+def test_add_and_discard_qubits():
+    print("\nTesting add_qubits, kron_with and discard_qubits...")
+    low = QuantumCircuit.random(2, 3, seed=1).execute()
+    high = QuantumCircuit.random(2, 3, seed=2).execute()
+    joined = low.kron_with(high)
+    # Qubits 0-1 hold `low`, qubits 2-3 hold `high`
+    expected = [l * h for h in amplitudes(high) for l in amplitudes(low)]
+    kron_error = max_error(amplitudes(joined), expected)
+
+    widened = low.add_qubits(3)
+    pad_error = max_error(amplitudes(widened), amplitudes(low) + [0j] * 28)
+    back = widened.discard_qubits([2, 3, 4])
+    discard_error = 1.0 - back.fidelity(low)
+    recovered = 1.0 - joined.discard_qubits([0, 1]).fidelity(high)
+    print(f"   kron {kron_error:.2e}, padding {pad_error:.2e}, discard {discard_error:.2e}, {recovered:.2e}")
+
+    bell = QuantumCircuit.bell().execute()
+    return (kron_error < TOLERANCE and pad_error < TOLERANCE and len(widened.get_state_vector()) == 32
+            and discard_error < TOLERANCE and recovered < TOLERANCE
+            and raises("discarding half of a Bell pair", ValueError, lambda: bell.discard_qubits([1]))
+            and raises("add_qubits(2**64)", ValueError, lambda: QuantumState(2).add_qubits(2 ** 64 - 1))
+            and raises("add_qubits(64)", ValueError, lambda: QuantumState(2).add_qubits(64)))
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Register Resizing, Permutation and Relabelling")
+    print("============================================================")
+
+    results = [
+        test_remap_and_permute(),
+        test_add_and_discard_qubits(),
+    ]
+
+    if all(results):
+        print("\nREGISTERS VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
@@ -0,0 +1,425 @@
+import cmath
+import json
+import math
+import os
+import pickle
+import sys
+import tempfile
+from types import SimpleNamespace
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+QuantumEngineError = quantum_engine.QuantumEngineError
+TOLERANCE = 1e-4
+
+
+def max_error(a, b):
+    return max(abs(complex(*x) - complex(*y)) for x, y in zip(a.get_state_vector(), b.get_state_vector()))
+
+
+def rejects(label, parse, source):
+    """True when `parse(source)` raises an engine error instead of returning"""
+    try:
+        parse(source)
+    except (QuantumEngineError, ValueError) as error:
+        print(f"   {label}: {type(error).__name__}")
+        return True
+    print(f"   {label}: accepted")
+    return False
+
+
+HEADER = 'OPENQASM 2.0;\ninclude "qelib1.inc";\n'
+
+# (label, program) pairs the importer must reject with an error, never a
+# crash or a silently different circuit
+MALFORMED_QASM = [
+    ("missing header", "qreg q[2];\nh q[0];"),
+    ("unknown gate", HEADER + "qreg q[2];\nfoo q[0];"),
+    ("qubit out of range", HEADER + "qreg q[2];\nh q[5];"),
+    ("undeclared register", HEADER + "qreg q[2];\nh r[0];"),
+    ("duplicate register", HEADER + "qreg q[2];\nqreg q[3];"),
+    ("absurd register", HEADER + "qreg q[99999999999];"),
+    ("repeated operand", HEADER + "qreg q[2];\ncx q[0],q[0];"),
+]
+
+
+def test_qasm():
+    print("\nTesting the OpenQASM 2.0 importer and exporter...")
+    ok = all([rejects(label, QuantumCircuit.from_qasm, src) for label, src in MALFORMED_QASM])
+    circuit = QuantumCircuit.random(5, 8, seed=11)
+    circuit.ccx(0, 1, 2)
+    circuit.u(3, 0.1, 0.2, 0.3)
+    error = max_error(QuantumCircuit.from_qasm(circuit.to_qasm()).execute(), circuit.execute())
+    print(f"   round trip: max amplitude error {error:.2e}")
+    return ok and error < TOLERANCE
+
+
+def test_qasm_measurement():
+    print("\nTesting that QASM measure and reset survive import...")
+    bad = [
+        ("clbit out of range", HEADER + "qreg q[2];\ncreg c[1];\nmeasure q[1] -> c[4];"),
+        ("measure without creg", HEADER + "qreg q[2];\nmeasure q[0] -> c[0];"),
+    ]
+    ok = all([rejects(label, QuantumCircuit.from_qasm, src) for label, src in bad])
+    program = HEADER + "qreg q[2];\ncreg c[2];\nh q[0];\nmeasure q[0] -> c[1];\nh q[0];\nreset q[1];\n"
+    circuit = QuantumCircuit.from_qasm(program)
+    names = [name for name, _, _ in circuit.gates()]
+    print(f"   imported gates: {names}, clbits={circuit.clbits}")
+    again = QuantumCircuit.from_qasm(circuit.to_qasm())
+    return ok and names == ["h", "measure", "h", "reset"] and circuit.clbits == 2 and again.gates() == circuit.gates()
+
+
+MALFORMED_JSON = [
+    ("truncated JSON", "{"),
+    ("wrong version", '{"version":99,"circuit":{"qubits":1,"clbits":0,"gates":[],"params":[],"initial":null}}'),
+    ("qubit out of range", '{"version":1,"circuit":{"qubits":1,"clbits":0,'
+                           '"gates":[{"gate_type":"h","target":3,"control":null}],"params":[],"initial":null}}'),
+    ("clbit out of range", '{"version":1,"circuit":{"qubits":1,"clbits":0,'
+                           '"gates":[{"gate_type":{"measure":0},"target":0,"control":null}],"params":[],"initial":null}}'),
+]
+
+
+def test_json_and_bytes():
+    print("\nTesting JSON and binary circuit serialization...")
+    ok = all([rejects(label, QuantumCircuit.from_json, src) for label, src in MALFORMED_JSON])
+    data = QuantumCircuit.ghz(3).to_bytes()
+    ok &= rejects("bad bytes header", QuantumCircuit.from_bytes, b"\x00\x01garbage")
+    ok &= rejects("truncated bytes", QuantumCircuit.from_bytes, data[:-3])
+
+    circuit = QuantumCircuit.random(5, 8, seed=11)
+    circuit.ry_param(1, "theta")
+    bound = {"theta": 0.4}
+    expected = circuit.bind(bound).execute()
+    for label, copy in (("json", QuantumCircuit.from_json(circuit.to_json())),
+                        ("bytes", QuantumCircuit.from_bytes(circuit.to_bytes()))):
+        error = max_error(copy.bind(bound).execute(), expected)
+        print(f"   {label} round trip: max amplitude error {error:.2e}, parameters {copy.parameters()}")
+        ok &= error < TOLERANCE and copy.parameters() == ["theta"]
+    return ok
+
+
+def test_pickle():
+    print("\nTesting pickle round trips of circuits, states and density matrices...")
+    circuit = QuantumCircuit.random(4, 6, seed=2)
+    state = circuit.execute()
+    density = circuit.execute_noisy((0.1, 0.1))
+    copy = pickle.loads(pickle.dumps(circuit))
+    state_copy = pickle.loads(pickle.dumps(state))
+    density_copy = pickle.loads(pickle.dumps(density))
+    circuit_error = max_error(copy.execute(), state)
+    state_error = max_error(state_copy, state)
+    density_error = max(abs(a - b) for a, b in zip(density_copy.probabilities(), density.probabilities()))
+    print(f"   circuit {circuit_error:.2e}, state {state_error:.2e}, density matrix {density_error:.2e}")
+    return (circuit_error < TOLERANCE and state_error < TOLERANCE and density_error < TOLERANCE
+            and abs(density_copy.purity() - density.purity()) < TOLERANCE)
+
+
+def test_checkpoints():
+    print("\nTesting checkpoint files...")
+    state = QuantumCircuit.random(4, 4, seed=3).execute()
+    density = QuantumCircuit.ghz(3).execute_noisy((0.2, 0.0))
+    with tempfile.TemporaryDirectory() as directory:
+        path = os.path.join(directory, "state.qeck")
+        state.save(path)
+        error = max_error(quantum_engine.QuantumState.load(path), state)
+        wide = quantum_engine.QuantumStateF64.load(path).to_f32()
+        error = max(error, max_error(wide, state))
+        dm_path = os.path.join(directory, "density.qeck")
+        density.save(dm_path)
+        purity_error = abs(quantum_engine.DensityMatrix.load(dm_path).purity() - density.purity())
+        print(f"   reloaded: max amplitude error {error:.2e}, purity error {purity_error:.2e}")
+
+        with open(path, "rb") as f:
+            data = f.read()
+        truncated = os.path.join(directory, "truncated.qeck")
+        with open(truncated, "wb") as f:
+            f.write(data[:-5])
+        garbage = os.path.join(directory, "garbage.qeck")
+        with open(garbage, "wb") as f:
+            f.write(b"not a checkpoint at all")
+        load = quantum_engine.QuantumState.load
+        return (error < TOLERANCE and purity_error < TOLERANCE
+                and rejects("truncated checkpoint", load, truncated)
+                and rejects("garbage checkpoint", load, garbage)
+                and rejects("density matrix as a state", load, dm_path))
+
+
+MALFORMED_STIM = [
+    ("unknown instruction", "FOO 0"),
+    ("unclosed repeat", "REPEAT 2 {\nH 0"),
+    ("stray brace", "H 0\n}"),
+    ("unbounded repeat", "REPEAT 1000000000 {\nH 0\n}"),
+    ("nested unbounded repeat", "REPEAT 100000 {\nREPEAT 100000 {\nH 0\n}\n}"),
+]
+
+
+def test_stim():
+    print("\nTesting the Stim importer and exporter on Clifford circuits...")
+    ok = all([rejects(label, QuantumCircuit.from_stim, src) for label, src in MALFORMED_STIM])
+    circuit = QuantumCircuit.random(4, 6, seed=5, gate_set=["h", "s", "x", "cnot", "swap"])
+    error = max_error(QuantumCircuit.from_stim(circuit.to_stim()).execute(), circuit.execute())
+    print(f"   round trip: max amplitude error {error:.2e}")
+    repeated = QuantumCircuit.from_stim("REPEAT 3 {\nH 0\nCX 0 1\n}\nM 0 1")
+    print(f"   REPEAT 3 unrolls to {len(repeated.gates())} instructions, clbits={repeated.clbits}")
+    noncliff = QuantumCircuit(1)
+    noncliff.t(0)
+    return (ok and error < TOLERANCE and len(repeated.gates()) == 8 and repeated.clbits == 2
+            and rejects("non-Clifford export", lambda c: c.to_stim(), noncliff))
+
+
+class StandInParameter:
+    """The parts of qiskit.circuit.Parameter (and k * Parameter) that from_qiskit reads"""
+
+    def __init__(self, name, scale=1.0, offset=0.0):
+        self.name, self.scale, self.offset = name, scale, offset
+        self.parameters = [self] if scale == 1.0 and offset == 0.0 else [StandInParameter(name)]
+
+    def gradient(self, parameter):
+        return self.scale
+
+    def bind(self, values):
+        return self.offset
+
+
+class StandInMatrix(list):
+    def tolist(self):
+        return list(self)
+
+
+class StandInQiskitCircuit:
+    """Duck-typed qiskit.QuantumCircuit: num_qubits, num_clbits, data and find_bit"""
+
+    def __init__(self, qubits, clbits, instructions):
+        self.num_qubits, self.num_clbits = qubits, clbits
+        self.data = [SimpleNamespace(operation=SimpleNamespace(name=name, params=params, **extra),
+                                     qubits=qs, clbits=cs)
+                     for name, qs, cs, params, extra in instructions]
+
+    def find_bit(self, bit):
+        return SimpleNamespace(index=bit)
+
+
+SX = StandInMatrix([[0.5 + 0.5j, 0.5 - 0.5j], [0.5 - 0.5j, 0.5 + 0.5j]])
+
+
+def test_qiskit():
+    print("\nTesting from_qiskit on a stand-in circuit and to_qiskit round trips...")
+    stand_in = StandInQiskitCircuit(3, 1, [
+        ("h", [0], [], [], {}),
+        ("cx", [0, 2], [], [], {}),
+        ("barrier", [0, 1, 2], [], [], {}),
+        ("u2", [1], [], [0.3, 0.7], {}),
+        ("rz", [2], [], [StandInParameter("theta", scale=2.0)], {}),
+        ("sx", [1], [], [], {"to_matrix": lambda: SX}),
+        ("measure", [0], [0], [], {}),
+    ])
+    circuit = QuantumCircuit.from_qiskit(stand_in)
+    names = [name for name, _, _ in circuit.gates()]
+    reference = QuantumCircuit(3, 1)
+    reference.h(0)
+    reference.cnot(0, 2)
+    reference.u(1, math.pi / 2, 0.3, 0.7)
+    reference.rz(2, 0.8)
+    reference.unitary([[(z.real, z.imag) for z in row] for row in SX], [1])
+    reference.measure_qubit(0, 0)
+    error = max_error(circuit.bind({"theta": 0.4}).execute(seed=1), reference.execute(seed=1))
+    print(f"   imported {names}, parameters {circuit.parameters()}, max amplitude error {error:.2e}")
+    ok = (names == ["h", "cnot", "u", "rz", "unitary", "measure"] and circuit.parameters() == ["theta"]
+          and error < TOLERANCE and circuit.clbits == 1
+          and rejects("offset parameter expression", QuantumCircuit.from_qiskit, StandInQiskitCircuit(
+              1, 0, [("rx", [0], [], [StandInParameter("phi", offset=0.1)], {})]))
+          and rejects("unknown gate without to_matrix", QuantumCircuit.from_qiskit, StandInQiskitCircuit(
+              1, 0, [("frobnicate", [0], [], [], {})])))
+    try:
+        import qiskit  # noqa: F401
+    except ImportError:
+        print("   qiskit not installed; checking the ImportError path of to_qiskit only")
+        try:
+            circuit.to_qiskit()
+        except ImportError as exc:
+            print(f"   to_qiskit: {type(exc).__name__}")
+            return ok
+        return False
+    original = QuantumCircuit.random(4, 5, seed=80, gate_set=["h", "s", "t", "rx", "u", "cnot", "crz", "iswap"])
+    original.ry_param(2, "theta")
+    copy = QuantumCircuit.from_qiskit(original.to_qiskit())
+    error = max_error(copy.bind({"theta": 0.4}).execute(), original.bind({"theta": 0.4}).execute())
+    print(f"   to_qiskit / from_qiskit round trip: max amplitude error {error:.2e}")
+    return ok and error < TOLERANCE and copy.parameters() == ["theta"]
+
+
+EXPORT_GATE_SET = ["h", "x", "y", "z", "s", "t", "sdg", "tdg", "rx", "ry", "rz", "p", "u",
+                   "cnot", "ch", "swap", "iswap", "crx", "cry", "crz", "cphase", "rxx", "ryy", "rzz"]
+I2 = [[1, 0], [0, 1]]
+PAULI = {"X": [[0, 1], [1, 0]], "Y": [[0, -1j], [1j, 0]], "Z": [[1, 0], [0, -1]]}
+H2 = [[1 / math.sqrt(2), 1 / math.sqrt(2)], [1 / math.sqrt(2), -1 / math.sqrt(2)]]
+
+
+def kron(a, b):
+    """a (x) b with bit 0 of the index on b"""
+    return [[a[r // len(b)][c // len(b)] * b[r % len(b)][c % len(b)] for c in range(len(a) * len(b))]
+            for r in range(len(a) * len(b))]
+
+
+def dagger(m):
+    return [[m[c][r].conjugate() for c in range(len(m))] for r in range(len(m))]
+
+
+def apply_matrix(amps, matrix, targets, controls=()):
+    """`matrix` on `targets` (bit i of its index is targets[i]) where every control is 1"""
+    out = list(amps)
+    control_mask = sum(1 << c for c in controls)
+    target_mask = sum(1 << t for t in targets)
+    for base in range(len(amps)):
+        if base & target_mask or base & control_mask != control_mask:
+            continue
+        index = [base | sum(1 << targets[i] for i in range(len(targets)) if j >> i & 1) for j in range(len(matrix))]
+        for row in range(len(matrix)):
+            out[index[row]] = sum(matrix[row][col] * amps[index[col]] for col in range(len(matrix)))
+    return out
+
+
+def rotation_matrix(axis, theta):
+    return [[cmath.cos(theta / 2) * I2[r][c] - 1j * cmath.sin(theta / 2) * PAULI[axis][r][c] for c in range(2)]
+            for r in range(2)]
+
+
+QUIL_FIXED = {
+    "H": H2, "X": PAULI["X"], "Y": PAULI["Y"], "Z": PAULI["Z"], "S": [[1, 0], [0, 1j]],
+    "T": [[1, 0], [0, cmath.exp(0.25j * math.pi)]],
+    # Most significant bit (the first qubit argument) is the control
+    "CNOT": [[1, 0, 0, 0], [0, 1, 0, 0], [0, 0, 0, 1], [0, 0, 1, 0]],
+    "SWAP": [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]],
+    "ISWAP": [[1, 0, 0, 0], [0, 0, 1j, 0], [0, 1j, 0, 0], [0, 0, 0, 1]],
+}
+
+
+def quil_gate(name, angle):
+    if name in QUIL_FIXED:
+        return QUIL_FIXED[name]
+    if name in ("RX", "RY", "RZ"):
+        return rotation_matrix(name[1], angle)
+    if name == "PHASE":
+        return [[1, 0], [0, cmath.exp(1j * angle)]]
+    if name == "CPHASE":
+        return [[1, 0, 0, 0], [0, 1, 0, 0], [0, 0, 1, 0], [0, 0, 0, cmath.exp(1j * angle)]]
+    raise ValueError(f"no reference for Quil gate {name}")
+
+
+def run_quil(program, qubits):
+    """Amplitudes a Quil program over the gates `to_quil` emits prepares from |0...0>"""
+    amps = [1 + 0j] + [0j] * ((1 << qubits) - 1)
+    definitions, current = {}, None
+    for line in program.splitlines():
+        if line.startswith("DEFGATE "):
+            current = definitions.setdefault(line[len("DEFGATE "):].rstrip(":"), [])
+        elif line.startswith("    "):
+            current.append([complex(entry.strip().replace("i", "j")) for entry in line.split(",")])
+        elif line and not line.startswith(("DECLARE", "#")):
+            words = line.split()
+            modifiers = []
+            while words[0] in ("CONTROLLED", "DAGGER"):
+                modifiers.append(words.pop(0))
+            name, _, angle = words[0].partition("(")
+            if name == "CCNOT":
+                modifiers, name = ["CONTROLLED", "CONTROLLED"], "X"
+            matrix = definitions.get(name) or quil_gate(name, float(angle.rstrip(")") or 0))
+            args = [int(w) for w in words[1:]]
+            controls = args[:modifiers.count("CONTROLLED")]
+            if "DAGGER" in modifiers:
+                matrix = dagger(matrix)
+            # The first remaining argument is the most significant bit of the matrix
+            amps = apply_matrix(amps, matrix, args[len(controls):][::-1], controls)
+    return amps
+
+
+def cirq_matrix(gate):
+    kind = gate["cirq_type"]
+    if kind in ("Rx", "Ry", "Rz"):
+        return rotation_matrix(kind[1].upper(), gate["rads"])
+    if kind == "MatrixGate":
+        return [[complex(e["real"], e["imag"]) for e in row] for row in gate["matrix"]]
+    if kind == "ISwapPowGate" and gate["exponent"] == 1.0:
+        return QUIL_FIXED["ISWAP"]
+    # EigenGates with eigenvalues +-1: U = e^(i pi t s) (P+ + e^(i pi t) P-), P- = (I - M) / 2
+    involution = {
+        "HPowGate": H2, "XPowGate": PAULI["X"], "YPowGate": PAULI["Y"], "ZPowGate": PAULI["Z"],
+        "CXPowGate": QUIL_FIXED["CNOT"], "CZPowGate": [[1 if r == c and r != 3 else -1 if r == c else 0
+                                                          for c in range(4)] for r in range(4)],
+        "SwapPowGate": QUIL_FIXED["SWAP"], "XXPowGate": kron(PAULI["X"], PAULI["X"]),
+        "YYPowGate": kron(PAULI["Y"], PAULI["Y"]), "ZZPowGate": kron(PAULI["Z"], PAULI["Z"]),
+    }[kind]
+    t, s = gate["exponent"], gate["global_shift"]
+    dim = len(involution)
+    return [[cmath.exp(1j * math.pi * t * s) * ((r == c) + involution[r][c]
+                                                + cmath.exp(1j * math.pi * t) * ((r == c) - involution[r][c])) / 2
+             for c in range(dim)] for r in range(dim)]
+
+
+def run_cirq(document, qubits):
+    """Amplitudes a cirq.Circuit JSON document of `to_cirq_json` gates prepares from |0...0>"""
+    amps = [1 + 0j] + [0j] * ((1 << qubits) - 1)
+    for moment in document["moments"]:
+        for op in moment["operations"]:
+            wires = [q["x"] for q in op["qubits"]]
+            gate, controls = op["gate"], []
+            if gate["cirq_type"] == "ControlledGate":
+                controls, wires = wires[:gate["num_controls"]], wires[gate["num_controls"]:]
+                gate = gate["sub_gate"]
+            # Cirq lists the most significant qubit of a gate matrix first
+            amps = apply_matrix(amps, cirq_matrix(gate), wires[::-1], controls)
+    return amps
+
+
+def test_cirq_and_quil_export():
+    print("\nTesting Cirq JSON and Quil exports by simulating what they describe...")
+    ok = True
+    for seed in (82, 83):
+        circuit = QuantumCircuit.random(4, 6, seed=seed, gate_set=EXPORT_GATE_SET)
+        circuit.mcx([0, 1], 3)
+        circuit.mcx([0, 1, 2], 3)
+        circuit.controlled("ry", [2, 3], 0, [0.6])
+        expected = [complex(*a) for a in circuit.execute().get_state_vector()]
+        document = json.loads(circuit.to_cirq_json())
+        error_cirq = max(abs(a - b) for a, b in zip(run_cirq(document, 4), expected))
+        error_quil = max(abs(a - b) for a, b in zip(run_quil(circuit.to_quil(), 4), expected))
+        print(f"   seed {seed}: {len(document['moments'])} moments for depth {circuit.depth()}, "
+              f"Cirq error {error_cirq:.2e}, Quil error {error_quil:.2e}")
+        ok &= error_cirq < TOLERANCE and error_quil < TOLERANCE and len(document["moments"]) == circuit.depth()
+    measured = QuantumCircuit(2, 2)
+    measured.h(0)
+    measured.measure_qubit(0, 1)
+    quil = measured.to_quil().splitlines()
+    ops = [op["gate"]["cirq_type"] for m in json.loads(measured.to_cirq_json())["moments"] for op in m["operations"]]
+    print(f"   measured circuit: Quil {quil}, Cirq {ops}")
+    return ok and quil == ["DECLARE ro BIT[2]", "H 0", "MEASURE 0 ro[1]"] and ops == ["HPowGate", "MeasurementGate"]
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Circuit Parsers, Exporters and Checkpoints")
+    print("============================================================")
+
+    results = [
+        test_qasm(),
+        test_qasm_measurement(),
+        test_json_and_bytes(),
+        test_pickle(),
+        test_checkpoints(),
+        test_stim(),
+        test_qiskit(),
+        test_cirq_and_quil_export(),
+    ]
+
+    if all(results):
+        print("\nSERIALIZATION VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
@@ -0,0 +1,150 @@
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+QUBITS = 5
+SEEDS = (2, 7, 13)
+TOLERANCE = 1e-4
+
+
+def test_circuit(seed):
+    """Random layers plus gates that every pass has work to do on: long-range
+    and multi-controlled gates, a T and a cancelling H pair"""
+    circuit = QuantumCircuit.random(QUBITS, 6, seed=seed)
+    circuit.cnot(0, QUBITS - 1)
+    circuit.controlled("ry", [0, 1], 3, [0.4])
+    circuit.ccx(0, 2, 4)
+    circuit.t(1)
+    circuit.h(1)
+    circuit.h(1)
+    circuit.cphase(1, 4, 0.9)
+    return circuit
+
+
+def infidelity(a, b):
+    """1 - |<a|b>|^2, which ignores global phase"""
+    return 1.0 - a.execute().fidelity(b.execute())
+
+
+def report(label, error):
+    print(f"   {label}: infidelity {error:.2e}")
+    return error < TOLERANCE
+
+
+def test_transpile():
+    print("\nTesting gate fusion at each transpile level...")
+    ok = True
+    for seed in SEEDS:
+        circuit = test_circuit(seed)
+        for level in (0, 1, 2):
+            fused = circuit.transpile(level)
+            ok &= report(f"seed {seed} level {level} ({len(fused.gates())} of {len(circuit.gates())} gates)",
+                         infidelity(circuit, fused))
+            if level > 0:
+                ok &= len(fused.gates()) < len(circuit.gates())
+    return ok
+
+
+def test_optimize():
+    print("\nTesting cancellation and rotation merging...")
+    ok = True
+    for seed in SEEDS:
+        circuit = test_circuit(seed)
+        optimized, removed = circuit.optimize()
+        ok &= report(f"seed {seed} {removed}", infidelity(circuit, optimized))
+        ok &= removed["cancelled"] >= 2 and removed["gates_after"] == len(optimized.gates())
+
+    trivial = QuantumCircuit(2)
+    trivial.h(0)
+    trivial.h(0)
+    trivial.rx(1, 0.3)
+    trivial.rx(1, -0.3)
+    trivial.s(0)
+    trivial.sdg(0)
+    optimized, _ = trivial.optimize()
+    print(f"   h h, rx rx^-1, s sdg leave {len(optimized.gates())} gates")
+    return ok and len(optimized.gates()) == 0
+
+
+BASES = [
+    ["rz", "ry", "cnot"],
+    ["u", "cnot"],
+    ["rz", "rx", "cz"],
+    ["p", "ry", "cnot"],
+]
+
+
+def test_decompose():
+    print("\nTesting decompose into each supported basis...")
+    ok = True
+    for seed in SEEDS:
+        circuit = test_circuit(seed)
+        for basis in BASES:
+            decomposed = circuit.decompose(basis)
+            allowed = {"cphase" if name == "cz" else name for name in basis}
+            outside = set(decomposed.count_ops()) - allowed
+            if outside:
+                print(f"   seed {seed} {basis}: gates outside the basis {sorted(outside)}")
+                ok = False
+            ok &= report(f"seed {seed} {'/'.join(basis)}", infidelity(circuit, decomposed))
+    return ok
+
+
+# A line 0 - 1 - 2 - 3 - 4, so the long-range gates need SWAPs
+LINE = [(q, q + 1) for q in range(QUBITS - 1)]
+
+
+def permuted(amplitudes, layout):
+    """Amplitudes re-indexed so bit v of the index is physical qubit layout[v]"""
+    out = []
+    for i in range(len(amplitudes)):
+        p = sum(1 << layout[v] for v in range(len(layout)) if i >> v & 1)
+        out.append(complex(*amplitudes[p]))
+    return out
+
+
+def test_route():
+    print(f"\nTesting route on a {QUBITS}-qubit line...")
+    coupling = quantum_engine.CouplingMap(QUBITS, LINE)
+    edges = {frozenset(e) for e in LINE}
+    ok = True
+    for seed in SEEDS:
+        circuit = test_circuit(seed).decompose()
+        routed, layout, swaps = circuit.route(coupling)
+        off_map = [(name, qubits) for name, qubits, _ in routed.gates()
+                   if len(qubits) == 2 and frozenset(qubits) not in edges]
+        if off_map:
+            print(f"   seed {seed}: gates on unconnected qubits {off_map[:3]}")
+            ok = False
+
+        expected = [complex(*a) for a in circuit.execute().get_state_vector()]
+        actual = permuted(routed.execute().get_state_vector(), layout)
+        overlap = abs(sum(a.conjugate() * b for a, b in zip(expected, actual))) ** 2
+        ok &= report(f"seed {seed} ({swaps} swaps, final layout {layout})", 1.0 - overlap)
+    return ok
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Transpiler, Decomposition and Routing Equivalence")
+    print("============================================================")
+
+    results = [
+        test_transpile(),
+        test_optimize(),
+        test_decompose(),
+        test_route(),
+    ]
+
+    if all(results):
+        print("\nTRANSPILER VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)
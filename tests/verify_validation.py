@@ -0,0 +1,87 @@
+import sys
+
+try:
+    import quantum_engine
+    print("Rust Quantum Engine Loaded")
+except ImportError:
+    print("Rust Quantum Engine NOT Found")
+    sys.exit(1)
+
+QuantumCircuit = quantum_engine.QuantumCircuit
+
+
+def raises(label, error_type, call):
+    try:
+        call()
+    except error_type as error:
+        print(f"   {label}: {type(error).__name__} ({error})")
+        return True
+    except Exception as error:
+        print(f"   {label}: {type(error).__name__} instead of {error_type.__name__} ({error})")
+        return False
+    print(f"   {label}: no error")
+    return False
+
+
+def test_build_time_validation():
+    print("\nTesting that bad qubit indices are rejected when the gate is added...")
+    circuit = QuantumCircuit(3)
+    ok = all([
+        raises("h past the last qubit", quantum_engine.QubitIndexError, lambda: circuit.h(3)),
+        raises("cnot onto itself", quantum_engine.InvalidGateError, lambda: circuit.cnot(1, 1)),
+        raises("ccx with a repeated control", quantum_engine.InvalidGateError, lambda: circuit.ccx(0, 0, 2)),
+        raises("swap past the last qubit", quantum_engine.QubitIndexError, lambda: circuit.swap(0, 7)),
+        raises("measure_basis past the last qubit", quantum_engine.QubitIndexError,
+               lambda: circuit.measure_basis(5, "X", 0)),
+    ])
+    ok &= circuit.gates() == []
+
+    # Indices past a machine word are validated without building a bitmask
+    wide = QuantumCircuit(100)
+    wide.h(70)
+    wide.cnot(70, 99)
+    wide.cnot(99, 0)
+    ok &= raises("100-qubit cnot onto itself", quantum_engine.InvalidGateError, lambda: wide.cnot(70, 70))
+    ok &= raises("qubit 100 of a 100-qubit circuit", quantum_engine.QubitIndexError, lambda: wide.h(100))
+    counts = wide.execute_stabilizer().measure(100, seed=2)
+    ones = "".join("1" if q in (0, 70, 99) else "0" for q in range(100))
+    print(f"   100-qubit stabilizer run over qubits 0, 70 and 99: {len(counts)} distinct outcomes")
+    return ok and sorted(counts) == sorted(["0" * 100, ones])
+
+
+def test_exception_hierarchy():
+    print("\nTesting the exception hierarchy...")
+    QubitIndexError = quantum_engine.QubitIndexError
+    bases = [
+        issubclass(quantum_engine.QuantumEngineError, ValueError),
+        issubclass(QubitIndexError, quantum_engine.QuantumEngineError),
+        issubclass(QubitIndexError, IndexError),
+        issubclass(quantum_engine.InvalidGateError, quantum_engine.QuantumEngineError),
+        issubclass(quantum_engine.DimensionMismatchError, quantum_engine.QuantumEngineError),
+    ]
+    print(f"   QubitIndexError MRO: {[c.__name__ for c in QubitIndexError.__mro__]}")
+    ok = all(bases) and QubitIndexError.__module__ == "quantum_engine"
+    # Raised from Rust, caught by each of the handlers a caller might write
+    for handler in (QubitIndexError, IndexError, quantum_engine.QuantumEngineError, ValueError):
+        ok &= raises(f"except {handler.__name__}", handler, lambda: QuantumCircuit(2).h(5))
+    ok &= raises("except IndexError from a state method", IndexError,
+                 lambda: quantum_engine.QuantumState(2).reduced_density_matrix([3]))
+    return ok
+
+
+if __name__ == "__main__":
+    print("============================================================")
+    print("Verifying Build-Time Validation and the Exception Hierarchy")
+    print("============================================================")
+
+    results = [
+        test_build_time_validation(),
+        test_exception_hierarchy(),
+    ]
+
+    if all(results):
+        print("\nVALIDATION VERIFIED")
+        sys.exit(0)
+    else:
+        print("\nVERIFICATION FAILED")
+        sys.exit(1)